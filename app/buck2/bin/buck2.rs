@@ -122,6 +122,7 @@ fn main(init: fbinit::FacebookInit) -> ! {
     buck2_bxl::init_late_bindings();
     buck2_cfg_constructor::init_late_bindings();
     buck2_configured::init_late_bindings();
+    buck2_events::init_late_bindings();
     buck2_query_impls::init_late_bindings();
     buck2_interpreter_for_build::init_late_bindings();
     buck2_server_commands::init_late_bindings();
@@ -0,0 +1,16 @@
+# Completion of buck2 command lines for bash, printed by `buck2 completion bash`.
+# Install with `source <(buck2 completion bash)` in ~/.bashrc.
+
+_buck2_complete() {
+    local line="${COMP_LINE:0:COMP_POINT}"
+    local word="${line##*[[:space:]]}"
+    local IFS=$'\n'
+    COMPREPLY=($(buck2 complete --line "$line" 2>/dev/null))
+    # Bash splits words at colons, so only the part after the last one is replaced.
+    if [[ "$word" == *:* ]]; then
+        local colon_prefix="${word%"${word##*:}"}"
+        COMPREPLY=("${COMPREPLY[@]#"$colon_prefix"}")
+    fi
+}
+
+complete -o default -F _buck2_complete buck2
@@ -0,0 +1,8 @@
+# Completion of buck2 command lines for fish, printed by `buck2 completion fish`.
+# Install with `buck2 completion fish | source` in ~/.config/fish/config.fish.
+
+function __buck2_complete
+    buck2 complete --line (commandline -cp) 2>/dev/null
+end
+
+complete -c buck2 -a '(__buck2_complete)'
@@ -0,0 +1,239 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Shell completion of `buck2` command lines.
+//!
+//! The scripts printed by `buck2 completion` call `buck2 complete` with the command line being
+//! edited. Subcommands and flags are completed from the clap definitions of the commands, and
+//! target names from the targets of the package, listed by the daemon.
+
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::exit_result::ExitResult;
+use clap::Parser;
+
+use crate::commands::completion::targets::complete_targets;
+use crate::Opt;
+
+mod targets;
+
+#[derive(Debug, Clone, Copy, clap::ArgEnum)]
+#[clap(rename_all = "snake_case")]
+enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// Print a script which completes `buck2` command lines, including target names.
+///
+/// Install it with `source <(buck2 completion bash)` in `~/.bashrc`, `source <(buck2 completion
+/// zsh)` in `~/.zshrc`, or `buck2 completion fish | source` in `~/.config/fish/config.fish`.
+///
+/// Target names are only completed when a daemon is running for the project, so completion
+/// never starts one.
+#[derive(Debug, clap::Parser)]
+pub(crate) struct CompletionCommand {
+    #[clap(arg_enum)]
+    shell: Shell,
+}
+
+impl CompletionCommand {
+    pub(crate) fn exec(
+        self,
+        _matches: &clap::ArgMatches,
+        _ctx: ClientCommandContext<'_>,
+    ) -> ExitResult {
+        let script = match self.shell {
+            Shell::Bash => include_str!("completion.bash"),
+            Shell::Zsh => include_str!("completion.zsh"),
+            Shell::Fish => include_str!("completion.fish"),
+        };
+        buck2_client_ctx::print!("{}", script)?;
+        ExitResult::success()
+    }
+}
+
+/// Print the completions of the last word of a command line, one per line. Used by the scripts
+/// printed by `buck2 completion`.
+#[derive(Debug, clap::Parser)]
+pub(crate) struct CompleteCommand {
+    /// The command line up to the cursor, starting with `buck2`.
+    #[clap(long)]
+    line: String,
+}
+
+impl CompleteCommand {
+    pub(crate) fn exec(
+        self,
+        _matches: &clap::ArgMatches,
+        ctx: ClientCommandContext<'_>,
+    ) -> ExitResult {
+        let mut words: Vec<&str> = self.line.split_whitespace().collect();
+        let current = if self.line.ends_with(char::is_whitespace) {
+            ""
+        } else {
+            words.pop().unwrap_or_default()
+        };
+        // Skip `buck2` itself.
+        let words = words.get(1..).unwrap_or_default();
+
+        let candidates = match complete_words(&Opt::clap(), words, current) {
+            Completion::Candidates(candidates) => candidates,
+            Completion::Targets => {
+                let current = current.to_owned();
+                ctx.with_runtime(async move |ctx| complete_targets(&ctx, &current).await)?
+            }
+            Completion::None => Vec::new(),
+        };
+        for candidate in candidates {
+            buck2_client_ctx::println!("{}", candidate)?;
+        }
+        ExitResult::success()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Completion {
+    Candidates(Vec<String>),
+    /// The current word is a target pattern, complete the target names of its package.
+    Targets,
+    /// Let the shell complete file names.
+    None,
+}
+
+fn find_arg<'a, 'help>(
+    commands: &[&'a clap::Command<'help>],
+    matches: impl Fn(&clap::Arg<'help>) -> bool,
+) -> Option<&'a clap::Arg<'help>> {
+    let (current, parents) = commands.split_last()?;
+    current.get_arguments().find(|a| matches(a)).or_else(|| {
+        parents
+            .iter()
+            .flat_map(|c| c.get_arguments())
+            .find(|a| a.is_global_set() && matches(a))
+    })
+}
+
+/// Complete `current`, the word after `words` (the words of the command line after `buck2`).
+fn complete_words(command: &clap::Command<'_>, words: &[&str], current: &str) -> Completion {
+    let mut commands = vec![command];
+    let mut in_positionals = false;
+    let mut words = words.iter();
+    while let Some(word) = words.next() {
+        if *word == "--" {
+            in_positionals = true;
+        } else if let Some(flag) = word.strip_prefix("--") {
+            if !flag.contains('=') {
+                if let Some(arg) = find_arg(&commands, |a| a.get_long() == Some(flag)) {
+                    if arg.is_takes_value_set() {
+                        words.next();
+                    }
+                }
+            }
+        } else if let Some(flag) = word.strip_prefix('-') {
+            let mut chars = flag.chars();
+            if let (Some(c), None) = (chars.next(), chars.next()) {
+                if let Some(arg) = find_arg(&commands, |a| a.get_short() == Some(c)) {
+                    if arg.is_takes_value_set() {
+                        words.next();
+                    }
+                }
+            }
+        } else if !in_positionals {
+            let current_command = commands[commands.len() - 1];
+            match current_command
+                .get_subcommands()
+                .find(|s| s.get_name() == *word || s.get_all_aliases().any(|a| a == *word))
+            {
+                Some(subcommand) => commands.push(subcommand),
+                None => in_positionals = true,
+            }
+        }
+    }
+
+    let current_command = commands[commands.len() - 1];
+    if current.starts_with('-') {
+        let parents = &commands[..commands.len() - 1];
+        let args = current_command.get_arguments().chain(
+            parents
+                .iter()
+                .flat_map(|c| c.get_arguments())
+                .filter(|a| a.is_global_set()),
+        );
+        let mut flags: Vec<String> = args
+            .filter(|a| !a.is_hide_set())
+            .filter_map(|a| a.get_long())
+            .map(|long| format!("--{}", long))
+            .filter(|flag| flag.starts_with(current))
+            .collect();
+        flags.sort();
+        flags.dedup();
+        Completion::Candidates(flags)
+    } else if !in_positionals && current_command.has_subcommands() {
+        let mut subcommands: Vec<String> = current_command
+            .get_subcommands()
+            .filter(|s| !s.is_hide_set())
+            .map(|s| s.get_name().to_owned())
+            .filter(|name| name.starts_with(current))
+            .collect();
+        subcommands.sort();
+        Completion::Candidates(subcommands)
+    } else if current.contains(':') {
+        Completion::Targets
+    } else {
+        Completion::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use crate::commands::completion::complete_words;
+    use crate::commands::completion::Completion;
+    use crate::Opt;
+
+    fn complete(words: &[&str], current: &str) -> Completion {
+        complete_words(&Opt::clap(), words, current)
+    }
+
+    fn candidates(candidates: &[&str]) -> Completion {
+        Completion::Candidates(candidates.iter().map(|c| (*c).to_owned()).collect())
+    }
+
+    #[test]
+    fn test_complete_subcommands() {
+        assert_eq!(candidates(&["build", "bxl"]), complete(&[], "b"));
+        // `--isolation-dir` takes a value, which is not a subcommand.
+        assert_eq!(
+            candidates(&["build"]),
+            complete(&["--isolation-dir", "v3"], "bu")
+        );
+    }
+
+    #[test]
+    fn test_complete_flags() {
+        assert_eq!(
+            candidates(&["--show-full-output"]),
+            complete(&["build"], "--show-full-o")
+        );
+        // Global flags of `buck2` are also accepted after the subcommand.
+        assert_eq!(candidates(&["--oncall"]), complete(&["build"], "--onc"));
+    }
+
+    #[test]
+    fn test_complete_targets() {
+        assert_eq!(Completion::Targets, complete(&["build"], "//foo:b"));
+        assert_eq!(
+            Completion::Targets,
+            complete(&["build", "//foo:bar", "-c", "a.b=c"], "cell//foo:")
+        );
+        assert_eq!(Completion::None, complete(&["build"], "foo/"));
+    }
+}
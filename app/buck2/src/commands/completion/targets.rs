@@ -0,0 +1,174 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use buck2_cli_proto::targets_request;
+use buck2_cli_proto::TargetsRequest;
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::daemon::client::connect::BuckdConnectOptions;
+use buck2_client_ctx::events_ctx::PartialResultCtx;
+use buck2_client_ctx::events_ctx::PartialResultHandler;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_core::fs::paths::file_name::FileName;
+
+/// How long the names of the targets of a package are reused without asking the daemon.
+const CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// How long to wait for the daemon, e.g. if the package needs to be loaded. Completion falls back
+/// to the names in the cache, however old, or to nothing.
+const QUERY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Target names by package, kept in the daemon dir.
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+struct TargetsCache {
+    packages: BTreeMap<String, CachedPackage>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CachedPackage {
+    /// Seconds since the epoch.
+    time: u64,
+    names: Vec<String>,
+}
+
+impl TargetsCache {
+    fn path(ctx: &ClientCommandContext<'_>) -> anyhow::Result<AbsNormPathBuf> {
+        Ok(ctx
+            .paths()?
+            .daemon_dir()?
+            .path
+            .join(FileName::new("completion_targets.json")?))
+    }
+
+    fn load(ctx: &ClientCommandContext<'_>) -> anyhow::Result<TargetsCache> {
+        // A cache we can't read is just empty.
+        Ok(fs_util::read_to_string_if_exists(Self::path(ctx)?)?
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default())
+    }
+
+    fn save(&self, ctx: &ClientCommandContext<'_>) -> anyhow::Result<()> {
+        fs_util::write(Self::path(ctx)?, serde_json::to_string(self)?)
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Receive the output of `targets`.
+struct CaptureStdout {
+    buf: Vec<u8>,
+}
+
+#[async_trait]
+impl PartialResultHandler for CaptureStdout {
+    type PartialResult = buck2_cli_proto::StdoutBytes;
+
+    async fn handle_partial_result(
+        &mut self,
+        _ctx: PartialResultCtx<'_, '_>,
+        partial_res: Self::PartialResult,
+    ) -> anyhow::Result<()> {
+        self.buf.extend(partial_res.data);
+        Ok(())
+    }
+}
+
+/// The names of the targets of a package, e.g. `//foo` or `cell//foo`, listed by the daemon.
+async fn query_target_names(
+    ctx: &ClientCommandContext<'_>,
+    package: &str,
+) -> anyhow::Result<Vec<String>> {
+    let mut buckd = ctx
+        .connect_buckd(BuckdConnectOptions::existing_only_no_console())
+        .await?;
+    let mut capture = CaptureStdout { buf: Vec::new() };
+    let response = buckd
+        .with_flushing()
+        .targets(
+            TargetsRequest {
+                context: Some(ctx.empty_client_context("complete")?),
+                target_patterns: vec![buck2_data::TargetPattern {
+                    value: format!("{}:", package),
+                }],
+                output_format: targets_request::OutputFormat::Text as i32,
+                targets: Some(targets_request::Targets::Other(targets_request::Other {
+                    cached: true,
+                    ..Default::default()
+                })),
+                output: None,
+                concurrency: None,
+            },
+            None,
+            &mut capture,
+        )
+        .await??;
+
+    let mut output = String::from_utf8(capture.buf)?;
+    output.push_str(&response.serialized_targets_output);
+    Ok(output
+        .lines()
+        .filter_map(|label| label.rsplit_once(':'))
+        .map(|(_, name)| name.to_owned())
+        .collect())
+}
+
+/// Complete the target pattern `current`, e.g. `//foo:ba` to `//foo:bar` and `//foo:baz`.
+pub(crate) async fn complete_targets(
+    ctx: &ClientCommandContext<'_>,
+    current: &str,
+) -> anyhow::Result<Vec<String>> {
+    let Some((package, prefix)) = current.rsplit_once(':') else {
+        return Ok(Vec::new());
+    };
+
+    // Relative packages depend on the working dir.
+    let key = format!("{}\n{}", ctx.working_dir.path(), package);
+    let mut cache = TargetsCache::load(ctx)?;
+    let now = now_secs();
+    let fresh = cache
+        .packages
+        .get(&key)
+        .map_or(false, |p| now.saturating_sub(p.time) < CACHE_TTL.as_secs());
+    if !fresh {
+        match tokio::time::timeout(QUERY_TIMEOUT, query_target_names(ctx, package)).await {
+            Ok(Ok(names)) => {
+                cache
+                    .packages
+                    .insert(key.clone(), CachedPackage { time: now, names });
+                cache
+                    .packages
+                    .retain(|_, p| now.saturating_sub(p.time) < CACHE_TTL.as_secs());
+                cache.save(ctx)?;
+            }
+            Ok(Err(e)) => tracing::debug!("Failed to list targets of `{}`: {:#}", package, e),
+            Err(_) => tracing::debug!("Timed out listing targets of `{}`", package),
+        }
+    }
+
+    Ok(cache
+        .packages
+        .get(&key)
+        .map(|p| {
+            p.names
+                .iter()
+                .filter(|name| name.starts_with(prefix))
+                .map(|name| format!("{}:{}", package, name))
+                .collect()
+        })
+        .unwrap_or_default())
+}
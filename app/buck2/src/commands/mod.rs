@@ -7,6 +7,7 @@
  * of this source tree.
  */
 
+pub(crate) mod completion;
 pub mod daemon;
 pub(crate) mod daemon_lower_priority;
 pub(crate) mod daemonize;
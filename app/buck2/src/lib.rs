@@ -17,9 +17,12 @@ use buck2_audit::AuditCommand;
 use buck2_client::args::expand_argfiles_with_context;
 use buck2_client::commands::build::BuildCommand;
 use buck2_client::commands::bxl::BxlCommand;
+use buck2_client::commands::changed_targets::ChangedTargetsCommand;
 use buck2_client::commands::clean::CleanCommand;
 use buck2_client::commands::ctargets::ConfiguredTargetsCommand;
 use buck2_client::commands::debug::DebugCommand;
+use buck2_client::commands::doctor::DoctorCommand;
+use buck2_client::commands::explain::ExplainCommand;
 use buck2_client::commands::help_env::HelpEnvCommand;
 use buck2_client::commands::init::InitCommand;
 use buck2_client::commands::install::InstallCommand;
@@ -63,6 +66,8 @@ use dupe::Dupe;
 use no_buckd::start_in_process_daemon;
 
 use crate::check_user_allowed::check_user_allowed;
+use crate::commands::completion::CompleteCommand;
+use crate::commands::completion::CompletionCommand;
 use crate::commands::daemon::DaemonCommand;
 use crate::commands::docs::DocsCommand;
 use crate::commands::forkserver::ForkserverCommand;
@@ -264,6 +269,10 @@ pub(crate) enum CommandKind {
     Aquery(AqueryCommand),
     Build(BuildCommand),
     Bxl(BxlCommand),
+    ChangedTargets(ChangedTargetsCommand),
+    Completion(CompletionCommand),
+    #[clap(setting(AppSettings::Hidden))]
+    Complete(CompleteCommand),
     HelpEnv(HelpEnvCommand),
     Test(TestCommand),
     Cquery(CqueryCommand),
@@ -287,6 +296,8 @@ pub(crate) enum CommandKind {
     #[clap(subcommand, setting(AppSettings::Hidden))]
     Debug(DebugCommand),
     Docs(DocsCommand),
+    Doctor(DoctorCommand),
+    Explain(ExplainCommand),
     #[clap(subcommand)]
     Profile(ProfileCommand),
     #[clap(hide(true))] // @oss-enable
@@ -390,12 +401,17 @@ impl CommandKind {
             CommandKind::Targets(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Utargets(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Ctargets(cmd) => cmd.exec(matches, command_ctx),
+            CommandKind::ChangedTargets(cmd) => cmd.exec(matches, command_ctx),
+            CommandKind::Completion(cmd) => cmd.exec(matches, command_ctx),
+            CommandKind::Complete(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Audit(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Starlark(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Run(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Uquery(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Debug(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Docs(cmd) => cmd.exec(matches, command_ctx),
+            CommandKind::Doctor(cmd) => cmd.exec(matches, command_ctx),
+            CommandKind::Explain(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Profile(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Rage(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Init(cmd) => cmd.exec(matches, command_ctx),
@@ -60,7 +60,7 @@ mod imp {
     use buck2_core::error::StructuredErrorOptions;
     use buck2_data::Location;
     use buck2_events::metadata;
-    use buck2_events::sink::scribe::new_thrift_scribe_sink_if_enabled;
+    use buck2_events::sink::remote::new_remote_event_sink_if_enabled;
     use buck2_events::BuckEvent;
     use buck2_util::threads::thread_spawn;
     use fbinit::FacebookInit;
@@ -214,7 +214,7 @@ mod imp {
             return;
         }
 
-        let sink = match new_thrift_scribe_sink_if_enabled(
+        let sink = match new_remote_event_sink_if_enabled(
             fb,
             /* buffer size */ 100,
             /* retry_backoff */ Duration::from_millis(500),
@@ -0,0 +1,882 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! `ctx.actions.archive()` and `ctx.actions.unarchive()`: tar and zip archives created and
+//! extracted by Buck2 itself rather than by host tools.
+//!
+//! Archives only depend on the contents of their sources: entries are sorted, have no owner, a
+//! fixed timestamp, and a mode which only says whether they are executable. So the same sources
+//! give the same archive on all platforms.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Read;
+use std::io::Seek;
+use std::io::Write;
+use std::slice;
+use std::time::Instant;
+
+use allocative::Allocative;
+use anyhow::Context as _;
+use async_trait::async_trait;
+use buck2_artifact::artifact::build_artifact::BuildArtifact;
+use buck2_build_api::actions::execute::action_executor::ActionExecutionKind;
+use buck2_build_api::actions::execute::action_executor::ActionExecutionMetadata;
+use buck2_build_api::actions::execute::action_executor::ActionOutputs;
+use buck2_build_api::actions::execute::error::ExecuteError;
+use buck2_build_api::actions::Action;
+use buck2_build_api::actions::ActionExecutable;
+use buck2_build_api::actions::ActionExecutionCtx;
+use buck2_build_api::actions::IncrementalActionExecutable;
+use buck2_build_api::actions::UnregisteredAction;
+use buck2_build_api::artifact_groups::ArtifactGroup;
+use buck2_common::file_ops::FileDigestConfig;
+use buck2_core::category::Category;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
+use buck2_core::fs::paths::forward_rel_path::ForwardRelativePathBuf;
+use buck2_core::fs::project_rel_path::ProjectRelativePath;
+use buck2_execute::artifact::artifact_dyn::ArtifactDyn;
+use buck2_execute::artifact::fs::ExecutorFs;
+use buck2_execute::artifact_value::ArtifactValue;
+use buck2_execute::directory::INTERNER;
+use buck2_execute::entry::build_entry_from_disk;
+use buck2_execute::execute::command_executor::ActionExecutionTimingData;
+use dupe::Dupe;
+use gazebo::prelude::*;
+use indexmap::indexmap;
+use indexmap::IndexMap;
+use indexmap::IndexSet;
+use once_cell::sync::Lazy;
+use starlark::values::OwnedFrozenValue;
+
+#[derive(Debug, buck2_error::Error)]
+enum ArchiveError {
+    #[error("Unknown archive format `{0}`, expected `tar`, `tar.gz`, `tar.zst` or `zip`")]
+    #[buck2(user)]
+    UnknownFormat(String),
+    #[error(
+        "Can't infer the archive format of `{0}`, pass `format` or use one of the extensions \
+        `.tar`, `.tar.gz`, `.tgz`, `.tar.zst` or `.zip`"
+    )]
+    #[buck2(user)]
+    UnknownExtension(String),
+    #[error("Invalid compression level {level} for `{format}`, expected {expected}")]
+    #[buck2(user)]
+    InvalidCompressionLevel {
+        format: ArchiveFormat,
+        level: i32,
+        expected: &'static str,
+    },
+    #[error("Archive path `{0}` is given more than once")]
+    #[buck2(user)]
+    DuplicateEntry(String),
+    #[error("`{0}` is a file, so it needs a path in the archive")]
+    #[buck2(user)]
+    FileAtRoot(String),
+    #[error("Archive entry `{0}` is not a forward relative path")]
+    #[buck2(user)]
+    UnsafePath(String),
+    #[error("Archive entry `{0}` is not a file, a directory or a symlink")]
+    #[buck2(user)]
+    UnsupportedEntry(String),
+    #[error("Archive path `{0}` is not valid UTF-8")]
+    #[buck2(user)]
+    NonUtf8Path(String),
+    #[error("Archive path `{0}` is not a single artifact")]
+    #[buck2(user)]
+    UnsupportedInput(String),
+    #[error("{0} received {1} inputs, expected 1")]
+    WrongInputCount(&'static str, usize),
+    #[error("{0} received {1} outputs, expected 1")]
+    WrongOutputCount(&'static str, usize),
+}
+
+#[derive(Copy, Clone, Dupe, Debug, PartialEq, Eq, Allocative)]
+pub(crate) enum ArchiveFormat {
+    Tar,
+    TarGz,
+    TarZst,
+    Zip,
+}
+
+impl ArchiveFormat {
+    /// The format named `format`, or else the format of the archive named `filename`.
+    pub(crate) fn new(format: Option<&str>, filename: &str) -> anyhow::Result<ArchiveFormat> {
+        match format {
+            Some("tar") => Ok(ArchiveFormat::Tar),
+            Some("tar.gz") => Ok(ArchiveFormat::TarGz),
+            Some("tar.zst") => Ok(ArchiveFormat::TarZst),
+            Some("zip") => Ok(ArchiveFormat::Zip),
+            Some(format) => Err(ArchiveError::UnknownFormat(format.to_owned()).into()),
+            None => {
+                if filename.ends_with(".tar") {
+                    Ok(ArchiveFormat::Tar)
+                } else if filename.ends_with(".tar.gz") || filename.ends_with(".tgz") {
+                    Ok(ArchiveFormat::TarGz)
+                } else if filename.ends_with(".tar.zst") {
+                    Ok(ArchiveFormat::TarZst)
+                } else if filename.ends_with(".zip") {
+                    Ok(ArchiveFormat::Zip)
+                } else {
+                    Err(ArchiveError::UnknownExtension(filename.to_owned()).into())
+                }
+            }
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            ArchiveFormat::Tar => "tar",
+            ArchiveFormat::TarGz => "tar.gz",
+            ArchiveFormat::TarZst => "tar.zst",
+            ArchiveFormat::Zip => "zip",
+        }
+    }
+
+    /// Check that `level` makes sense for this format. For `zip`, 0 stores files and any other
+    /// level deflates them.
+    pub(crate) fn validate_compression_level(self, level: Option<i32>) -> anyhow::Result<()> {
+        let (range, expected) = match self {
+            ArchiveFormat::Tar => (0..=-1, "none, since `tar` is not compressed"),
+            ArchiveFormat::TarGz => (0..=9, "0 to 9"),
+            ArchiveFormat::TarZst => (1..=22, "1 to 22"),
+            ArchiveFormat::Zip => (0..=9, "0 to 9"),
+        };
+        match level {
+            Some(level) if !range.contains(&level) => Err(ArchiveError::InvalidCompressionLevel {
+                format: self,
+                level,
+                expected,
+            }
+            .into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for ArchiveFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What to put at a path of an archive.
+#[derive(Debug, PartialEq)]
+enum ArchiveEntry {
+    Directory,
+    File {
+        path: AbsNormPathBuf,
+        is_executable: bool,
+    },
+}
+
+/// The entries of an archive of `srcs`, pairs of paths in the archive and on disk, sorted by
+/// path. Directories are archived with their contents, and symlinks are followed.
+fn collect_entries(
+    srcs: &[(ForwardRelativePathBuf, AbsNormPathBuf)],
+) -> anyhow::Result<BTreeMap<String, ArchiveEntry>> {
+    fn add(
+        entries: &mut BTreeMap<String, ArchiveEntry>,
+        archive_path: &ForwardRelativePath,
+        disk_path: &AbsNormPath,
+    ) -> anyhow::Result<()> {
+        let metadata = fs_util::metadata(disk_path)?;
+        let entry = if metadata.is_dir() {
+            ArchiveEntry::Directory
+        } else {
+            ArchiveEntry::File {
+                path: disk_path.to_buf(),
+                is_executable: is_executable(&metadata),
+            }
+        };
+        // The root of the archive has no entry, only directories can be put there.
+        if archive_path.is_empty() {
+            if !metadata.is_dir() {
+                return Err(ArchiveError::FileAtRoot(disk_path.to_string()).into());
+            }
+        } else if entries.insert(archive_path.to_string(), entry).is_some() {
+            return Err(ArchiveError::DuplicateEntry(archive_path.to_string()).into());
+        }
+        if metadata.is_dir() {
+            let mut children = Vec::new();
+            for child in fs_util::read_dir(disk_path)? {
+                let name = child?.file_name();
+                let name = name
+                    .to_str()
+                    .ok_or_else(|| ArchiveError::NonUtf8Path(name.to_string_lossy().into_owned()))?
+                    .to_owned();
+                children.push(name);
+            }
+            children.sort();
+            for name in children {
+                let name = ForwardRelativePath::new(&name)?;
+                add(entries, &archive_path.join(name), &disk_path.join(name))?;
+            }
+        }
+        Ok(())
+    }
+
+    let mut entries = BTreeMap::new();
+    for (archive_path, disk_path) in srcs {
+        add(&mut entries, archive_path, disk_path)?;
+    }
+    Ok(entries)
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &std::fs::Metadata) -> bool {
+    false
+}
+
+fn mode(entry: &ArchiveEntry) -> u32 {
+    match entry {
+        ArchiveEntry::Directory
+        | ArchiveEntry::File {
+            is_executable: true,
+            ..
+        } => 0o755,
+        ArchiveEntry::File { .. } => 0o644,
+    }
+}
+
+fn write_archive(
+    output: &AbsNormPath,
+    format: ArchiveFormat,
+    compression_level: Option<i32>,
+    entries: &BTreeMap<String, ArchiveEntry>,
+) -> anyhow::Result<()> {
+    if let Some(parent) = output.parent() {
+        fs_util::create_dir_all(parent)?;
+    }
+    let file = BufWriter::new(
+        File::create(output).with_context(|| format!("Error creating `{}`", output))?,
+    );
+    let mut file = match format {
+        ArchiveFormat::Tar => write_tar(file, entries)?,
+        ArchiveFormat::TarGz => {
+            let level = flate2::Compression::new(compression_level.unwrap_or(6) as u32);
+            // The gzip header has no timestamp nor file name by default.
+            write_tar(flate2::write::GzEncoder::new(file, level), entries)?.finish()?
+        }
+        ArchiveFormat::TarZst => {
+            let encoder = zstd::Encoder::new(file, compression_level.unwrap_or(3))?;
+            write_tar(encoder, entries)?.finish()?
+        }
+        ArchiveFormat::Zip => write_zip(file, compression_level, entries)?,
+    };
+    file.flush()
+        .with_context(|| format!("Error writing `{}`", output))?;
+    Ok(())
+}
+
+fn write_tar<W: Write>(writer: W, entries: &BTreeMap<String, ArchiveEntry>) -> anyhow::Result<W> {
+    let mut builder = tar::Builder::new(writer);
+    for (archive_path, entry) in entries {
+        let mut header = tar::Header::new_gnu();
+        header.set_mtime(0);
+        header.set_uid(0);
+        header.set_gid(0);
+        header.set_mode(mode(entry));
+        match entry {
+            ArchiveEntry::Directory => {
+                header.set_entry_type(tar::EntryType::Directory);
+                header.set_size(0);
+                builder.append_data(&mut header, archive_path, io::empty())?;
+            }
+            ArchiveEntry::File { path, .. } => {
+                let file = File::open(path).with_context(|| format!("Error opening `{}`", path))?;
+                header.set_entry_type(tar::EntryType::Regular);
+                header.set_size(file.metadata()?.len());
+                builder.append_data(&mut header, archive_path, BufReader::new(file))?;
+            }
+        }
+    }
+    Ok(builder.into_inner()?)
+}
+
+fn write_zip<W: Write + Seek>(
+    writer: W,
+    compression_level: Option<i32>,
+    entries: &BTreeMap<String, ArchiveEntry>,
+) -> anyhow::Result<W> {
+    let method = match compression_level {
+        Some(0) => zip::CompressionMethod::Stored,
+        _ => zip::CompressionMethod::Deflated,
+    };
+    let options = zip::write::FileOptions::default()
+        .compression_method(method)
+        // 1980-01-01, the earliest zip timestamp.
+        .last_modified_time(zip::DateTime::default());
+    let mut zip = zip::ZipWriter::new(writer);
+    for (archive_path, entry) in entries {
+        let options = options.unix_permissions(mode(entry));
+        match entry {
+            ArchiveEntry::Directory => zip.add_directory(archive_path.as_str(), options)?,
+            ArchiveEntry::File { path, .. } => {
+                let mut file =
+                    File::open(path).with_context(|| format!("Error opening `{}`", path))?;
+                let large_file = file.metadata()?.len() >= u32::MAX as u64;
+                zip.start_file(archive_path.as_str(), options.large_file(large_file))?;
+                io::copy(&mut file, &mut zip)?;
+            }
+        }
+    }
+    Ok(zip.finish()?)
+}
+
+/// The path to extract an entry of an archive at, relative to the output, or `None` if it is
+/// one of the `strip_components` leading directories.
+fn extraction_path(
+    archive_path: &str,
+    strip_components: u32,
+) -> anyhow::Result<Option<ForwardRelativePathBuf>> {
+    let components: Vec<&str> = archive_path
+        .split('/')
+        .filter(|c| !c.is_empty() && *c != ".")
+        .skip(strip_components as usize)
+        .collect();
+    if components.is_empty() {
+        return Ok(None);
+    }
+    match ForwardRelativePath::new(&components.join("/")) {
+        Ok(path) => Ok(Some(path.to_buf())),
+        Err(_) => Err(ArchiveError::UnsafePath(archive_path.to_owned()).into()),
+    }
+}
+
+fn extract_archive(
+    archive: &AbsNormPath,
+    format: ArchiveFormat,
+    strip_components: u32,
+    output: &AbsNormPath,
+) -> anyhow::Result<()> {
+    fs_util::create_dir_all(output)?;
+    let file = BufReader::new(
+        File::open(archive).with_context(|| format!("Error opening `{}`", archive))?,
+    );
+    match format {
+        ArchiveFormat::Tar => extract_tar(file, strip_components, output),
+        ArchiveFormat::TarGz => {
+            extract_tar(flate2::read::GzDecoder::new(file), strip_components, output)
+        }
+        ArchiveFormat::TarZst => extract_tar(zstd::Decoder::new(file)?, strip_components, output),
+        ArchiveFormat::Zip => extract_zip(file, strip_components, output),
+    }
+}
+
+fn extract_file(
+    mut contents: impl Read,
+    dest: &AbsNormPath,
+    is_executable: bool,
+) -> anyhow::Result<()> {
+    if let Some(parent) = dest.parent() {
+        fs_util::create_dir_all(parent)?;
+    }
+    let mut file = File::create(dest).with_context(|| format!("Error creating `{}`", dest))?;
+    io::copy(&mut contents, &mut file).with_context(|| format!("Error writing `{}`", dest))?;
+    drop(file);
+    if is_executable {
+        fs_util::set_executable(dest)?;
+    }
+    Ok(())
+}
+
+fn extract_tar(
+    reader: impl Read,
+    strip_components: u32,
+    output: &AbsNormPath,
+) -> anyhow::Result<()> {
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let archive_path = entry.path()?;
+        let archive_path = archive_path
+            .to_str()
+            .ok_or_else(|| ArchiveError::NonUtf8Path(archive_path.display().to_string()))?
+            .to_owned();
+        let entry_type = entry.header().entry_type();
+        if matches!(
+            entry_type,
+            tar::EntryType::XGlobalHeader | tar::EntryType::XHeader
+        ) {
+            continue;
+        }
+        let Some(path) = extraction_path(&archive_path, strip_components)? else {
+            continue;
+        };
+        let dest = output.join(&path);
+        match entry_type {
+            tar::EntryType::Directory => fs_util::create_dir_all(&dest)?,
+            tar::EntryType::Regular | tar::EntryType::Continuous => {
+                let is_executable = entry.header().mode()? & 0o111 != 0;
+                extract_file(&mut entry, &dest, is_executable)?;
+            }
+            tar::EntryType::Symlink => {
+                let target = entry
+                    .link_name()?
+                    .ok_or_else(|| ArchiveError::UnsupportedEntry(archive_path.clone()))?;
+                if let Some(parent) = dest.parent() {
+                    fs_util::create_dir_all(parent)?;
+                }
+                fs_util::symlink(&*target, &dest)?;
+            }
+            _ => return Err(ArchiveError::UnsupportedEntry(archive_path).into()),
+        }
+    }
+    Ok(())
+}
+
+fn extract_zip(
+    reader: impl Read + Seek,
+    strip_components: u32,
+    output: &AbsNormPath,
+) -> anyhow::Result<()> {
+    let mut archive = zip::ZipArchive::new(reader)?;
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i)?;
+        let Some(path) = extraction_path(entry.name(), strip_components)? else {
+            continue;
+        };
+        let dest = output.join(&path);
+        if entry.is_dir() {
+            fs_util::create_dir_all(&dest)?;
+        } else {
+            let is_executable = entry.unix_mode().map_or(false, |mode| mode & 0o111 != 0);
+            extract_file(entry, &dest, is_executable)?;
+        }
+    }
+    Ok(())
+}
+
+/// The value of an output which was written to disk.
+async fn value_from_disk(
+    ctx: &dyn ActionExecutionCtx,
+    path: &ProjectRelativePath,
+) -> anyhow::Result<ArtifactValue> {
+    let (entry, _hashing_info) = build_entry_from_disk(
+        ctx.fs().fs().resolve(path),
+        FileDigestConfig::build(ctx.digest_config().cas_digest_config()),
+        ctx.blocking_executor(),
+        ctx.fs().fs().root(),
+    )
+    .await?;
+    let entry = entry
+        .with_context(|| format!("Action did not produce `{}`", path))?
+        .map_dir(|dir| {
+            dir.fingerprint(ctx.digest_config().as_directory_serializer())
+                .shared(&*INTERNER)
+        });
+    Ok(ArtifactValue::from(entry))
+}
+
+fn single_output(
+    action: &'static str,
+    outputs: IndexSet<BuildArtifact>,
+) -> anyhow::Result<BuildArtifact> {
+    let count = outputs.len();
+    outputs
+        .into_iter()
+        .into_singleton()
+        .ok_or_else(|| ArchiveError::WrongOutputCount(action, count).into())
+}
+
+#[derive(Allocative, Debug)]
+pub(crate) struct UnregisteredArchiveAction {
+    format: ArchiveFormat,
+    compression_level: Option<i32>,
+    /// The inputs, with their paths in the archive.
+    srcs: Vec<(ArtifactGroup, ForwardRelativePathBuf)>,
+}
+
+impl UnregisteredArchiveAction {
+    pub(crate) fn new(
+        format: ArchiveFormat,
+        compression_level: Option<i32>,
+        srcs: Vec<(ArtifactGroup, ForwardRelativePathBuf)>,
+    ) -> anyhow::Result<Self> {
+        format.validate_compression_level(compression_level)?;
+        for (input, path) in &srcs {
+            match input {
+                ArtifactGroup::Artifact(..) | ArtifactGroup::Promise(..) => {}
+                _ => return Err(ArchiveError::UnsupportedInput(path.to_string()).into()),
+            }
+        }
+        Ok(Self {
+            format,
+            compression_level,
+            srcs,
+        })
+    }
+
+    pub(crate) fn inputs(&self) -> IndexSet<ArtifactGroup> {
+        self.srcs.iter().map(|(input, _)| input.dupe()).collect()
+    }
+}
+
+impl UnregisteredAction for UnregisteredArchiveAction {
+    fn register(
+        self: Box<Self>,
+        inputs: IndexSet<ArtifactGroup>,
+        outputs: IndexSet<BuildArtifact>,
+        _starlark_data: Option<OwnedFrozenValue>,
+        _error_handler: Option<OwnedFrozenValue>,
+    ) -> anyhow::Result<Box<dyn Action>> {
+        Ok(Box::new(ArchiveAction {
+            inputs: inputs.into_iter().collect(),
+            output: single_output("ArchiveAction", outputs)?,
+            inner: *self,
+        }))
+    }
+}
+
+#[derive(Debug, Allocative)]
+struct ArchiveAction {
+    inputs: Box<[ArtifactGroup]>,
+    output: BuildArtifact,
+    inner: UnregisteredArchiveAction,
+}
+
+#[async_trait]
+impl Action for ArchiveAction {
+    fn kind(&self) -> buck2_data::ActionKind {
+        buck2_data::ActionKind::Copy
+    }
+
+    fn inputs(&self) -> anyhow::Result<Cow<'_, [ArtifactGroup]>> {
+        Ok(Cow::Borrowed(&self.inputs))
+    }
+
+    fn outputs(&self) -> anyhow::Result<Cow<'_, [BuildArtifact]>> {
+        Ok(Cow::Borrowed(slice::from_ref(&self.output)))
+    }
+
+    fn as_executable(&self) -> ActionExecutable<'_> {
+        ActionExecutable::Incremental(self)
+    }
+
+    fn category(&self) -> &Category {
+        static ARCHIVE_CATEGORY: Lazy<Category> =
+            Lazy::new(|| Category::try_from("archive").unwrap());
+
+        &ARCHIVE_CATEGORY
+    }
+
+    fn identifier(&self) -> Option<&str> {
+        Some(self.output.get_path().path().as_str())
+    }
+
+    fn aquery_attributes(&self, _fs: &ExecutorFs) -> IndexMap<String, String> {
+        indexmap! {
+            "format".to_owned() => self.inner.format.to_string(),
+            "compression_level".to_owned() => match self.inner.compression_level {
+                None => "None".to_owned(),
+                Some(level) => level.to_string(),
+            },
+            "paths".to_owned() => self.inner.srcs.iter().map(|(_, p)| p.as_str()).collect::<Vec<_>>().join(", "),
+        }
+    }
+}
+
+#[async_trait]
+impl IncrementalActionExecutable for ArchiveAction {
+    async fn execute(
+        &self,
+        ctx: &mut dyn ActionExecutionCtx,
+    ) -> Result<(ActionOutputs, ActionExecutionMetadata), ExecuteError> {
+        let execution_start = Instant::now();
+        ctx.cleanup_outputs().await?;
+
+        let fs = ctx.fs();
+        let mut srcs = Vec::with_capacity(self.inner.srcs.len());
+        let mut to_materialize = Vec::new();
+        for (input, archive_path) in &self.inner.srcs {
+            let (artifact, _) = ctx
+                .artifact_values(input)
+                .iter()
+                .into_singleton()
+                .context("Input did not dereference to exactly one artifact")?;
+            let path = artifact.resolve_path(fs)?;
+            if !artifact.is_source() {
+                to_materialize.push(path.clone());
+            }
+            srcs.push((archive_path.clone(), fs.fs().resolve(&path)));
+        }
+        ctx.materializer()
+            .ensure_materialized(to_materialize)
+            .await?;
+
+        let output = fs.resolve_build(self.output.get_path());
+        let abs_output = fs.fs().resolve(&output);
+        ctx.blocking_executor()
+            .execute_io_inline(|| {
+                write_archive(
+                    &abs_output,
+                    self.inner.format,
+                    self.inner.compression_level,
+                    &collect_entries(&srcs)?,
+                )
+            })
+            .await?;
+
+        let value = value_from_disk(ctx, &output).await?;
+        ctx.materializer()
+            .declare_existing(vec![(output, value.dupe())])
+            .await?;
+
+        Ok((
+            ActionOutputs::new(indexmap![self.output.get_path().dupe() => value]),
+            ActionExecutionMetadata {
+                execution_kind: ActionExecutionKind::Simple,
+                timing: ActionExecutionTimingData {
+                    wall_time: execution_start.elapsed(),
+                },
+            },
+        ))
+    }
+}
+
+#[derive(Allocative, Debug)]
+pub(crate) struct UnregisteredUnarchiveAction {
+    format: ArchiveFormat,
+    strip_components: u32,
+}
+
+impl UnregisteredUnarchiveAction {
+    pub(crate) fn new(format: ArchiveFormat, strip_components: u32) -> Self {
+        Self {
+            format,
+            strip_components,
+        }
+    }
+}
+
+impl UnregisteredAction for UnregisteredUnarchiveAction {
+    fn register(
+        self: Box<Self>,
+        inputs: IndexSet<ArtifactGroup>,
+        outputs: IndexSet<BuildArtifact>,
+        _starlark_data: Option<OwnedFrozenValue>,
+        _error_handler: Option<OwnedFrozenValue>,
+    ) -> anyhow::Result<Box<dyn Action>> {
+        let count = inputs.len();
+        let input = inputs
+            .into_iter()
+            .into_singleton()
+            .ok_or(ArchiveError::WrongInputCount("UnarchiveAction", count))?;
+        Ok(Box::new(UnarchiveAction {
+            input,
+            output: single_output("UnarchiveAction", outputs)?,
+            inner: *self,
+        }))
+    }
+}
+
+#[derive(Debug, Allocative)]
+struct UnarchiveAction {
+    input: ArtifactGroup,
+    output: BuildArtifact,
+    inner: UnregisteredUnarchiveAction,
+}
+
+#[async_trait]
+impl Action for UnarchiveAction {
+    fn kind(&self) -> buck2_data::ActionKind {
+        buck2_data::ActionKind::Copy
+    }
+
+    fn inputs(&self) -> anyhow::Result<Cow<'_, [ArtifactGroup]>> {
+        Ok(Cow::Borrowed(slice::from_ref(&self.input)))
+    }
+
+    fn outputs(&self) -> anyhow::Result<Cow<'_, [BuildArtifact]>> {
+        Ok(Cow::Borrowed(slice::from_ref(&self.output)))
+    }
+
+    fn as_executable(&self) -> ActionExecutable<'_> {
+        ActionExecutable::Incremental(self)
+    }
+
+    fn category(&self) -> &Category {
+        static UNARCHIVE_CATEGORY: Lazy<Category> =
+            Lazy::new(|| Category::try_from("unarchive").unwrap());
+
+        &UNARCHIVE_CATEGORY
+    }
+
+    fn identifier(&self) -> Option<&str> {
+        Some(self.output.get_path().path().as_str())
+    }
+
+    fn aquery_attributes(&self, _fs: &ExecutorFs) -> IndexMap<String, String> {
+        indexmap! {
+            "format".to_owned() => self.inner.format.to_string(),
+            "strip_components".to_owned() => self.inner.strip_components.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl IncrementalActionExecutable for UnarchiveAction {
+    async fn execute(
+        &self,
+        ctx: &mut dyn ActionExecutionCtx,
+    ) -> Result<(ActionOutputs, ActionExecutionMetadata), ExecuteError> {
+        let execution_start = Instant::now();
+        ctx.cleanup_outputs().await?;
+
+        let fs = ctx.fs();
+        let (artifact, _) = ctx
+            .artifact_values(&self.input)
+            .iter()
+            .into_singleton()
+            .context("Input did not dereference to exactly one artifact")?;
+        let archive = artifact.resolve_path(fs)?;
+        if !artifact.is_source() {
+            ctx.materializer()
+                .ensure_materialized(vec![archive.clone()])
+                .await?;
+        }
+
+        let output = fs.resolve_build(self.output.get_path());
+        let abs_archive = fs.fs().resolve(&archive);
+        let abs_output = fs.fs().resolve(&output);
+        ctx.blocking_executor()
+            .execute_io_inline(|| {
+                extract_archive(
+                    &abs_archive,
+                    self.inner.format,
+                    self.inner.strip_components,
+                    &abs_output,
+                )
+            })
+            .await?;
+
+        let value = value_from_disk(ctx, &output).await?;
+        ctx.materializer()
+            .declare_existing(vec![(output, value.dupe())])
+            .await?;
+
+        Ok((
+            ActionOutputs::new(indexmap![self.output.get_path().dupe() => value]),
+            ActionExecutionMetadata {
+                execution_kind: ActionExecutionKind::Simple,
+                timing: ActionExecutionTimingData {
+                    wall_time: execution_start.elapsed(),
+                },
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_core::fs::paths::abs_path::AbsPath;
+
+    use super::*;
+
+    #[test]
+    fn test_format() -> anyhow::Result<()> {
+        assert_eq!(ArchiveFormat::TarGz, ArchiveFormat::new(None, "out.tgz")?);
+        assert_eq!(
+            ArchiveFormat::TarZst,
+            ArchiveFormat::new(None, "out.tar.zst")?
+        );
+        assert_eq!(
+            ArchiveFormat::Zip,
+            ArchiveFormat::new(Some("zip"), "out.jar")?
+        );
+        assert!(ArchiveFormat::new(None, "out.jar").is_err());
+        assert!(ArchiveFormat::new(Some("rar"), "out.rar").is_err());
+
+        assert!(
+            ArchiveFormat::TarGz
+                .validate_compression_level(Some(9))
+                .is_ok()
+        );
+        assert!(
+            ArchiveFormat::TarGz
+                .validate_compression_level(Some(10))
+                .is_err()
+        );
+        assert!(ArchiveFormat::Tar.validate_compression_level(None).is_ok());
+        assert!(
+            ArchiveFormat::Tar
+                .validate_compression_level(Some(0))
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_extraction_path() -> anyhow::Result<()> {
+        assert_eq!(
+            Some(ForwardRelativePathBuf::unchecked_new("b/c".to_owned())),
+            extraction_path("./a/b/c", 1)?
+        );
+        assert_eq!(None, extraction_path("a/", 1)?);
+        assert!(extraction_path("a/../../etc/passwd", 0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_archive_is_deterministic() -> anyhow::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let root = AbsNormPathBuf::new(tempdir.path().to_owned())?;
+        let src = root.join(ForwardRelativePath::new("src")?);
+        fs_util::create_dir_all(src.join(ForwardRelativePath::new("dir")?))?;
+        fs_util::write(src.join(ForwardRelativePath::new("dir/b.txt")?), "b")?;
+        fs_util::write(src.join(ForwardRelativePath::new("a.txt")?), "a")?;
+
+        for format in [
+            ArchiveFormat::Tar,
+            ArchiveFormat::TarGz,
+            ArchiveFormat::TarZst,
+            ArchiveFormat::Zip,
+        ] {
+            let entries = collect_entries(&[(
+                ForwardRelativePathBuf::unchecked_new("pkg".to_owned()),
+                src.clone(),
+            )])?;
+            assert_eq!(
+                vec!["pkg", "pkg/a.txt", "pkg/dir", "pkg/dir/b.txt"],
+                entries.keys().collect::<Vec<_>>()
+            );
+
+            let first = root.join(ForwardRelativePath::new("first")?);
+            let second = root.join(ForwardRelativePath::new("second")?);
+            write_archive(&first, format, None, &entries)?;
+            write_archive(&second, format, None, &entries)?;
+            assert_eq!(fs_util::read(&first)?, fs_util::read(&second)?);
+
+            let extracted = root.join(ForwardRelativePath::new(&format!("out_{}", format))?);
+            extract_archive(&first, format, 1, &extracted)?;
+            assert_eq!(
+                "b",
+                fs_util::read_to_string(AsRef::<AbsPath>::as_ref(
+                    &extracted.join(ForwardRelativePath::new("dir/b.txt")?)
+                ))?
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,342 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! `ctx.actions.build_info()`: a JSON file stamping the build with stable keys, which are part
+//! of the action, and volatile keys, which are computed when the action runs.
+//!
+//! The action only runs again when its stable keys change, so that a new timestamp or revision
+//! alone doesn't invalidate it nor the actions which depend on it: volatile keys are as of the
+//! last time it ran.
+//!
+//! The volatile values are in the output though, so they are part of its digest: whenever the
+//! action does run (its stable keys changed, or its output is not in the daemon's state anymore,
+//! e.g. after a restart), the output has a new digest and every action using it runs again
+//! instead of hitting the action cache. Rules should only pass the output to the few actions
+//! which need it, e.g. the final link, and not to compilation.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::slice;
+use std::str::FromStr;
+use std::time::Instant;
+use std::time::SystemTime;
+
+use allocative::Allocative;
+use anyhow::Context as _;
+use async_trait::async_trait;
+use buck2_artifact::artifact::build_artifact::BuildArtifact;
+use buck2_build_api::actions::execute::action_executor::ActionExecutionKind;
+use buck2_build_api::actions::execute::action_executor::ActionExecutionMetadata;
+use buck2_build_api::actions::execute::action_executor::ActionOutputs;
+use buck2_build_api::actions::execute::error::ExecuteError;
+use buck2_build_api::actions::Action;
+use buck2_build_api::actions::ActionExecutable;
+use buck2_build_api::actions::ActionExecutionCtx;
+use buck2_build_api::actions::IncrementalActionExecutable;
+use buck2_build_api::actions::UnregisteredAction;
+use buck2_build_api::artifact_groups::ArtifactGroup;
+use buck2_core::category::Category;
+use buck2_execute::artifact::fs::ExecutorFs;
+use buck2_execute::execute::command_executor::ActionExecutionTimingData;
+use buck2_execute::materialize::materializer::WriteRequest;
+use dupe::Dupe;
+use indexmap::indexmap;
+use indexmap::IndexMap;
+use indexmap::IndexSet;
+use once_cell::sync::Lazy;
+use starlark::values::OwnedFrozenValue;
+
+#[derive(Debug, buck2_error::Error)]
+pub(crate) enum BuildInfoError {
+    #[error("BuildInfoAction received inputs")]
+    TooManyInputs,
+    #[error("BuildInfoAction received no outputs")]
+    NoOutputs,
+    #[error("BuildInfoAction received more than one output")]
+    TooManyOutputs,
+    #[error("Unknown volatile build info key `{0}`, expected `timestamp` or `revision`")]
+    #[buck2(user)]
+    UnknownVolatileKey(String),
+    #[error("Build info key `{0}` is both stable and volatile")]
+    #[buck2(user)]
+    DuplicateKey(String),
+}
+
+/// The keys whose values are only known when the action runs.
+#[derive(Copy, Clone, Dupe, Debug, PartialEq, Eq, Allocative)]
+pub(crate) enum VolatileKey {
+    /// When the action ran, in seconds since the epoch.
+    Timestamp,
+    /// The revision the working copy is based on, if the file watcher knows it.
+    Revision,
+}
+
+impl VolatileKey {
+    fn as_str(self) -> &'static str {
+        match self {
+            VolatileKey::Timestamp => "timestamp",
+            VolatileKey::Revision => "revision",
+        }
+    }
+}
+
+impl fmt::Display for VolatileKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for VolatileKey {
+    type Err = BuildInfoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "timestamp" => Ok(VolatileKey::Timestamp),
+            "revision" => Ok(VolatileKey::Revision),
+            _ => Err(BuildInfoError::UnknownVolatileKey(s.to_owned())),
+        }
+    }
+}
+
+#[derive(Allocative, Debug)]
+pub(crate) struct UnregisteredBuildInfoAction {
+    stable: IndexMap<String, String>,
+    volatile: Vec<VolatileKey>,
+}
+
+impl UnregisteredBuildInfoAction {
+    pub(crate) fn new(
+        stable: IndexMap<String, String>,
+        volatile: Vec<VolatileKey>,
+    ) -> anyhow::Result<Self> {
+        for key in &volatile {
+            if stable.contains_key(key.as_str()) {
+                return Err(BuildInfoError::DuplicateKey(key.to_string()).into());
+            }
+        }
+        Ok(Self { stable, volatile })
+    }
+
+    fn contents(&self, timestamp: SystemTime, revision: Option<&str>) -> anyhow::Result<Vec<u8>> {
+        let mut info = serde_json::Map::new();
+        for (key, value) in &self.stable {
+            info.insert(key.clone(), serde_json::Value::String(value.clone()));
+        }
+        for key in &self.volatile {
+            let value = match key {
+                VolatileKey::Timestamp => timestamp
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs())
+                    .into(),
+                VolatileKey::Revision => revision.map_or(serde_json::Value::Null, |r| r.into()),
+            };
+            info.insert(key.to_string(), value);
+        }
+        let mut contents = serde_json::to_vec_pretty(&info)?;
+        contents.push(b'\n');
+        Ok(contents)
+    }
+}
+
+impl UnregisteredAction for UnregisteredBuildInfoAction {
+    fn register(
+        self: Box<Self>,
+        inputs: IndexSet<ArtifactGroup>,
+        outputs: IndexSet<BuildArtifact>,
+        _starlark_data: Option<OwnedFrozenValue>,
+        _error_handler: Option<OwnedFrozenValue>,
+    ) -> anyhow::Result<Box<dyn Action>> {
+        let mut outputs = outputs.into_iter();
+        let output = match (outputs.next(), outputs.next()) {
+            (Some(o), None) => o,
+            (None, ..) => return Err(BuildInfoError::NoOutputs.into()),
+            (Some(..), Some(..)) => return Err(BuildInfoError::TooManyOutputs.into()),
+        };
+        if !inputs.is_empty() {
+            return Err(BuildInfoError::TooManyInputs.into());
+        }
+        Ok(Box::new(BuildInfoAction {
+            output,
+            inner: *self,
+        }))
+    }
+}
+
+#[derive(Debug, Allocative)]
+struct BuildInfoAction {
+    output: BuildArtifact,
+    inner: UnregisteredBuildInfoAction,
+}
+
+#[async_trait]
+impl Action for BuildInfoAction {
+    fn kind(&self) -> buck2_data::ActionKind {
+        buck2_data::ActionKind::Write
+    }
+
+    fn inputs(&self) -> anyhow::Result<Cow<'_, [ArtifactGroup]>> {
+        Ok(Cow::Borrowed(&[]))
+    }
+
+    fn outputs(&self) -> anyhow::Result<Cow<'_, [BuildArtifact]>> {
+        Ok(Cow::Borrowed(slice::from_ref(&self.output)))
+    }
+
+    fn as_executable(&self) -> ActionExecutable<'_> {
+        ActionExecutable::Incremental(self)
+    }
+
+    fn category(&self) -> &Category {
+        static BUILD_INFO_CATEGORY: Lazy<Category> =
+            Lazy::new(|| Category::try_from("build_info").unwrap());
+
+        &BUILD_INFO_CATEGORY
+    }
+
+    fn identifier(&self) -> Option<&str> {
+        Some(self.output.get_path().path().as_str())
+    }
+
+    fn aquery_attributes(&self, _fs: &ExecutorFs) -> IndexMap<String, String> {
+        indexmap! {
+            "stable".to_owned() => self
+                .inner
+                .stable
+                .iter()
+                .map(|(k, v)| format!("{}={}", k, v))
+                .collect::<Vec<_>>()
+                .join(", "),
+            "volatile".to_owned() => self
+                .inner
+                .volatile
+                .iter()
+                .map(|k| k.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+        }
+    }
+}
+
+#[async_trait]
+impl IncrementalActionExecutable for BuildInfoAction {
+    async fn execute(
+        &self,
+        ctx: &mut dyn ActionExecutionCtx,
+    ) -> Result<(ActionOutputs, ActionExecutionMetadata), ExecuteError> {
+        let fs = ctx.fs();
+        let revision = ctx.mergebase().0.dupe();
+
+        let mut execution_start = None;
+
+        let value = ctx
+            .materializer()
+            .declare_write(Box::new(|| {
+                execution_start = Some(Instant::now());
+                let content = self
+                    .inner
+                    .contents(SystemTime::now(), revision.as_deref())?;
+                Ok(vec![WriteRequest {
+                    path: fs.resolve_build(self.output.get_path()),
+                    content,
+                    is_executable: false,
+                }])
+            }))
+            .await?
+            .into_iter()
+            .next()
+            .context("Write did not execute")?;
+
+        let wall_time = execution_start
+            .context("Action did not set execution_start")?
+            .elapsed();
+
+        Ok((
+            ActionOutputs::new(indexmap![self.output.get_path().dupe() => value]),
+            ActionExecutionMetadata {
+                execution_kind: ActionExecutionKind::Simple,
+                timing: ActionExecutionTimingData { wall_time },
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_volatile_key_from_str() {
+        assert_eq!(
+            VolatileKey::Revision,
+            "revision".parse::<VolatileKey>().unwrap()
+        );
+        assert!("hostname".parse::<VolatileKey>().is_err());
+    }
+
+    #[test]
+    fn test_duplicate_key() {
+        assert!(
+            UnregisteredBuildInfoAction::new(
+                indexmap! { "timestamp".to_owned() => "0".to_owned() },
+                vec![VolatileKey::Timestamp],
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_contents() -> anyhow::Result<()> {
+        let action = UnregisteredBuildInfoAction::new(
+            indexmap! { "version".to_owned() => "1.2".to_owned() },
+            vec![VolatileKey::Timestamp, VolatileKey::Revision],
+        )?;
+        let timestamp = SystemTime::UNIX_EPOCH + Duration::from_secs(1700000000);
+        assert_eq!(
+            serde_json::json!({"version": "1.2", "timestamp": 1700000000, "revision": "abc123"}),
+            serde_json::from_slice::<serde_json::Value>(
+                &action.contents(timestamp, Some("abc123"))?
+            )?
+        );
+        assert_eq!(
+            serde_json::Value::Null,
+            serde_json::from_slice::<serde_json::Value>(&action.contents(timestamp, None)?)?["revision"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_volatile_keys_change_the_output() -> anyhow::Result<()> {
+        let stable = indexmap! { "version".to_owned() => "1.2".to_owned() };
+        let first = SystemTime::UNIX_EPOCH + Duration::from_secs(1700000000);
+        let second = first + Duration::from_secs(1);
+
+        // The output is only reproducible without volatile keys...
+        let action = UnregisteredBuildInfoAction::new(stable.clone(), Vec::new())?;
+        assert_eq!(
+            action.contents(first, Some("abc123"))?,
+            action.contents(second, Some("def456"))?
+        );
+
+        // ... otherwise each run has a different output, so a different digest for its users.
+        let action =
+            UnregisteredBuildInfoAction::new(stable.clone(), vec![VolatileKey::Timestamp])?;
+        assert_ne!(
+            action.contents(first, None)?,
+            action.contents(second, None)?
+        );
+        let action = UnregisteredBuildInfoAction::new(stable, vec![VolatileKey::Revision])?;
+        assert_ne!(
+            action.contents(first, Some("abc123"))?,
+            action.contents(first, Some("def456"))?
+        );
+        Ok(())
+    }
+}
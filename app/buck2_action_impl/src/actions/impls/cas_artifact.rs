@@ -49,6 +49,7 @@ use remote_execution as RE;
 use starlark::values::OwnedFrozenValue;
 
 use crate::actions::impls::offline;
+use crate::actions::impls::offline::OfflineError;
 
 #[derive(Debug, buck2_error::Error)]
 enum CasArtifactActionDeclarationError {
@@ -204,6 +205,14 @@ impl IncrementalActionExecutable for CasArtifactAction {
             return self.execute_for_offline(ctx).await.map_err(Into::into);
         }
 
+        if ctx.run_action_knobs().offline {
+            return Err(anyhow::Error::from(OfflineError::Cas {
+                target: ctx.target().owner().to_string(),
+                digest: self.inner.digest.to_string(),
+            })
+            .into());
+        }
+
         let expiration = ctx
             .re_client()
             .get_digest_expirations(vec![self.inner.digest.to_re()], self.inner.re_use_case)
@@ -0,0 +1,266 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! `ctx.actions.digest_tree()`: a JSON manifest of the entries of a directory artifact, so that
+//! incremental tools (e.g. packagers) can diff two versions of a tree without hashing it again.
+//!
+//! The manifest is computed from the artifact value of the directory, which Buck2 already knows,
+//! so the directory doesn't need to be materialized.
+
+use std::borrow::Cow;
+use std::slice;
+use std::time::Instant;
+
+use allocative::Allocative;
+use anyhow::Context as _;
+use async_trait::async_trait;
+use buck2_artifact::artifact::build_artifact::BuildArtifact;
+use buck2_build_api::actions::execute::action_executor::ActionExecutionKind;
+use buck2_build_api::actions::execute::action_executor::ActionExecutionMetadata;
+use buck2_build_api::actions::execute::action_executor::ActionOutputs;
+use buck2_build_api::actions::execute::error::ExecuteError;
+use buck2_build_api::actions::Action;
+use buck2_build_api::actions::ActionExecutable;
+use buck2_build_api::actions::ActionExecutionCtx;
+use buck2_build_api::actions::IncrementalActionExecutable;
+use buck2_build_api::actions::UnregisteredAction;
+use buck2_build_api::artifact_groups::ArtifactGroup;
+use buck2_core::category::Category;
+use buck2_core::directory::Directory;
+use buck2_core::directory::DirectoryEntry;
+use buck2_core::directory::DirectoryIterator;
+use buck2_execute::artifact::fs::ExecutorFs;
+use buck2_execute::artifact_value::ArtifactValue;
+use buck2_execute::directory::ActionDirectoryMember;
+use buck2_execute::execute::command_executor::ActionExecutionTimingData;
+use buck2_execute::materialize::materializer::WriteRequest;
+use dupe::Dupe;
+use gazebo::prelude::*;
+use indexmap::indexmap;
+use indexmap::IndexMap;
+use indexmap::IndexSet;
+use once_cell::sync::Lazy;
+use starlark::values::OwnedFrozenValue;
+
+#[derive(Debug, buck2_error::Error)]
+enum DigestTreeError {
+    #[error("`digest_tree` input `{0}` is not a directory")]
+    #[buck2(user)]
+    NotADirectory(String),
+    #[error("DigestTreeAction received {0} inputs, expected 1")]
+    WrongInputCount(usize),
+    #[error("DigestTreeAction received {0} outputs, expected 1")]
+    WrongOutputCount(usize),
+}
+
+#[derive(Allocative, Debug)]
+pub(crate) struct UnregisteredDigestTreeAction;
+
+impl UnregisteredAction for UnregisteredDigestTreeAction {
+    fn register(
+        self: Box<Self>,
+        inputs: IndexSet<ArtifactGroup>,
+        outputs: IndexSet<BuildArtifact>,
+        _starlark_data: Option<OwnedFrozenValue>,
+        _error_handler: Option<OwnedFrozenValue>,
+    ) -> anyhow::Result<Box<dyn Action>> {
+        let (input_count, output_count) = (inputs.len(), outputs.len());
+        Ok(Box::new(DigestTreeAction {
+            input: inputs
+                .into_iter()
+                .into_singleton()
+                .ok_or(DigestTreeError::WrongInputCount(input_count))?,
+            output: outputs
+                .into_iter()
+                .into_singleton()
+                .ok_or(DigestTreeError::WrongOutputCount(output_count))?,
+        }))
+    }
+}
+
+#[derive(Debug, Allocative)]
+struct DigestTreeAction {
+    input: ArtifactGroup,
+    output: BuildArtifact,
+}
+
+/// The manifest of a directory, with its entries sorted by path relative to the directory:
+///
+/// ```json
+/// {
+///   "entries": {
+///     "bin": {"type": "directory"},
+///     "bin/tool": {"type": "file", "digest": "<hash>", "size": 1234, "mode": "0755"},
+///     "lib/libfoo.so": {"type": "file", "digest": "<hash>", "size": 5678, "mode": "0644"},
+///     "tool": {"type": "symlink", "target": "bin/tool"}
+///   }
+/// }
+/// ```
+///
+/// `lib` is omitted here for brevity, but all directories are listed, so that empty directories
+/// are part of the manifest too.
+fn contents(value: &ArtifactValue) -> Option<Vec<u8>> {
+    let DirectoryEntry::Dir(dir) = value.entry() else {
+        return None;
+    };
+    let mut entries = serde_json::Map::new();
+    for (path, entry) in dir.ordered_walk().with_paths() {
+        let entry = match entry {
+            DirectoryEntry::Dir(_) => serde_json::json!({"type": "directory"}),
+            DirectoryEntry::Leaf(ActionDirectoryMember::File(metadata)) => serde_json::json!({
+                "type": "file",
+                "digest": metadata.digest.raw_digest().to_string(),
+                "size": metadata.digest.size(),
+                "mode": if metadata.is_executable { "0755" } else { "0644" },
+            }),
+            DirectoryEntry::Leaf(ActionDirectoryMember::Symlink(symlink)) => serde_json::json!({
+                "type": "symlink",
+                "target": symlink.target().as_str(),
+            }),
+            DirectoryEntry::Leaf(ActionDirectoryMember::ExternalSymlink(symlink)) => {
+                serde_json::json!({
+                    "type": "symlink",
+                    "target": symlink.to_path_buf().to_string_lossy(),
+                })
+            }
+        };
+        entries.insert(path.to_string(), entry);
+    }
+    let mut contents =
+        serde_json::to_vec_pretty(&serde_json::json!({ "entries": entries })).unwrap();
+    contents.push(b'\n');
+    Some(contents)
+}
+
+#[async_trait]
+impl Action for DigestTreeAction {
+    fn kind(&self) -> buck2_data::ActionKind {
+        buck2_data::ActionKind::Write
+    }
+
+    fn inputs(&self) -> anyhow::Result<Cow<'_, [ArtifactGroup]>> {
+        Ok(Cow::Borrowed(slice::from_ref(&self.input)))
+    }
+
+    fn outputs(&self) -> anyhow::Result<Cow<'_, [BuildArtifact]>> {
+        Ok(Cow::Borrowed(slice::from_ref(&self.output)))
+    }
+
+    fn as_executable(&self) -> ActionExecutable<'_> {
+        ActionExecutable::Incremental(self)
+    }
+
+    fn category(&self) -> &Category {
+        static DIGEST_TREE_CATEGORY: Lazy<Category> =
+            Lazy::new(|| Category::try_from("digest_tree").unwrap());
+
+        &DIGEST_TREE_CATEGORY
+    }
+
+    fn identifier(&self) -> Option<&str> {
+        Some(self.output.get_path().path().as_str())
+    }
+
+    fn aquery_attributes(&self, _fs: &ExecutorFs) -> IndexMap<String, String> {
+        indexmap! {}
+    }
+}
+
+#[async_trait]
+impl IncrementalActionExecutable for DigestTreeAction {
+    async fn execute(
+        &self,
+        ctx: &mut dyn ActionExecutionCtx,
+    ) -> Result<(ActionOutputs, ActionExecutionMetadata), ExecuteError> {
+        let execution_start = Instant::now();
+
+        let (artifact, value) = ctx
+            .artifact_values(&self.input)
+            .iter()
+            .into_singleton()
+            .context("Input did not dereference to exactly one artifact")?;
+        let content = contents(value).ok_or_else(|| {
+            anyhow::Error::from(DigestTreeError::NotADirectory(
+                artifact.get_path().with_short_path(|p| p.to_string()),
+            ))
+        })?;
+
+        let fs = ctx.fs();
+        let value = ctx
+            .materializer()
+            .declare_write(Box::new(|| {
+                Ok(vec![WriteRequest {
+                    path: fs.resolve_build(self.output.get_path()),
+                    content,
+                    is_executable: false,
+                }])
+            }))
+            .await?
+            .into_iter()
+            .next()
+            .context("Write did not execute")?;
+
+        Ok((
+            ActionOutputs::new(indexmap![self.output.get_path().dupe() => value]),
+            ActionExecutionMetadata {
+                execution_kind: ActionExecutionKind::Simple,
+                timing: ActionExecutionTimingData {
+                    wall_time: execution_start.elapsed(),
+                },
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_common::file_ops::FileMetadata;
+    use buck2_common::file_ops::TrackedFileDigest;
+    use buck2_core::fs::project_rel_path::ProjectRelativePath;
+    use buck2_execute::digest_config::DigestConfig;
+    use buck2_execute::directory::insert_file;
+    use buck2_execute::directory::ActionDirectoryBuilder;
+    use buck2_execute::directory::INTERNER;
+
+    use super::*;
+
+    #[test]
+    fn test_contents() -> anyhow::Result<()> {
+        let digest_config = DigestConfig::testing_default();
+        let file = FileMetadata {
+            digest: TrackedFileDigest::from_content(b"hello", digest_config.cas_digest_config()),
+            is_executable: true,
+        };
+        let mut builder = ActionDirectoryBuilder::empty();
+        insert_file(
+            &mut builder,
+            ProjectRelativePath::new("bin/hello")?,
+            file.dupe(),
+        )?;
+        let dir = ArtifactValue::dir(
+            builder
+                .fingerprint(digest_config.as_directory_serializer())
+                .shared(&*INTERNER),
+        );
+
+        let manifest: serde_json::Value = serde_json::from_slice(&contents(&dir).unwrap())?;
+        assert_eq!("directory", manifest["entries"]["bin"]["type"]);
+        let hello = &manifest["entries"]["bin/hello"];
+        assert_eq!("file", hello["type"]);
+        assert_eq!(5, hello["size"]);
+        assert_eq!("0755", hello["mode"]);
+        assert_eq!(
+            serde_json::Value::String(file.digest.raw_digest().to_string()),
+            hello["digest"]
+        );
+
+        assert_eq!(None, contents(&ArtifactValue::file(file)));
+        Ok(())
+    }
+}
@@ -33,17 +33,21 @@ use buck2_core::category::Category;
 use buck2_execute::artifact_value::ArtifactValue;
 use buck2_execute::digest_config::DigestConfig;
 use buck2_execute::execute::command_executor::ActionExecutionTimingData;
+use buck2_execute::materialize::download_cache::DownloadCache;
 use buck2_execute::materialize::http::http_download;
 use buck2_execute::materialize::http::http_head;
+use buck2_execute::materialize::http::try_mirrors;
 use buck2_execute::materialize::http::Checksum;
 use buck2_execute::materialize::materializer::HttpDownloadInfo;
 use buck2_http::HttpClient;
 use dupe::Dupe;
+use dupe::IterDupedExt;
 use indexmap::IndexSet;
 use once_cell::sync::Lazy;
 use starlark::values::OwnedFrozenValue;
 
 use crate::actions::impls::offline;
+use crate::actions::impls::offline::OfflineError;
 
 #[derive(Debug, buck2_error::Error)]
 enum DownloadFileActionError {
@@ -57,6 +61,8 @@ enum DownloadFileActionError {
 pub(crate) struct UnregisteredDownloadFileAction {
     checksum: Checksum,
     url: Arc<str>,
+    /// Tried in order after `url` fails.
+    mirrors: Vec<Arc<str>>,
     vpnless_url: Option<Arc<str>>,
     is_executable: bool,
     is_deferrable: bool,
@@ -66,6 +72,7 @@ impl UnregisteredDownloadFileAction {
     pub(crate) fn new(
         checksum: Checksum,
         url: Arc<str>,
+        mirrors: Vec<Arc<str>>,
         vpnless_url: Option<Arc<str>>,
         is_executable: bool,
         is_deferrable: bool,
@@ -73,6 +80,7 @@ impl UnregisteredDownloadFileAction {
         Self {
             checksum,
             url,
+            mirrors,
             vpnless_url,
             is_executable,
             is_deferrable,
@@ -129,20 +137,27 @@ impl DownloadFileAction {
             .expect("a single artifact by construction")
     }
 
-    fn url(&self, client: &HttpClient) -> &Arc<str> {
-        if client.supports_vpnless() {
+    /// The URLs to try, in order.
+    fn urls(&self, client: &HttpClient) -> Vec<Arc<str>> {
+        let url = if client.supports_vpnless() {
             self.inner.vpnless_url.as_ref().unwrap_or(&self.inner.url)
         } else {
             &self.inner.url
-        }
+        };
+        let mut urls = vec![url.dupe()];
+        urls.extend(self.inner.mirrors.iter().duped());
+        urls
     }
 
     /// Try to produce a FileMetadata without downloading the file.
-    async fn declared_metadata(
+    ///
+    /// Also returns the first of `urls` which is available.
+    async fn declared_metadata<'a>(
         &self,
         client: &HttpClient,
         digest_config: DigestConfig,
-    ) -> anyhow::Result<Option<FileMetadata>> {
+        urls: &'a [Arc<str>],
+    ) -> anyhow::Result<Option<(FileMetadata, &'a Arc<str>)>> {
         if !self.inner.is_deferrable {
             return Ok(None);
         }
@@ -166,8 +181,40 @@ impl DownloadFileAction {
             None => return Ok(None),
         };
 
-        let url = self.url(client);
-        let head = http_head(client, url).await?;
+        // A file in the download cache will be materialized from there without using the network,
+        // so there is no need to ask the network for its size.
+        let cached_len =
+            DownloadCache::new(client).and_then(|c| c.cached_len(&self.inner.checksum));
+        let (content_length, url) = match cached_len {
+            Some(len) => (Some(len), &urls[0]),
+            None => self.content_length(client, urls).await?,
+        };
+
+        match content_length {
+            Some(length) => {
+                let digest = TrackedFileDigest::new(
+                    FileDigest::new(digest, length),
+                    digest_config.cas_digest_config(),
+                );
+                Ok(Some((
+                    FileMetadata {
+                        digest,
+                        is_executable: self.inner.is_executable,
+                    },
+                    url,
+                )))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Ask the first available of `urls` for the size of the file.
+    async fn content_length<'a>(
+        &self,
+        client: &HttpClient,
+        urls: &'a [Arc<str>],
+    ) -> anyhow::Result<(Option<u64>, &'a Arc<str>)> {
+        let (head, url) = try_mirrors(urls, |url| http_head(client, url)).await?;
 
         let content_length = head
             .headers()
@@ -189,20 +236,7 @@ impl DownloadFileAction {
                     http::header::CONTENT_LENGTH
                 )
             })?;
-
-        match content_length {
-            Some(length) => {
-                let digest = TrackedFileDigest::new(
-                    FileDigest::new(digest, length),
-                    digest_config.cas_digest_config(),
-                );
-                Ok(Some(FileMetadata {
-                    digest,
-                    is_executable: self.inner.is_executable,
-                }))
-            }
-            None => Ok(None),
-        }
+        Ok((content_length, url))
     }
 
     /// Execute this action for offline builds (e.g. no network).
@@ -271,11 +305,26 @@ impl IncrementalActionExecutable for DownloadFileAction {
         }
 
         let client = ctx.http_client();
-        let url = self.url(&client);
+        let urls = self.urls(&client);
+
+        if ctx.run_action_knobs().offline
+            && DownloadCache::new(&client)
+                .and_then(|c| c.cached_len(&self.inner.checksum))
+                .is_none()
+        {
+            return Err(anyhow::Error::from(OfflineError::Download {
+                target: ctx.target().owner().to_string(),
+                url: urls[0].to_string(),
+            })
+            .into());
+        }
 
         let (value, execution_kind) = {
-            match self.declared_metadata(&client, ctx.digest_config()).await? {
-                Some(metadata) => {
+            match self
+                .declared_metadata(&client, ctx.digest_config(), &urls)
+                .await?
+            {
+                Some((metadata, url)) => {
                     let artifact_fs = ctx.fs();
                     let rel_path = artifact_fs.resolve_build(self.output().get_path());
 
@@ -303,15 +352,18 @@ impl IncrementalActionExecutable for DownloadFileAction {
                     let rel_path = artifact_fs.resolve_build(self.output().get_path());
 
                     // Slow path: download now.
-                    let digest = http_download(
-                        &client,
-                        project_fs,
-                        ctx.digest_config(),
-                        &rel_path,
-                        url,
-                        &self.inner.checksum,
-                        self.inner.is_executable,
-                    )
+                    let digest_config = ctx.digest_config();
+                    let (digest, _url) = try_mirrors(&urls, |url| {
+                        http_download(
+                            &client,
+                            project_fs,
+                            digest_config,
+                            &rel_path,
+                            url,
+                            &self.inner.checksum,
+                            self.inner.is_executable,
+                        )
+                    })
                     .await?;
 
                     let metadata = FileMetadata {
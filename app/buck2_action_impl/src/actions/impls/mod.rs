@@ -7,10 +7,14 @@
  * of this source tree.
  */
 
+pub(crate) mod archive;
+pub(crate) mod build_info;
 pub(crate) mod cas_artifact;
 pub(crate) mod copy;
+pub(crate) mod digest_tree;
 pub(crate) mod download_file;
 pub(crate) mod offline;
+pub(crate) mod outputs_metadata;
 pub mod run;
 pub(crate) mod symlinked_dir;
 pub(crate) mod write;
@@ -18,6 +18,22 @@ use buck2_execute::entry::build_entry_from_disk;
 use buck2_execute::materialize::materializer::CopiedArtifact;
 use dupe::Dupe;
 
+/// Network access needed by an action of an offline build (`--offline`).
+#[derive(Debug, buck2_error::Error)]
+#[buck2(user, tag = Offline)]
+pub(crate) enum OfflineError {
+    #[error(
+        "`{target}` needs to download `{url}`, which is not in the download cache, \
+        but the build is `--offline`"
+    )]
+    Download { target: String, url: String },
+    #[error(
+        "`{target}` needs to fetch `{digest}` from the remote execution CAS, \
+        but the build is `--offline`"
+    )]
+    Cas { target: String, digest: String },
+}
+
 /// Declares a copy materialization to copy the output BuildArtifact to the
 /// offline cache for use in an offline build. Returns the project-relative path
 /// to the offline cached file.
@@ -86,3 +102,25 @@ async fn declare_copy_materialization(
         )
         .await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offline_error_tag() {
+        let download = OfflineError::Download {
+            target: "root//:a".to_owned(),
+            url: "https://example.com/a".to_owned(),
+        };
+        let cas = OfflineError::Cas {
+            target: "root//:a".to_owned(),
+            digest: "0123:1".to_owned(),
+        };
+        for e in [download, cas] {
+            let e = buck2_error::Error::from(e);
+            assert_eq!(vec![buck2_error::ErrorTag::Offline], e.tags());
+            assert!(e.to_string().contains("`root//:a`"), "{}", e);
+        }
+    }
+}
@@ -0,0 +1,233 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! `ctx.actions.run(outputs_metadata = True)`: a small JSON file describing the outputs of a run
+//! action, so that downstream rules can check them (e.g. for size regressions) with
+//! `dynamic_output` without materializing the outputs themselves.
+//!
+//! The metadata is computed from the artifact values of the outputs, which are known as soon as
+//! the run action has executed, including when it was a cache hit.
+
+use std::borrow::Cow;
+use std::slice;
+use std::time::Instant;
+
+use allocative::Allocative;
+use anyhow::Context as _;
+use async_trait::async_trait;
+use buck2_artifact::artifact::build_artifact::BuildArtifact;
+use buck2_build_api::actions::execute::action_executor::ActionExecutionKind;
+use buck2_build_api::actions::execute::action_executor::ActionExecutionMetadata;
+use buck2_build_api::actions::execute::action_executor::ActionOutputs;
+use buck2_build_api::actions::execute::error::ExecuteError;
+use buck2_build_api::actions::Action;
+use buck2_build_api::actions::ActionExecutable;
+use buck2_build_api::actions::ActionExecutionCtx;
+use buck2_build_api::actions::IncrementalActionExecutable;
+use buck2_build_api::actions::UnregisteredAction;
+use buck2_build_api::artifact_groups::ArtifactGroup;
+use buck2_core::category::Category;
+use buck2_execute::artifact::fs::ExecutorFs;
+use buck2_execute::artifact_value::ArtifactValue;
+use buck2_execute::execute::command_executor::ActionExecutionTimingData;
+use buck2_execute::materialize::materializer::WriteRequest;
+use buck2_execute::output_size::OutputSize;
+use dupe::Dupe;
+use indexmap::indexmap;
+use indexmap::IndexMap;
+use indexmap::IndexSet;
+use once_cell::sync::Lazy;
+use starlark::values::OwnedFrozenValue;
+
+#[derive(Debug, buck2_error::Error)]
+enum OutputsMetadataError {
+    #[error("OutputsMetadataAction received no inputs")]
+    NoInputs,
+    #[error("OutputsMetadataAction received no outputs")]
+    NoOutputs,
+    #[error("OutputsMetadataAction received more than one output")]
+    TooManyOutputs,
+}
+
+#[derive(Allocative, Debug)]
+pub(crate) struct UnregisteredOutputsMetadataAction;
+
+impl UnregisteredAction for UnregisteredOutputsMetadataAction {
+    fn register(
+        self: Box<Self>,
+        inputs: IndexSet<ArtifactGroup>,
+        outputs: IndexSet<BuildArtifact>,
+        _starlark_data: Option<OwnedFrozenValue>,
+        _error_handler: Option<OwnedFrozenValue>,
+    ) -> anyhow::Result<Box<dyn Action>> {
+        let mut outputs = outputs.into_iter();
+        let output = match (outputs.next(), outputs.next()) {
+            (Some(o), None) => o,
+            (None, ..) => return Err(OutputsMetadataError::NoOutputs.into()),
+            (Some(..), Some(..)) => return Err(OutputsMetadataError::TooManyOutputs.into()),
+        };
+        if inputs.is_empty() {
+            return Err(OutputsMetadataError::NoInputs.into());
+        }
+        Ok(Box::new(OutputsMetadataAction {
+            inputs: inputs.into_iter().collect(),
+            output,
+        }))
+    }
+}
+
+#[derive(Debug, Allocative)]
+struct OutputsMetadataAction {
+    /// The outputs of the run action.
+    inputs: Box<[ArtifactGroup]>,
+    output: BuildArtifact,
+}
+
+/// The metadata of outputs, by their short path:
+///
+/// ```json
+/// {
+///   "outputs": {
+///     "lib.so": {"size": 1234, "files": 1, "digest": "<hash>:1234"},
+///     "headers": {"size": 567, "files": 3, "digest": null}
+///   },
+///   "size": 1801
+/// }
+/// ```
+///
+/// Directories have no digest.
+fn contents<'a>(outputs: impl IntoIterator<Item = (String, &'a ArtifactValue)>) -> Vec<u8> {
+    let mut total_size = 0;
+    let mut metadata = serde_json::Map::new();
+    for (short_path, value) in outputs {
+        let size = value.calc_output_count_and_bytes();
+        total_size += size.bytes;
+        metadata.insert(
+            short_path,
+            serde_json::json!({
+                "size": size.bytes,
+                "files": size.count,
+                "digest": value.digest().map(|d| d.to_string()),
+            }),
+        );
+    }
+    let mut contents = serde_json::to_vec_pretty(&serde_json::json!({
+        "outputs": metadata,
+        "size": total_size,
+    }))
+    .unwrap();
+    contents.push(b'\n');
+    contents
+}
+
+#[async_trait]
+impl Action for OutputsMetadataAction {
+    fn kind(&self) -> buck2_data::ActionKind {
+        buck2_data::ActionKind::Write
+    }
+
+    fn inputs(&self) -> anyhow::Result<Cow<'_, [ArtifactGroup]>> {
+        Ok(Cow::Borrowed(&self.inputs))
+    }
+
+    fn outputs(&self) -> anyhow::Result<Cow<'_, [BuildArtifact]>> {
+        Ok(Cow::Borrowed(slice::from_ref(&self.output)))
+    }
+
+    fn as_executable(&self) -> ActionExecutable<'_> {
+        ActionExecutable::Incremental(self)
+    }
+
+    fn category(&self) -> &Category {
+        static OUTPUTS_METADATA_CATEGORY: Lazy<Category> =
+            Lazy::new(|| Category::try_from("outputs_metadata").unwrap());
+
+        &OUTPUTS_METADATA_CATEGORY
+    }
+
+    fn identifier(&self) -> Option<&str> {
+        Some(self.output.get_path().path().as_str())
+    }
+
+    fn aquery_attributes(&self, _fs: &ExecutorFs) -> IndexMap<String, String> {
+        indexmap! {}
+    }
+}
+
+#[async_trait]
+impl IncrementalActionExecutable for OutputsMetadataAction {
+    async fn execute(
+        &self,
+        ctx: &mut dyn ActionExecutionCtx,
+    ) -> Result<(ActionOutputs, ActionExecutionMetadata), ExecuteError> {
+        let execution_start = Instant::now();
+
+        let content = contents(self.inputs.iter().flat_map(|input| {
+            ctx.artifact_values(input).iter().map(|(artifact, value)| {
+                (
+                    artifact.get_path().with_short_path(|p| p.to_string()),
+                    value,
+                )
+            })
+        }));
+
+        let fs = ctx.fs();
+        let value = ctx
+            .materializer()
+            .declare_write(Box::new(|| {
+                Ok(vec![WriteRequest {
+                    path: fs.resolve_build(self.output.get_path()),
+                    content,
+                    is_executable: false,
+                }])
+            }))
+            .await?
+            .into_iter()
+            .next()
+            .context("Write did not execute")?;
+
+        Ok((
+            ActionOutputs::new(indexmap![self.output.get_path().dupe() => value]),
+            ActionExecutionMetadata {
+                execution_kind: ActionExecutionKind::Simple,
+                timing: ActionExecutionTimingData {
+                    wall_time: execution_start.elapsed(),
+                },
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_common::file_ops::FileMetadata;
+    use buck2_common::file_ops::TrackedFileDigest;
+    use buck2_execute::digest_config::DigestConfig;
+
+    use super::*;
+
+    #[test]
+    fn test_contents() -> anyhow::Result<()> {
+        let digest_config = DigestConfig::testing_default();
+        let file = ArtifactValue::file(FileMetadata {
+            digest: TrackedFileDigest::from_content(b"hello", digest_config.cas_digest_config()),
+            is_executable: false,
+        });
+        let metadata: serde_json::Value =
+            serde_json::from_slice(&contents([("out/hello.txt".to_owned(), &file)]))?;
+        assert_eq!(5, metadata["size"]);
+        assert_eq!(5, metadata["outputs"]["out/hello.txt"]["size"]);
+        assert_eq!(1, metadata["outputs"]["out/hello.txt"]["files"]);
+        assert_eq!(
+            serde_json::Value::String(file.digest().unwrap().to_string()),
+            metadata["outputs"]["out/hello.txt"]["digest"]
+        );
+        Ok(())
+    }
+}
@@ -10,6 +10,7 @@
 use std::borrow::Cow;
 use std::fmt::Display;
 use std::ops::ControlFlow;
+use std::time::Duration;
 
 use allocative::Allocative;
 use anyhow::Context;
@@ -161,6 +162,8 @@ pub(crate) struct UnregisteredRunAction {
     pub(crate) executor_preference: ExecutorPreference,
     pub(crate) always_print_stderr: bool,
     pub(crate) weight: WeightClass,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) env_inherit: Vec<String>,
     pub(crate) low_pass_filter: bool,
     pub(crate) dep_files: RunActionDepFiles,
     pub(crate) metadata_param: Option<MetadataParameter>,
@@ -583,23 +586,17 @@ impl Action for RunAction {
     }
 
     fn aquery_attributes(&self, fs: &ExecutorFs) -> indexmap::IndexMap<String, String> {
-        let mut cli_rendered = Vec::<String>::new();
-        let mut ctx = DefaultCommandLineContext::new(fs);
-        let values = Self::unpack(&self.starlark_values).unwrap();
-        values
-            .exe
-            .add_to_command_line(&mut cli_rendered, &mut ctx)
-            .unwrap();
-        values
-            .args
-            .add_to_command_line(&mut cli_rendered, &mut ctx)
-            .unwrap();
-        let cmd = format!("[{}]", cli_rendered.iter().join(", "));
+        let cmd = format!("[{}]", self.aquery_arguments(fs).iter().join(", "));
         indexmap! {
             "cmd".to_owned() => cmd,
             "executor_preference".to_owned() => self.inner.executor_preference.to_string(),
             "always_print_stderr".to_owned() => self.inner.always_print_stderr.to_string(),
             "weight".to_owned() => self.inner.weight.to_string(),
+            "timeout_s".to_owned() => match self.inner.timeout {
+                None => "None".to_owned(),
+                Some(timeout) => timeout.as_secs().to_string(),
+            },
+            "env_inherit".to_owned() => self.inner.env_inherit.join(", "),
             "dep_files".to_owned() => self.inner.dep_files.to_string(),
             "metadata_param".to_owned() => match &self.inner.metadata_param {
                 None => "None".to_owned(),
@@ -611,6 +608,21 @@ impl Action for RunAction {
         }
     }
 
+    fn aquery_arguments(&self, fs: &ExecutorFs) -> Vec<String> {
+        let mut cli_rendered = Vec::<String>::new();
+        let mut ctx = DefaultCommandLineContext::new(fs);
+        let values = Self::unpack(&self.starlark_values).unwrap();
+        values
+            .exe
+            .add_to_command_line(&mut cli_rendered, &mut ctx)
+            .unwrap();
+        values
+            .args
+            .add_to_command_line(&mut cli_rendered, &mut ctx)
+            .unwrap();
+        cli_rendered
+    }
+
     fn error_handler(&self) -> Option<OwnedFrozenValue> {
         self.error_handler.clone()
     }
@@ -639,16 +651,27 @@ impl IncrementalActionExecutable for RunAction {
         // Run actions are assumed to be shared
         let host_sharing_requirements = HostSharingRequirements::Shared(self.inner.weight);
 
+        let executor_preference = match ctx.category_executor_preference() {
+            Some(preference) => self.inner.executor_preference.and(preference.into())?,
+            None => self.inner.executor_preference,
+        };
+
         let req = prepared_run_action
             .into_command_execution_request()
             .with_prefetch_lossy_stderr(true)
-            .with_executor_preference(self.inner.executor_preference)
+            .with_executor_preference(executor_preference)
             .with_host_sharing_requirements(host_sharing_requirements)
             .with_low_pass_filter(self.inner.low_pass_filter)
             .with_outputs_cleanup(!self.inner.no_outputs_cleanup)
             .with_local_environment_inheritance(EnvironmentInheritance::local_command_exclusions())
             .with_force_full_hybrid_if_capable(self.inner.force_full_hybrid_if_capable)
-            .with_unique_input_inodes(self.inner.unique_input_inodes);
+            .with_unique_input_inodes(self.inner.unique_input_inodes)
+            .with_env_inherit(self.inner.env_inherit.clone())
+            .with_perturb_environment(knobs.perturb_environment);
+        let req = match self.inner.timeout.or_else(|| ctx.default_timeout()) {
+            Some(timeout) => req.with_timeout(timeout),
+            None => req,
+        };
 
         let (mut dep_file_bundle, req) = if let Some(visitor) = dep_file_visitor {
             let bundle = make_dep_file_bundle(ctx, visitor, cmdline_digest, req.paths())?;
@@ -662,7 +685,9 @@ impl IncrementalActionExecutable for RunAction {
         // First, check in the local dep file cache if an identical action can be found there.
         // Do this before checking the action cache as we can avoid a potentially large download.
         // Once the action cache lookup misses, we will do the full dep file cache look up.
-        let should_fully_check_dep_file_cache = if let Some(dep_file_bundle) = &dep_file_bundle {
+        let should_fully_check_dep_file_cache = if let Some(dep_file_bundle) =
+            dep_file_bundle.as_ref().filter(|_| !knobs.force_execution)
+        {
             let (outputs, should_fully_check_dep_file_cache) = dep_file_bundle
                 .check_local_dep_file_cache_for_identical_action(ctx, self.outputs.as_slice())
                 .await?;
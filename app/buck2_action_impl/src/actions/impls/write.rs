@@ -8,6 +8,9 @@
  */
 
 use std::borrow::Cow;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
 use std::slice;
 use std::time::Instant;
 
@@ -28,9 +31,19 @@ use buck2_build_api::actions::UnregisteredAction;
 use buck2_build_api::artifact_groups::ArtifactGroup;
 use buck2_build_api::interpreter::rule_defs::cmd_args::value_as::ValueAsCommandLineLike;
 use buck2_build_api::interpreter::rule_defs::cmd_args::AbsCommandLineContext;
+use buck2_build_api::interpreter::rule_defs::cmd_args::CommandLineBuilder;
 use buck2_build_api::interpreter::rule_defs::cmd_args::DefaultCommandLineContext;
+use buck2_common::cas_digest::Digester;
+use buck2_common::file_ops::FileDigest;
+use buck2_common::file_ops::FileDigestKind;
+use buck2_common::file_ops::FileMetadata;
+use buck2_common::file_ops::TrackedFileDigest;
 use buck2_core::category::Category;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
 use buck2_execute::artifact::fs::ExecutorFs;
+use buck2_execute::artifact_value::ArtifactValue;
+use buck2_execute::digest_config::DigestConfig;
 use buck2_execute::execute::command_executor::ActionExecutionTimingData;
 use buck2_execute::materialize::materializer::WriteRequest;
 use dupe::Dupe;
@@ -117,7 +130,15 @@ impl WriteAction {
 
     fn get_contents(&self, fs: &ExecutorFs) -> anyhow::Result<String> {
         let mut cli = Vec::<String>::new();
+        self.add_contents(fs, &mut cli)?;
+        Ok(cli.join("\n"))
+    }
 
+    fn add_contents(
+        &self,
+        fs: &ExecutorFs,
+        cli: &mut dyn CommandLineBuilder,
+    ) -> anyhow::Result<()> {
         let mut ctx = if let Some(macro_files) = &self.inner.macro_files {
             DefaultCommandLineContext::new_with_write_to_file_macros_support(fs, macro_files)
         } else {
@@ -136,9 +157,121 @@ impl WriteAction {
         ValueAsCommandLineLike::unpack_value_err(self.contents.value())
             .unwrap()
             .0
-            .add_to_command_line(&mut cli, ctx)?;
+            .add_to_command_line(cli, ctx)?;
 
-        Ok(cli.join("\n"))
+        Ok(())
+    }
+
+    /// Generate the contents, in memory up to `max_in_memory` bytes and streamed to `path` past
+    /// that.
+    fn write_contents(
+        &self,
+        fs: &ExecutorFs,
+        path: &AbsNormPath,
+        max_in_memory: u64,
+        digest_config: DigestConfig,
+    ) -> anyhow::Result<WrittenContents> {
+        let mut writer = ContentsWriter::new(path, max_in_memory, digest_config);
+        self.add_contents(fs, &mut writer)?;
+        writer.finish(self.inner.is_executable)
+    }
+}
+
+enum WrittenContents {
+    InMemory(Vec<u8>),
+    OnDisk(FileMetadata),
+}
+
+/// Joins the arguments of the contents with newlines, like `get_contents`, switching from a
+/// buffer to the output file once the contents are larger than `max_in_memory`, so that very
+/// large contents (e.g. link argsfiles from a tset projection) are never collected in memory.
+struct ContentsWriter<'a> {
+    path: &'a AbsNormPath,
+    max_in_memory: u64,
+    buffer: Vec<u8>,
+    file: Option<BufWriter<File>>,
+    digester: Digester<FileDigestKind>,
+    digest_config: DigestConfig,
+    first: bool,
+    error: Option<anyhow::Error>,
+}
+
+impl<'a> ContentsWriter<'a> {
+    fn new(path: &'a AbsNormPath, max_in_memory: u64, digest_config: DigestConfig) -> Self {
+        Self {
+            path,
+            max_in_memory,
+            buffer: Vec::new(),
+            file: None,
+            digester: FileDigest::digester(digest_config.cas_digest_config()),
+            digest_config,
+            first: true,
+            error: None,
+        }
+    }
+
+    fn finish(self, is_executable: bool) -> anyhow::Result<WrittenContents> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        match self.file {
+            None => Ok(WrittenContents::InMemory(self.buffer)),
+            Some(mut file) => {
+                file.flush()
+                    .with_context(|| format!("Error writing `{}`", self.path))?;
+                drop(file);
+                if is_executable {
+                    fs_util::set_executable(self.path)?;
+                }
+                Ok(WrittenContents::OnDisk(FileMetadata {
+                    digest: TrackedFileDigest::new(
+                        self.digester.finalize(),
+                        self.digest_config.cas_digest_config(),
+                    ),
+                    is_executable,
+                }))
+            }
+        }
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        self.digester.update(bytes);
+        if self.file.is_none() && (self.buffer.len() + bytes.len()) as u64 <= self.max_in_memory {
+            self.buffer.extend_from_slice(bytes);
+            return Ok(());
+        }
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => {
+                if let Some(parent) = self.path.parent() {
+                    fs_util::create_dir_all(parent)?;
+                }
+                let mut file = BufWriter::new(
+                    File::create(self.path)
+                        .with_context(|| format!("Error creating `{}`", self.path))?,
+                );
+                file.write_all(&std::mem::take(&mut self.buffer))?;
+                self.file.insert(file)
+            }
+        };
+        file.write_all(bytes)
+            .with_context(|| format!("Error writing `{}`", self.path))
+    }
+}
+
+impl CommandLineBuilder for ContentsWriter<'_> {
+    fn push_arg(&mut self, s: String) {
+        if self.error.is_some() {
+            return;
+        }
+        let res = if std::mem::take(&mut self.first) {
+            self.write(s.as_bytes())
+        } else {
+            self.write(b"\n").and_then(|()| self.write(s.as_bytes()))
+        };
+        if let Err(e) = res {
+            self.error = Some(e);
+        }
     }
 }
 
@@ -188,6 +321,10 @@ impl IncrementalActionExecutable for WriteAction {
         &self,
         ctx: &mut dyn ActionExecutionCtx,
     ) -> Result<(ActionOutputs, ActionExecutionMetadata), ExecuteError> {
+        if let Some(max_in_memory) = ctx.run_action_knobs().write_action_max_in_memory_bytes {
+            return self.execute_streaming(ctx, max_in_memory).await;
+        }
+
         let fs = ctx.fs();
 
         let mut execution_start = None;
@@ -222,10 +359,109 @@ impl IncrementalActionExecutable for WriteAction {
     }
 }
 
+impl WriteAction {
+    async fn execute_streaming(
+        &self,
+        ctx: &mut dyn ActionExecutionCtx,
+        max_in_memory: u64,
+    ) -> Result<(ActionOutputs, ActionExecutionMetadata), ExecuteError> {
+        let execution_start = Instant::now();
+
+        // The contents may be written to the output directly, so clean it up first.
+        ctx.cleanup_outputs().await?;
+
+        let fs = ctx.fs();
+        let path = fs.resolve_build(self.output.get_path());
+        let abs_path = fs.fs().resolve(&path);
+        let executor_fs = ctx.executor_fs();
+        let digest_config = ctx.digest_config();
+        let contents = ctx
+            .blocking_executor()
+            .execute_io_inline(|| {
+                self.write_contents(&executor_fs, &abs_path, max_in_memory, digest_config)
+            })
+            .await?;
+
+        let value = match contents {
+            WrittenContents::InMemory(content) => ctx
+                .materializer()
+                .declare_write(Box::new(|| {
+                    Ok(vec![WriteRequest {
+                        path,
+                        content,
+                        is_executable: self.inner.is_executable,
+                    }])
+                }))
+                .await?
+                .into_iter()
+                .next()
+                .context("Write did not execute")?,
+            WrittenContents::OnDisk(metadata) => {
+                let value = ArtifactValue::file(metadata);
+                ctx.materializer()
+                    .declare_existing(vec![(path, value.dupe())])
+                    .await?;
+                value
+            }
+        };
+
+        Ok((
+            ActionOutputs::new(indexmap![self.output.get_path().dupe() => value]),
+            ActionExecutionMetadata {
+                execution_kind: ActionExecutionKind::Simple,
+                timing: ActionExecutionTimingData {
+                    wall_time: execution_start.elapsed(),
+                },
+            },
+        ))
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+
+    use super::*;
+
     // TODO: This needs proper tests, but right now it's kind of a pain to get the
     //       action framework up and running to test actions
     #[test]
     fn writes_file() {}
+
+    fn write_args(path: &AbsNormPath, max_in_memory: u64) -> WrittenContents {
+        let mut writer = ContentsWriter::new(path, max_in_memory, DigestConfig::testing_default());
+        for arg in ["first", "", "second argument", "third"] {
+            writer.push_arg(arg.to_owned());
+        }
+        writer.finish(false).unwrap()
+    }
+
+    #[test]
+    fn test_streamed_contents_match_in_memory() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = AbsNormPathBuf::try_from(tempdir.path().to_owned()).unwrap();
+
+        let in_memory = match write_args(&root.join("in_memory"), 1024) {
+            WrittenContents::InMemory(content) => content,
+            WrittenContents::OnDisk(_) => panic!("Expected the contents to be in memory"),
+        };
+        assert_eq!(b"first\n\nsecond argument\nthird", in_memory.as_slice());
+        assert!(!fs_util::try_exists(root.join("in_memory")).unwrap());
+
+        // Past the threshold in the middle of an argument.
+        let streamed_path = root.join("dir/streamed");
+        let metadata = match write_args(&streamed_path, 10) {
+            WrittenContents::OnDisk(metadata) => metadata,
+            WrittenContents::InMemory(_) => panic!("Expected the contents to be streamed"),
+        };
+        assert_eq!(in_memory, fs_util::read(&streamed_path).unwrap());
+        assert_eq!(
+            &FileDigest::from_content(
+                &in_memory,
+                DigestConfig::testing_default().cas_digest_config()
+            ),
+            metadata.digest.data()
+        );
+        assert!(!metadata.is_executable);
+    }
 }
@@ -9,6 +9,7 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Context;
 use buck2_artifact::artifact::artifact_type::OutputArtifact;
@@ -78,12 +79,19 @@ use starlark_map::small_map;
 use starlark_map::small_map::SmallMap;
 use starlark_map::small_set::SmallSet;
 
+use crate::actions::impls::archive::ArchiveFormat;
+use crate::actions::impls::archive::UnregisteredArchiveAction;
+use crate::actions::impls::archive::UnregisteredUnarchiveAction;
+use crate::actions::impls::build_info::UnregisteredBuildInfoAction;
+use crate::actions::impls::build_info::VolatileKey;
 use crate::actions::impls::cas_artifact::ArtifactKind;
 use crate::actions::impls::cas_artifact::DirectoryKind;
 use crate::actions::impls::cas_artifact::UnregisteredCasArtifactAction;
 use crate::actions::impls::copy::CopyMode;
 use crate::actions::impls::copy::UnregisteredCopyAction;
+use crate::actions::impls::digest_tree::UnregisteredDigestTreeAction;
 use crate::actions::impls::download_file::UnregisteredDownloadFileAction;
+use crate::actions::impls::outputs_metadata::UnregisteredOutputsMetadataAction;
 use crate::actions::impls::run::dep_files::RunActionDepFiles;
 use crate::actions::impls::run::new_executor_preference;
 use crate::actions::impls::run::MetadataParameter;
@@ -118,6 +126,8 @@ enum RunActionError {
     InvalidWeight(i32),
     #[error("`weight` and `weight_percentage` cannot both be passed")]
     DuplicateWeightsSpecified,
+    #[error("`timeout_s` must be a positive integer, got `{0}`")]
+    InvalidTimeout(i32),
     #[error("`dep_files` value with key `{}` has an invalid count of associated outputs. Expected 1, got {}.", .key, .count)]
     InvalidDepFileOutputs { key: String, count: usize },
     #[error("`dep_files` with keys `{}` and {} are using the same tag", .first, .second)]
@@ -325,6 +335,11 @@ fn analysis_actions_methods_actions(builder: &mut MethodsBuilder) {
     /// The content is often a string, but can be any `ArgLike` value. This is occasionally useful
     /// for generating scripts to run as a part of another action. `cmd_args` in the content are
     /// newline separated unless another delimiter is explicitly specified.
+    ///
+    /// Very large contents (e.g. link argsfiles) are best passed as a transitive set projection,
+    /// which is only expanded when the action runs. With `build.write_action_max_in_memory_bytes`
+    /// set, contents larger than that are streamed to the output file rather than collected in
+    /// memory first.
     fn write<'v>(
         this: &AnalysisActions<'v>,
         #[starlark(require = pos)] output: OutputArtifactArg<'v>,
@@ -602,6 +617,136 @@ fn analysis_actions_methods_actions(builder: &mut MethodsBuilder) {
         create_dir_tree(eval, this, output, srcs, true)
     }
 
+    /// Returns an `artifact` which is an archive of `srcs`, a dictionary of path in the archive
+    /// (as string, the empty string putting the contents of a directory at the root) to bound
+    /// `artifact`.
+    ///
+    /// * `format`: one of `tar`, `tar.gz`, `tar.zst` or `zip`. Defaults to the format of the
+    ///   extension of `output`
+    /// * `compression_level`: 0 to 9 for `tar.gz` (defaults to 6), 1 to 22 for `tar.zst` (defaults
+    ///   to 3), and 0 (stored) to 9 for `zip` (defaults to deflated with level 6)
+    ///
+    /// The archive is created by Buck2 rather than by a host tool, and only depends on the
+    /// contents of `srcs`: entries are sorted, directories are archived with their contents,
+    /// symlinks are followed, and entries have no owner, a fixed timestamp and a mode of `0755`
+    /// for directories and executables and `0644` otherwise.
+    fn archive<'v>(
+        this: &AnalysisActions<'v>,
+        #[starlark(require = pos)] output: OutputArtifactArg<'v>,
+        #[starlark(require = pos)] srcs: DictOf<'v, &'v str, ValueAsArtifactLike<'v>>,
+        #[starlark(require = named)] format: Option<&str>,
+        #[starlark(require = named)] compression_level: Option<i32>,
+        eval: &mut Evaluator<'v, '_>,
+    ) -> anyhow::Result<ValueTyped<'v, StarlarkDeclaredArtifact>> {
+        let srcs = srcs
+            .collect_entries()
+            .into_iter()
+            .map(|(k, v)| {
+                anyhow::Ok((
+                    v.0.get_artifact_group()?,
+                    ForwardRelativePathBuf::try_from(k.to_owned())
+                        .context("dict key must be a forward relative path")?,
+                ))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let mut this = this.state();
+        let (declaration, output_artifact) =
+            this.get_or_declare_output(eval, output, OutputType::File)?;
+        let format = output_artifact
+            .get_path()
+            .with_short_path(|p| ArchiveFormat::new(format, p.as_str()))?;
+        let action = UnregisteredArchiveAction::new(format, compression_level, srcs)?;
+        this.register_action(
+            action.inputs(),
+            indexset![output_artifact],
+            action,
+            None,
+            None,
+        )?;
+
+        Ok(declaration.into_declared_artifact(AssociatedArtifacts::new()))
+    }
+
+    /// Returns an `artifact` which is a directory containing the files extracted from the archive
+    /// `src`.
+    ///
+    /// * `format`: one of `tar`, `tar.gz`, `tar.zst` or `zip`. Defaults to the format of the
+    ///   extension of `src`
+    /// * `strip_components`: the number of leading path components removed from entries, like
+    ///   `tar --strip-components`. Entries with fewer components are skipped
+    ///
+    /// Entries with an absolute path or a path containing `..` are an error. Files are only
+    /// extracted with their executable bit, not their owner, timestamp nor other permissions.
+    fn unarchive<'v>(
+        this: &AnalysisActions<'v>,
+        #[starlark(require = pos)] output: OutputArtifactArg<'v>,
+        #[starlark(require = pos)] src: ValueAsArtifactLike<'v>,
+        #[starlark(require = named)] format: Option<&str>,
+        #[starlark(require = named, default = 0)] strip_components: u32,
+        eval: &mut Evaluator<'v, '_>,
+    ) -> anyhow::Result<ValueTyped<'v, StarlarkDeclaredArtifact>> {
+        let format = match format {
+            Some(_) => ArchiveFormat::new(format, "")?,
+            None => src
+                .0
+                .get_bound_artifact()?
+                .get_path()
+                .with_short_path(|p| ArchiveFormat::new(None, p.as_str()))?,
+        };
+        let input = src.0.get_artifact_group()?;
+
+        let mut this = this.state();
+        let (declaration, output_artifact) =
+            this.get_or_declare_output(eval, output, OutputType::Directory)?;
+        this.register_action(
+            indexset![input],
+            indexset![output_artifact],
+            UnregisteredUnarchiveAction::new(format, strip_components),
+            None,
+            None,
+        )?;
+
+        Ok(declaration.into_declared_artifact(AssociatedArtifacts::new()))
+    }
+
+    /// Returns an `artifact` which is a JSON manifest of the directory `src`, giving the type of
+    /// each entry by path relative to `src`, along with the digest, size and mode (`0755` or
+    /// `0644`) of files and the target of symlinks:
+    ///
+    /// ```json
+    /// {
+    ///   "entries": {
+    ///     "bin": {"type": "directory"},
+    ///     "bin/tool": {"type": "file", "digest": "<hash>", "size": 1234, "mode": "0755"},
+    ///     "tool": {"type": "symlink", "target": "bin/tool"}
+    ///   }
+    /// }
+    /// ```
+    ///
+    /// The manifest is computed from the digests Buck2 already has, without materializing `src`,
+    /// so incremental tools can diff two versions of a tree without hashing it again.
+    fn digest_tree<'v>(
+        this: &AnalysisActions<'v>,
+        #[starlark(require = pos)] output: OutputArtifactArg<'v>,
+        #[starlark(require = pos)] src: ValueAsArtifactLike<'v>,
+        eval: &mut Evaluator<'v, '_>,
+    ) -> anyhow::Result<ValueTyped<'v, StarlarkDeclaredArtifact>> {
+        let input = src.0.get_artifact_group()?;
+        let mut this = this.state();
+        let (declaration, output_artifact) =
+            this.get_or_declare_output(eval, output, OutputType::File)?;
+        this.register_action(
+            indexset![input],
+            indexset![output_artifact],
+            UnregisteredDigestTreeAction,
+            None,
+            None,
+        )?;
+
+        Ok(declaration.into_declared_artifact(AssociatedArtifacts::new()))
+    }
+
     /// Runs a command
     ///
     /// * `arguments`: must be of type `cmd_args`, or a type convertible to such (such as a list of
@@ -610,6 +755,11 @@ fn analysis_actions_methods_actions(builder: &mut MethodsBuilder) {
     ///   event stream, and must be unique for a given target
     /// * `weight`: used to note how heavy the command is and will typically be set to a higher
     ///   value to indicate that less such commands should be run in parallel (if running locally)
+    /// * `timeout_s`: how long in seconds the command may run before it is killed (if running
+    ///   locally) or its remote execution is cancelled, failing the action with a timeout error.
+    ///   Defaults to the timeout of the category in `default_timeouts_s` of the executor config
+    /// * `env_inherit`: environment variables of the daemon which the command may inherit when
+    ///   `build.action_env_policy` is `hermetic`, in addition to `build.action_env_allowlist`
     /// * `no_outputs_cleanup`: if this flag is set then Buck2 won't clean the outputs of a previous
     ///   build that might be present on a disk; in which case, command from arguments should be
     ///   responsible for the cleanup (that is useful, for example, when an action is supporting
@@ -636,8 +786,15 @@ fn analysis_actions_methods_actions(builder: &mut MethodsBuilder) {
     ///     * Setting more than one of those options is an error.
     ///     * Those flags behave the same way as the equivalent `--prefer-remote`, `--prefer-local`
     ///     and `--local-only` CLI flags. The CLI flags take precedence.
+    ///     * They are combined with the `executor_preferences` of the executor config for the
+    ///     category of the action, and take precedence over its preferences (but not requirements).
     ///     * The `force_full_hybrid_if_capable` option overrides the `use_limited_hybrid` hybrid.
     ///     The options listed above take precedence if set.
+    /// * `outputs_metadata`: if set, `run` returns a JSON artifact with the size, file count and
+    ///   digest of each output, by short path, and their total `size`. It is produced by a
+    ///   separate action which doesn't materialize the outputs, so downstream rules can read it
+    ///   with `dynamic_output` (e.g. to check for size regressions) without depending on the
+    ///   outputs themselves. Otherwise `run` returns `None`.
     ///
     /// When actions execute, they'll do so from the root of the repository. As they execute,
     /// actions have exclusive access to their output directory.
@@ -661,6 +818,9 @@ fn analysis_actions_methods_actions(builder: &mut MethodsBuilder) {
         #[starlark(require = named, default = false)] always_print_stderr: bool,
         #[starlark(require = named)] weight: Option<i32>,
         #[starlark(require = named)] weight_percentage: Option<i32>,
+        #[starlark(require = named)] timeout_s: Option<i32>,
+        #[starlark(require = named, default = UnpackListOrTuple::default())]
+        env_inherit: UnpackListOrTuple<String>,
         #[starlark(require = named)] dep_files: Option<SmallMap<&'v str, &'v ArtifactTag>>,
         #[starlark(require = named)] metadata_env_var: Option<String>,
         #[starlark(require = named)] metadata_path: Option<String>,
@@ -674,8 +834,9 @@ fn analysis_actions_methods_actions(builder: &mut MethodsBuilder) {
         >,
         #[starlark(require = named, default = false)] unique_input_inodes: bool,
         #[starlark(require = named)] error_handler: Option<StarlarkCallable<'v>>,
+        #[starlark(require = named, default = false)] outputs_metadata: bool,
         eval: &mut Evaluator<'v, '_>,
-    ) -> anyhow::Result<NoneType> {
+    ) -> anyhow::Result<NoneOr<StarlarkDeclaredArtifact>> {
         struct RunCommandArtifactVisitor {
             inner: SimpleCommandLineArtifactVisitor,
             tagged_outputs: HashMap<ArtifactTag, Vec<OutputArtifact>>,
@@ -765,6 +926,12 @@ fn analysis_actions_methods_actions(builder: &mut MethodsBuilder) {
             }
         };
 
+        let timeout = match timeout_s {
+            None => None,
+            Some(v) if v > 0 => Some(Duration::from_secs(v as u64)),
+            Some(v) => return Err(RunActionError::InvalidTimeout(v).into()),
+        };
+
         let starlark_env = match env {
             None => Value::new_none(),
             Some(env) => {
@@ -839,12 +1006,25 @@ fn analysis_actions_methods_actions(builder: &mut MethodsBuilder) {
             worker: heap.alloc(starlark_worker),
         });
 
+        // The metadata is named after the action, which is unique within the target.
+        let outputs_metadata_filename = match &identifier {
+            Some(identifier) => format!("{}/{}.json", category, identifier),
+            None => format!("{}.json", category),
+        };
+        let run_outputs = if outputs_metadata {
+            Some(artifacts.outputs.clone())
+        } else {
+            None
+        };
+
         let action = UnregisteredRunAction {
             category,
             identifier,
             executor_preference,
             always_print_stderr,
             weight,
+            timeout,
+            env_inherit: env_inherit.items,
             low_pass_filter,
             dep_files: dep_files_configuration,
             metadata_param,
@@ -861,7 +1041,79 @@ fn analysis_actions_methods_actions(builder: &mut MethodsBuilder) {
             Some(starlark_values),
             error_handler,
         )?;
-        Ok(NoneType)
+
+        let Some(run_outputs) = run_outputs else {
+            return Ok(NoneOr::None);
+        };
+        let mut this = this.state();
+        let declaration_location = eval.call_stack_top_location();
+        let metadata = this.declare_output(
+            Some("__outputs_metadata__"),
+            &outputs_metadata_filename,
+            OutputType::File,
+            declaration_location.dupe(),
+        )?;
+        let inputs = run_outputs
+            .into_iter()
+            .map(|output| {
+                Ok(ArtifactGroup::Artifact(
+                    (*output).dupe().ensure_bound()?.into_artifact(),
+                ))
+            })
+            .collect::<anyhow::Result<IndexSet<_>>>()?;
+        this.register_action(
+            inputs,
+            indexset![metadata.as_output()],
+            UnregisteredOutputsMetadataAction,
+            None,
+            None,
+        )?;
+        Ok(NoneOr::Other(StarlarkDeclaredArtifact::new(
+            declaration_location,
+            metadata,
+            AssociatedArtifacts::new(),
+        )))
+    }
+
+    /// Returns an `artifact` stamping the build, as a JSON object of `stable` and `volatile` keys
+    ///
+    /// * `stable`: keys and values which are part of the action, e.g. the release version
+    /// * `volatile`: keys computed when the action runs: `timestamp` (seconds since the epoch)
+    ///   and `revision` (the revision the working copy is based on, or `null` if unknown)
+    ///
+    /// The action only runs again when its stable keys change: a new timestamp or revision alone
+    /// doesn't invalidate it or the actions using it, so volatile keys are as of the last time it
+    /// ran. This replaces non-hermetic rules which stamp with the current time or revision.
+    ///
+    /// The volatile values are part of the output's digest, so each time the action runs, all the
+    /// actions using the output run again too: only pass it to the actions which need it.
+    fn build_info<'v>(
+        this: &AnalysisActions<'v>,
+        #[starlark(require = pos)] output: OutputArtifactArg<'v>,
+        #[starlark(require = named, default = SmallMap::new())] stable: SmallMap<String, String>,
+        #[starlark(require = named, default = UnpackListOrTuple::default())]
+        volatile: UnpackListOrTuple<String>,
+        eval: &mut Evaluator<'v, '_>,
+    ) -> anyhow::Result<ValueTyped<'v, StarlarkDeclaredArtifact>> {
+        let mut this = this.state();
+        let (declaration, output_artifact) =
+            this.get_or_declare_output(eval, output, OutputType::File)?;
+
+        let volatile = volatile
+            .items
+            .iter()
+            .map(|key| key.parse::<VolatileKey>())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        this.register_action(
+            IndexSet::new(),
+            indexset![output_artifact],
+            UnregisteredBuildInfoAction::new(stable.into_iter().collect(), volatile)?,
+            None,
+            None,
+        )?;
+
+        Ok(declaration.into_declared_artifact(AssociatedArtifacts::new()))
     }
 
     /// Downloads a URL to an output (filename as string or output artifact). The file at the URL
@@ -869,11 +1121,16 @@ fn analysis_actions_methods_actions(builder: &mut MethodsBuilder) {
     /// indicates whether the resulting file should be marked with executable permissions.
     /// (Meta-internal) The optional parameter vpnless_url indicates a url from which this resource
     /// can be downloaded off VPN; this has the same restrictions as `url` above.
+    /// The optional parameter mirrors lists other urls serving the same file, tried in order when
+    /// downloading from `url` fails (e.g. because it's down or its host is not in
+    /// `http.allowed_hosts`).
     fn download_file<'v>(
         this: &AnalysisActions<'v>,
         #[starlark(require = pos)] output: OutputArtifactArg<'v>,
         #[starlark(require = pos)] url: &str,
         #[starlark(require = named, default = NoneOr::None)] vpnless_url: NoneOr<&str>,
+        #[starlark(require = named, default = UnpackListOrTuple::default())]
+        mirrors: UnpackListOrTuple<&str>,
         #[starlark(require = named, default = NoneOr::None)] sha1: NoneOr<&str>,
         #[starlark(require = named, default = NoneOr::None)] sha256: NoneOr<&str>,
         #[starlark(require = named, default = false)] is_executable: bool,
@@ -892,6 +1149,7 @@ fn analysis_actions_methods_actions(builder: &mut MethodsBuilder) {
             UnregisteredDownloadFileAction::new(
                 checksum,
                 Arc::from(url),
+                mirrors.items.into_iter().map(Arc::from).collect(),
                 vpnless_url.into_option().map(Arc::from),
                 is_executable,
                 is_deferrable,
@@ -217,3 +217,39 @@ fn declare_output_require_bound() -> anyhow::Result<()> {
         ),
     })
 }
+
+#[test]
+fn run_timeout_s() -> anyhow::Result<()> {
+    let content = indoc!(
+        r#"
+         def test(c):
+             a = c.actions.declare_output("a")
+             c.actions.run([a.as_output()], category = "test_category", timeout_s = 30)
+         "#
+    );
+
+    run_ctx_test(content, |ret| {
+        ret.unwrap();
+        Ok(())
+    })
+}
+
+#[test]
+fn run_timeout_s_must_be_positive() -> anyhow::Result<()> {
+    let content = indoc!(
+        r#"
+         def test(c):
+             a = c.actions.declare_output("a")
+             c.actions.run([a.as_output()], category = "test_category", timeout_s = 0)
+         "#
+    );
+
+    let expect = "`timeout_s` must be a positive integer, got `0`";
+    run_ctx_test(content, |ret| match ret {
+        Err(e) if e.to_string().contains(expect) => Ok(()),
+        _ => panic!(
+            "Expected a specific failure containing `{}`, got {:?}",
+            expect, ret
+        ),
+    })
+}
@@ -53,6 +53,8 @@ use futures::StreamExt;
 use smallvec::SmallVec;
 use starlark::eval::ProfileMode;
 
+use crate::analysis::cutoff::get_dep_providers;
+use crate::analysis::cutoff::get_target_providers;
 use crate::analysis::env::get_user_defined_rule_spec;
 use crate::analysis::env::run_analysis;
 use crate::analysis::env::RuleSpec;
@@ -180,10 +182,9 @@ async fn resolve_queries_impl(
                         let label = node.label();
                         query_results.push((
                             label.dupe(),
-                            ctx.get_analysis_result(label)
+                            get_target_providers(ctx, label)
                                 .await?
-                                .require_compatible()?
-                                .provider_collection,
+                                .require_compatible()?,
                         ))
                     }
 
@@ -260,7 +261,11 @@ async fn get_analysis_result_inner(
     };
 
     let configured_node = configured_node.as_ref();
-    let mut dep_analysis = get_dep_analysis(configured_node, ctx).await?;
+    let dep_providers = match configured_node.rule_type() {
+        RuleType::Starlark(_) => get_dep_providers(configured_node, ctx).await?,
+        // Forward nodes return the analysis result of their dep.
+        RuleType::Forward => Vec::new(),
+    };
 
     let now = Instant::now();
 
@@ -291,7 +296,7 @@ async fn get_analysis_result_inner(
                                     run_analysis(
                                         ctx,
                                         target,
-                                        dep_analysis,
+                                        dep_providers,
                                         query_results,
                                         configured_node.execution_platform_resolution(),
                                         &rule_spec,
@@ -330,6 +335,7 @@ async fn get_analysis_result_inner(
                         );
                     }
                 }
+                let mut dep_analysis = get_dep_analysis(configured_node, ctx).await?;
                 assert!(dep_analysis.len() == 1);
                 Ok(MaybeCompatible::Compatible(dep_analysis.pop().unwrap().1))
             }
@@ -391,8 +397,10 @@ pub async fn profile_analysis(
     })
 }
 
-fn all_deps(node: ConfiguredTargetNode) -> LabelIndexedSet<ConfiguredTargetNode> {
-    let mut stack = vec![node];
+fn all_deps(
+    nodes: impl IntoIterator<Item = ConfiguredTargetNode>,
+) -> LabelIndexedSet<ConfiguredTargetNode> {
+    let mut stack: Vec<_> = nodes.into_iter().collect();
     let mut visited = LabelIndexedSet::new();
     while let Some(node) = stack.pop() {
         if visited.insert(node.dupe()) {
@@ -402,9 +410,11 @@ fn all_deps(node: ConfiguredTargetNode) -> LabelIndexedSet<ConfiguredTargetNode>
     visited
 }
 
+/// Profile the analysis of `targets` and all of their transitive dependencies, returning the
+/// merged profile. Targets shared between several roots are only counted once.
 pub async fn profile_analysis_recursively(
     ctx: &mut DiceComputations<'_>,
-    target: &ConfiguredTargetLabel,
+    targets: &[ConfiguredTargetLabel],
 ) -> anyhow::Result<StarlarkProfileDataAndStats> {
     // Self check.
     let profile_mode = ctx.get_profile_mode_for_intermediate_analysis().await?;
@@ -415,12 +425,18 @@ pub async fn profile_analysis_recursively(
         return Err(ProfileAnalysisError::RecursiveProfileConfiguredIncorrectly.into());
     }
 
-    let node = ctx
-        .get_configured_target_node(target)
-        .await?
-        .require_compatible()?;
+    let nodes = ctx
+        .try_compute_join(targets, |ctx, target| {
+            async move {
+                ctx.get_configured_target_node(target)
+                    .await?
+                    .require_compatible()
+            }
+            .boxed()
+        })
+        .await?;
 
-    let all_deps = all_deps(node);
+    let all_deps = all_deps(nodes);
 
     let ctx_ref = &*ctx;
     let mut futures = all_deps
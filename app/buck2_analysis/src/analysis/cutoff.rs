@@ -0,0 +1,214 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! The providers of a target, as seen by the analysis of the targets depending on it.
+//!
+//! Analysis results never compare equal, since they also hold the actions of the target. Targets
+//! only see the providers of their deps though, so they depend on [`AnalysisProvidersKey`], whose
+//! values compare equal when the providers can't be told apart (see
+//! [`FrozenProviderCollectionValue::is_indistinguishable_from`]). A change to a target which is
+//! not reflected in its providers, e.g. to the flags of one of its actions, then re-analyzes the
+//! target, but not the targets depending on it.
+//!
+//! With `BUCK2_VALIDATE_ANALYSIS_CUTOFF=true`, this never stops re-analysis. Instead, when a target
+//! is analyzed again with the same node, rule and indistinguishable deps as before, and its
+//! providers change, the cutoff would have kept stale providers, which is reported with the soft
+//! error `analysis_cutoff_mismatch`.
+
+use std::sync::Arc;
+
+use allocative::Allocative;
+use async_trait::async_trait;
+use buck2_build_api::analysis::calculation::RuleAnalysisCalculation;
+use buck2_build_api::interpreter::rule_defs::provider::collection::FrozenProviderCollectionValue;
+use buck2_build_api::keep_going;
+use buck2_core::buck2_env;
+use buck2_core::configuration::compatibility::MaybeCompatible;
+use buck2_core::soft_error;
+use buck2_core::target::configured_target_label::ConfiguredTargetLabel;
+use buck2_interpreter::file_loader::LoadedModule;
+use buck2_interpreter::load_module::InterpreterCalculation;
+use buck2_node::nodes::configured::ConfiguredTargetNode;
+use buck2_node::nodes::configured::ConfiguredTargetNodeRef;
+use buck2_node::nodes::configured_frontend::ConfiguredTargetNodeCalculation;
+use buck2_node::rule_type::RuleType;
+use dice::CancellationContext;
+use dice::DiceComputations;
+use dice::Key;
+use dupe::Dupe;
+use futures::stream::FuturesUnordered;
+
+#[derive(Debug, buck2_error::Error)]
+enum AnalysisCutoffError {
+    #[error(
+        "The providers of `{0}` changed although its node, its rule and the providers of its deps \
+        did not, so the analysis cutoff would have kept stale providers"
+    )]
+    Mismatch(ConfiguredTargetLabel),
+}
+
+#[derive(
+    Clone,
+    Dupe,
+    derive_more::Display,
+    Debug,
+    Eq,
+    Hash,
+    PartialEq,
+    Allocative
+)]
+#[display(fmt = "{}", "_0")]
+struct AnalysisProvidersKey(ConfiguredTargetLabel);
+
+#[derive(Clone, Dupe, Allocative)]
+struct AnalysisProviders {
+    providers: FrozenProviderCollectionValue,
+    /// What the analysis of the target depends on, when validating the cutoff.
+    inputs: Option<Arc<AnalysisInputs>>,
+}
+
+#[derive(Allocative)]
+struct AnalysisInputs {
+    node: ConfiguredTargetNode,
+    rule: Option<LoadedModule>,
+    deps: Vec<FrozenProviderCollectionValue>,
+}
+
+impl AnalysisInputs {
+    fn same_as(&self, other: &AnalysisInputs) -> bool {
+        let same_rule = match (&self.rule, &other.rule) {
+            (Some(a), Some(b)) => a.ptr_eq(b),
+            (None, None) => true,
+            _ => false,
+        };
+        self.node == other.node
+            && same_rule
+            && self.deps.len() == other.deps.len()
+            && self
+                .deps
+                .iter()
+                .zip(&other.deps)
+                .all(|(a, b)| a.is_indistinguishable_from(b))
+    }
+}
+
+impl AnalysisProviders {
+    /// Whether the targets depending on this target can keep the analysis they did with `old`.
+    fn unchanged_from(&self, old: &AnalysisProviders) -> bool {
+        let unchanged = self.providers.is_indistinguishable_from(&old.providers);
+        match (&self.inputs, &old.inputs) {
+            (Some(inputs), Some(old_inputs)) => {
+                if !unchanged && inputs.same_as(old_inputs) {
+                    let _ignore = soft_error!(
+                        "analysis_cutoff_mismatch",
+                        AnalysisCutoffError::Mismatch(inputs.node.label().dupe()).into()
+                    );
+                }
+                false
+            }
+            _ => unchanged,
+        }
+    }
+}
+
+fn validate_analysis_cutoff() -> anyhow::Result<bool> {
+    buck2_env!("BUCK2_VALIDATE_ANALYSIS_CUTOFF", bool)
+}
+
+async fn analysis_inputs(
+    ctx: &mut DiceComputations<'_>,
+    target: &ConfiguredTargetLabel,
+) -> anyhow::Result<AnalysisInputs> {
+    let node = ctx
+        .get_configured_target_node(target)
+        .await?
+        .require_compatible()?;
+    let rule = match node.rule_type() {
+        RuleType::Starlark(func) => Some(
+            ctx.get_loaded_module_from_import_path(&func.import_path)
+                .await?,
+        ),
+        RuleType::Forward => None,
+    };
+    let deps = get_dep_providers(node.as_ref(), ctx)
+        .await?
+        .into_iter()
+        .map(|(_, providers)| providers)
+        .collect();
+    Ok(AnalysisInputs { node, rule, deps })
+}
+
+#[async_trait]
+impl Key for AnalysisProvidersKey {
+    type Value = buck2_error::Result<MaybeCompatible<AnalysisProviders>>;
+
+    async fn compute(
+        &self,
+        ctx: &mut DiceComputations,
+        _cancellation: &CancellationContext,
+    ) -> Self::Value {
+        let providers = match ctx.get_analysis_result(&self.0).await? {
+            MaybeCompatible::Incompatible(reason) => {
+                return Ok(MaybeCompatible::Incompatible(reason));
+            }
+            MaybeCompatible::Compatible(result) => result.providers().dupe(),
+        };
+        let inputs = if validate_analysis_cutoff()? {
+            Some(Arc::new(analysis_inputs(ctx, &self.0).await?))
+        } else {
+            None
+        };
+        Ok(MaybeCompatible::Compatible(AnalysisProviders {
+            providers,
+            inputs,
+        }))
+    }
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        match (x, y) {
+            (Ok(MaybeCompatible::Compatible(x)), Ok(MaybeCompatible::Compatible(y))) => {
+                y.unchanged_from(x)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The providers of `target`, which the caller only depends on if they change.
+pub(crate) async fn get_target_providers(
+    ctx: &mut DiceComputations<'_>,
+    target: &ConfiguredTargetLabel,
+) -> anyhow::Result<MaybeCompatible<FrozenProviderCollectionValue>> {
+    Ok(ctx
+        .compute(&AnalysisProvidersKey(target.dupe()))
+        .await?
+        .map_err(anyhow::Error::from)?
+        .map(|p| p.providers))
+}
+
+/// The providers of the deps of `node`, for its analysis.
+pub(crate) async fn get_dep_providers<'v>(
+    configured_node: ConfiguredTargetNodeRef<'v>,
+    ctx: &mut DiceComputations<'_>,
+) -> anyhow::Result<Vec<(&'v ConfiguredTargetLabel, FrozenProviderCollectionValue)>> {
+    let ctx = &*ctx;
+    keep_going::try_join_all(
+        ctx,
+        configured_node
+            .deps()
+            .map(async move |dep| {
+                let res = get_target_providers(&mut ctx.bad_dice(), dep.label())
+                    .await
+                    .and_then(|v| v.require_compatible());
+                res.map(|x| (dep.label(), x))
+            })
+            .collect::<FuturesUnordered<_>>(),
+    )
+    .await
+}
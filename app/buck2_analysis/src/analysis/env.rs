@@ -22,8 +22,11 @@ use buck2_build_api::interpreter::rule_defs::context::AnalysisContext;
 use buck2_build_api::interpreter::rule_defs::provider::builtin::template_placeholder_info::FrozenTemplatePlaceholderInfo;
 use buck2_build_api::interpreter::rule_defs::provider::collection::FrozenProviderCollectionValue;
 use buck2_build_api::interpreter::rule_defs::provider::collection::ProviderCollection;
+use buck2_common::dice::cells::HasCellResolver;
+use buck2_common::legacy_configs::dice::HasLegacyConfigs;
 use buck2_core::base_deferred_key::BaseDeferredKey;
 use buck2_core::execution_types::execution::ExecutionPlatformResolution;
+use buck2_core::fs::case_collisions;
 use buck2_core::provider::label::ConfiguredProvidersLabel;
 use buck2_core::target::configured_target_label::ConfiguredTargetLabel;
 use buck2_core::unsafe_send_future::UnsafeSendFuture;
@@ -187,15 +190,20 @@ struct AnalysisEnv<'a> {
 pub(crate) async fn run_analysis<'a>(
     dice: &'a mut DiceComputations<'_>,
     label: &ConfiguredTargetLabel,
-    results: Vec<(&'a ConfiguredTargetLabel, AnalysisResult)>,
+    dep_providers: Vec<(&'a ConfiguredTargetLabel, FrozenProviderCollectionValue)>,
     query_results: HashMap<String, Arc<AnalysisQueryResult>>,
     execution_platform: &'a ExecutionPlatformResolution,
     rule_spec: &'a dyn RuleSpec,
     node: ConfiguredTargetNodeRef<'a>,
     profile_mode: &'a StarlarkProfileModeOrInstrumentation,
 ) -> anyhow::Result<AnalysisResult> {
-    let analysis_env =
-        AnalysisEnv::new(label, results, query_results, execution_platform, rule_spec)?;
+    let analysis_env = AnalysisEnv::new(
+        label,
+        dep_providers,
+        query_results,
+        execution_platform,
+        rule_spec,
+    );
     run_analysis_with_env(dice, analysis_env, node, profile_mode).await
 }
 
@@ -203,18 +211,18 @@ impl<'a> AnalysisEnv<'a> {
     /// Create a new `AnalysisEnv`, ensuring that all heaps are kept alive that need to be
     fn new(
         label: &ConfiguredTargetLabel,
-        results: Vec<(&'a ConfiguredTargetLabel, AnalysisResult)>,
+        dep_providers: Vec<(&'a ConfiguredTargetLabel, FrozenProviderCollectionValue)>,
         query_results: HashMap<String, Arc<AnalysisQueryResult>>,
         execution_platform: &'a ExecutionPlatformResolution,
         rule_spec: &'a dyn RuleSpec,
-    ) -> anyhow::Result<Self> {
-        Ok(AnalysisEnv {
+    ) -> Self {
+        AnalysisEnv {
             rule_spec,
-            deps: get_deps_from_analysis_results(results)?,
+            deps: dep_providers.into_iter().collect(),
             query_results,
             execution_platform,
             label: label.dupe(),
-        })
+        }
     }
 }
 
@@ -264,10 +272,20 @@ async fn run_analysis_with_env_underlying(
         )
     };
 
-    let registry = AnalysisRegistry::new_from_owner(
+    let mut registry = AnalysisRegistry::new_from_owner(
         BaseDeferredKey::TargetLabel(node.label().dupe()),
         analysis_env.execution_platform.dupe(),
     )?;
+    let root_cell = dice.get_cell_resolver().await?.root_cell();
+    let check_case_collisions = dice
+        .parse_legacy_config_property(
+            root_cell,
+            case_collisions::CONFIG_SECTION,
+            case_collisions::CONFIG_KEY,
+        )
+        .await?
+        .unwrap_or_else(case_collisions::enabled_by_default);
+    registry.set_check_case_collisions(check_case_collisions);
 
     let mut profiler_opt = profile_mode
         .profile_mode()
@@ -8,5 +8,6 @@
  */
 
 pub mod calculation;
+mod cutoff;
 pub mod env;
 mod plugins;
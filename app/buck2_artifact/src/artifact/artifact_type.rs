@@ -133,6 +133,33 @@ impl Artifact {
         }
     }
 
+    /// An artifact at `path` inside this artifact. Whether this artifact is a directory containing
+    /// `path` is only known once it is built, so a missing path is reported when the projected
+    /// artifact is used as an input.
+    pub fn project(&self, path: &ForwardRelativePath, hide_prefix: bool) -> Self {
+        if path.is_empty() {
+            return self.dupe();
+        }
+
+        let hidden_components_count = self.0.hidden_components_count
+            + if hide_prefix {
+                self.get_path().with_short_path(|p| p.iter().count())
+            } else {
+                0
+            };
+
+        let (base, projected_path) = self.as_parts();
+        let projected_path = match projected_path {
+            Some(existing_path) => existing_path.join(path),
+            None => path.to_owned(),
+        };
+        Self::new(
+            base.dupe(),
+            Some(Arc::new(projected_path)),
+            hidden_components_count,
+        )
+    }
+
     pub fn get_path(&self) -> ArtifactPath<'_> {
         let (base, projected_path) = self.as_parts();
 
@@ -42,6 +42,14 @@ pub enum DeferredMaterializerSubcommand {
         count: usize,
     },
     FlushAccessTimes,
+    /// Show what the materializer knows about a path in buck-out: its state, how it is
+    /// materialized (including the action that produced it for CAS downloads), its digest,
+    /// and the recent materializer commands that touched it.
+    Path {
+        /// The path, absolute or relative to the current directory.
+        #[clap()]
+        path: String,
+    },
 }
 
 #[async_trait]
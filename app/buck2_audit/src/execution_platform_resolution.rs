@@ -15,7 +15,10 @@ use crate::AuditSubcommand;
 #[derive(Debug, clap::Parser, serde::Serialize, serde::Deserialize)]
 #[clap(
     name = "audit-execution-platform-resolution",
-    about = "prints out information about execution platform resolution"
+    about = "prints out information about execution platform resolution",
+    long_about = "Prints out information about execution platform resolution: for each target, \
+    its `exec_compatible_with` constraints, the execution platform all its actions run on, \
+    and the higher priority platforms which were rejected, with the reason why."
 )]
 pub struct AuditExecutionPlatformResolutionCommand {
     #[clap(flatten)]
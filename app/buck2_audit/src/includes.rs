@@ -25,9 +25,23 @@ pub struct AuditIncludesCommand {
     #[clap(long)]
     pub json: bool,
 
+    /// Also print the cell path and the digest of each included file.
+    #[clap(long)]
+    pub digests: bool,
+
+    /// Print the packages in `--universe` whose build files transitively load the given `.bzl`
+    /// files, i.e. the packages which are reloaded when these files change, instead of the files
+    /// loaded by build files.
+    #[clap(long, conflicts_with = "digests")]
+    pub reverse: bool,
+
+    /// Comma separated list of patterns of the packages searched by `--reverse`.
+    #[clap(long, use_delimiter = true, default_value = "//...")]
+    pub universe: Vec<String>,
+
     #[clap(
         name = "BUILD_FILES",
-        help = "Build files to audit. These are expected to be relative paths from the working dir cell."
+        help = "Build files to audit, or `.bzl` files with `--reverse`. These are expected to be relative paths from the working dir cell."
     )]
     pub patterns: Vec<String>,
 }
@@ -70,6 +70,7 @@ pub enum AuditCommand {
     Providers(AuditProvidersCommand),
     Subtargets(AuditSubtargetsCommand),
     AnalysisQueries(AuditAnalysisQueriesCommand),
+    #[clap(alias = "exec-platform")]
     ExecutionPlatformResolution(AuditExecutionPlatformResolutionCommand),
     Visibility(AuditVisibilityCommand),
     #[clap(subcommand)]
@@ -164,3 +165,29 @@ impl StreamingCommand for AuditCommand {
         &self.as_subcommand().common_opts().config_opts
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[derive(Debug, clap::Parser)]
+    struct Audit {
+        #[clap(subcommand)]
+        command: AuditCommand,
+    }
+
+    #[test]
+    fn test_execution_platform_resolution_alias() {
+        for name in ["execution-platform-resolution", "exec-platform"] {
+            let audit = Audit::try_parse_from(["audit", name, "//foo:bar"]).unwrap();
+            match audit.command {
+                AuditCommand::ExecutionPlatformResolution(command) => {
+                    assert_eq!(vec!["//foo:bar".to_owned()], command.patterns);
+                }
+                command => panic!("Unexpected command for `{}`: {:?}", name, command),
+            }
+        }
+    }
+}
@@ -35,7 +35,7 @@ impl AuditSubcommand for DeferredMaterializerCommand {
             .as_deferred_materializer_extension()
             .context("Deferred materializer is not in use")?;
 
-        match self.subcommand {
+        match &self.subcommand {
             DeferredMaterializerSubcommand::List => {
                 let mut stream = deferred_materializer
                     .iterate()
@@ -71,7 +71,7 @@ impl AuditSubcommand for DeferredMaterializerCommand {
             }
             DeferredMaterializerSubcommand::Refresh { min_ttl } => {
                 deferred_materializer
-                    .refresh_ttls(min_ttl)
+                    .refresh_ttls(*min_ttl)
                     .await
                     .context("Failed to refresh")?;
             }
@@ -85,7 +85,7 @@ impl AuditSubcommand for DeferredMaterializerCommand {
             }
             DeferredMaterializerSubcommand::TestIter { count } => {
                 let text = deferred_materializer
-                    .test_iter(count)
+                    .test_iter(*count)
                     .await
                     .context("Failed to test_iter")?;
 
@@ -99,6 +99,17 @@ impl AuditSubcommand for DeferredMaterializerCommand {
 
                 write!(stdout, "{}", text)?;
             }
+            DeferredMaterializerSubcommand::Path { path } => {
+                let path = server_ctx
+                    .project_root()
+                    .relativize_any(server_ctx.working_dir_abs().path().as_abs_path().join(path))?;
+                let text = deferred_materializer
+                    .path_info(path)
+                    .await
+                    .context("Failed to get path info")?;
+
+                write!(stdout, "{}", text)?;
+            }
         }
 
         anyhow::Ok(())
@@ -18,8 +18,11 @@ use buck2_core::pattern::pattern_type::ConfigurationPredicate;
 use buck2_core::pattern::pattern_type::ConfiguredTargetPatternExtra;
 use buck2_core::pattern::ParsedPattern;
 use buck2_core::target::label::TargetLabel;
+use buck2_node::attrs::inspect_options::AttrInspectOptions;
+use buck2_node::attrs::internal::EXEC_COMPATIBLE_WITH_ATTRIBUTE_FIELD;
 use buck2_node::load_patterns::load_patterns;
 use buck2_node::load_patterns::MissingTargetBehavior;
+use buck2_node::nodes::configured::ConfiguredTargetNode;
 use buck2_node::nodes::configured_frontend::ConfiguredTargetNodeCalculation;
 use buck2_node::target_calculation::ConfiguredTargetCalculation;
 use buck2_server_ctx::ctx::ServerCommandContextTrait;
@@ -100,11 +103,18 @@ impl AuditSubcommand for AuditExecutionPlatformResolutionCommand {
                     let configured_node = ctx.get_configured_target_node(&configured_target).await?;
                     let configured_node = configured_node.require_compatible()?;
                     writeln!(stdout, "{}:", configured_target)?;
+                    if let Some(attr) = configured_node.as_ref().get(EXEC_COMPATIBLE_WITH_ATTRIBUTE_FIELD, AttrInspectOptions::All) {
+                        writeln!(stdout, "  exec_compatible_with:")?;
+                        for constraint in ConfiguredTargetNode::attr_as_target_compatible_with(attr.value) {
+                            writeln!(stdout, "    {}", constraint?)?;
+                        }
+                    }
                     let resolution = configured_node.execution_platform_resolution();
                     match resolution.platform() {
                         Ok(platform) => {
                             writeln!(stdout, "  Execution platform: {}", platform.id())?;
                             writeln!(stdout, "    Execution platform configuration: {}", platform.cfg())?;
+                            writeln!(stdout, "    Executor: {}", platform.executor_config().executor)?;
                             writeln!(stdout, "    Execution deps:")?;
                             for execution_dep in configured_node.exec_deps() {
                                 writeln!(stdout, "      {}", execution_dep.label())?;
@@ -13,6 +13,9 @@ use async_trait::async_trait;
 use buck2_audit::includes::AuditIncludesCommand;
 use buck2_cli_proto::ClientContext;
 use buck2_common::dice::cells::HasCellResolver;
+use buck2_common::dice::file_ops::DiceFileOps;
+use buck2_common::file_ops::FileOps;
+use buck2_common::file_ops::RawPathMetadata;
 use buck2_core::bzl::ImportPath;
 use buck2_core::cells::cell_path::CellPath;
 use buck2_core::cells::CellResolver;
@@ -22,9 +25,13 @@ use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
 use buck2_core::fs::paths::file_name::FileNameBuf;
 use buck2_core::fs::project::ProjectRoot;
 use buck2_core::package::PackageLabel;
+use buck2_core::pattern::pattern_type::TargetPatternExtra;
+use buck2_core::pattern::ParsedPattern;
 use buck2_interpreter::file_loader::LoadedModule;
 use buck2_interpreter::load_module::InterpreterCalculation;
 use buck2_interpreter::paths::module::StarlarkModulePath;
+use buck2_node::load_patterns::load_patterns;
+use buck2_node::load_patterns::MissingTargetBehavior;
 use buck2_node::nodes::eval_result::EvaluationResult;
 use buck2_node::nodes::frontend::TargetGraphCalculation;
 use buck2_query::query::graph::node::LabeledNode;
@@ -36,6 +43,7 @@ use buck2_query::query::traversal::ChildVisitor;
 use buck2_server_ctx::ctx::ServerCommandContextTrait;
 use buck2_server_ctx::ctx::ServerCommandDiceContext;
 use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
+use buck2_server_ctx::pattern::parse_patterns_from_cli_args;
 use derive_more::Display;
 use dice::DiceComputations;
 use dupe::Dupe;
@@ -166,6 +174,66 @@ async fn load_and_collect_includes(
     Ok(get_transitive_includes(ctx, &load_result).await?)
 }
 
+/// A file loaded by a build file.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Include {
+    Path(AbsNormPathBuf),
+    Detailed {
+        path: AbsNormPathBuf,
+        cell_path: String,
+        /// `None` if the file is not a regular file, e.g. a symlink.
+        digest: Option<String>,
+    },
+}
+
+impl std::fmt::Display for Include {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Include::Path(path) => write!(f, "{}", path),
+            Include::Detailed {
+                path,
+                cell_path,
+                digest,
+            } => write!(
+                f,
+                "{}\t{}\t{}",
+                path,
+                cell_path,
+                digest.as_deref().unwrap_or("-")
+            ),
+        }
+    }
+}
+
+async fn describe_include(
+    ctx: &DiceComputations<'_>,
+    cells: &CellResolver,
+    fs: &ProjectRoot,
+    include: ImportPath,
+    digests: bool,
+) -> anyhow::Result<Include> {
+    let cell_path = include.path();
+    let cell = cells.get(cell_path.cell())?;
+    // To match buck1, we print absolute paths.
+    let path = fs.resolve(&cell.path().join(cell_path.path()));
+    if !digests {
+        return Ok(Include::Path(path));
+    }
+    let digest = match DiceFileOps(ctx)
+        .read_path_metadata_if_exists(cell_path.as_ref())
+        .await?
+    {
+        Some(RawPathMetadata::File(metadata)) => Some(metadata.digest.to_string()),
+        _ => None,
+    };
+    Ok(Include::Detailed {
+        path,
+        cell_path: cell_path.to_string(),
+        digest,
+    })
+}
+
 fn resolve_path(
     cells: &CellResolver,
     fs: &ProjectRoot,
@@ -186,6 +254,96 @@ fn resolve_path(
     cells.get_cell_path(&project_path)
 }
 
+/// Print the packages of the universe whose build files load the given files.
+async fn reverse_execute(
+    command: &AuditIncludesCommand,
+    server_ctx: &dyn ServerCommandContextTrait,
+    mut stdout: PartialResultDispatcher<buck2_cli_proto::StdoutBytes>,
+) -> anyhow::Result<()> {
+    server_ctx
+        .with_dice_ctx(async move |server_ctx, mut ctx| {
+            let cells = ctx.get_cell_resolver().await?;
+            let cwd = server_ctx.working_dir();
+            let current_cell = cells.get(cells.find(cwd)?)?;
+            let fs = server_ctx.project_root();
+            let current_cell_abs_path = fs.resolve(current_cell.path().as_project_relative_path());
+
+            let files: Vec<(String, CellPath)> = command
+                .patterns
+                .iter()
+                .unique()
+                .map(|path| {
+                    Ok((
+                        path.to_owned(),
+                        resolve_path(&cells, fs, &current_cell_abs_path, path)?,
+                    ))
+                })
+                .collect::<anyhow::Result<_>>()?;
+
+            let universe = parse_patterns_from_cli_args::<TargetPatternExtra>(
+                &mut ctx,
+                &command
+                    .universe
+                    .map(|pat| buck2_data::TargetPattern { value: pat.clone() }),
+                cwd,
+            )
+            .await?;
+            let loaded = load_patterns(&mut ctx, universe, MissingTargetBehavior::Fail).await?;
+            let futures: FuturesOrdered<_> = loaded
+                .iter()
+                .map(|(package, _)| {
+                    let mut ctx = ctx.dupe();
+                    async move {
+                        let load_result = ctx.get_interpreter_results(package.dupe()).await?;
+                        let includes = get_transitive_includes(&ctx, &load_result).await?;
+                        anyhow::Ok((package, includes))
+                    }
+                })
+                .collect();
+            let universe: Vec<(PackageLabel, Vec<ImportPath>)> = futures
+                .collect::<Vec<_>>()
+                .await
+                .into_iter()
+                .collect::<anyhow::Result<_>>()?;
+
+            let results: Vec<(&String, Vec<&PackageLabel>)> = files
+                .iter()
+                .map(|(path, cell_path)| {
+                    let packages = universe
+                        .iter()
+                        .filter(|(_, includes)| {
+                            includes.iter().any(|include| include.path() == cell_path)
+                        })
+                        .map(|(package, _)| package)
+                        .collect();
+                    (path, packages)
+                })
+                .collect();
+
+            let mut stdout = stdout.as_writer();
+            if command.json {
+                let mut ser = serde_json::Serializer::pretty(&mut stdout);
+                let mut map = ser.serialize_map(Some(results.len()))?;
+                for (path, packages) in &results {
+                    let packages: Vec<String> = packages.map(|p| p.to_string());
+                    map.serialize_entry(path, &indexmap! {"packages" => packages})?;
+                }
+                map.end()?;
+                writeln!(stdout)?;
+            } else {
+                for (path, packages) in &results {
+                    // intentionally add a blank line after the header
+                    writeln!(stdout, "# {}\n", path)?;
+                    for package in packages {
+                        writeln!(stdout, "{}", package)?;
+                    }
+                }
+            }
+            Ok(())
+        })
+        .await
+}
+
 #[async_trait]
 impl AuditSubcommand for AuditIncludesCommand {
     async fn server_execute(
@@ -194,6 +352,10 @@ impl AuditSubcommand for AuditIncludesCommand {
         mut stdout: PartialResultDispatcher<buck2_cli_proto::StdoutBytes>,
         _client_ctx: ClientContext,
     ) -> anyhow::Result<()> {
+        if self.reverse {
+            return reverse_execute(self, server_ctx, stdout).await;
+        }
+
         server_ctx
             .with_dice_ctx(async move |server_ctx, mut ctx| {
                 let cells = ctx.get_cell_resolver().await?;
@@ -222,19 +384,20 @@ impl AuditSubcommand for AuditIncludesCommand {
                     .collect();
 
                 let results: Vec<(_, buck2_error::Result<Vec<_>>)> = futures.collect().await;
-                // This is expected to not return any errors, and so we're not careful about not propagating it.
-                let to_absolute_path = move |include: ImportPath| -> anyhow::Result<_> {
-                    let include = include.path();
-                    let cell = cells.get(include.cell())?;
-                    let path = cell.path().join(include.path());
-                    Ok(fs.resolve(&path))
-                };
-                let absolutize_paths =
-                    |paths: Vec<ImportPath>| -> buck2_error::Result<Vec<AbsNormPathBuf>> {
-                        Ok(paths.into_try_map(&to_absolute_path)?)
+                let mut described = Vec::with_capacity(results.len());
+                for (path, includes) in results {
+                    let includes: buck2_error::Result<Vec<Include>> = try {
+                        let mut described_includes = Vec::new();
+                        for include in includes? {
+                            described_includes.push(
+                                describe_include(&ctx, &cells, fs, include, self.digests).await?,
+                            );
+                        }
+                        described_includes
                     };
-                let results: Vec<(String, buck2_error::Result<Vec<AbsNormPathBuf>>)> = results
-                    .into_map(|(path, includes)| (path, includes.and_then(absolutize_paths)));
+                    described.push((path, includes));
+                }
+                let results = described;
 
                 let mut stdout = stdout.as_writer();
 
@@ -273,7 +436,6 @@ impl AuditSubcommand for AuditIncludesCommand {
                                 // intentionally add a blank line after the header
                                 writeln!(stdout, "# {}\n", path)?;
                                 for include in includes {
-                                    // To match buck1, we print absolute paths.
                                     writeln!(stdout, "{}", include)?;
                                 }
                             }
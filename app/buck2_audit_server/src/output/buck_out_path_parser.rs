@@ -18,6 +18,8 @@ use buck2_core::cells::CellResolver;
 use buck2_core::fs::paths::file_name::FileName;
 use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
 use buck2_core::fs::paths::forward_rel_path::ForwardRelativePathBuf;
+use buck2_core::fs::project_rel_path::ProjectRelativePath;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
 use buck2_core::package::PackageLabel;
 use buck2_core::target::label::TargetLabel;
 use buck2_core::target::name::TargetNameRef;
@@ -79,8 +81,14 @@ pub(crate) enum BuckOutPathType {
     },
 }
 
+/// Reads a file of the project, given its path relative to the project root, returning `None` if
+/// it does not exist. Used to read the mapping files of the short buck-out path scheme.
+pub(crate) type ReadProjectFile<'v> =
+    &'v (dyn Fn(&ProjectRelativePath) -> anyhow::Result<Option<String>> + Sync);
+
 pub(crate) struct BuckOutPathParser<'v> {
     cell_resolver: &'v CellResolver,
+    read_file: ReadProjectFile<'v>,
 }
 
 fn validate_buck_out_and_isolation_prefix<'v>(
@@ -270,9 +278,91 @@ fn get_bxl_function_label<'v>(
     Ok(bxl_function_label)
 }
 
+/// The target owning the outputs in a short buck-out path, from the contents of its mapping file:
+/// `<configured target label>` or `<configured target label> (action <key>)`.
+fn parse_short_path_mapping(contents: &str) -> anyhow::Result<TargetLabel> {
+    // The configuration follows the unconfigured label, after a space.
+    let label = contents.trim().split(' ').next().unwrap_or_default();
+    let (cell, rest) = label
+        .split_once("//")
+        .with_context(|| format!("Invalid target label in short path mapping: `{}`", label))?;
+    let (package, name) = rest
+        .split_once(':')
+        .with_context(|| format!("Invalid target label in short path mapping: `{}`", label))?;
+    let package = PackageLabel::new(
+        CellName::unchecked_new(cell)?,
+        CellRelativePath::new(ForwardRelativePath::new(package)?),
+    );
+    Ok(TargetLabel::new(package, TargetNameRef::new(name)?))
+}
+
 impl<'v> BuckOutPathParser<'v> {
-    pub(crate) fn new(cell_resolver: &'v CellResolver) -> BuckOutPathParser {
-        BuckOutPathParser { cell_resolver }
+    pub(crate) fn new(
+        cell_resolver: &'v CellResolver,
+        read_file: ReadProjectFile<'v>,
+    ) -> BuckOutPathParser<'v> {
+        BuckOutPathParser {
+            cell_resolver,
+            read_file,
+        }
+    }
+
+    /// Whether the path continues with `<configuration hash>/<target hash>` rather than a cell,
+    /// as `buck2.buck_out_path_scheme = short` lays out the outputs of targets.
+    fn is_short_path<'a>(
+        &self,
+        iter: &Peekable<impl Iterator<Item = &'a FileName> + Clone>,
+    ) -> bool {
+        match iter.clone().peek() {
+            Some(part) => match CellName::unchecked_new(part.as_str()) {
+                Ok(cell_name) => self.cell_resolver.get(cell_name).is_err(),
+                Err(_) => true,
+            },
+            None => false,
+        }
+    }
+
+    /// Parse the rest of an output path of a target in the short scheme,
+    /// `<configuration hash>[-<exec configuration hash>]/<target hash>/<outputs>`, finding the
+    /// target in the mapping file written next to the outputs.
+    fn parse_short_rule_output<'a>(
+        &self,
+        buck_out: &ForwardRelativePath,
+        iter: &mut Peekable<impl Iterator<Item = &'a FileName> + Clone>,
+    ) -> anyhow::Result<BuckOutPathType> {
+        let (Some(config_dir), Some(target_hash)) = (iter.next(), iter.next()) else {
+            return Err(anyhow::anyhow!("Path does not have a target hash"));
+        };
+        let mapping = ProjectRelativePathBuf::unchecked_new(format!(
+            "{}/short-paths/{}/{}",
+            buck_out, config_dir, target_hash
+        ));
+        let contents = (self.read_file)(&mapping)?.with_context(|| {
+            format!(
+                "`{}` is not a cell, and the path is not a short buck-out path either, as `{}` does not exist",
+                config_dir, mapping
+            )
+        })?;
+        let target_label = parse_short_path_mapping(&contents)?;
+        self.cell_resolver.get(target_label.pkg().cell_name())?;
+
+        let path_after_target_name = ForwardRelativePathBuf::new(iter.clone().join("/"))?;
+        let mut raw_path_to_output = <&ForwardRelativePath>::from(target_hash).to_buf();
+        raw_path_to_output.push(&path_after_target_name);
+        // The exec configuration is not part of the configuration of the target.
+        let config_hash = match config_dir.as_str().split_once('-') {
+            Some((cfg, _exec_cfg)) => cfg,
+            None => config_dir.as_str(),
+        };
+        Ok(BuckOutPathType::RuleOutput {
+            path: target_label.pkg().to_cell_path(),
+            target_label,
+            path_after_target_name,
+            common_attrs: BuckOutPathTypeCommon {
+                config_hash: config_hash.to_owned(),
+                raw_path_to_output,
+            },
+        })
     }
 
     // Validates and parses the buck-out path, returning the `BuckOutPathType`. Assumes
@@ -299,6 +389,15 @@ impl<'v> BuckOutPathParser<'v> {
         let mut iter = path_as_forward_rel_path.iter().peekable();
 
         validate_buck_out_and_isolation_prefix(&mut iter, output_path)?;
+        let buck_out = ForwardRelativePath::new(
+            path_as_forward_rel_path
+                .iter()
+                .take(2)
+                .map(|part| part.as_str())
+                .join("/")
+                .as_str(),
+        )?
+        .to_buf();
 
         // Advance the iterator to the prefix (tmp, test, gen, gen-anon, or gen-bxl)
         match iter.next() {
@@ -335,6 +434,9 @@ impl<'v> BuckOutPathParser<'v> {
                             common_attrs,
                         })
                     }
+                    "gen" if self.is_short_path(&iter) => {
+                        self.parse_short_rule_output(&buck_out, &mut iter)
+                    }
                     "gen" => {
                         let buck_out_path_data =
                             get_cell_path(&mut iter, self.cell_resolver, "gen")?;
@@ -410,6 +512,7 @@ mod tests {
     use std::collections::BTreeMap;
 
     use buck2_build_api::bxl::types::BxlFunctionLabel;
+    use buck2_core::base_deferred_key::BaseDeferredKey;
     use buck2_core::cells::cell_path::CellPath;
     use buck2_core::cells::cell_root_path::CellRootPath;
     use buck2_core::cells::name::CellName;
@@ -417,12 +520,17 @@ mod tests {
     use buck2_core::cells::CellResolver;
     use buck2_core::configuration::data::ConfigurationData;
     use buck2_core::configuration::data::ConfigurationDataData;
+    use buck2_core::fs::buck_out_path::BuckOutPath;
+    use buck2_core::fs::buck_out_path::BuckOutPathResolver;
+    use buck2_core::fs::buck_out_path::BuckOutPathScheme;
     use buck2_core::fs::paths::forward_rel_path::ForwardRelativePathBuf;
     use buck2_core::fs::project_rel_path::ProjectRelativePath;
+    use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
     use buck2_core::package::PackageLabel;
     use buck2_core::target::label::TargetLabel;
     use buck2_core::target::name::TargetNameRef;
     use buck2_interpreter::paths::bxl::BxlFilePath;
+    use dupe::Dupe;
 
     use crate::output::buck_out_path_parser::BuckOutPathParser;
     use crate::output::buck_out_path_parser::BuckOutPathType;
@@ -448,7 +556,7 @@ mod tests {
         )
         .unwrap();
         let cell_resolver = get_parse_test_cell_resolver()?;
-        let buck_out_parser = BuckOutPathParser::new(&cell_resolver);
+        let buck_out_parser = BuckOutPathParser::new(&cell_resolver, &|_| Ok(None));
 
         let malformed_path1 = "does/not/start/with/buck-out/blah/blah";
         let malformed_path2 = "buck-out/v2/invalid_buck_prefix/blah/blah/blah/blah";
@@ -498,7 +606,7 @@ mod tests {
         )
         .unwrap();
         let cell_resolver = get_parse_test_cell_resolver()?;
-        let buck_out_parser = BuckOutPathParser::new(&cell_resolver);
+        let buck_out_parser = BuckOutPathParser::new(&cell_resolver, &|_| Ok(None));
 
         let pkg = PackageLabel::new(
             CellName::testing_new("bar"),
@@ -719,4 +827,62 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_buck_path_parser_short_scheme() -> anyhow::Result<()> {
+        let configuration = ConfigurationData::from_platform(
+            "cfg_for//:testing_exec".to_owned(),
+            ConfigurationDataData {
+                constraints: BTreeMap::new(),
+            },
+        )
+        .unwrap();
+        let cell_resolver = get_parse_test_cell_resolver()?;
+        let target_label = TargetLabel::new(
+            PackageLabel::new(
+                CellName::testing_new("bar"),
+                CellRelativePath::unchecked_new("path/to/target"),
+            ),
+            TargetNameRef::new("target_name")?,
+        );
+        let owner = BaseDeferredKey::TargetLabel(target_label.configure(configuration.dupe()));
+
+        let path_resolver = BuckOutPathResolver::with_scheme(
+            ProjectRelativePathBuf::unchecked_new("buck-out/v2".to_owned()),
+            BuckOutPathScheme::Short,
+        );
+        let output = path_resolver.resolve_gen(&BuckOutPath::new(
+            owner.dupe(),
+            ForwardRelativePathBuf::unchecked_new("dir/output".to_owned()),
+        ));
+        let (mapping, contents) = path_resolver.short_path_mapping(&owner, None).unwrap();
+
+        let read_file =
+            |path: &ProjectRelativePath| Ok((path == &*mapping).then(|| contents.clone()));
+        let buck_out_parser = BuckOutPathParser::new(&cell_resolver, &read_file);
+
+        match buck_out_parser.parse(output.as_str())? {
+            BuckOutPathType::RuleOutput {
+                path,
+                target_label: parsed_label,
+                path_after_target_name,
+                common_attrs,
+            } => {
+                assert_eq!(parsed_label, target_label);
+                assert_eq!(path, target_label.pkg().to_cell_path());
+                assert_eq!(path_after_target_name.as_str(), "dir/output");
+                assert_eq!(
+                    common_attrs.config_hash,
+                    configuration.output_hash().as_str()
+                );
+            }
+            _ => panic!("Should have parsed short buck-out path successfully"),
+        }
+
+        // Without its mapping file, the owner of a short path is unknown.
+        let res = BuckOutPathParser::new(&cell_resolver, &|_| Ok(None)).parse(output.as_str());
+        assert!(res.err().unwrap().to_string().contains("Malformed"));
+
+        Ok(())
+    }
 }
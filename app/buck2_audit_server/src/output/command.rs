@@ -18,8 +18,10 @@ use buck2_build_api::audit_output::AuditOutputResult;
 use buck2_build_api::audit_output::AUDIT_OUTPUT;
 use buck2_cli_proto::ClientContext;
 use buck2_common::dice::cells::HasCellResolver;
+use buck2_common::dice::data::HasIoProvider;
 use buck2_common::global_cfg_options::GlobalCfgOptions;
 use buck2_core::cells::CellResolver;
+use buck2_core::fs::fs_util;
 use buck2_core::fs::project_rel_path::ProjectRelativePath;
 use buck2_node::target_calculation::ConfiguredTargetCalculation;
 use buck2_server_ctx::ctx::ServerCommandContextTrait;
@@ -27,6 +29,7 @@ use buck2_server_ctx::ctx::ServerCommandDiceContext;
 use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
 use buck2_server_ctx::pattern::global_cfg_options_from_client_context;
 use dice::DiceComputations;
+use dupe::Dupe;
 
 use crate::output::buck_out_path_parser::BuckOutPathParser;
 use crate::output::buck_out_path_parser::BuckOutPathType;
@@ -47,7 +50,14 @@ async fn audit_output<'v>(
     dice_ctx: &'v mut DiceComputations<'_>,
     global_cfg_options: &'v GlobalCfgOptions,
 ) -> anyhow::Result<Option<AuditOutputResult>> {
-    let buck_out_parser = BuckOutPathParser::new(cell_resolver);
+    let project_root = dice_ctx
+        .global_data()
+        .get_io_provider()
+        .project_root()
+        .dupe();
+    let read_file =
+        |path: &ProjectRelativePath| fs_util::read_to_string_if_exists(project_root.resolve(path));
+    let buck_out_parser = BuckOutPathParser::new(cell_resolver, &read_file);
     let parsed = buck_out_parser.parse(output_path)?;
 
     let (target_label, config_hash, path_after_target_name) = match parsed {
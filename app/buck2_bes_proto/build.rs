@@ -0,0 +1,22 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::io;
+
+fn main() -> io::Result<()> {
+    let proto_files = &[
+        "proto/build_event_stream/build_event_stream.proto",
+        "proto/google/devtools/build/v1/build_events.proto",
+        "proto/google/devtools/build/v1/publish_build_event.proto",
+    ];
+
+    buck2_protoc_dev::configure()
+        .setup_protoc()
+        .compile(proto_files, &["./proto/"])
+}
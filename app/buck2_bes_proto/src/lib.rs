@@ -0,0 +1,23 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! The messages of the Build Event Protocol, which Buck2 sends to Build Event Services.
+
+pub mod build_event_stream {
+    tonic::include_proto!("build_event_stream");
+}
+pub mod google {
+    pub mod devtools {
+        pub mod build {
+            pub mod v1 {
+                tonic::include_proto!("google.devtools.build.v1");
+            }
+        }
+    }
+}
@@ -0,0 +1,459 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Export of `aquery` results in the `analysis_v2.proto` schema of Bazel's `aquery
+//! --output=proto`, so that tools built for Bazel action graphs can read Buck2 action graphs.
+//!
+//! Only the fields Buck2 has an equivalent for are written: actions have no aspects,
+//! environment or param files, and targets have no rule class. Inputs coming from transitive set
+//! projections only contain the outputs of the actions which are part of the exported result.
+
+use std::collections::HashMap;
+
+use buck2_artifact::actions::key::ActionKey;
+use buck2_core::base_deferred_key::BaseDeferredKey;
+use buck2_core::fs::artifact_path_resolver::ArtifactFs;
+use buck2_core::fs::project_rel_path::ProjectRelativePath;
+use buck2_execute::artifact::fs::ExecutorFs;
+use buck2_execute::execute::request::OutputType;
+use dupe::Dupe;
+use serde::Serialize;
+
+use crate::actions::query::ActionInput;
+use crate::actions::query::ActionQueryNode;
+use crate::actions::query::ActionQueryNodeData;
+use crate::actions::query::ActionQueryNodeRef;
+use crate::actions::query::SetProjectionInputs;
+use crate::artifact_groups::ArtifactGroup;
+
+#[derive(Clone, PartialEq, prost::Message, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionGraphContainer {
+    #[prost(message, repeated, tag = "1")]
+    pub artifacts: Vec<Artifact>,
+    #[prost(message, repeated, tag = "2")]
+    pub actions: Vec<Action>,
+    #[prost(message, repeated, tag = "3")]
+    pub targets: Vec<Target>,
+    #[prost(message, repeated, tag = "4")]
+    pub dep_set_of_files: Vec<DepSetOfFiles>,
+    #[prost(message, repeated, tag = "5")]
+    pub configuration: Vec<Configuration>,
+    #[prost(message, repeated, tag = "8")]
+    pub path_fragments: Vec<PathFragment>,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Artifact {
+    #[prost(uint32, tag = "1")]
+    pub id: u32,
+    #[prost(uint32, tag = "2")]
+    pub path_fragment_id: u32,
+    #[prost(bool, tag = "3")]
+    pub is_tree_artifact: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Action {
+    #[prost(uint32, tag = "1")]
+    pub target_id: u32,
+    #[prost(string, tag = "3")]
+    pub action_key: String,
+    #[prost(string, tag = "4")]
+    pub mnemonic: String,
+    #[prost(uint32, tag = "5")]
+    pub configuration_id: u32,
+    #[prost(string, repeated, tag = "6")]
+    pub arguments: Vec<String>,
+    #[prost(uint32, repeated, tag = "8")]
+    pub input_dep_set_ids: Vec<u32>,
+    #[prost(uint32, repeated, tag = "9")]
+    pub output_ids: Vec<u32>,
+    #[prost(uint32, tag = "12")]
+    pub primary_output_id: u32,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Target {
+    #[prost(uint32, tag = "1")]
+    pub id: u32,
+    #[prost(string, tag = "2")]
+    pub label: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DepSetOfFiles {
+    #[prost(uint32, tag = "1")]
+    pub id: u32,
+    #[prost(uint32, repeated, tag = "2")]
+    pub transitive_dep_set_ids: Vec<u32>,
+    #[prost(uint32, repeated, tag = "3")]
+    pub direct_artifact_ids: Vec<u32>,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Configuration {
+    #[prost(uint32, tag = "1")]
+    pub id: u32,
+    #[prost(string, tag = "2")]
+    pub mnemonic: String,
+    #[prost(string, tag = "3")]
+    pub platform_name: String,
+    #[prost(string, tag = "4")]
+    pub checksum: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathFragment {
+    #[prost(uint32, tag = "1")]
+    pub id: u32,
+    #[prost(string, tag = "2")]
+    pub label: String,
+    #[prost(uint32, tag = "3")]
+    pub parent_id: u32,
+}
+
+impl ActionGraphContainer {
+    /// The binary encoding, like `aquery --output=proto`.
+    pub fn encode_proto(&self) -> Vec<u8> {
+        prost::Message::encode_to_vec(self)
+    }
+}
+
+/// Ids in `analysis_v2` start at 1, 0 meaning unset.
+fn next_id(len: usize) -> u32 {
+    len as u32 + 1
+}
+
+#[derive(Default)]
+struct ActionGraphBuilder {
+    container: ActionGraphContainer,
+    path_fragments: HashMap<(u32, String), u32>,
+    artifacts: HashMap<u32, u32>,
+    targets: HashMap<String, u32>,
+    configurations: HashMap<String, u32>,
+    projections: HashMap<SetProjectionInputs, u32>,
+    outputs: HashMap<ActionKey, Vec<u32>>,
+}
+
+impl ActionGraphBuilder {
+    fn path_fragment(&mut self, path: &ProjectRelativePath) -> u32 {
+        let mut parent_id = 0;
+        for component in path.iter() {
+            let key = (parent_id, component.as_str().to_owned());
+            parent_id = match self.path_fragments.get(&key) {
+                Some(id) => *id,
+                None => {
+                    let id = next_id(self.container.path_fragments.len());
+                    self.container.path_fragments.push(PathFragment {
+                        id,
+                        label: key.1.clone(),
+                        parent_id,
+                    });
+                    self.path_fragments.insert(key, id);
+                    id
+                }
+            };
+        }
+        parent_id
+    }
+
+    fn artifact(&mut self, path: &ProjectRelativePath, is_tree_artifact: bool) -> u32 {
+        let path_fragment_id = self.path_fragment(path);
+        if let Some(id) = self.artifacts.get(&path_fragment_id) {
+            return *id;
+        }
+        let id = next_id(self.container.artifacts.len());
+        self.container.artifacts.push(Artifact {
+            id,
+            path_fragment_id,
+            is_tree_artifact,
+        });
+        self.artifacts.insert(path_fragment_id, id);
+        id
+    }
+
+    fn target(&mut self, owner: &BaseDeferredKey) -> u32 {
+        let label = match owner.unpack_target_label() {
+            Some(label) => label.unconfigured().to_string(),
+            None => owner.to_string(),
+        };
+        if let Some(id) = self.targets.get(&label) {
+            return *id;
+        }
+        let id = next_id(self.container.targets.len());
+        self.container.targets.push(Target {
+            id,
+            label: label.clone(),
+        });
+        self.targets.insert(label, id);
+        id
+    }
+
+    fn configuration(&mut self, owner: &BaseDeferredKey) -> u32 {
+        let Some(label) = owner.unpack_target_label() else {
+            return 0;
+        };
+        let cfg = label.cfg();
+        let name = cfg.to_string();
+        if let Some(id) = self.configurations.get(&name) {
+            return *id;
+        }
+        let id = next_id(self.container.configuration.len());
+        self.container.configuration.push(Configuration {
+            id,
+            mnemonic: cfg.short_name().to_owned(),
+            platform_name: cfg.label().unwrap_or_default().to_owned(),
+            checksum: cfg.output_hash().to_string(),
+        });
+        self.configurations.insert(name, id);
+        id
+    }
+
+    fn dep_set(&mut self, direct_artifact_ids: Vec<u32>, transitive_dep_set_ids: Vec<u32>) -> u32 {
+        let id = next_id(self.container.dep_set_of_files.len());
+        self.container.dep_set_of_files.push(DepSetOfFiles {
+            id,
+            transitive_dep_set_ids,
+            direct_artifact_ids,
+        });
+        id
+    }
+
+    /// The dep set of a transitive set projection, after those of its children. Iterative, as
+    /// transitive sets can be deeper than the stack.
+    fn projection(&mut self, projection: &SetProjectionInputs) -> u32 {
+        let mut stack = vec![(projection, false)];
+        while let Some((node, children_added)) = stack.pop() {
+            if self.projections.contains_key(node) {
+                continue;
+            }
+            if !children_added {
+                stack.push((node, true));
+                for child in node.node.children.iter().rev() {
+                    if !self.projections.contains_key(child) {
+                        stack.push((child, false));
+                    }
+                }
+                continue;
+            }
+            let direct: Vec<u32> = node
+                .node
+                .direct
+                .iter()
+                .filter_map(|node| match node {
+                    ActionQueryNodeRef::Action(key) => self.outputs.get(key),
+                    ActionQueryNodeRef::Analysis(..) => None,
+                })
+                .flatten()
+                .copied()
+                .collect();
+            let transitive: Vec<u32> = node
+                .node
+                .children
+                .iter()
+                .map(|child| self.projections[child])
+                .collect();
+            let id = self.dep_set(direct, transitive);
+            self.projections.insert(node.dupe(), id);
+        }
+        self.projections[projection]
+    }
+}
+
+fn output_is_tree_artifact(output_type: OutputType) -> bool {
+    output_type == OutputType::Directory
+}
+
+/// The `analysis_v2` action graph of the actions among `nodes`.
+pub fn action_graph_container<'a>(
+    nodes: impl IntoIterator<Item = &'a ActionQueryNode>,
+) -> anyhow::Result<ActionGraphContainer> {
+    let actions: Vec<_> = nodes
+        .into_iter()
+        .filter_map(|node| match node.data() {
+            ActionQueryNodeData::Action(data) => Some(data),
+            ActionQueryNodeData::Analysis(..) => None,
+        })
+        .collect();
+
+    let mut builder = ActionGraphBuilder::default();
+
+    // Outputs first, so that inputs from transitive sets can refer to them.
+    for data in &actions {
+        let fs: &ArtifactFs = data.fs();
+        let action = data.action();
+        let mut output_ids = Vec::new();
+        for output in action.outputs()?.iter() {
+            let path = fs.resolve_build(output.get_path());
+            output_ids.push(builder.artifact(&path, output_is_tree_artifact(output.output_type())));
+        }
+        builder.outputs.insert(action.key().dupe(), output_ids);
+    }
+
+    for data in &actions {
+        let fs = data.fs();
+        let action = data.action();
+
+        let mut direct = Vec::new();
+        for input in action.inputs()?.iter() {
+            let artifact = match input {
+                ArtifactGroup::Artifact(artifact) => artifact,
+                ArtifactGroup::Promise(promise) => promise.get_err()?,
+                // Listed in the deps of the action, below.
+                ArtifactGroup::TransitiveSetProjection(..) => continue,
+            };
+            let path = artifact.get_path().resolve(fs)?;
+            direct.push(builder.artifact(&path, false));
+        }
+        let mut input_dep_set_ids = Vec::new();
+        if !direct.is_empty() {
+            input_dep_set_ids.push(builder.dep_set(direct, Vec::new()));
+        }
+        for dep in data.deps() {
+            if let ActionInput::IndirectInputs(projection) = dep {
+                input_dep_set_ids.push(builder.projection(projection));
+            }
+        }
+
+        let output_ids = builder.outputs[action.key()].clone();
+        let arguments = action.aquery_arguments(&ExecutorFs::new(
+            fs,
+            action.execution_config().options.path_separator,
+        ));
+        let target_id = builder.target(action.owner());
+        let configuration_id = builder.configuration(action.owner());
+        builder.container.actions.push(Action {
+            target_id,
+            action_key: action.key().to_string(),
+            mnemonic: action.category().as_str().to_owned(),
+            configuration_id,
+            arguments,
+            input_dep_set_ids,
+            primary_output_id: output_ids.first().copied().unwrap_or_default(),
+            output_ids,
+        });
+    }
+
+    Ok(builder.container)
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_artifact::deferred::data::DeferredData;
+    use buck2_artifact::deferred::id::DeferredId;
+    use buck2_artifact::deferred::key::DeferredKey;
+    use buck2_core::base_deferred_key::BaseDeferredKey;
+    use buck2_core::configuration::data::ConfigurationData;
+    use buck2_core::fs::project_rel_path::ProjectRelativePath;
+    use buck2_core::target::configured_target_label::ConfiguredTargetLabel;
+    use prost::Message;
+
+    use crate::actions::analysis_v2::ActionGraphBuilder;
+    use crate::actions::analysis_v2::ActionGraphContainer;
+    use crate::actions::query::SetProjectionInputs;
+    use crate::artifact_groups::TransitiveSetProjectionKey;
+
+    fn projection(id: u32, children: Vec<SetProjectionInputs>) -> SetProjectionInputs {
+        let target =
+            ConfiguredTargetLabel::testing_parse("root//foo:bar", ConfigurationData::testing_new());
+        SetProjectionInputs::new(
+            TransitiveSetProjectionKey {
+                key: DeferredData::unchecked_new(DeferredKey::Base(
+                    BaseDeferredKey::TargetLabel(target),
+                    DeferredId::testing_new(id),
+                )),
+                projection: 0,
+            },
+            Vec::new(),
+            children,
+        )
+    }
+
+    #[test]
+    fn test_path_fragments() {
+        let mut builder = ActionGraphBuilder::default();
+        let a = builder.artifact(ProjectRelativePath::unchecked_new("buck-out/v2/a.o"), false);
+        let b = builder.artifact(ProjectRelativePath::unchecked_new("buck-out/v2/b.o"), false);
+        assert_eq!(
+            a,
+            builder.artifact(ProjectRelativePath::unchecked_new("buck-out/v2/a.o"), false)
+        );
+        assert_ne!(a, b);
+
+        let fragments = &builder.container.path_fragments;
+        assert_eq!(4, fragments.len());
+        assert_eq!(
+            ("buck-out", 0),
+            (fragments[0].label.as_str(), fragments[0].parent_id)
+        );
+        assert_eq!(
+            ("v2", 1),
+            (fragments[1].label.as_str(), fragments[1].parent_id)
+        );
+        assert_eq!(
+            ("b.o", 2),
+            (fragments[3].label.as_str(), fragments[3].parent_id)
+        );
+    }
+
+    #[test]
+    fn test_encode() -> anyhow::Result<()> {
+        let mut builder = ActionGraphBuilder::default();
+        let a = builder.artifact(ProjectRelativePath::unchecked_new("foo/a.c"), false);
+        builder.dep_set(vec![a], Vec::new());
+        let container = builder.container;
+
+        let decoded = ActionGraphContainer::decode(container.encode_to_vec().as_slice())?;
+        assert_eq!(container, decoded);
+
+        let json = serde_json::to_value(&container)?;
+        assert_eq!(2, json["artifacts"][0]["pathFragmentId"]);
+        assert_eq!(1, json["depSetOfFiles"][0]["directArtifactIds"][0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_projection_shares_children() {
+        let shared = projection(0, Vec::new());
+        let left = projection(1, vec![shared.clone()]);
+        let right = projection(2, vec![shared.clone()]);
+        let root = projection(3, vec![left, right]);
+
+        let mut builder = ActionGraphBuilder::default();
+        let root_id = builder.projection(&root);
+
+        let dep_sets = &builder.container.dep_set_of_files;
+        assert_eq!(4, dep_sets.len());
+        assert_eq!(4, root_id);
+        assert_eq!(vec![2, 3], dep_sets[3].transitive_dep_set_ids);
+        assert_eq!(vec![1], dep_sets[1].transitive_dep_set_ids);
+        assert_eq!(vec![1], dep_sets[2].transitive_dep_set_ids);
+    }
+
+    #[test]
+    fn test_deep_projection() {
+        let mut set = projection(0, Vec::new());
+        for id in 1..100_000 {
+            set = projection(id, vec![set]);
+        }
+
+        let mut builder = ActionGraphBuilder::default();
+        assert_eq!(100_000, builder.projection(&set));
+        // Dropping the chain recursively would overflow the stack too.
+        std::mem::forget(builder);
+        std::mem::forget(set);
+    }
+}
@@ -19,10 +19,13 @@ use buck2_events::dispatch::current_span;
 use buck2_events::dispatch::span_async;
 use buck2_execute::artifact::artifact_dyn::ArtifactDyn;
 use buck2_execute::materialize::materializer::HasMaterializer;
+use buck2_futures::cancellation::CancellationContext;
 use dice::DiceComputations;
 use dupe::Dupe;
 
 use crate::actions::artifact::get_artifact_fs::GetArtifactFs;
+use crate::actions::calculation::expired_artifact_path;
+use crate::actions::calculation::rebuild_expired_action;
 use crate::build_signals::HasBuildSignals;
 
 #[async_trait]
@@ -66,7 +69,19 @@ impl ArtifactMaterializer for DiceComputations<'_> {
 
             let result: anyhow::Result<_> = try {
                 if required {
-                    materializer.ensure_materialized(vec![path]).await?;
+                    match materializer.ensure_materialized(vec![path.clone()]).await {
+                        Err(e) if expired_artifact_path(&e).is_some() => {
+                            // The artifact expired in the CAS: produce it again, then retry.
+                            rebuild_expired_action(
+                                self,
+                                artifact.key(),
+                                CancellationContext::never_cancelled(),
+                            )
+                            .await?;
+                            materializer.ensure_materialized(vec![path]).await?;
+                        }
+                        res => res?,
+                    }
                 } else {
                     materializer.try_materialize_final_artifact(path).await?;
                 }
@@ -7,6 +7,7 @@
  * of this source tree.
  */
 
+use std::collections::HashSet;
 use std::iter::zip;
 use std::sync::Arc;
 use std::time::Instant;
@@ -17,6 +18,10 @@ use buck2_artifact::actions::key::ActionKey;
 use buck2_artifact::artifact::build_artifact::BuildArtifact;
 use buck2_build_signals::NodeDuration;
 use buck2_common::events::HasEvents;
+use buck2_core::execution_types::executor_config::CommandExecutorConfig;
+use buck2_core::execution_types::executor_config::Executor;
+use buck2_core::execution_types::executor_config::RemoteEnabledExecutor;
+use buck2_core::fs::project_rel_path::ProjectRelativePath;
 use buck2_data::ActionErrorDiagnostics;
 use buck2_data::ActionSubErrors;
 use buck2_data::ToProtoMessage;
@@ -27,6 +32,7 @@ use buck2_events::dispatch::span_async;
 use buck2_events::span::SpanId;
 use buck2_execute::execute::result::CommandExecutionReport;
 use buck2_execute::execute::result::CommandExecutionStatus;
+use buck2_execute::materialize::materializer::MaterializationError;
 use buck2_execute::output_size::OutputSize;
 use buck2_futures::cancellation::CancellationContext;
 use buck2_interpreter::error::BuckStarlarkError;
@@ -36,6 +42,7 @@ use dice::DiceComputations;
 use dice::Key;
 use dupe::Dupe;
 use futures::future;
+use futures::future::BoxFuture;
 use futures::stream::FuturesOrdered;
 use futures::FutureExt;
 use indexmap::IndexMap;
@@ -45,15 +52,22 @@ use starlark::environment::Module;
 use starlark::eval::Evaluator;
 use tracing::debug;
 
+use crate::actions::artifact::get_artifact_fs::GetArtifactFs;
 use crate::actions::error::ActionError;
 use crate::actions::error_handler::ActionErrorHandlerError;
 use crate::actions::error_handler::ActionSubErrorResult;
 use crate::actions::error_handler::StarlarkActionErrorContext;
+use crate::actions::execute::action_executor::ActionExecutionMetadata;
+use crate::actions::execute::action_executor::ActionExecutor;
 use crate::actions::execute::action_executor::ActionOutputs;
 use crate::actions::execute::action_executor::HasActionExecutor;
+use crate::actions::execute::error::ExecuteError;
+use crate::actions::impls::run_action_knobs::HasRunActionKnobs;
 use crate::actions::key::ActionKeyExt;
 use crate::actions::RegisteredAction;
 use crate::artifact_groups::calculation::ensure_artifact_group_staged;
+use crate::artifact_groups::ArtifactGroup;
+use crate::artifact_groups::ArtifactGroupValues;
 use crate::deferred::calculation::DeferredCalculation;
 use crate::keep_going;
 use crate::starlark::values::type_repr::StarlarkTypeRepr;
@@ -67,6 +81,17 @@ pub trait ActionCalculation {
     async fn build_artifact(&self, artifact: &BuildArtifact) -> anyhow::Result<ActionOutputs>;
 }
 
+#[derive(Debug, buck2_error::Error)]
+enum ExpiredArtifactError {
+    #[error(
+        "Outputs of `{action}` expired in the CAS, and executing it again produced different \
+        contents for: {outputs}. The actions using them were given the previous contents, which \
+        are not available anymore, so this action must be deterministic to be executed again. \
+        To proceed, restart Buck using `buck2 killall`"
+    )]
+    NondeterministicRebuild { action: String, outputs: String },
+}
+
 async fn build_action_impl(
     ctx: &mut DiceComputations<'_>,
     cancellation: &CancellationContext<'_>,
@@ -96,30 +121,7 @@ async fn build_action_no_redirect(
     cancellation: &CancellationContext<'_>,
     action: Arc<RegisteredAction>,
 ) -> anyhow::Result<ActionOutputs> {
-    let materialized_inputs = {
-        let inputs = action.inputs()?;
-
-        let ensure_futs: FuturesOrdered<_> = inputs
-            .iter()
-            .map(|v| async {
-                let resolved = v.resolved_artifact(ctx).await?;
-                anyhow::Ok(
-                    ensure_artifact_group_staged(&mut ctx.bad_dice(), resolved.clone())
-                        .await?
-                        .to_group_values(&resolved)?,
-                )
-            })
-            .collect();
-
-        let ready_inputs: Vec<_> =
-            tokio::task::unconstrained(keep_going::try_join_all(ctx, ensure_futs)).await?;
-
-        let mut results = IndexMap::with_capacity(inputs.len());
-        for (artifact, ready) in zip(inputs.iter(), ready_inputs) {
-            results.insert(artifact.clone(), ready);
-        }
-        results
-    };
+    let materialized_inputs = action_inputs(ctx, &action).await?;
 
     let start_event = buck2_data::ActionExecutionStart {
         key: Some(action.key().as_proto()),
@@ -138,11 +140,17 @@ async fn build_action_no_redirect(
     let now = Instant::now();
     let action = &action;
 
-    let ctx = &*ctx;
+    let execution_ctx = &mut *ctx;
     let fut = async move {
-        let (execute_result, command_reports) = executor
-            .execute(materialized_inputs, action, cancellation)
-            .await;
+        let (execute_result, command_reports) = execute_rebuilding_expired_inputs(
+            execution_ctx,
+            &*executor,
+            materialized_inputs,
+            action,
+            cancellation,
+        )
+        .await;
+        let ctx = &*execution_ctx;
 
         let allow_omit_details = execute_result.is_ok();
 
@@ -311,6 +319,281 @@ async fn build_action_no_redirect(
     res
 }
 
+/// Build the inputs of an action, and stage them for its execution.
+async fn action_inputs(
+    ctx: &mut DiceComputations<'_>,
+    action: &RegisteredAction,
+) -> anyhow::Result<IndexMap<ArtifactGroup, ArtifactGroupValues>> {
+    let inputs = action.inputs()?;
+
+    let ensure_futs: FuturesOrdered<_> = inputs
+        .iter()
+        .map(|v| async {
+            let resolved = v.resolved_artifact(ctx).await?;
+            anyhow::Ok(
+                ensure_artifact_group_staged(&mut ctx.bad_dice(), resolved.clone())
+                    .await?
+                    .to_group_values(&resolved)?,
+            )
+        })
+        .collect();
+
+    let ready_inputs: Vec<_> =
+        tokio::task::unconstrained(keep_going::try_join_all(ctx, ensure_futs)).await?;
+
+    let mut results = IndexMap::with_capacity(inputs.len());
+    for (artifact, ready) in zip(inputs.iter(), ready_inputs) {
+        results.insert(artifact.clone(), ready);
+    }
+    Ok(results)
+}
+
+/// Keys of the actions producing the inputs of an action, building these inputs.
+pub async fn action_input_keys(
+    ctx: &mut DiceComputations<'_>,
+    action: &RegisteredAction,
+) -> anyhow::Result<Vec<ActionKey>> {
+    let inputs = action_inputs(ctx, action).await?;
+    Ok(inputs
+        .values()
+        .flat_map(|values| values.iter())
+        .filter_map(|(artifact, _)| artifact.action_key().cloned())
+        .collect())
+}
+
+/// Execute an action again, bypassing the caches, for `buck2 debug determinism`. The action is
+/// executed locally, with a perturbed environment, so returns `None` if it can only be executed
+/// remotely.
+///
+/// The new outputs replace the previous ones on disk and in the materializer, but not in DICE, so
+/// the caller is responsible for restoring the previous outputs if they differ.
+pub async fn reexecute_action(
+    ctx: &mut DiceComputations<'_>,
+    action: &Arc<RegisteredAction>,
+) -> anyhow::Result<Option<ActionOutputs>> {
+    let Some(executor_config) = local_executor_config(action.execution_config()) else {
+        return Ok(None);
+    };
+    let inputs = action_inputs(ctx, action).await?;
+
+    let mut run_action_knobs = ctx.per_transaction_data().get_run_action_knobs();
+    run_action_knobs.force_execution = true;
+    run_action_knobs.perturb_environment = true;
+    let executor = ctx
+        .get_action_executor_with_knobs(&executor_config, run_action_knobs)
+        .await
+        .context(format!("for action `{}`", action))?;
+
+    let (execute_result, command_reports) = executor
+        .execute(inputs, action, CancellationContext::never_cancelled())
+        .await;
+    match execute_result {
+        Ok((outputs, _)) => Ok(Some(outputs)),
+        Err(e) => {
+            let last_command = match command_reports.last() {
+                Some(report) => Some(command_execution_report_to_proto(report, false).await),
+                None => None,
+            };
+            let action_name = buck2_data::ActionName {
+                category: action.category().as_str().to_owned(),
+                identifier: action.identifier().unwrap_or("").to_owned(),
+            };
+            Err(
+                ActionError::new(e, action_name, action.key().as_proto(), last_command, None)
+                    .into(),
+            )
+        }
+    }
+}
+
+/// Execute an action. If materializing its inputs failed because some of them expired in the CAS,
+/// execute the actions producing these inputs again, then execute the action again.
+async fn execute_rebuilding_expired_inputs(
+    ctx: &mut DiceComputations<'_>,
+    executor: &dyn ActionExecutor,
+    inputs: IndexMap<ArtifactGroup, ArtifactGroupValues>,
+    action: &RegisteredAction,
+    cancellation: &CancellationContext<'_>,
+) -> (
+    Result<(ActionOutputs, ActionExecutionMetadata), ExecuteError>,
+    Vec<CommandExecutionReport>,
+) {
+    let mut rebuilt = HashSet::new();
+    loop {
+        let (execute_result, command_reports) =
+            executor.execute(inputs.clone(), action, cancellation).await;
+        if execute_result.is_ok() {
+            return (execute_result, command_reports);
+        }
+
+        let expired = match command_reports.last().map(|r| &r.status) {
+            Some(CommandExecutionStatus::Error { error, .. }) => expired_artifact_path(error),
+            _ => None,
+        };
+        let Some(expired) = expired else {
+            return (execute_result, command_reports);
+        };
+        let producer = match expired_input_producer(ctx, &inputs, expired).await {
+            Ok(Some(producer)) => producer,
+            Ok(None) => return (execute_result, command_reports),
+            Err(e) => {
+                debug!("Failed to find the action producing `{}`: {:#}", expired, e);
+                return (execute_result, command_reports);
+            }
+        };
+        // Don't loop if executing the producer again didn't make its outputs available.
+        if !rebuilt.insert(producer.dupe()) {
+            return (execute_result, command_reports);
+        }
+        if let Err(e) = rebuild_expired_action(ctx, &producer, cancellation).await {
+            debug!("Failed to execute `{}` again: {:#}", producer, e);
+            return (execute_result, command_reports);
+        }
+    }
+}
+
+/// The key of the action producing the input of an action which expired at `path`.
+async fn expired_input_producer(
+    ctx: &mut DiceComputations<'_>,
+    inputs: &IndexMap<ArtifactGroup, ArtifactGroupValues>,
+    path: &ProjectRelativePath,
+) -> anyhow::Result<Option<ActionKey>> {
+    let artifact_fs = ctx.get_artifact_fs().await?;
+    for values in inputs.values() {
+        for (artifact, _) in values.iter() {
+            if let Some(key) = artifact.action_key() {
+                let artifact_path = artifact.get_path().resolve(&artifact_fs)?;
+                if path.starts_with(&artifact_path) || artifact_path.starts_with(path) {
+                    return Ok(Some(key.dupe()));
+                }
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// The path of the artifact which expired in the CAS, if this is why `error` happened.
+pub(crate) fn expired_artifact_path(error: &anyhow::Error) -> Option<&ProjectRelativePath> {
+    error
+        .chain()
+        .find_map(|e| match e.downcast_ref::<MaterializationError>() {
+            Some(MaterializationError::NotFound { path, .. }) => Some(path.as_ref()),
+            _ => None,
+        })
+}
+
+/// Execute an action again, bypassing the caches, because some of its outputs expired in the CAS.
+/// Its inputs which expired too are rebuilt the same way, so this is transitive.
+///
+/// The action is expected to produce the same outputs again, because the actions depending on it
+/// have been given the previous ones.
+pub(crate) fn rebuild_expired_action<'a>(
+    ctx: &'a mut DiceComputations<'_>,
+    action_key: &'a ActionKey,
+    cancellation: &'a CancellationContext<'_>,
+) -> BoxFuture<'a, anyhow::Result<()>> {
+    // Boxed, because this and `execute_rebuilding_expired_inputs` call each other.
+    async move {
+        let action = ActionCalculation::get_action(ctx, action_key).await?;
+        let previous_outputs = ActionCalculation::build_action(ctx, action_key.dupe()).await?;
+        let inputs = action_inputs(ctx, &action).await?;
+
+        let mut run_action_knobs = ctx.per_transaction_data().get_run_action_knobs();
+        run_action_knobs.force_execution = true;
+        let executor = ctx
+            .get_action_executor_with_knobs(
+                &uncached_executor_config(action.execution_config()),
+                run_action_knobs,
+            )
+            .await
+            .context(format!("for action `{}`", action))?;
+
+        debug!(
+            "Executing `{}` again, its outputs expired in the CAS",
+            action
+        );
+        let (execute_result, _) =
+            execute_rebuilding_expired_inputs(ctx, &*executor, inputs, &action, cancellation).await;
+        let (outputs, _) = execute_result.map_err(|e| {
+            let action_name = buck2_data::ActionName {
+                category: action.category().as_str().to_owned(),
+                identifier: action.identifier().unwrap_or("").to_owned(),
+            };
+            ActionError::new(e, action_name, action.key().as_proto(), None, None)
+        })?;
+        check_rebuilt_outputs(&action.to_string(), &previous_outputs, &outputs)?;
+
+        ctx.per_transaction_data()
+            .get_dispatcher()
+            .counters()
+            .record_expired_artifact_rebuild();
+        Ok(())
+    }
+    .boxed()
+}
+
+/// Check that executing an action again because its outputs expired in the CAS produced the
+/// outputs the actions depending on it were given. Fails listing the outputs which changed
+/// otherwise.
+pub fn check_rebuilt_outputs(
+    action: &str,
+    previous: &ActionOutputs,
+    rebuilt: &ActionOutputs,
+) -> anyhow::Result<()> {
+    let changed: Vec<String> = previous
+        .iter()
+        .filter(|(path, value)| rebuilt.get(path) != Some(*value))
+        .map(|(path, _)| format!("`{}`", path.path()))
+        .collect();
+    if changed.is_empty() {
+        return Ok(());
+    }
+    Err(ExpiredArtifactError::NondeterministicRebuild {
+        action: action.to_owned(),
+        outputs: changed.join(", "),
+    }
+    .into())
+}
+
+/// `config` without the remote caches, so that its commands are executed.
+fn uncached_executor_config(config: &CommandExecutorConfig) -> CommandExecutorConfig {
+    let mut executor = config.executor.clone();
+    if let Executor::RemoteEnabled {
+        remote_cache_enabled,
+        remote_dep_file_cache_enabled,
+        ..
+    } = &mut executor
+    {
+        *remote_cache_enabled = false;
+        *remote_dep_file_cache_enabled = false;
+    }
+    CommandExecutorConfig {
+        executor,
+        options: config.options,
+        default_timeouts: config.default_timeouts.dupe(),
+        executor_preferences: config.executor_preferences.dupe(),
+    }
+}
+
+/// The executor config running the commands of `config` locally, if it allows it. This config has
+/// no cache.
+fn local_executor_config(config: &CommandExecutorConfig) -> Option<CommandExecutorConfig> {
+    let local = match &config.executor {
+        Executor::Local(local) => local,
+        Executor::RemoteEnabled { executor, .. } => match executor {
+            RemoteEnabledExecutor::Local(local) => local,
+            RemoteEnabledExecutor::Hybrid { local, .. } => local,
+            RemoteEnabledExecutor::Remote(_) => return None,
+        },
+    };
+    Some(CommandExecutorConfig {
+        executor: Executor::Local(local.dupe()),
+        options: config.options,
+        default_timeouts: config.default_timeouts.dupe(),
+        executor_preferences: config.executor_preferences.dupe(),
+    })
+}
+
 // Attempt to run the error handler if one was specified. Returns either the error diagnostics, or
 // an actual error if the handler failed to run successfully.
 fn try_run_error_handler(
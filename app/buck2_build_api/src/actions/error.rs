@@ -35,11 +35,19 @@ impl std::error::Error for ActionError {
                 Some(buck2_data::command_execution::Status::Failure { .. })
             )
         });
+        let is_command_timeout = self.last_command.as_ref().is_some_and(|c| {
+            matches!(
+                c.status,
+                Some(buck2_data::command_execution::Status::Timeout { .. })
+            )
+        });
 
         let typ = match &self.execute_error {
             ExecuteError::CommandExecutionError => {
                 if is_command_failure {
                     Some(buck2_error::ErrorType::ActionCommandFailure)
+                } else if is_command_timeout {
+                    Some(buck2_error::ErrorType::ActionTimeout)
                 } else {
                     None
                 }
@@ -49,7 +57,7 @@ impl std::error::Error for ActionError {
 
         let category = match &self.execute_error {
             ExecuteError::CommandExecutionError => {
-                if is_command_failure {
+                if is_command_failure || is_command_timeout {
                     Some(buck2_error::Category::User)
                 } else {
                     None
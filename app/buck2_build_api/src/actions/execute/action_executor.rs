@@ -10,6 +10,7 @@
 use std::fmt::Debug;
 use std::ops::ControlFlow;
 use std::sync::Arc;
+use std::time::Duration;
 
 use allocative::Allocative;
 use anyhow::Context;
@@ -20,9 +21,12 @@ use buck2_common::events::HasEvents;
 use buck2_common::http::HasHttpClient;
 use buck2_common::io::IoProvider;
 use buck2_common::liveliness_observer::NoopLivelinessObserver;
+use buck2_core::base_deferred_key::BaseDeferredKey;
+use buck2_core::execution_types::executor_config::CategoryExecutorPreference;
 use buck2_core::execution_types::executor_config::CommandExecutorConfig;
 use buck2_core::fs::artifact_path_resolver::ArtifactFs;
 use buck2_core::fs::buck_out_path::BuckOutPath;
+use buck2_core::fs::fs_util;
 use buck2_events::dispatch::EventDispatcher;
 use buck2_execute::artifact::fs::ExecutorFs;
 use buck2_execute::artifact_value::ArtifactValue;
@@ -39,6 +43,8 @@ use buck2_execute::execute::clean_output_paths::CleanOutputPaths;
 use buck2_execute::execute::command_executor::ActionExecutionTimingData;
 use buck2_execute::execute::command_executor::CommandExecutor;
 use buck2_execute::execute::dep_file_digest::DepFileDigest;
+use buck2_execute::execute::dispatch_gate::DispatchGate;
+use buck2_execute::execute::dispatch_gate::HasDispatchGate;
 use buck2_execute::execute::kind::CommandExecutionKind;
 use buck2_execute::execute::manager::CommandExecutionManager;
 use buck2_execute::execute::prepared::PreparedAction;
@@ -71,6 +77,7 @@ use crate::actions::execute::dice_data::CommandExecutorResponse;
 use crate::actions::execute::dice_data::DiceHasCommandExecutor;
 use crate::actions::execute::dice_data::GetReClient;
 use crate::actions::execute::error::ExecuteError;
+use crate::actions::execute::undeclared_inputs::undeclared_inputs_warning;
 use crate::actions::impls::run_action_knobs::HasRunActionKnobs;
 use crate::actions::impls::run_action_knobs::RunActionKnobs;
 use crate::actions::ActionExecutable;
@@ -230,6 +237,13 @@ pub trait HasActionExecutor {
         &mut self,
         config: &CommandExecutorConfig,
     ) -> anyhow::Result<Arc<dyn ActionExecutor>>;
+
+    /// Like `get_action_executor`, but with other knobs than those of the transaction.
+    async fn get_action_executor_with_knobs(
+        &mut self,
+        config: &CommandExecutorConfig,
+        run_action_knobs: RunActionKnobs,
+    ) -> anyhow::Result<Arc<dyn ActionExecutor>>;
 }
 
 #[async_trait]
@@ -237,6 +251,16 @@ impl HasActionExecutor for DiceComputations<'_> {
     async fn get_action_executor(
         &mut self,
         executor_config: &CommandExecutorConfig,
+    ) -> anyhow::Result<Arc<dyn ActionExecutor>> {
+        let run_action_knobs = self.per_transaction_data().get_run_action_knobs();
+        self.get_action_executor_with_knobs(executor_config, run_action_knobs)
+            .await
+    }
+
+    async fn get_action_executor_with_knobs(
+        &mut self,
+        executor_config: &CommandExecutorConfig,
+        run_action_knobs: RunActionKnobs,
     ) -> anyhow::Result<Arc<dyn ActionExecutor>> {
         let artifact_fs = self.get_artifact_fs().await?;
         let digest_config = self.global_data().get_digest_config();
@@ -251,10 +275,10 @@ impl HasActionExecutor for DiceComputations<'_> {
         let materializer = self.per_transaction_data().get_materializer();
         let events = self.per_transaction_data().get_dispatcher().dupe();
         let re_client = self.per_transaction_data().get_re_client();
-        let run_action_knobs = self.per_transaction_data().get_run_action_knobs();
         let io_provider = self.global_data().get_io_provider();
         let http_client = self.per_transaction_data().get_http_client();
         let mergebase = self.per_transaction_data().get_mergebase();
+        let dispatch_gate = self.per_transaction_data().get_dispatch_gate();
 
         Ok(Arc::new(BuckActionExecutor::new(
             CommandExecutor::new(
@@ -274,6 +298,7 @@ impl HasActionExecutor for DiceComputations<'_> {
             io_provider,
             http_client,
             mergebase,
+            dispatch_gate,
         )))
     }
 }
@@ -289,6 +314,7 @@ pub struct BuckActionExecutor {
     io_provider: Arc<dyn IoProvider>,
     http_client: HttpClient,
     mergebase: Mergebase,
+    dispatch_gate: Option<DispatchGate>,
 }
 
 impl BuckActionExecutor {
@@ -303,6 +329,7 @@ impl BuckActionExecutor {
         io_provider: Arc<dyn IoProvider>,
         http_client: HttpClient,
         mergebase: Mergebase,
+        dispatch_gate: Option<DispatchGate>,
     ) -> Self {
         Self {
             command_executor,
@@ -315,6 +342,33 @@ impl BuckActionExecutor {
             io_provider,
             http_client,
             mergebase,
+            dispatch_gate,
+        }
+    }
+
+    /// With the short buck-out path scheme, write the files recording which targets own the
+    /// directories of these outputs. They are only for humans, so errors are logged and ignored.
+    fn write_short_path_mappings(&self, outputs: &[BuildArtifact]) {
+        let fs = self.command_executor.fs();
+        for output in outputs {
+            let Some((path, contents)) = fs
+                .buck_out_path_resolver()
+                .short_path_mapping(output.get_path().owner(), output.get_path().action_key())
+            else {
+                continue;
+            };
+            let path = fs.fs().resolve(&path);
+            let res: anyhow::Result<()> = try {
+                if !fs_util::try_exists(&path)? {
+                    if let Some(dir) = path.parent() {
+                        fs_util::create_dir_all(dir)?;
+                    }
+                    fs_util::write(&path, contents)?;
+                }
+            };
+            if let Err(e) = res {
+                tracing::warn!("Error writing short buck-out path mapping: {:#}", e);
+            }
         }
     }
 }
@@ -375,7 +429,21 @@ impl ActionExecutionCtx for BuckActionExecutionContext<'_> {
     }
 
     fn run_action_knobs(&self) -> RunActionKnobs {
-        self.executor.run_action_knobs
+        self.executor.run_action_knobs.dupe()
+    }
+
+    fn default_timeout(&self) -> Option<Duration> {
+        self.action
+            .execution_config()
+            .default_timeouts
+            .get(self.action.category().as_str())
+    }
+
+    fn category_executor_preference(&self) -> Option<CategoryExecutorPreference> {
+        self.action
+            .execution_config()
+            .executor_preferences
+            .get(self.action.category().as_str())
     }
 
     fn cancellation_context(&self) -> &CancellationContext {
@@ -433,7 +501,26 @@ impl ActionExecutionCtx for BuckActionExecutionContext<'_> {
             dep_file_key,
             eligible_for_full_hybrid,
             dep_file_metadata: _,
+            undeclared_inputs,
         } = result;
+        if !undeclared_inputs.is_empty() {
+            let allowlisted = match self.action.owner() {
+                BaseDeferredKey::TargetLabel(label) => self
+                    .executor
+                    .run_action_knobs
+                    .undeclared_inputs_allowlist
+                    .contains(label.unconfigured()),
+                _ => false,
+            };
+            if !allowlisted {
+                self.executor
+                    .events
+                    .console_warning(undeclared_inputs_warning(
+                        self.action.owner(),
+                        &undeclared_inputs,
+                    ));
+            }
+        }
         // TODO (@torozco): The execution kind should be made to come via the command reports too.
         let res = match &report.status {
             CommandExecutionStatus::Success { execution_kind } => {
@@ -577,8 +664,14 @@ impl ActionExecutor for BuckActionExecutor {
     ) {
         let mut command_reports = Vec::new();
 
+        // Hold back new work while the user has paused dispatch from the console.
+        if let Some(dispatch_gate) = &self.dispatch_gate {
+            dispatch_gate.wait().await;
+        }
+
         let res = async {
             let outputs = action.outputs()?;
+            self.write_short_path_mappings(outputs.as_ref());
 
             let mut ctx = BuckActionExecutionContext {
                 executor: self,
@@ -790,6 +883,7 @@ mod tests {
                 .unwrap()
                 .build(),
             Default::default(),
+            None,
         );
 
         #[derive(Debug, Allocative)]
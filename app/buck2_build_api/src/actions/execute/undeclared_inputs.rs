@@ -0,0 +1,125 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Reporting of the files which actions read without declaring them as inputs, found when
+//! `build.trace_undeclared_inputs` is set.
+//!
+//! Targets known to have undeclared inputs can be listed in `build.undeclared_inputs_allowlist`,
+//! so that the warnings only report new ones while the existing ones are fixed.
+
+use std::fmt::Write;
+use std::sync::Arc;
+
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use buck2_core::target::label::TargetLabel;
+use dupe::Dupe;
+
+/// Number of files listed in a warning.
+const MAX_LISTED_FILES: usize = 10;
+
+/// Targets whose undeclared inputs are not reported.
+///
+/// Entries are target labels like `cell//pkg:name`, `cell//pkg:` for all the targets of a
+/// package, or `cell//pkg/...` for all the targets of a package and its subpackages.
+#[derive(Clone, Dupe, Default)]
+pub struct UndeclaredInputsAllowlist(Arc<Vec<String>>);
+
+impl UndeclaredInputsAllowlist {
+    pub fn new(entries: Vec<String>) -> UndeclaredInputsAllowlist {
+        UndeclaredInputsAllowlist(Arc::new(
+            entries
+                .into_iter()
+                .map(|e| e.trim().to_owned())
+                .filter(|e| !e.is_empty())
+                .collect(),
+        ))
+    }
+
+    pub(crate) fn contains(&self, target: &TargetLabel) -> bool {
+        let pkg = target.pkg().to_string();
+        self.0.iter().any(|entry| {
+            if let Some(prefix) = entry.strip_suffix("...") {
+                // `cell//...` and `cell//pkg/...`.
+                format!("{}/", pkg).starts_with(prefix)
+            } else if let Some(entry_pkg) = entry.strip_suffix(':') {
+                pkg == entry_pkg
+            } else {
+                target.to_string() == *entry
+            }
+        })
+    }
+}
+
+/// Warning for the undeclared inputs of an action of `target`.
+pub(crate) fn undeclared_inputs_warning(
+    target: &dyn std::fmt::Display,
+    files: &[ProjectRelativePathBuf],
+) -> String {
+    let mut warning = format!(
+        "Action of `{}` read {} file(s) which are not declared as inputs:",
+        target,
+        files.len()
+    );
+    for file in files.iter().take(MAX_LISTED_FILES) {
+        write!(warning, "\n  {}", file).unwrap();
+    }
+    if files.len() > MAX_LISTED_FILES {
+        write!(
+            warning,
+            "\n  ...and {} more",
+            files.len() - MAX_LISTED_FILES
+        )
+        .unwrap();
+    }
+    write!(
+        warning,
+        "\nDeclare them as inputs, or add the target to `build.undeclared_inputs_allowlist` to \
+        silence this warning."
+    )
+    .unwrap();
+    warning
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allowlist() {
+        let allowlist = UndeclaredInputsAllowlist::new(vec![
+            "root//foo:bar".to_owned(),
+            " root//baz: ".to_owned(),
+            "root//qux/...".to_owned(),
+            "other//...".to_owned(),
+        ]);
+        let contains = |label| allowlist.contains(&TargetLabel::testing_parse(label));
+
+        assert!(contains("root//foo:bar"));
+        assert!(!contains("root//foo:baz"));
+        assert!(contains("root//baz:anything"));
+        assert!(!contains("root//baz/sub:anything"));
+        assert!(contains("root//qux:a"));
+        assert!(contains("root//qux/sub:a"));
+        assert!(!contains("root//quxx:a"));
+        assert!(contains("other//:a"));
+        assert!(contains("other//deep/pkg:a"));
+        assert!(!contains("root//:a"));
+    }
+
+    #[test]
+    fn test_warning() {
+        let files = (0..12)
+            .map(|i| ProjectRelativePathBuf::unchecked_new(format!("foo/{}.h", i)))
+            .collect::<Vec<_>>();
+        let warning = undeclared_inputs_warning(&"root//foo:bar", &files);
+        assert!(warning.starts_with("Action of `root//foo:bar` read 12 file(s)"));
+        assert!(warning.contains("\n  foo/9.h\n  ...and 2 more\n"));
+        assert!(!warning.contains("foo/10.h"));
+    }
+}
@@ -10,8 +10,10 @@
 use dice::UserComputationData;
 use dupe::Dupe;
 
+use crate::actions::execute::undeclared_inputs::UndeclaredInputsAllowlist;
+
 /// Knobs controlling how RunAction works.
-#[derive(Copy, Clone, Dupe, Default)]
+#[derive(Clone, Dupe, Default)]
 pub struct RunActionKnobs {
     /// Process dep files as they are generated.
     pub eager_dep_files: bool,
@@ -24,6 +26,24 @@ pub struct RunActionKnobs {
     /// for network actions (download_file, cas_artifact). Used to support offline
     /// builds.
     pub use_network_action_output_cache: bool,
+
+    /// Fail network actions which can't be satisfied from a local cache (`--offline`).
+    pub offline: bool,
+
+    /// Skip the local dep file cache lookups, so that actions are executed again even if their
+    /// outputs are already known (`buck2 debug determinism`).
+    pub force_execution: bool,
+
+    /// Vary the environment order and temporary directory of local executions, to find actions
+    /// depending on them (`buck2 debug determinism`).
+    pub perturb_environment: bool,
+
+    /// Targets whose undeclared inputs are not reported (`build.undeclared_inputs_allowlist`).
+    pub undeclared_inputs_allowlist: UndeclaredInputsAllowlist,
+
+    /// Contents of write actions larger than this are streamed to the output file instead of
+    /// being collected in memory (`build.write_action_max_in_memory_bytes`).
+    pub write_action_max_in_memory_bytes: Option<u64>,
 }
 
 pub trait HasRunActionKnobs {
@@ -38,9 +58,9 @@ impl HasRunActionKnobs for UserComputationData {
     }
 
     fn get_run_action_knobs(&self) -> RunActionKnobs {
-        *self
-            .data
+        self.data
             .get::<RunActionKnobs>()
             .expect("RunActionKnobs should be set")
+            .dupe()
     }
 }
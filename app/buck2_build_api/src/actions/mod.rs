@@ -27,6 +27,7 @@ use std::borrow::Cow;
 use std::fmt::Debug;
 use std::ops::ControlFlow;
 use std::sync::Arc;
+use std::time::Duration;
 
 use allocative::Allocative;
 use async_trait::async_trait;
@@ -37,6 +38,7 @@ use buck2_artifact::artifact::provide_outputs::ProvideOutputs;
 use buck2_common::io::IoProvider;
 use buck2_core::base_deferred_key::BaseDeferredKey;
 use buck2_core::category::Category;
+use buck2_core::execution_types::executor_config::CategoryExecutorPreference;
 use buck2_core::execution_types::executor_config::CommandExecutorConfig;
 use buck2_core::fs::artifact_path_resolver::ArtifactFs;
 use buck2_core::fs::paths::forward_rel_path::ForwardRelativePathBuf;
@@ -75,6 +77,7 @@ use crate::artifact_groups::ArtifactGroupValues;
 use crate::deferred::types::AnyValue;
 use crate::deferred::types::TrivialDeferred;
 
+pub mod analysis_v2;
 pub mod artifact;
 pub mod box_slice_set;
 pub mod calculation;
@@ -156,6 +159,12 @@ pub trait Action: Allocative + Debug + Send + Sync + 'static {
         indexmap! {}
     }
 
+    /// The command line this action runs, for exports of the action graph. Empty for actions
+    /// which don't run a command.
+    fn aquery_arguments(&self, _fs: &ExecutorFs) -> Vec<String> {
+        Vec::new()
+    }
+
     /// error handler
     fn error_handler(&self) -> Option<OwnedFrozenValue> {
         None
@@ -263,6 +272,12 @@ pub trait ActionExecutionCtx: Send + Sync {
     /// Obtain per-command knobs for RunAction.
     fn run_action_knobs(&self) -> RunActionKnobs;
 
+    /// The timeout of the commands of this action if it doesn't set one, from the executor config.
+    fn default_timeout(&self) -> Option<Duration>;
+
+    /// Where to run the commands of this action, by its category, from the executor config.
+    fn category_executor_preference(&self) -> Option<CategoryExecutorPreference>;
+
     fn cancellation_context(&self) -> &CancellationContext;
 
     /// I/O layer access to add non-source files (e.g. downloaded files) to
@@ -286,6 +301,12 @@ pub enum ActionErrors {
         "Multiple artifacts and/or metadata files are declared at conflicting output locations. Output path `{0}` conflicts with the following output paths: {1:?}."
     )]
     ConflictingOutputPaths(ForwardRelativePathBuf, Vec<String>),
+    #[error(
+        "Output path `{0}` differs only by case from the output path `{1}`, so they are the same \
+        file on case-insensitive file systems (set `buck2.check_case_collisions = false` to allow \
+        this)."
+    )]
+    CaseCollidingOutputPaths(ForwardRelativePathBuf, ForwardRelativePathBuf),
     #[error(
         "Action category `{0}` contains duplicate identifier `{1}`; category-identifier pairs must be unique within a rule"
     )]
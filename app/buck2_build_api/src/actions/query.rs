@@ -219,6 +219,18 @@ pub struct ActionData {
 }
 
 impl ActionData {
+    pub(crate) fn action(&self) -> &Arc<RegisteredAction> {
+        &self.action
+    }
+
+    pub(crate) fn deps(&self) -> &[ActionInput] {
+        &self.deps
+    }
+
+    pub(crate) fn fs(&self) -> &ArtifactFs {
+        &self.fs
+    }
+
     fn attrs(&self) -> IndexMap<String, String> {
         let mut attrs = self.action.action().aquery_attributes(&ExecutorFs::new(
             &self.fs,
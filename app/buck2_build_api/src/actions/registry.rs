@@ -7,6 +7,7 @@
  * of this source tree.
  */
 
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::sync::Arc;
@@ -27,6 +28,7 @@ use buck2_core::directory::DirectoryIterator;
 use buck2_core::directory::NoDigest;
 use buck2_core::execution_types::execution::ExecutionPlatformResolution;
 use buck2_core::fs::buck_out_path::BuckOutPath;
+use buck2_core::fs::case_collisions;
 use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
 use buck2_core::fs::paths::forward_rel_path::ForwardRelativePathBuf;
 use buck2_execute::execute::request::OutputType;
@@ -56,6 +58,9 @@ pub struct ActionsRegistry {
     )>,
     execution_platform: ExecutionPlatformResolution,
     claimed_output_paths: DirectoryBuilder<Option<FileSpan>, NoDigest>,
+    /// Claimed output paths and their ancestors, by case-folded path, when checking for paths
+    /// which differ only by case.
+    case_folded_output_paths: Option<HashMap<String, ForwardRelativePathBuf>>,
 }
 
 impl ActionsRegistry {
@@ -67,9 +72,40 @@ impl ActionsRegistry {
             pending: Default::default(),
             execution_platform,
             claimed_output_paths: DirectoryBuilder::empty(),
+            case_folded_output_paths: case_collisions::enabled_by_default().then(HashMap::new),
         }
     }
 
+    pub fn set_check_case_collisions(&mut self, check: bool) {
+        self.case_folded_output_paths = check.then(HashMap::new);
+    }
+
+    fn check_case_collisions(&mut self, path: &ForwardRelativePath) -> anyhow::Result<()> {
+        let Some(folded_paths) = &mut self.case_folded_output_paths else {
+            return Ok(());
+        };
+        let mut prefix = String::new();
+        for name in path.iter() {
+            if !prefix.is_empty() {
+                prefix.push('/');
+            }
+            prefix.push_str(name.as_str());
+            match folded_paths.entry(case_collisions::case_fold(&prefix)) {
+                Entry::Occupied(e) if e.get().as_str() != prefix => {
+                    return Err(anyhow::anyhow!(ActionErrors::CaseCollidingOutputPaths(
+                        path.to_owned(),
+                        e.get().clone(),
+                    )));
+                }
+                Entry::Occupied(_) => {}
+                Entry::Vacant(e) => {
+                    e.insert(ForwardRelativePathBuf::unchecked_new(prefix.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub fn set_action_key(&mut self, action_key: Arc<str>) {
         self.action_key = Some(action_key);
     }
@@ -94,6 +130,8 @@ impl ActionsRegistry {
             location.map_or(&"<unknown>" as _, |l| l as _)
         }
 
+        self.check_case_collisions(path)?;
+
         match self
             .claimed_output_paths
             .insert(path, DirectoryEntry::Leaf(declaration_location))
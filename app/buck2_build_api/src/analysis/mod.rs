@@ -28,6 +28,7 @@ pub mod calculation;
 pub mod dynamic_lambda_params;
 pub mod extra_v;
 pub mod registry;
+pub mod shared_strings;
 
 use allocative::Allocative;
 use dupe::Dupe;
@@ -57,6 +57,7 @@ use crate::analysis::anon_targets_registry::AnonTargetsRegistryDyn;
 use crate::analysis::anon_targets_registry::ANON_TARGET_REGISTRY_NEW;
 use crate::analysis::extra_v::AnalysisExtraValue;
 use crate::analysis::extra_v::FrozenAnalysisExtraValue;
+use crate::analysis::shared_strings::analysis_shared_strings;
 use crate::artifact_groups::promise::PromiseArtifact;
 use crate::artifact_groups::promise::PromiseArtifactId;
 use crate::artifact_groups::registry::ArtifactGroupRegistry;
@@ -129,6 +130,11 @@ impl<'v> AnalysisRegistry<'v> {
         self.actions.set_action_key(action_key);
     }
 
+    /// Whether declaring artifacts whose paths differ only by case is an error.
+    pub fn set_check_case_collisions(&mut self, check: bool) {
+        self.actions.set_check_case_collisions(check);
+    }
+
     /// Reserves a path in an output directory. Doesn't declare artifact,
     /// but checks that there is no previously declared artifact with a path
     /// which is in conflict with claimed `path`.
@@ -322,7 +328,10 @@ impl<'v> AnalysisRegistry<'v> {
 
         analysis_value_storage.write_to_module(env)?;
         Ok(move |env: Module| {
-            let frozen_env = env.freeze()?;
+            let frozen_env = match analysis_shared_strings()? {
+                Some(shared_strings) => env.freeze_sharing_strings(shared_strings)?,
+                None => env.freeze()?,
+            };
             let analysis_value_fetcher = AnalysisValueFetcher {
                 frozen_module: Some(frozen_env.dupe()),
             };
@@ -0,0 +1,40 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Strings shared by the frozen heaps of all analysis results, so that the strings which are equal
+//! across targets (flags, toolchain paths, labels stored in providers, ...) are stored once.
+//!
+//! Shared strings are never freed, so the table is bounded by
+//! `BUCK2_ANALYSIS_SHARED_STRINGS_MAX_BYTES`, which is read when the daemon analyses its first
+//! target. Setting it to 0 disables sharing.
+
+use buck2_core::buck2_env;
+use once_cell::sync::OnceCell;
+use starlark::values::string::SharedFrozenStrings;
+use starlark::values::string::SharedFrozenStringsStats;
+
+/// Longer strings are rarely equal across targets.
+const MAX_LEN: usize = 256;
+const DEFAULT_MAX_BYTES: usize = 64 << 20;
+
+static SHARED_STRINGS: OnceCell<Option<SharedFrozenStrings>> = OnceCell::new();
+
+pub fn analysis_shared_strings() -> anyhow::Result<Option<&'static SharedFrozenStrings>> {
+    let shared = SHARED_STRINGS.get_or_try_init(|| {
+        let max_bytes = buck2_env!("BUCK2_ANALYSIS_SHARED_STRINGS_MAX_BYTES", type=usize)?
+            .unwrap_or(DEFAULT_MAX_BYTES);
+        anyhow::Ok((max_bytes > 0).then(|| SharedFrozenStrings::new(MAX_LEN, max_bytes)))
+    })?;
+    Ok(shared.as_ref())
+}
+
+/// Statistics of the shared strings, if any analysis ran with them.
+pub fn analysis_shared_strings_stats() -> Option<SharedFrozenStringsStats> {
+    SHARED_STRINGS.get()?.as_ref().map(|s| s.stats())
+}
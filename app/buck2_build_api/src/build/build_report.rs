@@ -22,6 +22,7 @@ use anyhow::Context as _;
 use buck2_core::cells::CellResolver;
 use buck2_core::configuration::compatibility::MaybeCompatible;
 use buck2_core::configuration::data::ConfigurationData;
+use buck2_core::error::CommandSoftErrors;
 use buck2_core::fs::artifact_path_resolver::ArtifactFs;
 use buck2_core::fs::fs_util;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
@@ -33,7 +34,10 @@ use buck2_core::provider::label::NonDefaultProvidersName;
 use buck2_core::provider::label::ProvidersLabel;
 use buck2_core::provider::label::ProvidersName;
 use buck2_core::target::label::TargetLabel;
+use buck2_error::ErrorCode;
 use buck2_error::UniqueRootId;
+use buck2_events::dispatch::CommandCounters;
+use buck2_events::dispatch::EventDispatcher;
 use buck2_events::errors::create_error_report;
 use buck2_execute::artifact::artifact_dyn::ArtifactDyn;
 use buck2_wrapper_common::invocation_id::TraceId;
@@ -75,6 +79,17 @@ pub struct BuildReport {
     project_root: AbsNormPathBuf,
     truncated: bool,
     strings: BTreeMap<String, String>,
+    /// How many times each soft error category was hit during this command
+    soft_errors: BTreeMap<String, u64>,
+    /// How many times each deprecation category was used at each call site during this command
+    deprecations: BTreeMap<String, BTreeMap<String, u64>>,
+    /// Number of errors in `results` with each error code
+    error_codes: BTreeMap<String, u64>,
+    /// How many times RE executions were retried after transient errors during this command
+    re_retries: u64,
+    /// How many actions were executed again during this command because their outputs expired
+    /// in the CAS
+    expired_artifact_rebuilds: u64,
 }
 
 /// The fields that stored in the unconfigured `BuildReportEntry` for buck1 backcompat.
@@ -138,6 +153,8 @@ struct BuildReportEntry {
 struct BuildReportError {
     message_content: String,
     action_error: Option<BuildReportActionError>,
+    /// Stable code identifying the kind of error, see `buck2_error::ErrorCode`
+    error_code: String,
     /// An opaque index that can be use to de-duplicate errors. Two errors with the same
     /// cause index have the same cause
     ///
@@ -174,11 +191,14 @@ pub struct BuildReportCollector<'a> {
     failures: HashMap<EntryLabel, String>,
     include_failures: bool,
     include_package_project_relative_paths: bool,
+    error_codes: BTreeMap<String, u64>,
 }
 
 impl<'a> BuildReportCollector<'a> {
     pub fn convert(
         trace_id: &TraceId,
+        soft_errors: &CommandSoftErrors,
+        counters: &CommandCounters,
         artifact_fs: &'a ArtifactFs,
         cell_resolver: &'a CellResolver,
         project_root: &ProjectRoot,
@@ -201,6 +221,7 @@ impl<'a> BuildReportCollector<'a> {
             failures: HashMap::default(),
             include_failures,
             include_package_project_relative_paths,
+            error_codes: BTreeMap::default(),
         };
         let mut entries = HashMap::new();
 
@@ -246,6 +267,11 @@ impl<'a> BuildReportCollector<'a> {
             // Setting this to false since we don't currently truncate buck2's build report.
             truncated: false,
             strings: this.strings,
+            soft_errors: soft_errors.counts(),
+            deprecations: soft_errors.deprecation_call_site_counts(),
+            error_codes: this.error_codes,
+            re_retries: counters.re_retries(),
+            expired_artifact_rebuilds: counters.expired_artifact_rebuilds(),
         }
     }
 
@@ -433,6 +459,7 @@ impl<'a> BuildReportCollector<'a> {
             cause_index: Option<usize>,
             message: String,
             action_error: Option<BuildReportActionError>,
+            error_code: ErrorCode,
         }
 
         let mut temp = Vec::with_capacity(errors.len());
@@ -453,6 +480,7 @@ impl<'a> BuildReportCollector<'a> {
                 action_error: e
                     .action_error()
                     .map(|e| BuildReportActionError::new(e, self)),
+                error_code: e.error_code(),
             });
         }
         // Sort the errors. This sort *almost* guarantees full determinism, but unfortunately
@@ -495,10 +523,15 @@ impl<'a> BuildReportCollector<'a> {
             };
 
             let message_content = self.update_string_cache(info.message.clone());
+            *self
+                .error_codes
+                .entry(info.error_code.as_str().to_owned())
+                .or_default() += 1;
 
             out.push(BuildReportError {
                 message_content,
                 action_error: info.action_error,
+                error_code: info.error_code.as_str().to_owned(),
                 cause_index,
             });
         }
@@ -540,12 +573,14 @@ pub fn generate_build_report(
     cell_resolver: &CellResolver,
     project_root: &ProjectRoot,
     cwd: &ProjectRelativePath,
-    trace_id: &TraceId,
+    events: &EventDispatcher,
     configured: &BTreeMap<ConfiguredProvidersLabel, Option<ConfiguredBuildTargetResult>>,
     other_errors: &BTreeMap<Option<ProvidersLabel>, Vec<buck2_error::Error>>,
 ) -> Result<String, buck2_error::Error> {
     let build_report = BuildReportCollector::convert(
-        trace_id,
+        events.trace_id(),
+        events.soft_errors(),
+        events.counters(),
         artifact_fs,
         cell_resolver,
         project_root,
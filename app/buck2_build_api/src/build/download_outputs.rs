@@ -0,0 +1,124 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Which outputs `buck2 build` downloads, set by `[build] download_outputs` in the root cell.
+//!
+//! With deferred materialization, outputs of remote actions stay in the CAS until something needs
+//! them locally: a local action using them as inputs, or the build requesting them as outputs of
+//! its targets. This policy decides the latter, and can be changed for each build with `-c`,
+//! unlike `[buck2] materializations`, which is read when the daemon starts.
+
+use std::str::FromStr;
+
+use buck2_cli_proto::build_request::Materializations;
+use buck2_common::legacy_configs::dice::HasLegacyConfigs;
+use buck2_core::cells::name::CellName;
+use dice::DiceComputations;
+use dupe::Dupe;
+
+const SECTION: &str = "build";
+const DOWNLOAD_OUTPUTS: &str = "download_outputs";
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(user)]
+enum DownloadOutputsError {
+    #[error(
+        "Invalid value for buckconfig `[build] download_outputs`. Got `{0}`. Expected one of `top_level` or `minimal`."
+    )]
+    InvalidValue(String),
+}
+
+#[derive(Debug, Clone, Copy, Dupe, Default, PartialEq, Eq)]
+pub enum DownloadOutputs {
+    /// Download the outputs of the targets requested on the command line.
+    #[default]
+    TopLevel,
+    /// Don't download the outputs of the requested targets either, unless the build is run with
+    /// `--materializations=materialize`. They can be fetched on demand later, with
+    /// `buck2 debug materialize`, and are fetched when a local action needs them.
+    Minimal,
+}
+
+impl FromStr for DownloadOutputs {
+    type Err = DownloadOutputsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "top_level" => Ok(DownloadOutputs::TopLevel),
+            "minimal" => Ok(DownloadOutputs::Minimal),
+            _ => Err(DownloadOutputsError::InvalidValue(s.to_owned())),
+        }
+    }
+}
+
+impl DownloadOutputs {
+    pub async fn from_config(
+        ctx: &DiceComputations<'_>,
+        root_cell: CellName,
+    ) -> anyhow::Result<DownloadOutputs> {
+        Ok(ctx
+            .parse_legacy_config_property(root_cell, SECTION, DOWNLOAD_OUTPUTS)
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// How to materialize the outputs of the requested targets, given the `--materializations`
+    /// of the build. Only the default is affected by this policy.
+    pub fn final_artifact_materializations(self, requested: Materializations) -> Materializations {
+        match (self, requested) {
+            (DownloadOutputs::Minimal, Materializations::Default) => Materializations::Skip,
+            (_, requested) => requested,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(
+            DownloadOutputs::TopLevel,
+            "top_level".parse::<DownloadOutputs>().unwrap()
+        );
+        assert_eq!(
+            DownloadOutputs::Minimal,
+            "minimal".parse::<DownloadOutputs>().unwrap()
+        );
+        assert!("all".parse::<DownloadOutputs>().is_err());
+    }
+
+    #[test]
+    fn test_final_artifact_materializations() {
+        for requested in [
+            Materializations::Default,
+            Materializations::Materialize,
+            Materializations::Skip,
+        ] {
+            assert_eq!(
+                requested,
+                DownloadOutputs::TopLevel.final_artifact_materializations(requested)
+            );
+        }
+
+        assert_eq!(
+            Materializations::Skip,
+            DownloadOutputs::Minimal.final_artifact_materializations(Materializations::Default)
+        );
+        assert_eq!(
+            Materializations::Materialize,
+            DownloadOutputs::Minimal.final_artifact_materializations(Materializations::Materialize)
+        );
+        assert_eq!(
+            Materializations::Skip,
+            DownloadOutputs::Minimal.final_artifact_materializations(Materializations::Skip)
+        );
+    }
+}
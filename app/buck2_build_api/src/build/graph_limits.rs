@@ -0,0 +1,338 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Limits on the size of the configured graph of a build, checked before analysis so that an
+//! accidentally huge build (e.g. `//...` in a large repository) fails fast instead of taking
+//! down a shared daemon.
+//!
+//! Configured in the `[graph_limits]` buckconfig section of the root cell.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::sync::Mutex;
+
+use buck2_common::legacy_configs::dice::HasLegacyConfigs;
+use buck2_core::cells::name::CellName;
+use buck2_core::configuration::compatibility::MaybeCompatible;
+use buck2_core::target::configured_target_label::ConfiguredTargetLabel;
+use buck2_node::nodes::configured::ConfiguredTargetNode;
+use buck2_node::nodes::configured_frontend::ConfiguredTargetNodeCalculation;
+use buck2_util::dominators::dominators;
+use dice::DiceComputations;
+use dupe::Dupe;
+use dupe::IterDupedExt;
+
+const SECTION: &str = "graph_limits";
+const MAX_CONFIGURED_NODES: &str = "max_configured_nodes";
+const MAX_DEPS_PER_TARGET: &str = "max_deps_per_target";
+const MAX_TRANSITIVE_SRCS: &str = "max_transitive_srcs";
+
+/// Number of targets listed in the dominator report of a failure.
+const TOP_DOMINATORS: usize = 10;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(user)]
+enum GraphLimitsError {
+    #[error(
+        "Building `{target}` brings the number of configured targets in this build to {count}, \
+        more than the limit of {limit} set by `graph_limits.max_configured_nodes`.\n\
+        Targets through which most of the graph is reached:\n{report}"
+    )]
+    TooManyConfiguredNodes {
+        target: ConfiguredTargetLabel,
+        count: u64,
+        limit: u64,
+        report: String,
+    },
+    #[error(
+        "Target `{target}` (a dependency of `{root}`) has {count} dependencies, more than the \
+        limit of {limit} set by `graph_limits.max_deps_per_target`"
+    )]
+    TooManyDeps {
+        target: ConfiguredTargetLabel,
+        root: ConfiguredTargetLabel,
+        count: u64,
+        limit: u64,
+    },
+    #[error(
+        "Target `{target}` has {count} transitive source files, more than the limit of {limit} \
+        set by `graph_limits.max_transitive_srcs`.\n\
+        Dependencies through which most of the source files are reached:\n{report}"
+    )]
+    TooManyTransitiveSrcs {
+        target: ConfiguredTargetLabel,
+        count: u64,
+        limit: u64,
+        report: String,
+    },
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GraphLimits {
+    /// Maximum number of distinct configured targets in the graphs of all the targets built.
+    pub max_configured_nodes: Option<u64>,
+    /// Maximum number of direct dependencies of any configured target.
+    pub max_deps_per_target: Option<u64>,
+    /// Maximum number of source files in the graph of any target built.
+    pub max_transitive_srcs: Option<u64>,
+}
+
+impl GraphLimits {
+    pub async fn from_config(
+        ctx: &DiceComputations<'_>,
+        root_cell: CellName,
+    ) -> anyhow::Result<GraphLimits> {
+        Ok(GraphLimits {
+            max_configured_nodes: ctx
+                .parse_legacy_config_property(root_cell, SECTION, MAX_CONFIGURED_NODES)
+                .await?,
+            max_deps_per_target: ctx
+                .parse_legacy_config_property(root_cell, SECTION, MAX_DEPS_PER_TARGET)
+                .await?,
+            max_transitive_srcs: ctx
+                .parse_legacy_config_property(root_cell, SECTION, MAX_TRANSITIVE_SRCS)
+                .await?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == GraphLimits::default()
+    }
+}
+
+/// Checks the targets of one build against `GraphLimits`.
+#[derive(Debug)]
+pub struct GraphLimitsChecker {
+    limits: GraphLimits,
+    state: Mutex<CheckerState>,
+}
+
+#[derive(Default, Debug)]
+struct CheckerState {
+    /// Top-level targets checked so far.
+    roots: Vec<ConfiguredTargetNode>,
+    /// All the targets in their graphs.
+    visited: HashSet<ConfiguredTargetNode>,
+}
+
+impl GraphLimitsChecker {
+    pub fn new(limits: GraphLimits) -> GraphLimitsChecker {
+        GraphLimitsChecker {
+            limits,
+            state: Mutex::new(CheckerState::default()),
+        }
+    }
+
+    /// Check the graph of a target about to be built. Incompatible targets are not checked.
+    pub(crate) async fn check(
+        &self,
+        ctx: &mut DiceComputations<'_>,
+        target: &ConfiguredTargetLabel,
+    ) -> anyhow::Result<()> {
+        let node = match ctx.get_configured_target_node(target).await? {
+            MaybeCompatible::Compatible(node) => node,
+            MaybeCompatible::Incompatible(_) => return Ok(()),
+        };
+        self.check_node(node)
+    }
+
+    fn check_node(&self, node: ConfiguredTargetNode) -> anyhow::Result<()> {
+        if let Some(limit) = self.limits.max_transitive_srcs {
+            let mut count = 0;
+            for n in transitive_closure(&node) {
+                count += n.inputs().count() as u64;
+            }
+            if count > limit {
+                return Err(GraphLimitsError::TooManyTransitiveSrcs {
+                    target: node.label().dupe(),
+                    count,
+                    limit,
+                    report: dominator_report(&[node], |n| n.inputs().count() as u64, "sources"),
+                }
+                .into());
+            }
+        }
+
+        let mut state = self.state.lock().unwrap();
+        state.roots.push(node.dupe());
+        let mut queue = vec![node.dupe()];
+        while let Some(n) = queue.pop() {
+            if !state.visited.insert(n.dupe()) {
+                continue;
+            }
+            if let Some(limit) = self.limits.max_deps_per_target {
+                let count = n.deps().count() as u64;
+                if count > limit {
+                    return Err(GraphLimitsError::TooManyDeps {
+                        target: n.label().dupe(),
+                        root: node.label().dupe(),
+                        count,
+                        limit,
+                    }
+                    .into());
+                }
+            }
+            queue.extend(n.deps().duped());
+        }
+
+        if let Some(limit) = self.limits.max_configured_nodes {
+            let count = state.visited.len() as u64;
+            if count > limit {
+                return Err(GraphLimitsError::TooManyConfiguredNodes {
+                    target: node.label().dupe(),
+                    count,
+                    limit,
+                    report: dominator_report(&state.roots, |_| 1, "targets"),
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+}
+
+fn transitive_closure(node: &ConfiguredTargetNode) -> HashSet<&ConfiguredTargetNode> {
+    let mut queue = vec![node];
+    let mut visited = HashSet::new();
+    while let Some(n) = queue.pop() {
+        if visited.insert(n) {
+            queue.extend(n.deps());
+        }
+    }
+    visited
+}
+
+fn intern<'a>(
+    node: &'a ConfiguredTargetNode,
+    nodes: &mut Vec<Option<&'a ConfiguredTargetNode>>,
+    index: &mut HashMap<&'a ConfiguredTargetNode, usize>,
+    succs: &mut Vec<Vec<usize>>,
+    queue: &mut Vec<usize>,
+) -> usize {
+    *index.entry(node).or_insert_with(|| {
+        nodes.push(Some(node));
+        succs.push(Vec::new());
+        queue.push(nodes.len() - 1);
+        nodes.len() - 1
+    })
+}
+
+/// List the targets which dominate the most `weight` in the graph of `roots`: everything they
+/// dominate is only reachable through them, so they are where to look to shrink the graph.
+fn dominator_report(
+    roots: &[ConfiguredTargetNode],
+    weight: impl Fn(&ConfiguredTargetNode) -> u64,
+    unit: &str,
+) -> String {
+    // Node 0 is a virtual root pointing to all the roots.
+    let mut nodes: Vec<Option<&ConfiguredTargetNode>> = vec![None];
+    let mut index: HashMap<&ConfiguredTargetNode, usize> = HashMap::new();
+    let mut succs: Vec<Vec<usize>> = vec![Vec::new()];
+    let mut queue = Vec::new();
+
+    for root in roots {
+        let i = intern(root, &mut nodes, &mut index, &mut succs, &mut queue);
+        succs[0].push(i);
+    }
+    while let Some(i) = queue.pop() {
+        let node = nodes[i].unwrap();
+        for dep in node.deps() {
+            let j = intern(dep, &mut nodes, &mut index, &mut succs, &mut queue);
+            succs[i].push(j);
+        }
+    }
+
+    let (postorder, idom) = dominators(&succs);
+    let mut retained: Vec<u64> = nodes.iter().map(|n| n.map_or(0, &weight)).collect();
+    for &n in &postorder {
+        if n != 0 {
+            retained[idom[n]] += retained[n];
+        }
+    }
+
+    let mut top: Vec<usize> = (1..nodes.len()).collect();
+    top.sort_by_key(|&n| std::cmp::Reverse(retained[n]));
+    let mut out = String::new();
+    for n in top.into_iter().take(TOP_DOMINATORS) {
+        writeln!(
+            out,
+            "  {} {}: {}",
+            retained[n],
+            unit,
+            nodes[n].unwrap().label()
+        )
+        .unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_core::configuration::data::ConfigurationData;
+
+    use super::*;
+
+    fn node(name: &str, deps: &[&ConfiguredTargetNode]) -> ConfiguredTargetNode {
+        ConfiguredTargetNode::testing_new_with_deps(
+            ConfiguredTargetLabel::testing_parse(name, ConfigurationData::testing_new()),
+            "foo_lib",
+            deps.iter().map(|d| (*d).dupe()).collect(),
+        )
+    }
+
+    #[test]
+    fn test_max_configured_nodes() {
+        let shared = node("root//:shared", &[]);
+        let big_deps: Vec<_> = (0..5)
+            .map(|i| node(&format!("root//:big{}", i), &[]))
+            .collect();
+        let big = node("root//:big", &big_deps.iter().collect::<Vec<_>>());
+        let a = node("root//:a", &[&shared]);
+        let b = node("root//:b", &[&shared, &big]);
+
+        let checker = GraphLimitsChecker::new(GraphLimits {
+            max_configured_nodes: Some(5),
+            ..GraphLimits::default()
+        });
+        checker.check_node(a).unwrap();
+        let err = format!("{:#}", checker.check_node(b).unwrap_err());
+        assert!(err.contains("to 9, more than the limit of 5"), "{}", err);
+        // `big` dominates itself and its 5 deps.
+        assert!(err.contains("  7 targets: root//:b"), "{}", err);
+        assert!(err.contains("  6 targets: root//:big"), "{}", err);
+    }
+
+    #[test]
+    fn test_max_deps_per_target() {
+        let deps: Vec<_> = (0..3)
+            .map(|i| node(&format!("root//:d{}", i), &[]))
+            .collect();
+        let wide = node("root//:wide", &deps.iter().collect::<Vec<_>>());
+        let top = node("root//:top", &[&wide]);
+
+        let checker = GraphLimitsChecker::new(GraphLimits {
+            max_deps_per_target: Some(2),
+            ..GraphLimits::default()
+        });
+        let err = format!("{:#}", checker.check_node(top).unwrap_err());
+        assert!(
+            err.contains("Target `root//:wide")
+                && err.contains("has 3 dependencies, more than the limit of 2"),
+            "{}",
+            err
+        );
+
+        let checker = GraphLimitsChecker::new(GraphLimits {
+            max_deps_per_target: Some(3),
+            ..GraphLimits::default()
+        });
+        checker.check_node(node("root//:top2", &[&wide])).unwrap();
+    }
+}
@@ -49,6 +49,9 @@ use crate::artifact_groups::ArtifactGroup;
 use crate::artifact_groups::ArtifactGroupValues;
 use crate::artifact_groups::ResolvedArtifactGroup;
 use crate::artifact_groups::ResolvedArtifactGroupBuildSignalsKey;
+use crate::build::graph_limits::GraphLimitsChecker;
+use crate::build::speculative::Speculation;
+use crate::build::speculative::SpeculativeBuilds;
 use crate::build_signals::HasBuildSignals;
 use crate::interpreter::rule_defs::cmd_args::AbsCommandLineContext;
 use crate::interpreter::rule_defs::cmd_args::CommandLineArgLike;
@@ -59,7 +62,10 @@ use crate::keep_going;
 
 mod action_error;
 pub mod build_report;
+pub mod download_outputs;
+pub mod graph_limits;
 mod graph_size;
+pub mod speculative;
 /// The types of provider to build on the configured providers label
 #[derive(Debug, Clone, Dupe, Allocative)]
 pub enum BuildProviderType {
@@ -248,10 +254,14 @@ pub enum BuildEvent {
     },
 }
 
-#[derive(Copy, Clone, Dupe, Debug)]
+#[derive(Clone, Dupe, Debug)]
 pub struct BuildConfiguredLabelOptions {
     pub skippable: bool,
     pub want_configured_graph_size: bool,
+    /// Checked before analysis of the target.
+    pub graph_limits: Option<Arc<GraphLimitsChecker>>,
+    /// Builds the dependencies of the target while it is analyzed.
+    pub speculative_builds: Option<Arc<SpeculativeBuilds>>,
 }
 
 pub async fn build_configured_label<'a>(
@@ -288,11 +298,25 @@ async fn build_configured_label_inner<'a>(
 ) -> anyhow::Result<BoxStream<'a, ConfiguredBuildEvent>> {
     let artifact_fs = ctx.bad_dice().get_artifact_fs().await?;
 
+    if let Some(graph_limits) = &opts.graph_limits {
+        graph_limits
+            .check(&mut ctx.bad_dice(), providers_label.target())
+            .await?;
+    }
+
+    let mut speculation = match &opts.speculative_builds {
+        Some(speculative_builds) => {
+            speculative_builds
+                .start(ctx, providers_label.target())
+                .await
+        }
+        None => Speculation::none(),
+    };
+
     let (outputs, run_args, target_rule_type_name) = {
         // A couple of these objects aren't Send and so scope them here so async transform doesn't get concerned.
-        let providers = match ctx
-            .bad_dice()
-            .get_providers(providers_label.as_ref())
+        let providers = match speculation
+            .run_until(ctx.bad_dice().get_providers(providers_label.as_ref()))
             .await?
         {
             MaybeCompatible::Incompatible(reason) => {
@@ -457,6 +481,7 @@ async fn build_configured_label_inner<'a>(
                 variant: ConfiguredBuildEventVariant::Output { index, output },
             }
         });
+    let outputs = speculation.alongside(outputs);
 
     let stream = futures::stream::once(futures::future::ready(ConfiguredBuildEvent {
         label: providers_label.dupe(),
@@ -0,0 +1,279 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Speculative builds of the dependencies of the targets being built.
+//!
+//! The outputs of a target are only requested once its analysis, and so the analysis of all its
+//! transitive dependencies, is done, and the actions of the dependencies only through the inputs
+//! of those outputs. So for a large binary, no action is looked up in the cache before the last
+//! dependency is analyzed.
+//!
+//! With `buck2.speculative_build_concurrency` set, the default outputs of the dependencies of a
+//! target are requested as soon as the analysis of each dependency is done, leaves first, while
+//! the target is still analyzed. They are built but not materialized, and the actions the target
+//! later needs are then already looked up in the cache, or running. Outputs the target doesn't
+//! need are built for nothing, which is what the configured concurrency bounds: it is the number of
+//! dependencies built speculatively at once for the whole build. No speculative build starts after
+//! the analysis of the target, and the ones still running when its outputs are built are dropped.
+
+use std::collections::HashSet;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use buck2_artifact::artifact::artifact_type::Artifact;
+use buck2_common::legacy_configs::dice::HasLegacyConfigs;
+use buck2_core::cells::name::CellName;
+use buck2_core::configuration::compatibility::MaybeCompatible;
+use buck2_core::provider::label::ConfiguredProvidersLabel;
+use buck2_core::target::configured_target_label::ConfiguredTargetLabel;
+use buck2_node::nodes::configured::ConfiguredTargetNode;
+use buck2_node::nodes::configured_frontend::ConfiguredTargetNodeCalculation;
+use dice::DiceComputations;
+use dupe::Dupe;
+use futures::future::Either;
+use futures::stream::BoxStream;
+use futures::Future;
+use futures::Stream;
+use futures::StreamExt;
+use tokio::sync::Semaphore;
+
+use crate::analysis::calculation::RuleAnalysisCalculation;
+use crate::artifact_groups::calculation::ArtifactGroupCalculation;
+use crate::artifact_groups::ArtifactGroup;
+use crate::interpreter::rule_defs::provider::collection::FrozenProviderCollectionValue;
+
+const SECTION: &str = "buck2";
+const CONCURRENCY: &str = "speculative_build_concurrency";
+
+/// The speculative builds of one build command, shared by all its targets.
+#[derive(Debug)]
+pub struct SpeculativeBuilds {
+    concurrency: usize,
+    permits: Semaphore,
+    /// The dependencies claimed by a target so far, which the other targets don't build again.
+    claimed: Mutex<HashSet<ConfiguredTargetLabel>>,
+}
+
+impl SpeculativeBuilds {
+    /// `None` if speculative builds are not enabled.
+    pub async fn from_config(
+        ctx: &DiceComputations<'_>,
+        root_cell: CellName,
+    ) -> anyhow::Result<Option<Arc<SpeculativeBuilds>>> {
+        let concurrency: usize = ctx
+            .parse_legacy_config_property(root_cell, SECTION, CONCURRENCY)
+            .await?
+            .unwrap_or(0);
+        Ok((concurrency > 0).then(|| Arc::new(SpeculativeBuilds::new(concurrency))))
+    }
+
+    pub fn new(concurrency: usize) -> SpeculativeBuilds {
+        SpeculativeBuilds {
+            concurrency,
+            permits: Semaphore::new(concurrency),
+            claimed: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// The transitive dependencies of `node` not claimed yet, each after its own dependencies,
+    /// since those are analyzed first.
+    fn claim_deps(&self, node: &ConfiguredTargetNode) -> Vec<ConfiguredTargetLabel> {
+        let mut visited = HashSet::new();
+        let mut postorder = Vec::new();
+        let mut stack = vec![(node, false)];
+        while let Some((n, expanded)) = stack.pop() {
+            if expanded {
+                postorder.push(n);
+            } else if visited.insert(n) {
+                stack.push((n, true));
+                stack.extend(n.deps().map(|dep| (dep, false)));
+            }
+        }
+
+        let mut claimed = self.claimed.lock().unwrap();
+        postorder
+            .into_iter()
+            .filter(|n| *n != node && claimed.insert(n.label().dupe()))
+            .map(|n| n.label().dupe())
+            .collect()
+    }
+
+    /// Starts the speculative builds of the dependencies of `target`, which make progress while
+    /// the returned `Speculation` is polled.
+    pub(crate) async fn start<'a>(
+        self: &Arc<Self>,
+        ctx: &'a DiceComputations<'_>,
+        target: &ConfiguredTargetLabel,
+    ) -> Speculation<'a> {
+        let node = match ctx.bad_dice().get_configured_target_node(target).await {
+            Ok(MaybeCompatible::Compatible(node)) => node,
+            // Nothing of an incompatible target is built, and errors are reported by the build.
+            _ => return Speculation::none(),
+        };
+        let stopped = Arc::new(AtomicBool::new(false));
+        let builds = futures::stream::iter(self.claim_deps(&node))
+            .take_while({
+                let stopped = stopped.dupe();
+                move |_| futures::future::ready(!stopped.load(Ordering::Relaxed))
+            })
+            .map({
+                let this = self.dupe();
+                let stopped = stopped.dupe();
+                move |dep| this.dupe().build(ctx, dep, stopped.dupe())
+            })
+            .buffer_unordered(self.concurrency)
+            .boxed();
+        Speculation { builds, stopped }
+    }
+
+    async fn build(
+        self: Arc<Self>,
+        ctx: &DiceComputations<'_>,
+        dep: ConfiguredTargetLabel,
+        stopped: Arc<AtomicBool>,
+    ) {
+        // Waiting for the analysis doesn't count towards the concurrency, only building does.
+        let outputs = match ctx
+            .bad_dice()
+            .get_providers(&ConfiguredProvidersLabel::default_for(dep))
+            .await
+        {
+            Ok(MaybeCompatible::Compatible(providers)) => default_outputs(&providers),
+            // The builds needing the dependency, if any, report the error.
+            _ => return,
+        };
+        let Ok(_permit) = self.permits.acquire().await else {
+            return;
+        };
+        if stopped.load(Ordering::Relaxed) {
+            return;
+        }
+        futures::future::join_all(outputs.into_iter().map(|output| async move {
+            // The same, so the builds needing it report the error.
+            let _ignored = ctx
+                .ensure_artifact_group(&ArtifactGroup::Artifact(output))
+                .await;
+        }))
+        .await;
+    }
+}
+
+fn default_outputs(providers: &FrozenProviderCollectionValue) -> Vec<Artifact> {
+    let mut outputs = Vec::new();
+    // A `DefaultInfo` which can't be read only loses the speculation, the builds reading it fail.
+    let _ignored = providers
+        .provider_collection()
+        .default_info()
+        .for_each_default_output_artifact_only(&mut |output| {
+            outputs.push(output);
+            Ok(())
+        });
+    outputs
+}
+
+/// The speculative builds started for one target.
+pub(crate) struct Speculation<'a> {
+    builds: BoxStream<'a, ()>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl<'a> Speculation<'a> {
+    pub(crate) fn none() -> Speculation<'a> {
+        Speculation {
+            builds: futures::stream::empty().boxed(),
+            stopped: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    /// Runs the speculative builds until `fut`, typically the analysis of the target, is done.
+    pub(crate) async fn run_until<F: Future>(&mut self, fut: F) -> F::Output {
+        let builds = self
+            .builds
+            .by_ref()
+            .for_each(|()| futures::future::ready(()));
+        match futures::future::select(Box::pin(fut), builds).await {
+            Either::Left((res, _)) => res,
+            Either::Right(((), fut)) => fut.await,
+        }
+    }
+
+    /// Stops starting speculative builds, and runs the ones already started alongside `stream`
+    /// until it ends, since it likely needs some of them.
+    pub(crate) fn alongside<S>(self, stream: S) -> impl Stream<Item = S::Item> + 'a
+    where
+        S: Stream + Send + 'a,
+        S::Item: Send + 'a,
+    {
+        self.stopped.store(true, Ordering::Relaxed);
+        let builds = self.builds.filter_map(|()| futures::future::ready(None));
+        let stream = stream
+            .map(Some)
+            .chain(futures::stream::once(futures::future::ready(None)));
+        futures::stream::select(stream, builds)
+            .take_while(|item| futures::future::ready(item.is_some()))
+            .filter_map(futures::future::ready)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_core::configuration::data::ConfigurationData;
+
+    use super::*;
+
+    fn node(name: &str, deps: &[&ConfiguredTargetNode]) -> ConfiguredTargetNode {
+        ConfiguredTargetNode::testing_new_with_deps(
+            ConfiguredTargetLabel::testing_parse(name, ConfigurationData::testing_new()),
+            "foo_lib",
+            deps.iter().map(|d| (*d).dupe()).collect(),
+        )
+    }
+
+    fn names(labels: Vec<ConfiguredTargetLabel>) -> Vec<String> {
+        labels
+            .iter()
+            .map(|l| l.name().as_str().to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_claim_deps() {
+        let leaf = node("root//:leaf", &[]);
+        let a = node("root//:a", &[&leaf]);
+        let b = node("root//:b", &[&leaf]);
+        let top = node("root//:top", &[&a, &b]);
+        let other = node("root//:other", &[&a, &node("root//:c", &[])]);
+
+        let builds = SpeculativeBuilds::new(2);
+        let deps = names(builds.claim_deps(&top));
+        // Each dependency once, after its own dependencies, and not the target itself.
+        assert_eq!(3, deps.len());
+        assert_eq!("leaf", deps[0]);
+        assert!(deps.contains(&"a".to_owned()) && deps.contains(&"b".to_owned()));
+        // Dependencies claimed by another target are not built again.
+        assert_eq!(vec!["c"], names(builds.claim_deps(&other)));
+    }
+
+    #[tokio::test]
+    async fn test_alongside_ends_with_stream() {
+        let speculation = Speculation {
+            builds: futures::stream::pending().boxed(),
+            stopped: Arc::new(AtomicBool::new(false)),
+        };
+        let stopped = speculation.stopped.dupe();
+        let items: Vec<_> = speculation
+            .alongside(futures::stream::iter([1, 2]))
+            .collect()
+            .await;
+        assert_eq!(vec![1, 2], items);
+        assert!(stopped.load(Ordering::Relaxed));
+    }
+}
@@ -16,6 +16,8 @@ use buck2_common::legacy_configs::dice::SetLegacyConfigs;
 use buck2_common::legacy_configs::LegacyBuckConfig;
 use buck2_execute::digest_config::DigestConfig;
 use buck2_execute::digest_config::SetDigestConfig;
+use buck2_interpreter::bzl_cache::BzlCache;
+use buck2_interpreter::bzl_cache::SetBzlCache;
 use dice::DetectCycles;
 use dice::Dice;
 use dice::WhichDice;
@@ -25,6 +27,7 @@ use dice::WhichDice;
 pub async fn configure_dice_for_buck(
     io: Arc<dyn IoProvider>,
     digest_config: DigestConfig,
+    bzl_cache: Option<Arc<BzlCache>>,
     root_config: Option<&LegacyBuckConfig>,
     detect_cycles: Option<DetectCycles>,
     which_dice: Option<WhichDice>,
@@ -56,6 +59,7 @@ pub async fn configure_dice_for_buck(
     };
     dice.set_io_provider(io);
     dice.set_digest_config(digest_config);
+    dice.set_bzl_cache(bzl_cache);
 
     let dice = dice.build(detect_cycles);
     let mut dice_ctx = dice.updater();
@@ -14,6 +14,7 @@ use std::sync::Arc;
 use allocative::Allocative;
 use async_trait::async_trait;
 use buck2_core::fs::buck_out_path::BuckOutPathResolver;
+use buck2_core::fs::buck_out_path::BuckOutPathScheme;
 use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
 use derive_more::Display;
 use dice::DiceComputations;
@@ -27,12 +28,21 @@ pub trait HasBuildContextData {
 }
 
 pub trait SetBuildContextData {
-    fn set_buck_out_path(&mut self, path: Option<ProjectRelativePathBuf>) -> anyhow::Result<()>;
+    fn set_buck_out_path(&mut self, path: Option<ProjectRelativePathBuf>) -> anyhow::Result<()> {
+        self.set_buck_out_path_with_scheme(path, BuckOutPathScheme::default())
+    }
+
+    fn set_buck_out_path_with_scheme(
+        &mut self,
+        path: Option<ProjectRelativePathBuf>,
+        scheme: BuckOutPathScheme,
+    ) -> anyhow::Result<()>;
 }
 
 #[derive(PartialEq, Eq, Allocative)]
 pub struct BuildData {
     buck_out_path: ProjectRelativePathBuf,
+    buck_out_path_scheme: BuckOutPathScheme,
 }
 
 #[derive(Clone, Dupe, Display, Debug, Eq, Hash, PartialEq, Allocative)]
@@ -51,18 +61,26 @@ impl InjectedKey for BuildDataKey {
 impl HasBuildContextData for DiceComputations<'_> {
     async fn get_buck_out_path(&mut self) -> anyhow::Result<BuckOutPathResolver> {
         let data = self.compute(&BuildDataKey).await?;
-        Ok(BuckOutPathResolver::new(data.buck_out_path.to_buf()))
+        Ok(BuckOutPathResolver::with_scheme(
+            data.buck_out_path.to_buf(),
+            data.buck_out_path_scheme,
+        ))
     }
 }
 
 impl SetBuildContextData for DiceTransactionUpdater {
-    fn set_buck_out_path(&mut self, path: Option<ProjectRelativePathBuf>) -> anyhow::Result<()> {
+    fn set_buck_out_path_with_scheme(
+        &mut self,
+        path: Option<ProjectRelativePathBuf>,
+        scheme: BuckOutPathScheme,
+    ) -> anyhow::Result<()> {
         Ok(self.changed_to(vec![(
             BuildDataKey,
             Arc::new(BuildData {
                 buck_out_path: path.unwrap_or_else(|| {
                     ProjectRelativePathBuf::unchecked_new("buck-out/v2".to_owned())
                 }),
+                buck_out_path_scheme: scheme,
             }),
         )])?)
     }
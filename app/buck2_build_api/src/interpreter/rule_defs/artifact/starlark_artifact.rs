@@ -13,6 +13,8 @@ use allocative::Allocative;
 use buck2_artifact::artifact::artifact_type::Artifact;
 use buck2_artifact::artifact::artifact_type::BaseArtifactKind;
 use buck2_core::base_deferred_key::BaseDeferredKey;
+use buck2_core::fs::paths::file_name::FileName;
+use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
 use buck2_core::provider::label::ConfiguredProvidersLabel;
 use buck2_core::provider::label::ProvidersName;
 use buck2_execute::path::artifact_path::ArtifactPath;
@@ -26,6 +28,7 @@ use starlark::environment::Methods;
 use starlark::environment::MethodsBuilder;
 use starlark::environment::MethodsStatic;
 use starlark::typing::Ty;
+use starlark::values::list::AllocList;
 use starlark::values::list::ListOf;
 use starlark::values::starlark_value;
 use starlark::values::type_repr::StarlarkTypeRepr;
@@ -41,6 +44,7 @@ use crate::artifact_groups::ArtifactGroup;
 use crate::interpreter::rule_defs::artifact::associated::AssociatedArtifacts;
 use crate::interpreter::rule_defs::artifact::starlark_artifact_like::ArtifactFingerprint;
 use crate::interpreter::rule_defs::artifact::starlark_artifact_like::ValueAsArtifactLike;
+use crate::interpreter::rule_defs::artifact::starlark_promise_artifact::StarlarkPromiseArtifact;
 use crate::interpreter::rule_defs::artifact::ArtifactError;
 use crate::interpreter::rule_defs::artifact::StarlarkArtifactLike;
 use crate::interpreter::rule_defs::artifact::StarlarkDeclaredArtifact;
@@ -253,8 +257,6 @@ impl<'v> StarlarkValue<'v> for StarlarkArtifact {
 enum CannotProject {
     #[error("Source artifacts cannot be projected")]
     SourceArtifact,
-    #[error("This artifact was declared by another rule: `{0}`")]
-    DeclaredElsewhere(BaseDeferredKey),
 }
 
 pub(crate) struct StarlarkArtifactHelpers;
@@ -283,6 +285,14 @@ impl StarlarkArtifactHelpers {
         })
     }
 
+    /// The base name of this artifact without its extension. e.g. for an artifact at
+    /// `foo/bar.sh`, this is `bar`.
+    pub(crate) fn stem<'v>(artifact: &Artifact, heap: &'v Heap) -> anyhow::Result<StringValue<'v>> {
+        artifact
+            .get_path()
+            .with_filename(|filename| Ok(StarlarkArtifactHelpers::alloc_stem(filename?, heap)))
+    }
+
     /// The `Label` of the rule that originally created this artifact. May also be None in
     /// the case of source files, or if the artifact has not be used in an action, or if the
     /// action was not created by a rule.
@@ -317,6 +327,29 @@ impl StarlarkArtifactHelpers {
             Some(x) => heap.alloc_str_concat(".", x),
         }
     }
+
+    pub(crate) fn alloc_stem<'v>(filename: &FileName, heap: &'v Heap) -> StringValue<'v> {
+        heap.alloc_str(filename.file_stem().unwrap_or(filename.as_str()))
+    }
+
+    /// The artifacts materialized along with an artifact. Associated transitive set projections
+    /// are not listed.
+    pub(crate) fn associated_artifacts<'v>(
+        associated_artifacts: &AssociatedArtifacts,
+        heap: &'v Heap,
+    ) -> Value<'v> {
+        heap.alloc(AllocList(associated_artifacts.iter().filter_map(
+            |group| match group {
+                ArtifactGroup::Artifact(artifact) => {
+                    Some(heap.alloc(StarlarkArtifact::new(artifact.dupe())))
+                }
+                ArtifactGroup::Promise(promise) => {
+                    Some(heap.alloc(StarlarkPromiseArtifact::new(None, promise.dupe(), None)))
+                }
+                ArtifactGroup::TransitiveSetProjection(_) => None,
+            },
+        )))
+    }
 }
 
 /// A single input or output file for an action.
@@ -338,6 +371,21 @@ fn artifact_methods(builder: &mut MethodsBuilder) {
         StarlarkArtifactHelpers::extension(&this.artifact, heap)
     }
 
+    /// The base name of this artifact without its extension. e.g. for an artifact at
+    /// `foo/bar.sh`, this is `bar`. Together with `extension`, this is the `basename`.
+    #[starlark(attribute)]
+    fn stem<'v>(this: &StarlarkArtifact, heap: &Heap) -> anyhow::Result<StringValue<'v>> {
+        StarlarkArtifactHelpers::stem(&this.artifact, heap)
+    }
+
+    /// The size of the artifact in bytes, if it is known. Artifacts are not built during
+    /// analysis, so this is always `None` there; the values of built artifacts in
+    /// `dynamic_output` know their size.
+    #[starlark(attribute)]
+    fn size_hint(#[starlark(this)] _this: &StarlarkArtifact) -> anyhow::Result<Option<u64>> {
+        Ok(None)
+    }
+
     /// Whether the artifact represents a source file
     #[starlark(attribute)]
     fn is_source(this: &StarlarkArtifact) -> anyhow::Result<bool> {
@@ -383,22 +431,42 @@ fn artifact_methods(builder: &mut MethodsBuilder) {
     /// yields the file bar. It is possible for projected artifacts to hide the prefix in order to
     /// have the short name of the resulting artifact only contain the projected path, by passing
     /// `hide_prefix = True` to `project()`.
+    ///
+    /// This also works for artifacts declared by other rules, e.g. to use one header of a
+    /// generated directory as an input without copying it. If the path doesn't exist in the
+    /// directory, actions using the projected artifact fail once the directory is built.
     fn project<'v>(
         this: &'v StarlarkArtifact,
         #[starlark(require = pos)] path: &str,
         #[starlark(require = named, default = false)] hide_prefix: bool,
     ) -> anyhow::Result<StarlarkArtifact> {
-        let _ignored = hide_prefix;
-
-        let err = anyhow::Error::from(match this.artifact.owner() {
-            Some(owner) => CannotProject::DeclaredElsewhere(owner.dupe()),
-            None => CannotProject::SourceArtifact,
-        });
+        if this.artifact.is_source() {
+            return Err(
+                anyhow::Error::from(CannotProject::SourceArtifact).context(format!(
+                    "Cannot project path `{}` in artifact `{}`",
+                    path, this
+                )),
+            );
+        }
+        let path = ForwardRelativePath::new(path)?;
+        Ok(StarlarkArtifact {
+            artifact: this.artifact.project(path, hide_prefix),
+            associated_artifacts: this.associated_artifacts.dupe(),
+        })
+    }
 
-        Err(err.context(format!(
-            "Cannot project path `{}` in artifact `{}`",
-            path, this
-        )))
+    /// The artifacts which are materialized along with this artifact wherever it is used, e.g.
+    /// debug info or source maps, as added by `with_associated_artifacts`. Associated transitive
+    /// set projections are not listed.
+    #[starlark(attribute)]
+    fn associated_artifacts<'v>(
+        this: &StarlarkArtifact,
+        heap: &'v Heap,
+    ) -> anyhow::Result<Value<'v>> {
+        Ok(StarlarkArtifactHelpers::associated_artifacts(
+            &this.associated_artifacts,
+            heap,
+        ))
     }
 
     /// Returns a `StarlarkArtifact` instance which is identical to the original artifact, except
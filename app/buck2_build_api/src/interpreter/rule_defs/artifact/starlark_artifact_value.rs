@@ -105,6 +105,17 @@ fn artifact_value_methods(builder: &mut MethodsBuilder) {
         fs_util::read_to_string(path)
     }
 
+    /// The size of the file in bytes, or `None` if the artifact is a directory.
+    #[starlark(attribute)]
+    fn size_hint(this: &StarlarkArtifactValue) -> anyhow::Result<Option<u64>> {
+        let metadata = fs_util::metadata(this.fs.resolve(&this.path))?;
+        Ok(if metadata.is_dir() {
+            None
+        } else {
+            Some(metadata.len())
+        })
+    }
+
     fn read_json<'v>(this: &StarlarkArtifactValue, heap: &'v Heap) -> starlark::Result<Value<'v>> {
         let path = this.fs.resolve(&this.path);
         let file = File::open(&path).with_context(|| format!("Error opening file `{}`", path))?;
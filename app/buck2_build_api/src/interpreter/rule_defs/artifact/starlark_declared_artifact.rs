@@ -12,6 +12,7 @@ use std::fmt::Debug;
 use std::fmt::Display;
 
 use allocative::Allocative;
+use anyhow::Context;
 use buck2_artifact::artifact::artifact_type::Artifact;
 use buck2_artifact::artifact::artifact_type::DeclaredArtifact;
 use buck2_artifact::artifact::artifact_type::OutputArtifact;
@@ -101,6 +102,17 @@ impl StarlarkDeclaredArtifact {
         }
     }
 
+    /// Context for errors about this artifact, which point to where it was declared.
+    fn declared_at(&self) -> String {
+        match &self.declaration_location {
+            Some(location) => format!("Artifact `{}` was declared at {}", self.artifact, location),
+            None => format!(
+                "Artifact `{}` was declared at an unknown location",
+                self.artifact
+            ),
+        }
+    }
+
     pub fn output_artifact(&self) -> OutputArtifact {
         self.artifact.as_output()
     }
@@ -175,10 +187,13 @@ impl CommandLineArgLike for StarlarkDeclaredArtifact {
     }
 
     fn visit_artifacts(&self, visitor: &mut dyn CommandLineArtifactVisitor) -> anyhow::Result<()> {
-        visitor.visit_input(
-            ArtifactGroup::Artifact(self.artifact.dupe().ensure_bound()?.into_artifact()),
-            None,
-        );
+        let artifact = self
+            .artifact
+            .dupe()
+            .ensure_bound()
+            .with_context(|| self.declared_at())?
+            .into_artifact();
+        visitor.visit_input(ArtifactGroup::Artifact(artifact), None);
         self.associated_artifacts
             .iter()
             .for_each(|ag| visitor.visit_input(ag.dupe(), None));
@@ -203,10 +218,12 @@ impl Freeze for StarlarkDeclaredArtifact {
         // ensure_bound() moves out of self and so we can't construct the error
         // after calling that, so we need to check first.
         if !self.artifact.is_bound() {
-            return Err(ArtifactError::DeclaredArtifactWasNotBound {
-                repr: self.to_string(),
-            }
-            .into());
+            return Err(
+                anyhow::Error::from(ArtifactError::DeclaredArtifactWasNotBound {
+                    repr: self.to_string(),
+                })
+                .context(self.declared_at()),
+            );
         }
         let artifact = self.artifact.ensure_bound()?.into_artifact();
         Ok(StarlarkArtifact {
@@ -277,6 +294,25 @@ fn declared_artifact_methods(builder: &mut MethodsBuilder) {
         })
     }
 
+    /// The base name of this artifact without its extension. e.g. for an artifact at
+    /// `foo/bar.sh`, this is `bar`. Together with `extension`, this is the `basename`.
+    #[starlark(attribute)]
+    fn stem<'v>(this: &StarlarkDeclaredArtifact, heap: &Heap) -> anyhow::Result<StringValue<'v>> {
+        this.artifact
+            .get_path()
+            .with_filename(|filename| Ok(StarlarkArtifactHelpers::alloc_stem(filename?, heap)))
+    }
+
+    /// The size of the artifact in bytes, if it is known. Artifacts are not built during
+    /// analysis, so this is always `None` there; the values of built artifacts in
+    /// `dynamic_output` know their size.
+    #[starlark(attribute)]
+    fn size_hint(
+        #[starlark(this)] _this: &StarlarkDeclaredArtifact,
+    ) -> anyhow::Result<Option<u64>> {
+        Ok(None)
+    }
+
     /// Whether the artifact represents a source file
     #[starlark(attribute)]
     fn is_source(this: &StarlarkDeclaredArtifact) -> anyhow::Result<bool> {
@@ -338,6 +374,20 @@ fn declared_artifact_methods(builder: &mut MethodsBuilder) {
         })
     }
 
+    /// The artifacts which are materialized along with this artifact wherever it is used, e.g.
+    /// debug info or source maps, as added by `with_associated_artifacts`. Associated transitive
+    /// set projections are not listed.
+    #[starlark(attribute)]
+    fn associated_artifacts<'v>(
+        this: &StarlarkDeclaredArtifact,
+        heap: &'v Heap,
+    ) -> anyhow::Result<Value<'v>> {
+        Ok(StarlarkArtifactHelpers::associated_artifacts(
+            &this.associated_artifacts,
+            heap,
+        ))
+    }
+
     /// Returns a `StarlarkDeclaredArtifact` instance which is identical to the original artifact,
     /// except with no associated artifacts
     fn without_associated_artifacts(
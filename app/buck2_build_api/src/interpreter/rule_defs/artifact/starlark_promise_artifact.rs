@@ -26,6 +26,7 @@ use starlark::environment::Methods;
 use starlark::environment::MethodsBuilder;
 use starlark::environment::MethodsStatic;
 use starlark::typing::Ty;
+use starlark::values::list::AllocList;
 use starlark::values::list::ListOf;
 use starlark::values::starlark_value;
 use starlark::values::type_repr::StarlarkTypeRepr;
@@ -285,6 +286,27 @@ fn promise_artifact_methods(builder: &mut MethodsBuilder) {
         }
     }
 
+    /// The base name of this artifact without its extension. e.g. for an artifact at
+    /// `foo/bar.sh`, this is `bar`. Together with `extension`, this is the `basename`.
+    #[starlark(attribute)]
+    fn stem<'v>(this: &StarlarkPromiseArtifact, heap: &Heap) -> anyhow::Result<StringValue<'v>> {
+        match this.artifact.get() {
+            Some(v) => StarlarkArtifactHelpers::stem(v, heap),
+            None => Ok(StarlarkArtifactHelpers::alloc_stem(
+                this.file_name_err()?,
+                heap,
+            )),
+        }
+    }
+
+    /// The size of the artifact in bytes, if it is known. Artifacts are not built during
+    /// analysis, so this is always `None` there; the values of built artifacts in
+    /// `dynamic_output` know their size.
+    #[starlark(attribute)]
+    fn size_hint(#[starlark(this)] _this: &StarlarkPromiseArtifact) -> anyhow::Result<Option<u64>> {
+        Ok(None)
+    }
+
     /// Whether the artifact represents a source file
     #[starlark(attribute)]
     fn is_source(this: &StarlarkPromiseArtifact) -> anyhow::Result<bool> {
@@ -337,6 +359,16 @@ fn promise_artifact_methods(builder: &mut MethodsBuilder) {
         Err(PromiseArtifactError::CannotProject(this.clone()).into())
     }
 
+    /// The artifacts which are materialized along with this artifact wherever it is used. Promise
+    /// artifacts have none.
+    #[starlark(attribute)]
+    fn associated_artifacts<'v>(
+        this: &StarlarkPromiseArtifact,
+        heap: &'v Heap,
+    ) -> anyhow::Result<Value<'v>> {
+        Ok(heap.alloc(AllocList::EMPTY))
+    }
+
     /// Returns a `StarlarkPromiseArtifact` instance which is identical to the original artifact,
     /// except with no associated artifacts
     fn without_associated_artifacts(
@@ -17,6 +17,7 @@ use std::fmt::Formatter;
 use std::marker::PhantomData;
 
 use allocative::Allocative;
+use anyhow::Context;
 use buck2_core::fs::paths::RelativePathBuf;
 use buck2_util::thin_box::ThinBoxSlice;
 use display_container::display_pair;
@@ -215,9 +216,23 @@ impl<'v, F: Fields<'v>> CommandLineArgLike for FieldsRef<'v, F> {
 
     fn visit_artifacts(&self, visitor: &mut dyn CommandLineArtifactVisitor) -> anyhow::Result<()> {
         if !self.ignore_artifacts() {
-            for item in self.0.items().iter().chain(self.0.hidden().iter()) {
+            let items = self
+                .0
+                .items()
+                .iter()
+                .enumerate()
+                .map(|(i, a)| (false, i, a));
+            let hidden = self
+                .0
+                .hidden()
+                .iter()
+                .enumerate()
+                .map(|(i, a)| (true, i, a));
+            for (hidden, i, item) in items.chain(hidden) {
                 visitor.push_frame()?;
-                item.as_command_line_arg().visit_artifacts(visitor)?;
+                item.as_command_line_arg()
+                    .visit_artifacts(visitor)
+                    .with_context(|| argument_context(hidden, i))?;
                 visitor.pop_frame();
             }
         }
@@ -519,6 +534,16 @@ impl CommandLineArgLike for FrozenStarlarkCmdArgs {
     }
 }
 
+/// Context of an error about an argument of a `cmd_args`. Errors about nested `cmd_args` get one
+/// per level of nesting.
+fn argument_context(hidden: bool, index: usize) -> String {
+    if hidden {
+        format!("In hidden argument {} of `cmd_args`", index)
+    } else {
+        format!("In argument {} of `cmd_args`", index)
+    }
+}
+
 impl<'v> Freeze for StarlarkCmdArgs<'v> {
     type Frozen = FrozenStarlarkCmdArgs;
     fn freeze(self, freezer: &Freezer) -> anyhow::Result<Self::Frozen> {
@@ -528,8 +553,19 @@ impl<'v> Freeze for StarlarkCmdArgs<'v> {
             options,
         } = self.0.into_inner();
 
-        let items = ThinBoxSlice::from_iter(items.freeze(freezer)?);
-        let hidden = ThinBoxSlice::from_iter(hidden.freeze(freezer)?);
+        // Record which argument failed to freeze, e.g. because it's an artifact that was never
+        // bound, since the nesting of `cmd_args` isn't visible in the error otherwise.
+        let freeze_args = |args: Vec<CommandLineArg<'v>>, hidden: bool| {
+            args.into_iter()
+                .enumerate()
+                .map(|(i, arg)| {
+                    arg.freeze(freezer)
+                        .with_context(|| argument_context(hidden, i))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()
+        };
+        let items = ThinBoxSlice::from_iter(freeze_args(items, false)?);
+        let hidden = ThinBoxSlice::from_iter(freeze_args(hidden, true)?);
         let options = options
             .try_map(|options| (*options).freeze(freezer))?
             .unwrap_or_default();
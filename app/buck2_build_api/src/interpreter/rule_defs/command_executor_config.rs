@@ -9,9 +9,12 @@
 
 use std::collections::HashSet;
 use std::sync::Arc;
+use std::time::Duration;
 
 use allocative::Allocative;
 use anyhow::Context as _;
+use buck2_core::execution_types::executor_config::ActionExecutorPreferences;
+use buck2_core::execution_types::executor_config::ActionTimeouts;
 use buck2_core::execution_types::executor_config::CacheUploadBehavior;
 use buck2_core::execution_types::executor_config::CommandExecutorConfig;
 use buck2_core::execution_types::executor_config::CommandGenerationOptions;
@@ -90,6 +93,11 @@ pub fn register_command_executor_config(builder: &mut GlobalsBuilder) {
     /// * `experimental_low_pass_filter`: Whether to use the experimental low pass filter
     /// * `remote_output_paths`: How to express output paths to RE
     /// * `remote_execution_dependencies`: Dependencies for remote execution for this platform
+    /// * `default_timeouts_s`: Timeouts in seconds of the actions which don't set `timeout_s`,
+    /// by action category
+    /// * `executor_preferences`: Where to run the actions of a category, one of `local_only`,
+    /// `prefer_local`, `prefer_remote` or `remote_only`, e.g. `{"link": "local_only"}`. Combined
+    /// with the preference of the action itself, and an error if they conflict
     #[starlark(as_type = StarlarkCommandExecutorConfig)]
     fn CommandExecutorConfig<'v>(
         #[starlark(require = named)] local_enabled: bool,
@@ -117,6 +125,12 @@ pub fn register_command_executor_config(builder: &mut GlobalsBuilder) {
         #[starlark(default=UnpackList::default())] remote_execution_dependencies: UnpackList<
             SmallMap<String, String>,
         >,
+        #[starlark(default = NoneOr::None, require = named)] default_timeouts_s: NoneOr<
+            SmallMap<String, i32>,
+        >,
+        #[starlark(default = NoneOr::None, require = named)] executor_preferences: NoneOr<
+            SmallMap<String, String>,
+        >,
     ) -> anyhow::Result<StarlarkCommandExecutorConfig> {
         let command_executor_config = {
             let remote_execution_max_input_files_mebibytes =
@@ -296,6 +310,38 @@ pub fn register_command_executor_config(builder: &mut GlobalsBuilder) {
                 ))?
                 .unwrap_or_default();
 
+            let default_timeouts = ActionTimeouts {
+                by_category: Arc::new(
+                    default_timeouts_s
+                        .into_option()
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|(category, timeout)| match u64::try_from(timeout) {
+                            Ok(timeout) if timeout > 0 => {
+                                Ok((category, Duration::from_secs(timeout)))
+                            }
+                            _ => Err(CommandExecutorConfigErrors::InvalidField(
+                                "default_timeouts_s",
+                            )),
+                        })
+                        .collect::<Result<_, _>>()?,
+                ),
+            };
+
+            let executor_preferences = ActionExecutorPreferences {
+                by_category: Arc::new(
+                    executor_preferences
+                        .into_option()
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|(category, preference)| anyhow::Ok((category, preference.parse()?)))
+                        .collect::<anyhow::Result<_>>()
+                        .context(CommandExecutorConfigErrors::InvalidField(
+                            "executor_preferences",
+                        ))?,
+                ),
+            };
+
             CommandExecutorConfig {
                 executor,
                 options: CommandGenerationOptions {
@@ -306,6 +352,8 @@ pub fn register_command_executor_config(builder: &mut GlobalsBuilder) {
                     },
                     output_paths_behavior,
                 },
+                default_timeouts,
+                executor_preferences,
             }
         };
 
@@ -9,17 +9,21 @@
 
 use std::fmt::Debug;
 use std::ptr;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 
 use allocative::Allocative;
 use anyhow::Context;
 use buck2_artifact::artifact::artifact_type::Artifact;
 use buck2_build_api_derive::internal_provider;
 use dupe::Dupe;
+use once_cell::sync::Lazy;
 use starlark::any::ProvidesStaticType;
 use starlark::coerce::Coerce;
 use starlark::collections::SmallMap;
 use starlark::environment::GlobalsBuilder;
 use starlark::eval::Evaluator;
+use starlark::values::dict::AllocDict;
 use starlark::values::dict::Dict;
 use starlark::values::dict::FrozenDictRef;
 use starlark::values::list::AllocList;
@@ -27,14 +31,17 @@ use starlark::values::list::ListRef;
 use starlark::values::none::NoneType;
 use starlark::values::type_repr::DictType;
 use starlark::values::Freeze;
+use starlark::values::FrozenHeap;
 use starlark::values::FrozenRef;
 use starlark::values::FrozenValue;
 use starlark::values::FrozenValueTyped;
+use starlark::values::OwnedFrozenValue;
 use starlark::values::Trace;
 use starlark::values::UnpackValue;
 use starlark::values::Value;
 use starlark::values::ValueError;
 use starlark::values::ValueLike;
+use starlark::values::ValueOfUnchecked;
 
 use crate::artifact_groups::ArtifactGroup;
 use crate::interpreter::rule_defs::artifact::StarlarkArtifact;
@@ -317,6 +324,37 @@ impl FrozenDefaultInfo {
     }
 }
 
+/// The `DefaultInfo` with no outputs and no sub-targets, e.g. of sub-targets and of rules which
+/// only return other providers. It is the most common one, so all analysis results share it
+/// rather than each allocating its own.
+static EMPTY_DEFAULT_INFO: Lazy<OwnedFrozenValue> = Lazy::new(|| {
+    let heap = FrozenHeap::new();
+    let empty = FrozenDefaultInfo {
+        sub_targets: heap.alloc(AllocDict::EMPTY),
+        default_outputs: heap.alloc(AllocList::EMPTY),
+        other_outputs: heap.alloc(AllocList::EMPTY),
+    };
+    let empty = heap.alloc(empty);
+    // Safe because we just created the value on the heap.
+    unsafe { OwnedFrozenValue::new(heap.into_ref(), empty) }
+});
+
+static EMPTY_DEFAULT_INFO_USES: AtomicU64 = AtomicU64::new(0);
+
+/// Number of times the shared empty `DefaultInfo` was used instead of allocating one, and the bytes
+/// those would have taken in the frozen heaps of analysis results.
+pub fn shared_empty_default_info_stats() -> (u64, u64) {
+    let uses = EMPTY_DEFAULT_INFO_USES.load(Ordering::Relaxed);
+    // Only the `DefaultInfo` itself and its header, not its empty fields.
+    let size = (std::mem::size_of::<FrozenDefaultInfo>() + std::mem::size_of::<usize>()) as u64;
+    (uses, uses * size)
+}
+
+fn shared_empty_default_info<'v>(frozen_heap: &'v FrozenHeap) -> Value<'v> {
+    EMPTY_DEFAULT_INFO_USES.fetch_add(1, Ordering::Relaxed);
+    EMPTY_DEFAULT_INFO.owned_value(frozen_heap)
+}
+
 impl PartialEq for FrozenDefaultInfo {
     // frozen default infos can be compared by ptr for a simple equality
     fn eq(&self, other: &Self) -> bool {
@@ -391,18 +429,18 @@ fn default_info_creator(builder: &mut GlobalsBuilder) {
         #[starlark(default = AllocList::EMPTY)] other_outputs: Value<'v>,
         #[starlark(default = SmallMap::new())] sub_targets: SmallMap<String, Value<'v>>,
         eval: &mut Evaluator<'v, '_>,
-    ) -> anyhow::Result<DefaultInfo<'v>> {
+    ) -> anyhow::Result<ValueOfUnchecked<'v, DefaultInfo<'v>>> {
         let heap = eval.heap();
-        let default_info_creator = || {
-            let default_outputs = heap.alloc(AllocList::EMPTY);
-            let other_outputs = heap.alloc(AllocList::EMPTY);
-            let sub_targets = heap.alloc(Dict::default());
-            heap.alloc(DefaultInfo {
-                sub_targets,
-                default_outputs,
-                other_outputs,
-            })
-        };
+        let frozen_heap = eval.frozen_heap();
+        let default_info_creator = || shared_empty_default_info(frozen_heap);
+
+        if default_output.is_none()
+            && default_outputs.is_none()
+            && sub_targets.is_empty()
+            && ListRef::from_value(other_outputs).map_or(false, |l| l.is_empty())
+        {
+            return Ok(ValueOfUnchecked::new(default_info_creator()));
+        }
 
         // support both list and singular options for now until we migrate all the rules.
         let valid_default_outputs = if !default_outputs.is_none() {
@@ -467,10 +505,10 @@ fn default_info_creator(builder: &mut GlobalsBuilder) {
             })
             .collect::<anyhow::Result<SmallMap<Value<'v>, Value<'v>>>>()?;
 
-        Ok(DefaultInfo {
+        Ok(ValueOfUnchecked::new(heap.alloc(DefaultInfo {
             default_outputs: valid_default_outputs,
             other_outputs: valid_other_outputs,
             sub_targets: heap.alloc(Dict::new(valid_sub_targets)),
-        })
+        })))
     }
 }
@@ -98,6 +98,26 @@ pub struct ExternalRunnerTestInfoGen<V> {
     /// should be ignored when executing tests even if those are passed as required from test runner.
     #[provider(field_type = DictType<String, Option<StarlarkConfiguredProvidersLabel>>)]
     local_resources: V,
+
+    /// Mapping from a resource pool name (e.g. `gpu` or `device:pixel6`) to the number of units
+    /// of that pool the test needs. Buck2 doesn't run more tests at once than the capacity of a
+    /// pool, set by `test.resource_pools`, allows.
+    #[provider(field_type = DictType<String, u32>)]
+    resource_pools: V,
+
+    /// The number of shards to split this test into. Each shard is a separate execution of the
+    /// test, with `TEST_SHARD_INDEX` and `TEST_TOTAL_SHARDS` set in its environment.
+    ///
+    /// Defaults to `1`, i.e. the test is not sharded.
+    #[provider(field_type = u32)]
+    shards: V,
+
+    /// Whether this test must run on RE, from the inputs stored in the CAS, so that its inputs
+    /// are never materialized locally. Only its logs and its outputs are downloaded.
+    ///
+    /// Defaults to `False`.
+    #[provider(field_type = bool)]
+    remote_only: V,
 }
 
 // NOTE: All the methods here unwrap because we validate at freeze time.
@@ -152,6 +172,24 @@ impl FrozenExternalRunnerTestInfo {
         unwrap_all(iter_local_resources(self.local_resources.to_value())).collect()
     }
 
+    pub fn resource_pools(&self) -> IndexMap<&str, u32> {
+        unwrap_all(iter_resource_pools(self.resource_pools.to_value())).collect()
+    }
+
+    pub fn remote_only(&self) -> bool {
+        NoneOr::<bool>::unpack_value(self.remote_only.to_value())
+            .unwrap()
+            .into_option()
+            .unwrap_or(false)
+    }
+
+    pub fn shards(&self) -> u32 {
+        NoneOr::<u32>::unpack_value(self.shards.to_value())
+            .unwrap()
+            .into_option()
+            .unwrap_or(1)
+    }
+
     pub fn visit_artifacts(
         &self,
         visitor: &mut dyn CommandLineArtifactVisitor,
@@ -384,6 +422,47 @@ fn iter_local_resources<'v>(
     }))
 }
 
+fn iter_resource_pools<'v>(
+    resource_pools: Value<'v>,
+) -> impl Iterator<Item = anyhow::Result<(&'v str, u32)>> {
+    if resource_pools.is_none() {
+        return Either::Left(Either::Left(empty()));
+    }
+
+    let resource_pools = match DictRef::from_value(resource_pools) {
+        Some(resource_pools) => resource_pools,
+        None => {
+            return Either::Left(Either::Right(once(Err(anyhow::anyhow!(
+                "Invalid `resource_pools`: Expected a dict, got: `{}`",
+                resource_pools
+            )))));
+        }
+    };
+
+    #[allow(clippy::needless_collect)]
+    let resource_pools = resource_pools.iter().collect::<Vec<_>>();
+
+    Either::Right(resource_pools.into_iter().map(|(key, value)| {
+        let key = key.unpack_str().with_context(|| {
+            format!(
+                "Invalid key in `resource_pools`: Expected a str, got: `{}`",
+                key
+            )
+        })?;
+
+        let units = u32::unpack_value(value)
+            .filter(|units| *units > 0)
+            .with_context(|| {
+                format!(
+                    "Invalid value in `resource_pools` for key `{}`: Expected a positive int, got: `{}`",
+                    key, value
+                )
+            })?;
+
+        Ok((key, units))
+    }))
+}
+
 fn unpack_opt_executor<'v>(
     executor: Value<'v>,
 ) -> anyhow::Result<Option<&'v StarlarkCommandExecutorConfig>> {
@@ -426,10 +505,16 @@ where
     check_all(iter_opt_str_list(info.contacts.to_value(), "contacts"))?;
     check_all(iter_executor_overrides(info.executor_overrides.to_value()))?;
     check_all(iter_local_resources(info.local_resources.to_value()))?;
+    check_all(iter_resource_pools(info.resource_pools.to_value()))?;
     NoneOr::<bool>::unpack_value(info.use_project_relative_paths.to_value())
         .context("`use_project_relative_paths` must be a bool if provided")?;
     NoneOr::<bool>::unpack_value(info.run_from_project_root.to_value())
         .context("`run_from_project_root` must be a bool if provided")?;
+    NoneOr::<bool>::unpack_value(info.remote_only.to_value())
+        .context("`remote_only` must be a bool if provided")?;
+    NoneOr::<u32>::unpack_value(info.shards.to_value())
+        .filter(|shards| shards.into_option() != Some(0))
+        .context("`shards` must be a positive int if provided")?;
     unpack_opt_executor(info.default_executor.to_value()).context("Invalid `default_executor`")?;
     info.test_type
         .to_value()
@@ -452,6 +537,9 @@ fn external_runner_test_info_creator(globals: &mut GlobalsBuilder) {
         #[starlark(default = NoneType)] default_executor: Value<'v>,
         #[starlark(default = NoneType)] executor_overrides: Value<'v>,
         #[starlark(default = NoneType)] local_resources: Value<'v>,
+        #[starlark(default = NoneType)] resource_pools: Value<'v>,
+        #[starlark(default = NoneType)] shards: Value<'v>,
+        #[starlark(default = NoneType)] remote_only: Value<'v>,
     ) -> anyhow::Result<ExternalRunnerTestInfo<'v>> {
         let res = ExternalRunnerTestInfo {
             test_type: r#type,
@@ -464,6 +552,9 @@ fn external_runner_test_info_creator(globals: &mut GlobalsBuilder) {
             default_executor,
             executor_overrides,
             local_resources,
+            resource_pools,
+            shards,
+            remote_only,
         };
         validate_external_runner_test_info(&res)?;
         Ok(res)
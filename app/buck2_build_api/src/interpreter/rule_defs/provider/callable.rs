@@ -225,6 +225,20 @@ fn user_provider_callable_display(
     Ok(())
 }
 
+fn user_provider_callable_documentation(
+    docs: &Option<DocString>,
+    fields: &SmallMap<String, UserProviderField>,
+) -> DocItem {
+    let field_types: Vec<Ty> = fields.values().map(|f| f.ty.as_ty().dupe()).collect();
+    provider_callable_documentation(
+        None,
+        docs,
+        &fields.keys().map(|x| x.as_str()).collect::<Vec<_>>(),
+        &vec![None; fields.len()],
+        &field_types,
+    )
+}
+
 impl Display for UserProviderCallable {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         user_provider_callable_display(self.id(), &self.fields, f)
@@ -373,14 +387,9 @@ impl<'v> StarlarkValue<'v> for UserProviderCallable {
     }
 
     fn documentation(&self) -> Option<DocItem> {
-        let return_types = vec![Ty::any(); self.fields.len()];
-        Some(provider_callable_documentation(
-            None,
+        Some(user_provider_callable_documentation(
             &self.docs,
-            &self.fields.keys().map(|x| x.as_str()).collect::<Vec<_>>(),
-            // TODO(nga): types.
-            &vec![None; self.fields.len()],
-            &return_types,
+            &self.fields,
         ))
     }
 
@@ -449,13 +458,9 @@ impl<'v> StarlarkValue<'v> for FrozenUserProviderCallable {
     }
 
     fn documentation(&self) -> Option<DocItem> {
-        let return_types = vec![Ty::any(); self.fields.len()];
-        Some(provider_callable_documentation(
-            None,
+        Some(user_provider_callable_documentation(
             &self.docs,
-            &self.fields.keys().map(|x| x.as_str()).collect::<Vec<_>>(),
-            &vec![None; self.fields.len()],
-            &return_types,
+            &self.fields,
         ))
     }
 
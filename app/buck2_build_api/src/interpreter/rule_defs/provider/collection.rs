@@ -27,6 +27,7 @@ use serde::Serializer;
 use starlark::any::ProvidesStaticType;
 use starlark::coerce::Coerce;
 use starlark::collections::SmallMap;
+use starlark::debug::DebugChildren;
 use starlark::environment::GlobalsBuilder;
 use starlark::environment::Methods;
 use starlark::environment::MethodsBuilder;
@@ -35,6 +36,7 @@ use starlark::values::list::ListRef;
 use starlark::values::none::NoneOr;
 use starlark::values::starlark_value;
 use starlark::values::starlark_value_as_type::StarlarkValueAsType;
+use starlark::values::Demand;
 use starlark::values::Freeze;
 use starlark::values::Freezer;
 use starlark::values::FrozenRef;
@@ -50,6 +52,7 @@ use starlark::values::ValueLike;
 use starlark::values::ValueOfUnchecked;
 use starlark::StarlarkDocs;
 
+use crate::interpreter::rule_defs::provider::indistinguishable::indistinguishable;
 use crate::interpreter::rule_defs::provider::ty::abstract_provider::AbstractProvider;
 use crate::interpreter::rule_defs::provider::DefaultInfo;
 use crate::interpreter::rule_defs::provider::DefaultInfoCallable;
@@ -319,6 +322,19 @@ where
         static RES: MethodsStatic = MethodsStatic::new();
         RES.methods(provider_collection_methods)
     }
+
+    fn provide(&'v self, demand: &mut Demand<'_, 'v>) {
+        demand.provide_value::<&dyn DebugChildren<'v>>(self);
+    }
+}
+
+impl<'v, V: ValueLike<'v>> DebugChildren<'v> for ProviderCollectionGen<V> {
+    fn debug_children(&self) -> Vec<(String, Value<'v>)> {
+        self.providers
+            .iter()
+            .map(|(id, v)| (id.name.clone(), v.to_value()))
+            .collect()
+    }
 }
 
 unsafe impl<'v> Trace<'v> for ProviderCollection<'v> {
@@ -417,6 +433,13 @@ impl FrozenProviderCollectionValue {
         self.value.as_ref()
     }
 
+    /// Whether the analysis of a target depending on these providers could not tell them apart
+    /// from `other`. Conservative: providers with values of types not known to be compared
+    /// exactly are only indistinguishable from themselves.
+    pub fn is_indistinguishable_from(&self, other: &Self) -> bool {
+        indistinguishable(self.value.to_value(), other.value.to_value())
+    }
+
     pub fn lookup_inner(&self, label: &ConfiguredProvidersLabel) -> anyhow::Result<Self> {
         match label.name() {
             ProvidersName::Default => anyhow::Ok(self.dupe()),
@@ -19,6 +19,7 @@ use buck2_interpreter::error::BuckStarlarkError;
 use buck2_interpreter::types::configured_providers_label::StarlarkConfiguredProvidersLabel;
 use starlark::any::ProvidesStaticType;
 use starlark::coerce::Coerce;
+use starlark::debug::DebugChildren;
 use starlark::environment::GlobalsBuilder;
 use starlark::environment::Methods;
 use starlark::environment::MethodsBuilder;
@@ -27,6 +28,7 @@ use starlark::typing::Ty;
 use starlark::values::none::NoneOr;
 use starlark::values::starlark_value;
 use starlark::values::starlark_value_as_type::StarlarkValueAsType;
+use starlark::values::Demand;
 use starlark::values::Freeze;
 use starlark::values::FrozenValue;
 use starlark::values::Heap;
@@ -147,6 +149,23 @@ where
     fn is_in(&self, other: Value<'v>) -> starlark::Result<bool> {
         self.providers_collection.to_value().is_in(other)
     }
+
+    fn provide(&'v self, demand: &mut Demand<'_, 'v>) {
+        demand.provide_value::<&dyn DebugChildren<'v>>(self);
+    }
+}
+
+/// In the debugger, show the label followed by the providers, rather than the methods.
+impl<'v, V: ValueLike<'v>> DebugChildren<'v> for DependencyGen<V> {
+    fn debug_children(&self) -> Vec<(String, Value<'v>)> {
+        let mut children = vec![("label".to_owned(), self.label.to_value())];
+        if let Some(collection) =
+            ProviderCollection::from_value(self.providers_collection.to_value())
+        {
+            children.extend(collection.debug_children());
+        }
+        children
+    }
 }
 
 /// Dependency type. In Starlark typing it can be represented with `Dependency` global.
@@ -0,0 +1,117 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Whether two provider values, typically produced by two analyses of the same target, can be told
+//! apart by the analysis of a target depending on them.
+//!
+//! Starlark `==` is not enough for that: dicts compare equal regardless of their order, `1 == 1.0`,
+//! and artifacts compare by path, ignoring the action producing them. So values are only considered
+//! indistinguishable when they are the same value, or are made of the types this module knows
+//! with the same contents in the same order. Anything else (functions, transitive sets,
+//! command lines...) is considered different.
+
+use buck2_interpreter::types::configured_providers_label::StarlarkConfiguredProvidersLabel;
+use buck2_interpreter::types::configured_providers_label::StarlarkProvidersLabel;
+use buck2_interpreter::types::target_label::StarlarkConfiguredTargetLabel;
+use buck2_interpreter::types::target_label::StarlarkTargetLabel;
+use starlark::values::dict::DictRef;
+use starlark::values::list::ListRef;
+use starlark::values::structs::StructRef;
+use starlark::values::tuple::TupleRef;
+use starlark::values::Value;
+
+use crate::interpreter::rule_defs::artifact::starlark_artifact::StarlarkArtifact;
+use crate::interpreter::rule_defs::provider::collection::FrozenProviderCollection;
+use crate::interpreter::rule_defs::provider::ValueAsProviderLike;
+
+pub(crate) fn indistinguishable<'v>(a: Value<'v>, b: Value<'v>) -> bool {
+    if a.ptr_eq(b) {
+        return true;
+    }
+    if a.get_type() != b.get_type() {
+        return false;
+    }
+    match a.get_type() {
+        // `==` on these compares the exact value.
+        "NoneType" | "bool" | "int" | "string" => return a.equals(b).unwrap_or(false),
+        // Unlike `==`, distinguishes `0.0` and `-0.0`.
+        "float" => return a.to_repr() == b.to_repr(),
+        _ => {}
+    }
+
+    if let (Some(a), Some(b)) = (ListRef::from_value(a), ListRef::from_value(b)) {
+        return all_indistinguishable(a.content(), b.content());
+    }
+    if let (Some(a), Some(b)) = (TupleRef::from_value(a), TupleRef::from_value(b)) {
+        return all_indistinguishable(a.content(), b.content());
+    }
+    if let (Some(a), Some(b)) = (DictRef::from_value(a), DictRef::from_value(b)) {
+        return a.len() == b.len()
+            && a.iter().zip(b.iter()).all(|((ak, av), (bk, bv))| {
+                indistinguishable(ak, bk) && indistinguishable(av, bv)
+            });
+    }
+    if let (Some(a), Some(b)) = (StructRef::from_value(a), StructRef::from_value(b)) {
+        return a.iter().len() == b.iter().len()
+            && a.iter()
+                .zip(b.iter())
+                .all(|((ak, av), (bk, bv))| ak == bk && indistinguishable(av, bv));
+    }
+    if let (Some(a), Some(b)) = (
+        a.downcast_ref::<StarlarkArtifact>(),
+        b.downcast_ref::<StarlarkArtifact>(),
+    ) {
+        // Artifacts only compare their paths, but depending on an artifact means depending on the
+        // action producing it. Associated artifacts may contain transitive sets.
+        return a.artifact == b.artifact
+            && a.artifact.action_key() == b.artifact.action_key()
+            && a.associated_artifacts.is_empty()
+            && b.associated_artifacts.is_empty();
+    }
+    if let (Some(a), Some(b)) = (
+        a.downcast_ref::<FrozenProviderCollection>(),
+        b.downcast_ref::<FrozenProviderCollection>(),
+    ) {
+        return a.providers.len() == b.providers.len()
+            && a.providers.iter().zip(b.providers.iter()).all(
+                |((a_id, a_provider), (b_id, b_provider))| {
+                    a_id == b_id && indistinguishable(a_provider.to_value(), b_provider.to_value())
+                },
+            );
+    }
+    if let (Some(a), Some(b)) = (a.as_provider(), b.as_provider()) {
+        let (a_items, b_items) = (a.items(), b.items());
+        return a.id() == b.id()
+            && a_items.len() == b_items.len()
+            && a_items
+                .iter()
+                .zip(b_items.iter())
+                .all(|((ak, av), (bk, bv))| ak == bk && indistinguishable(*av, *bv));
+    }
+    // Labels compare the exact label.
+    if is_label(a) {
+        return a.equals(b).unwrap_or(false);
+    }
+    false
+}
+
+fn all_indistinguishable<'v>(a: &[Value<'v>], b: &[Value<'v>]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| indistinguishable(*a, *b))
+}
+
+fn is_label(value: Value) -> bool {
+    value
+        .downcast_ref::<StarlarkConfiguredProvidersLabel>()
+        .is_some()
+        || value.downcast_ref::<StarlarkProvidersLabel>().is_some()
+        || value.downcast_ref::<StarlarkTargetLabel>().is_some()
+        || value
+            .downcast_ref::<StarlarkConfiguredTargetLabel>()
+            .is_some()
+}
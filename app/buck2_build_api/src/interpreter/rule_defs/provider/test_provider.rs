@@ -87,6 +87,7 @@ impl TestProvider for FrozenExternalRunnerTestInfo {
             contacts: self.contacts().map(|l| l.to_owned()).collect(),
             oncall: self.contacts().exactly_one().ok().map(str::to_owned),
             working_dir_cell,
+            shards: self.shards(),
         };
 
         async move { executor.external_runner_spec(spec).await }.boxed()
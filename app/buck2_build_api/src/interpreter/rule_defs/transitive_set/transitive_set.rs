@@ -163,11 +163,37 @@ impl<V> TransitiveSetGen<V> {
 }
 
 impl<'v> NodeGen<Value<'v>> {
-    fn freeze(self, freezer: &Freezer) -> anyhow::Result<NodeGen<FrozenValue>> {
+    /// Freeze the node of a set of type `definition`, so that errors can say which set and
+    /// projection contained the invalid value.
+    fn freeze(
+        self,
+        definition: FrozenValue,
+        freezer: &Freezer,
+    ) -> anyhow::Result<NodeGen<FrozenValue>> {
         let Self { value, projections } = self;
 
-        let value = value.freeze(freezer)?;
-        let projections = projections.freeze(freezer)?;
+        let def = transitive_set_definition_from_value(definition.to_value());
+        let describe = || match def {
+            Some(def) => def.describe(),
+            None => "<invalid>".to_owned(),
+        };
+
+        let value = value
+            .freeze(freezer)
+            .with_context(|| format!("In the value of a node of transitive set {}", describe()))?;
+        let projections = projections
+            .into_vec()
+            .into_iter()
+            .enumerate()
+            .map(|(i, projection)| {
+                projection.freeze(freezer).with_context(|| {
+                    let name = def
+                        .and_then(|def| def.operations().projections.get_index(i))
+                        .map_or("<invalid>", |(name, _)| name.as_str());
+                    format!("In projection `{}` of transitive set {}", name, describe())
+                })
+            })
+            .collect::<anyhow::Result<Box<[_]>>>()?;
 
         Ok(NodeGen { value, projections })
     }
@@ -365,7 +391,7 @@ impl<'v> Freeze for TransitiveSet<'v> {
             children,
         } = self;
         let definition = definition.freeze(freezer)?;
-        let node = node.try_map(|node| node.freeze(freezer))?;
+        let node = node.try_map(|node| node.freeze(definition, freezer))?;
         let children = children.freeze(freezer)?;
         let reductions = reductions.freeze(freezer)?;
         Ok(TransitiveSetGen {
@@ -392,6 +392,9 @@ pub trait TransitiveSetDefinitionLike<'v> {
     fn matches_type(&self, ty: &str) -> bool;
 
     fn operations(&self) -> &TransitiveSetOperations<'v>;
+
+    /// The name of the set and the file defining it, for error messages.
+    fn describe(&self) -> String;
 }
 
 impl<'v> TransitiveSetDefinitionLike<'v> for TransitiveSetDefinition<'v> {
@@ -413,6 +416,16 @@ impl<'v> TransitiveSetDefinitionLike<'v> for TransitiveSetDefinition<'v> {
     fn operations(&self) -> &TransitiveSetOperations<'v> {
         &self.operations
     }
+
+    fn describe(&self) -> String {
+        match self.exported.get() {
+            Some(exported) => format!(
+                "`{}` defined in `{}`",
+                exported.id.name, exported.id.module_id
+            ),
+            None => format!("unnamed transitive set defined in `{}`", self.module_id),
+        }
+    }
 }
 
 impl<'v> TransitiveSetDefinitionLike<'v> for FrozenTransitiveSetDefinition {
@@ -431,6 +444,13 @@ impl<'v> TransitiveSetDefinitionLike<'v> for FrozenTransitiveSetDefinition {
     fn operations(&self) -> &TransitiveSetOperations<'v> {
         coerce(&self.operations)
     }
+
+    fn describe(&self) -> String {
+        format!(
+            "`{}` defined in `{}`",
+            self.exported.id.name, self.exported.id.module_id
+        )
+    }
 }
 
 #[starlark_module]
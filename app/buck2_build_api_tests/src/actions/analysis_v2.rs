@@ -0,0 +1,167 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::sync::Arc;
+
+use buck2_artifact::artifact::artifact_type::testing::BuildArtifactTestingExt;
+use buck2_artifact::artifact::artifact_type::Artifact;
+use buck2_artifact::artifact::build_artifact::BuildArtifact;
+use buck2_artifact::artifact::source_artifact::SourceArtifact;
+use buck2_artifact::deferred::data::DeferredData;
+use buck2_artifact::deferred::id::DeferredId;
+use buck2_artifact::deferred::key::DeferredKey;
+use buck2_build_api::actions::analysis_v2::action_graph_container;
+use buck2_build_api::actions::analysis_v2::ActionGraphContainer;
+use buck2_build_api::actions::query::ActionInput;
+use buck2_build_api::actions::query::ActionQueryNode;
+use buck2_build_api::actions::query::ActionQueryNodeRef;
+use buck2_build_api::actions::query::SetProjectionInputs;
+use buck2_build_api::actions::RegisteredAction;
+use buck2_build_api::artifact_groups::ArtifactGroup;
+use buck2_build_api::artifact_groups::TransitiveSetProjectionKey;
+use buck2_core::base_deferred_key::BaseDeferredKey;
+use buck2_core::buck_path::path::BuckPath;
+use buck2_core::category::Category;
+use buck2_core::configuration::data::ConfigurationData;
+use buck2_core::execution_types::executor_config::CommandExecutorConfig;
+use buck2_core::fs::artifact_path_resolver::ArtifactFs;
+use buck2_core::fs::buck_out_path::BuckOutPathResolver;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_core::fs::paths::forward_rel_path::ForwardRelativePathBuf;
+use buck2_core::fs::project::ProjectRoot;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use buck2_core::package::package_relative_path::PackageRelativePathBuf;
+use buck2_core::package::PackageLabel;
+use buck2_core::target::configured_target_label::ConfiguredTargetLabel;
+use buck2_interpreter_for_build::interpreter::testing::cells;
+use dupe::Dupe;
+use indexmap::indexset;
+use indexmap::IndexSet;
+use prost::Message;
+
+use crate::actions::testings::SimpleAction;
+
+fn target() -> ConfiguredTargetLabel {
+    ConfiguredTargetLabel::testing_parse("root//foo:bar", ConfigurationData::testing_new())
+}
+
+fn artifact_fs() -> Arc<ArtifactFs> {
+    let cell_info = cells(None).unwrap();
+    Arc::new(ArtifactFs::new(
+        cell_info.1,
+        BuckOutPathResolver::new(ProjectRelativePathBuf::unchecked_new(
+            "buck-out/v2".to_owned(),
+        )),
+        ProjectRoot::new(AbsNormPathBuf::try_from(std::env::current_dir().unwrap()).unwrap())
+            .unwrap(),
+    ))
+}
+
+fn action(
+    id: u32,
+    output: &str,
+    inputs: IndexSet<ArtifactGroup>,
+) -> (BuildArtifact, Arc<RegisteredAction>) {
+    let output = BuildArtifact::testing_new(
+        target(),
+        ForwardRelativePathBuf::unchecked_new(output.to_owned()),
+        DeferredId::testing_new(id),
+    );
+    let action = RegisteredAction::new(
+        output.key().dupe(),
+        Box::new(SimpleAction::new(
+            inputs,
+            indexset![output.dupe()],
+            vec!["cc".to_owned()],
+            Category::try_from("cxx_compile").unwrap(),
+            None,
+        )),
+        CommandExecutorConfig::testing_local(),
+    );
+    (output, Arc::new(action))
+}
+
+fn projection(
+    id: u32,
+    direct: Vec<ActionQueryNodeRef>,
+    children: Vec<SetProjectionInputs>,
+) -> SetProjectionInputs {
+    SetProjectionInputs::new(
+        TransitiveSetProjectionKey {
+            key: DeferredData::unchecked_new(DeferredKey::Base(
+                BaseDeferredKey::TargetLabel(target()),
+                DeferredId::testing_new(id),
+            )),
+            projection: 0,
+        },
+        direct,
+        children,
+    )
+}
+
+#[test]
+fn test_action_graph_with_projection() -> anyhow::Result<()> {
+    let fs = artifact_fs();
+    let source = Artifact::from(SourceArtifact::new(BuckPath::testing_new(
+        PackageLabel::testing_parse("root//foo"),
+        PackageRelativePathBuf::unchecked_new("src.c".to_owned()),
+    )));
+
+    let (_, compile) = action(0, "a.o", IndexSet::new());
+    let (_, link) = action(1, "b.out", indexset![ArtifactGroup::Artifact(source)]);
+
+    let projected = projection(
+        10,
+        vec![ActionQueryNodeRef::Action(compile.key().dupe())],
+        vec![projection(11, Vec::new(), Vec::new())],
+    );
+    let nodes = vec![
+        ActionQueryNode::new_action(compile.dupe(), Vec::new(), fs.dupe()),
+        ActionQueryNode::new_action(
+            link.dupe(),
+            vec![ActionInput::IndirectInputs(projected)],
+            fs.dupe(),
+        ),
+    ];
+
+    let container = action_graph_container(nodes.iter())?;
+    let container = ActionGraphContainer::decode(container.encode_proto().as_slice())?;
+
+    let label = |artifact_id: u32| {
+        let artifact = &container.artifacts[artifact_id as usize - 1];
+        container.path_fragments[artifact.path_fragment_id as usize - 1]
+            .label
+            .clone()
+    };
+
+    assert_eq!(1, container.targets.len());
+    assert_eq!("root//foo:bar", container.targets[0].label);
+
+    assert_eq!(2, container.actions.len());
+    let (compile, link) = (&container.actions[0], &container.actions[1]);
+    assert_eq!("cxx_compile", compile.mnemonic);
+    assert_eq!(vec![1], compile.output_ids);
+    assert_eq!("a.o", label(compile.primary_output_id));
+    assert!(compile.input_dep_set_ids.is_empty());
+
+    assert_eq!("b.out", label(link.primary_output_id));
+    // The direct inputs, then the projection.
+    assert_eq!(vec![1, 3], link.input_dep_set_ids);
+    let dep_sets = &container.dep_set_of_files;
+    assert_eq!(3, dep_sets.len());
+    assert_eq!("src.c", label(dep_sets[0].direct_artifact_ids[0]));
+    // The projection contains the output of the compile action, and its child is empty.
+    assert_eq!(vec![1], dep_sets[2].direct_artifact_ids);
+    assert_eq!(vec![2], dep_sets[2].transitive_dep_set_ids);
+    assert!(dep_sets[1].direct_artifact_ids.is_empty());
+
+    let json = serde_json::to_value(&container)?;
+    assert_eq!(3, json["actions"][1]["inputDepSetIds"][1]);
+    Ok(())
+}
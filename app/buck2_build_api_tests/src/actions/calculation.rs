@@ -17,8 +17,10 @@ use buck2_artifact::artifact::artifact_type::Artifact;
 use buck2_artifact::artifact::build_artifact::BuildArtifact;
 use buck2_artifact::artifact::source_artifact::SourceArtifact;
 use buck2_artifact::deferred::id::DeferredId;
+use buck2_build_api::actions::calculation::check_rebuilt_outputs;
 use buck2_build_api::actions::calculation::command_details;
 use buck2_build_api::actions::calculation::ActionCalculation;
+use buck2_build_api::actions::execute::action_executor::ActionOutputs;
 use buck2_build_api::actions::execute::dice_data::set_fallback_executor_config;
 use buck2_build_api::actions::execute::dice_data::CommandExecutorResponse;
 use buck2_build_api::actions::execute::dice_data::HasCommandExecutor;
@@ -90,6 +92,7 @@ use dice::testing::DiceBuilder;
 use dice::DiceTransaction;
 use dice::UserComputationData;
 use dupe::Dupe;
+use indexmap::indexmap;
 use indexmap::indexset;
 use maplit::btreemap;
 use sorted_vector_map::sorted_vector_map;
@@ -547,3 +550,28 @@ async fn test_command_details_omission() {
     assert_eq!(&proto.stdout, "stdout");
     assert_eq!(&proto.stderr, "stderr");
 }
+
+#[test]
+fn test_check_rebuilt_outputs() {
+    let digest_config = DigestConfig::testing_default();
+    let file = |content: &[u8]| {
+        ArtifactValue::file(FileMetadata {
+            digest: TrackedFileDigest::from_content(content, digest_config.cas_digest_config()),
+            is_executable: false,
+        })
+    };
+    let path = create_test_build_artifact("cell", "pkg", "foo")
+        .get_path()
+        .dupe();
+    let previous = ActionOutputs::new(indexmap! { path.dupe() => file(b"previous") });
+
+    let same = ActionOutputs::new(indexmap! { path.dupe() => file(b"previous") });
+    assert!(check_rebuilt_outputs("foo", &previous, &same).is_ok());
+
+    let changed = ActionOutputs::new(indexmap! { path.dupe() => file(b"rebuilt") });
+    let error = check_rebuilt_outputs("foo", &previous, &changed)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("`bar.out`"), "{}", error);
+    assert!(error.contains("buck2 killall"), "{}", error);
+}
@@ -7,6 +7,7 @@
  * of this source tree.
  */
 
+mod analysis_v2;
 mod calculation;
 mod impls;
 pub(crate) mod registry;
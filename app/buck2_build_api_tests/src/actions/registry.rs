@@ -134,6 +134,39 @@ fn claiming_conflicting_path() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn claiming_case_colliding_path() -> anyhow::Result<()> {
+    let target = ConfiguredTargetLabel::testing_parse(
+        "cell//pkg:my_target",
+        ConfigurationData::testing_new(),
+    );
+    let mut actions = ActionsRegistry::new(
+        BaseDeferredKey::TargetLabel(target.dupe()),
+        ExecutionPlatformResolution::unspecified(),
+    );
+    actions.set_check_case_collisions(true);
+
+    actions.claim_output_path(&ForwardRelativePathBuf::unchecked_new("foo/a".into()), None)?;
+    actions.claim_output_path(&ForwardRelativePathBuf::unchecked_new("foo/b".into()), None)?;
+
+    for colliding in ["foo/A", "Foo/c"] {
+        assert_matches!(
+            actions.claim_output_path(&ForwardRelativePathBuf::unchecked_new(colliding.into()), None),
+            Err(e) => {
+                assert_matches!(
+                    e.downcast_ref::<ActionErrors>(),
+                    Some(ActionErrors::CaseCollidingOutputPaths(..))
+                );
+            }
+        );
+    }
+
+    actions.set_check_case_collisions(false);
+    actions.claim_output_path(&ForwardRelativePathBuf::unchecked_new("foo/A".into()), None)?;
+
+    Ok(())
+}
+
 #[test]
 fn register_actions() -> anyhow::Result<()> {
     let base = BaseDeferredKey::TargetLabel(ConfiguredTargetLabel::testing_parse(
@@ -43,6 +43,7 @@ use buck2_events::dispatch::EventDispatcher;
 use buck2_execute::digest_config::DigestConfig;
 use buck2_execute::digest_config::SetDigestConfig;
 use buck2_interpreter::dice::starlark_debug::SetStarlarkDebugger;
+use buck2_interpreter::extra::host_toolchains::HostToolchains;
 use buck2_interpreter::extra::InterpreterHostArchitecture;
 use buck2_interpreter::extra::InterpreterHostPlatform;
 use buck2_interpreter::file_loader::LoadedModules;
@@ -175,6 +176,7 @@ async fn test_analysis_calculation() -> anyhow::Result<()> {
             InterpreterHostPlatform::Linux,
             InterpreterHostArchitecture::X86_64,
             None,
+            HostToolchains::default(),
             false,
             false,
             |_| {},
@@ -32,28 +32,36 @@ fn source_artifact() -> buck2_error::Result<()> {
                 assert_eq("quz.h", a1.basename)
                 assert_eq("baz/quz.h", a1.short_path)
                 assert_eq(".h", a1.extension)
+                assert_eq("quz", a1.stem)
                 assert_eq(True, a1.is_source)
+                assert_eq(None, a1.size_hint)
                 assert_eq(None, a1.owner)
 
                 assert_eq("<source foo/bar/baz/file1>", repr(a2))
                 assert_eq("file1", a2.basename)
                 assert_eq("baz/file1", a2.short_path)
                 assert_eq("", a2.extension)
+                assert_eq("file1", a2.stem)
                 assert_eq(True, a2.is_source)
+                assert_eq(None, a2.size_hint)
                 assert_eq(None, a2.owner)
 
                 assert_eq("<source foo/bar/baz/quz.cpp>", repr(a3))
                 assert_eq("quz.cpp", a3.basename)
                 assert_eq("baz/quz.cpp", a3.short_path)
                 assert_eq(".cpp", a3.extension)
+                assert_eq("quz", a3.stem)
                 assert_eq(True, a3.is_source)
+                assert_eq(None, a3.size_hint)
                 assert_eq(None, a3.owner)
 
                 assert_eq("<source foo/bar/baz/file2>", repr(a4))
                 assert_eq("file2", a4.basename)
                 assert_eq("baz/file2", a4.short_path)
                 assert_eq("", a4.extension)
+                assert_eq("file2", a4.stem)
                 assert_eq(True, a4.is_source)
+                assert_eq(None, a4.size_hint)
                 assert_eq(None, a4.owner)
 
                 # Validate that attrs are setup properly
@@ -97,28 +105,36 @@ fn bound_artifact() -> buck2_error::Result<()> {
                 assert_eq("quz.h", a1.basename)
                 assert_eq("baz/quz.h", a1.short_path)
                 assert_eq(".h", a1.extension)
+                assert_eq("quz", a1.stem)
                 assert_eq(False, a1.is_source)
+                assert_eq(None, a1.size_hint)
                 assert_eq("bar", a1.owner.name)
 
                 assert_eq_ignore_hash("<build artifact baz/file1 bound to root//foo:bar (<testing>#<HASH>)>", repr(a2))
                 assert_eq("file1", a2.basename)
                 assert_eq("baz/file1", a2.short_path)
                 assert_eq("", a2.extension)
+                assert_eq("file1", a2.stem)
                 assert_eq(False, a2.is_source)
+                assert_eq(None, a2.size_hint)
                 assert_eq("bar", a2.owner.name)
 
                 assert_eq_ignore_hash("<build artifact baz/quz.cpp bound to root//foo:bar (<testing>#<HASH>)>", repr(a3))
                 assert_eq("quz.cpp", a3.basename)
                 assert_eq("baz/quz.cpp", a3.short_path)
                 assert_eq(".cpp", a3.extension)
+                assert_eq("quz", a3.stem)
                 assert_eq(False, a3.is_source)
+                assert_eq(None, a3.size_hint)
                 assert_eq("bar", a3.owner.name)
 
                 assert_eq_ignore_hash("<build artifact baz/file2 bound to root//foo:bar (<testing>#<HASH>)>", repr(a4))
                 assert_eq("file2", a4.basename)
                 assert_eq("baz/file2", a4.short_path)
                 assert_eq("", a4.extension)
+                assert_eq("file2", a4.stem)
                 assert_eq(False, a4.is_source)
+                assert_eq(None, a4.size_hint)
                 assert_eq("bar", a4.owner.name)
 
                 # Validate that attrs are setup properly
@@ -156,15 +172,19 @@ fn declared_artifact() -> buck2_error::Result<()> {
 
                 assert_eq("<build artifact baz/quz.cpp>", repr(a1))
                 assert_eq("quz.cpp", a1.basename)
+                assert_eq("quz", a1.stem)
                 assert_eq(".cpp", a1.extension)
                 assert_eq(False, a1.is_source)
+                assert_eq(None, a1.size_hint)
                 assert_eq(None, a1.owner)
                 assert_eq("<output artifact for baz/quz.cpp>", repr(a1.as_output()))
 
                 assert_eq("<build artifact baz/file2>", repr(a2))
                 assert_eq("file2", a2.basename)
                 assert_eq("", a2.extension)
+                assert_eq("file2", a2.stem)
                 assert_eq(False, a2.is_source)
+                assert_eq(None, a2.size_hint)
                 assert_eq(None, a2.owner)
                 assert_eq("<output artifact for baz/file2>", repr(a2.as_output()))
 
@@ -197,28 +217,36 @@ fn declared_bound() -> buck2_error::Result<()> {
                 assert_eq("quz.h", a1.basename)
                 assert_eq("baz/quz.h", a1.short_path)
                 assert_eq(".h", a1.extension)
+                assert_eq("quz", a1.stem)
                 assert_eq(False, a1.is_source)
+                assert_eq(None, a1.size_hint)
                 assert_eq("bar", a1.owner.name)
 
                 assert_eq_ignore_hash("<build artifact baz/file1 bound to root//foo:bar (<testing>#<HASH>)>", repr(a2))
                 assert_eq("file1", a2.basename)
                 assert_eq("baz/file1", a2.short_path)
                 assert_eq("", a2.extension)
+                assert_eq("file1", a2.stem)
                 assert_eq(False, a2.is_source)
+                assert_eq(None, a2.size_hint)
                 assert_eq("bar", a2.owner.name)
 
                 assert_eq_ignore_hash("<build artifact baz/quz.cpp bound to root//foo:bar (<testing>#<HASH>)>", repr(a3))
                 assert_eq("quz.cpp", a3.basename)
                 assert_eq("baz/quz.cpp", a3.short_path)
                 assert_eq(".cpp", a3.extension)
+                assert_eq("quz", a3.stem)
                 assert_eq(False, a3.is_source)
+                assert_eq(None, a3.size_hint)
                 assert_eq("bar", a3.owner.name)
 
                 assert_eq_ignore_hash("<build artifact baz/file2 bound to root//foo:bar (<testing>#<HASH>)>", repr(a4))
                 assert_eq("file2", a4.basename)
                 assert_eq("baz/file2", a4.short_path)
                 assert_eq("", a4.extension)
+                assert_eq("file2", a4.stem)
                 assert_eq(False, a4.is_source)
+                assert_eq(None, a4.size_hint)
                 assert_eq("bar", a4.owner.name)
 
                 # Validate that attrs are setup properly
@@ -309,17 +337,21 @@ fn project_source_artifact() -> buck2_error::Result<()> {
 fn project_artifact() -> buck2_error::Result<()> {
     let mut tester = Tester::new()?;
     tester.additional_globals(artifactory);
-    let test = indoc!(
+    tester.run_starlark_bzl_test(indoc!(
         r#"
             def test():
-                bound_artifact("//foo:bar", "baz").project("foo")
+                projected = bound_artifact("//foo:bar", "baz").project("include/qux.h")
+                assert_eq_ignore_hash("<build artifact baz/include/qux.h bound to root//foo:bar (<testing>#<HASH>)>", repr(projected))
+                assert_eq("baz/include/qux.h", projected.short_path)
+                assert_eq("qux.h", projected.basename)
+                assert_eq(".h", projected.extension)
+                assert_eq(False, projected.is_source)
+                assert_eq(None, projected.size_hint)
+
+                assert_eq("qux.h", bound_artifact("//foo:bar", "baz").project("include", hide_prefix=True).project("qux.h", hide_prefix=True).short_path)
+                assert_eq("include/qux.h", bound_artifact("//foo:bar", "baz").project("include/qux.h", hide_prefix=True).short_path)
             "#
-    );
-    expect_error(
-        tester.run_starlark_bzl_test(test),
-        test,
-        "This artifact was declared by another rule",
-    );
+    ))?;
     Ok(())
 }
 
@@ -376,6 +408,13 @@ fn bound_artifact_with_associated_artifacts() -> buck2_error::Result<()> {
                 a7 = a5.without_associated_artifacts()
                 assert_eq(a5.short_path, a7.short_path)
                 assert_eq(get_associated_artifacts_as_string(a7), "")
+                assert_eq([], a7.associated_artifacts)
+
+                assert_eq([], a1.associated_artifacts)
+                assert_eq([a1], a2.associated_artifacts)
+                assert_eq([a4], a5.associated_artifacts)
+                assert_eq([a4, a1], a5.with_associated_artifacts([a1]).associated_artifacts)
+                assert_eq([a1], a1.with_associated_artifacts([a1]).with_associated_artifacts([a1]).associated_artifacts)
             "#
     ))
 }
@@ -465,6 +465,26 @@ fn test_inputs_outputs() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_associated_artifacts_are_inputs() -> anyhow::Result<()> {
+    let mut tester = tester()?;
+    tester.run_starlark_bzl_test(indoc!(
+        r#"
+        def test():
+            dwo = source_artifact("foo", "bar/baz.dwo")
+            obj = declared_bound_artifact_with_associated_artifacts("bar/baz.o", [dwo])
+
+            assert_eq(make_inputs([obj, dwo]), cmd_args(obj).inputs)
+            assert_eq(2, len(cmd_args(obj).inputs))
+
+            cli = cmd_args(obj.without_associated_artifacts())
+            assert_eq(1, len(cli.inputs))
+            assert_eq(make_inputs([obj.without_associated_artifacts()]), cli.inputs)
+        "#
+    ))?;
+    Ok(())
+}
+
 #[test]
 fn test_ignore_artifacts() -> anyhow::Result<()> {
     let mut tester = tester()?;
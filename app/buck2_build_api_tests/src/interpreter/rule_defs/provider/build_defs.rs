@@ -8,8 +8,17 @@
  */
 
 use buck2_build_api::interpreter::rule_defs::provider::callable::register_provider;
+use buck2_core::bzl::ImportPath;
+use buck2_interpreter::file_loader::LoadedModules;
 use buck2_interpreter_for_build::interpreter::testing::Tester;
 use indoc::indoc;
+use starlark::docs::DocItem;
+use starlark::docs::DocMember;
+use starlark::docs::DocObject;
+use starlark::docs::DocProperty;
+use starlark::docs::DocString;
+use starlark::docs::DocStringKind;
+use starlark::typing::Ty;
 
 #[test]
 fn test_provider() -> anyhow::Result<()> {
@@ -35,3 +44,43 @@ fn test_provider() -> anyhow::Result<()> {
     ))?;
     Ok(())
 }
+
+#[test]
+fn test_provider_documentation_has_field_types() -> anyhow::Result<()> {
+    let mut tester = Tester::new().unwrap();
+    tester.additional_globals(register_provider);
+    let res = tester.eval_import(
+        &ImportPath::testing_new("root//:defs.bzl"),
+        indoc!(
+            r#"
+            TypedInfo = provider(doc = "Typed docs", fields = {
+                "x": provider_field(int),
+                "y": provider_field(str, default = ""),
+                "z": provider_field(typing.Any, default = None),
+            })
+            "#
+        ),
+        LoadedModules::default(),
+    )?;
+    let docs = res
+        .env()
+        .get("TypedInfo")
+        .expect("TypedInfo to exist")
+        .value()
+        .documentation()
+        .unwrap();
+
+    let property = |typ| DocMember::Property(DocProperty { docs: None, typ });
+    let expected = DocItem::Object(DocObject {
+        docs: DocString::from_docstring(DocStringKind::Starlark, "Typed docs"),
+        members: [
+            ("x".to_owned(), property(Ty::int())),
+            ("y".to_owned(), property(Ty::string())),
+            ("z".to_owned(), property(Ty::any())),
+        ]
+        .into_iter()
+        .collect(),
+    });
+    assert_eq!(expected, docs);
+    Ok(())
+}
@@ -7,6 +7,7 @@
  * of this source tree.
  */
 
+use buck2_build_api::interpreter::rule_defs::provider::builtin::default_info::shared_empty_default_info_stats;
 use buck2_build_api::interpreter::rule_defs::provider::callable::register_provider;
 use buck2_build_api::interpreter::rule_defs::provider::registration::register_builtin_providers;
 use buck2_build_api::interpreter::rule_defs::register_rule_defs;
@@ -63,6 +64,28 @@ fn default_info_is_available() -> buck2_error::Result<()> {
         ))
 }
 
+#[test]
+fn empty_default_info_is_shared() -> buck2_error::Result<()> {
+    let (uses_before, _) = shared_empty_default_info_stats();
+    let mut tester = Tester::new()?;
+    tester.additional_globals(register_rule_defs);
+    tester.run_starlark_bzl_test(indoc!(
+        r#"
+            def test():
+                empty = DefaultInfo()
+                # Created for the sub-target, which has no `DefaultInfo`.
+                with_sub_target = DefaultInfo(sub_targets={"foo": []})
+                assert_eq(repr(empty), repr(with_sub_target.sub_targets["foo"][DefaultInfo]))
+                assert_eq("DefaultInfo(sub_targets={}, default_outputs=[], other_outputs=[])", repr(empty))
+            "#
+    ))?;
+    let (uses_after, saved_bytes) = shared_empty_default_info_stats();
+    // Other tests may use it concurrently.
+    assert!(uses_after >= uses_before + 2);
+    assert!(saved_bytes > 0);
+    Ok(())
+}
+
 #[test]
 fn default_info_validates_types() -> buck2_error::Result<()> {
     // TODO(nmj): More complex types
@@ -15,9 +15,11 @@ use buck2_core::pattern::ParsedPattern;
 use buck2_interpreter_for_build::interpreter::build_context::BuildContext;
 use buck2_interpreter_for_build::interpreter::testing::Tester;
 use indoc::indoc;
+use starlark::debug::InspectVariableInfo;
 use starlark::environment::GlobalsBuilder;
 use starlark::eval::Evaluator;
 use starlark::starlark_module;
+use starlark::values::Heap;
 use starlark::values::Value;
 
 #[starlark_module]
@@ -47,6 +49,22 @@ fn dependency_creator(builder: &mut GlobalsBuilder) {
 
         Ok(Dependency::new(eval.heap(), label, collection, None))
     }
+
+    fn create_provider_collection<'v>(
+        providers: Value<'v>,
+        heap: &'v Heap,
+    ) -> anyhow::Result<Value<'v>> {
+        Ok(heap.alloc(ProviderCollection::try_from_value(providers)?))
+    }
+
+    /// The names of the children the debugger shows when `value` is expanded.
+    fn debugger_children<'v>(value: Value<'v>, heap: &'v Heap) -> starlark::Result<Vec<String>> {
+        Ok(InspectVariableInfo::try_from_value(value, heap)?
+            .sub_values
+            .into_iter()
+            .map(|v| v.name.to_string())
+            .collect())
+    }
 }
 
 #[test]
@@ -72,3 +90,27 @@ fn dependency_works() -> buck2_error::Result<()> {
     ))?;
     Ok(())
 }
+
+#[test]
+fn dependency_debugger_children() -> buck2_error::Result<()> {
+    let mut tester = Tester::new()?;
+    tester.additional_globals(buck2_build_api::interpreter::rule_defs::register_rule_defs);
+    tester.additional_globals(dependency_creator);
+    tester.run_starlark_bzl_test(indoc!(
+        r#"
+        MyInfo = provider(fields = ["x"])
+        frozen = create_collection("root//foo:bar", [DefaultInfo(), MyInfo(x = 1)])
+        def test():
+            notfrozen = create_collection("root//foo:bar", [DefaultInfo(), MyInfo(x = 1)])
+            assert_eq(["label", "DefaultInfo", "MyInfo"], debugger_children(notfrozen))
+            assert_eq(["label", "DefaultInfo", "MyInfo"], debugger_children(frozen))
+
+            collection = create_provider_collection([DefaultInfo(), MyInfo(x = 1)])
+            assert_eq(["DefaultInfo", "MyInfo"], debugger_children(collection))
+
+            # The providers themselves expand to their fields.
+            assert_eq(["x"], debugger_children(notfrozen[MyInfo]))
+        "#
+    ))?;
+    Ok(())
+}
@@ -34,6 +34,8 @@ fn test_construction() -> anyhow::Result<()> {
             ExternalRunnerTestInfo(type = "foo", labels = ("foo",))
             ExternalRunnerTestInfo(type = "foo", use_project_relative_paths = True)
             ExternalRunnerTestInfo(type = "foo", run_from_project_root = True)
+            ExternalRunnerTestInfo(type = "foo", shards = 4)
+            ExternalRunnerTestInfo(type = "foo", remote_only = True)
         "#
     );
     let mut tester = tester();
@@ -184,6 +186,26 @@ fn test_validation() -> anyhow::Result<()> {
         "`executor_overrides`",
     );
 
+    tester.run_starlark_bzl_test_expecting_error(
+        indoc!(
+            r#"
+        def test():
+            ExternalRunnerTestInfo(type = "foo", shards = 0)
+        "#
+        ),
+        "`shards`",
+    );
+
+    tester.run_starlark_bzl_test_expecting_error(
+        indoc!(
+            r#"
+        def test():
+            ExternalRunnerTestInfo(type = "foo", remote_only = "foo")
+        "#
+        ),
+        "`remote_only`",
+    );
+
     Ok(())
 }
 
@@ -9,6 +9,7 @@
 
 use buck2_build_api::interpreter::rule_defs::provider::callable::register_provider;
 use buck2_build_api::interpreter::rule_defs::provider::collection::tester::collection_creator;
+use buck2_build_api::interpreter::rule_defs::provider::collection::FrozenProviderCollectionValue;
 use buck2_build_api::interpreter::rule_defs::register_rule_defs;
 use buck2_core::bzl::ImportPath;
 use buck2_interpreter_for_build::interpreter::testing::expect_error;
@@ -16,6 +17,7 @@ use buck2_interpreter_for_build::interpreter::testing::Tester;
 use indoc::indoc;
 
 use crate::interpreter::rule_defs::artifact::testing::artifactory;
+use crate::interpreter::rule_defs::provider::testing::FrozenProviderCollectionValueExt;
 
 fn provider_collection_tester() -> buck2_error::Result<Tester> {
     let mut tester = Tester::new()?;
@@ -205,3 +207,37 @@ fn provider_collection_get() -> buck2_error::Result<()> {
             "#
     ))
 }
+
+#[test]
+fn provider_collections_indistinguishable() {
+    let collection = |providers: &str| {
+        FrozenProviderCollectionValue::testing_new(&format!(
+            "Foo = provider(fields=[\"x\"])\n{}",
+            providers
+        ))
+    };
+    let indistinguishable =
+        |a: &str, b: &str| collection(a).is_indistinguishable_from(&collection(b));
+
+    let foo = r#"[DefaultInfo(sub_targets={"a": [Foo(x=1)]}), Foo(x={"a": [1, "b"], "c": (None, True)})]"#;
+    assert!(indistinguishable(foo, foo));
+    assert!(!indistinguishable(
+        foo,
+        r#"[DefaultInfo(), Foo(x={"a": [1, "b"], "c": (None, True)})]"#
+    ));
+    // Equal, but not in the same order.
+    assert!(!indistinguishable(
+        r#"[DefaultInfo(), Foo(x={"a": 1, "b": 2})]"#,
+        r#"[DefaultInfo(), Foo(x={"b": 2, "a": 1})]"#,
+    ));
+    // Equal, but not of the same type.
+    assert!(!indistinguishable(
+        "[DefaultInfo(), Foo(x=1)]",
+        "[DefaultInfo(), Foo(x=1.0)]",
+    ));
+    // Functions are never indistinguishable from another evaluation.
+    assert!(!indistinguishable(
+        "[DefaultInfo(), Foo(x=lambda x: x)]",
+        "[DefaultInfo(), Foo(x=lambda x: x)]",
+    ));
+}
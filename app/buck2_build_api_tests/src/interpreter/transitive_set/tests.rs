@@ -440,6 +440,39 @@ fn test_projection_inputs() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_projection_unbound_artifact_error() -> anyhow::Result<()> {
+    let mut tester = transitive_set_tester();
+
+    let contents = indoc!(
+        r#"
+        def project(value):
+            return cmd_args("-I", declared_artifact("foo.h"))
+
+        FooSet = transitive_set(args_projections = {
+            "project": project
+        })
+
+        f1 = make_tset(FooSet, value = 1)
+
+        def test():
+            pass
+        "#
+    );
+
+    // The error says which projection and `cmd_args` contained the artifact.
+    let res = tester.run_starlark_bzl_test(contents);
+    expect_error(
+        res,
+        contents,
+        "In projection `project` of transitive set `FooSet` defined in `root//some/package/defs.bzl`",
+    );
+    let res = tester.run_starlark_bzl_test(contents);
+    expect_error(res, contents, "In argument 1 of `cmd_args`");
+
+    Ok(())
+}
+
 #[test]
 fn test_projection_iteration() -> anyhow::Result<()> {
     let mut tester = transitive_set_tester();
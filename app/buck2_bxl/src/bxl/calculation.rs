@@ -14,6 +14,7 @@ use buck2_build_api::bxl::calculation::BxlCalculationDyn;
 use buck2_build_api::bxl::calculation::BxlComputeResult;
 use buck2_build_api::bxl::calculation::BXL_CALCULATION_IMPL;
 use buck2_core::base_deferred_key::BaseDeferredKeyDyn;
+use buck2_error::ErrorTag;
 use buck2_futures::cancellation::CancellationContext;
 use buck2_interpreter::dice::starlark_profiler::GetStarlarkProfilerInstrumentation;
 use dice::DiceComputations;
@@ -69,7 +70,7 @@ impl Key for internal::BxlComputeKey {
                 async move {
                     eval(ctx, key, profiler, observer)
                         .await
-                        .map_err(buck2_error::Error::from)
+                        .map_err(|e| buck2_error::Error::from(e).tag([ErrorTag::Bxl]))
                         .map(|(result, _, materializations)| BxlComputeResult {
                             bxl_result: Arc::new(result),
                             materializations,
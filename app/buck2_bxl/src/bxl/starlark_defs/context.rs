@@ -58,6 +58,7 @@ use buck2_events::dispatch::console_message;
 use buck2_execute::digest_config::DigestConfig;
 use buck2_execute::digest_config::HasDigestConfig;
 use buck2_futures::cancellable_future::CancellationObserver;
+use buck2_interpreter::cells::visible_cells;
 use buck2_interpreter::dice::starlark_provider::with_starlark_eval_provider;
 use buck2_interpreter::error::BuckStarlarkError;
 use buck2_interpreter::factory::StarlarkEvaluatorProvider;
@@ -732,6 +733,13 @@ fn context_methods(builder: &mut MethodsBuilder) {
         Ok(this.cell_root_abs().to_owned().to_string())
     }
 
+    /// Returns the cells visible from the cell of the BXL script, as a dict from cell alias to a
+    /// struct with the `name` of the cell, its `root` relative to the project root, and whether
+    /// it is an `external` cell. This is the same as the `cells()` global of build files.
+    fn cells<'v>(this: &'v BxlContext<'v>, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+        visible_cells(heap, this.cell_resolver(), this.cell_name())
+    }
+
     /// Gets the target nodes for the `labels`, accepting an optional `target_platform` which is the
     /// target platform configuration used to resolve configurations of any unconfigured target
     /// nodes.
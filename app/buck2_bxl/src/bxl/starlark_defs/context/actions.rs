@@ -104,6 +104,8 @@ pub(crate) async fn resolve_bxl_execution_platform(
         // if something here changes.
         &resolved_transitions,
         &platform_cfgs,
+        // There is no target, so no package to resolve ambiguous selects.
+        None,
     );
 
     let toolchain_deps_configured: Vec<_> = toolchain_deps
@@ -236,6 +236,8 @@ pub(crate) fn build<'v>(
                                         BuildConfiguredLabelOptions {
                                             skippable: false,
                                             want_configured_graph_size: false,
+                                            graph_limits: None,
+                                            speculative_builds: None,
                                         },
                                     ).await
                                 }.then(|stream| stream.collect::<Vec<_>>()).boxed()
@@ -11,6 +11,7 @@ use std::time::Instant;
 
 use buck2_build_api::interpreter::rule_defs::artifact::starlark_artifact_like::ValueAsArtifactLikeUnpack;
 use buck2_build_api::interpreter::rule_defs::cmd_args::value_as::ValueAsCommandLineLike;
+use buck2_interpreter::types::time::StarlarkTimestamp;
 use buck2_node::nodes::configured::ConfiguredTargetNode;
 use buck2_node::nodes::unconfigured::TargetNode;
 use buck2_query::query::syntax::simple::eval::file_set::FileSet;
@@ -213,6 +214,19 @@ pub(crate) fn register_instant_function(builder: &mut GlobalsBuilder) {
         //   where it can cause non-determinism.
         Ok(StarlarkInstant(Instant::now()))
     }
+
+    /// The current wall clock time, as a `timestamp` (see the `time` module).
+    ///
+    /// This is not hermetic, which is why it is only available in BXL.
+    ///
+    /// Sample usage:
+    /// ```text
+    /// def _impl_utc_now(ctx):
+    ///     ctx.output.print(utc_now().format_iso8601())
+    /// ```
+    fn utc_now() -> anyhow::Result<StarlarkTimestamp> {
+        Ok(StarlarkTimestamp::utc_now())
+    }
 }
 
 /// This is used to mark the error returned by `fail_no_stacktrace()` (via context chaining).
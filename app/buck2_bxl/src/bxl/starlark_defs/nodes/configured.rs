@@ -134,6 +134,23 @@ fn configured_target_node_value_methods(builder: &mut MethodsBuilder) {
         Ok(StarlarkFileNode(this.0.buildfile_path().path()))
     }
 
+    /// Gets the values set by `PACKAGE` files for the package of this configured target node,
+    /// including package modifiers. Returns a dict.
+    ///
+    /// Sample usage:
+    /// ```text
+    /// def _impl_package_values(ctx):
+    ///     target_node = ctx.cquery().eval("owner('path/to/file')")[0]
+    ///     ctx.output.print(target_node.package_values)
+    /// ```
+    #[starlark(attribute)]
+    fn package_values<'v>(
+        this: &StarlarkConfiguredTargetNode,
+        heap: &Heap,
+    ) -> anyhow::Result<Value<'v>> {
+        Ok(heap.alloc(this.0.package().package_values.to_value()))
+    }
+
     /// Returns a struct of all the attributes of this target node. The structs fields are the
     /// attributes names, and the values are [`StarlarkConfiguredAttr`].
     ///
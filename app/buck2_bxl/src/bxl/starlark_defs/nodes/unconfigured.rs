@@ -117,6 +117,20 @@ fn target_node_value_methods(builder: &mut MethodsBuilder) {
         Ok(StarlarkFileNode(this.0.buildfile_path().path()))
     }
 
+    /// Gets the values set by `PACKAGE` files for the package of this unconfigured target node,
+    /// including package modifiers. Returns a dict.
+    ///
+    /// Sample usage:
+    /// ```text
+    /// def _impl_package_values(ctx):
+    ///     target_node = ctx.uquery().eval("owner('path/to/file')")[0]
+    ///     ctx.output.print(target_node.package_values)
+    /// ```
+    #[starlark(attribute)]
+    fn package_values<'v>(this: &StarlarkTargetNode, heap: &Heap) -> anyhow::Result<Value<'v>> {
+        Ok(heap.alloc(this.0.package().package_values.to_value()))
+    }
+
     /// Gets the fully qualified name of the rule for this unconfigured target node as a
     /// string. This includes the import path as well.
     ///
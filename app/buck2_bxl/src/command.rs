@@ -231,7 +231,7 @@ async fn bxl(
             &cell_resolver,
             server_ctx.project_root(),
             cwd,
-            server_ctx.events().trace_id(),
+            server_ctx.events(),
             &labeled_configured_build_results
                 .iter()
                 .map(|(k, v)| (k.to_owned(), Some(v.to_owned())))
@@ -65,6 +65,8 @@ pub(crate) struct CfgConstructor {
     pub(crate) cfg_constructor_pre_constraint_analysis: OwnedFrozenValue,
     pub(crate) cfg_constructor_post_constraint_analysis: OwnedFrozenValue,
     pub(crate) key: MetadataKey,
+    /// Short names for modifiers, passed to `stage0`.
+    pub(crate) aliases: Option<OwnedFrozenValue>,
 }
 
 async fn eval_pre_constraint_analysis<'v>(
@@ -74,6 +76,7 @@ async fn eval_pre_constraint_analysis<'v>(
     package_cfg_modifiers: Option<&MetadataValue>,
     target_cfg_modifiers: Option<&MetadataValue>,
     cli_modifiers: &[String],
+    aliases: Option<&OwnedFrozenValue>,
     rule_type: &RuleType,
     module: &'v Module,
     print: &'v EventDispatcherPrintHandler,
@@ -99,6 +102,10 @@ async fn eval_pre_constraint_analysis<'v>(
                 .alloc(package_cfg_modifiers.map(|m| m.as_json()));
             let target_cfg_modifiers = eval.heap().alloc(target_cfg_modifiers.map(|m| m.as_json()));
             let cli_modifiers = eval.heap().alloc(cli_modifiers);
+            let aliases = match aliases {
+                Some(aliases) => aliases.owned_value(eval.frozen_heap()),
+                None => Value::new_none(),
+            };
             let rule_name = eval.heap().alloc(rule_type.name());
 
             // TODO: should eventually accept cli modifiers and target modifiers (T163570597)
@@ -107,6 +114,7 @@ async fn eval_pre_constraint_analysis<'v>(
                 ("package_modifiers", package_cfg_modifiers),
                 ("target_modifiers", target_cfg_modifiers),
                 ("cli_modifiers", cli_modifiers),
+                ("aliases", aliases),
                 ("rule_name", rule_name),
             ];
 
@@ -226,6 +234,7 @@ async fn eval_underlying(
         package_cfg_modifiers,
         target_cfg_modifiers,
         cli_modifiers,
+        cfg_constructor.aliases.as_ref(),
         rule_type,
         &module,
         &print,
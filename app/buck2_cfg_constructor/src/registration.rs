@@ -25,6 +25,7 @@ use starlark::any::ProvidesStaticType;
 use starlark::environment::GlobalsBuilder;
 use starlark::eval::Evaluator;
 use starlark::starlark_module;
+use starlark::values::none::NoneOr;
 use starlark::values::none::NoneType;
 use starlark::values::starlark_value;
 use starlark::values::Freeze;
@@ -61,6 +62,7 @@ struct StarlarkCfgConstructor<'v> {
     stage0: Value<'v>,
     stage1: Value<'v>,
     key: String,
+    aliases: Option<Value<'v>>,
 }
 
 #[derive(
@@ -75,6 +77,7 @@ struct FrozenStarlarkCfgConstructor {
     stage0: FrozenValue,
     stage1: FrozenValue,
     key: String,
+    aliases: Option<FrozenValue>,
 }
 
 #[starlark_value(type = "StarlarkCfgConstructor")]
@@ -93,12 +96,14 @@ impl<'v> Freeze for StarlarkCfgConstructor<'v> {
             stage0,
             stage1,
             key,
+            aliases,
         } = self;
-        let (stage0, stage1) = (stage0, stage1).freeze(freezer)?;
+        let (stage0, stage1, aliases) = (stage0, stage1, aliases).freeze(freezer)?;
         Ok(FrozenStarlarkCfgConstructor {
             stage0,
             stage1,
             key,
+            aliases,
         })
     }
 }
@@ -107,10 +112,17 @@ fn make_cfg_constructor(
     cfg_constructor: OwnedFrozenValue,
 ) -> anyhow::Result<Arc<dyn CfgConstructorImpl>> {
     let cfg_constructor = cfg_constructor.downcast_anyhow::<FrozenStarlarkCfgConstructor>()?;
-    let (cfg_constructor_pre_constraint_analysis, cfg_constructor_post_constraint_analysis) = unsafe {
+    let (
+        cfg_constructor_pre_constraint_analysis,
+        cfg_constructor_post_constraint_analysis,
+        aliases,
+    ) = unsafe {
         (
             OwnedFrozenValue::new(cfg_constructor.owner().dupe(), cfg_constructor.stage0),
             OwnedFrozenValue::new(cfg_constructor.owner().dupe(), cfg_constructor.stage1),
+            cfg_constructor
+                .aliases
+                .map(|aliases| OwnedFrozenValue::new(cfg_constructor.owner().dupe(), aliases)),
         )
     };
     let key = MetadataKeyRef::new(&cfg_constructor.key)?.to_owned();
@@ -118,6 +130,7 @@ fn make_cfg_constructor(
         cfg_constructor_pre_constraint_analysis,
         cfg_constructor_post_constraint_analysis,
         key,
+        aliases,
     }))
 }
 
@@ -131,10 +144,13 @@ pub(crate) fn register_set_cfg_constructor(globals: &mut GlobalsBuilder) {
     ///   stage0: The first cfg constructor that will be invoked before configuration rules are analyzed.
     ///   stage1: The second cfg constructor that will be invoked after configuration rules are analyzed.
     ///   key: The key for cfg modifiers on PACKAGE values and metadata.
+    ///   aliases: Short names for modifiers, e.g. `struct(asan = "//sanitizers:asan")`, which
+    ///     are passed as is to `stage0` to resolve the modifiers from the command line.
     fn set_cfg_constructor<'v>(
         #[starlark(require=named)] stage0: Value<'v>,
         #[starlark(require=named)] stage1: Value<'v>,
         #[starlark(require=named)] key: &str,
+        #[starlark(require=named, default = NoneOr::None)] aliases: NoneOr<Value<'v>>,
         eval: &mut Evaluator<'v, '_>,
     ) -> anyhow::Result<NoneType> {
         let build_context = BuildContext::from_context(eval)?;
@@ -159,6 +175,7 @@ pub(crate) fn register_set_cfg_constructor(globals: &mut GlobalsBuilder) {
                 stage0,
                 stage1,
                 key: key.to_owned(),
+                aliases: aliases.into_option(),
             })
         });
         Ok(NoneType)
@@ -18,6 +18,14 @@ pub mod protobuf_util;
 
 tonic::include_proto!("buck.daemon");
 
+/// The version of the protocol between the client and the daemon.
+///
+/// A client accepts a daemon of a different buck2 version for read-only commands (like queries) if
+/// the daemon reports the same protocol version. Bump this when changing the daemon API in a way
+/// that older clients or daemons can't handle, e.g. removing a field, changing its meaning, or
+/// changing the events that read-only commands rely on. Adding fields doesn't need a bump.
+pub const PROTOCOL_VERSION: u32 = 1;
+
 #[derive(Debug, buck2_error::Error)]
 enum BuckDaemonProtoError {
     #[error("daemon request was missing client context")]
@@ -14,12 +14,16 @@ use serde::Serialize;
 pub enum NewGenericRequest {
     Materialize(MaterializeRequest),
     DebugEval(DebugEvalRequest),
+    ChangedTargets(ChangedTargetsRequest),
+    DebugDeterminism(DebugDeterminismRequest),
 }
 
 #[derive(Serialize, Deserialize)]
 pub enum NewGenericResponse {
     Materialize(MaterializeResponse),
     DebugEval(DebugEvalResponse),
+    ChangedTargets(ChangedTargetsResponse),
+    DebugDeterminism(DebugDeterminismResponse),
 }
 
 #[derive(Serialize, Deserialize)]
@@ -38,3 +42,57 @@ pub struct DebugEvalRequest {
 
 #[derive(Serialize, Deserialize)]
 pub struct DebugEvalResponse {}
+
+#[derive(Serialize, Deserialize)]
+pub struct ChangedTargetsRequest {
+    /// Absolute paths of the changed files, including deleted ones.
+    pub changed_files: Vec<String>,
+    /// Patterns of the targets which may be impacted, `//...` if empty.
+    pub universe: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ChangedTargetsResponse {
+    /// Targets of the universe impacted by the changes, sorted.
+    pub targets: Vec<String>,
+    /// Packages whose build file was deleted, sorted.
+    pub deleted_packages: Vec<String>,
+    /// A buckconfig file changed, so every target of the universe is impacted.
+    pub config_changed: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DebugDeterminismRequest {
+    /// Patterns of the targets whose default outputs are checked.
+    pub patterns: Vec<String>,
+    /// Also check the actions producing the inputs of these outputs, transitively.
+    pub transitive: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DebugDeterminismResponse {
+    /// Number of actions which were executed twice.
+    pub checked: u64,
+    /// Actions which were not checked because they can only be executed remotely.
+    pub skipped: Vec<String>,
+    pub nondeterministic: Vec<NondeterministicAction>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct NondeterministicAction {
+    pub action: String,
+    pub outputs: Vec<NondeterministicOutput>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct NondeterministicOutput {
+    /// Project-relative path of the output.
+    pub path: String,
+    /// Where the outputs of the first and second executions were copied, relative to the project
+    /// root.
+    pub first: String,
+    pub second: String,
+    /// Files of the output which differ, relative to the output. Empty when the output itself is
+    /// a file.
+    pub files: Vec<String>,
+}
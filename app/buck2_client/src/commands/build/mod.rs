@@ -33,6 +33,9 @@ use buck2_client_ctx::final_console::FinalConsole;
 use buck2_client_ctx::output_destination_arg::OutputDestinationArg;
 use buck2_client_ctx::path_arg::PathArg;
 use buck2_client_ctx::streaming::StreamingCommand;
+use buck2_common::output_template::OutputTemplate;
+use buck2_common::output_template::TemplateValue;
+use buck2_common::output_template::TemplateVariableKind;
 use dupe::Dupe;
 use gazebo::prelude::*;
 
@@ -67,6 +70,24 @@ pub struct BuildCommand {
     #[clap(flatten)]
     show_output: CommonOutputOptions,
 
+    /// Print a line for each target built, following a template, e.g. `{label} {outputs[0]}`.
+    /// The variables are `label`, `config`, `outputs` (the paths of the default outputs relative
+    /// to the project root, `{outputs[N]}` for one of them) and `project_root`. Use `{{` and `}}`
+    /// for literal braces.
+    #[clap(
+        long,
+        value_name = "TEMPLATE",
+        conflicts_with_all = &[
+            "show-output",
+            "show-full-output",
+            "show-simple-output",
+            "show-full-simple-output",
+            "show-json-output",
+            "show-full-json-output",
+        ]
+    )]
+    output_template: Option<String>,
+
     #[clap(
         long = "materializations",
         short = 'M',
@@ -206,6 +227,11 @@ impl StreamingCommand for BuildCommand {
         ctx: &mut ClientCommandContext<'_>,
     ) -> ExitResult {
         let show_default_other_outputs = false;
+        let output_template = self
+            .output_template
+            .as_deref()
+            .map(|t| OutputTemplate::parse(t, BUILD_TEMPLATE_VARIABLES))
+            .transpose()?;
         let context = ctx.client_context(matches, &self)?;
 
         let result = buckd
@@ -223,6 +249,7 @@ impl StreamingCommand for BuildCommand {
                     }),
                     response_options: Some(ResponseOptions {
                         return_outputs: self.show_output.format().is_some()
+                            || output_template.is_some()
                             || self.output_path.is_some(),
                         return_default_other_outputs: show_default_other_outputs,
                     }),
@@ -297,6 +324,14 @@ impl StreamingCommand for BuildCommand {
                     format,
                     show_default_other_outputs,
                 )?;
+            } else if let Some(template) = &output_template {
+                print_outputs_template(
+                    &mut stdout,
+                    response.build_targets,
+                    &response.project_root,
+                    template,
+                    show_default_other_outputs,
+                )?;
             }
 
             ExitResult::success()
@@ -334,6 +369,44 @@ pub(crate) fn print_build_failed(console: &FinalConsole) -> anyhow::Result<()> {
     console.print_error("BUILD FAILED")
 }
 
+const BUILD_TEMPLATE_VARIABLES: &[(&str, TemplateVariableKind)] = &[
+    ("label", TemplateVariableKind::String),
+    ("config", TemplateVariableKind::String),
+    ("outputs", TemplateVariableKind::List),
+    ("project_root", TemplateVariableKind::String),
+];
+
+fn print_outputs_template(
+    mut out: impl Write,
+    targets: Vec<BuildTarget>,
+    project_root: &str,
+    template: &OutputTemplate,
+    show_all_outputs: bool,
+) -> anyhow::Result<()> {
+    let mut buffer = String::new();
+    for build_target in targets {
+        let outputs: Vec<String> = build_target
+            .outputs
+            .into_iter()
+            .filter(|output| {
+                output
+                    .providers
+                    .as_ref()
+                    .map_or(true, |p| show_all_outputs || (p.default_info && !p.other))
+            })
+            .map(|output| output.path)
+            .collect();
+        template.render(&mut buffer, |name| match name {
+            "label" => TemplateValue::String(&build_target.target),
+            "config" => TemplateValue::String(&build_target.configuration),
+            "outputs" => TemplateValue::List(&outputs),
+            _ => TemplateValue::String(project_root),
+        });
+    }
+    out.write_all(buffer.as_bytes())?;
+    Ok(())
+}
+
 pub(crate) fn print_outputs(
     out: impl Write,
     targets: Vec<BuildTarget>,
@@ -384,6 +457,28 @@ mod tests {
         )?)
     }
 
+    #[test]
+    fn output_template() -> anyhow::Result<()> {
+        let template =
+            OutputTemplate::parse("{label} ({config}) {outputs[0]}", BUILD_TEMPLATE_VARIABLES)?;
+        let targets = vec![BuildTarget {
+            target: "root//:foo".to_owned(),
+            configuration: "cfg".to_owned(),
+            outputs: vec![buck2_cli_proto::build_target::BuildOutput {
+                path: "buck-out/foo".to_owned(),
+                providers: None,
+            }],
+            ..BuildTarget::default()
+        }];
+        let mut out = Vec::new();
+        print_outputs_template(&mut out, targets, "/repo", &template, false)?;
+        assert_eq!(
+            "root//:foo (cfg) buck-out/foo\n",
+            std::str::from_utf8(&out)?
+        );
+        Ok(())
+    }
+
     #[test]
     fn infos_default() -> anyhow::Result<()> {
         let opts = parse(&[])?;
@@ -0,0 +1,186 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::path::Path;
+use std::process::Stdio;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use buck2_cli_proto::new_generic::ChangedTargetsRequest;
+use buck2_cli_proto::new_generic::ChangedTargetsResponse;
+use buck2_cli_proto::new_generic::NewGenericRequest;
+use buck2_cli_proto::new_generic::NewGenericResponse;
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::common::CommonBuildConfigurationOptions;
+use buck2_client_ctx::common::CommonCommandOptions;
+use buck2_client_ctx::common::CommonConsoleOptions;
+use buck2_client_ctx::common::CommonDaemonCommandOptions;
+use buck2_client_ctx::daemon::client::BuckdClientConnector;
+use buck2_client_ctx::exit_result::ExitResult;
+use buck2_client_ctx::path_arg::PathArg;
+use buck2_client_ctx::streaming::StreamingCommand;
+use buck2_util::process::async_background_command;
+use clap::ArgMatches;
+use gazebo::prelude::SliceExt;
+
+#[derive(Debug, buck2_error::Error)]
+enum ChangedTargetsError {
+    #[error("Could not find a Mercurial or Git repository containing `{0}`")]
+    NoRepository(String),
+    #[error("`{0}` failed: {1}")]
+    VcsFailed(String, String),
+    #[error("Unexpected response to `changed-targets` (internal error)")]
+    UnexpectedResponse,
+}
+
+/// Print the targets impacted by changes to files.
+///
+/// The changed files are given by `--files`, or else asked to source control (Mercurial or Git):
+/// the changes of the working copy compared to the revision `REV`.
+///
+/// A target is impacted if its files, build file, `PACKAGE` files or any `.bzl` file they load
+/// changed, if it depends on a target in a deleted package, or if it depends on an impacted
+/// target. A buckconfig change impacts every target.
+#[derive(Debug, clap::Parser)]
+#[clap(name = "changed-targets")]
+pub struct ChangedTargetsCommand {
+    #[clap(flatten)]
+    common_opts: CommonCommandOptions,
+
+    /// Revision to compare the working copy to. Defaults to the parent of the working copy, i.e.
+    /// the uncommitted changes.
+    #[clap(value_name = "REV", conflicts_with = "files")]
+    rev: Option<String>,
+
+    /// Changed files, instead of asking source control.
+    #[clap(long, multiple_values = true)]
+    files: Vec<PathArg>,
+
+    /// Patterns of the targets which may be impacted.
+    #[clap(long, multiple_values = true, default_value = "//...")]
+    universe: Vec<String>,
+
+    /// Print the result as JSON, including the deleted packages.
+    #[clap(long)]
+    json: bool,
+}
+
+/// Paths of the files changed in the working copy since `rev`, relative to `root`.
+async fn vcs_changed_files(root: &Path, rev: Option<&str>) -> anyhow::Result<Vec<String>> {
+    let mut command = if root.ancestors().any(|dir| dir.join(".hg").exists()) {
+        let mut command = async_background_command("hg");
+        // Paths are relative to the current directory when a pattern is given.
+        command.args(["status", "--no-status", "--rev", rev.unwrap_or("."), "."]);
+        command
+    } else if root.ancestors().any(|dir| dir.join(".git").exists()) {
+        let mut command = async_background_command("git");
+        command.args([
+            "diff",
+            "--name-only",
+            "--no-renames",
+            "--relative",
+            rev.unwrap_or("HEAD"),
+        ]);
+        command
+    } else {
+        return Err(ChangedTargetsError::NoRepository(root.display().to_string()).into());
+    };
+    let output = command
+        .current_dir(root)
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .context("Error running source control")?;
+    if !output.status.success() {
+        return Err(ChangedTargetsError::VcsFailed(
+            format!("{:?}", command.as_std()),
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        )
+        .into());
+    }
+    Ok(String::from_utf8(output.stdout)
+        .context("Source control output is not UTF-8")?
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_owned())
+        .collect())
+}
+
+#[async_trait]
+impl StreamingCommand for ChangedTargetsCommand {
+    const COMMAND_NAME: &'static str = "changed-targets";
+
+    async fn exec_impl(
+        self,
+        buckd: &mut BuckdClientConnector,
+        matches: &ArgMatches,
+        ctx: &mut ClientCommandContext<'_>,
+    ) -> ExitResult {
+        let changed_files = if self.files.is_empty() {
+            let root = ctx.paths()?.project_root().root().as_path().to_owned();
+            vcs_changed_files(&root, self.rev.as_deref())
+                .await?
+                .map(|path| root.join(path).to_string_lossy().into_owned())
+        } else {
+            self.files
+                .try_map(|p| anyhow::Ok(p.resolve(&ctx.working_dir).to_str()?.to_owned()))?
+        };
+
+        let context = ctx.client_context(matches, &self)?;
+        let response = buckd
+            .with_flushing()
+            .new_generic(
+                context,
+                NewGenericRequest::ChangedTargets(ChangedTargetsRequest {
+                    changed_files,
+                    universe: self.universe.clone(),
+                }),
+                ctx.stdin()
+                    .console_interaction_stream(&self.common_opts.console_opts),
+            )
+            .await??;
+        let NewGenericResponse::ChangedTargets(response) = response else {
+            return ExitResult::err(ChangedTargetsError::UnexpectedResponse.into());
+        };
+        let ChangedTargetsResponse {
+            targets,
+            deleted_packages,
+            config_changed,
+        } = response;
+
+        if self.json {
+            buck2_client_ctx::println!(
+                "{}",
+                serde_json::to_string_pretty(&serde_json::json!({
+                    "targets": targets,
+                    "deleted_packages": deleted_packages,
+                    "config_changed": config_changed,
+                }))?
+            )?;
+        } else {
+            for target in targets {
+                buck2_client_ctx::println!("{}", target)?;
+            }
+        }
+
+        ExitResult::success()
+    }
+
+    fn console_opts(&self) -> &CommonConsoleOptions {
+        &self.common_opts.console_opts
+    }
+
+    fn event_log_opts(&self) -> &CommonDaemonCommandOptions {
+        &self.common_opts.event_log_opts
+    }
+
+    fn common_opts(&self) -> &CommonBuildConfigurationOptions {
+        &self.common_opts.config_opts
+    }
+}
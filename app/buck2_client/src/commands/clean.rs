@@ -22,6 +22,7 @@ use buck2_client_ctx::streaming::BuckSubcommand;
 use buck2_common::argv::Argv;
 use buck2_common::argv::SanitizedArgv;
 use buck2_common::daemon_dir::DaemonDir;
+use buck2_common::invocation_roots::home_buck_downloads_dir;
 use buck2_core::fs::fs_util;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
 use buck2_core::fs::paths::abs_path::AbsPath;
@@ -70,6 +71,11 @@ the specified duration, without killing the daemon",
     ///  - Writing to `buck-out` without being expected by Buck
     #[clap(long = "tracked-only", requires = "stale")]
     tracked_only: bool,
+
+    /// Delete the cache of downloaded files shared by all the checkouts of this user (enabled by
+    /// `http.download_cache`) instead of `buck-out`, without killing the daemon.
+    #[clap(long, conflicts_with = "stale")]
+    downloads: bool,
 }
 
 impl CleanCommand {
@@ -84,6 +90,13 @@ impl CleanCommand {
             return cmd.exec(matches, ctx);
         }
 
+        if self.downloads {
+            return ctx.instant_command("clean", async move |_ctx| {
+                let console = &self.common_opts.console_opts.final_console();
+                clean_downloads(console, self.dry_run)
+            });
+        }
+
         ctx.instant_command("clean", async move |ctx| {
             let buck_out_dir = ctx.paths()?.buck_out_path();
             let daemon_dir = ctx.paths()?.daemon_dir()?;
@@ -149,6 +162,19 @@ async fn clean(
     Ok(())
 }
 
+/// Entries of the download cache are written atomically and a missing entry is just downloaded
+/// again, so the cache can be deleted while daemons are using it.
+fn clean_downloads(console: &FinalConsole, dry_run: bool) -> anyhow::Result<()> {
+    let dir = home_buck_downloads_dir()?;
+    if fs_util::try_exists(&dir)? {
+        if !dry_run {
+            fs_util::remove_all(&dir)?;
+        }
+        console.print_stderr(&dir.to_string())?;
+    }
+    Ok(())
+}
+
 fn collect_paths_to_clean(buck_out_path: &AbsNormPathBuf) -> anyhow::Result<Vec<AbsNormPathBuf>> {
     let mut paths_to_clean = vec![];
     let dir = fs_util::read_dir(buck_out_path)?;
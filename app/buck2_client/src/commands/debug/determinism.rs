@@ -0,0 +1,134 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use async_trait::async_trait;
+use buck2_cli_proto::new_generic::DebugDeterminismRequest;
+use buck2_cli_proto::new_generic::DebugDeterminismResponse;
+use buck2_cli_proto::new_generic::NewGenericRequest;
+use buck2_cli_proto::new_generic::NewGenericResponse;
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::common::CommonBuildConfigurationOptions;
+use buck2_client_ctx::common::CommonCommandOptions;
+use buck2_client_ctx::common::CommonConsoleOptions;
+use buck2_client_ctx::common::CommonDaemonCommandOptions;
+use buck2_client_ctx::daemon::client::BuckdClientConnector;
+use buck2_client_ctx::exit_result::ExitCode;
+use buck2_client_ctx::exit_result::ExitResult;
+use buck2_client_ctx::streaming::StreamingCommand;
+use clap::ArgMatches;
+
+#[derive(Debug, buck2_error::Error)]
+enum DeterminismError {
+    #[error("Unexpected response to `debug determinism` (internal error)")]
+    UnexpectedResponse,
+}
+
+/// Check that the actions producing the default outputs of targets are deterministic.
+///
+/// The targets are built, then their actions are executed a second time, locally, bypassing the
+/// caches and with a different temporary directory and environment order, and the outputs of both
+/// executions are compared. The differing outputs of both
+/// executions are copied to `buck-out/v2/determinism`.
+///
+/// Exits with a failure if any action is nondeterministic.
+#[derive(Debug, clap::Parser)]
+pub struct DeterminismCommand {
+    #[clap(flatten)]
+    common_opts: CommonCommandOptions,
+
+    /// Patterns of the targets to check.
+    #[clap(value_name = "TARGET_PATTERNS", required = true)]
+    patterns: Vec<String>,
+
+    /// Also check the actions producing their inputs, transitively.
+    #[clap(long)]
+    transitive: bool,
+
+    /// Print the result as JSON.
+    #[clap(long)]
+    json: bool,
+}
+
+#[async_trait]
+impl StreamingCommand for DeterminismCommand {
+    const COMMAND_NAME: &'static str = "determinism";
+
+    async fn exec_impl(
+        self,
+        buckd: &mut BuckdClientConnector,
+        matches: &ArgMatches,
+        ctx: &mut ClientCommandContext<'_>,
+    ) -> ExitResult {
+        let context = ctx.client_context(matches, &self)?;
+        let response = buckd
+            .with_flushing()
+            .new_generic(
+                context,
+                NewGenericRequest::DebugDeterminism(DebugDeterminismRequest {
+                    patterns: self.patterns.clone(),
+                    transitive: self.transitive,
+                }),
+                ctx.stdin()
+                    .console_interaction_stream(&self.common_opts.console_opts),
+            )
+            .await??;
+        let NewGenericResponse::DebugDeterminism(response) = response else {
+            return ExitResult::err(DeterminismError::UnexpectedResponse.into());
+        };
+
+        if self.json {
+            buck2_client_ctx::println!("{}", serde_json::to_string_pretty(&response)?)?;
+        } else {
+            print_response(&response)?;
+        }
+
+        if response.nondeterministic.is_empty() {
+            ExitResult::success()
+        } else {
+            ExitResult::status(ExitCode::UserError)
+        }
+    }
+
+    fn console_opts(&self) -> &CommonConsoleOptions {
+        &self.common_opts.console_opts
+    }
+
+    fn event_log_opts(&self) -> &CommonDaemonCommandOptions {
+        &self.common_opts.event_log_opts
+    }
+
+    fn common_opts(&self) -> &CommonBuildConfigurationOptions {
+        &self.common_opts.config_opts
+    }
+}
+
+fn print_response(response: &DebugDeterminismResponse) -> anyhow::Result<()> {
+    for action in &response.nondeterministic {
+        buck2_client_ctx::println!("Nondeterministic action: {}", action.action)?;
+        for output in &action.outputs {
+            buck2_client_ctx::println!("  {}", output.path)?;
+            for file in &output.files {
+                if !file.is_empty() {
+                    buck2_client_ctx::println!("    differs: {}", file)?;
+                }
+            }
+            buck2_client_ctx::println!("    first execution: {}", output.first)?;
+            buck2_client_ctx::println!("    second execution: {}", output.second)?;
+        }
+    }
+    for action in &response.skipped {
+        buck2_client_ctx::eprintln!("Skipped action which can only run remotely: {}", action)?;
+    }
+    buck2_client_ctx::eprintln!(
+        "Checked {} actions, {} nondeterministic",
+        response.checked,
+        response.nondeterministic.len()
+    )?;
+    Ok(())
+}
@@ -28,6 +28,11 @@ pub struct DiceDumpCommand {
     serde: bool,
     #[clap(long, group = "dice_dump_format")]
     serde_pretty: bool,
+    /// Write statistics as JSON instead of the graph: for every key type, the number of keys,
+    /// values, edges, invalidations and the memory used, then the 100 keys invalidated the most
+    /// times.
+    #[clap(long, group = "dice_dump_format")]
+    stats: bool,
 }
 
 #[async_trait]
@@ -48,6 +53,8 @@ impl StreamingCommand for DiceDumpCommand {
             DiceDumpFormat::Bincode
         } else if self.serde_pretty {
             DiceDumpFormat::JsonPretty
+        } else if self.stats {
+            DiceDumpFormat::Stats
         } else {
             DiceDumpFormat::Tsv
         };
@@ -0,0 +1,106 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use anyhow::Context as _;
+use async_trait::async_trait;
+use buck2_cli_proto::UnstableAllocatorStatsRequest;
+use buck2_cli_proto::UnstableHeapDumpRequest;
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::common::CommonBuildConfigurationOptions;
+use buck2_client_ctx::common::CommonConsoleOptions;
+use buck2_client_ctx::common::CommonDaemonCommandOptions;
+use buck2_client_ctx::daemon::client::BuckdClientConnector;
+use buck2_client_ctx::exit_result::ExitResult;
+use buck2_client_ctx::path_arg::PathArg;
+use buck2_client_ctx::streaming::StreamingCommand;
+use buck2_core::fs::fs_util;
+
+/// Write the allocator statistics and a heap profile of the daemon to a directory.
+///
+/// The directory contains `allocator_stats.json`, the output of jemalloc's `malloc_stats_print`
+/// as JSON, and `heap.prof`, a profile of currently allocated memory which can be read with
+/// `jeprof`. The daemon keeps running.
+///
+/// The heap profile requires the daemon to have been started with the env variable
+/// `MALLOC_CONF=prof:true,prof_final:false`; without it, only the allocator statistics are written.
+/// The daemon also records allocator statistics in the `Snapshot` events of every command's event
+/// log.
+#[derive(Debug, clap::Parser)]
+pub struct HeapProfileCommand {
+    /// The directory to write the statistics and the profile to.
+    #[clap(short, long, value_name = "PATH")]
+    dir: PathArg,
+}
+
+#[async_trait]
+impl StreamingCommand for HeapProfileCommand {
+    const COMMAND_NAME: &'static str = "heap_profile";
+
+    fn existing_only() -> bool {
+        true
+    }
+
+    async fn exec_impl(
+        self,
+        buckd: &mut BuckdClientConnector,
+        _matches: &clap::ArgMatches,
+        ctx: &mut ClientCommandContext<'_>,
+    ) -> ExitResult {
+        let dir = self.dir.resolve(&ctx.working_dir);
+        fs_util::create_dir_all(&dir)?;
+
+        let stats = buckd
+            .with_flushing()
+            .unstable_allocator_stats(UnstableAllocatorStatsRequest {
+                options: "Jmdablxg".to_owned(),
+            })
+            .await?;
+        let stats_path = dir.join("allocator_stats.json");
+        fs_util::write(&stats_path, stats.response)?;
+        buck2_client_ctx::eprintln!("Allocator stats written to `{}`", stats_path.display())?;
+
+        let profile_path = dir.join("heap.prof");
+        buckd
+            .with_flushing()
+            .unstable_heap_dump(UnstableHeapDumpRequest {
+                destination_path: profile_path.to_str()?.to_owned(),
+            })
+            .await
+            .context("Failed to write heap profile")?;
+        buck2_client_ctx::eprintln!("Heap profile written to `{}`", profile_path.display())?;
+
+        ExitResult::success()
+    }
+
+    fn console_opts(&self) -> &CommonConsoleOptions {
+        CommonConsoleOptions::none_ref()
+    }
+
+    fn event_log_opts(&self) -> &CommonDaemonCommandOptions {
+        CommonDaemonCommandOptions::default_ref()
+    }
+
+    fn common_opts(&self) -> &CommonBuildConfigurationOptions {
+        CommonBuildConfigurationOptions::default_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let command = HeapProfileCommand::try_parse_from(["heap-profile", "--dir", "out"]).unwrap();
+        assert_eq!(command.dir.display().to_string(), "out");
+        assert!(HeapProfileCommand::try_parse_from(["heap-profile"]).is_err());
+    }
+}
@@ -19,11 +19,13 @@ use dice_dump::DiceDumpCommand;
 use file_status::FileStatusCommand;
 use flush_dep_files::FlushDepFilesCommand;
 use heap_dump::HeapDumpCommand;
+use heap_profile::HeapProfileCommand;
 use internal_version::InternalVersionCommand;
 use materialize::MaterializeCommand;
 
 use crate::commands::debug::allocative::AllocativeCommand;
 use crate::commands::debug::daemon_dir::DaemonDirCommand;
+use crate::commands::debug::determinism::DeterminismCommand;
 use crate::commands::debug::eval::EvalCommand;
 use crate::commands::debug::exe::ExeCommand;
 use crate::commands::debug::log_perf::LogPerfCommand;
@@ -41,12 +43,14 @@ mod allocator_stats;
 mod chrome_trace;
 mod crash;
 mod daemon_dir;
+mod determinism;
 mod dice_dump;
 mod eval;
 mod exe;
 mod file_status;
 mod flush_dep_files;
 mod heap_dump;
+mod heap_profile;
 mod internal_version;
 mod log_perf;
 mod materialize;
@@ -69,6 +73,7 @@ pub enum DebugCommand {
     HeapDump(HeapDumpCommand),
     /// Dumps allocator stat
     AllocatorStats(AllocatorStatsCommand),
+    HeapProfile(HeapProfileCommand),
     /// Dump the DICE graph to a file and saves it to disk.
     DiceDump(DiceDumpCommand),
     #[clap(setting(clap::AppSettings::Hidden))]
@@ -103,6 +108,7 @@ pub enum DebugCommand {
     #[clap(subcommand)]
     Paranoid(ParanoidCommand),
     Eval(EvalCommand),
+    Determinism(DeterminismCommand),
 }
 
 impl DebugCommand {
@@ -113,6 +119,7 @@ impl DebugCommand {
             DebugCommand::Crash(cmd) => cmd.exec(matches, ctx),
             DebugCommand::HeapDump(cmd) => cmd.exec(matches, ctx),
             DebugCommand::AllocatorStats(cmd) => cmd.exec(matches, ctx),
+            DebugCommand::HeapProfile(cmd) => cmd.exec(matches, ctx),
             DebugCommand::Replay(cmd) => cmd.exec(matches, ctx),
             DebugCommand::InternalVersion(cmd) => cmd.exec(matches, ctx),
             DebugCommand::ChromeTrace(cmd) => cmd.exec(matches, ctx),
@@ -131,6 +138,7 @@ impl DebugCommand {
             DebugCommand::PersistEventLogs(cmd) => cmd.exec(matches, ctx),
             DebugCommand::Paranoid(cmd) => cmd.exec(matches, ctx),
             DebugCommand::Eval(cmd) => cmd.exec(matches, ctx),
+            DebugCommand::Determinism(cmd) => cmd.exec(matches, ctx),
         }
     }
 
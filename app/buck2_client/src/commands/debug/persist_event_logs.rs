@@ -22,8 +22,8 @@ use buck2_core::soft_error;
 use buck2_data::instant_event::Data;
 use buck2_data::InstantEvent;
 use buck2_data::PersistEventLogSubprocess;
-use buck2_events::sink::scribe::new_thrift_scribe_sink_if_enabled;
-use buck2_events::sink::scribe::ThriftScribeSink;
+use buck2_events::sink::remote::new_remote_event_sink_if_enabled;
+use buck2_events::sink::remote::RemoteEventSink;
 use buck2_events::BuckEvent;
 use buck2_wrapper_common::invocation_id::TraceId;
 use thiserror::Error;
@@ -44,6 +44,10 @@ fn manifold_ttl_s() -> anyhow::Result<Option<u64>> {
 
 const MAX_WAIT: Duration = Duration::from_secs(5 * 60);
 
+/// How many chunks in a row can fail to upload before the upload is abandoned. After a failure,
+/// the upload resumes from the last uploaded byte with the next chunk.
+const MAX_CONSECUTIVE_FAILURES: u32 = 5;
+
 #[derive(Debug, Error)]
 pub(crate) enum PersistEventLogError {
     #[error("Read more bytes than are available")]
@@ -186,7 +190,10 @@ async fn upload_task(
                         uploader.bump_total_bytes(n);
                         while uploader.can_fill_chunk()? {
                             uploader.last_upload_attempt = Instant::now();
-                            uploader.upload_chunk().await?;
+                            if !uploader.try_upload_chunk().await? {
+                                // Resume with the next chunk.
+                                break;
+                            }
                         }
                     },
                     // This indicates that we have finished writing to the log file
@@ -197,7 +204,7 @@ async fn upload_task(
                 // We have waited enough since the last upload
                 uploader.last_upload_attempt = Instant::now();
                 if uploader.something_to_upload() {
-                    uploader.upload_chunk().await?;
+                    uploader.try_upload_chunk().await?;
                 }
             }
         }
@@ -205,11 +212,15 @@ async fn upload_task(
 
     // When tx gets dropped, rx will return None
     while uploader.can_fill_chunk()? {
-        uploader.upload_chunk().await?;
+        if !uploader.try_upload_chunk().await? {
+            sleep(uploader.backoff()).await;
+        }
     }
 
     // Last chunk to upload is smaller than &reader
-    uploader.upload_chunk().await?;
+    while !uploader.try_upload_chunk().await? {
+        sleep(uploader.backoff()).await;
+    }
 
     Ok(())
 }
@@ -223,6 +234,7 @@ struct Uploader<'a> {
     reader: ChunkReader,
     total_bytes: u64,
     last_upload_attempt: Instant,
+    consecutive_failures: u32,
 }
 
 impl<'a> Uploader<'a> {
@@ -245,6 +257,7 @@ impl<'a> Uploader<'a> {
             reader: ChunkReader::new()?,
             total_bytes: 0,
             last_upload_attempt: Instant::now(),
+            consecutive_failures: 0,
         })
     }
 
@@ -261,6 +274,37 @@ impl<'a> Uploader<'a> {
         Ok(())
     }
 
+    /// Uploads a chunk, returning whether it was uploaded. A failed chunk is uploaded again from
+    /// the same position by the next attempt, unless too many failed in a row.
+    async fn try_upload_chunk(&mut self) -> anyhow::Result<bool> {
+        match self.upload_chunk().await {
+            Ok(()) => {
+                self.consecutive_failures = 0;
+                Ok(true)
+            }
+            Err(e) => {
+                self.consecutive_failures += 1;
+                if self.consecutive_failures >= MAX_CONSECUTIVE_FAILURES {
+                    return Err(e.context(format!(
+                        "Failed to upload {} chunks in a row",
+                        self.consecutive_failures
+                    )));
+                }
+                tracing::warn!(
+                    "Failed to upload event log chunk at {}, will resume: {:#}",
+                    self.manifold.position(),
+                    e
+                );
+                Ok(false)
+            }
+        }
+    }
+
+    /// How long to wait before uploading again after a failure.
+    fn backoff(&self) -> Duration {
+        Duration::from_secs(1 << self.consecutive_failures.min(6))
+    }
+
     fn bump_total_bytes(&mut self, n: u64) {
         self.total_bytes += n
     }
@@ -340,7 +384,7 @@ fn categorize_error(err: &anyhow::Error) -> &'static str {
 }
 
 async fn dispatch_event_to_scribe(
-    sink: Option<&ThriftScribeSink>,
+    sink: Option<&RemoteEventSink>,
     invocation_id: &TraceId,
     result: PersistEventLogSubprocess,
 ) {
@@ -360,8 +404,8 @@ async fn dispatch_event_to_scribe(
     };
 }
 
-fn create_scribe_sink(ctx: &ClientCommandContext) -> anyhow::Result<Option<ThriftScribeSink>> {
-    new_thrift_scribe_sink_if_enabled(
+fn create_scribe_sink(ctx: &ClientCommandContext) -> anyhow::Result<Option<RemoteEventSink>> {
+    new_remote_event_sink_if_enabled(
         ctx.fbinit(),
         /* buffer size */ 100,
         /* retry_backoff */ Duration::from_millis(500),
@@ -0,0 +1,561 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::fmt;
+use std::process::Stdio;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::daemon::client::connect::BuckdProcessInfo;
+use buck2_client_ctx::daemon_constraints;
+use buck2_client_ctx::exit_result::ExitCode;
+use buck2_client_ctx::exit_result::ExitResult;
+use buck2_common::argv::Argv;
+use buck2_common::argv::SanitizedArgv;
+use buck2_common::daemon_dir::DaemonDir;
+use buck2_common::legacy_configs::cells::BuckConfigBasedCells;
+use buck2_common::legacy_configs::LegacyBuckConfig;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+use buck2_core::is_open_source;
+use buck2_http::HttpClientBuilder;
+use buck2_util::process::async_background_command;
+use bytesize::ByteSize;
+use chrono::DateTime;
+use chrono::Utc;
+use serde::Serialize;
+
+/// How long each check may take.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Free space under buck-out below which builds are likely to fail.
+const MIN_FREE_SPACE: u64 = 1 << 30;
+/// Free space under buck-out below which builds may soon fail.
+const LOW_FREE_SPACE: u64 = 10 << 30;
+
+/// Clock skew above which cache entries may be considered expired too early or too late.
+const MAX_CLOCK_SKEW: Duration = Duration::from_secs(5 * 60);
+/// Clock skew which is probably not a measurement error.
+const NOTABLE_CLOCK_SKEW: Duration = Duration::from_secs(30);
+
+/// Buckconfig keys of the remote execution addresses, in the `buck2_re_client` section.
+const RE_ADDRESS_KEYS: &[&str] = &[
+    "address",
+    "engine_address",
+    "cas_address",
+    "action_cache_address",
+];
+
+/// Check the environment for common problems, like an unreachable file watcher or remote
+/// execution service, a full disk or a skewed clock, and suggest how to fix them.
+///
+/// Exits with a failure if any problem prevents builds from working, so it can be used as a
+/// preflight check in CI.
+#[derive(Debug, clap::Parser)]
+pub struct DoctorCommand {
+    /// Print the results as JSON.
+    #[clap(long)]
+    json: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Severity {
+    Ok,
+    Skipped,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Ok => write!(f, "OK"),
+            Severity::Skipped => write!(f, "SKIPPED"),
+            Severity::Warning => write!(f, "WARNING"),
+            Severity::Error => write!(f, "ERROR"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct CheckResult {
+    check: &'static str,
+    severity: Severity,
+    message: String,
+    /// How to fix the problem, when there is one.
+    remediation: Option<String>,
+}
+
+impl CheckResult {
+    fn new(check: &'static str, severity: Severity, message: impl Into<String>) -> Self {
+        CheckResult {
+            check,
+            severity,
+            message: message.into(),
+            remediation: None,
+        }
+    }
+
+    fn ok(check: &'static str, message: impl Into<String>) -> Self {
+        Self::new(check, Severity::Ok, message)
+    }
+
+    fn skipped(check: &'static str, message: impl Into<String>) -> Self {
+        Self::new(check, Severity::Skipped, message)
+    }
+
+    fn warning(
+        check: &'static str,
+        message: impl Into<String>,
+        remediation: impl Into<String>,
+    ) -> Self {
+        Self::new(check, Severity::Warning, message).with_remediation(remediation)
+    }
+
+    fn error(
+        check: &'static str,
+        message: impl Into<String>,
+        remediation: impl Into<String>,
+    ) -> Self {
+        Self::new(check, Severity::Error, message).with_remediation(remediation)
+    }
+
+    fn with_remediation(mut self, remediation: impl Into<String>) -> Self {
+        self.remediation = Some(remediation.into());
+        self
+    }
+}
+
+impl DoctorCommand {
+    pub fn exec(self, _matches: &clap::ArgMatches, ctx: ClientCommandContext<'_>) -> ExitResult {
+        ctx.with_runtime(async move |ctx| {
+            let results = run_checks(&ctx).await?;
+
+            if self.json {
+                buck2_client_ctx::println!("{}", serde_json::to_string_pretty(&results)?)?;
+            } else {
+                for result in &results {
+                    buck2_client_ctx::println!(
+                        "[{}] {}: {}",
+                        result.severity,
+                        result.check,
+                        result.message
+                    )?;
+                    if let Some(remediation) = &result.remediation {
+                        buck2_client_ctx::println!("    Fix: {}", remediation)?;
+                    }
+                }
+            }
+
+            if results.iter().any(|r| r.severity == Severity::Error) {
+                ExitResult::status(ExitCode::UserError)
+            } else {
+                ExitResult::success()
+            }
+        })
+    }
+
+    pub fn sanitize_argv(&self, argv: Argv) -> SanitizedArgv {
+        argv.no_need_to_sanitize()
+    }
+}
+
+async fn run_checks(ctx: &ClientCommandContext<'_>) -> anyhow::Result<Vec<CheckResult>> {
+    let paths = ctx.paths()?;
+    let mut results = Vec::new();
+
+    let config = match BuckConfigBasedCells::parse(paths.project_root()) {
+        Ok(cells) => {
+            let config = cells
+                .configs_by_name
+                .get(cells.cell_resolver.root_cell())?
+                .clone();
+            results.push(CheckResult::ok("Buckconfig", "Parsed the buckconfigs"));
+            Some(config)
+        }
+        Err(e) => {
+            results.push(CheckResult::error(
+                "Buckconfig",
+                format!("Failed to parse the buckconfigs: {:#}", e),
+                "Fix the buckconfig files of the project",
+            ));
+            None
+        }
+    };
+
+    if let Some(config) = &config {
+        results.push(check_watchman(config).await);
+        results.extend(check_remote_execution(config).await);
+    }
+    results.push(check_disk_space(
+        &paths.buck_out_path(),
+        paths.project_root().root(),
+    ));
+    results.push(check_daemon(&paths.daemon_dir()?).await);
+    if let Some(config) = &config {
+        results.push(check_clock_skew(config, ctx).await);
+    }
+    Ok(results)
+}
+
+async fn check_watchman(config: &LegacyBuckConfig) -> CheckResult {
+    const CHECK: &str = "Watchman";
+
+    let default = if is_open_source() {
+        "notify"
+    } else {
+        "watchman"
+    };
+    let file_watcher = config.get("buck2", "file_watcher").unwrap_or(default);
+    if file_watcher != "watchman" {
+        return CheckResult::skipped(
+            CHECK,
+            format!("Not used, `buck2.file_watcher` is `{}`", file_watcher),
+        );
+    }
+
+    let output = async_background_command("watchman")
+        .args(["--no-pretty", "version"])
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output();
+    match tokio::time::timeout(CHECK_TIMEOUT, output).await {
+        Err(_) => CheckResult::error(
+            CHECK,
+            format!(
+                "`watchman version` did not respond within {}s",
+                CHECK_TIMEOUT.as_secs()
+            ),
+            "Restart watchman with `watchman shutdown-server`, then check `watchman version` works",
+        ),
+        Ok(Err(e)) => CheckResult::error(
+            CHECK,
+            format!("Failed to run watchman: {}", e),
+            "Install watchman and make sure it is in the `PATH`, or set `buck2.file_watcher = notify`",
+        ),
+        Ok(Ok(output)) if !output.status.success() => CheckResult::error(
+            CHECK,
+            format!(
+                "`watchman version` failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            "Restart watchman with `watchman shutdown-server`, then check `watchman version` works",
+        ),
+        Ok(Ok(output)) => {
+            let version = serde_json::from_slice::<serde_json::Value>(&output.stdout)
+                .ok()
+                .and_then(|v| v.get("version")?.as_str().map(ToOwned::to_owned));
+            CheckResult::ok(
+                CHECK,
+                format!(
+                    "Reachable, version {}",
+                    version.as_deref().unwrap_or("unknown")
+                ),
+            )
+        }
+    }
+}
+
+async fn check_remote_execution(config: &LegacyBuckConfig) -> Vec<CheckResult> {
+    const CHECK: &str = "Remote execution";
+
+    let tls = config
+        .parse::<bool>("buck2_re_client", "tls")
+        .ok()
+        .flatten()
+        .unwrap_or(true);
+    let mut addresses = Vec::new();
+    for key in RE_ADDRESS_KEYS {
+        if let Some(address) = config.get("buck2_re_client", key) {
+            if !addresses.contains(&address) {
+                addresses.push(address);
+            }
+        }
+    }
+    if addresses.is_empty() {
+        return vec![CheckResult::skipped(
+            CHECK,
+            "No address is configured in the `buck2_re_client` section",
+        )];
+    }
+
+    let mut results = Vec::new();
+    for address in addresses {
+        let Some(host_port) = host_port(address, tls) else {
+            results.push(CheckResult::error(
+                CHECK,
+                format!("Invalid address `{}`", address),
+                "Fix the addresses of the `buck2_re_client` section of the buckconfig",
+            ));
+            continue;
+        };
+        let connect = tokio::net::TcpStream::connect(&host_port);
+        results.push(match tokio::time::timeout(CHECK_TIMEOUT, connect).await {
+            Ok(Ok(_)) => CheckResult::ok(CHECK, format!("`{}` is reachable", host_port)),
+            Ok(Err(e)) => CheckResult::error(
+                CHECK,
+                format!("Failed to connect to `{}`: {}", host_port, e),
+                "Check the network connection, VPN and proxy settings, and the addresses of the \
+                `buck2_re_client` section of the buckconfig",
+            ),
+            Err(_) => CheckResult::error(
+                CHECK,
+                format!(
+                    "Connecting to `{}` timed out after {}s",
+                    host_port,
+                    CHECK_TIMEOUT.as_secs()
+                ),
+                "Check the network connection, VPN and proxy settings, and the addresses of the \
+                `buck2_re_client` section of the buckconfig",
+            ),
+        });
+    }
+    results
+}
+
+/// The `host:port` to connect to for a remote execution address, which may have a scheme and a
+/// path.
+fn host_port(address: &str, tls: bool) -> Option<String> {
+    let (rest, default_port) = match address.split_once("://") {
+        Some(("https" | "grpcs", rest)) => (rest, 443),
+        Some(("http" | "grpc", rest)) => (rest, 80),
+        Some(_) => return None,
+        None => (address, if tls { 443 } else { 80 }),
+    };
+    let authority = rest.split('/').next()?;
+    if authority.is_empty() {
+        return None;
+    }
+    // The port follows the last `:`, which is not inside the brackets of an IPv6 address.
+    let has_port = authority
+        .rsplit_once(':')
+        .map_or(false, |(_, port)| !port.contains(']'));
+    if has_port {
+        Some(authority.to_owned())
+    } else {
+        Some(format!("{}:{}", authority, default_port))
+    }
+}
+
+fn check_disk_space(buck_out: &AbsNormPath, project_root: &AbsNormPath) -> CheckResult {
+    const CHECK: &str = "Disk space";
+
+    let path = if buck_out.exists() {
+        buck_out
+    } else {
+        project_root
+    };
+    match available_space(path) {
+        Err(e) => CheckResult::error(
+            CHECK,
+            format!("Failed to get the free space of `{}`: {:#}", path, e),
+            "Check that the project directory is accessible",
+        ),
+        Ok(None) => CheckResult::skipped(CHECK, "Not supported on this platform"),
+        Ok(Some(free)) => {
+            let message = format!("{} free under `{}`", ByteSize(free), path);
+            let remediation = "Free up disk space, for example by deleting old outputs with \
+                `buck2 clean --stale`";
+            if free < MIN_FREE_SPACE {
+                CheckResult::error(CHECK, message, remediation)
+            } else if free < LOW_FREE_SPACE {
+                CheckResult::warning(CHECK, message, remediation)
+            } else {
+                CheckResult::ok(CHECK, message)
+            }
+        }
+    }
+}
+
+/// Bytes available to unprivileged users on the file system of `path`.
+#[cfg(unix)]
+fn available_space(path: &AbsNormPath) -> anyhow::Result<Option<u64>> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes())?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: the path is NUL-terminated and `stat` is valid for writes.
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    #[allow(clippy::unnecessary_cast)] // The field types differ between platforms.
+    Ok(Some(stat.f_bavail as u64 * stat.f_frsize as u64))
+}
+
+#[cfg(not(unix))]
+fn available_space(_path: &AbsNormPath) -> anyhow::Result<Option<u64>> {
+    Ok(None)
+}
+
+async fn check_daemon(daemon_dir: &DaemonDir) -> CheckResult {
+    const CHECK: &str = "Daemon";
+
+    let Ok(info) = BuckdProcessInfo::load(daemon_dir) else {
+        return CheckResult::skipped(CHECK, "No daemon is running");
+    };
+    let status = async {
+        let buckd = info.create_channel().await?.upgrade().await?;
+        buckd
+            .with_subscribers(Default::default())
+            .with_flushing()
+            .status(false)
+            .await
+    };
+    let status = match tokio::time::timeout(CHECK_TIMEOUT, status).await {
+        Ok(Ok(status)) => status,
+        Ok(Err(e)) => {
+            return CheckResult::error(
+                CHECK,
+                format!("The daemon is not responding: {:#}", e),
+                "Kill it with `buck2 kill`, the next command starts a new daemon",
+            );
+        }
+        Err(_) => {
+            return CheckResult::error(
+                CHECK,
+                format!(
+                    "The daemon did not respond within {}s",
+                    CHECK_TIMEOUT.as_secs()
+                ),
+                "Kill it with `buck2 kill`, the next command starts a new daemon",
+            );
+        }
+    };
+
+    let client_version = daemon_constraints::version();
+    match status.daemon_constraints.map(|c| c.version) {
+        Some(daemon_version) if daemon_version != client_version => CheckResult::warning(
+            CHECK,
+            format!(
+                "The daemon runs buck2 version `{}`, and this client is version `{}`",
+                daemon_version, client_version
+            ),
+            "The next command restarts the daemon, or run `buck2 kill` to restart it now. If this \
+            keeps happening, several versions of buck2 are used in this project",
+        ),
+        _ => CheckResult::ok(CHECK, "Running the same version as this client"),
+    }
+}
+
+async fn check_clock_skew(
+    config: &LegacyBuckConfig,
+    ctx: &ClientCommandContext<'_>,
+) -> CheckResult {
+    const CHECK: &str = "Clock skew";
+
+    let Some(url) = config.get("doctor", "clock_reference_url") else {
+        return CheckResult::skipped(
+            CHECK,
+            "Set `doctor.clock_reference_url` to an HTTP URL whose server has a correct clock to \
+            check it",
+        );
+    };
+
+    let client = if is_open_source() {
+        HttpClientBuilder::oss()
+    } else {
+        HttpClientBuilder::internal(ctx.allow_vpnless_for_logging().unwrap_or_default())
+    };
+    let client = match client {
+        Ok(client) => client.build(),
+        Err(e) => {
+            return CheckResult::error(
+                CHECK,
+                format!("Failed to create an HTTP client: {:#}", e),
+                "Check the TLS and proxy settings of this machine",
+            );
+        }
+    };
+
+    let before = SystemTime::now();
+    let response = match tokio::time::timeout(CHECK_TIMEOUT, client.head(url)).await {
+        Ok(Ok(response)) => response,
+        Ok(Err(e)) => {
+            return CheckResult::error(
+                CHECK,
+                format!("Request to `{}` failed: {}", url, e),
+                "Check the network connection, and `doctor.clock_reference_url`",
+            );
+        }
+        Err(_) => {
+            return CheckResult::error(
+                CHECK,
+                format!(
+                    "Request to `{}` timed out after {}s",
+                    url,
+                    CHECK_TIMEOUT.as_secs()
+                ),
+                "Check the network connection, and `doctor.clock_reference_url`",
+            );
+        }
+    };
+    let after = SystemTime::now();
+
+    let Some(date) = response
+        .headers()
+        .get("date")
+        .and_then(|d| d.to_str().ok())
+        .and_then(|d| DateTime::parse_from_rfc2822(d).ok())
+    else {
+        return CheckResult::error(
+            CHECK,
+            format!("The response of `{}` has no valid `Date` header", url),
+            "Set `doctor.clock_reference_url` to a server which sends one",
+        );
+    };
+
+    // The server generated the date at some point during the request.
+    let local: DateTime<Utc> =
+        (before + after.duration_since(before).unwrap_or_default() / 2).into();
+    let skew = (local - date.with_timezone(&Utc))
+        .to_std()
+        .or_else(|_| (date.with_timezone(&Utc) - local).to_std())
+        .unwrap_or_default();
+    let message = format!("The local clock is {}s off from `{}`", skew.as_secs(), url);
+    let remediation = "Synchronize the clock of this machine, for example by enabling NTP. A \
+        skewed clock makes cache entries expire too early or be used after they expired";
+    if skew > MAX_CLOCK_SKEW {
+        CheckResult::error(CHECK, message, remediation)
+    } else if skew > NOTABLE_CLOCK_SKEW {
+        CheckResult::warning(CHECK, message, remediation)
+    } else {
+        CheckResult::ok(CHECK, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_port() {
+        assert_eq!(
+            Some("re.example.com:443".to_owned()),
+            host_port("re.example.com", true)
+        );
+        assert_eq!(
+            Some("re.example.com:80".to_owned()),
+            host_port("re.example.com", false)
+        );
+        assert_eq!(
+            Some("re.example.com:8980".to_owned()),
+            host_port("grpc://re.example.com:8980", true)
+        );
+        assert_eq!(
+            Some("re.example.com:443".to_owned()),
+            host_port("https://re.example.com/instance", false)
+        );
+        assert_eq!(Some("[::1]:443".to_owned()), host_port("[::1]", true));
+        assert_eq!(Some("[::1]:8980".to_owned()), host_port("[::1]:8980", true));
+        assert_eq!(None, host_port("ftp://re.example.com", true));
+        assert_eq!(None, host_port("grpc://", true));
+    }
+}
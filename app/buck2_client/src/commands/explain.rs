@@ -0,0 +1,513 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::exit_result::ExitResult;
+use buck2_client_ctx::path_arg::PathArg;
+use buck2_common::argv::Argv;
+use buck2_common::argv::SanitizedArgv;
+use buck2_core::fs::fs_util;
+use buck2_data::ActionExecutionKind;
+use buck2_event_log::stream_value::StreamValue;
+use buck2_event_observer::display;
+use buck2_event_observer::display::TargetDisplayOptions;
+use buck2_event_observer::fmt_duration::fmt_duration;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio_stream::StreamExt;
+
+use crate::commands::log::critical_path::critical_path_entry_name;
+use crate::commands::log::options::EventLogOptions;
+use crate::commands::log::summary::timestamp;
+
+/// Actions shown in the timeline, the longest ones, so the page stays small for large builds.
+const MAX_TIMELINE_ACTIONS: usize = 500;
+
+/// Characters of the stderr of a failed action kept in the report, from its end.
+const MAX_STDERR_CHARS: usize = 20_000;
+
+/// Write a self-contained HTML report of a build, to share a build investigation with people
+/// who don't use the command line.
+///
+/// The report shows the outcome of the command, how actions were executed (including cache hits),
+/// the critical path, the failed actions with their errors and stderr, and a timeline of the
+/// longest actions. It is read from the event log of the command, the last one by default.
+#[derive(Debug, clap::Parser)]
+#[clap(group = clap::ArgGroup::with_name("destination").required(true))]
+pub struct ExplainCommand {
+    #[clap(flatten)]
+    event_log: EventLogOptions,
+
+    /// Write the report to this file.
+    #[clap(short, long, group = "destination", value_name = "PATH")]
+    output: Option<PathArg>,
+
+    /// Serve the report on localhost until interrupted.
+    #[clap(long, group = "destination")]
+    serve: bool,
+
+    /// The port to serve the report on. Any free port by default.
+    #[clap(long, requires = "serve", default_value = "0")]
+    port: u16,
+}
+
+impl ExplainCommand {
+    pub fn exec(self, _matches: &clap::ArgMatches, ctx: ClientCommandContext<'_>) -> ExitResult {
+        ctx.with_runtime(async move |ctx| {
+            let log_path = self.event_log.get(&ctx).await?;
+            let (invocation, mut events) = log_path.unpack_stream().await?;
+
+            let mut report = Report {
+                command_line: invocation.display_command_line(),
+                trace_id: invocation.trace_id.to_string(),
+                ..Report::default()
+            };
+            while let Some(event) = events.try_next().await? {
+                match event {
+                    StreamValue::Event(event) => report.update_with_event(&event)?,
+                    StreamValue::Result(..) | StreamValue::PartialResult(..) => {}
+                }
+            }
+            let html = report.render();
+
+            match self.output {
+                Some(output) => {
+                    let output = output.resolve(&ctx.working_dir);
+                    fs_util::write(&output, html)?;
+                    buck2_client_ctx::eprintln!("Report written to `{}`", output.display())?;
+                }
+                None => serve(&html, self.port).await?,
+            }
+            anyhow::Ok(())
+        })?;
+
+        ExitResult::success()
+    }
+
+    pub fn sanitize_argv(&self, argv: Argv) -> SanitizedArgv {
+        argv.no_need_to_sanitize()
+    }
+}
+
+/// Serve the same page for every request until interrupted.
+async fn serve(html: &str, port: u16) -> anyhow::Result<()> {
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", port)).await?;
+    buck2_client_ctx::eprintln!(
+        "Serving the report at http://{}/, press Ctrl-C to stop",
+        listener.local_addr()?
+    )?;
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let res: std::io::Result<()> = try {
+            // The request doesn't matter, but the client expects its headers to be read.
+            let mut request = Vec::new();
+            let mut buf = [0; 4096];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = stream.read(&mut buf).await?;
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            let headers = format!(
+                "HTTP/1.1 200 OK\r\n\
+                Content-Type: text/html; charset=utf-8\r\n\
+                Content-Length: {}\r\n\
+                Connection: close\r\n\r\n",
+                html.len()
+            );
+            stream.write_all(headers.as_bytes()).await?;
+            stream.write_all(html.as_bytes()).await?;
+            stream.shutdown().await?;
+        };
+        if let Err(e) = res {
+            tracing::debug!("Failed to serve the report: {}", e);
+        }
+    }
+}
+
+struct ActionRecord {
+    name: String,
+    execution_kind: &'static str,
+    /// Time since the epoch.
+    end: Duration,
+    duration: Duration,
+    failure: Option<ActionFailure>,
+}
+
+struct ActionFailure {
+    error: String,
+    stderr: String,
+}
+
+struct CriticalPathRow {
+    kind: &'static str,
+    name: String,
+    action: String,
+    duration: Option<Duration>,
+}
+
+#[derive(Default)]
+struct Report {
+    command_line: String,
+    trace_id: String,
+    /// Times since the epoch of the first and the last event.
+    first_event: Option<Duration>,
+    last_event: Option<Duration>,
+    success: Option<bool>,
+    actions: Vec<ActionRecord>,
+    execution_kinds: BTreeMap<&'static str, u64>,
+    critical_path: Vec<CriticalPathRow>,
+}
+
+fn execution_kind_name(kind: i32) -> &'static str {
+    match ActionExecutionKind::from_i32(kind) {
+        Some(ActionExecutionKind::Local) => "local",
+        Some(ActionExecutionKind::LocalWorker) => "local worker",
+        Some(ActionExecutionKind::Remote) => "remote",
+        Some(ActionExecutionKind::ActionCache) => "cache hit",
+        Some(ActionExecutionKind::RemoteDepFileCache) => "cache hit (remote dep file)",
+        Some(ActionExecutionKind::LocalDepFile) => "cache hit (local dep file)",
+        Some(ActionExecutionKind::Simple) => "simple",
+        Some(ActionExecutionKind::Deferred) => "deferred",
+        Some(ActionExecutionKind::NotSet) | None => "unknown",
+    }
+}
+
+fn action_failure(end: &buck2_data::ActionExecutionEnd) -> ActionFailure {
+    use buck2_data::action_execution_end::Error;
+
+    let error = match &end.error {
+        Some(Error::Unknown(message)) => message.clone(),
+        Some(Error::MissingOutputs(missing)) => missing.message.clone(),
+        Some(Error::CommandExecutionError(_)) => "The command failed".to_owned(),
+        None => "The action failed".to_owned(),
+    };
+    let stderr = end
+        .commands
+        .last()
+        .and_then(|c| c.details.as_ref())
+        .map(|d| display::sanitize_output_colors(d.stderr.as_bytes()))
+        .unwrap_or_default();
+    let stderr = match stderr.char_indices().rev().nth(MAX_STDERR_CHARS) {
+        Some((i, _)) => format!("[...]\n{}", &stderr[i..]),
+        None => stderr,
+    };
+    ActionFailure { error, stderr }
+}
+
+impl Report {
+    fn update_with_event(&mut self, event: &buck2_data::BuckEvent) -> anyhow::Result<()> {
+        let time = timestamp(event);
+        if self.first_event.is_none() {
+            self.first_event = time;
+        }
+        self.last_event = time.or(self.last_event);
+
+        match &event.data {
+            Some(buck2_data::buck_event::Data::SpanEnd(end)) => match &end.data {
+                Some(buck2_data::span_end_event::Data::ActionExecution(action)) => {
+                    let execution_kind = execution_kind_name(action.execution_kind);
+                    *self.execution_kinds.entry(execution_kind).or_default() += 1;
+                    let name = display::display_action_identity(
+                        action.key.as_ref(),
+                        action.name.as_ref(),
+                        TargetDisplayOptions::for_log(),
+                    )
+                    .unwrap_or_else(|_| "unknown".to_owned());
+                    self.actions.push(ActionRecord {
+                        name,
+                        execution_kind,
+                        end: time.unwrap_or_default(),
+                        duration: end
+                            .duration
+                            .clone()
+                            .and_then(|d| d.try_into().ok())
+                            .unwrap_or_default(),
+                        failure: action.failed.then(|| action_failure(action)),
+                    });
+                }
+                Some(buck2_data::span_end_event::Data::Command(command)) => {
+                    self.success = Some(command.is_success);
+                }
+                _ => {}
+            },
+            Some(buck2_data::buck_event::Data::Instant(instant)) => match &instant.data {
+                Some(buck2_data::instant_event::Data::BuildGraphInfo(info)) => {
+                    self.critical_path.clear();
+                    for entry in &info.critical_path2 {
+                        if let Some(name) = critical_path_entry_name(entry)? {
+                            self.critical_path.push(CriticalPathRow {
+                                kind: name.kind,
+                                name: name.name,
+                                action: format!("{} {}", name.category, name.identifier)
+                                    .trim()
+                                    .to_owned(),
+                                duration: entry
+                                    .total_duration
+                                    .clone()
+                                    .and_then(|d| d.try_into().ok()),
+                            });
+                        }
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn render(&self) -> String {
+        let mut html = String::new();
+        self.render_to(&mut html).unwrap();
+        html
+    }
+
+    fn render_to(&self, html: &mut String) -> std::fmt::Result {
+        let elapsed = match (self.first_event, self.last_event) {
+            (Some(first), Some(last)) => last.saturating_sub(first),
+            _ => Duration::ZERO,
+        };
+
+        writeln!(html, "<!DOCTYPE html>")?;
+        writeln!(html, "<html><head><meta charset=\"utf-8\">")?;
+        writeln!(
+            html,
+            "<title>buck2 build {}</title>",
+            escape(&self.trace_id)
+        )?;
+        writeln!(html, "<style>{}</style>", STYLE)?;
+        writeln!(html, "</head><body>")?;
+
+        writeln!(html, "<h1>Build report</h1>")?;
+        writeln!(html, "<table>")?;
+        writeln!(
+            html,
+            "<tr><th>Command</th><td><code>{}</code></td></tr>",
+            escape(&self.command_line)
+        )?;
+        writeln!(
+            html,
+            "<tr><th>Build ID</th><td>{}</td></tr>",
+            escape(&self.trace_id)
+        )?;
+        writeln!(
+            html,
+            "<tr><th>Duration</th><td>{}</td></tr>",
+            fmt_duration(elapsed, 1.0)
+        )?;
+        let result = match self.success {
+            Some(true) => "<span class=\"ok\">Succeeded</span>",
+            Some(false) => "<span class=\"failed\">Failed</span>",
+            None => "Did not finish",
+        };
+        writeln!(html, "<tr><th>Result</th><td>{}</td></tr>", result)?;
+        writeln!(html, "</table>")?;
+
+        writeln!(html, "<h2>Actions</h2>")?;
+        writeln!(html, "<table>")?;
+        writeln!(html, "<tr><th>Execution</th><th>Actions</th></tr>")?;
+        for (kind, count) in &self.execution_kinds {
+            writeln!(html, "<tr><td>{}</td><td>{}</td></tr>", kind, count)?;
+        }
+        writeln!(
+            html,
+            "<tr><th>Total</th><th>{}</th></tr>",
+            self.actions.len()
+        )?;
+        writeln!(html, "</table>")?;
+
+        if !self.critical_path.is_empty() {
+            writeln!(html, "<h2>Critical path</h2>")?;
+            writeln!(html, "<table>")?;
+            writeln!(
+                html,
+                "<tr><th>Kind</th><th>Name</th><th>Action</th><th>Duration</th></tr>"
+            )?;
+            for row in &self.critical_path {
+                writeln!(
+                    html,
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    row.kind,
+                    escape(&row.name),
+                    escape(&row.action),
+                    row.duration
+                        .map(|d| fmt_duration(d, 1.0))
+                        .unwrap_or_default()
+                )?;
+            }
+            writeln!(html, "</table>")?;
+        }
+
+        let failed: Vec<_> = self
+            .actions
+            .iter()
+            .filter_map(|a| Some((a, a.failure.as_ref()?)))
+            .collect();
+        if !failed.is_empty() {
+            writeln!(html, "<h2>Failed actions</h2>")?;
+            for (action, failure) in failed {
+                writeln!(html, "<details open>")?;
+                writeln!(
+                    html,
+                    "<summary class=\"failed\">{}</summary>",
+                    escape(&action.name)
+                )?;
+                writeln!(html, "<p>{}</p>", escape(&failure.error))?;
+                if !failure.stderr.is_empty() {
+                    writeln!(html, "<pre>{}</pre>", escape(&failure.stderr))?;
+                }
+                writeln!(html, "</details>")?;
+            }
+        }
+
+        self.render_timeline(html, elapsed)?;
+
+        writeln!(html, "</body></html>")
+    }
+
+    fn render_timeline(&self, html: &mut String, elapsed: Duration) -> std::fmt::Result {
+        let (Some(first), false) = (self.first_event, elapsed.is_zero()) else {
+            return Ok(());
+        };
+        let mut actions: Vec<_> = self.actions.iter().collect();
+        actions.sort_by_key(|a| std::cmp::Reverse(a.duration));
+        let omitted = actions.len().saturating_sub(MAX_TIMELINE_ACTIONS);
+        actions.truncate(MAX_TIMELINE_ACTIONS);
+        actions.sort_by_key(|a| a.end.saturating_sub(a.duration));
+
+        writeln!(html, "<h2>Timeline</h2>")?;
+        if omitted > 0 {
+            writeln!(
+                html,
+                "<p>The {} longest actions are shown, {} shorter ones are omitted.</p>",
+                MAX_TIMELINE_ACTIONS, omitted
+            )?;
+        }
+        writeln!(html, "<div class=\"timeline\">")?;
+        let percent = |d: Duration| 100.0 * d.as_secs_f64() / elapsed.as_secs_f64();
+        for action in actions {
+            let start = action
+                .end
+                .saturating_sub(action.duration)
+                .saturating_sub(first);
+            writeln!(
+                html,
+                "<div class=\"row\" title=\"{} ({}, {})\"><span class=\"label\">{}</span>\
+                <span class=\"track\"><span class=\"bar{}\" style=\"margin-left:{:.2}%;width:{:.2}%\"></span></span></div>",
+                escape(&action.name),
+                action.execution_kind,
+                fmt_duration(action.duration, 1.0),
+                escape(&action.name),
+                if action.failure.is_some() {
+                    " failed"
+                } else {
+                    ""
+                },
+                percent(start),
+                percent(action.duration).max(0.1),
+            )?;
+        }
+        writeln!(html, "</div>")
+    }
+}
+
+const STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.6em; text-align: left; }
+pre { background: #f4f4f4; padding: 0.6em; overflow-x: auto; }
+.ok { color: #080; }
+.failed { color: #c00; }
+.timeline .row { display: flex; font-size: 0.8em; }
+.timeline .label { width: 30%; overflow: hidden; white-space: nowrap; text-overflow: ellipsis; }
+.timeline .track { width: 70%; }
+.timeline .bar { display: inline-block; height: 0.9em; background: #48c; }
+.timeline .bar.failed { background: #c00; }";
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use std::time::SystemTime;
+
+    use crate::commands::explain::escape;
+    use crate::commands::explain::Report;
+
+    fn action_end(at_secs: u64, duration_secs: u64, failed: bool) -> buck2_data::BuckEvent {
+        buck2_data::BuckEvent {
+            timestamp: Some((SystemTime::UNIX_EPOCH + Duration::from_secs(at_secs)).into()),
+            data: Some(buck2_data::buck_event::Data::SpanEnd(
+                buck2_data::SpanEndEvent {
+                    duration: Some(Duration::from_secs(duration_secs).try_into().unwrap()),
+                    data: Some(buck2_data::span_end_event::Data::ActionExecution(Box::new(
+                        buck2_data::ActionExecutionEnd {
+                            name: Some(buck2_data::ActionName {
+                                category: "cxx_compile".to_owned(),
+                                identifier: "main.cpp".to_owned(),
+                            }),
+                            failed,
+                            error: failed.then(|| {
+                                buck2_data::action_execution_end::Error::Unknown(
+                                    "<boom>".to_owned(),
+                                )
+                            }),
+                            execution_kind: buck2_data::ActionExecutionKind::ActionCache as i32,
+                            ..Default::default()
+                        },
+                    ))),
+                    ..Default::default()
+                },
+            )),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            "&lt;a href=&quot;x&quot;&gt;&amp;&#39;",
+            escape("<a href=\"x\">&'")
+        );
+    }
+
+    #[test]
+    fn test_report() -> anyhow::Result<()> {
+        let mut report = Report::default();
+        report.update_with_event(&action_end(100, 10, false))?;
+        report.update_with_event(&action_end(120, 5, true))?;
+
+        assert_eq!(Some(&2), report.execution_kinds.get("cache hit"));
+        let html = report.render();
+        assert!(html.contains("<h2>Failed actions</h2>"));
+        assert!(html.contains("<p>&lt;boom&gt;</p>"));
+        // The second action starts 15s after the first event, in a build of 20s.
+        assert!(html.contains("margin-left:75.00%;width:25.00%"));
+        Ok(())
+    }
+}
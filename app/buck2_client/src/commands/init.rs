@@ -7,6 +7,7 @@
  * of this source tree.
  */
 
+use std::fmt::Write as _;
 use std::io::ErrorKind;
 use std::io::Write;
 
@@ -28,6 +29,9 @@ use buck2_util::process::background_command;
 /// This command is intended to be part-tutorial part-convenience
 /// for generating buck2 projects. Given a path and optional name
 /// (in the case that the folder name is not desirable).
+///
+/// The generated `toolchains//` cell configures toolchains for the C/C++ compiler, Python
+/// interpreter and Rust compiler found on the `PATH`, so `buck2 build //...` works right away.
 #[derive(Debug, clap::Parser)]
 #[clap(name = "install", about = "Initialize a buck2 project")]
 pub struct InitCommand {
@@ -54,6 +58,11 @@ pub struct InitCommand {
     // Use git to initialize the project and pull in buck2-prelude as a submodule
     #[clap(long)]
     git: bool,
+
+    /// Pin the prelude to this commit of buck2-prelude, fetched by buck2 as an external cell,
+    /// instead of downloading it into `prelude/`.
+    #[clap(long, value_name = "HASH", conflicts_with = "no-prelude")]
+    prelude_commit: Option<String>,
 }
 
 impl InitCommand {
@@ -122,10 +131,108 @@ fn exec_impl(
         }
     }
 
-    set_up_project(&absolute, git, !cmd.no_prelude)
+    let prelude_commit = cmd.prelude_commit.as_deref();
+    if let Some(commit) = prelude_commit {
+        if commit.len() != 40 || !commit.bytes().all(|c| c.is_ascii_hexdigit()) {
+            return Err(anyhow::anyhow!(
+                "`--prelude-commit` must be a full 40 character commit hash, got `{}`",
+                commit
+            ));
+        }
+    }
+
+    let host_tools = if cmd.no_prelude {
+        HostTools::default()
+    } else {
+        let host_tools = HostTools::detect();
+        console.print_stderr(&host_tools.summary())?;
+        host_tools
+    };
+
+    set_up_project(&absolute, git, !cmd.no_prelude, prelude_commit, &host_tools)
+}
+
+/// The C and C++ compilers, as `system_cxx_toolchain` arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CxxTools {
+    compiler: &'static str,
+    cxx_compiler: &'static str,
+    linker: &'static str,
+    compiler_type: &'static str,
+}
+
+/// Tools found on the host, which the generated toolchains use.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct HostTools {
+    cxx: Option<CxxTools>,
+    python: Option<&'static str>,
+    rustc: bool,
+}
+
+fn find_on_path(name: &str) -> bool {
+    let Some(path) = std::env::var_os("PATH") else {
+        return false;
+    };
+    std::env::split_paths(&path).any(|dir| {
+        let candidate = dir.join(name);
+        candidate.is_file() || (cfg!(windows) && candidate.with_extension("exe").is_file())
+    })
 }
 
-fn initialize_buckconfig(repo_root: &AbsPath, prelude: bool, git: bool) -> anyhow::Result<()> {
+impl HostTools {
+    fn detect() -> HostTools {
+        Self::detect_with(find_on_path)
+    }
+
+    fn detect_with(found: impl Fn(&str) -> bool) -> HostTools {
+        let cxx = [
+            CxxTools {
+                compiler: "clang",
+                cxx_compiler: "clang++",
+                linker: "clang++",
+                compiler_type: "clang",
+            },
+            CxxTools {
+                compiler: "gcc",
+                cxx_compiler: "g++",
+                linker: "g++",
+                compiler_type: "gcc",
+            },
+            CxxTools {
+                compiler: "cl.exe",
+                cxx_compiler: "cl.exe",
+                linker: "link.exe",
+                compiler_type: "windows",
+            },
+        ]
+        .into_iter()
+        .find(|tools| found(tools.compiler) && found(tools.cxx_compiler) && found(tools.linker));
+        let python = ["python3", "python"].into_iter().find(|p| found(p));
+        HostTools {
+            cxx,
+            python,
+            rustc: found("rustc"),
+        }
+    }
+
+    fn summary(&self) -> String {
+        let mut summary = String::from("Detected host tools:");
+        let mut line = |lang: &str, tool: Option<&str>| {
+            write!(summary, "\n  {}: {}", lang, tool.unwrap_or("not found")).unwrap();
+        };
+        line("C/C++", self.cxx.as_ref().map(|cxx| cxx.compiler));
+        line("Python", self.python);
+        line("Rust", self.rustc.then_some("rustc"));
+        summary
+    }
+}
+
+fn initialize_buckconfig(
+    repo_root: &AbsPath,
+    prelude: bool,
+    prelude_commit: Option<&str>,
+    git: bool,
+) -> anyhow::Result<()> {
     let mut buckconfig = std::fs::File::create(repo_root.join(".buckconfig"))?;
     writeln!(buckconfig, "[repositories]")?;
     writeln!(buckconfig, "root = .")?;
@@ -149,6 +256,18 @@ fn initialize_buckconfig(repo_root: &AbsPath, prelude: bool, git: bool) -> anyho
             buckconfig,
             "target_platform_detector_spec = target:root//...->prelude//platforms:default"
         )?;
+        if let Some(commit) = prelude_commit {
+            writeln!(buckconfig)?;
+            writeln!(buckconfig, "[external_cells]")?;
+            writeln!(buckconfig, "prelude = git")?;
+            writeln!(buckconfig)?;
+            writeln!(buckconfig, "[external_cell_prelude]")?;
+            writeln!(
+                buckconfig,
+                "git_origin = https://github.com/facebook/buck2-prelude.git"
+            )?;
+            writeln!(buckconfig, "commit_hash = {}", commit)?;
+        }
     } else {
         // For the no-prelude mode, create an empty prelude/prelude.bzl as Buck2 expects one.
         let prelude_dir = repo_root.join("prelude");
@@ -163,18 +282,74 @@ fn initialize_buckconfig(repo_root: &AbsPath, prelude: bool, git: bool) -> anyho
     Ok(())
 }
 
-fn initialize_toolchains_buck(repo_root: &AbsPath) -> anyhow::Result<()> {
-    std::fs::write(
-        repo_root.join("BUCK"),
-        r#"
-load("@prelude//toolchains:demo.bzl", "system_demo_toolchains")
-
-# All the default toolchains, suitable for a quick demo or early prototyping.
-# Most real projects should copy/paste the implementation to configure them.
-system_demo_toolchains()
-"#
-        .trim(),
-    )?;
+fn toolchains_buck(tools: &HostTools) -> String {
+    let mut buck = String::new();
+    if tools.cxx.is_some() {
+        buck.push_str("load(\"@prelude//toolchains:cxx.bzl\", \"system_cxx_toolchain\")\n");
+    }
+    buck.push_str("load(\"@prelude//toolchains:genrule.bzl\", \"system_genrule_toolchain\")\n");
+    buck.push_str("load(\"@prelude//toolchains:python.bzl\", \"system_python_bootstrap_toolchain\", \"system_python_toolchain\")\n");
+    buck.push_str("load(\"@prelude//toolchains:remote_test_execution.bzl\", \"remote_test_execution_toolchain\")\n");
+    if tools.rustc {
+        buck.push_str("load(\"@prelude//toolchains:rust.bzl\", \"system_rust_toolchain\")\n");
+    }
+    buck.push_str(
+        "\n# Toolchains for the tools `buck2 init` found on the host. Most real projects should\n\
+         # adjust them, e.g. to pin the versions of the tools or to add flags.\n",
+    );
+
+    let mut rule = |rule: &str, name: &str, attrs: &[(&str, &str)]| {
+        writeln!(buck, "\n{}(", rule).unwrap();
+        writeln!(buck, "    name = \"{}\",", name).unwrap();
+        for (attr, value) in attrs {
+            writeln!(buck, "    {} = \"{}\",", attr, value).unwrap();
+        }
+        writeln!(buck, "    visibility = [\"PUBLIC\"],").unwrap();
+        writeln!(buck, ")").unwrap();
+    };
+    if let Some(cxx) = &tools.cxx {
+        rule(
+            "system_cxx_toolchain",
+            "cxx",
+            &[
+                ("compiler", cxx.compiler),
+                ("cxx_compiler", cxx.cxx_compiler),
+                ("linker", cxx.linker),
+                ("compiler_type", cxx.compiler_type),
+            ],
+        );
+    }
+    rule("system_genrule_toolchain", "genrule", &[]);
+    // The prelude's own tools are Python scripts, so the Python toolchains are always defined,
+    // with the prelude's default interpreter if none was found.
+    let python: Vec<_> = tools
+        .python
+        .map(|p| ("interpreter", p))
+        .into_iter()
+        .collect();
+    rule("system_python_toolchain", "python", &python);
+    rule(
+        "system_python_bootstrap_toolchain",
+        "python_bootstrap",
+        &python,
+    );
+    if tools.rustc {
+        rule(
+            "system_rust_toolchain",
+            "rust",
+            &[("default_edition", "2021")],
+        );
+    }
+    rule(
+        "remote_test_execution_toolchain",
+        "remote_test_execution",
+        &[],
+    );
+    buck
+}
+
+fn initialize_toolchains_buck(toolchains_dir: &AbsPath, tools: &HostTools) -> anyhow::Result<()> {
+    fs_util::write(toolchains_dir.join("BUCK"), toolchains_buck(tools))?;
     Ok(())
 }
 
@@ -237,7 +412,13 @@ fn set_up_buckroot(repo_root: &AbsPath) -> anyhow::Result<()> {
     Ok(())
 }
 
-fn set_up_project(repo_root: &AbsPath, git: bool, prelude: bool) -> anyhow::Result<()> {
+fn set_up_project(
+    repo_root: &AbsPath,
+    git: bool,
+    prelude: bool,
+    prelude_commit: Option<&str>,
+    host_tools: &HostTools,
+) -> anyhow::Result<()> {
     set_up_buckroot(repo_root)?;
 
     if git {
@@ -252,7 +433,8 @@ fn set_up_project(repo_root: &AbsPath, git: bool, prelude: bool) -> anyhow::Resu
         set_up_gitignore(repo_root)?;
     }
 
-    if prelude {
+    // A pinned prelude is fetched by buck2 itself.
+    if prelude && prelude_commit.is_none() {
         set_up_prelude(repo_root, git)?;
     }
 
@@ -261,12 +443,12 @@ fn set_up_project(repo_root: &AbsPath, git: bool, prelude: bool) -> anyhow::Resu
         return Ok(());
     }
 
-    initialize_buckconfig(repo_root, prelude, git)?;
+    initialize_buckconfig(repo_root, prelude, prelude_commit, git)?;
     if prelude {
         let toolchains = repo_root.join("toolchains");
         if !toolchains.exists() {
             fs_util::create_dir(&toolchains)?;
-            initialize_toolchains_buck(&toolchains)?;
+            initialize_toolchains_buck(&toolchains, host_tools)?;
         }
     }
     if !repo_root.join("BUCK").exists() {
@@ -284,6 +466,8 @@ mod tests {
     use crate::commands::init::initialize_root_buck;
     use crate::commands::init::set_up_gitignore;
     use crate::commands::init::set_up_project;
+    use crate::commands::init::toolchains_buck;
+    use crate::commands::init::HostTools;
 
     #[test]
     fn test_set_up_project_with_prelude_no_git() -> anyhow::Result<()> {
@@ -293,7 +477,7 @@ mod tests {
         fs_util::create_dir_all(tempdir_path)?;
 
         // no git, with prelude
-        set_up_project(tempdir_path, false, true)?;
+        set_up_project(tempdir_path, false, true, None, &HostTools::default())?;
         assert!(tempdir_path.join(".buckconfig").exists());
         assert!(tempdir_path.join("toolchains").exists());
         assert!(tempdir_path.join("toolchains/BUCK").exists());
@@ -341,7 +525,7 @@ mod tests {
         fs_util::create_dir_all(tempdir_path)?;
 
         let buckconfig_path = tempdir_path.join(".buckconfig");
-        initialize_buckconfig(tempdir_path, true, true)?;
+        initialize_buckconfig(tempdir_path, true, None, true)?;
         let actual_buckconfig = fs_util::read_to_string(buckconfig_path)?;
         let expected_buckconfig = "[repositories]
 root = .
@@ -375,7 +559,7 @@ ignore = .git
         fs_util::create_dir_all(tempdir_path)?;
 
         let buckconfig_path = tempdir_path.join(".buckconfig");
-        initialize_buckconfig(tempdir_path, false, false)?;
+        initialize_buckconfig(tempdir_path, false, None, false)?;
         let actual_buckconfig = fs_util::read_to_string(buckconfig_path)?;
         let expected_buckconfig = "[repositories]
 root = .
@@ -409,4 +593,61 @@ genrule(
         assert_eq!(actual_buck, expected_buck);
         Ok(())
     }
+
+    #[test]
+    fn test_buckconfig_generation_with_prelude_commit() -> anyhow::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let tempdir_path = tempdir.path();
+        let tempdir_path = AbsPath::new(tempdir_path)?;
+        fs_util::create_dir_all(tempdir_path)?;
+
+        let commit = "0123456789abcdef0123456789abcdef01234567";
+        initialize_buckconfig(tempdir_path, true, Some(commit), false)?;
+        let actual_buckconfig = fs_util::read_to_string(tempdir_path.join(".buckconfig"))?;
+        assert!(actual_buckconfig.ends_with(
+            "
+[external_cells]
+prelude = git
+
+[external_cell_prelude]
+git_origin = https://github.com/facebook/buck2-prelude.git
+commit_hash = 0123456789abcdef0123456789abcdef01234567
+"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_toolchains_for_host_tools() {
+        let tools = HostTools::detect_with(|tool| ["gcc", "g++", "python3"].contains(&tool));
+        let buck = toolchains_buck(&tools);
+        assert!(buck.contains(
+            "
+system_cxx_toolchain(
+    name = \"cxx\",
+    compiler = \"gcc\",
+    cxx_compiler = \"g++\",
+    linker = \"g++\",
+    compiler_type = \"gcc\",
+    visibility = [\"PUBLIC\"],
+)
+"
+        ));
+        assert!(buck.contains(
+            "
+system_python_bootstrap_toolchain(
+    name = \"python_bootstrap\",
+    interpreter = \"python3\",
+    visibility = [\"PUBLIC\"],
+)
+"
+        ));
+        assert!(!buck.contains("rust"));
+
+        // Without tools, only the toolchains the prelude itself needs are defined.
+        let buck = toolchains_buck(&HostTools::detect_with(|_| false));
+        assert!(!buck.contains("cxx"));
+        assert!(!buck.contains("interpreter"));
+        assert!(buck.contains("system_genrule_toolchain("));
+    }
 }
@@ -19,6 +19,8 @@ use buck2_client_ctx::startup_deadline::StartupDeadline;
 use buck2_common::argv::Argv;
 use buck2_common::argv::SanitizedArgv;
 
+use crate::commands::status::all_daemon_dirs;
+
 /// Kill the buck daemon.
 ///
 /// Note there's also `buck2 killall` and `buck2 clean`.
@@ -26,22 +28,43 @@ use buck2_common::argv::SanitizedArgv;
 /// `buck2 killall` kills all the buck2 processes on the machine.
 ///
 /// `buck2 clean` kills the buck2 daemon and also deletes the buck2 state files.
+///
+/// Use the global `--isolation-dir` flag to kill the daemon of another isolation dir, and
+/// `buck2 status --all` to list the daemons of all isolation dirs.
 #[derive(Debug, clap::Parser)]
-pub struct KillCommand {}
+pub struct KillCommand {
+    /// Kill the daemons of all projects and isolation dirs, instead of only the current one. Unlike
+    /// `buck2 killall`, this asks each daemon to shut down before killing it, and leaves alone the
+    /// buck2 processes which are not daemons.
+    #[clap(long)]
+    all: bool,
+}
 
 impl KillCommand {
     pub fn exec(self, _matches: &clap::ArgMatches, ctx: ClientCommandContext<'_>) -> ExitResult {
         ctx.instant_command("kill", async move |ctx| {
-            let daemon_dir = ctx.paths()?.daemon_dir()?;
+            let daemon_dirs = if self.all {
+                all_daemon_dirs(&ctx)?
+            } else {
+                vec![ctx.paths()?.daemon_dir()?]
+            };
+
+            for daemon_dir in daemon_dirs {
+                if self.all {
+                    buck2_client_ctx::eprintln!("{}:", daemon_dir)?;
+                }
+
+                let lifecycle_lock = BuckdLifecycleLock::lock_with_timeout(
+                    daemon_dir.clone(),
+                    StartupDeadline::duration_from_now(Duration::from_secs(10))?,
+                )
+                .await
+                .with_context(|| "Error locking buckd lifecycle.lock")?;
 
-            let lifecycle_lock = BuckdLifecycleLock::lock_with_timeout(
-                daemon_dir.clone(),
-                StartupDeadline::duration_from_now(Duration::from_secs(10))?,
-            )
-            .await
-            .with_context(|| "Error locking buckd lifecycle.lock")?;
+                kill_command_impl(&lifecycle_lock, "`buck kill` was invoked").await?;
+            }
 
-            kill_command_impl(&lifecycle_lock, "`buck kill` was invoked").await
+            Ok(())
         })
     }
 
@@ -114,3 +137,16 @@ pub async fn kill_command_impl(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_all() {
+        assert!(!KillCommand::try_parse_from(["kill"]).unwrap().all);
+        assert!(KillCommand::try_parse_from(["kill", "--all"]).unwrap().all);
+    }
+}
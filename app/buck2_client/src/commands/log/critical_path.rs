@@ -73,82 +73,112 @@ impl CriticalPathCommand {
     }
 }
 
-fn log_critical_path(critical_path: &buck2_data::BuildGraphExecutionInfo) -> anyhow::Result<()> {
+/// What a critical path entry is, as shown to users.
+pub(crate) struct CriticalPathEntryName<'a> {
+    pub(crate) kind: &'static str,
+    pub(crate) name: String,
+    pub(crate) category: &'a str,
+    pub(crate) identifier: &'a str,
+}
+
+/// Describe a critical path entry, or `None` if the entry is missing data.
+pub(crate) fn critical_path_entry_name(
+    entry: &buck2_data::CriticalPathEntry2,
+) -> anyhow::Result<Option<CriticalPathEntryName<'_>>> {
+    use buck2_data::critical_path_entry2::Entry;
+
     let target_display_options = TargetDisplayOptions::for_log();
 
-    for entry in &critical_path.critical_path2 {
-        use buck2_data::critical_path_entry2::Entry;
+    let kind;
+    let name;
+    let mut category = "";
+    let mut identifier = "";
 
-        let kind;
-        let name;
-        let mut category = "";
-        let mut identifier = "";
+    match &entry.entry {
+        Some(Entry::Analysis(analysis)) => {
+            use buck2_data::critical_path_entry2::analysis::Target;
 
-        match &entry.entry {
-            Some(Entry::Analysis(analysis)) => {
-                use buck2_data::critical_path_entry2::analysis::Target;
+            kind = "analysis";
 
-                kind = "analysis";
+            name = match &analysis.target {
+                Some(Target::StandardTarget(t)) => {
+                    display::display_configured_target_label(t, target_display_options)?
+                }
+                None => return Ok(None),
+            };
+        }
+        Some(Entry::ActionExecution(action_execution)) => {
+            use buck2_data::critical_path_entry2::action_execution::Owner;
 
-                name = match &analysis.target {
-                    Some(Target::StandardTarget(t)) => {
-                        display::display_configured_target_label(t, target_display_options)?
-                    }
-                    None => continue,
-                };
-            }
-            Some(Entry::ActionExecution(action_execution)) => {
-                use buck2_data::critical_path_entry2::action_execution::Owner;
-
-                kind = "action";
-
-                name = match &action_execution.owner {
-                    Some(Owner::TargetLabel(t)) => {
-                        display::display_configured_target_label(t, target_display_options)?
-                    }
-                    Some(Owner::BxlKey(t)) => display::display_bxl_key(t)?,
-                    Some(Owner::AnonTarget(t)) => display::display_anon_target(t)?,
-                    None => continue,
-                };
-
-                match &action_execution.name {
-                    Some(name) => {
-                        category = &name.category;
-                        identifier = &name.identifier;
-                    }
-                    None => {}
+            kind = "action";
+
+            name = match &action_execution.owner {
+                Some(Owner::TargetLabel(t)) => {
+                    display::display_configured_target_label(t, target_display_options)?
+                }
+                Some(Owner::BxlKey(t)) => display::display_bxl_key(t)?,
+                Some(Owner::AnonTarget(t)) => display::display_anon_target(t)?,
+                None => return Ok(None),
+            };
+
+            match &action_execution.name {
+                Some(name) => {
+                    category = &name.category;
+                    identifier = &name.identifier;
                 }
+                None => {}
             }
-            Some(Entry::Materialization(materialization)) => {
-                use buck2_data::critical_path_entry2::materialization::Owner;
+        }
+        Some(Entry::Materialization(materialization)) => {
+            use buck2_data::critical_path_entry2::materialization::Owner;
 
-                kind = "materialization";
+            kind = "materialization";
 
-                name = match &materialization.owner {
-                    Some(Owner::TargetLabel(t)) => {
-                        display::display_configured_target_label(t, target_display_options)?
-                    }
-                    Some(Owner::BxlKey(t)) => display::display_bxl_key(t)?,
-                    Some(Owner::AnonTarget(t)) => display::display_anon_target(t)?,
-                    None => continue,
-                };
+            name = match &materialization.owner {
+                Some(Owner::TargetLabel(t)) => {
+                    display::display_configured_target_label(t, target_display_options)?
+                }
+                Some(Owner::BxlKey(t)) => display::display_bxl_key(t)?,
+                Some(Owner::AnonTarget(t)) => display::display_anon_target(t)?,
+                None => return Ok(None),
+            };
 
-                identifier = &materialization.path;
-            }
-            Some(Entry::ComputeCriticalPath(..)) => {
-                kind = "compute-critical-path";
-                name = "".to_owned();
-            }
-            Some(Entry::Load(load)) => {
-                kind = "load";
-                name = load.package.clone();
-            }
-            Some(Entry::Listing(listing)) => {
-                kind = "listing";
-                name = listing.package.clone();
-            }
-            None => continue,
+            identifier = &materialization.path;
+        }
+        Some(Entry::ComputeCriticalPath(..)) => {
+            kind = "compute-critical-path";
+            name = "".to_owned();
+        }
+        Some(Entry::Load(load)) => {
+            kind = "load";
+            name = load.package.clone();
+        }
+        Some(Entry::Listing(listing)) => {
+            kind = "listing";
+            name = listing.package.clone();
         }
+        None => return Ok(None),
+    }
+
+    Ok(Some(CriticalPathEntryName {
+        kind,
+        name,
+        category,
+        identifier,
+    }))
+}
+
+fn log_critical_path(critical_path: &buck2_data::BuildGraphExecutionInfo) -> anyhow::Result<()> {
+    for entry in &critical_path.critical_path2 {
+        let Some(CriticalPathEntryName {
+            kind,
+            name,
+            category,
+            identifier,
+        }) = critical_path_entry_name(entry)?
+        else {
+            continue;
+        };
 
         struct OptionalDuration {
             inner: Option<Duration>,
@@ -7,7 +7,7 @@
  * of this source tree.
  */
 
-mod critical_path;
+pub(crate) mod critical_path;
 pub(crate) mod debug_replay;
 pub(crate) mod debug_what_ran;
 pub(crate) mod options;
@@ -15,7 +15,7 @@ pub(crate) mod path_log;
 mod replay;
 mod show_log;
 mod show_user_log;
-mod summary;
+pub(crate) mod summary;
 mod what_cmd;
 mod what_failed;
 mod what_materialized;
@@ -17,6 +17,7 @@ use buck2_client_ctx::replayer::Replayer;
 use buck2_client_ctx::signal_handler::with_simple_sigint_handler;
 use buck2_client_ctx::subscribers::get::get_console_with_root;
 use buck2_client_ctx::subscribers::subscribers::EventSubscribers;
+use buck2_event_log::validate::validate_event_log;
 
 use crate::commands::log::options::EventLogOptions;
 
@@ -42,6 +43,12 @@ pub struct ReplayCommand {
     #[clap(long)]
     preload: bool,
 
+    /// Read the whole event log before replaying it, and fail if this buck2 can't interpret all
+    /// of it (e.g. the log was written by a newer buck2, or it is truncated), listing every
+    /// event it can't interpret.
+    #[clap(long)]
+    strict: bool,
+
     #[clap(flatten)]
     console_opts: CommonConsoleOptions,
 
@@ -55,14 +62,29 @@ impl ReplayCommand {
             event_log,
             speed,
             preload,
+            strict,
             console_opts,
             override_args: _,
         } = self;
 
         ctx.with_runtime(async move |mut ctx| {
             let work = async {
-                let (replayer, invocation) =
-                    Replayer::new(event_log.get(&ctx).await?, speed, preload).await?;
+                let event_log = event_log.get(&ctx).await?;
+                if strict {
+                    let issues = validate_event_log(&event_log).await?;
+                    if !issues.is_empty() {
+                        for issue in &issues {
+                            buck2_client_ctx::eprintln!("{}", issue)?;
+                        }
+                        return ExitResult::bail(format!(
+                            "Event log `{}` has {} incompatibilities",
+                            event_log.path().display(),
+                            issues.len()
+                        ));
+                    }
+                }
+
+                let (replayer, invocation) = Replayer::new(event_log, speed, preload).await?;
 
                 let console = get_console_with_root(
                     invocation.trace_id,
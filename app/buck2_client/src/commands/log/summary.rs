@@ -9,6 +9,7 @@
 
 use std::fmt::Display;
 use std::fmt::Formatter;
+use std::time::Duration;
 
 use buck2_client_ctx::client_ctx::ClientCommandContext;
 use buck2_client_ctx::exit_result::ExitResult;
@@ -29,11 +30,69 @@ struct Stats {
     total_remote_actions: u64,
     total_other_actions: u64,
     total_targets_analysed: u64,
+    load: LoadParallelism,
+}
+
+/// How many build files were loaded at the same time, to see how parallel the load phase was.
+#[derive(Default)]
+struct LoadParallelism {
+    in_flight: u64,
+    peak_in_flight: u64,
+    total_loads: u64,
+    /// Sum of the durations of all loads.
+    total_duration: Duration,
+    /// Time since the epoch of the first load start and of the last load end.
+    first_start: Option<Duration>,
+    last_end: Option<Duration>,
+}
+
+impl LoadParallelism {
+    fn start(&mut self, event: &buck2_data::BuckEvent) {
+        self.in_flight += 1;
+        self.peak_in_flight = self.peak_in_flight.max(self.in_flight);
+        if self.first_start.is_none() {
+            self.first_start = timestamp(event);
+        }
+    }
+
+    fn end(&mut self, event: &buck2_data::BuckEvent, end: &buck2_data::SpanEndEvent) {
+        self.in_flight = self.in_flight.saturating_sub(1);
+        self.total_loads += 1;
+        if let Some(duration) = end
+            .duration
+            .as_ref()
+            .and_then(|d| Duration::try_from(d.clone()).ok())
+        {
+            self.total_duration += duration;
+        }
+        self.last_end = timestamp(event).or(self.last_end);
+    }
+
+    /// Average number of loads in flight between the first load start and the last load end.
+    fn average_parallelism(&self) -> Option<f64> {
+        let elapsed = self.last_end?.checked_sub(self.first_start?)?;
+        if elapsed.is_zero() {
+            return None;
+        }
+        Some(self.total_duration.as_secs_f64() / elapsed.as_secs_f64())
+    }
+}
+
+pub(crate) fn timestamp(event: &buck2_data::BuckEvent) -> Option<Duration> {
+    let timestamp = event.timestamp.as_ref()?;
+    Some(Duration::new(
+        timestamp.seconds.try_into().ok()?,
+        timestamp.nanos.try_into().ok()?,
+    ))
 }
 
 impl Stats {
     fn update_with_event(&mut self, event: &buck2_data::BuckEvent) {
         match &event.data {
+            Some(buck2_data::buck_event::Data::SpanStart(start)) => match start.data.as_ref() {
+                Some(buck2_data::span_start_event::Data::Load(_)) => self.load.start(event),
+                _ => {}
+            },
             Some(buck2_data::buck_event::Data::SpanEnd(end)) => match end.data.as_ref() {
                 Some(buck2_data::span_end_event::Data::ReUpload(ref data)) => {
                     self.total_bytes_uploaded += data.bytes_uploaded.unwrap_or_default();
@@ -53,6 +112,7 @@ impl Stats {
                 Some(buck2_data::span_end_event::Data::Analysis(_)) => {
                     self.total_targets_analysed += 1;
                 }
+                Some(buck2_data::span_end_event::Data::Load(_)) => self.load.end(event, end),
                 _ => {}
             },
             _ => {}
@@ -76,11 +136,26 @@ impl Display for Stats {
         writeln!(f, "local actions: {}", self.total_local_actions)?;
         writeln!(f, "remote actions: {}", self.total_remote_actions)?;
         writeln!(f, "other actions: {}", self.total_other_actions)?;
-        writeln!(f, "targets analysed: {}", self.total_targets_analysed)
+        writeln!(f, "targets analysed: {}", self.total_targets_analysed)?;
+        writeln!(f, "build files loaded: {}", self.load.total_loads)?;
+        writeln!(
+            f,
+            "peak concurrent build file loads: {}",
+            self.load.peak_in_flight
+        )?;
+        match self.load.average_parallelism() {
+            Some(parallelism) => writeln!(f, "average load parallelism: {:.2}", parallelism),
+            None => writeln!(f, "average load parallelism: n/a"),
+        }
     }
 }
 
 /// Outputs high level statistics about the build
+///
+/// This includes how parallel the evaluation of build files was: build files are evaluated on
+/// the threads of the daemon runtime, so the average number of build files evaluated at the same
+/// time is at most the number of threads, and is lower when packages wait on each other's
+/// `.bzl` imports, or when few packages are needed at once.
 #[derive(Debug, clap::Parser)]
 pub struct SummaryCommand {
     #[clap(flatten)]
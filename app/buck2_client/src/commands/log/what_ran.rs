@@ -9,6 +9,7 @@
 
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::time::Duration;
 
 use anyhow::Context;
 use buck2_client_ctx::client_ctx::ClientCommandContext;
@@ -61,7 +62,7 @@ pub struct WhatRanCommand {
     pub common: WhatRanCommandCommon,
 
     /// Show only commands that failed
-    #[clap(long, conflicts_with = "incomplete")]
+    #[clap(long, alias = "failed-only", conflicts_with = "incomplete")]
     pub failed: bool,
 
     /// Show only commands that were not completed.
@@ -98,11 +99,19 @@ pub struct WhatRanCommandCommon {
 
     #[clap(flatten)]
     pub options: WhatRanOptions,
+
+    /// Show only commands of actions that took at least this long (e.g. `10s`). Actions that did
+    /// not finish are not shown, since their duration is unknown.
+    #[clap(long, value_name = "DURATION")]
+    pub min_duration: Option<humantime::Duration>,
 }
 
 struct WhatRanCommandOptions {
     options: WhatRanOptions,
 
+    /// Print commands only if their action took at least this long.
+    min_duration: Option<Duration>,
+
     /// Print commands only if they failed.
     failed: bool,
 
@@ -118,6 +127,7 @@ impl WhatRanCommand {
                     event_log,
                     output,
                     options,
+                    min_duration,
                 },
             failed,
             incomplete,
@@ -136,17 +146,23 @@ impl WhatRanCommand {
                 let (invocation, events) = log_path.unpack_stream().await?;
 
                 buck2_client_ctx::eprintln!(
-                    "Showing commands from: {}{}",
+                    "Showing commands from: {}{}{}",
                     invocation.display_command_line(),
                     if options.filter_category.is_some() {
                         ", filtered by action category"
                     } else {
                         ""
+                    },
+                    if min_duration.is_some() {
+                        ", filtered by duration"
+                    } else {
+                        ""
                     }
                 )?;
 
                 let options = WhatRanCommandOptions {
                     options,
+                    min_duration: min_duration.map(Into::into),
                     failed,
                     incomplete,
                 };
@@ -266,7 +282,7 @@ impl WhatRanCommandState {
                     if let Some(entry) =
                         self.known_actions.remove(&SpanId::from_u64(event.span_id)?)
                     {
-                        if should_emit_finished_action(&span.data, options) {
+                        if should_emit_finished_action(span, options) {
                             entry.emit_what_ran_entry(output, &span.data, options)?;
                         }
                     }
@@ -293,14 +309,24 @@ impl WhatRanCommandState {
 }
 
 fn should_emit_finished_action(
-    data: &Option<buck2_data::span_end_event::Data>,
+    span: &buck2_data::SpanEndEvent,
     options: &WhatRanCommandOptions,
 ) -> bool {
     if options.incomplete {
         return false;
     }
 
-    match data {
+    if let Some(min_duration) = options.min_duration {
+        let duration = span
+            .duration
+            .as_ref()
+            .and_then(|d| Duration::try_from(d.clone()).ok());
+        if duration.map_or(true, |d| d < min_duration) {
+            return false;
+        }
+    }
+
+    match &span.data {
         Some(buck2_data::span_end_event::Data::ActionExecution(action)) => {
             action.failed || !options.failed
         }
@@ -310,7 +336,8 @@ fn should_emit_finished_action(
 }
 
 fn should_emit_unfinished_action(options: &WhatRanCommandOptions) -> bool {
-    !options.failed // We don't know if it failed or not.
+    // We don't know if it failed or not, nor how long it took.
+    !options.failed && options.min_duration.is_none()
 }
 
 /// An output that writes to stdout in a tabulated format.
@@ -630,4 +657,44 @@ mod tests {
         assert_eq!(expected, serde_json::to_string_pretty(&command)?);
         Ok(())
     }
+
+    fn options_with_min_duration(min_duration: Duration) -> WhatRanCommandOptions {
+        WhatRanCommandOptions {
+            options: WhatRanOptions::default(),
+            min_duration: Some(min_duration),
+            failed: false,
+            incomplete: false,
+        }
+    }
+
+    fn action_end(duration: Option<Duration>) -> buck2_data::SpanEndEvent {
+        buck2_data::SpanEndEvent {
+            duration: duration.map(|d| prost_types::Duration::try_from(d).unwrap()),
+            data: Some(buck2_data::span_end_event::Data::ActionExecution(
+                Default::default(),
+            )),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_min_duration() {
+        let options = options_with_min_duration(Duration::from_secs(10));
+
+        assert!(!should_emit_finished_action(
+            &action_end(Some(Duration::from_secs(5))),
+            &options
+        ));
+        assert!(should_emit_finished_action(
+            &action_end(Some(Duration::from_secs(10))),
+            &options
+        ));
+        assert!(should_emit_finished_action(
+            &action_end(Some(Duration::from_secs(20))),
+            &options
+        ));
+        // Without a duration we can't tell how long it took.
+        assert!(!should_emit_finished_action(&action_end(None), &options));
+        assert!(!should_emit_unfinished_action(&options));
+    }
 }
@@ -9,10 +9,13 @@
 
 pub mod build;
 pub mod bxl;
+pub mod changed_targets;
 pub mod clean;
 pub mod clean_stale;
 pub mod ctargets;
 pub mod debug;
+pub mod doctor;
+pub mod explain;
 pub mod help_env;
 pub mod init;
 pub mod install;
@@ -103,6 +103,8 @@ enum BuckProfileMode {
     Bytecode,
     BytecodePairs,
     Typecheck,
+    HeapRetention,
+    LoadingBreakdown,
 }
 
 #[derive(Debug, clap::Parser)]
@@ -125,10 +127,13 @@ pub struct BuckProfileOptions {
 
 #[derive(Debug, clap::Parser)]
 pub struct AnalysisLoadProfileOptions {
+    /// Targets or packages to profile.
+    ///
+    /// When more than one target is matched, the profiles of all of them are merged.
     #[clap(value_name = "TARGET_PATTERNS")]
     target_patterns: Vec<String>,
 
-    /// In analysis profiling, capture the profile of the target and its dependencies,
+    /// In analysis profiling, capture the profile of the targets and their dependencies,
     /// and output the merged profile.
     #[clap(long, short = 'r')]
     recursive: bool,
@@ -155,6 +160,12 @@ pub struct ProfileCommonOptions {
     /// This is probably what you want when profiling analysis.
     ///
     /// `-allocated` means allocated memory, including memory which is later garbage collected.
+    ///
+    /// `heap-retention` (analysis only) writes a dominator tree of the frozen heaps kept alive
+    /// by the analysis of the targets, with the value types that take the most space in each.
+    ///
+    /// `loading-breakdown` (loading only) writes a JSON report of the time spent listing files,
+    /// in `glob` calls and evaluating macros for every `BUCK` file, slowest first.
     #[clap(long, value_enum)]
     mode: BuckProfileMode,
 }
@@ -175,6 +186,8 @@ fn profile_mode_to_profile(mode: &BuckProfileMode) -> Profiler {
         BuckProfileMode::Bytecode => Profiler::Bytecode,
         BuckProfileMode::BytecodePairs => Profiler::BytecodePairs,
         BuckProfileMode::Typecheck => Profiler::Typecheck,
+        BuckProfileMode::HeapRetention => Profiler::HeapRetention,
+        BuckProfileMode::LoadingBreakdown => Profiler::LoadingBreakdown,
     }
 }
 
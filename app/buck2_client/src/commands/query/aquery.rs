@@ -8,6 +8,7 @@
  */
 
 use async_trait::async_trait;
+use buck2_cli_proto::aquery_request::AnalysisV2Format;
 use buck2_cli_proto::AqueryRequest;
 use buck2_cli_proto::AqueryResponse;
 use buck2_client_ctx::client_ctx::ClientCommandContext;
@@ -51,6 +52,10 @@ List all the commands for run actions for building a target
 
 `buck2 aquery 'kind(run, deps("//java/com/example/app:amazing+more"))' --output-attribute=cmd`
 
+Export the actions of a target and their deps for tools reading Bazel action graphs
+
+`buck2 aquery 'deps("//java/com/example/app:amazing")' --analysis-v2=proto > actions.pb`
+
 Dynamic outputs (`ctx.actions.dynamic_output`):
 
 Currently, aquery interacts poorly with dynamic outputs. It may
@@ -72,12 +77,33 @@ pub struct AqueryCommand {
 
     #[clap(flatten)]
     query_common: CommonQueryOptions,
+
+    /// Print the resulting actions as an action graph in the `analysis_v2.proto` schema of Bazel's
+    /// `aquery`, binary encoded (`proto`) or as JSON (`jsonproto`), for tools built for Bazel
+    /// action graphs.
+    #[clap(
+        long,
+        value_name = "FORMAT",
+        arg_enum,
+        conflicts_with_all = &["json", "dot", "dot-compact", "output-format", "output-template"]
+    )]
+    analysis_v2: Option<AnalysisV2FormatArg>,
+}
+
+#[derive(Debug, Clone, Copy, clap::ArgEnum)]
+enum AnalysisV2FormatArg {
+    Proto,
+    Jsonproto,
 }
 
 #[async_trait]
 impl StreamingCommand for AqueryCommand {
     const COMMAND_NAME: &'static str = "aquery";
 
+    fn allows_version_skew() -> bool {
+        true
+    }
+
     async fn exec_impl(
         self,
         buckd: &mut BuckdClientConnector,
@@ -97,7 +123,13 @@ impl StreamingCommand for AqueryCommand {
                     query_args,
                     context: Some(context),
                     output_attributes,
+                    output_template: self.query_common.output_template(),
                     unstable_output_format,
+                    analysis_v2_format: match self.analysis_v2 {
+                        None => AnalysisV2Format::None,
+                        Some(AnalysisV2FormatArg::Proto) => AnalysisV2Format::Proto,
+                        Some(AnalysisV2FormatArg::Jsonproto) => AnalysisV2Format::JsonProto,
+                    } as i32,
                 },
                 ctx.stdin()
                     .console_interaction_stream(&self.common_opts.console_opts),
@@ -60,6 +60,19 @@ pub(crate) struct CommonQueryOptions {
     )]
     output_format: Option<QueryOutputFormatArg>,
 
+    #[clap(
+        long,
+        value_name = "TEMPLATE",
+        conflicts_with_all = &["json", "dot", "dot-compact", "output-format"],
+        help = "Print a line for each target following a template, e.g. `{label} {config}`.",
+        long_help = "Print a line for each target following a template, e.g. `{label} {config}`. \n
+           The variables are `label`, the label without configuration (or the path for files), \n
+           and `config`, the configuration (empty for unconfigured targets). \n
+           Use `{{` and `}}` for literal braces.
+         "
+    )]
+    output_template: Option<String>,
+
     #[clap(
         name = "QUERY_ARGS",
         help = "list of literals for a multi-query (one containing `%s` or `%Ss`)"
@@ -83,6 +96,9 @@ impl CommonQueryOptions {
     }
 
     pub fn output_format(&self) -> QueryOutputFormat {
+        if self.output_template.is_some() {
+            return QueryOutputFormat::Template;
+        }
         match self.output_format {
             Some(QueryOutputFormatArg::Json) => QueryOutputFormat::Json,
             Some(QueryOutputFormatArg::Dot) => QueryOutputFormat::Dot,
@@ -101,6 +117,10 @@ impl CommonQueryOptions {
         }
     }
 
+    pub fn output_template(&self) -> String {
+        self.output_template.clone().unwrap_or_default()
+    }
+
     pub fn get_query(&self) -> (String, Vec<String>) {
         if self.query.contains(QUERY_PERCENT_SS_PLACEHOLDER) {
             let replacement = Self::args_as_set(&self.query_args);
@@ -38,7 +38,9 @@ are resolved. When provided, any literals will resolve to all
 matching targets within the universe (which includes the targets
 passed as the universe and all transitive deps of them).  When not
 provided, we implicitly set the universe to be rooted at every
-target literal in the `cquery`.
+target literal in the `cquery`. Patterns prefixed with `-` are
+subtracted from the universe, and literals which match nothing in
+the universe are reported rather than silently resolving to nothing.
 
 Run `buck2 docs cquery` or
 "#,
@@ -56,6 +58,10 @@ Print all the attributes of a target
 
 `buck2 cquery //java/com/example/app:amazing --output-all-attributes`
 
+Print which condition each select in the attributes of a target chose
+
+`buck2 cquery //java/com/example/app:amazing --show-selects`
+
 List the deps of a target (special characters in a target will
 require quotes):
 
@@ -82,11 +88,16 @@ pub struct CqueryCommand {
         long,
         short = 'u',
         use_delimiter = true,
+        allow_hyphen_values = true,
         help = "Comma separated list of targets at which to root the queryable universe.
                 This is useful since targets can exist in multiple configurations. While
                 this argument isn't required, it's recommended for most non-trivial queries.
                 In the absence of this argument, buck2 will use the target literals
-                in your cquery expression as the argument to this."
+                in your cquery expression as the argument to this.
+                Can be repeated to combine several universes. Patterns prefixed with `-`
+                (e.g. `--target-universe=-//foo/test/...`) remove the targets they match from
+                the universe. Target literals which match no target of the universe are
+                reported."
     )]
     target_universe: Vec<String>,
 
@@ -96,6 +107,13 @@ pub struct CqueryCommand {
     )]
     show_providers: bool,
 
+    #[clap(
+        long,
+        help = "For each target, show the condition chosen by every select in its attributes \
+                (`DEFAULT` for the default), in the order the selects appear"
+    )]
+    show_selects: bool,
+
     #[allow(rustdoc::bare_urls)]
     /// Enable deprecated `owner()` function behavior.
     ///
@@ -115,6 +133,10 @@ pub struct CqueryCommand {
 impl StreamingCommand for CqueryCommand {
     const COMMAND_NAME: &'static str = "cquery";
 
+    fn allows_version_skew() -> bool {
+        true
+    }
+
     async fn exec_impl(
         self,
         buckd: &mut BuckdClientConnector,
@@ -147,6 +169,8 @@ impl StreamingCommand for CqueryCommand {
                     output_attributes,
                     target_universe: self.target_universe,
                     show_providers: self.show_providers,
+                    show_selects: self.show_selects,
+                    output_template: self.query_common.output_template(),
                     unstable_output_format,
                     correct_owner,
                 },
@@ -86,6 +86,10 @@ pub struct UqueryCommand {
 impl StreamingCommand for UqueryCommand {
     const COMMAND_NAME: &'static str = "uquery";
 
+    fn allows_version_skew() -> bool {
+        true
+    }
+
     async fn exec_impl(
         mut self,
         buckd: &mut BuckdClientConnector,
@@ -105,6 +109,7 @@ impl StreamingCommand for UqueryCommand {
                     query_args,
                     context: Some(context),
                     output_attributes,
+                    output_template: self.query_common.output_template(),
                     unstable_output_format,
                 },
                 ctx.stdin()
@@ -0,0 +1,263 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Local rage bundle, written by `buck2 rage --bundle <path>` to be attached to bug reports,
+//! instead of uploading the collected data.
+//!
+//! The bundle is a zstd-compressed tar archive. All of its files are redacted: matches of the
+//! default rules (the home directory, the username, and values which look like secrets) and of
+//! the `--redact` patterns are replaced with `<redacted>`.
+//!
+//! With `--encrypt-to <recipient>`, the archive is encrypted with `gpg` to the public key of the
+//! recipient, which must be in the keyring of the user, so only the recipient can read it.
+
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+use std::process::Stdio;
+use std::time::SystemTime;
+
+use anyhow::Context;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
+use buck2_event_log::read::EventLogPathBuf;
+use regex::Regex;
+use tokio_stream::StreamExt;
+
+const REDACTED: &str = "<redacted>";
+
+/// Buckconfig files of the project which are included in the bundle.
+const BUCKCONFIG_FILES: &[&str] = &[".buckconfig", ".buckconfig.local"];
+
+#[derive(Debug, buck2_error::Error)]
+enum RageBundleError {
+    #[error("Invalid redaction pattern `{0}`")]
+    #[buck2(user)]
+    InvalidPattern(String),
+    #[error("Error running `gpg` to encrypt the rage bundle (is it installed?)")]
+    #[buck2(user)]
+    GpgNotRun,
+    #[error("`gpg` failed to encrypt the rage bundle to `{0}`: {1}")]
+    #[buck2(user)]
+    GpgFailed(String, String),
+}
+
+/// Rewrites the contents of the bundle to remove sensitive data.
+pub(crate) struct Redactor {
+    /// Patterns and what their matches are replaced with.
+    rules: Vec<(Regex, String)>,
+}
+
+impl Redactor {
+    pub(crate) fn new(
+        home_dir: Option<&str>,
+        username: Option<&str>,
+        patterns: &[String],
+    ) -> anyhow::Result<Redactor> {
+        let mut rules = Vec::new();
+        // Keep the name of the secret, only redact its value.
+        rules.push((
+            Regex::new(
+                r#"(?i)((?:token|secret|password|passwd|api[_-]?key|credentials?)["']?\s*[:=]\s*["']?)[^\s"',;&]+"#,
+            )?,
+            format!("${{1}}{}", REDACTED),
+        ));
+        if let Some(home_dir) = home_dir.filter(|h| !h.is_empty()) {
+            rules.push((Regex::new(&regex::escape(home_dir))?, REDACTED.to_owned()));
+        }
+        if let Some(username) = username.filter(|u| !u.is_empty()) {
+            rules.push((
+                Regex::new(&format!(r"\b{}\b", regex::escape(username)))?,
+                REDACTED.to_owned(),
+            ));
+        }
+        for pattern in patterns {
+            let regex = Regex::new(pattern)
+                .with_context(|| RageBundleError::InvalidPattern(pattern.clone()))?;
+            rules.push((regex, REDACTED.to_owned()));
+        }
+        Ok(Redactor { rules })
+    }
+
+    pub(crate) fn redact(&self, text: &str) -> String {
+        let mut text = text.to_owned();
+        for (regex, replacement) in &self.rules {
+            text = regex.replace_all(&text, replacement.as_str()).into_owned();
+        }
+        text
+    }
+}
+
+/// Files of the bundle, by path in the archive.
+#[derive(Default)]
+pub(crate) struct RageBundle {
+    files: Vec<(String, String)>,
+}
+
+impl RageBundle {
+    pub(crate) fn add(&mut self, path: impl Into<String>, content: String) {
+        self.files.push((path.into(), content));
+    }
+
+    /// Write the archive to `dest`, redacting each file, and encrypting it to `recipient` if set.
+    pub(crate) fn write(
+        &self,
+        dest: &Path,
+        redactor: &Redactor,
+        recipient: Option<&str>,
+    ) -> anyhow::Result<()> {
+        match recipient {
+            None => {
+                let file = std::fs::File::create(dest)
+                    .with_context(|| format!("Error creating rage bundle `{}`", dest.display()))?;
+                self.write_archive(file, redactor)
+            }
+            Some(recipient) => {
+                let mut archive = Vec::new();
+                self.write_archive(&mut archive, redactor)?;
+                gpg_encrypt(&archive, recipient, dest)
+            }
+        }
+    }
+
+    fn write_archive(&self, out: impl Write, redactor: &Redactor) -> anyhow::Result<()> {
+        let mut archive = tar::Builder::new(zstd::Encoder::new(out, 0)?.auto_finish());
+        let mtime = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        for (path, content) in &self.files {
+            let content = redactor.redact(content);
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(mtime);
+            header.set_cksum();
+            archive.append_data(&mut header, path, content.as_bytes())?;
+        }
+        archive.into_inner()?.flush()?;
+        Ok(())
+    }
+}
+
+/// Encrypt `data` to the public key of `recipient` with `gpg`, writing the result to `dest`.
+fn gpg_encrypt(data: &[u8], recipient: &str, dest: &Path) -> anyhow::Result<()> {
+    let mut child = Command::new("gpg")
+        .args([
+            "--batch",
+            "--yes",
+            "--encrypt",
+            "--recipient",
+            recipient,
+            "--output",
+        ])
+        .arg(dest)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context(RageBundleError::GpgNotRun)?;
+    // Dropping stdin closes it, so that `gpg` sees the end of the input.
+    child
+        .stdin
+        .take()
+        .context("stdin of `gpg` not piped")?
+        .write_all(data)?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(RageBundleError::GpgFailed(
+            recipient.to_owned(),
+            String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// The buckconfig files of the project, by file name.
+pub(crate) fn buckconfig_files(
+    project_root: &AbsNormPath,
+) -> anyhow::Result<Vec<(String, String)>> {
+    let mut files = Vec::new();
+    for name in BUCKCONFIG_FILES {
+        if let Some(content) = fs_util::read_to_string_if_exists(
+            project_root.join(ForwardRelativePath::unchecked_new(name)),
+        )? {
+            files.push(((*name).to_owned(), content));
+        }
+    }
+    Ok(files)
+}
+
+/// An event log, as JSON lines like `buck2 log show` prints them.
+pub(crate) async fn event_log_json(log: &EventLogPathBuf) -> anyhow::Result<String> {
+    let (invocation, mut events) = log.unpack_stream().await?;
+    let mut json = serde_json::to_string(&invocation)?;
+    json.push('\n');
+    while let Some(event) = events.try_next().await? {
+        json.push_str(&serde_json::to_string(&event)?);
+        json.push('\n');
+    }
+    Ok(json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact() -> anyhow::Result<()> {
+        let redactor = Redactor::new(
+            Some("/home/alice"),
+            Some("alice"),
+            &["secret-project-[0-9]+".to_owned()],
+        )?;
+        assert_eq!(
+            "<redacted>/repo: <redacted> built secret-projects",
+            redactor.redact("/home/alice/repo: alice built secret-projects")
+        );
+        assert_eq!(
+            "AUTH_TOKEN=<redacted> password: \"<redacted>\"",
+            redactor.redact("AUTH_TOKEN=abc123 password: \"hunter2\"")
+        );
+        assert_eq!("<redacted>", redactor.redact("secret-project-42"));
+        assert_eq!("malice", redactor.redact("malice"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_archive() -> anyhow::Result<()> {
+        let mut bundle = RageBundle::default();
+        bundle.add("rage.txt", "built by alice".to_owned());
+        bundle.add("buckconfig/.buckconfig", "[buck2]\n".to_owned());
+        let mut archive = Vec::new();
+        bundle.write_archive(&mut archive, &Redactor::new(None, Some("alice"), &[])?)?;
+
+        let mut files = Vec::new();
+        for entry in tar::Archive::new(zstd::Decoder::new(archive.as_slice())?).entries()? {
+            let mut entry = entry?;
+            let mut content = String::new();
+            std::io::Read::read_to_string(&mut entry, &mut content)?;
+            files.push((entry.path()?.to_string_lossy().into_owned(), content));
+        }
+        assert_eq!(
+            vec![
+                ("rage.txt".to_owned(), "built by <redacted>".to_owned()),
+                ("buckconfig/.buckconfig".to_owned(), "[buck2]\n".to_owned()),
+            ],
+            files
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        assert!(Redactor::new(None, None, &["(".to_owned()]).is_err());
+    }
+}
@@ -8,6 +8,7 @@
  */
 
 mod build_info;
+mod bundle;
 mod dice;
 mod manifold;
 mod materializer;
@@ -28,9 +29,11 @@ use buck2_client_ctx::daemon::client::connect::BuckdProcessInfo;
 use buck2_client_ctx::exit_result::ExitResult;
 use buck2_client_ctx::manifold::Bucket;
 use buck2_client_ctx::manifold::ManifoldClient;
+use buck2_client_ctx::path_arg::PathArg;
 use buck2_client_ctx::stdin::Stdin;
 use buck2_common::argv::Argv;
 use buck2_common::argv::SanitizedArgv;
+use buck2_core::fs::fs_util;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
 use buck2_data::instant_event::Data;
@@ -40,11 +43,13 @@ use buck2_event_log::file_names::do_find_log_by_trace_id;
 use buck2_event_log::file_names::get_local_logs;
 use buck2_event_log::read::EventLogPathBuf;
 use buck2_event_log::read::EventLogSummary;
-use buck2_events::sink::scribe::new_thrift_scribe_sink_if_enabled;
-use buck2_events::sink::scribe::ThriftScribeSink;
+use buck2_events::sink::remote::new_remote_event_sink_if_enabled;
+use buck2_events::sink::remote::RemoteEventSink;
 use buck2_events::BuckEvent;
 use buck2_util::process::async_background_command;
 use buck2_wrapper_common::invocation_id::TraceId;
+use bundle::RageBundle;
+use bundle::Redactor;
 use chrono::offset::Local;
 use chrono::DateTime;
 use derive_more::Display;
@@ -103,14 +108,35 @@ pub struct RageCommand {
     /// or is called in a machine with no pastry command
     #[clap(long)]
     no_paste: bool,
+    /// Write the information to a local bundle at this path instead of uploading it, to attach
+    /// it to a bug report. The bundle is a zstd-compressed tar archive, with the home directory,
+    /// username and values which look like secrets redacted
+    #[clap(long, value_name = "PATH")]
+    bundle: Option<PathArg>,
+    /// Also redact the matches of this regex from the bundle. Can be repeated
+    #[clap(long, value_name = "REGEX", requires = "bundle")]
+    redact: Vec<String>,
+    /// Encrypt the bundle with `gpg` to the public key of this recipient (a key ID or email
+    /// address in your keyring), so that only they can read it
+    #[clap(long, value_name = "RECIPIENT", requires = "bundle")]
+    encrypt_to: Option<String>,
 }
 
+/// Number of recent event logs included in a bundle, besides the one of the selected
+/// invocation.
+const BUNDLE_RECENT_EVENT_LOGS: usize = 3;
+
 impl RageCommand {
     pub fn exec(self, _matches: &clap::ArgMatches, ctx: ClientCommandContext<'_>) -> ExitResult {
-        buck2_core::facebook_only();
+        if self.bundle.is_none() {
+            buck2_core::facebook_only();
+        }
 
         ctx.with_runtime(async move |ctx| {
-            self.exec_impl(ctx).await?;
+            match &self.bundle {
+                Some(bundle) => self.write_bundle(ctx, bundle).await?,
+                None => self.exec_impl(ctx).await?,
+            }
             ExitResult::success()
         })
     }
@@ -270,9 +296,117 @@ impl RageCommand {
         Ok(())
     }
 
+    async fn write_bundle(
+        &self,
+        mut ctx: ClientCommandContext<'_>,
+        dest: &PathArg,
+    ) -> anyhow::Result<()> {
+        let dest = dest.resolve(&ctx.working_dir);
+        let paths = ctx.paths.as_ref().map_err(|e| e.dupe())?;
+        let daemon_dir = paths.daemon_dir()?;
+        let stderr_path = daemon_dir.buckd_stderr();
+        let logdir = paths.log_dir();
+        let project_root = paths.project_root().root().to_buf();
+
+        buck2_client_ctx::eprintln!(
+            "Data collection will terminate after {} seconds (override with --timeout param)",
+            self.timeout
+        )?;
+
+        let selected_invocation = maybe_select_invocation(ctx.stdin(), &logdir, self).await?;
+        let mut event_logs = get_local_logs(&logdir)?
+            .into_iter()
+            .rev() // newest first
+            .take(BUNDLE_RECENT_EVENT_LOGS)
+            .collect::<Vec<_>>();
+        if let Some(selected) = &selected_invocation {
+            if !event_logs.iter().any(|log| log.path() == selected.path()) {
+                event_logs.push(selected.clone());
+            }
+        }
+
+        buck2_client_ctx::eprintln!("Collecting debug info...")?;
+
+        let system_info = self.section("System info", system_info::get).await;
+        let build_info = self
+            .skippable_section(
+                "Associated invocation info",
+                selected_invocation
+                    .as_ref()
+                    .map(|inv| || build_info::get(inv)),
+            )
+            .await;
+        let dice_metrics = self
+            .section("DICE metrics", || async {
+                let buckd = BuckdProcessInfo::load(&daemon_dir)?
+                    .create_channel()
+                    .await?
+                    .upgrade()
+                    .await?;
+                let status = buckd
+                    .with_subscribers(Default::default())
+                    .with_flushing()
+                    .status(true)
+                    .await?;
+                Ok(serde_json::to_string_pretty(&status.snapshot)?)
+            })
+            .await;
+        let daemon_stderr = self
+            .section("Daemon stderr", || async {
+                let stderr = fs_util::read_if_exists(&stderr_path)?.unwrap_or_default();
+                Ok(String::from_utf8_lossy(&stderr).into_owned())
+            })
+            .await;
+        let buckconfigs = self
+            .section("Buckconfig", || async {
+                bundle::buckconfig_files(&project_root)
+            })
+            .await;
+
+        let mut bundle = RageBundle::default();
+        let mut report = vec![system_info.to_string(), build_info.to_string()];
+        add_bundle_section(&mut bundle, &mut report, "dice_metrics.json", dice_metrics);
+        add_bundle_section(&mut bundle, &mut report, "daemon_stderr.log", daemon_stderr);
+        match buckconfigs.status {
+            CommandStatus::Success { output } => {
+                for (name, content) in output {
+                    bundle.add(format!("buckconfig/{}", name), content);
+                }
+            }
+            _ => report.push(buckconfigs.to_string()),
+        }
+        for log in &event_logs {
+            let name = log.path().file_name().map_or_else(
+                || "event_log".to_owned(),
+                |n| n.to_string_lossy().into_owned(),
+            );
+            let name = name.strip_suffix(log.extension()).unwrap_or(&name);
+            let title = format!("Event log {}", name);
+            let json = self.section(&title, || bundle::event_log_json(log)).await;
+            add_bundle_section(
+                &mut bundle,
+                &mut report,
+                &format!("event_logs/{}.jsonl", name),
+                json,
+            );
+        }
+        bundle.add("rage.txt", report.join(""));
+
+        let username = system_info.get_field(|o| o.username.clone());
+        let home_dir = std::env::var(if cfg!(windows) { "USERPROFILE" } else { "HOME" }).ok();
+        let redactor = Redactor::new(home_dir.as_deref(), username.as_deref(), &self.redact)?;
+        bundle.write(&dest, &redactor, self.encrypt_to.as_deref())?;
+
+        buck2_client_ctx::eprintln!(
+            "\nWrote the rage bundle to `{}`. Check it before attaching it to a bug report.",
+            dest.display()
+        )?;
+        Ok(())
+    }
+
     async fn send_to_scuba(
         &self,
-        sink: Option<ThriftScribeSink>,
+        sink: Option<RemoteEventSink>,
         invocation_id: Option<TraceId>,
         system_info: RageSection<system_info::SystemInfo>,
         daemon_stderr_dump: RageSection<String>,
@@ -485,6 +619,25 @@ where
     }
 }
 
+/// Add the output of `section` to the bundle at `path`, or note its failure in the report.
+fn add_bundle_section(
+    bundle: &mut RageBundle,
+    report: &mut Vec<String>,
+    path: &str,
+    section: RageSection<String>,
+) {
+    match section.status {
+        CommandStatus::Success { output } => bundle.add(path, output),
+        status => report.push(
+            RageSection {
+                title: section.title,
+                status,
+            }
+            .to_string(),
+        ),
+    }
+}
+
 fn insert_if_some<D>(data: &mut HashMap<String, D>, key: &str, value: Option<D>) {
     if let Some(value) = value {
         data.insert(key.to_owned(), value);
@@ -522,7 +675,7 @@ async fn upload_re_logs_impl(
 }
 
 async fn dispatch_result_event(
-    sink: Option<&ThriftScribeSink>,
+    sink: Option<&RemoteEventSink>,
     rage_id: &TraceId,
     result: RageResult,
 ) -> anyhow::Result<()> {
@@ -532,7 +685,7 @@ async fn dispatch_result_event(
 }
 
 async fn dispatch_event_to_scribe(
-    sink: Option<&ThriftScribeSink>,
+    sink: Option<&RemoteEventSink>,
     trace_id: &TraceId,
     event: InstantEvent,
 ) -> anyhow::Result<()> {
@@ -555,10 +708,10 @@ async fn dispatch_event_to_scribe(
 }
 
 #[allow(unused_variables)] // Conditional compilation
-fn create_scribe_sink(ctx: &ClientCommandContext) -> anyhow::Result<Option<ThriftScribeSink>> {
+fn create_scribe_sink(ctx: &ClientCommandContext) -> anyhow::Result<Option<RemoteEventSink>> {
     // TODO(swgiillespie) scribe_logging is likely the right feature for this, but we should be able to inject a sink
     // without using configurations at the call site
-    new_thrift_scribe_sink_if_enabled(
+    new_remote_event_sink_if_enabled(
         ctx.fbinit(),
         /* buffer size */ 100,
         /* retry_backoff */ Duration::from_millis(500),
@@ -34,10 +34,34 @@ enum StatusError {
 pub struct StatusCommand {
     #[clap(long, help = "Whether to include a state snapshot in the output.")]
     snapshot: bool,
-    #[clap(long, help = "Enable printing status for all running buckd")]
+    #[clap(
+        long,
+        help = "Enable printing status for all running buckd, across all projects and isolation dirs"
+    )]
     all: bool,
 }
 
+/// The daemon dirs of all the projects and isolation dirs which have a buckd running, or which
+/// had one that didn't clean up after itself.
+pub(crate) fn all_daemon_dirs(ctx: &ClientCommandContext<'_>) -> anyhow::Result<Vec<DaemonDir>> {
+    let mut daemon_dirs = Vec::new();
+    let root = ctx.paths()?.roots.common_buckd_dir()?;
+    let walker = WalkDir::new(&root).follow_links(false).into_iter();
+    for entry in walker {
+        let entry = entry?;
+        if entry.file_type().is_dir() {
+            let dir = DaemonDir {
+                path: entry.into_path().try_into()?,
+            };
+
+            if dir.buckd_info().exists() {
+                daemon_dirs.push(dir);
+            }
+        }
+    }
+    Ok(daemon_dirs)
+}
+
 impl StatusCommand {
     pub fn exec(
         self,
@@ -46,21 +70,7 @@ impl StatusCommand {
     ) -> anyhow::Result<()> {
         ctx.with_runtime(async move |ctx| {
             if self.all {
-                let mut daemon_dirs = Vec::new();
-                let root = ctx.paths()?.roots.common_buckd_dir()?;
-                let walker = WalkDir::new(&root).follow_links(false).into_iter();
-                for entry in walker {
-                    let entry = entry?;
-                    if entry.file_type().is_dir() {
-                        let dir = DaemonDir {
-                            path: entry.into_path().try_into()?,
-                        };
-
-                        if dir.buckd_info().exists() {
-                            daemon_dirs.push(dir);
-                        }
-                    }
-                }
+                let daemon_dirs = all_daemon_dirs(&ctx)?;
 
                 let mut statuses = Vec::new();
                 for dir in daemon_dirs {
@@ -143,6 +153,8 @@ fn process_status(status: StatusResponse) -> anyhow::Result<serde_json::Value> {
         "forkserver_pid": serde_json::to_value(status.forkserver_pid)?,
         "supports_vpnless": status.supports_vpnless.unwrap_or_default(),
         "http2": status.http2,
+        "rss_bytes": status.rss_bytes,
+        "active_commands": serde_json::to_value(status.active_commands)?,
     }))
 }
 
@@ -150,7 +162,11 @@ fn process_status(status: StatusResponse) -> anyhow::Result<serde_json::Value> {
 mod tests {
     use std::time::Duration;
 
+    use buck2_cli_proto::ActiveCommandStatus;
+    use buck2_cli_proto::StatusResponse;
+
     use crate::commands::status::duration_to_string;
+    use crate::commands::status::process_status;
     use crate::commands::status::timestamp_to_string;
 
     #[test]
@@ -169,4 +185,26 @@ mod tests {
             duration_to_string(Duration::new(3600 + 120 + 3, 123456789))
         );
     }
+
+    #[test]
+    fn test_process_status() {
+        let status = process_status(StatusResponse {
+            rss_bytes: Some(1024),
+            active_commands: vec![ActiveCommandStatus {
+                trace_id: "trace".to_owned(),
+                argv: vec!["buck2".to_owned(), "build".to_owned()],
+            }],
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(serde_json::json!(1024), status["rss_bytes"]);
+        assert_eq!(
+            serde_json::json!([{"trace_id": "trace", "argv": ["buck2", "build"]}]),
+            status["active_commands"]
+        );
+
+        let status = process_status(StatusResponse::default()).unwrap();
+        assert_eq!(serde_json::Value::Null, status["rss_bytes"]);
+        assert_eq!(serde_json::json!([]), status["active_commands"]);
+    }
 }
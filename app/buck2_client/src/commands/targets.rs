@@ -91,7 +91,31 @@ pub struct TargetsCommand {
     #[clap(long)]
     stats: bool,
 
-    /// Print the fully-qualified build target for the specified aliases
+    /// Print a line for each target following a template, e.g. `{label} {type}`. The variables
+    /// are `label`, `package`, `name`, `type`, `oncall` and `target_hash` (empty unless a target
+    /// hash is requested). Use `{{` and `}}` for literal braces.
+    #[clap(
+        long,
+        value_name = "TEMPLATE",
+        conflicts_with_all = &[
+            "json",
+            "json-lines",
+            "stats",
+            "resolve-alias",
+            "show-output",
+            "show-full-output",
+            "show-simple-output",
+            "show-full-simple-output",
+            "show-json-output",
+            "show-full-json-output",
+        ]
+    )]
+    output_template: Option<String>,
+
+    /// Print the fully-qualified build target for the specified aliases, in order. Pass many
+    /// aliases (or an `@argfile` of them) to resolve them all at once. With `--keep-going`,
+    /// aliases which don't resolve to an existing target are reported instead of failing: as an
+    /// empty line in the text output, or with `buck.error` in the JSON output.
     #[clap(long, alias = "resolvealias")]
     resolve_alias: bool,
 
@@ -111,8 +135,7 @@ pub struct TargetsCommand {
         long,
         arg_enum,
         ignore_case = true,
-        default_value = "paths_and_contents",
-        conflicts_with = "streaming"
+        default_value = "paths_and_contents"
     )]
     target_hash_file_mode: TargetHashFileMode,
 
@@ -120,7 +143,7 @@ pub struct TargetsCommand {
     /// `PATHS_ONLY`. If a target or its dependencies reference a file from this set, the target's hash
     /// will be different than if this option was omitted. Otherwise, the target's hash will be the same
     /// as if this option was omitted.
-    #[clap(long, multiple_values = true, conflicts_with = "streaming")]
+    #[clap(long, multiple_values = true)]
     target_hash_modified_paths: Vec<PathArg>,
 
     /// Selects either the "fast" or the "strong" target hash function to be used for computing target hashes.
@@ -131,7 +154,7 @@ pub struct TargetsCommand {
     target_hash_function: TargetHashFunction,
 
     /// When true, emit the hash or target node and all dependencies recursively.
-    /// When false, hash only the target node.
+    /// When false, hash only the target node. Hashes are never recursive with `--streaming`.
     #[clap(long, action = clap::ArgAction::Set, default_value = "true", conflicts_with = "streaming")]
     target_hash_recursive: bool,
 
@@ -152,7 +175,8 @@ pub struct TargetsCommand {
 
     /// Write output as soon as it is available. The order of the output items
     /// is non-deterministic and if multiple patterns cover the same target, may
-    /// have duplicates.
+    /// have duplicates. With `--show-unconfigured-target-hash`, the hash of each
+    /// target covers its attributes and files, but not its dependencies.
     #[clap(long)]
     streaming: bool,
 
@@ -195,7 +219,12 @@ pub struct TargetsCommand {
 impl TargetsCommand {
     #[allow(clippy::if_same_then_else)]
     fn output_format(&self) -> anyhow::Result<OutputFormat> {
-        if self.json {
+        if self.output_template.is_some() {
+            if !self.attributes.get()?.is_empty() {
+                return Err(TargetsError::IncompatibleArguments.into());
+            }
+            Ok(OutputFormat::Template)
+        } else if self.json {
             if self.json_lines || self.stats {
                 return Err(TargetsError::IncompatibleArguments.into());
             }
@@ -290,7 +319,9 @@ impl StreamingCommand for TargetsCommand {
             }),
             output_format: output_format as i32,
             targets: Some(if self.resolve_alias {
-                targets_request::Targets::ResolveAlias(targets_request::ResolveAlias {})
+                targets_request::Targets::ResolveAlias(targets_request::ResolveAlias {
+                    keep_going: self.keep_going,
+                })
             } else {
                 targets_request::Targets::Other(targets_request::Other {
                     output_attributes,
@@ -315,6 +346,7 @@ impl StreamingCommand for TargetsCommand {
                     cached: !self.no_cache,
                     imports: self.imports,
                     package_values,
+                    output_template: self.output_template.clone().unwrap_or_default(),
                 })
             }),
             output: self
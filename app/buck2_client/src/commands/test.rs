@@ -142,6 +142,11 @@ If include patterns are present, regardless of whether exclude patterns are pres
     #[clap(long = "overall-timeout")]
     timeout: Option<humantime::Duration>,
 
+    /// Only list the tests: test runner requests to list test binaries are executed, requests to
+    /// run tests are not. Listings of unchanged test binaries are reused from previous commands.
+    #[clap(long)]
+    listing_only: bool,
+
     #[clap(name = "TARGET_PATTERNS", help = "Patterns to test")]
     patterns: Vec<String>,
 
@@ -209,6 +214,7 @@ impl StreamingCommand for TestCommand {
                             || self.unstable_allow_all_tests_on_re,
                         force_use_project_relative_paths: self.unstable_allow_all_tests_on_re,
                         force_run_from_project_root: self.unstable_allow_all_tests_on_re,
+                        listing_only: self.listing_only,
                     }),
                     timeout: self
                         .timeout
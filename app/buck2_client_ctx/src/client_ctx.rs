@@ -138,6 +138,14 @@ impl<'a> ClientCommandContext<'a> {
             unstable_typecheck: config_opts.unstable_typecheck,
             skip_targets_with_duplicate_names: config_opts.skip_targets_with_duplicate_names,
             reuse_current_config: config_opts.reuse_current_config,
+            config_snapshot: config_opts
+                .config_snapshot
+                .as_ref()
+                .map(|p| p.resolve(&self.working_dir).to_string_lossy().into_owned()),
+            replay_config: config_opts
+                .replay_config
+                .as_ref()
+                .map(|p| p.resolve(&self.working_dir).to_string_lossy().into_owned()),
             sanitized_argv: cmd.sanitize_argv(self.argv.clone()).argv,
             exit_when_different_state: config_opts.exit_when_different_state,
             argfiles: self
@@ -182,6 +190,8 @@ impl<'a> ClientCommandContext<'a> {
             skip_targets_with_duplicate_names: false,
             trace_id: format!("{}", self.trace_id),
             reuse_current_config: false,
+            config_snapshot: None,
+            replay_config: None,
             daemon_uuid,
             sanitized_argv: Vec::new(),
             argfiles: Vec::new(),
@@ -131,6 +131,12 @@ pub struct CommonDaemonCommandOptions {
     /// regarding the stability of the format.
     #[clap(long, value_name = "PATH")]
     pub(crate) unstable_write_invocation_record: Option<PathArg>,
+
+    /// Print a summary of the resources used by the command when it finishes: local command
+    /// time by action category, peak daemon memory, remote execution queue time, cache hit rate,
+    /// bytes downloaded and uploaded, and Starlark evaluation time.
+    #[clap(long)]
+    pub(crate) resource_summary: bool,
 }
 
 impl CommonDaemonCommandOptions {
@@ -140,6 +146,7 @@ impl CommonDaemonCommandOptions {
             no_event_log: false,
             write_build_id: None,
             unstable_write_invocation_record: None,
+            resource_summary: false,
         };
         &DEFAULT
     }
@@ -181,7 +188,7 @@ pub struct CommonBuildConfigurationOptions {
         use_value_delimiter = true,
         value_delimiter=',',
         short = 'm',
-        help = "A configuration modifier to configure all targets on the command line. This may be a constraint value target.",
+        help = "A configuration modifier to configure all targets on the command line. This may be a constraint value target or a modifier alias. Modifiers are applied in order, a later one overriding an earlier one for the same constraint setting. `buck2 build` also accepts modifiers for a single pattern, e.g. `//foo:bar?asan+debug`, applied after these.",
         // Needs to be explicitly set, otherwise will treat `-c a b c` -> [a, b, c]
         // rather than [a] and other positional arguments `b c`.
         number_of_values = 1
@@ -232,9 +239,21 @@ pub struct CommonBuildConfigurationOptions {
     ///
     /// If there is no previous command but the flag was set, then the flag is ignored,
     /// the command behaves as if the flag was not set at all.
-    #[clap(long)]
+    #[clap(long, conflicts_with = "replay-config")]
     pub reuse_current_config: bool,
 
+    /// Write the fully resolved configuration of all cells, after includes, `--config` and
+    /// `--config-file` were applied, as JSON to this file, so that it can be reproduced later
+    /// with `--replay-config`.
+    #[clap(long, value_name = "PATH")]
+    pub config_snapshot: Option<PathArg>,
+
+    /// Use the configuration recorded by `--config-snapshot` instead of reading the buckconfig
+    /// files of the cells, so that local files like `.buckconfig.local` don't change it.
+    /// `--config` and `--config-file` still apply on top of it.
+    #[clap(long, value_name = "PATH")]
+    pub replay_config: Option<PathArg>,
+
     /// Used for exiting a concurrent command when a different state is detected.
     #[clap(long)]
     pub exit_when_different_state: bool,
@@ -344,6 +363,8 @@ impl CommonBuildConfigurationOptions {
             target_call_stacks: false,
             skip_targets_with_duplicate_names: false,
             reuse_current_config: false,
+            config_snapshot: None,
+            replay_config: None,
             exit_when_different_state: false,
         };
         &DEFAULT
@@ -503,6 +524,20 @@ pub struct CommonBuildOptions {
     /// Materializes inputs for failed actions which ran on RE
     #[clap(long)]
     materialize_failed_inputs: bool,
+
+    /// Fail any action or download which needs the network, with an error naming the target,
+    /// instead of using the network. Implies `--local-only` and `--no-remote-cache`.
+    ///
+    /// Downloads are still taken from the download cache (`http.download_cache`) and from the
+    /// offline cache (`buck2.use_network_action_output_cache`).
+    #[clap(long, conflicts_with_all = &["remote-only", "prefer-local", "prefer-remote"])]
+    offline: bool,
+
+    /// Print the output of the actions with this category (e.g. `cxx_compile`) or owning target
+    /// (e.g. `cell//foo:bar`) while they run, instead of only when they fail. Only applies to
+    /// actions running locally. Can be repeated.
+    #[clap(long, value_name = "CATEGORY|TARGET", number_of_values = 1)]
+    show_action_output: Vec<String>,
 }
 
 impl CommonBuildOptions {
@@ -530,7 +565,7 @@ impl CommonBuildOptions {
 
         buck2_cli_proto::CommonBuildOptions {
             concurrency,
-            execution_strategy: if self.local_only {
+            execution_strategy: if self.local_only || self.offline {
                 ExecutionStrategy::LocalOnly as i32
             } else if self.remote_only {
                 ExecutionStrategy::RemoteOnly as i32
@@ -547,13 +582,15 @@ impl CommonBuildOptions {
             unstable_build_report_filename,
             eager_dep_files: self.eager_dep_files,
             upload_all_actions: self.upload_all_actions,
-            skip_cache_read: self.no_remote_cache,
-            skip_cache_write: self.no_remote_cache && !self.write_to_cache_anyway,
+            skip_cache_read: self.no_remote_cache || self.offline,
+            skip_cache_write: (self.no_remote_cache && !self.write_to_cache_anyway) || self.offline,
             fail_fast: self.fail_fast,
             keep_going: self.keep_going,
             skip_missing_targets: self.skip_missing_targets,
             skip_incompatible_targets: self.skip_incompatible_targets,
             materialize_failed_inputs: self.materialize_failed_inputs,
+            offline: self.offline,
+            show_action_output: self.show_action_output.clone(),
             unstable_include_failures_build_report,
             unstable_include_package_project_relative_paths,
         }
@@ -68,6 +68,8 @@ pub struct DaemonConstraintsRequest {
     pub reject_daemon: Option<String>,
     pub reject_materializer_state: Option<String>,
     pub daemon_startup_config: DaemonStartupConfig,
+    /// Accept a daemon of a different buck2 version, if it speaks the same protocol.
+    pub allow_version_skew: bool,
 }
 
 #[derive(Debug, derive_more::Display)]
@@ -123,6 +125,7 @@ impl DaemonConstraintsRequest {
             reject_daemon: None,
             reject_materializer_state: None,
             daemon_startup_config: immediate_config.daemon_startup_config()?.clone(),
+            allow_version_skew: false,
         })
     }
 
@@ -134,7 +137,10 @@ impl DaemonConstraintsRequest {
         &self,
         daemon: &buck2_cli_proto::DaemonConstraints,
     ) -> Result<(), ConstraintUnsatisfiedReason> {
-        if self.version != daemon.version {
+        if self.version != daemon.version
+            && !(self.allow_version_skew
+                && daemon.protocol_version == buck2_cli_proto::PROTOCOL_VERSION)
+        {
             return Err(ConstraintUnsatisfiedReason::Version);
         }
 
@@ -1027,6 +1033,7 @@ mod tests {
             daemon_startup_config: Some(
                 serde_json::to_string(&DaemonStartupConfig::testing_empty()).unwrap(),
             ),
+            protocol_version: buck2_cli_proto::PROTOCOL_VERSION,
         }
     }
 
@@ -1038,6 +1045,7 @@ mod tests {
             reject_daemon: None,
             reject_materializer_state: None,
             daemon_startup_config: DaemonStartupConfig::testing_empty(),
+            allow_version_skew: false,
         }
     }
 
@@ -1062,6 +1070,20 @@ mod tests {
         assert!(req.satisfied(&daemon).is_err());
     }
 
+    #[test]
+    fn test_version_skew() {
+        let mut req = request(DesiredTraceIoState::Existing);
+        let mut daemon = constraints(true);
+        daemon.version = "other".to_owned();
+        assert!(req.satisfied(&daemon).is_err());
+
+        req.allow_version_skew = true;
+        assert!(req.satisfied(&daemon).is_ok());
+
+        daemon.protocol_version = 0;
+        assert!(req.satisfied(&daemon).is_err());
+    }
+
     #[test]
     fn test_trace_io_is_enabled() {
         let c = request(DesiredTraceIoState::Enabled);
@@ -1080,6 +1102,7 @@ mod tests {
             reject_daemon: None,
             reject_materializer_state: None,
             daemon_startup_config: DaemonStartupConfig::testing_empty(),
+            allow_version_skew: false,
         };
 
         let daemon = buck2_cli_proto::DaemonConstraints {
@@ -1090,6 +1113,7 @@ mod tests {
             daemon_startup_config: Some(
                 serde_json::to_string(&DaemonStartupConfig::testing_empty()).unwrap(),
             ),
+            protocol_version: buck2_cli_proto::PROTOCOL_VERSION,
         };
 
         assert!(req.satisfied(&daemon).is_ok());
@@ -1108,6 +1132,7 @@ mod tests {
             reject_daemon: None,
             reject_materializer_state: None,
             daemon_startup_config: DaemonStartupConfig::testing_empty(),
+            allow_version_skew: false,
         };
 
         let daemon = buck2_cli_proto::DaemonConstraints {
@@ -1121,6 +1146,7 @@ mod tests {
             daemon_startup_config: Some(
                 serde_json::to_string(&DaemonStartupConfig::testing_empty()).unwrap(),
             ),
+            protocol_version: buck2_cli_proto::PROTOCOL_VERSION,
         };
 
         assert!(req.satisfied(&daemon).is_ok());
@@ -1139,6 +1165,7 @@ mod tests {
             reject_daemon: None,
             reject_materializer_state: None,
             daemon_startup_config: DaemonStartupConfig::testing_empty(),
+            allow_version_skew: false,
         };
 
         let daemon = buck2_cli_proto::DaemonConstraints {
@@ -1152,6 +1179,7 @@ mod tests {
             daemon_startup_config: Some(
                 serde_json::to_string(&DaemonStartupConfig::testing_empty()).unwrap(),
             ),
+            protocol_version: buck2_cli_proto::PROTOCOL_VERSION,
         };
 
         assert!(req.satisfied(&daemon).is_ok());
@@ -252,14 +252,17 @@ impl<'a> BuckdClient<'a> {
             .context("Error dispatching request");
         let stream = grpc_to_stream(response);
         pin_mut!(stream);
-        events_ctx
+        events_ctx.daemon = Some(client.clone());
+        let res = events_ctx
             .unpack_stream(
                 partial_result_handler,
                 stream,
                 self.tailers.take(),
                 console_interaction,
             )
-            .await
+            .await;
+        events_ctx.daemon = None;
+        res
     }
 
     pub async fn status(&mut self, snapshot: bool) -> anyhow::Result<StatusResponse> {
@@ -21,6 +21,7 @@ pub fn gen_daemon_constraints(
         daemon_id: buck2_events::daemon_id::DAEMON_UUID.to_string(),
         daemon_startup_config: Some(daemon_startup_config.serialize()?),
         extra: None,
+        protocol_version: buck2_cli_proto::PROTOCOL_VERSION,
     })
 }
 
@@ -15,7 +15,10 @@ use std::time::SystemTime;
 use anyhow::Context;
 use async_trait::async_trait;
 use buck2_cli_proto::command_result;
+use buck2_cli_proto::console_control_request;
+use buck2_cli_proto::daemon_api_client::DaemonApiClient;
 use buck2_cli_proto::CommandResult;
+use buck2_cli_proto::ConsoleControlRequest;
 use buck2_common::daemon_dir::DaemonDir;
 use buck2_event_log::stream_value::StreamValue;
 use buck2_events::BuckEvent;
@@ -28,12 +31,15 @@ use gazebo::prelude::VecExt;
 use thiserror::Error;
 use tokio::sync::mpsc;
 use tokio::sync::mpsc::UnboundedReceiver;
+use tonic::codegen::InterceptedService;
+use tonic::transport::Channel;
 
 use crate::client_cpu_tracker::ClientCpuTracker;
 use crate::command_outcome::CommandOutcome;
 use crate::console_interaction_stream::ConsoleInteraction;
 use crate::console_interaction_stream::ConsoleInteractionStream;
 use crate::console_interaction_stream::NoopConsoleInteraction;
+use crate::daemon::client::connect::BuckAddAuthTokenInterceptor;
 use crate::daemon::client::tonic_status_to_error;
 use crate::daemon::client::NoPartialResultHandler;
 use crate::exit_result::ExitResult;
@@ -107,6 +113,11 @@ pub struct EventsCtx<'a> {
     pub(crate) subscribers: EventSubscribers<'a>,
     ticker: Ticker,
     client_cpu_tracker: ClientCpuTracker,
+    /// Set while a command is streaming, so console interactions can be forwarded to the daemon.
+    pub(crate) daemon:
+        Option<DaemonApiClient<InterceptedService<Channel, BuckAddAuthTokenInterceptor>>>,
+    /// The trace id of the command being streamed, taken from its events.
+    trace_id: Option<String>,
 }
 
 #[derive(PartialEq, Eq, Debug)]
@@ -127,6 +138,8 @@ impl<'a> EventsCtx<'a> {
             subscribers,
             ticker: Ticker::new(TICKS_PER_SECOND),
             client_cpu_tracker: ClientCpuTracker::new(),
+            daemon: None,
+            trace_id: None,
         }
     }
 
@@ -151,6 +164,9 @@ impl<'a> EventsCtx<'a> {
             };
             match next {
                 StreamValue::Event(event) => {
+                    if self.trace_id.is_none() {
+                        self.trace_id = Some(event.trace_id.clone());
+                    }
                     let event = event.try_into()?;
                     events.push(event);
                 }
@@ -393,6 +409,27 @@ impl<'a> EventsCtx<'a> {
     }
 
     async fn handle_console_interaction(&mut self, c: char) -> anyhow::Result<()> {
+        let action = match c {
+            's' => Some(console_control_request::Action::ToggleActionDispatch),
+            't' => Some(console_control_request::Action::DumpStacks),
+            _ => None,
+        };
+        if let (Some(action), Some(daemon), Some(trace_id)) =
+            (action, &mut self.daemon, &self.trace_id)
+        {
+            // The daemon reports the outcome over the event stream, so there is nothing to do
+            // with the response. Failures (e.g. an older daemon) must not fail the command.
+            if let Err(e) = daemon
+                .console_control(tonic::Request::new(ConsoleControlRequest {
+                    action: action as i32,
+                    trace_id: trace_id.clone(),
+                }))
+                .await
+            {
+                tracing::warn!("Failed to send console control to daemon: {}", e);
+            }
+        }
+
         self.subscribers
             .for_each_subscriber(|subscriber| subscriber.handle_console_interaction(c))
             .await
@@ -33,6 +33,7 @@ use crate::subscribers::get::try_get_build_graph_stats;
 use crate::subscribers::get::try_get_build_id_writer;
 use crate::subscribers::get::try_get_event_log_subscriber;
 use crate::subscribers::get::try_get_re_log_subscriber;
+use crate::subscribers::get::try_get_resource_summary;
 use crate::subscribers::recorder::try_get_invocation_recorder;
 use crate::subscribers::subscriber::EventSubscriber;
 use crate::subscribers::subscribers::EventSubscribers;
@@ -72,6 +73,9 @@ fn default_subscribers<'a, T: StreamingCommand>(
     if let Some(build_graph_stats) = try_get_build_graph_stats(cmd, ctx)? {
         subscribers.push(build_graph_stats)
     }
+    if let Some(resource_summary) = try_get_resource_summary(cmd.event_log_opts())? {
+        subscribers.push(resource_summary)
+    }
     let recorder = try_get_invocation_recorder(
         ctx,
         cmd.event_log_opts(),
@@ -113,6 +117,14 @@ pub trait StreamingCommand: Sized + Send + Sync {
         DesiredTraceIoState::Existing
     }
 
+    /// Whether this command can run on a daemon of a different buck2 version, as long as it
+    /// speaks the same client-daemon protocol, instead of restarting it. Only appropriate for
+    /// read-only commands, whose output doesn't depend much on the buck2 version. Defaults to
+    /// `false`.
+    fn allows_version_skew() -> bool {
+        false
+    }
+
     fn console_opts(&self) -> &CommonConsoleOptions;
 
     fn event_log_opts(&self) -> &CommonDaemonCommandOptions;
@@ -161,6 +173,7 @@ impl<T: StreamingCommand> BuckSubcommand for T {
                 } else {
                     let mut req =
                         DaemonConstraintsRequest::new(ctx.immediate_config, T::trace_io(&self))?;
+                    req.allow_version_skew = T::allows_version_skew();
                     ctx.restarter.apply_to_constraints(&mut req);
                     BuckdConnectConstraints::Constraints(req)
                 };
@@ -12,7 +12,7 @@ use std::time::SystemTime;
 
 use async_trait::async_trait;
 use buck2_cli_proto::command_result;
-use buck2_events::sink::scribe::new_thrift_scribe_sink_if_enabled;
+use buck2_events::sink::remote::new_remote_event_sink_if_enabled;
 use buck2_wrapper_common::invocation_id::TraceId;
 use dupe::Dupe;
 use fbinit::FacebookInit;
@@ -76,7 +76,7 @@ impl BuildGraphStats {
 
     async fn send_events(&self, events: Vec<buck2_events::BuckEvent>) {
         if let Ok(Some(sink)) =
-            new_thrift_scribe_sink_if_enabled(self.fb, 1, Duration::from_millis(100), 2, None)
+            new_remote_event_sink_if_enabled(self.fb, 1, Duration::from_millis(100), 2, None)
         {
             tracing::info!("Sending events to Scribe: {:?}", &events);
             sink.send_messages_now(events).await;
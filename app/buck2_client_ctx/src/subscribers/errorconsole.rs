@@ -8,6 +8,7 @@
  */
 
 use async_trait::async_trait;
+use buck2_events::errors::error_report_code_line;
 
 use crate::subscribers::subscriber_unpack::UnpackingEventSubscriber;
 
@@ -29,6 +30,7 @@ impl UnpackingEventSubscriber for ErrorConsole {
             crate::eprintln!("Command failed: ")?;
             for e in &e.errors {
                 crate::eprintln!("{}", e.message)?;
+                crate::eprintln!("{}", error_report_code_line(e))?;
             }
         }
         Ok(())
@@ -24,6 +24,7 @@ use crate::subscribers::build_id_writer::BuildIdWriter;
 use crate::subscribers::errorconsole::ErrorConsole;
 use crate::subscribers::event_log::EventLog;
 use crate::subscribers::re_log::ReLog;
+use crate::subscribers::resource_summary::ResourceSummaryPrinter;
 use crate::subscribers::simpleconsole::SimpleConsole;
 use crate::subscribers::subscriber::EventSubscriber;
 use crate::subscribers::subscriber_unpack::UnpackingEventSubscriberAsEventSubscriber;
@@ -143,6 +144,16 @@ pub(crate) fn try_get_build_id_writer<'a>(
     }
 }
 
+pub(crate) fn try_get_resource_summary<'a>(
+    opts: &CommonDaemonCommandOptions,
+) -> anyhow::Result<Option<Box<dyn EventSubscriber + 'a>>> {
+    if opts.resource_summary {
+        Ok(Some(Box::new(ResourceSummaryPrinter::default())))
+    } else {
+        Ok(None)
+    }
+}
+
 pub(crate) fn try_get_build_graph_stats<'a, T: StreamingCommand>(
     cmd: &T,
     ctx: &ClientCommandContext<'a>,
@@ -16,6 +16,7 @@ pub mod get;
 pub(crate) mod observer;
 pub mod re_log;
 pub mod recorder;
+pub(crate) mod resource_summary;
 pub(crate) mod simpleconsole;
 pub mod stdout_stderr_forwarder;
 pub mod subscriber;
@@ -24,6 +24,7 @@ use anyhow::Context;
 use async_trait::async_trait;
 use buck2_cli_proto::command_result;
 use buck2_common::convert::ProstDurationExt;
+use buck2_common::daemon_dir::DAEMON_CRASH_REPORT_PREFIX;
 use buck2_core::fs::fs_util;
 use buck2_core::fs::paths::abs_path::AbsPathBuf;
 use buck2_data::error::ErrorTag;
@@ -34,7 +35,8 @@ use buck2_event_observer::cache_hit_rate::total_cache_hit_rate;
 use buck2_event_observer::last_command_execution_kind;
 use buck2_event_observer::last_command_execution_kind::LastCommandExecutionKind;
 use buck2_events::errors::create_error_report;
-use buck2_events::sink::scribe::new_thrift_scribe_sink_if_enabled;
+use buck2_events::errors::error_report_code;
+use buck2_events::sink::remote::new_remote_event_sink_if_enabled;
 use buck2_events::BuckEvent;
 use buck2_util::cleanup_ctx::AsyncCleanupContext;
 use buck2_util::system_stats::system_memory_stats;
@@ -53,6 +55,7 @@ use crate::client_metadata::ClientMetadata;
 use crate::common::CommonDaemonCommandOptions;
 use crate::subscribers::classify_server_stderr::classify_server_stderr;
 use crate::subscribers::observer::ErrorObserver;
+use crate::subscribers::resource_summary::ResourceSummary;
 use crate::subscribers::subscriber::EventSubscriber;
 
 struct ErrorIntermediate {
@@ -142,6 +145,7 @@ pub(crate) struct InvocationRecorder<'a> {
     /// To append to gRPC errors.
     server_stderr: String,
     target_rule_type_names: Vec<String>,
+    resource_summary: ResourceSummary,
 }
 
 impl<'a> InvocationRecorder<'a> {
@@ -237,6 +241,7 @@ impl<'a> InvocationRecorder<'a> {
             errors: Vec::new(),
             server_stderr: String::new(),
             target_rule_type_names: Vec::new(),
+            resource_summary: ResourceSummary::default(),
         }
     }
 
@@ -286,6 +291,16 @@ impl<'a> InvocationRecorder<'a> {
 
             error.processed.message.push('\n');
 
+            // Put the crash report first, so it isn't lost when stderr is truncated.
+            if let Some(crash_report) = self
+                .server_stderr
+                .lines()
+                .find(|l| l.starts_with(DAEMON_CRASH_REPORT_PREFIX))
+            {
+                error.processed.message.push_str(crash_report);
+                error.processed.message.push('\n');
+            }
+
             if self.server_stderr.is_empty() {
                 error.processed.message.push_str("buckd stderr is empty\n");
             } else {
@@ -459,6 +474,13 @@ impl<'a> InvocationRecorder<'a> {
             errors: std::mem::take(&mut self.errors).into_map(|e| e.processed),
             best_error_tag: best_error_tag.map(|t| t.to_owned()),
             target_rule_type_names: std::mem::take(&mut self.target_rule_type_names),
+            local_command_time_ms_by_category: self
+                .resource_summary
+                .local_command_time_ms_by_category(),
+            re_queue_time_ms: Some(self.resource_summary.re_queue_time().as_millis() as u64),
+            starlark_evaluation_time_ms: Some(
+                self.resource_summary.starlark_time().as_millis() as u64
+            ),
         };
 
         let event = BuckEvent::new(
@@ -491,7 +513,7 @@ impl<'a> InvocationRecorder<'a> {
         }
 
         if let Ok(Some(scribe_sink)) =
-            new_thrift_scribe_sink_if_enabled(self.fb, 1, Duration::from_millis(500), 5, None)
+            new_remote_event_sink_if_enabled(self.fb, 1, Duration::from_millis(500), 5, None)
         {
             tracing::info!("Recording invocation to Scribe: {:?}", &event);
             Some(async move {
@@ -965,6 +987,7 @@ impl<'a> InvocationRecorder<'a> {
             ));
         }
         self.event_count += 1;
+        self.resource_summary.handle_event(event);
 
         match event.data() {
             buck2_data::buck_event::Data::SpanStart(ref start) => {
@@ -1079,6 +1102,7 @@ fn process_error_report(error: buck2_data::ErrorReport) -> buck2_data::Processed
         ErrorTag::from_i32(*tag)))
     .map(|t| t.as_str_name())
     .unwrap_or(ERROR_TAG_UNCLASSIFIED);
+    let error_code = error_report_code(&error);
     buck2_data::ProcessedErrorReport {
         category: error.category,
         message: error.message,
@@ -1096,6 +1120,7 @@ fn process_error_report(error: buck2_data::ErrorReport) -> buck2_data::Processed
             .map(|t| t.as_str_name().to_owned())
             .collect(),
         best_tag: Some(best_tag.to_owned()),
+        error_code: Some(error_code.as_str().to_owned()),
     }
 }
 
@@ -0,0 +1,377 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Aggregate resource usage of a command, printed when it finishes with `--resource-summary`.
+//! The totals which the invocation record doesn't have otherwise are logged in it too.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use buck2_event_observer::cache_hit_rate::total_cache_hit_rate;
+use buck2_event_observer::fmt_duration::fmt_duration;
+use buck2_event_observer::humanized::HumanizedBytes;
+use buck2_event_observer::last_command_execution_kind;
+use buck2_event_observer::last_command_execution_kind::LastCommandExecutionKind;
+use buck2_events::BuckEvent;
+
+use crate::subscribers::subscriber::EventSubscriber;
+
+/// Snapshot counters which are totals since the daemon started.
+#[derive(Default, Clone, Copy)]
+struct SnapshotCounters {
+    re_download_bytes: u64,
+    re_upload_bytes: u64,
+    http_download_bytes: u64,
+}
+
+impl SnapshotCounters {
+    fn new(snapshot: &buck2_data::Snapshot) -> Self {
+        SnapshotCounters {
+            re_download_bytes: snapshot.re_download_bytes,
+            re_upload_bytes: snapshot.re_upload_bytes,
+            http_download_bytes: snapshot.http_download_bytes,
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct ResourceSummary {
+    /// Execution time of the local commands, by category of their action.
+    local_command_time: BTreeMap<String, Duration>,
+    re_queue_time: Duration,
+    local_actions: u64,
+    remote_actions: u64,
+    cached_actions: u64,
+    remote_dep_file_cached_actions: u64,
+    load_time: Duration,
+    analysis_time: Duration,
+    bxl_time: Duration,
+    peak_daemon_rss: Option<u64>,
+    first_counters: Option<SnapshotCounters>,
+    last_counters: Option<SnapshotCounters>,
+}
+
+fn duration(d: &Option<prost_types::Duration>) -> Duration {
+    d.as_ref()
+        .and_then(|d| Duration::try_from(d.clone()).ok())
+        .unwrap_or_default()
+}
+
+impl ResourceSummary {
+    pub(crate) fn handle_event(&mut self, event: &BuckEvent) {
+        match event.data() {
+            buck2_data::buck_event::Data::SpanEnd(end) => match &end.data {
+                Some(buck2_data::span_end_event::Data::ActionExecution(action)) => {
+                    self.handle_action_execution_end(action)
+                }
+                Some(buck2_data::span_end_event::Data::Load(_)) => {
+                    self.load_time += duration(&end.duration)
+                }
+                Some(buck2_data::span_end_event::Data::Analysis(_)) => {
+                    self.analysis_time += duration(&end.duration)
+                }
+                Some(buck2_data::span_end_event::Data::BxlExecution(_)) => {
+                    self.bxl_time += duration(&end.duration)
+                }
+                _ => {}
+            },
+            buck2_data::buck_event::Data::Instant(instant) => {
+                if let Some(buck2_data::instant_event::Data::Snapshot(snapshot)) = &instant.data {
+                    self.peak_daemon_rss = Some(
+                        self.peak_daemon_rss
+                            .unwrap_or(0)
+                            .max(snapshot.buck2_max_rss),
+                    );
+                    let counters = SnapshotCounters::new(snapshot);
+                    self.first_counters.get_or_insert(counters);
+                    self.last_counters = Some(counters);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_action_execution_end(&mut self, action: &buck2_data::ActionExecutionEnd) {
+        use buck2_data::command_execution_kind::Command;
+
+        let category = action
+            .name
+            .as_ref()
+            .map_or("unknown", |n| n.category.as_str());
+        for command in &action.commands {
+            let Some(details) = &command.details else {
+                continue;
+            };
+            let metadata = details.metadata.as_ref();
+            match details
+                .command_kind
+                .as_ref()
+                .and_then(|k| k.command.as_ref())
+            {
+                Some(
+                    Command::LocalCommand(_)
+                    | Command::OmittedLocalCommand(_)
+                    | Command::WorkerCommand(_)
+                    | Command::WorkerInitCommand(_),
+                ) => {
+                    *self
+                        .local_command_time
+                        .entry(category.to_owned())
+                        .or_default() +=
+                        metadata.map_or(Duration::ZERO, |m| duration(&m.execution_time));
+                }
+                Some(Command::RemoteCommand(remote)) => {
+                    self.re_queue_time += duration(&remote.queue_time);
+                }
+                None => {}
+            }
+        }
+
+        match last_command_execution_kind::get_last_command_execution_kind(action) {
+            LastCommandExecutionKind::Local | LastCommandExecutionKind::LocalWorker => {
+                self.local_actions += 1
+            }
+            LastCommandExecutionKind::Remote => self.remote_actions += 1,
+            LastCommandExecutionKind::Cached => self.cached_actions += 1,
+            LastCommandExecutionKind::RemoteDepFileCached => {
+                self.remote_dep_file_cached_actions += 1
+            }
+            LastCommandExecutionKind::NoCommand => {}
+        }
+    }
+
+    fn counters_delta(&self) -> Option<SnapshotCounters> {
+        let (first, last) = (self.first_counters?, self.last_counters?);
+        Some(SnapshotCounters {
+            re_download_bytes: last
+                .re_download_bytes
+                .saturating_sub(first.re_download_bytes),
+            re_upload_bytes: last.re_upload_bytes.saturating_sub(first.re_upload_bytes),
+            http_download_bytes: last
+                .http_download_bytes
+                .saturating_sub(first.http_download_bytes),
+        })
+    }
+
+    pub(crate) fn local_command_time_ms_by_category(
+        &self,
+    ) -> std::collections::HashMap<String, u64> {
+        self.local_command_time
+            .iter()
+            .map(|(category, time)| (category.clone(), time.as_millis() as u64))
+            .collect()
+    }
+
+    pub(crate) fn re_queue_time(&self) -> Duration {
+        self.re_queue_time
+    }
+
+    pub(crate) fn starlark_time(&self) -> Duration {
+        self.load_time + self.analysis_time + self.bxl_time
+    }
+}
+
+impl fmt::Display for ResourceSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let secs = |d: Duration| fmt_duration(d, 1.0);
+
+        writeln!(f, "Resource usage:")?;
+        if self.local_command_time.is_empty() {
+            writeln!(f, "  Local command time: none")?;
+        } else {
+            let total: Duration = self.local_command_time.values().sum();
+            writeln!(f, "  Local command time: {}", secs(total))?;
+            let mut by_time: Vec<_> = self.local_command_time.iter().collect();
+            by_time.sort_by(|a, b| b.1.cmp(a.1));
+            let width = by_time.iter().map(|(c, _)| c.len()).max().unwrap_or(0);
+            for (category, time) in by_time {
+                writeln!(f, "    {:width$}  {}", category, secs(*time), width = width)?;
+            }
+        }
+        writeln!(
+            f,
+            "  Remote execution queue time: {}",
+            secs(self.re_queue_time)
+        )?;
+
+        let cached = self.cached_actions + self.remote_dep_file_cached_actions;
+        let total = cached + self.local_actions + self.remote_actions;
+        if total == 0 {
+            writeln!(f, "  Cache hit rate: no actions ran")?;
+        } else {
+            let rate = total_cache_hit_rate(
+                self.local_actions,
+                self.remote_actions,
+                self.cached_actions,
+                self.remote_dep_file_cached_actions,
+            );
+            writeln!(
+                f,
+                "  Cache hit rate: {:.0}% ({} of {} actions)",
+                rate * 100.0,
+                cached,
+                total
+            )?;
+        }
+
+        if let Some(rss) = self.peak_daemon_rss {
+            writeln!(f, "  Peak daemon RSS: {}", HumanizedBytes::new(rss))?;
+        }
+        if let Some(delta) = self.counters_delta() {
+            writeln!(
+                f,
+                "  Remote execution: {} downloaded, {} uploaded",
+                HumanizedBytes::new(delta.re_download_bytes),
+                HumanizedBytes::new(delta.re_upload_bytes)
+            )?;
+            writeln!(
+                f,
+                "  HTTP: {} downloaded",
+                HumanizedBytes::new(delta.http_download_bytes)
+            )?;
+        }
+        write!(
+            f,
+            "  Starlark evaluation time: {} (loading {}, analysis {}, BXL {})",
+            secs(self.starlark_time()),
+            secs(self.load_time),
+            secs(self.analysis_time),
+            secs(self.bxl_time)
+        )
+    }
+}
+
+/// Prints the resource summary to stderr when the command finishes.
+#[derive(Default)]
+pub(crate) struct ResourceSummaryPrinter {
+    summary: ResourceSummary,
+}
+
+#[async_trait]
+impl EventSubscriber for ResourceSummaryPrinter {
+    async fn handle_events(&mut self, events: &[Arc<BuckEvent>]) -> anyhow::Result<()> {
+        for event in events {
+            self.summary.handle_event(event);
+        }
+        Ok(())
+    }
+
+    async fn exit(&mut self) -> anyhow::Result<()> {
+        let mut summary = String::new();
+        write!(summary, "{}", self.summary)?;
+        crate::eprintln!("{}", summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use buck2_wrapper_common::invocation_id::TraceId;
+
+    use super::*;
+
+    fn prost_duration(secs: i64) -> Option<prost_types::Duration> {
+        Some(prost_types::Duration {
+            seconds: secs,
+            nanos: 0,
+        })
+    }
+
+    fn span_end(data: buck2_data::span_end_event::Data, secs: i64) -> BuckEvent {
+        BuckEvent::new(
+            SystemTime::now(),
+            TraceId::new(),
+            None,
+            None,
+            buck2_data::SpanEndEvent {
+                duration: prost_duration(secs),
+                data: Some(data),
+                ..Default::default()
+            }
+            .into(),
+        )
+    }
+
+    fn action(category: &str, command: buck2_data::command_execution_kind::Command) -> BuckEvent {
+        span_end(
+            buck2_data::ActionExecutionEnd {
+                name: Some(buck2_data::ActionName {
+                    category: category.to_owned(),
+                    identifier: String::new(),
+                }),
+                commands: vec![buck2_data::CommandExecution {
+                    details: Some(buck2_data::CommandExecutionDetails {
+                        command_kind: Some(buck2_data::CommandExecutionKind {
+                            command: Some(command),
+                        }),
+                        metadata: Some(buck2_data::CommandExecutionMetadata {
+                            execution_time: prost_duration(2),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }
+            .into(),
+            3,
+        )
+    }
+
+    #[test]
+    fn test_summary() {
+        use buck2_data::command_execution_kind::Command;
+
+        let mut summary = ResourceSummary::default();
+        for event in [
+            action("cxx_compile", Command::LocalCommand(Default::default())),
+            action("cxx_compile", Command::LocalCommand(Default::default())),
+            action("cxx_link", Command::WorkerCommand(Default::default())),
+            action(
+                "cxx_compile",
+                Command::RemoteCommand(buck2_data::RemoteCommand {
+                    cache_hit: true,
+                    ..Default::default()
+                }),
+            ),
+            action(
+                "cxx_compile",
+                Command::RemoteCommand(buck2_data::RemoteCommand {
+                    queue_time: prost_duration(4),
+                    ..Default::default()
+                }),
+            ),
+            span_end(buck2_data::LoadBuildFileEnd::default().into(), 1),
+            span_end(buck2_data::AnalysisEnd::default().into(), 2),
+        ] {
+            summary.handle_event(&event);
+        }
+
+        assert_eq!(
+            std::collections::HashMap::from([
+                ("cxx_compile".to_owned(), 4000),
+                ("cxx_link".to_owned(), 2000)
+            ]),
+            summary.local_command_time_ms_by_category()
+        );
+        assert_eq!(Duration::from_secs(4), summary.re_queue_time());
+        assert_eq!(Duration::from_secs(3), summary.starlark_time());
+        assert!(
+            summary
+                .to_string()
+                .contains("Cache hit rate: 20% (1 of 5 actions)")
+        );
+    }
+}
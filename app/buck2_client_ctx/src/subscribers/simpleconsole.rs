@@ -64,6 +64,23 @@ fn with_timestamps(message: &str) -> String {
     s
 }
 
+/// The lines of streamed action output, prefixed with the action they come from.
+pub(crate) fn format_action_output(output: &buck2_data::ActionOutput) -> Vec<String> {
+    let mut action = output.owner.clone();
+    if let Some(name) = &output.name {
+        write!(action, " {}", name.category).unwrap();
+        if !name.identifier.is_empty() {
+            write!(action, " {}", name.identifier).unwrap();
+        }
+    }
+    output
+        .stdout
+        .lines()
+        .chain(output.stderr.lines())
+        .map(|line| format!("[{}] {}", action, line))
+        .collect()
+}
+
 // Echoes a message to stderr, along with a timestamp.
 macro_rules! echo {
     () => {
@@ -440,6 +457,17 @@ where
         Ok(())
     }
 
+    async fn handle_action_output(
+        &mut self,
+        output: &buck2_data::ActionOutput,
+    ) -> anyhow::Result<()> {
+        for line in format_action_output(output) {
+            echo!("{}", line)?;
+        }
+        self.notify_printed();
+        Ok(())
+    }
+
     async fn handle_test_discovery(
         &mut self,
         test_info: &buck2_data::TestDiscovery,
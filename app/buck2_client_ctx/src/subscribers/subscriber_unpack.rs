@@ -137,6 +137,9 @@ pub trait UnpackingEventSubscriber: Send {
             buck2_data::instant_event::Data::ActionError(error) => {
                 self.handle_action_error(error).await
             }
+            buck2_data::instant_event::Data::ActionOutput(output) => {
+                self.handle_action_output(output).await
+            }
             _ => Ok(()),
         }
     }
@@ -229,6 +232,13 @@ pub trait UnpackingEventSubscriber: Send {
     async fn handle_action_error(&mut self, _error: &buck2_data::ActionError)
     -> anyhow::Result<()>;
 
+    async fn handle_action_output(
+        &mut self,
+        _output: &buck2_data::ActionOutput,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+
     fn as_error_observer(&self) -> Option<&dyn ErrorObserver> {
         None
     }
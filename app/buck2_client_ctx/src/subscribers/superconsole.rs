@@ -27,6 +27,7 @@ use buck2_event_observer::what_ran::command_to_string;
 use buck2_event_observer::what_ran::worker_command_as_fallback_to_string;
 use buck2_event_observer::what_ran::WhatRanOptions;
 use buck2_event_observer::what_ran::WhatRanOptionsRegex;
+use buck2_events::errors::error_report_code_line;
 use buck2_events::BuckEvent;
 use buck2_wrapper_common::invocation_id::TraceId;
 use dupe::Dupe;
@@ -45,6 +46,7 @@ use superconsole::Lines;
 use superconsole::Span;
 pub(crate) use superconsole::SuperConsole;
 
+use crate::subscribers::simpleconsole::format_action_output;
 use crate::subscribers::simpleconsole::SimpleConsole;
 use crate::subscribers::subscriber::Tick;
 use crate::subscribers::subscriber_unpack::UnpackingEventSubscriber;
@@ -83,6 +85,8 @@ pub struct StatefulSuperConsole {
     state: SuperConsoleState,
     super_console: Option<SuperConsole>,
     verbosity: Verbosity,
+    /// Toggled from the console to show stderr and full commands for all actions.
+    verbose_action_output: bool,
 }
 
 #[derive(Copy, Clone, Dupe, Debug)]
@@ -289,6 +293,7 @@ impl StatefulSuperConsole {
             state: SuperConsoleState::new(replay_speed, trace_id, verbosity, expect_spans, config)?,
             super_console: Some(super_console),
             verbosity,
+            verbose_action_output: false,
         })
     }
 
@@ -321,6 +326,9 @@ impl StatefulSuperConsole {
                 lines
                     .0
                     .extend(Lines::from_multiline_string(&e.message, style).0);
+                lines
+                    .0
+                    .extend(Lines::from_multiline_string(&error_report_code_line(e), style).0);
             }
         }
         lines
@@ -353,6 +361,15 @@ impl SuperConsoleState {
 }
 
 impl StatefulSuperConsole {
+    /// The verbosity to use when rendering the output of actions.
+    fn action_output_verbosity(&self) -> Verbosity {
+        if self.verbose_action_output {
+            self.verbosity.with_action_output()
+        } else {
+            self.verbosity
+        }
+    }
+
     async fn toggle(
         &mut self,
         what: &str,
@@ -507,6 +524,11 @@ impl UnpackingEventSubscriber for StatefulSuperConsole {
         } else if c == 'c' {
             self.toggle("Commands", 'c', |s| &mut s.state.config.enable_commands)
                 .await?;
+        } else if c == 'v' {
+            self.toggle("Verbose action output", 'v', |s| {
+                &mut s.verbose_action_output
+            })
+            .await?;
         } else if c == '+' {
             self.state.config.max_lines = self.state.config.max_lines.saturating_add(1);
         } else if c == '-' {
@@ -521,6 +543,9 @@ impl UnpackingEventSubscriber for StatefulSuperConsole {
                 `r` = toggle detailed RE\n\
                 `i` = toggle I/O counters\n\
                 `p` = display target configurations\n\
+                `v` = toggle verbose action output\n\
+                `s` = pause/resume starting new actions\n\
+                `t` = dump daemon and Starlark stacks to the event log\n\
                 `+` = show more lines\n\
                 `-` = show fewer lines\n\
                 `h` = show this help",
@@ -628,6 +653,7 @@ impl UnpackingEventSubscriber for StatefulSuperConsole {
         action: &buck2_data::ActionExecutionEnd,
         event: &BuckEvent,
     ) -> anyhow::Result<()> {
+        let verbosity = self.action_output_verbosity();
         let super_console = match &mut self.super_console {
             Some(super_console) => super_console,
             None => {
@@ -645,7 +671,7 @@ impl UnpackingEventSubscriber for StatefulSuperConsole {
             return Ok(());
         }
 
-        if let Some(stderr) = display::success_stderr(action, self.verbosity)? {
+        if let Some(stderr) = display::success_stderr(action, verbosity)? {
             let mut lines = vec![];
             let display_platform = self.state.config.display_platform;
             let action_id = StyledContent::new(
@@ -672,7 +698,24 @@ impl UnpackingEventSubscriber for StatefulSuperConsole {
         Ok(())
     }
 
+    async fn handle_action_output(
+        &mut self,
+        output: &buck2_data::ActionOutput,
+    ) -> anyhow::Result<()> {
+        match &mut self.super_console {
+            Some(super_console) => {
+                super_console.emit(Lines::from_multiline_string(
+                    &format_action_output(output).join("\n"),
+                    ContentStyle::default(),
+                ));
+                Ok(())
+            }
+            None => self.state.simple_console.handle_action_output(output).await,
+        }
+    }
+
     async fn handle_action_error(&mut self, error: &buck2_data::ActionError) -> anyhow::Result<()> {
+        let verbosity = self.action_output_verbosity();
         let super_console = match &mut self.super_console {
             Some(super_console) => super_console,
             None => {
@@ -709,7 +752,7 @@ impl UnpackingEventSubscriber for StatefulSuperConsole {
         )]));
 
         if let Some(command) = command {
-            lines_for_command_details(&command, self.verbosity, &mut lines);
+            lines_for_command_details(&command, verbosity, &mut lines);
         }
 
         super_console.emit(Lines(lines));
@@ -10,6 +10,9 @@
 use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
 use buck2_core::fs::paths::file_name::FileName;
 
+/// Printed by the daemon to its stderr, followed by the path of the crash report, when it panics.
+pub const DAEMON_CRASH_REPORT_PREFIX: &str = "Buck2 daemon crash report written to ";
+
 /// `~/.buck/buckd/repo-path` directory.
 #[derive(Debug, Clone, derive_more::Display)]
 #[display(fmt = "{}", path.display())]
@@ -0,0 +1,144 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! A dedicated thread pool to compute the digests of files.
+//!
+//! Cold builds hash millions of files. On Tokio's blocking pool, hashing would compete with all
+//! the other blocking work (e.g. reading build files, or the console's I/O), and the pool would grow
+//! to hundreds of threads reading files at once. Hashing runs on a fixed number of threads instead,
+//! set by `buck2.digest_threads`.
+//!
+//! Each job reads its file with regular blocking reads: there is no io_uring, nor batching of reads
+//! across files.
+
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use dupe::Dupe;
+use once_cell::sync::OnceCell;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+#[derive(Debug, buck2_error::Error)]
+enum DigestPoolError {
+    #[error("Digest computation did not complete (internal error)")]
+    Dropped,
+    #[error(
+        "Digest pool already started with {actual} threads, `buck2.digest_threads = {requested}` is ignored (internal error)"
+    )]
+    AlreadyStarted { requested: usize, actual: usize },
+}
+
+struct DigestPool {
+    sender: Mutex<mpsc::Sender<Job>>,
+    threads: usize,
+}
+
+static POOL: OnceCell<DigestPool> = OnceCell::new();
+
+impl DigestPool {
+    fn new(threads: usize) -> DigestPool {
+        let threads = threads.max(1);
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for i in 0..threads {
+            let receiver = receiver.dupe();
+            std::thread::Builder::new()
+                .name(format!("buck2-digest-{}", i))
+                .spawn(move || {
+                    loop {
+                        // The lock is released once a job is received, before running it.
+                        let job = receiver.lock().unwrap().recv();
+                        match job {
+                            Ok(job) => job(),
+                            Err(mpsc::RecvError) => break,
+                        }
+                    }
+                })
+                .expect("Failed to spawn digest thread");
+        }
+        DigestPool {
+            sender: Mutex::new(sender),
+            threads,
+        }
+    }
+
+    fn default_threads() -> usize {
+        std::thread::available_parallelism().map_or(4, |n| n.get())
+    }
+
+    fn get() -> &'static DigestPool {
+        POOL.get_or_init(|| DigestPool::new(Self::default_threads()))
+    }
+}
+
+/// Create the pool with `threads` threads, or one per CPU if `None`. Otherwise, the first digest
+/// creates the pool with one thread per CPU, so this must be called before, i.e. when the daemon
+/// starts. Returns an error if the pool was already created with a different number of threads.
+pub fn init_digest_pool(threads: Option<usize>) -> anyhow::Result<()> {
+    let requested = threads.unwrap_or_else(DigestPool::default_threads).max(1);
+    let pool = POOL.get_or_init(|| DigestPool::new(requested));
+    if pool.threads != requested {
+        return Err(DigestPoolError::AlreadyStarted {
+            requested,
+            actual: pool.threads,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Run `f`, which hashes files, on the digest pool.
+pub async fn spawn_digest<R, F>(f: F) -> anyhow::Result<R>
+where
+    F: FnOnce() -> anyhow::Result<R> + Send + 'static,
+    R: Send + 'static,
+{
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let job: Job = Box::new(move || {
+        let _ignored = tx.send(f());
+    });
+    // The threads never exit while the pool exists, so this doesn't fail.
+    DigestPool::get()
+        .sender
+        .lock()
+        .unwrap()
+        .send(job)
+        .map_err(|_| DigestPoolError::Dropped)?;
+    rx.await.map_err(|_| DigestPoolError::Dropped)?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_spawn_digest() -> anyhow::Result<()> {
+        let thread_name = spawn_digest(|| Ok(std::thread::current().name().map(str::to_owned)))
+            .await?
+            .unwrap();
+        assert!(thread_name.starts_with("buck2-digest-"), "{}", thread_name);
+
+        assert!(
+            spawn_digest::<(), _>(|| Err(anyhow::anyhow!("error")))
+                .await
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_init_after_start() {
+        let threads = DigestPool::get().threads;
+        assert!(init_digest_pool(None).is_ok());
+        assert!(init_digest_pool(Some(threads)).is_ok());
+        assert!(init_digest_pool(Some(threads + 1)).is_err());
+    }
+}
@@ -212,7 +212,7 @@ impl FileDigest {
     /// Get the digest from disk. You should usually prefer `from_file`
     /// which also uses faster methods of getting the SHA1 if it can.
     pub fn from_file_disk(file: &AbsPath, config: FileDigestConfig) -> anyhow::Result<Self> {
-        let f = File::open(file.as_maybe_relativized())?;
+        let f = File::open(file.as_os_path())?;
         FileDigest::from_reader(f, config.as_cas_digest_config())
     }
 }
@@ -20,3 +20,22 @@ pub struct GlobalCfgOptions {
     pub target_platform: Option<TargetLabel>,
     pub cli_modifiers: Arc<Vec<String>>,
 }
+
+impl GlobalCfgOptions {
+    /// These options with `modifiers` applied after the `--modifier` ones.
+    pub fn with_extra_modifiers(&self, modifiers: &[String]) -> GlobalCfgOptions {
+        if modifiers.is_empty() {
+            return self.dupe();
+        }
+        GlobalCfgOptions {
+            target_platform: self.target_platform.dupe(),
+            cli_modifiers: Arc::new(
+                self.cli_modifiers
+                    .iter()
+                    .chain(modifiers)
+                    .cloned()
+                    .collect(),
+            ),
+        }
+    }
+}
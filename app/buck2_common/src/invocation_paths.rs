@@ -109,6 +109,14 @@ impl InvocationPaths {
             .join(ForwardRelativePath::unchecked_new("build_count"))
     }
 
+    /// When each soft error category was last hit, see `buck2_core::error::SoftErrorConfig`.
+    pub fn soft_error_history_path(&self) -> AbsNormPathBuf {
+        self.buck_out_path()
+            .join(ForwardRelativePath::unchecked_new(
+                "soft_error_history.json",
+            ))
+    }
+
     pub fn dice_dump_dir(&self) -> AbsNormPathBuf {
         self.buck_out_path()
             .join(ForwardRelativePath::unchecked_new("dice_dump"))
@@ -148,6 +156,17 @@ impl InvocationPaths {
             .join(self.materializer_state_dir_name())
     }
 
+    /// Subdirectory of `cache_dir` storing the digests of source files across daemons
+    pub fn source_digest_cache_path(&self) -> AbsNormPathBuf {
+        self.cache_dir_path()
+            .join(self.source_digest_cache_dir_name())
+    }
+
+    /// Subdirectory of `cache_dir` storing the evaluated `.bzl` modules across daemons
+    pub fn bzl_cache_path(&self) -> AbsNormPathBuf {
+        self.cache_dir_path().join(self.bzl_cache_dir_name())
+    }
+
     /// This is used by the forkserver to write the miniperf wrapper binary (if used), as well as
     /// temporary files used by miniperf. We put this in buck-out because that directory gets
     /// allowlisted for execution (because we write lots of tools there).
@@ -160,8 +179,20 @@ impl InvocationPaths {
         FileName::unchecked_new("materializer_state")
     }
 
+    pub fn source_digest_cache_dir_name(&self) -> &FileName {
+        FileName::unchecked_new("source_digests")
+    }
+
+    pub fn bzl_cache_dir_name(&self) -> &FileName {
+        FileName::unchecked_new("bzl")
+    }
+
     pub fn valid_cache_dirs(&self) -> Vec<&FileName> {
-        vec![self.materializer_state_dir_name()]
+        vec![
+            self.materializer_state_dir_name(),
+            self.source_digest_cache_dir_name(),
+            self.bzl_cache_dir_name(),
+        ]
     }
 }
 
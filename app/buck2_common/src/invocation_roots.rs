@@ -133,3 +133,9 @@ pub(crate) fn home_buck_dir() -> anyhow::Result<&'static AbsNormPath> {
 
     Ok(&Lazy::force(&DIR).as_ref().map_err(dupe::Dupe::dupe)?)
 }
+
+/// `~/.buck/downloads`, the cache of downloaded files shared by all the checkouts of a user (see
+/// `http.download_cache`).
+pub fn home_buck_downloads_dir() -> anyhow::Result<AbsNormPathBuf> {
+    Ok(home_buck_dir()?.join(FileName::new("downloads")?))
+}
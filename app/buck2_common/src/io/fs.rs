@@ -29,6 +29,7 @@ use once_cell::sync::Lazy;
 use tokio::sync::Semaphore;
 
 use crate::cas_digest::CasDigestConfig;
+use crate::digest_pool::spawn_digest;
 use crate::external_symlink::ExternalSymlink;
 use crate::file_ops::FileDigest;
 use crate::file_ops::FileDigestConfig;
@@ -38,11 +39,13 @@ use crate::file_ops::RawPathMetadata;
 use crate::file_ops::RawSymlink;
 use crate::file_ops::TrackedFileDigest;
 use crate::io::IoProvider;
+use crate::source_digest_cache::SourceDigestCache;
 
 #[derive(Clone, Dupe, Allocative)]
 pub struct FsIoProvider {
     fs: ProjectRoot,
     cas_digest_config: CasDigestConfig,
+    source_digest_cache: Option<Arc<SourceDigestCache>>,
 }
 
 impl FsIoProvider {
@@ -50,9 +53,17 @@ impl FsIoProvider {
         Self {
             fs,
             cas_digest_config,
+            source_digest_cache: None,
         }
     }
 
+    /// Reuse the digests of `source_digest_cache` for files which didn't change, and record the
+    /// digests computed in it.
+    pub fn with_source_digest_cache(mut self, source_digest_cache: Arc<SourceDigestCache>) -> Self {
+        self.source_digest_cache = Some(source_digest_cache);
+        self
+    }
+
     pub fn cas_digest_config(&self) -> CasDigestConfig {
         self.cas_digest_config
     }
@@ -78,13 +89,13 @@ impl FsIoProvider {
         let fs = self.fs.dupe();
         let path = path.into_forward_relative_path_buf();
         let file_digest_config = FileDigestConfig::source(self.cas_digest_config);
-        tokio::task::spawn_blocking(move || {
+        spawn_digest(move || {
             Ok(
                 read_unchecked(fs.root(), path, file_digest_config, options)?
                     .map(ProjectRelativePathBuf::from),
             )
         })
-        .await?
+        .await
     }
 }
 
@@ -150,18 +161,26 @@ impl IoProvider for FsIoProvider {
         &self,
         path: ProjectRelativePathBuf,
     ) -> anyhow::Result<Option<RawPathMetadata<ProjectRelativePathBuf>>> {
-        let fs = self.fs.dupe();
-        let path = path.into_forward_relative_path_buf();
-        let file_digest_config = FileDigestConfig::source(self.cas_digest_config);
+        let cache = match &self.source_digest_cache {
+            Some(cache) => cache,
+            None => return self.read_path_metadata_uncached(path).await,
+        };
 
-        tokio::task::spawn_blocking(move || {
-            let meta = read_path_metadata(fs.root(), &path, file_digest_config)?.map(
-                |raw_meta_or_redirection| raw_meta_or_redirection.map(ProjectRelativePathBuf::from),
-            );
+        let generation = cache.generation();
+        let cached = cache.get(&path);
+        if let Some(cached) = &cached {
+            if !cache.should_verify() {
+                return Ok(Some(RawPathMetadata::File(cached.dupe())));
+            }
+        }
 
-            Ok(meta)
-        })
-        .await?
+        let meta = self.read_path_metadata_uncached(path.clone()).await?;
+        let file_meta = match &meta {
+            Some(RawPathMetadata::File(file_meta)) => Some(file_meta),
+            _ => None,
+        };
+        cache.computed(path, generation, cached, file_meta);
+        Ok(meta)
     }
 
     async fn settle(&self) -> anyhow::Result<()> {
@@ -185,6 +204,27 @@ impl IoProvider for FsIoProvider {
     }
 }
 
+impl FsIoProvider {
+    async fn read_path_metadata_uncached(
+        &self,
+        path: ProjectRelativePathBuf,
+    ) -> anyhow::Result<Option<RawPathMetadata<ProjectRelativePathBuf>>> {
+        let fs = self.fs.dupe();
+        let path = path.into_forward_relative_path_buf();
+        let file_digest_config = FileDigestConfig::source(self.cas_digest_config);
+
+        // Hashes the file, if `path` is one.
+        spawn_digest(move || {
+            let meta = read_path_metadata(fs.root(), &path, file_digest_config)?.map(
+                |raw_meta_or_redirection| raw_meta_or_redirection.map(ProjectRelativePathBuf::from),
+            );
+
+            Ok(meta)
+        })
+        .await
+    }
+}
+
 /// A path and the corresponding absolute path.
 struct PathAndAbsPath {
     path: ForwardRelativePathBuf,
@@ -383,13 +423,16 @@ fn is_executable(_meta: &std::fs::Metadata) -> bool {
 
 #[cfg(all(test, unix))]
 mod tests {
+    use std::collections::HashMap;
     use std::os::unix;
 
     use assert_matches::assert_matches;
+    use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
     use buck2_core::fs::paths::abs_path::AbsPath;
     use tempfile::TempDir;
 
     use super::*;
+    use crate::source_digest_cache::SourceDigestCacheClock;
 
     #[test]
     fn test_read_not_symlink() -> anyhow::Result<()> {
@@ -542,4 +585,47 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_read_path_metadata_from_source_digest_cache() -> anyhow::Result<()> {
+        let t = TempDir::new()?;
+        let fs = ProjectRoot::new_unchecked(AbsNormPathBuf::new(t.path().to_owned())?);
+        fs_util::write(fs.root().join(ForwardRelativePath::new("x")?), "xx")?;
+        let config = CasDigestConfig::testing_default();
+        let x = ProjectRelativePathBuf::unchecked_new("x".to_owned());
+        let stale = FileMetadata {
+            digest: TrackedFileDigest::from_content(b"yy", config),
+            is_executable: false,
+        };
+
+        for verify_percentage in [0, 100] {
+            let cache = Arc::new(SourceDigestCache::open(
+                &fs.root().join(ForwardRelativePath::new(&format!(
+                    "cache{}",
+                    verify_percentage
+                ))?),
+                HashMap::new(),
+                config,
+                verify_percentage,
+            )?);
+            cache.synced(SourceDigestCacheClock {
+                clock: "c:1".to_owned(),
+                mergebase: None,
+            });
+            cache.computed(x.clone(), cache.generation(), None, Some(&stale));
+
+            let io = FsIoProvider::new(fs.dupe(), config).with_source_digest_cache(cache.dupe());
+            let meta = io.read_path_metadata_if_exists(x.clone()).await?;
+            if verify_percentage == 0 {
+                // Served by the cache, without hashing the file.
+                assert_eq!(Some(RawPathMetadata::File(stale.dupe())), meta);
+                assert_eq!(Some(stale.dupe()), cache.get(&x));
+            } else {
+                assert_matches!(meta, Some(RawPathMetadata::File(meta)) if meta != stale);
+                assert_eq!(None, cache.get(&x));
+            }
+        }
+
+        Ok(())
+    }
 }
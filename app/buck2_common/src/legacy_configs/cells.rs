@@ -28,10 +28,14 @@ use buck2_core::fs::project_rel_path::ProjectRelativePath;
 use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
 use gazebo::prelude::*;
 
+use crate::legacy_configs::external_cells::check_in_repositories;
+use crate::legacy_configs::external_cells::parse_external_cells;
 use crate::legacy_configs::init::DaemonStartupConfig;
 use crate::legacy_configs::path::BuckConfigFile;
 use crate::legacy_configs::path::DEFAULT_BUCK_CONFIG_FILES;
 use crate::legacy_configs::push_all_files_from_a_directory;
+use crate::legacy_configs::schema::ConfigSchema;
+use crate::legacy_configs::snapshot::ConfigSnapshot;
 use crate::legacy_configs::BuckConfigParseOptions;
 use crate::legacy_configs::CellResolutionState;
 use crate::legacy_configs::ConfigParserFileOps;
@@ -86,6 +90,7 @@ impl BuckConfigBasedCells {
             file_ops,
             &[],
             ProjectRelativePath::empty(),
+            None,
             opts,
         )?;
 
@@ -132,7 +137,28 @@ impl BuckConfigBasedCells {
         let opts = BuckConfigParseOptions {
             follow_includes: true,
         };
-        Self::parse_with_file_ops_and_options(project_fs, file_ops, config_args, cwd, opts)
+        Self::parse_with_file_ops_and_options(project_fs, file_ops, config_args, cwd, None, opts)
+    }
+
+    /// Like `parse_with_config_args`, but uses the configs of the cells recorded in `snapshot`
+    /// instead of reading their buckconfig files. `config_args` still apply on top.
+    pub fn parse_with_snapshot(
+        project_fs: &ProjectRoot,
+        snapshot: &ConfigSnapshot,
+        config_args: &[LegacyConfigCmdArg],
+        cwd: &ProjectRelativePath,
+    ) -> anyhow::Result<Self> {
+        let opts = BuckConfigParseOptions {
+            follow_includes: true,
+        };
+        Self::parse_with_file_ops_and_options(
+            project_fs,
+            &mut DefaultConfigParserFileOps {},
+            config_args,
+            cwd,
+            Some(snapshot),
+            opts,
+        )
     }
 
     fn parse_with_file_ops_and_options(
@@ -140,6 +166,7 @@ impl BuckConfigBasedCells {
         file_ops: &mut dyn ConfigParserFileOps,
         config_args: &[LegacyConfigCmdArg],
         cwd: &ProjectRelativePath,
+        snapshot: Option<&ConfigSnapshot>,
         options: BuckConfigParseOptions,
     ) -> anyhow::Result<Self> {
         // Tracing file ops to record config file accesses on command invocation.
@@ -198,36 +225,74 @@ impl BuckConfigBasedCells {
                 continue;
             }
 
-            let buckconfig_paths = get_buckconfig_paths_for_cell(&path, project_fs)?;
-
-            let existing_configs: Vec<MainConfigFile> = buckconfig_paths
-                .into_iter()
-                .filter(|main_config_file| file_ops.file_exists(&main_config_file.path))
-                .collect();
-
-            // Must contains a buckconfig owned by project, otherwise no cell can be found.
-            // This also check if existing_configs is empty
-            let has_project_owned_config = existing_configs
-                .iter()
-                .any(|main_config_file| main_config_file.owned_by_project);
-
-            let config = if has_project_owned_config {
-                LegacyBuckConfig::parse_with_file_ops_with_includes(
-                    existing_configs.as_slice(),
-                    &mut file_ops,
-                    &processed_config_args,
-                    options.follow_includes,
-                )?
+            let config = if let Some(snapshot) = snapshot {
+                // Snapshots record the resolved configs, so there is nothing to read, and
+                // nothing to validate against a schema either.
+                snapshot.config(&path, project_fs, &mut file_ops, &processed_config_args)?
             } else {
-                LegacyBuckConfig::empty()
+                let buckconfig_paths = get_buckconfig_paths_for_cell(&path, project_fs)?;
+
+                let existing_configs: Vec<MainConfigFile> = buckconfig_paths
+                    .into_iter()
+                    .filter(|main_config_file| file_ops.file_exists(&main_config_file.path))
+                    .collect();
+
+                // Must contains a buckconfig owned by project, otherwise no cell can be found.
+                // This also check if existing_configs is empty
+                let has_project_owned_config = existing_configs
+                    .iter()
+                    .any(|main_config_file| main_config_file.owned_by_project);
+
+                let config = if has_project_owned_config {
+                    LegacyBuckConfig::parse_with_file_ops_with_includes(
+                        existing_configs.as_slice(),
+                        &mut file_ops,
+                        &processed_config_args,
+                        options.follow_includes,
+                    )?
+                } else {
+                    LegacyBuckConfig::empty()
+                };
+
+                // Partial parses don't follow includes, so they would only report a part of the
+                // mismatches, and duplicate the warnings of the full parse.
+                if options.follow_includes {
+                    if let Some(schema) =
+                        ConfigSchema::load(&config, &path, project_fs, &mut file_ops)?
+                    {
+                        for warning in schema.validate(&config)? {
+                            tracing::warn!("{}", warning);
+                        }
+                    }
+                }
+
+                config
             };
 
             let is_root = path.is_repo_root();
 
+            // External cells are only declared in the root cell, and live where they are fetched.
+            let external_cells: HashMap<String, ProjectRelativePathBuf> = if is_root {
+                let external_cells = parse_external_cells(&config)?;
+                check_in_repositories(&config, &external_cells)?;
+                external_cells
+                    .into_iter()
+                    .map(|cell| {
+                        let path = cell.path();
+                        (cell.alias, path)
+                    })
+                    .collect()
+            } else {
+                HashMap::new()
+            };
+
             let repositories = config.get_section("repositories");
             if let Some(repositories) = repositories {
                 for (alias, alias_path) in repositories.iter() {
-                    let alias_path = CellRootPathBuf::new(path
+                    let alias_path = if let Some(external_path) = external_cells.get(alias) {
+                        CellRootPathBuf::new(external_path.clone())
+                    } else {
+                        CellRootPathBuf::new(path
                         .join_normalized(RelativePath::new(alias_path.as_str()))
                         .with_context(|| {
                             format!(
@@ -236,7 +301,8 @@ impl BuckConfigBasedCells {
                                 alias,
                                 path
                             )
-                        })?);
+                        })?)
+                    };
                     let alias = NonEmptyCellAlias::new(alias.to_owned())?;
                     if is_root {
                         root_aliases.insert(alias.clone(), alias_path.clone());
@@ -528,6 +594,44 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_external_cells() -> anyhow::Result<()> {
+        let mut file_ops = TestConfigParserFileOps::new(&[(
+            "/.buckconfig",
+            indoc!(
+                r#"
+                        [repositories]
+                            root = .
+                            prelude = prelude/
+                        [external_cells]
+                            prelude = git
+                        [external_cell_prelude]
+                            git_origin = https://example.com/prelude.git
+                            commit_hash = 0123456789abcdef0123456789abcdef01234567
+                    "#
+            ),
+        )])?;
+
+        let project_fs = create_project_filesystem();
+        let cells = BuckConfigBasedCells::parse_with_file_ops(
+            &project_fs,
+            &mut file_ops,
+            &[],
+            ProjectRelativePath::empty(),
+        )?;
+
+        assert_eq!(
+            "buck-out/external_cells/git/0123456789abcdef0123456789abcdef01234567",
+            cells
+                .cell_resolver
+                .get(CellName::testing_new("prelude"))?
+                .path()
+                .as_str()
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn test_multi_cell_with_config_file() -> anyhow::Result<()> {
         let mut file_ops = TestConfigParserFileOps::new(&[
@@ -847,4 +951,61 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_config_schema() -> anyhow::Result<()> {
+        let files = |jobs: &str| {
+            vec![
+                (
+                    "/.buckconfig",
+                    indoc!(
+                        r#"
+                            [repositories]
+                                root = .
+                                other = other/
+                        "#
+                    )
+                    .to_owned(),
+                ),
+                (
+                    "/other/.buckconfig",
+                    format!(
+                        "[repositories]\n  other = .\n[buckconfig]\n  schema = schema.json\n[cxx]\n  jobs = {}\n",
+                        jobs
+                    ),
+                ),
+                (
+                    "/other/schema.json",
+                    r#"{"sections": {"cxx": {"jobs": {"type": "int"}}}}"#.to_owned(),
+                ),
+            ]
+        };
+        let parse = |jobs: &str| {
+            let files = files(jobs);
+            let mut file_ops = TestConfigParserFileOps::new(
+                &files
+                    .iter()
+                    .map(|(path, contents)| (*path, contents.as_str()))
+                    .collect::<Vec<_>>(),
+            )?;
+            BuckConfigBasedCells::parse_with_file_ops(
+                &create_project_filesystem(),
+                &mut file_ops,
+                &[],
+                ProjectRelativePath::empty(),
+            )
+        };
+
+        let cells = parse("4")?;
+        assert!(
+            cells
+                .config_paths
+                .iter()
+                .any(|path| path.as_path().ends_with("other/schema.json"))
+        );
+
+        let error = format!("{:#}", parse("many").err().unwrap());
+        assert!(error.contains("`cxx.jobs = many`"), "{}", error);
+        Ok(())
+    }
 }
@@ -0,0 +1,235 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Cells whose sources are fetched from outside the repository, declared in the root buckconfig:
+//!
+//! ```ini
+//! [repositories]
+//!     prelude = prelude
+//! [external_cells]
+//!     prelude = git
+//! [external_cell_prelude]
+//!     git_origin = https://github.com/facebook/buck2-prelude.git
+//!     commit_hash = 0123456789abcdef0123456789abcdef01234567
+//! ```
+//!
+//! or, for an archive, `prelude = http_archive` with `url`, `sha256` and optionally
+//! `strip_prefix` keys.
+//!
+//! The path of an external cell in `[repositories]` is replaced by a directory determined by its
+//! revision or hash, where the daemon fetches it. Since the contents of these directories never
+//! change, they are shared between isolation dirs and never refetched.
+
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+
+use crate::legacy_configs::LegacyBuckConfig;
+
+/// Where the external cells are fetched, relative to the project root.
+pub const EXTERNAL_CELLS_DIR: &str = "buck-out/external_cells";
+
+const SECTION: &str = "external_cells";
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(user)]
+enum ExternalCellsError {
+    #[error("Unknown kind `{1}` for external cell `{0}`, expected `git` or `http_archive`")]
+    UnknownKind(String, String),
+    #[error("Missing `{1}` in section `[external_cell_{0}]` of the root buckconfig")]
+    MissingKey(String, &'static str),
+    #[error(
+        "`{1}` of external cell `{0}` must be {2} hexadecimal characters, got `{3}`. \
+        A full hash is required so that the fetched contents are reproducible"
+    )]
+    InvalidHash(String, &'static str, usize, String),
+    #[error("External cell `{0}` must also be declared in `[repositories]`")]
+    NotInRepositories(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExternalCellOrigin {
+    /// A commit of a git repository.
+    Git { origin: String, commit: String },
+    /// A `.tar.gz` or `.tar` archive, verified against its SHA256.
+    HttpArchive {
+        url: String,
+        sha256: String,
+        /// Directory of the archive to use as the cell root.
+        strip_prefix: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalCell {
+    /// Alias of the cell in `[repositories]`.
+    pub alias: String,
+    pub origin: ExternalCellOrigin,
+}
+
+impl ExternalCell {
+    /// Directory the cell is fetched to, relative to the project root.
+    pub fn path(&self) -> ProjectRelativePathBuf {
+        let (kind, id) = match &self.origin {
+            ExternalCellOrigin::Git { commit, .. } => ("git", commit),
+            ExternalCellOrigin::HttpArchive { sha256, .. } => ("http", sha256),
+        };
+        ProjectRelativePathBuf::unchecked_new(format!("{}/{}/{}", EXTERNAL_CELLS_DIR, kind, id))
+    }
+}
+
+fn check_hash(alias: &str, key: &'static str, len: usize, value: &str) -> anyhow::Result<String> {
+    if value.len() == len && value.bytes().all(|c| c.is_ascii_hexdigit()) {
+        Ok(value.to_ascii_lowercase())
+    } else {
+        Err(ExternalCellsError::InvalidHash(alias.to_owned(), key, len, value.to_owned()).into())
+    }
+}
+
+/// The external cells declared in the root buckconfig.
+pub fn parse_external_cells(config: &LegacyBuckConfig) -> anyhow::Result<Vec<ExternalCell>> {
+    let Some(section) = config.get_section(SECTION) else {
+        return Ok(Vec::new());
+    };
+    let mut cells = Vec::new();
+    for (alias, kind) in section.iter() {
+        let cell_section = format!("external_cell_{}", alias);
+        let get = |key: &'static str| {
+            config
+                .get(&cell_section, key)
+                .map(|v| v.to_owned())
+                .ok_or_else(|| ExternalCellsError::MissingKey(alias.to_owned(), key))
+        };
+        let origin = match kind.as_str() {
+            "git" => ExternalCellOrigin::Git {
+                origin: get("git_origin")?,
+                commit: check_hash(alias, "commit_hash", 40, &get("commit_hash")?)?,
+            },
+            "http_archive" => ExternalCellOrigin::HttpArchive {
+                url: get("url")?,
+                sha256: check_hash(alias, "sha256", 64, &get("sha256")?)?,
+                strip_prefix: get("strip_prefix").ok(),
+            },
+            _ => {
+                return Err(ExternalCellsError::UnknownKind(
+                    alias.to_owned(),
+                    kind.as_str().to_owned(),
+                )
+                .into());
+            }
+        };
+        cells.push(ExternalCell {
+            alias: alias.to_owned(),
+            origin,
+        });
+    }
+    Ok(cells)
+}
+
+/// Check that all the external cells are declared in `[repositories]`.
+pub(crate) fn check_in_repositories(
+    config: &LegacyBuckConfig,
+    cells: &[ExternalCell],
+) -> anyhow::Result<()> {
+    for cell in cells {
+        if config.get("repositories", &cell.alias).is_none() {
+            return Err(ExternalCellsError::NotInRepositories(cell.alias.clone()).into());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::legacy_configs::testing::parse;
+
+    #[test]
+    fn test_parse_external_cells() -> anyhow::Result<()> {
+        let config = parse(
+            &[(
+                "/config",
+                indoc!(
+                    r#"
+                        [repositories]
+                            prelude = prelude
+                            rules = rules
+                        [external_cells]
+                            prelude = git
+                            rules = http_archive
+                        [external_cell_prelude]
+                            git_origin = https://example.com/prelude.git
+                            commit_hash = 0123456789ABCDEF0123456789abcdef01234567
+                        [external_cell_rules]
+                            url = https://example.com/rules.tar.gz
+                            sha256 = 0000000000000000000000000000000000000000000000000000000000000000
+                            strip_prefix = rules-1.0
+                    "#
+                ),
+            )],
+            "/config",
+        )?;
+        let cells = parse_external_cells(&config)?;
+        check_in_repositories(&config, &cells)?;
+        assert_eq!(
+            vec![
+                ExternalCell {
+                    alias: "prelude".to_owned(),
+                    origin: ExternalCellOrigin::Git {
+                        origin: "https://example.com/prelude.git".to_owned(),
+                        commit: "0123456789abcdef0123456789abcdef01234567".to_owned(),
+                    },
+                },
+                ExternalCell {
+                    alias: "rules".to_owned(),
+                    origin: ExternalCellOrigin::HttpArchive {
+                        url: "https://example.com/rules.tar.gz".to_owned(),
+                        sha256: "0".repeat(64),
+                        strip_prefix: Some("rules-1.0".to_owned()),
+                    },
+                },
+            ],
+            cells
+        );
+        assert_eq!(
+            "buck-out/external_cells/git/0123456789abcdef0123456789abcdef01234567",
+            cells[0].path().as_str()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_external_cells_errors() -> anyhow::Result<()> {
+        let config = parse(
+            &[(
+                "/config",
+                indoc!(
+                    r#"
+                        [external_cells]
+                            prelude = git
+                        [external_cell_prelude]
+                            git_origin = https://example.com/prelude.git
+                            commit_hash = main
+                    "#
+                ),
+            )],
+            "/config",
+        )?;
+        let err = format!("{:#}", parse_external_cells(&config).unwrap_err());
+        assert!(err.contains("must be 40 hexadecimal characters"), "{}", err);
+
+        let config = parse(
+            &[("/config", "[external_cells]\n  prelude = svn\n")],
+            "/config",
+        )?;
+        let err = format!("{:#}", parse_external_cells(&config).unwrap_err());
+        assert!(err.contains("Unknown kind `svn`"), "{}", err);
+        Ok(())
+    }
+}
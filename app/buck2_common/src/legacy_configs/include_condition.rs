@@ -0,0 +1,181 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Conditions of buckconfig includes, like
+//!
+//! ```ini
+//! <file:windows.bcfg if="host_os=windows">
+//! <?file:ci.bcfg if="env.CI, host_arch!=aarch64">
+//! ```
+//!
+//! A condition is a comma-separated list of terms which must all hold. Terms compare the host
+//! (`host_os` and `host_arch`, with the values of Rust's `std::env::consts::{OS, ARCH}`) or an
+//! environment variable (`env.NAME`) with `=` or `!=`, or check whether an environment variable
+//! is set (`env.NAME`) or not (`!env.NAME`).
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(user)]
+enum IncludeConditionError {
+    #[error(
+        "Invalid buckconfig include condition term `{0}`, expected `host_os`, `host_arch` or \
+        `env.<NAME>` compared with `=` or `!=`, or `env.<NAME>` or `!env.<NAME>`"
+    )]
+    InvalidTerm(String),
+}
+
+#[derive(Debug, PartialEq)]
+enum Subject {
+    HostOs,
+    HostArch,
+    Env(String),
+}
+
+#[derive(Debug, PartialEq)]
+enum Term {
+    Equals(Subject, String),
+    NotEquals(Subject, String),
+    EnvSet(String),
+    EnvUnset(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct IncludeCondition(Vec<Term>);
+
+/// What conditions are evaluated against.
+pub(crate) struct IncludeConditionHost {
+    pub(crate) os: &'static str,
+    pub(crate) arch: &'static str,
+    pub(crate) env: fn(&str) -> Option<String>,
+}
+
+impl IncludeConditionHost {
+    /// The host running Buck2, and the environment of the daemon.
+    pub(crate) fn current() -> Self {
+        IncludeConditionHost {
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            env: |name| std::env::var(name).ok(),
+        }
+    }
+}
+
+fn parse_subject(subject: &str) -> Option<Subject> {
+    match subject {
+        "host_os" => Some(Subject::HostOs),
+        "host_arch" => Some(Subject::HostArch),
+        _ => parse_env(subject).map(|name| Subject::Env(name.to_owned())),
+    }
+}
+
+fn parse_env(subject: &str) -> Option<&str> {
+    subject
+        .strip_prefix("env.")
+        .filter(|name| !name.is_empty() && !name.contains(['=', '!']))
+}
+
+impl IncludeCondition {
+    /// Split the condition off an include directive: `path if="condition"`.
+    pub(crate) fn split(include: &str) -> anyhow::Result<(&str, Option<IncludeCondition>)> {
+        match include
+            .strip_suffix('"')
+            .and_then(|s| s.rsplit_once(" if=\""))
+        {
+            Some((path, condition)) => Ok((path.trim_end(), Some(Self::parse(condition)?))),
+            None => Ok((include, None)),
+        }
+    }
+
+    pub(crate) fn parse(condition: &str) -> anyhow::Result<IncludeCondition> {
+        condition
+            .split(',')
+            .map(str::trim)
+            .map(|term| {
+                let invalid = || IncludeConditionError::InvalidTerm(term.to_owned());
+                let term = if let Some((subject, value)) = term.split_once("!=") {
+                    Term::NotEquals(
+                        parse_subject(subject.trim()).ok_or_else(invalid)?,
+                        value.trim().to_owned(),
+                    )
+                } else if let Some((subject, value)) = term.split_once('=') {
+                    Term::Equals(
+                        parse_subject(subject.trim()).ok_or_else(invalid)?,
+                        value.trim().to_owned(),
+                    )
+                } else if let Some(subject) = term.strip_prefix('!') {
+                    Term::EnvUnset(parse_env(subject.trim()).ok_or_else(invalid)?.to_owned())
+                } else {
+                    Term::EnvSet(parse_env(term).ok_or_else(invalid)?.to_owned())
+                };
+                Ok(term)
+            })
+            .collect::<anyhow::Result<_>>()
+            .map(IncludeCondition)
+    }
+
+    pub(crate) fn holds(&self, host: &IncludeConditionHost) -> bool {
+        let value = |subject: &Subject| match subject {
+            Subject::HostOs => Some(host.os.to_owned()),
+            Subject::HostArch => Some(host.arch.to_owned()),
+            Subject::Env(name) => (host.env)(name),
+        };
+        self.0.iter().all(|term| match term {
+            Term::Equals(subject, expected) => value(subject).as_ref() == Some(expected),
+            Term::NotEquals(subject, expected) => value(subject).as_ref() != Some(expected),
+            Term::EnvSet(name) => (host.env)(name).is_some(),
+            Term::EnvUnset(name) => (host.env)(name).is_none(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::legacy_configs::include_condition::IncludeCondition;
+    use crate::legacy_configs::include_condition::IncludeConditionHost;
+
+    fn holds(condition: &str) -> anyhow::Result<bool> {
+        let host = IncludeConditionHost {
+            os: "windows",
+            arch: "x86_64",
+            env: |name| (name == "CI").then(|| "true".to_owned()),
+        };
+        Ok(IncludeCondition::parse(condition)?.holds(&host))
+    }
+
+    #[test]
+    fn test_holds() -> anyhow::Result<()> {
+        assert!(holds("host_os=windows")?);
+        assert!(!holds("host_os=linux")?);
+        assert!(holds("host_os!=linux, host_arch=x86_64")?);
+        assert!(!holds("host_os=windows, host_arch=aarch64")?);
+        assert!(holds("env.CI")?);
+        assert!(holds("env.CI=true")?);
+        assert!(!holds("!env.CI")?);
+        assert!(holds("!env.HOME")?);
+        assert!(holds("env.HOME!=/root")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(IncludeCondition::parse("os=windows").is_err());
+        assert!(IncludeCondition::parse("host_os").is_err());
+        assert!(IncludeCondition::parse("env.").is_err());
+        assert!(IncludeCondition::parse("").is_err());
+    }
+
+    #[test]
+    fn test_split() -> anyhow::Result<()> {
+        assert_eq!(("a/b.bcfg", None), IncludeCondition::split("a/b.bcfg")?);
+        let (path, condition) = IncludeCondition::split("a b.bcfg if=\"host_os=linux\"")?;
+        assert_eq!("a b.bcfg", path);
+        assert_eq!(Some(IncludeCondition::parse("host_os=linux")?), condition);
+        assert!(IncludeCondition::split("a.bcfg if=\"linux\"").is_err());
+        Ok(())
+    }
+}
@@ -53,6 +53,12 @@ pub struct HttpConfig {
     write_timeout_ms: Option<u64>,
     pub http2: bool,
     pub max_redirects: Option<usize>,
+    /// Refuse all http requests, e.g. to check that a build doesn't need the network.
+    pub offline: bool,
+    /// Only allow http requests to these hosts and their subdomains.
+    pub allowed_hosts: Option<Vec<String>>,
+    /// Look up and store downloaded files in `~/.buck/downloads`.
+    pub download_cache: bool,
 }
 
 impl HttpConfig {
@@ -62,6 +68,9 @@ impl HttpConfig {
         let write_timeout_ms = config.parse("http", "write_timeout_ms")?;
         let max_redirects = config.parse("http", "max_redirects")?;
         let http2 = config.parse("http", "http2")?.unwrap_or(true);
+        let offline = config.parse("http", "offline")?.unwrap_or_default();
+        let allowed_hosts = config.parse_list("http", "allowed_hosts")?;
+        let download_cache = config.parse("http", "download_cache")?.unwrap_or_default();
 
         Ok(Self {
             connect_timeout_ms,
@@ -69,6 +78,9 @@ impl HttpConfig {
             write_timeout_ms,
             max_redirects,
             http2,
+            offline,
+            allowed_hosts,
+            download_cache,
         })
     }
 
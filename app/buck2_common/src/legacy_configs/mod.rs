@@ -12,8 +12,12 @@
 
 pub mod cells;
 pub mod dice;
+pub mod external_cells;
+pub(crate) mod include_condition;
 pub mod init;
 pub(crate) mod path;
+pub(crate) mod schema;
+pub mod snapshot;
 pub mod view;
 
 use std::cell::OnceCell;
@@ -45,6 +49,8 @@ use regex::Regex;
 use starlark_map::sorted_map::SortedMap;
 
 use crate::legacy_configs::cells::BuckConfigBasedCells;
+use crate::legacy_configs::include_condition::IncludeCondition;
+use crate::legacy_configs::include_condition::IncludeConditionHost;
 use crate::legacy_configs::view::LegacyBuckConfigView;
 use crate::legacy_configs::view::LegacyBuckConfigsView;
 use crate::target_aliases::BuckConfigTargetAliasResolver;
@@ -502,11 +508,13 @@ struct LegacyConfigParser<'a> {
 }
 
 /// Matches file include directives. `optional` indicates whether it's an
-/// optional include, `include` is the path.  Examples:
+/// optional include, `include` is the path, optionally followed by a condition
+/// (see `include_condition`).  Examples:
 ///   <file:/some/absolute>
 ///   <?file:/optional/absolute>
 ///   <file:relative/to/current>
 ///   <file:../../doesnt/need/to/be/forward/relative>
+///   <file:windows.bcfg if="host_os=windows">
 static FILE_INCLUDE: Lazy<Regex> =
     Lazy::new(|| Regex::new("<(?P<optional>\\?)?file:(?P<include>..*)>").unwrap());
 
@@ -644,6 +652,27 @@ impl<'a> LegacyConfigParser<'a> {
         Ok(())
     }
 
+    fn apply_config_args(
+        &mut self,
+        config_args: &[ResolvedLegacyConfigArg],
+        cell_path: &AbsNormPath,
+        follow_includes: bool,
+    ) -> anyhow::Result<()> {
+        for config_arg in config_args {
+            match config_arg {
+                ResolvedLegacyConfigArg::Flag(config_value) => {
+                    self.apply_config_arg(config_value, cell_path.to_buf())?
+                }
+                ResolvedLegacyConfigArg::File(file_path) => self.parse_file(
+                    file_path,
+                    Some(Location::CommandLineArgument),
+                    follow_includes,
+                )?,
+            };
+        }
+        Ok(())
+    }
+
     fn parse_file_on_stack(
         &mut self,
         path: &AbsNormPath,
@@ -729,7 +758,13 @@ impl<'a> LegacyConfigParser<'a> {
                 );
             } else if let Some(m) = FILE_INCLUDE.captures(&line) {
                 if parse_includes {
-                    let include = m.name("include").unwrap().as_str();
+                    let (include, condition) =
+                        IncludeCondition::split(m.name("include").unwrap().as_str())?;
+                    if let Some(condition) = condition {
+                        if !condition.holds(&IncludeConditionHost::current()) {
+                            continue;
+                        }
+                    }
                     let include = if cfg!(windows) && include.contains(':') {
                         // On Windows absolute includes look like /C:/foo/bar.
                         // For compatibility with Python parser we need to support this.
@@ -932,7 +967,7 @@ impl ConfigResolver {
     }
 
     fn regex() -> &'static Regex {
-        static RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\$\(config ([^)]*)\)").unwrap());
+        static RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\$\((config|env) ([^)]*)\)").unwrap());
         &RE
     }
 
@@ -983,9 +1018,22 @@ impl ConfigResolver {
 
             let captures = re.captures(m.as_str()).unwrap();
 
-            let config_key = captures.get(1).unwrap().as_str();
+            let argument = captures.get(2).unwrap().as_str();
+
+            if captures.get(1).unwrap().as_str() == "env" {
+                // `$(env NAME)` or `$(env NAME:default)`, with the environment of the daemon.
+                let (name, default) = match argument.split_once(':') {
+                    Some((name, default)) => (name, default),
+                    None => (argument, ""),
+                };
+                match std::env::var(name.trim()) {
+                    Ok(value) => resolved.push_str(&value),
+                    Err(_) => resolved.push_str(default),
+                }
+                continue;
+            }
 
-            let config_section_and_key = parse_config_section_and_key(config_key, None)?;
+            let config_section_and_key = parse_config_section_and_key(argument, None)?;
 
             resolved.push_str(self.resolve_item(
                 resolved_items,
@@ -1204,22 +1252,9 @@ impl LegacyBuckConfig {
                 };
             }
         }
-        if cell_path.is_none() {
-            panic!("Could not find cell path");
-        }
+        let cell_path = cell_path.unwrap_or_else(|| panic!("Could not find cell path"));
 
-        for config_arg in config_args {
-            match config_arg {
-                ResolvedLegacyConfigArg::Flag(config_value) => {
-                    parser.apply_config_arg(config_value, cell_path.unwrap().to_buf())?
-                }
-                ResolvedLegacyConfigArg::File(file_path) => parser.parse_file(
-                    file_path,
-                    Some(Location::CommandLineArgument),
-                    follow_includes,
-                )?,
-            };
-        }
+        parser.apply_config_args(config_args, cell_path, follow_includes)?;
 
         parser.finish()
     }
@@ -1742,6 +1777,53 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_conditional_includes() -> anyhow::Result<()> {
+        let config = parse(
+            &[
+                ("/this_host", "[host]\nos = this\n"),
+                ("/other_host", "[host]\nos = other\n"),
+                (
+                    "/config",
+                    &format!(
+                        "<file:this_host if=\"host_os={}\">\n<file:other_host if=\"host_os!={}\">\n<file:missing if=\"env.BUCK2_TEST_UNSET_VARIABLE\">\n",
+                        std::env::consts::OS,
+                        std::env::consts::OS,
+                    )
+                    .as_str(),
+                ),
+            ],
+            "/config",
+        )?;
+        assert_config_value(&config, "host", "os", "this");
+        Ok(())
+    }
+
+    #[test]
+    fn test_env() -> anyhow::Result<()> {
+        std::env::set_var("BUCK2_TEST_CONFIG_ENV", "from_env");
+        let config = parse(
+            &[(
+                "/config",
+                indoc!(
+                    r#"
+                    [env]
+                        set = $(env BUCK2_TEST_CONFIG_ENV)
+                        set_with_default = $(env BUCK2_TEST_CONFIG_ENV:default)
+                        unset = <$(env BUCK2_TEST_UNSET_VARIABLE)>
+                        unset_with_default = $(env BUCK2_TEST_UNSET_VARIABLE:default value)
+                    "#
+                ),
+            )],
+            "/config",
+        )?;
+        assert_config_value(&config, "env", "set", "from_env");
+        assert_config_value(&config, "env", "set_with_default", "from_env");
+        assert_config_value(&config, "env", "unset", "<>");
+        assert_config_value(&config, "env", "unset_with_default", "default value");
+        Ok(())
+    }
+
     #[test]
     fn test_config_args_ordering() -> anyhow::Result<()> {
         let config_args = vec![
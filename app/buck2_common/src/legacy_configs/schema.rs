@@ -0,0 +1,312 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Schemas cells can ship for their buckconfig, so that typos and mistyped values don't go
+//! unnoticed. A cell points to its schema, relative to the cell root, with
+//!
+//! ```ini
+//! [buckconfig]
+//! schema = tools/buckconfig_schema.json
+//! ```
+//!
+//! and the schema lists the keys of the sections it covers:
+//!
+//! ```json
+//! {
+//!   "sections": {
+//!     "cxx": {
+//!       "compiler": {},
+//!       "use_lld": {"type": "bool"},
+//!       "mode": {"values": ["dev", "opt"]},
+//!       "old_flags": {"deprecated": "Use `cxx.flags` instead"}
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! Only the sections listed in the schema are checked: unknown keys and deprecated keys are
+//! warnings, and values which don't match their type are errors.
+
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use buck2_core::cells::cell_root_path::CellRootPath;
+use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
+use buck2_core::fs::project::ProjectRoot;
+use serde::Deserialize;
+
+use crate::legacy_configs::ConfigParserFileOps;
+use crate::legacy_configs::LegacyBuckConfig;
+
+const SECTION: &str = "buckconfig";
+const KEY: &str = "schema";
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(user)]
+enum ConfigSchemaError {
+    #[error("Invalid buckconfig schema `{0}`")]
+    InvalidSchema(String),
+    #[error("Buckconfig doesn't match its schema `{schema}`:\n{}", .mismatches.join("\n"))]
+    Mismatches {
+        schema: String,
+        mismatches: Vec<String>,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ConfigSchema {
+    #[serde(skip)]
+    path: String,
+    sections: BTreeMap<String, BTreeMap<String, KeySchema>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KeySchema {
+    #[serde(rename = "type", default)]
+    ty: ValueType,
+    /// The only values the key may have.
+    values: Option<Vec<String>>,
+    /// Why the key is deprecated, and what to use instead.
+    deprecated: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ValueType {
+    #[default]
+    String,
+    Bool,
+    Int,
+    Float,
+}
+
+impl ValueType {
+    fn matches(self, value: &str) -> bool {
+        match self {
+            ValueType::String => true,
+            ValueType::Bool => value.parse::<bool>().is_ok(),
+            ValueType::Int => value.parse::<i64>().is_ok(),
+            ValueType::Float => value.parse::<f64>().is_ok(),
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            ValueType::String => "a string",
+            ValueType::Bool => "`true` or `false`",
+            ValueType::Int => "an integer",
+            ValueType::Float => "a number",
+        }
+    }
+}
+
+impl ConfigSchema {
+    /// The schema named by the `buckconfig.schema` key of the config of the cell at `cell`.
+    pub(crate) fn load(
+        config: &LegacyBuckConfig,
+        cell: &CellRootPath,
+        project_fs: &ProjectRoot,
+        file_ops: &mut dyn ConfigParserFileOps,
+    ) -> anyhow::Result<Option<ConfigSchema>> {
+        let Some(path) = config.get(SECTION, KEY) else {
+            return Ok(None);
+        };
+        let path =
+            cell.as_project_relative_path()
+                .join(ForwardRelativePath::new(path).with_context(|| {
+                    format!("`{}.{}` must be a path relative to the cell", SECTION, KEY)
+                })?);
+        let contents = file_ops
+            .read_file_lines(&project_fs.resolve(&path))?
+            .collect::<Result<Vec<_>, _>>()?
+            .join("\n");
+        Self::parse(path.to_string(), &contents).map(Some)
+    }
+
+    fn parse(path: String, contents: &str) -> anyhow::Result<ConfigSchema> {
+        let mut schema: ConfigSchema = serde_json::from_str(contents)
+            .with_context(|| ConfigSchemaError::InvalidSchema(path.clone()))?;
+        schema.path = path;
+        Ok(schema)
+    }
+
+    /// Check `config` against the schema. Returns warnings for unknown and deprecated keys, and
+    /// fails if some values don't match their type.
+    pub(crate) fn validate(&self, config: &LegacyBuckConfig) -> anyhow::Result<Vec<String>> {
+        let mut warnings = Vec::new();
+        let mut mismatches = Vec::new();
+        for (section_name, keys) in &self.sections {
+            let Some(section) = config.get_section(section_name) else {
+                continue;
+            };
+            for (key, value) in section.iter() {
+                let Some(key_schema) = keys.get(key) else {
+                    warnings.push(format!(
+                        "Unknown buckconfig `{}.{}` {}{}",
+                        section_name,
+                        key,
+                        value.location(),
+                        did_you_mean(key, keys.keys()),
+                    ));
+                    continue;
+                };
+                if let Some(deprecated) = &key_schema.deprecated {
+                    warnings.push(format!(
+                        "Buckconfig `{}.{}` {} is deprecated: {}",
+                        section_name,
+                        key,
+                        value.location(),
+                        deprecated
+                    ));
+                }
+                // An empty value unsets the key.
+                let value_str = value.as_str();
+                if value_str.is_empty() {
+                    continue;
+                }
+                let expected = match &key_schema.values {
+                    Some(values) if !values.iter().any(|v| v == value_str) => Some(format!(
+                        "one of {}",
+                        values
+                            .iter()
+                            .map(|v| format!("`{}`", v))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )),
+                    _ if !key_schema.ty.matches(value_str) => {
+                        Some(key_schema.ty.description().to_owned())
+                    }
+                    _ => None,
+                };
+                if let Some(expected) = expected {
+                    mismatches.push(format!(
+                        "  `{}.{} = {}` {}: expected {}",
+                        section_name,
+                        key,
+                        value_str,
+                        value.location(),
+                        expected
+                    ));
+                }
+            }
+        }
+        if !mismatches.is_empty() {
+            return Err(ConfigSchemaError::Mismatches {
+                schema: self.path.clone(),
+                mismatches,
+            }
+            .into());
+        }
+        Ok(warnings)
+    }
+}
+
+fn did_you_mean<'a>(key: &str, known: impl IntoIterator<Item = &'a String>) -> String {
+    const MAX_LEVENSHTEIN_DISTANCE: usize = 2;
+    known
+        .into_iter()
+        .map(|k| (k, strsim::levenshtein(key, k)))
+        .filter(|(_, lev)| *lev <= MAX_LEVENSHTEIN_DISTANCE)
+        .min_by_key(|(_, lev)| *lev)
+        .map_or_else(String::new, |(k, _)| format!(", did you mean `{}`?", k))
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::legacy_configs::schema::ConfigSchema;
+    use crate::legacy_configs::testing::parse;
+
+    fn schema() -> anyhow::Result<ConfigSchema> {
+        ConfigSchema::parse(
+            "schema.json".to_owned(),
+            indoc!(
+                r#"
+                {
+                  "sections": {
+                    "cxx": {
+                      "compiler": {},
+                      "use_lld": {"type": "bool"},
+                      "jobs": {"type": "int"},
+                      "mode": {"values": ["dev", "opt"]},
+                      "old_flags": {"deprecated": "Use `cxx.flags` instead"}
+                    }
+                  }
+                }
+                "#
+            ),
+        )
+    }
+
+    #[test]
+    fn test_warnings() -> anyhow::Result<()> {
+        let config = parse(
+            &[(
+                "/config",
+                indoc!(
+                    r#"
+                    [cxx]
+                        compler = clang
+                        use_lld = true
+                        old_flags = -O2
+                        jobs =
+                    [other]
+                        anything = goes
+                    "#
+                ),
+            )],
+            "/config",
+        )?;
+        let warnings = schema()?.validate(&config)?;
+        assert_eq!(2, warnings.len(), "{:?}", warnings);
+        assert!(warnings[0].contains("`cxx.compler`"));
+        assert!(warnings[0].contains("did you mean `compiler`?"));
+        assert!(warnings[1].contains("`cxx.old_flags`"));
+        assert!(warnings[1].contains("Use `cxx.flags` instead"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_mismatches() -> anyhow::Result<()> {
+        let config = parse(
+            &[(
+                "/config",
+                indoc!(
+                    r#"
+                    [cxx]
+                        use_lld = yes
+                        jobs = 4
+                        mode = debug
+                    "#
+                ),
+            )],
+            "/config",
+        )?;
+        let error = format!("{:#}", schema()?.validate(&config).unwrap_err());
+        assert!(error.contains("`cxx.use_lld = yes`"), "{}", error);
+        assert!(!error.contains("cxx.jobs"), "{}", error);
+        assert!(error.contains("expected one of `dev`, `opt`"), "{}", error);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_schema() {
+        assert!(ConfigSchema::parse("schema.json".to_owned(), r#"{"sections": 1}"#).is_err());
+        assert!(
+            ConfigSchema::parse(
+                "schema.json".to_owned(),
+                r#"{"sections": {"cxx": {"jobs": {"type": "integer"}}}}"#
+            )
+            .is_err()
+        );
+    }
+}
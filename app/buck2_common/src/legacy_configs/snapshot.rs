@@ -0,0 +1,285 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Snapshots of the resolved configuration of all cells, written with `--config-snapshot` and
+//! read with `--replay-config`, so that the configuration of a build (e.g. on CI) can be
+//! reproduced elsewhere regardless of local files like `.buckconfig.local`:
+//!
+//! ```json
+//! {
+//!   "cells": {
+//!     "": {"cxx": {"compiler": "clang"}},
+//!     "prelude": {"buildfile": {"name": "BUCK"}}
+//!   }
+//! }
+//! ```
+//!
+//! Cells are keyed by their path relative to the project root, and values are recorded after
+//! `$(config)` and `$(env)` were resolved, so replaying doesn't depend on the environment either.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use anyhow::Context;
+use buck2_core::cells::cell_root_path::CellRootPath;
+use buck2_core::cells::CellResolver;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+use buck2_core::fs::project::ProjectRoot;
+use dupe::Dupe;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::legacy_configs::ConfigFile;
+use crate::legacy_configs::ConfigFileLocation;
+use crate::legacy_configs::ConfigParserFileOps;
+use crate::legacy_configs::ConfigValue;
+use crate::legacy_configs::LegacyBuckConfig;
+use crate::legacy_configs::LegacyBuckConfigs;
+use crate::legacy_configs::LegacyConfigParser;
+use crate::legacy_configs::Location;
+use crate::legacy_configs::ResolvedLegacyConfigArg;
+use crate::legacy_configs::ResolvedValue;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(user)]
+enum ConfigSnapshotError {
+    #[error("Invalid config snapshot `{0}`")]
+    InvalidSnapshot(String),
+    #[error(
+        "Config snapshot `{snapshot}` has no config for cell `{cell}`, \
+        it was probably recorded in a different repository"
+    )]
+    MissingCell { snapshot: String, cell: String },
+}
+
+type Sections = BTreeMap<String, BTreeMap<String, String>>;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigSnapshot {
+    #[serde(skip)]
+    path: String,
+    cells: BTreeMap<String, Sections>,
+}
+
+impl ConfigSnapshot {
+    /// Record the resolved values of `configs`.
+    pub fn new(cell_resolver: &CellResolver, configs: &LegacyBuckConfigs) -> anyhow::Result<Self> {
+        let mut cells = BTreeMap::new();
+        for (cell, config) in configs.iter() {
+            let sections = config
+                .all_sections()
+                .map(|(section, values)| {
+                    (
+                        section.clone(),
+                        values
+                            .iter()
+                            .map(|(key, value)| (key.to_owned(), value.as_str().to_owned()))
+                            .collect(),
+                    )
+                })
+                .collect();
+            cells.insert(
+                cell_resolver.get(cell)?.path().as_str().to_owned(),
+                sections,
+            );
+        }
+        Ok(ConfigSnapshot {
+            path: String::new(),
+            cells,
+        })
+    }
+
+    pub fn read(path: &AbsNormPath) -> anyhow::Result<Self> {
+        let contents = fs_util::read_to_string(path)?;
+        Self::parse(path.to_string(), &contents)
+    }
+
+    fn parse(path: String, contents: &str) -> anyhow::Result<Self> {
+        let mut snapshot: ConfigSnapshot = serde_json::from_str(contents)
+            .with_context(|| ConfigSnapshotError::InvalidSnapshot(path.clone()))?;
+        snapshot.path = path;
+        Ok(snapshot)
+    }
+
+    pub fn write(&self, path: &AbsNormPath) -> anyhow::Result<()> {
+        let mut contents = serde_json::to_vec_pretty(self)?;
+        contents.push(b'\n');
+        if let Some(parent) = path.parent() {
+            fs_util::create_dir_all(parent)?;
+        }
+        fs_util::write(path, contents)
+            .with_context(|| format!("Writing config snapshot `{}`", path))
+    }
+
+    /// The config of the cell at `cell`, with `config_args` applied on top of the recorded
+    /// values, like they are applied on top of the buckconfig files.
+    pub(crate) fn config(
+        &self,
+        cell: &CellRootPath,
+        project_fs: &ProjectRoot,
+        file_ops: &mut dyn ConfigParserFileOps,
+        config_args: &[ResolvedLegacyConfigArg],
+    ) -> anyhow::Result<LegacyBuckConfig> {
+        let sections =
+            self.cells
+                .get(cell.as_str())
+                .ok_or_else(|| ConfigSnapshotError::MissingCell {
+                    snapshot: self.path.clone(),
+                    cell: cell.as_str().to_owned(),
+                })?;
+        let source_file = Arc::new(ConfigFile {
+            id: self.path.clone(),
+            include_source: None,
+        });
+
+        let mut parser = LegacyConfigParser::new(file_ops);
+        for (section, values) in sections {
+            let section = parser.values.entry(section.clone()).or_default();
+            for (key, value) in values {
+                section.values.insert(
+                    key.clone(),
+                    ConfigValue {
+                        raw_value: value.clone(),
+                        // Recorded values are already resolved, and must not be resolved again.
+                        resolved_value: ResolvedValue::Resolved(value.clone()),
+                        source: Location::File(ConfigFileLocation {
+                            source_file: source_file.dupe(),
+                            line: 1,
+                        }),
+                    },
+                );
+            }
+        }
+        parser.apply_config_args(
+            config_args,
+            &project_fs.resolve(cell.as_project_relative_path()),
+            true,
+        )?;
+        parser.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_core::cells::cell_root_path::CellRootPath;
+    use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+    use buck2_core::fs::project::ProjectRoot;
+    use buck2_core::fs::project_rel_path::ProjectRelativePath;
+    use indoc::indoc;
+
+    use crate::legacy_configs::cells::BuckConfigBasedCells;
+    use crate::legacy_configs::snapshot::ConfigSnapshot;
+    use crate::legacy_configs::testing::TestConfigParserFileOps;
+    use crate::legacy_configs::tests::assert_config_value;
+    use crate::legacy_configs::LegacyBuckConfig;
+    use crate::legacy_configs::LegacyConfigCmdArg;
+
+    fn create_project_filesystem() -> ProjectRoot {
+        #[cfg(not(windows))]
+        let root_path = "/".to_owned();
+        #[cfg(windows)]
+        let root_path = "C:/".to_owned();
+        ProjectRoot::new_unchecked(AbsNormPathBuf::try_from(root_path).unwrap())
+    }
+
+    #[test]
+    fn test_round_trip() -> anyhow::Result<()> {
+        let mut file_ops = TestConfigParserFileOps::new(&[
+            (
+                "/.buckconfig",
+                indoc!(
+                    r#"
+                    [repositories]
+                        root = .
+                        other = other/
+                    [cxx]
+                        compiler = clang
+                        flags = $(config cxx.compiler) -O2
+                    "#
+                ),
+            ),
+            (
+                "/.buckconfig.local",
+                indoc!(
+                    r#"
+                    [cxx]
+                        compiler = gcc
+                    "#
+                ),
+            ),
+            (
+                "/other/.buckconfig",
+                indoc!(
+                    r#"
+                    [other]
+                        key = value
+                    "#
+                ),
+            ),
+        ])?;
+        let project_fs = create_project_filesystem();
+        let cells = BuckConfigBasedCells::parse_with_file_ops(
+            &project_fs,
+            &mut file_ops,
+            &[],
+            ProjectRelativePath::empty(),
+        )?;
+        let snapshot = ConfigSnapshot::new(&cells.cell_resolver, &cells.configs_by_name)?;
+
+        let contents = serde_json::to_string(&snapshot)?;
+        let snapshot = ConfigSnapshot::parse("snapshot.json".to_owned(), &contents)?;
+
+        // The buckconfig files have drifted since the snapshot was recorded.
+        let mut file_ops = TestConfigParserFileOps::new(&[(
+            "/.buckconfig",
+            indoc!(
+                r#"
+                [repositories]
+                    root = .
+                [cxx]
+                    compiler = msvc
+                "#
+            ),
+        )])?;
+        let root = CellRootPath::testing_new("");
+        let config = snapshot.config(root, &project_fs, &mut file_ops, &[])?;
+        assert_config_value(&config, "cxx", "compiler", "gcc");
+        assert_config_value(&config, "cxx", "flags", "gcc -O2");
+
+        let other = CellRootPath::testing_new("other");
+        let config = snapshot.config(other, &project_fs, &mut file_ops, &[])?;
+        assert_config_value(&config, "other", "key", "value");
+
+        let config_args = LegacyBuckConfig::process_config_args(
+            &[LegacyConfigCmdArg::flag(
+                "cxx.flags=$(config cxx.compiler) -O0",
+            )?],
+            None,
+            &mut file_ops,
+        )?;
+        let config = snapshot.config(root, &project_fs, &mut file_ops, &config_args)?;
+        assert_config_value(&config, "cxx", "flags", "gcc -O0");
+
+        let missing = CellRootPath::testing_new("missing");
+        assert!(
+            snapshot
+                .config(missing, &project_fs, &mut file_ops, &[])
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_snapshot() {
+        assert!(ConfigSnapshot::parse("snapshot.json".to_owned(), r#"{"cells": 1}"#).is_err());
+        assert!(ConfigSnapshot::parse("snapshot.json".to_owned(), r#"{"sections": {}}"#).is_err());
+    }
+}
@@ -28,6 +28,7 @@ pub mod client_utils;
 pub mod convert;
 pub mod daemon_dir;
 pub mod dice;
+pub mod digest_pool;
 pub mod events;
 pub mod external_symlink;
 pub mod file_ops;
@@ -44,10 +45,12 @@ pub mod legacy_configs;
 pub mod liveliness_observer;
 pub mod local_resource_state;
 pub mod memory;
+pub mod output_template;
 pub mod package_boundary;
 pub mod package_listing;
 pub mod pattern;
 pub mod scope;
+pub mod source_digest_cache;
 pub mod sqlite;
 pub mod target_aliases;
 pub mod temp_path;
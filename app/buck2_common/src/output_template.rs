@@ -0,0 +1,205 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Templates to print command results one line per item, e.g. `{label} {outputs[0]}`, for
+//! scripts which would otherwise parse output meant to be read by humans.
+//!
+//! A template is text with placeholders: `{name}` is replaced by the value of the variable
+//! `name`, `{name[N]}` by the element `N` of a list variable (or nothing if the list is shorter),
+//! and `{name}` with a list variable by its elements separated by spaces. `{{` and `}}` are
+//! literal braces.
+
+use std::fmt::Write;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(user)]
+enum OutputTemplateError {
+    #[error("Unknown variable `{0}` in output template, expected one of: {1}")]
+    UnknownVariable(String, String),
+    #[error("Variable `{0}` in output template is not a list and can't be indexed")]
+    NotAList(String),
+    #[error("Invalid index in `{{{0}}}` in output template")]
+    InvalidIndex(String),
+    #[error("Unterminated `{{` in output template, use `{{{{` for a literal brace")]
+    Unterminated,
+    #[error("Unmatched `}}` in output template, use `}}}}` for a literal brace")]
+    UnmatchedClose,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateVariableKind {
+    String,
+    List,
+}
+
+/// The value of a variable for one item.
+pub enum TemplateValue<'a> {
+    String(&'a str),
+    List(&'a [String]),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Variable { name: String, index: Option<usize> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputTemplate {
+    parts: Vec<Part>,
+}
+
+impl OutputTemplate {
+    /// Parse a template, which may only use the given variables.
+    pub fn parse(
+        template: &str,
+        variables: &[(&str, TemplateVariableKind)],
+    ) -> anyhow::Result<OutputTemplate> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '}' => return Err(OutputTemplateError::UnmatchedClose.into()),
+                '{' => {
+                    let mut placeholder = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => placeholder.push(c),
+                            None => return Err(OutputTemplateError::Unterminated.into()),
+                        }
+                    }
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(Self::parse_placeholder(&placeholder, variables)?);
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(OutputTemplate { parts })
+    }
+
+    fn parse_placeholder(
+        placeholder: &str,
+        variables: &[(&str, TemplateVariableKind)],
+    ) -> anyhow::Result<Part> {
+        let (name, index) = match placeholder.split_once('[') {
+            Some((name, index)) => {
+                let index = index
+                    .strip_suffix(']')
+                    .and_then(|i| i.trim().parse::<usize>().ok())
+                    .ok_or_else(|| OutputTemplateError::InvalidIndex(placeholder.to_owned()))?;
+                (name.trim(), Some(index))
+            }
+            None => (placeholder.trim(), None),
+        };
+        let kind = match variables.iter().find(|(n, _)| *n == name) {
+            Some((_, kind)) => *kind,
+            None => {
+                let known: Vec<_> = variables.iter().map(|(n, _)| format!("`{}`", n)).collect();
+                return Err(OutputTemplateError::UnknownVariable(
+                    name.to_owned(),
+                    known.join(", "),
+                )
+                .into());
+            }
+        };
+        if index.is_some() && kind != TemplateVariableKind::List {
+            return Err(OutputTemplateError::NotAList(name.to_owned()).into());
+        }
+        Ok(Part::Variable {
+            name: name.to_owned(),
+            index,
+        })
+    }
+
+    /// Render the template for one item, followed by a newline. `lookup` is only called with the
+    /// names of variables given to `parse`.
+    pub fn render<'a>(&self, out: &mut String, lookup: impl Fn(&str) -> TemplateValue<'a>) {
+        for part in &self.parts {
+            match part {
+                Part::Literal(s) => out.push_str(s),
+                Part::Variable { name, index } => match (lookup(name), index) {
+                    (TemplateValue::String(s), _) => out.push_str(s),
+                    (TemplateValue::List(l), Some(i)) => {
+                        if let Some(s) = l.get(*i) {
+                            out.push_str(s);
+                        }
+                    }
+                    (TemplateValue::List(l), None) => {
+                        for (i, s) in l.iter().enumerate() {
+                            if i != 0 {
+                                out.push(' ');
+                            }
+                            out.push_str(s);
+                        }
+                    }
+                },
+            }
+        }
+        writeln!(out).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::output_template::OutputTemplate;
+    use crate::output_template::TemplateValue;
+    use crate::output_template::TemplateVariableKind;
+
+    const VARIABLES: &[(&str, TemplateVariableKind)] = &[
+        ("label", TemplateVariableKind::String),
+        ("outputs", TemplateVariableKind::List),
+    ];
+
+    fn render(template: &str) -> anyhow::Result<String> {
+        let template = OutputTemplate::parse(template, VARIABLES)?;
+        let outputs = vec!["a.out".to_owned(), "b.out".to_owned()];
+        let mut out = String::new();
+        template.render(&mut out, |name| match name {
+            "label" => TemplateValue::String("root//:foo"),
+            _ => TemplateValue::List(&outputs),
+        });
+        Ok(out)
+    }
+
+    #[test]
+    fn test_render() -> anyhow::Result<()> {
+        assert_eq!("root//:foo a.out\n", render("{label} {outputs[0]}")?);
+        assert_eq!("root//:foo\tb.out\n", render("{ label }\t{outputs[1]}")?);
+        assert_eq!("[]\n", render("[{outputs[2]}]")?);
+        assert_eq!("a.out b.out\n", render("{outputs}")?);
+        assert_eq!("{label} root//:foo\n", render("{{label}} {label}")?);
+        assert_eq!("\n", render("")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(render("{name}").is_err());
+        assert!(render("{label[0]}").is_err());
+        assert!(render("{outputs[x]}").is_err());
+        assert!(render("{outputs[0}").is_err());
+        assert!(render("{label").is_err());
+        assert!(render("label}").is_err());
+    }
+}
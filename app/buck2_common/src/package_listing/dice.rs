@@ -113,4 +113,17 @@ impl DicePackageListingResolver<'_, '_> {
     ) -> anyhow::Result<PackageListing> {
         self.resolve(package).await.map_err(anyhow::Error::from)
     }
+
+    /// Compute the listing of a package without caching it on the DICE graph, e.g. to measure
+    /// how long it takes. The directory listings it is built from are still cached.
+    pub async fn resolve_package_listing_uncached(
+        &mut self,
+        package: PackageLabel,
+    ) -> anyhow::Result<PackageListing> {
+        let cell_resolver = self.0.get_cell_resolver().await?;
+        InterpreterPackageListingResolver::new(cell_resolver, self.0)
+            .resolve(package)
+            .await
+            .map_err(anyhow::Error::from)
+    }
 }
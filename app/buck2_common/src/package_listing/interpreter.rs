@@ -11,6 +11,9 @@ use async_trait::async_trait;
 use buck2_core::cells::cell_path::CellPath;
 use buck2_core::cells::cell_path::CellPathRef;
 use buck2_core::cells::CellResolver;
+use buck2_core::fs::case_collisions;
+use buck2_core::fs::case_collisions::find_case_collision;
+use buck2_core::fs::paths::file_name::FileName;
 use buck2_core::fs::paths::file_name::FileNameBuf;
 use buck2_core::package::package_relative_path::PackageRelativePath;
 use buck2_core::package::package_relative_path::PackageRelativePathBuf;
@@ -28,6 +31,7 @@ use starlark_map::sorted_vec::SortedVec;
 use crate::dice::file_ops::DiceFileOps;
 use crate::file_ops::FileOps;
 use crate::find_buildfile::find_buildfile;
+use crate::legacy_configs::dice::HasLegacyConfigs;
 use crate::package_listing::listing::PackageListing;
 use crate::package_listing::resolver::PackageListingResolver;
 
@@ -37,6 +41,11 @@ enum PackageListingError {
     NoBuildFile(CellPath, Vec<FileNameBuf>),
     #[error("Expected `{0}` to be within a package directory, but there was no buildfile in any parent directories. Expected one of `{}`", .1.join("`, `"))]
     NoContainingPackage(CellPath, Vec<FileNameBuf>),
+    #[error(
+        "Paths `{0}` and `{1}` differ only by case, so they are the same file on case-insensitive \
+        file systems (set `buck2.check_case_collisions = false` to allow this)"
+    )]
+    CaseCollision(CellPath, CellPath),
 }
 
 #[async_trait]
@@ -141,6 +150,7 @@ impl Directory {
         root: CellPathRef<'_>,
         path: &PackageRelativePath,
         is_root: bool,
+        check_case_collisions: bool,
     ) -> anyhow::Result<Option<Directory>> {
         let cell_path = root.join(path.as_forward_rel_path());
         let entries = DiceFileOps(ctx)
@@ -149,6 +159,17 @@ impl Directory {
             .user()?
             .included;
 
+        if check_case_collisions {
+            if let Some((a, b)) = find_case_collision(entries.iter().map(|e| e.file_name.as_str()))
+            {
+                return Err(PackageListingError::CaseCollision(
+                    cell_path.join(FileName::unchecked_new(a)),
+                    cell_path.join(FileName::unchecked_new(b)),
+                ))
+                .user();
+            }
+        }
+
         let buildfile = find_buildfile(buildfile_candidates, &entries);
 
         match (is_root, buildfile) {
@@ -177,8 +198,14 @@ impl Directory {
             }
         }
 
-        let (subdirs, subpackages) =
-            Self::gather_subdirs(ctx, buildfile_candidates, root, subdirs).await?;
+        let (subdirs, subpackages) = Self::gather_subdirs(
+            ctx,
+            buildfile_candidates,
+            root,
+            subdirs,
+            check_case_collisions,
+        )
+        .await?;
 
         let mut recursive_files_count = files.len();
         let mut recursive_dirs_count = subdirs.len();
@@ -206,6 +233,7 @@ impl Directory {
         buildfile_candidates: &'a [FileNameBuf],
         root: CellPathRef<'a>,
         subdirs: Vec<PackageRelativePathBuf>,
+        check_case_collisions: bool,
     ) -> BoxFuture<'a, anyhow::Result<(Vec<Directory>, Vec<ArcS<PackageRelativePath>>)>> {
         async move {
             let futs = ctx.compute_many(subdirs.into_iter().map(|path|
@@ -213,7 +241,7 @@ impl Directory {
                 #![with<'a>]
                 for <'x> |ctx: &'x mut DiceComputations<'a>| -> BoxFuture<'x, anyhow::Result<(PackageRelativePathBuf, Option<Directory>)>> {
                     async move {
-                        let res = Directory::gather(ctx, buildfile_candidates, root, &path, false).await?;
+                        let res = Directory::gather(ctx, buildfile_candidates, root, &path, false, check_case_collisions).await?;
                         Ok((path, res))
                     }.boxed()
                 }
@@ -280,12 +308,21 @@ async fn gather_package_listing_impl(
 ) -> anyhow::Result<PackageListing> {
     let cell_instance = cell_resolver.get(root.cell_name())?;
     let buildfile_candidates = cell_instance.buildfiles();
+    let check_case_collisions = ctx
+        .parse_legacy_config_property(
+            cell_resolver.root_cell(),
+            case_collisions::CONFIG_SECTION,
+            case_collisions::CONFIG_KEY,
+        )
+        .await?
+        .unwrap_or_else(case_collisions::enabled_by_default);
     Ok(Directory::gather(
         ctx,
         buildfile_candidates,
         root.as_cell_path(),
         PackageRelativePath::empty(),
         true,
+        check_case_collisions,
     )
     .await?
     .unwrap()
@@ -0,0 +1,624 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Digests of source files, kept on disk so that a new daemon can reuse the digests computed by
+//! the previous one instead of hashing every file it reads again.
+//!
+//! A digest is only valid as long as its file doesn't change, which the file watcher tells us. The
+//! cache stores the Watchman clock at which its entries were last known to be valid. A new daemon
+//! starts its Watchman query from that clock, so its first sync reports the changes made while no
+//! daemon was running: the cache drops the entries of the paths which changed (or all entries, on
+//! a fresh instance), and only serves digests once that happened (see
+//! [`SourceDigestCache::synced`]).
+//!
+//! To check this holds in practice, `verify_percentage` percent of the digests the cache would
+//! serve are computed anyway. A mismatch is reported with the soft error
+//! `source_digest_cache_mismatch`, and drops the whole cache.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use allocative::Allocative;
+use anyhow::Context;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_core::fs::paths::file_name::FileName;
+use buck2_core::fs::project_rel_path::ProjectRelativePath;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use buck2_core::soft_error;
+use dupe::Dupe;
+use parking_lot::Mutex;
+use rusqlite::Connection;
+
+use crate::cas_digest::CasDigestConfig;
+use crate::file_ops::FileDigest;
+use crate::file_ops::FileMetadata;
+use crate::file_ops::TrackedFileDigest;
+use crate::sqlite::KeyValueSqliteTable;
+
+/// Hand-maintained schema version for the source digest cache sqlite db. Bump it when making a
+/// breaking change to the schema.
+pub const DB_SCHEMA_VERSION: u64 = 1;
+
+const DIGESTS_TABLE_NAME: &str = "source_digests";
+const CLOCK_TABLE_NAME: &str = "clock";
+const CLOCK_KEY: &str = "clock";
+const MERGEBASE_KEY: &str = "mergebase";
+
+#[derive(Debug, buck2_error::Error)]
+enum SourceDigestCacheError {
+    #[error(
+        "The source digest cache has `{cached}` for `{path}`, but it is actually `{actual}`. \
+        The source digest cache is dropped."
+    )]
+    Mismatch {
+        path: ProjectRelativePathBuf,
+        cached: FileMetadata,
+        actual: String,
+    },
+    #[error("Expected versions {:?}. Found versions {:?} in sqlite db at {}", .expected, .found, .path)]
+    VersionMismatch {
+        expected: HashMap<String, String>,
+        found: HashMap<String, String>,
+        path: AbsNormPathBuf,
+    },
+}
+
+/// The Watchman clock (and mergebase, for SCM-aware queries) at which the entries of the cache were
+/// last known to be valid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceDigestCacheClock {
+    pub clock: String,
+    pub mergebase: Option<String>,
+}
+
+#[derive(Allocative)]
+enum PendingWrite {
+    Insert(ProjectRelativePathBuf, FileMetadata),
+    Remove(ProjectRelativePathBuf),
+    /// Remove the entries of all the paths under the directory.
+    RemoveDir(ProjectRelativePathBuf),
+    Clear,
+}
+
+#[derive(Allocative)]
+struct State {
+    /// The last clock up to which the changes reported by the file watcher were applied to the
+    /// entries. Nothing is served before the first one.
+    synced_clock: Option<SourceDigestCacheClock>,
+    /// Incremented whenever entries are invalidated, so that digests computed concurrently with
+    /// the invalidation are not inserted.
+    generation: u64,
+    entries: HashMap<ProjectRelativePathBuf, FileMetadata>,
+    /// Changes to `entries` which are not in the db yet, in order.
+    pending: Vec<PendingWrite>,
+}
+
+#[derive(Allocative)]
+pub struct SourceDigestCache {
+    state: Mutex<State>,
+    #[allocative(skip)]
+    db: Mutex<SourceDigestCacheDb>,
+    /// The clock written by the previous daemon.
+    saved_clock: Option<SourceDigestCacheClock>,
+    verify_percentage: u32,
+}
+
+impl SourceDigestCache {
+    const DB_FILENAME: &'static str = "db.sqlite";
+
+    /// Opens the cache in `dir`, reading the entries written by the previous daemon. If reading
+    /// them fails (e.g. they don't exist, or were written with different `versions`), the cache
+    /// starts empty, in a new db.
+    pub fn open(
+        dir: &AbsNormPath,
+        versions: HashMap<String, String>,
+        cas_digest_config: CasDigestConfig,
+        verify_percentage: u32,
+    ) -> anyhow::Result<Self> {
+        let db_path = dir.join(FileName::unchecked_new(Self::DB_FILENAME));
+
+        let loaded = SourceDigestCacheDb::load(&db_path, &versions, cas_digest_config);
+
+        let (db, saved_clock, entries) = match loaded {
+            Ok((db, clock, entries)) => (db, Some(clock), entries),
+            Err(e) => {
+                tracing::debug!("Not reusing the source digest cache: {:#}", e);
+                // sqlite can leave behind other files, so delete the whole directory.
+                if dir.exists() {
+                    fs_util::remove_dir_all(dir)?;
+                }
+                fs_util::create_dir_all(dir)?;
+                let db = SourceDigestCacheDb::open(&db_path)?;
+                db.create_all_tables()?;
+                db.versions_table.insert_all(versions)?;
+                (db, None, HashMap::new())
+            }
+        };
+
+        Ok(Self {
+            state: Mutex::new(State {
+                synced_clock: None,
+                generation: 0,
+                entries,
+                pending: Vec::new(),
+            }),
+            db: Mutex::new(db),
+            saved_clock,
+            verify_percentage,
+        })
+    }
+
+    /// The clock to start the file watcher from, so that its first sync reports the changes since
+    /// the entries were written.
+    pub fn saved_clock(&self) -> Option<&SourceDigestCacheClock> {
+        self.saved_clock.as_ref()
+    }
+
+    /// The cached metadata of the file at `path`.
+    pub fn get(&self, path: &ProjectRelativePath) -> Option<FileMetadata> {
+        let state = self.state.lock();
+        if state.synced_clock.is_none() {
+            return None;
+        }
+        state.entries.get(path).map(|meta| meta.dupe())
+    }
+
+    /// Whether to compute a digest the cache has anyway, to check the cache.
+    pub fn should_verify(&self) -> bool {
+        self.verify_percentage > 0 && rand::random::<u32>() % 100 < self.verify_percentage
+    }
+
+    /// To be read before computing the metadata of a file, and passed to
+    /// [`SourceDigestCache::computed`].
+    pub fn generation(&self) -> u64 {
+        self.state.lock().generation
+    }
+
+    /// Records the metadata of the file at `path`, computed after [`SourceDigestCache::generation`]
+    /// returned `generation`. `cached` is what [`SourceDigestCache::get`] returned after that, if
+    /// the file was hashed to check it. `meta` is `None` if `path` is not a file (or is behind a
+    /// symlink).
+    ///
+    /// Does nothing if entries were invalidated in the meantime: the file may have changed while
+    /// it was hashed.
+    pub fn computed(
+        &self,
+        path: ProjectRelativePathBuf,
+        generation: u64,
+        cached: Option<FileMetadata>,
+        meta: Option<&FileMetadata>,
+    ) {
+        let mut state = self.state.lock();
+        if state.generation != generation {
+            return;
+        }
+
+        if let Some(cached) = cached {
+            if meta != Some(&cached) {
+                Self::clear_state(&mut state);
+                drop(state);
+                let _ignore = soft_error!(
+                    "source_digest_cache_mismatch",
+                    SourceDigestCacheError::Mismatch {
+                        path,
+                        cached,
+                        actual: match meta {
+                            Some(meta) => meta.to_string(),
+                            None => "not a file".to_owned(),
+                        },
+                    }
+                    .into()
+                );
+                return;
+            }
+        }
+
+        let meta = match meta {
+            Some(meta) => meta,
+            None => return,
+        };
+        if state.entries.get(&path) == Some(meta) {
+            return;
+        }
+        state
+            .pending
+            .push(PendingWrite::Insert(path.clone(), meta.dupe()));
+        state.entries.insert(path, meta.dupe());
+    }
+
+    /// Drops the entries of `files`, and of the paths under `dirs`, which changed.
+    pub fn invalidate(
+        &self,
+        files: Vec<ProjectRelativePathBuf>,
+        dirs: Vec<ProjectRelativePathBuf>,
+    ) {
+        if files.is_empty() && dirs.is_empty() {
+            return;
+        }
+        let mut state = self.state.lock();
+        state.generation += 1;
+        for file in files {
+            state.entries.remove(&file);
+            state.pending.push(PendingWrite::Remove(file));
+        }
+        if !dirs.is_empty() {
+            state
+                .entries
+                .retain(|path, _| !dirs.iter().any(|dir| path.starts_with(dir)));
+            state
+                .pending
+                .extend(dirs.into_iter().map(PendingWrite::RemoveDir));
+        }
+    }
+
+    /// Drops all entries.
+    pub fn clear(&self) {
+        Self::clear_state(&mut self.state.lock());
+    }
+
+    fn clear_state(state: &mut State) {
+        state.generation += 1;
+        state.entries.clear();
+        state.pending.clear();
+        state.pending.push(PendingWrite::Clear);
+    }
+
+    /// Called once all changes up to `clock` were passed to [`SourceDigestCache::invalidate`] (or
+    /// [`SourceDigestCache::clear`]). The entries are valid at `clock` from then on, and are served.
+    ///
+    /// Entries inserted later are computed after `clock`, so a change to their file is reported by
+    /// a query since `clock` as well, and they can be written with `clock` too, by any later
+    /// [`SourceDigestCache::flush`].
+    pub fn synced(&self, clock: SourceDigestCacheClock) {
+        self.state.lock().synced_clock = Some(clock);
+    }
+
+    /// Writes the pending changes and the last synced clock to disk. Blocks on I/O.
+    pub fn flush(&self) -> anyhow::Result<()> {
+        // Taking the db lock first orders the flushes.
+        let mut db = self.db.lock();
+        let (pending, clock) = {
+            let mut state = self.state.lock();
+            let clock = match &state.synced_clock {
+                Some(clock) => clock.clone(),
+                None => return Ok(()),
+            };
+            if state.pending.is_empty() && db.written_clock.as_ref() == Some(&clock) {
+                return Ok(());
+            }
+            (std::mem::take(&mut state.pending), clock)
+        };
+        if let Err(e) = db.write(&pending, &clock) {
+            // Keep the changes for the next flush: missing a removal would keep a stale entry.
+            self.state.lock().pending.splice(0..0, pending);
+            return Err(e);
+        }
+        db.written_clock = Some(clock);
+        Ok(())
+    }
+}
+
+struct SourceDigestCacheDb {
+    connection: Arc<Mutex<Connection>>,
+    /// Versions of the buck2 which wrote the db (schema, digest config...). The entries are
+    /// dropped when they don't match.
+    versions_table: KeyValueSqliteTable,
+    /// The clock at which the entries are valid.
+    clock_table: KeyValueSqliteTable,
+    written_clock: Option<SourceDigestCacheClock>,
+}
+
+impl SourceDigestCacheDb {
+    fn load(
+        db_path: &AbsNormPath,
+        versions: &HashMap<String, String>,
+        cas_digest_config: CasDigestConfig,
+    ) -> anyhow::Result<(
+        Self,
+        SourceDigestCacheClock,
+        HashMap<ProjectRelativePathBuf, FileMetadata>,
+    )> {
+        if !db_path.exists() {
+            return Err(anyhow::anyhow!("Path {} does not exist", db_path));
+        }
+        let db = Self::open(db_path)?;
+        let found = db.versions_table.read_all()?;
+        if found != *versions {
+            return Err(SourceDigestCacheError::VersionMismatch {
+                expected: versions.clone(),
+                found,
+                path: db_path.to_owned(),
+            }
+            .into());
+        }
+        // Without a clock, the entries can't be checked.
+        let clock = db.read_clock()?.context("The db has no clock")?;
+        let entries = db.read_all(cas_digest_config)?;
+        Ok((db, clock, entries))
+    }
+
+    fn open(path: &AbsNormPath) -> anyhow::Result<Self> {
+        let connection = Connection::open(path)?;
+        if cfg!(unix) {
+            connection.pragma_update(None, "journal_mode", "WAL")?;
+        }
+        // Like the materializer state, losing the latest writes (or the whole db) on power loss
+        // is fine: the db is only ever at a clock its entries are valid at, and a corrupted db is
+        // dropped.
+        connection.pragma_update(None, "synchronous", "OFF")?;
+
+        let connection = Arc::new(Mutex::new(connection));
+        let versions_table = KeyValueSqliteTable::new("versions".to_owned(), connection.dupe());
+        let clock_table = KeyValueSqliteTable::new(CLOCK_TABLE_NAME.to_owned(), connection.dupe());
+        Ok(Self {
+            connection,
+            versions_table,
+            clock_table,
+            written_clock: None,
+        })
+    }
+
+    fn create_all_tables(&self) -> anyhow::Result<()> {
+        let sql = format!(
+            "CREATE TABLE {} (
+                path                TEXT NOT NULL PRIMARY KEY,
+                digest_size         INTEGER NOT NULL,
+                entry_hash          BLOB NOT NULL,
+                entry_hash_kind     INTEGER NOT NULL,
+                file_is_executable  INTEGER NOT NULL
+            )",
+            DIGESTS_TABLE_NAME,
+        );
+        self.connection
+            .lock()
+            .execute(&sql, [])
+            .with_context(|| format!("creating sqlite table {}", DIGESTS_TABLE_NAME))?;
+        self.versions_table.create_table()?;
+        self.clock_table.create_table()?;
+        Ok(())
+    }
+
+    fn read_clock(&self) -> anyhow::Result<Option<SourceDigestCacheClock>> {
+        Ok(self
+            .clock_table
+            .get(CLOCK_KEY)?
+            .map(|clock| -> anyhow::Result<_> {
+                Ok(SourceDigestCacheClock {
+                    clock,
+                    mergebase: self.clock_table.get(MERGEBASE_KEY)?,
+                })
+            })
+            .transpose()?)
+    }
+
+    fn read_all(
+        &self,
+        cas_digest_config: CasDigestConfig,
+    ) -> anyhow::Result<HashMap<ProjectRelativePathBuf, FileMetadata>> {
+        let sql = format!(
+            "SELECT path, digest_size, entry_hash, entry_hash_kind, file_is_executable FROM {}",
+            DIGESTS_TABLE_NAME
+        );
+        let connection = self.connection.lock();
+        let mut stmt = connection.prepare(&sql)?;
+        let rows = stmt
+            .query_map(
+                [],
+                |row| -> rusqlite::Result<(String, u64, Vec<u8>, u8, bool)> {
+                    Ok((
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                    ))
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()
+            .with_context(|| format!("reading from sqlite table {}", DIGESTS_TABLE_NAME))?;
+
+        rows.into_iter()
+            .map(|(path, size, hash, hash_kind, is_executable)| {
+                let hash_kind = hash_kind
+                    .try_into()
+                    .with_context(|| format!("Invalid entry_hash_kind: `{}`", hash_kind))?;
+                let digest = FileDigest::from_digest_bytes(hash_kind, &hash, size)?;
+                Ok((
+                    ProjectRelativePathBuf::unchecked_new(path),
+                    FileMetadata {
+                        digest: TrackedFileDigest::new(digest, cas_digest_config),
+                        is_executable,
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    fn write(
+        &self,
+        pending: &[PendingWrite],
+        clock: &SourceDigestCacheClock,
+    ) -> anyhow::Result<()> {
+        let mut connection = self.connection.lock();
+        let tx = connection.transaction()?;
+        {
+            let mut insert = tx.prepare(&format!(
+                "INSERT OR REPLACE INTO {} (path, digest_size, entry_hash, entry_hash_kind, file_is_executable) VALUES (?1, ?2, ?3, ?4, ?5)",
+                DIGESTS_TABLE_NAME
+            ))?;
+            let mut remove = tx.prepare(&format!(
+                "DELETE FROM {} WHERE path = ?1",
+                DIGESTS_TABLE_NAME
+            ))?;
+            // Paths under `dir` are the paths starting with `dir/`, i.e. greater than `dir/` and
+            // lower than `dir0`, since `0` follows `/`.
+            let mut remove_dir = tx.prepare(&format!(
+                "DELETE FROM {} WHERE path > ?1 AND path < ?2",
+                DIGESTS_TABLE_NAME
+            ))?;
+            let mut clear = tx.prepare(&format!("DELETE FROM {}", DIGESTS_TABLE_NAME))?;
+
+            for write in pending {
+                match write {
+                    PendingWrite::Insert(path, meta) => {
+                        insert.execute(rusqlite::params![
+                            path.as_str(),
+                            meta.digest.size(),
+                            meta.digest.raw_digest().as_bytes(),
+                            meta.digest.raw_digest().algorithm() as u8,
+                            meta.is_executable,
+                        ])?;
+                    }
+                    PendingWrite::Remove(path) => {
+                        remove.execute([path.as_str()])?;
+                    }
+                    PendingWrite::RemoveDir(dir) if dir.is_empty() => {
+                        clear.execute([])?;
+                    }
+                    PendingWrite::RemoveDir(dir) => {
+                        remove_dir.execute([format!("{}/", dir), format!("{}0", dir)])?;
+                    }
+                    PendingWrite::Clear => {
+                        clear.execute([])?;
+                    }
+                }
+            }
+        }
+        let set_clock = format!(
+            "INSERT OR REPLACE INTO {} (key, value) VALUES (?1, ?2)",
+            CLOCK_TABLE_NAME
+        );
+        tx.execute(&set_clock, [CLOCK_KEY, clock.clock.as_str()])?;
+        match &clock.mergebase {
+            Some(mergebase) => tx.execute(&set_clock, [MERGEBASE_KEY, mergebase.as_str()])?,
+            None => tx.execute(
+                &format!("DELETE FROM {} WHERE key = ?1", CLOCK_TABLE_NAME),
+                [MERGEBASE_KEY],
+            )?,
+        };
+        tx.commit()
+            .with_context(|| format!("writing to sqlite table {}", DIGESTS_TABLE_NAME))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_core::fs::project::ProjectRootTemp;
+
+    use super::*;
+
+    fn meta(content: &str) -> FileMetadata {
+        FileMetadata {
+            digest: TrackedFileDigest::from_content(
+                content.as_bytes(),
+                CasDigestConfig::testing_default(),
+            ),
+            is_executable: false,
+        }
+    }
+
+    fn path(path: &str) -> ProjectRelativePathBuf {
+        ProjectRelativePath::unchecked_new(path).to_buf()
+    }
+
+    fn open(fs: &ProjectRootTemp, version: &str) -> SourceDigestCache {
+        SourceDigestCache::open(
+            &fs.path()
+                .resolve(ProjectRelativePath::unchecked_new("source_digests")),
+            HashMap::from([("schema_version".to_owned(), version.to_owned())]),
+            CasDigestConfig::testing_default(),
+            0,
+        )
+        .unwrap()
+    }
+
+    fn clock(clock: &str) -> SourceDigestCacheClock {
+        SourceDigestCacheClock {
+            clock: clock.to_owned(),
+            mergebase: None,
+        }
+    }
+
+    fn insert(cache: &SourceDigestCache, p: &str, content: &str) {
+        cache.computed(path(p), cache.generation(), None, Some(&meta(content)));
+    }
+
+    #[test]
+    fn test_source_digest_cache_reopen() {
+        let fs = ProjectRootTemp::new().unwrap();
+
+        let cache = open(&fs, "1");
+        assert_eq!(None, cache.saved_clock());
+        insert(&cache, "a/x", "x");
+        insert(&cache, "a/y", "y");
+        insert(&cache, "ab/z", "z");
+        insert(&cache, "b", "b");
+        // Nothing is served before the first sync.
+        assert_eq!(None, cache.get(ProjectRelativePath::unchecked_new("b")));
+        cache.synced(clock("c:1"));
+        assert_eq!(
+            Some(meta("b")),
+            cache.get(ProjectRelativePath::unchecked_new("b"))
+        );
+        cache.flush().unwrap();
+
+        cache.invalidate(vec![path("b")], vec![path("a")]);
+        cache.synced(clock("c:2"));
+        assert_eq!(None, cache.get(ProjectRelativePath::unchecked_new("a/x")));
+        assert_eq!(
+            Some(meta("z")),
+            cache.get(ProjectRelativePath::unchecked_new("ab/z"))
+        );
+        cache.flush().unwrap();
+        drop(cache);
+
+        let cache = open(&fs, "1");
+        assert_eq!(Some(&clock("c:2")), cache.saved_clock());
+        cache.synced(clock("c:3"));
+        assert_eq!(None, cache.get(ProjectRelativePath::unchecked_new("a/x")));
+        assert_eq!(None, cache.get(ProjectRelativePath::unchecked_new("b")));
+        assert_eq!(
+            Some(meta("z")),
+            cache.get(ProjectRelativePath::unchecked_new("ab/z"))
+        );
+        drop(cache);
+
+        // Entries written with other versions are dropped.
+        let cache = open(&fs, "2");
+        assert_eq!(None, cache.saved_clock());
+        cache.synced(clock("c:4"));
+        assert_eq!(None, cache.get(ProjectRelativePath::unchecked_new("ab/z")));
+    }
+
+    #[test]
+    fn test_source_digest_cache_invalidation() {
+        let fs = ProjectRootTemp::new().unwrap();
+        let cache = open(&fs, "1");
+        cache.synced(clock("c:1"));
+
+        // Computed while the file changed.
+        let generation = cache.generation();
+        cache.invalidate(vec![path("a")], Vec::new());
+        cache.computed(path("a"), generation, None, Some(&meta("a")));
+        assert_eq!(None, cache.get(ProjectRelativePath::unchecked_new("a")));
+
+        insert(&cache, "a", "a");
+        insert(&cache, "b", "b");
+        // A mismatch drops everything.
+        let generation = cache.generation();
+        let cached = cache.get(ProjectRelativePath::unchecked_new("a"));
+        assert_eq!(Some(meta("a")), cached);
+        cache.computed(path("a"), generation, cached, Some(&meta("a2")));
+        assert_eq!(None, cache.get(ProjectRelativePath::unchecked_new("a")));
+        assert_eq!(None, cache.get(ProjectRelativePath::unchecked_new("b")));
+    }
+}
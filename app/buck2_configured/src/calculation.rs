@@ -63,7 +63,15 @@ impl ConfiguredTargetCalculationImpl for ConfiguredTargetCalculationInstance {
                 }
                 None => match node.get_default_target_platform() {
                     Some(target) => ctx.get_platform_configuration(target).await?,
-                    None => ctx.get_default_platform(target).await?,
+                    None => match super_package.platform_resolver() {
+                        Some(resolver) => {
+                            match resolver.resolve(ctx, target, super_package).await? {
+                                Some(platform) => ctx.get_platform_configuration(&platform).await?,
+                                None => ctx.get_default_platform(target).await?,
+                            }
+                        }
+                        None => ctx.get_default_platform(target).await?,
+                    },
                 },
             };
 
@@ -295,6 +295,7 @@ async fn execution_platforms_for_toolchain(
                 ConfigurationNoExec::unbound_exec(),
                 &resolved_transitions,
                 &platform_cfgs,
+                node.package().select_resolver.as_ref(),
             );
             let (gathered_deps, errors_and_incompats) =
                 gather_deps(&self.0, node.as_ref(), &cfg_ctx, ctx).await?;
@@ -352,6 +353,7 @@ pub async fn get_execution_platform_toolchain_dep(
             ConfigurationNoExec::unbound_exec(),
             &resolved_transitions,
             &platform_cfgs,
+            target_node.package().select_resolver.as_ref(),
         );
         let (gathered_deps, errors_and_incompats) =
             gather_deps(target_label, target_node, &cfg_ctx, ctx).await?;
@@ -835,6 +837,7 @@ async fn compute_configured_target_node_no_transition(
         ConfigurationNoExec::unbound_exec(),
         &resolved_transitions,
         &platform_cfgs,
+        target_node.package().select_resolver.as_ref(),
     );
     let (gathered_deps, mut errors_and_incompats) = gather_deps(
         partial_target_label,
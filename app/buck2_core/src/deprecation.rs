@@ -0,0 +1,154 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Deprecation of Starlark globals and rule attributes.
+//!
+//! Each use of something deprecated is a soft error of the category of its deprecation, so
+//! `[soft_errors] <category> = error` makes the uses of one deprecation hard errors. Uses are
+//! also counted per call site on the soft errors of the command, for the build report.
+
+use std::fmt;
+
+use allocative::Allocative;
+
+use crate::error::current_command_soft_errors;
+use crate::error::validate_category;
+use crate::soft_error;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(user)]
+enum DeprecationError {
+    #[error("{what} is deprecated{removal}: {message}{call_site}")]
+    Deprecated {
+        what: String,
+        message: String,
+        removal: RemovalDate,
+        call_site: CallSite,
+    },
+    #[error(
+        "Deprecation category must start with `{}`, got `{0}`",
+        DEPRECATION_CATEGORY
+    )]
+    InvalidCategory(String),
+    #[error("Removal date must be formatted as `YYYY-MM-DD`, got `{0}`")]
+    InvalidRemovalDate(String),
+}
+
+/// The prefix of the categories of deprecations, and the category used when none is given.
+pub const DEPRECATION_CATEGORY: &str = "starlark_deprecated";
+
+#[derive(Debug)]
+struct RemovalDate(Option<String>);
+
+impl fmt::Display for RemovalDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Some(date) => write!(f, " and will be removed on {}", date),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CallSite(Option<String>);
+
+impl fmt::Display for CallSite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Some(call_site) => write!(f, "\nUsed at {}", call_site),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Why something is deprecated, and when it is going away.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Allocative)]
+pub struct Deprecation {
+    category: String,
+    message: String,
+    /// `YYYY-MM-DD`.
+    removal_date: Option<String>,
+}
+
+impl Deprecation {
+    /// `category` defaults to `starlark_deprecated`, and otherwise must start with it.
+    pub fn new(
+        category: Option<&str>,
+        message: &str,
+        removal_date: Option<&str>,
+    ) -> anyhow::Result<Deprecation> {
+        let category = category.unwrap_or(DEPRECATION_CATEGORY);
+        if !category.starts_with(DEPRECATION_CATEGORY) {
+            return Err(DeprecationError::InvalidCategory(category.to_owned()).into());
+        }
+        validate_category(category)?;
+        if let Some(date) = removal_date {
+            if !is_date(date) {
+                return Err(DeprecationError::InvalidRemovalDate(date.to_owned()).into());
+            }
+        }
+        Ok(Deprecation {
+            category: category.to_owned(),
+            message: message.to_owned(),
+            removal_date: removal_date.map(str::to_owned),
+        })
+    }
+
+    pub fn category(&self) -> &str {
+        &self.category
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn removal_date(&self) -> Option<&str> {
+        self.removal_date.as_deref()
+    }
+
+    /// Report a use of `what`, e.g. ``function `foo` ``, at `call_site`, e.g. `foo/BUCK:3:1-10`.
+    /// Fails if the category of the deprecation is configured to be an error.
+    pub fn report(&self, what: &str, call_site: Option<String>) -> anyhow::Result<()> {
+        if let Some(command) = current_command_soft_errors() {
+            command.record_deprecation(&self.category, call_site.as_deref().unwrap_or_default());
+        }
+
+        soft_error!(
+            &self.category,
+            DeprecationError::Deprecated {
+                what: what.to_owned(),
+                message: self.message.clone(),
+                removal: RemovalDate(self.removal_date.clone()),
+                call_site: CallSite(call_site),
+            }
+            .into()
+        )?;
+        Ok(())
+    }
+}
+
+fn is_date(date: &str) -> bool {
+    let lens: Vec<usize> = date.split('-').map(str::len).collect();
+    lens == [4, 2, 2] && date.bytes().all(|b| b == b'-' || b.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deprecation::Deprecation;
+
+    #[test]
+    fn test_new() {
+        assert!(Deprecation::new(None, "Use `bar`", Some("2024-01-31")).is_ok());
+        assert!(Deprecation::new(Some("starlark_deprecated_foo"), "Use `bar`", None).is_ok());
+        assert!(Deprecation::new(Some("starlark_foo"), "Use `bar`", None).is_err());
+        assert!(Deprecation::new(Some("starlark_deprecated_"), "Use `bar`", None).is_err());
+        assert!(Deprecation::new(None, "Use `bar`", Some("31/01/2024")).is_err());
+        assert!(Deprecation::new(None, "Use `bar`", Some("2024-1-31")).is_err());
+    }
+}
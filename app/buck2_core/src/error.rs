@@ -7,6 +7,9 @@
  * of this source tree.
  */
 
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::str::FromStr;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
@@ -16,6 +19,7 @@ use std::sync::OnceLock;
 
 use anyhow::Context;
 use arc_swap::ArcSwapOption;
+use buck2_util::late_binding::LateBinding;
 use starlark_map::small_set::SmallSet;
 
 use crate::env::macros::buck2_env;
@@ -40,6 +44,11 @@ static HARD_ERROR_CONFIG: HardErrorConfigHolder = HardErrorConfigHolder {
 
 static ALL_SOFT_ERROR_COUNTERS: Mutex<Vec<&'static AtomicUsize>> = Mutex::new(Vec::new());
 
+/// The soft error state of the command the caller is running for, if any. Implemented by the
+/// ambient event dispatcher, which is propagated to all the work done for a command.
+pub static CURRENT_COMMAND_SOFT_ERRORS: LateBinding<fn() -> Option<Arc<CommandSoftErrors>>> =
+    LateBinding::new("CURRENT_COMMAND_SOFT_ERRORS");
+
 /// Throw a "soft_error" i.e. one that is destined to become a hard error
 /// in the near future. The macro lives in this crate to allow it be
 /// made available everywhere. Calling programs are responsible for
@@ -176,9 +185,22 @@ pub fn handle_soft_error(
         ALL_SOFT_ERROR_COUNTERS.lock().unwrap().push(count);
     });
 
+    let config = match current_command_soft_errors() {
+        Some(command) => {
+            command.record(category);
+            command.config.load_full()
+        }
+        None => None,
+    };
+    let mode = config.as_ref().and_then(|config| config.mode(category));
+
     // We want to limit each error to appearing at most 10 times in a build (no point spamming people)
     if count.fetch_add(1, Ordering::SeqCst) < 10 {
         if let Some(handler) = HANDLER.get() {
+            let options = StructuredErrorOptions {
+                quiet: options.quiet || mode == Some(SoftErrorMode::Log),
+                ..options
+            };
             handler(category, &err, loc, options);
         }
     }
@@ -187,6 +209,28 @@ pub fn handle_soft_error(
         return Err(err.context("Upgraded warning to failure via $BUCK2_HARD_ERROR"));
     }
 
+    if config
+        .as_ref()
+        .map_or(false, |config| config.promoted.contains(category))
+    {
+        return Err(err.context(format!(
+            "Upgraded warning to failure as it had not been hit for `{}.{}` days",
+            SoftErrorConfig::SECTION,
+            SoftErrorConfig::PROMOTE_AFTER_DAYS,
+        )));
+    }
+
+    match mode {
+        Some(SoftErrorMode::Error) => {
+            return Err(err.context(format!(
+                "Upgraded warning to failure via `{}` configuration",
+                SoftErrorConfig::SECTION
+            )));
+        }
+        Some(SoftErrorMode::Log | SoftErrorMode::Warn) => return Ok(err),
+        None => {}
+    }
+
     if is_open_source() {
         // We don't log these, and we have no legacy users, and they might not upgrade that often,
         // so lets just break open source things immediately.
@@ -203,6 +247,144 @@ pub fn reset_soft_error_counters() {
     }
 }
 
+/// The soft errors of the command running the current task, if any.
+pub(crate) fn current_command_soft_errors() -> Option<Arc<CommandSoftErrors>> {
+    CURRENT_COMMAND_SOFT_ERRORS
+        .get()
+        .ok()
+        .and_then(|current| current())
+}
+
+/// The soft errors of a command: how they are handled, and how many were hit.
+///
+/// Commands running concurrently on the same daemon each have their own, so that one's
+/// configuration does not apply to the other.
+#[derive(Default)]
+pub struct CommandSoftErrors {
+    config: ArcSwapOption<SoftErrorConfig>,
+    counts: Mutex<BTreeMap<String, u64>>,
+    /// Uses of deprecations, by category, then by call site.
+    deprecations: Mutex<BTreeMap<String, BTreeMap<String, u64>>>,
+}
+
+impl CommandSoftErrors {
+    /// Set how soft errors are handled, once the configuration of the command is known. Until then
+    /// the built-in behavior is used.
+    pub fn set_config(&self, config: SoftErrorConfig) {
+        self.config.store(Some(Arc::new(config)));
+    }
+
+    /// How many times each soft error category was hit by this command.
+    pub fn counts(&self) -> BTreeMap<String, u64> {
+        self.counts.lock().unwrap().clone()
+    }
+
+    /// Categories which were promoted to errors by `soft_errors.promote_after_days` for this
+    /// command.
+    pub fn promoted(&self) -> HashSet<String> {
+        self.config
+            .load_full()
+            .map(|config| config.promoted.clone())
+            .unwrap_or_default()
+    }
+
+    /// How many times each deprecation category was used at each call site by this command. Uses
+    /// without a known call site are counted under the empty string.
+    pub fn deprecation_call_site_counts(&self) -> BTreeMap<String, BTreeMap<String, u64>> {
+        self.deprecations.lock().unwrap().clone()
+    }
+
+    pub(crate) fn record_deprecation(&self, category: &str, call_site: &str) {
+        *self
+            .deprecations
+            .lock()
+            .unwrap()
+            .entry(category.to_owned())
+            .or_default()
+            .entry(call_site.to_owned())
+            .or_default() += 1;
+    }
+
+    fn record(&self, category: &str) {
+        *self
+            .counts
+            .lock()
+            .unwrap()
+            .entry(category.to_owned())
+            .or_default() += 1;
+    }
+}
+
+/// What to do when a soft error is hit.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SoftErrorMode {
+    /// Log the error to the event log, but don't show it to the user.
+    Log,
+    /// Log the error and show it to the user.
+    Warn,
+    /// Fail.
+    Error,
+}
+
+impl FromStr for SoftErrorMode {
+    type Err = InvalidSoftErrorMode;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "log" => Ok(Self::Log),
+            "warn" => Ok(Self::Warn),
+            "error" => Ok(Self::Error),
+            _ => Err(InvalidSoftErrorMode(s.to_owned())),
+        }
+    }
+}
+
+/// Per-category overrides of how soft errors are handled.
+///
+/// Categories without an override use the `default`, if set, and otherwise are errors in open
+/// source builds and warnings elsewhere. `$BUCK2_HARD_ERROR` takes precedence over all of this.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SoftErrorConfig {
+    pub default: Option<SoftErrorMode>,
+    pub categories: HashMap<String, SoftErrorMode>,
+    /// After how many days without being hit a category becomes an error.
+    pub promote_after_days: Option<u64>,
+    /// Categories which are errors because they have not been hit for `promote_after_days`,
+    /// regardless of their mode.
+    pub promoted: HashSet<String>,
+}
+
+impl SoftErrorConfig {
+    /// The buckconfig section this is read from, with a `category = log|warn|error` entry per
+    /// category, `default` for all the others, and `promote_after_days`.
+    pub const SECTION: &'static str = "soft_errors";
+    pub const PROMOTE_AFTER_DAYS: &'static str = "promote_after_days";
+
+    /// Build from the entries of the buckconfig section.
+    pub fn from_config_entries<'a>(
+        entries: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> anyhow::Result<Self> {
+        let mut config = SoftErrorConfig::default();
+        for (key, value) in entries {
+            if key == "default" {
+                config.default = Some(value.parse()?);
+            } else if key == Self::PROMOTE_AFTER_DAYS {
+                config.promote_after_days = Some(value.parse().with_context(|| {
+                    format!("Parsing `{}.{}`", Self::SECTION, Self::PROMOTE_AFTER_DAYS)
+                })?);
+            } else {
+                validate_category(key)?;
+                config.categories.insert(key.to_owned(), value.parse()?);
+            }
+        }
+        Ok(config)
+    }
+
+    fn mode(&self, category: &str) -> Option<SoftErrorMode> {
+        self.categories.get(category).copied().or(self.default)
+    }
+}
+
 pub fn initialize(handler: StructuredErrorHandler) -> anyhow::Result<()> {
     hard_error_config()?;
 
@@ -278,6 +460,10 @@ impl HardErrorConfigHolder {
 #[error("Invalid hard error config: `{0}`")]
 struct InvalidHardErrorConfig(String);
 
+#[derive(buck2_error::Error, Debug)]
+#[error("Invalid soft error mode `{0}`, expected `log`, `warn` or `error`")]
+pub struct InvalidSoftErrorMode(String);
+
 #[derive(buck2_error::Error, Debug)]
 enum InvalidSoftError {
     #[error("Invalid category, must be lower_snake_case, got `{0}`")]
@@ -285,7 +471,7 @@ enum InvalidSoftError {
 }
 
 /// A category must be a-z with no consecutive underscores. Or we raise an error.
-fn validate_category(category: &str) -> anyhow::Result<()> {
+pub(crate) fn validate_category(category: &str) -> anyhow::Result<()> {
     let mut allow_underscore = false;
     for &x in category.as_bytes() {
         if x.is_ascii_lowercase() {
@@ -353,6 +539,28 @@ pub(crate) mod tests {
         assert_eq!(**c2, HardErrorConfig::Bool(false));
     }
 
+    #[test]
+    fn test_soft_error_config() -> anyhow::Result<()> {
+        let config = SoftErrorConfig::from_config_entries([
+            ("default", "warn"),
+            ("some_category", "error"),
+            ("other_category", "log"),
+            ("promote_after_days", "7"),
+        ])?;
+        assert_eq!(Some(SoftErrorMode::Error), config.mode("some_category"));
+        assert_eq!(Some(SoftErrorMode::Log), config.mode("other_category"));
+        assert_eq!(Some(SoftErrorMode::Warn), config.mode("unlisted_category"));
+        assert_eq!(Some(7), config.promote_after_days);
+        assert_eq!(
+            None,
+            SoftErrorConfig::default().mode("unlisted_category"),
+            "No configuration keeps the built-in behavior"
+        );
+        assert!(SoftErrorConfig::from_config_entries([("some_category", "fatal")]).is_err());
+        assert!(SoftErrorConfig::from_config_entries([("Not-A-Category", "log")]).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_validate_category() {
         assert_matches!(validate_category("valid"), Ok(_));
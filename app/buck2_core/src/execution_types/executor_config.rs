@@ -13,6 +13,7 @@ use std::hash::Hash;
 use std::hash::Hasher;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use allocative::Allocative;
 use derive_more::Display;
@@ -231,10 +232,67 @@ pub struct CommandGenerationOptions {
     pub output_paths_behavior: OutputPathsBehavior,
 }
 
+/// Timeouts of the commands of the actions which don't set one, by action category.
+#[derive(Default, Debug, Clone, Dupe, PartialEq, Eq, Hash, Allocative)]
+pub struct ActionTimeouts {
+    pub by_category: Arc<SortedMap<String, Duration>>,
+}
+
+impl ActionTimeouts {
+    pub fn get(&self, category: &str) -> Option<Duration> {
+        self.by_category.get(category).copied()
+    }
+}
+
+/// Where to run the commands of an action category, e.g. `local_only` for `link`.
+#[derive(Debug, Display, Clone, Copy, Dupe, PartialEq, Eq, Hash, Allocative)]
+pub enum CategoryExecutorPreference {
+    #[display(fmt = "local_only")]
+    LocalOnly,
+    #[display(fmt = "prefer_local")]
+    PreferLocal,
+    #[display(fmt = "prefer_remote")]
+    PreferRemote,
+    #[display(fmt = "remote_only")]
+    RemoteOnly,
+}
+
+impl FromStr for CategoryExecutorPreference {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "local_only" => Ok(CategoryExecutorPreference::LocalOnly),
+            "prefer_local" => Ok(CategoryExecutorPreference::PreferLocal),
+            "prefer_remote" => Ok(CategoryExecutorPreference::PreferRemote),
+            "remote_only" => Ok(CategoryExecutorPreference::RemoteOnly),
+            _ => Err(anyhow::anyhow!(
+                "Invalid executor preference: `{}`, expected `local_only`, `prefer_local`, `prefer_remote` or `remote_only`",
+                s
+            )),
+        }
+    }
+}
+
+/// Where to run the commands of actions, by action category. Combined with the preference of the
+/// action itself (e.g. `prefer_local = True`).
+#[derive(Default, Debug, Clone, Dupe, PartialEq, Eq, Hash, Allocative)]
+pub struct ActionExecutorPreferences {
+    pub by_category: Arc<SortedMap<String, CategoryExecutorPreference>>,
+}
+
+impl ActionExecutorPreferences {
+    pub fn get(&self, category: &str) -> Option<CategoryExecutorPreference> {
+        self.by_category.get(category).copied()
+    }
+}
+
 #[derive(Debug, Eq, PartialEq, Hash, Allocative)]
 pub struct CommandExecutorConfig {
     pub executor: Executor,
     pub options: CommandGenerationOptions,
+    pub default_timeouts: ActionTimeouts,
+    pub executor_preferences: ActionExecutorPreferences,
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy, Dupe, Hash, Allocative)]
@@ -261,6 +319,32 @@ impl CommandExecutorConfig {
                 path_separator: PathSeparatorKind::system_default(),
                 output_paths_behavior: Default::default(),
             },
+            default_timeouts: ActionTimeouts::default(),
+            executor_preferences: ActionExecutorPreferences::default(),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_category_executor_preference_from_str() {
+        for preference in [
+            CategoryExecutorPreference::LocalOnly,
+            CategoryExecutorPreference::PreferLocal,
+            CategoryExecutorPreference::PreferRemote,
+            CategoryExecutorPreference::RemoteOnly,
+        ] {
+            assert_eq!(
+                preference,
+                preference
+                    .to_string()
+                    .parse::<CategoryExecutorPreference>()
+                    .unwrap()
+            );
+        }
+        assert!("local".parse::<CategoryExecutorPreference>().is_err());
+    }
+}
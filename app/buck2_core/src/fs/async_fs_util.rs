@@ -15,14 +15,14 @@ use crate::io_counters::IoCounterKey;
 
 pub async fn open<P: AsRef<AbsPath>>(path: P) -> anyhow::Result<File> {
     let _guard = IoCounterKey::Read.guard();
-    tokio::fs::File::open(path.as_ref().as_maybe_relativized())
+    tokio::fs::File::open(path.as_ref().as_os_path())
         .await
         .with_context(|| format!("open({})", path.as_ref().display()))
 }
 
 pub async fn write<P: AsRef<AbsPath>>(path: P, content: impl AsRef<[u8]>) -> anyhow::Result<()> {
     let _guard = IoCounterKey::Write.guard();
-    tokio::fs::write(path.as_ref().as_maybe_relativized(), content.as_ref())
+    tokio::fs::write(path.as_ref().as_os_path(), content.as_ref())
         .await
         .with_context(|| format!("write({})", path.as_ref().display()))
 }
@@ -10,6 +10,7 @@
 use std::collections::hash_map::DefaultHasher;
 use std::hash::Hash;
 use std::hash::Hasher;
+use std::str::FromStr;
 use std::sync::Arc;
 
 use allocative::Allocative;
@@ -22,6 +23,7 @@ use crate::fs::paths::forward_rel_path::ForwardRelativePath;
 use crate::fs::paths::forward_rel_path::ForwardRelativePathBuf;
 use crate::fs::project_rel_path::ProjectRelativePath;
 use crate::fs::project_rel_path::ProjectRelativePathBuf;
+use crate::target::configured_target_label::ConfiguredTargetLabel;
 
 #[derive(Clone, Debug, Display, Allocative, Hash, Eq, PartialEq)]
 #[display(fmt = "({})/{}", owner, "path.as_str()")]
@@ -169,19 +171,66 @@ impl BuckOutTestPath {
     }
 }
 
+#[derive(Debug, buck2_error::Error)]
+enum BuckOutPathSchemeError {
+    #[error("Unknown buck-out path scheme `{0}`, expected `full` or `short`")]
+    #[buck2(user)]
+    Unknown(String),
+}
+
+/// How the outputs of targets are laid out in buck-out, set by `buck2.buck_out_path_scheme`.
+#[derive(Clone, Copy, Dupe, Debug, Default, Eq, PartialEq, Hash, Allocative)]
+pub enum BuckOutPathScheme {
+    /// `<cell>/<configuration hash>/<package>/__<target name>__/<path>`.
+    #[default]
+    Full,
+    /// `<configuration hash>/<target hash>/<path>`, for toolchains which break on long paths
+    /// (like the ones limited by `MAX_PATH` on Windows). Which target a directory belongs to is
+    /// written to `buck-out/v2/short-paths/<configuration hash>/<target hash>`.
+    ///
+    /// Outputs of anonymous targets and BXL are laid out as with `Full`.
+    Short,
+}
+
+impl FromStr for BuckOutPathScheme {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "full" => Ok(BuckOutPathScheme::Full),
+            "short" => Ok(BuckOutPathScheme::Short),
+            _ => Err(BuckOutPathSchemeError::Unknown(s.to_owned()).into()),
+        }
+    }
+}
+
+/// Number of hex digits of the target hash in short paths.
+const SHORT_TARGET_HASH_LEN: usize = 16;
+
 #[derive(Clone, Allocative)]
-pub struct BuckOutPathResolver(ProjectRelativePathBuf);
+pub struct BuckOutPathResolver {
+    buck_out: ProjectRelativePathBuf,
+    scheme: BuckOutPathScheme,
+}
 
 impl BuckOutPathResolver {
     /// creates a 'BuckOutPathResolver' that will resolve outputs to the provided buck-out root.
     /// If not set, buck_out defaults to "buck-out/v2"
     pub fn new(buck_out: ProjectRelativePathBuf) -> Self {
-        BuckOutPathResolver(buck_out)
+        Self::with_scheme(buck_out, BuckOutPathScheme::default())
+    }
+
+    pub fn with_scheme(buck_out: ProjectRelativePathBuf, scheme: BuckOutPathScheme) -> Self {
+        BuckOutPathResolver { buck_out, scheme }
     }
 
     /// Returns the buck-out root.
     pub fn root(&self) -> &ProjectRelativePath {
-        &self.0
+        &self.buck_out
+    }
+
+    pub fn scheme(&self) -> BuckOutPathScheme {
+        self.scheme
     }
 
     /// Resolves a 'BuckOutPath' into a 'ProjectRelativePath' based on the base
@@ -216,7 +265,7 @@ impl BuckOutPathResolver {
     /// Resolve a test path
     pub fn resolve_test(&self, path: &BuckOutTestPath) -> ProjectRelativePathBuf {
         ProjectRelativePathBuf::from(ForwardRelativePathBuf::concat([
-            self.0.as_forward_relative_path(),
+            self.buck_out.as_forward_relative_path(),
             ForwardRelativePath::new("test").unwrap(),
             &path.base,
             &path.path,
@@ -230,7 +279,69 @@ impl BuckOutPathResolver {
         action_key: Option<&str>,
         path: &ForwardRelativePath,
     ) -> ProjectRelativePathBuf {
-        owner.make_hashed_path(&self.0, prefix, action_key, path)
+        match (self.scheme, owner) {
+            (BuckOutPathScheme::Short, BaseDeferredKey::TargetLabel(target)) => {
+                ProjectRelativePathBuf::unchecked_new(
+                    [
+                        self.buck_out.as_str(),
+                        "/",
+                        prefix.as_str(),
+                        "/",
+                        &Self::short_dir(target, action_key),
+                        "/",
+                        path.as_str(),
+                    ]
+                    .concat(),
+                )
+            }
+            _ => owner.make_hashed_path(&self.buck_out, prefix, action_key, path),
+        }
+    }
+
+    /// `<configuration hash>/<target hash>` of the outputs of `target` in the short scheme.
+    fn short_dir(target: &ConfiguredTargetLabel, action_key: Option<&str>) -> String {
+        // Unlike the configuration hash, this is stable across versions of buck2, so that the
+        // mapping files stay valid.
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(target.unconfigured().to_string().as_bytes());
+        if let Some(action_key) = action_key {
+            hasher.update(b"\0");
+            hasher.update(action_key.as_bytes());
+        }
+        let hash = hasher.finalize().to_hex();
+        let mut dir = target.cfg().output_hash().as_str().to_owned();
+        if let Some(exec_cfg) = target.exec_cfg() {
+            dir.push('-');
+            dir.push_str(exec_cfg.output_hash().as_str());
+        }
+        dir.push('/');
+        dir.push_str(&hash[..SHORT_TARGET_HASH_LEN]);
+        dir
+    }
+
+    /// In the short scheme, the mapping file recording which target owns the outputs at
+    /// `<configuration hash>/<target hash>`, and its contents. One file is written per directory,
+    /// so that concurrent actions don't need to coordinate.
+    pub fn short_path_mapping(
+        &self,
+        owner: &BaseDeferredKey,
+        action_key: Option<&str>,
+    ) -> Option<(ProjectRelativePathBuf, String)> {
+        match (self.scheme, owner) {
+            (BuckOutPathScheme::Short, BaseDeferredKey::TargetLabel(target)) => {
+                let path = ProjectRelativePathBuf::unchecked_new(format!(
+                    "{}/short-paths/{}",
+                    self.buck_out,
+                    Self::short_dir(target, action_key)
+                ));
+                let contents = match action_key {
+                    Some(action_key) => format!("{} (action {})\n", target, action_key),
+                    None => format!("{}\n", target),
+                };
+                Some((path, contents))
+            }
+            _ => None,
+        }
     }
 
     /// This function returns the exact location of the symlink of a given target.
@@ -239,7 +350,7 @@ impl BuckOutPathResolver {
     pub fn unhashed_gen(&self, path: &BuckOutPath) -> Option<ProjectRelativePathBuf> {
         Some(ProjectRelativePathBuf::from(
             ForwardRelativePathBuf::concat([
-                self.0.as_ref(),
+                self.buck_out.as_ref(),
                 ForwardRelativePath::unchecked_new("gen"),
                 &path.0.owner.make_unhashed_path()?,
                 path.path(),
@@ -266,6 +377,7 @@ mod tests {
     use crate::configuration::data::ConfigurationData;
     use crate::fs::buck_out_path::BuckOutPath;
     use crate::fs::buck_out_path::BuckOutPathResolver;
+    use crate::fs::buck_out_path::BuckOutPathScheme;
     use crate::fs::buck_out_path::BuckOutScratchPath;
     use crate::fs::paths::forward_rel_path::ForwardRelativePathBuf;
     use crate::fs::project_rel_path::ProjectRelativePathBuf;
@@ -390,6 +502,58 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn buck_target_output_path_resolves_short() -> anyhow::Result<()> {
+        let path_resolver = BuckOutPathResolver::with_scheme(
+            ProjectRelativePathBuf::unchecked_new("buck-out".into()),
+            BuckOutPathScheme::Short,
+        );
+
+        let pkg = PackageLabel::new(
+            CellName::testing_new("foo"),
+            CellRelativePath::unchecked_new("baz-package/with/a/deep/path"),
+        );
+        let target = TargetLabel::new(pkg, TargetNameRef::unchecked_new("target-name"));
+        let owner =
+            BaseDeferredKey::TargetLabel(target.configure(ConfigurationData::testing_new()));
+
+        let resolved = path_resolver.resolve_gen(&BuckOutPath::new(
+            owner.dupe(),
+            ForwardRelativePathBuf::unchecked_new("quux".to_owned()),
+        ));
+        let re = Regex::new("^buck-out/gen/[0-9a-f]{16}/[0-9a-f]{16}/quux$")?;
+        assert!(
+            re.is_match(resolved.as_str()),
+            "{}.is_match({})",
+            re,
+            resolved
+        );
+
+        let with_action_key = path_resolver.resolve_gen(&BuckOutPath::with_action_key(
+            owner.dupe(),
+            ForwardRelativePathBuf::unchecked_new("quux".to_owned()),
+            Some(Arc::from("xxx")),
+        ));
+        assert!(re.is_match(with_action_key.as_str()));
+        assert_ne!(resolved, with_action_key);
+
+        let (mapping, contents) = path_resolver.short_path_mapping(&owner, None).unwrap();
+        assert_eq!(
+            resolved
+                .as_str()
+                .replace("buck-out/gen/", "buck-out/short-paths/"),
+            format!("{}/quux", mapping)
+        );
+        assert!(contents.starts_with("foo//baz-package/with/a/deep/path:target-name ("));
+
+        assert!(
+            BuckOutPathResolver::new(ProjectRelativePathBuf::unchecked_new("buck-out".into()))
+                .short_path_mapping(&owner, None)
+                .is_none()
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_scratch_path_is_sensible() {
         let pkg = PackageLabel::new(
@@ -0,0 +1,58 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Detection of paths which differ only by case. They are the same file on case-insensitive file
+//! systems (the default on macOS and Windows), where they make builds fail in confusing ways.
+//!
+//! Checked when listing packages and declaring artifacts if `buck2.check_case_collisions` is set,
+//! which defaults to whether the file systems of the host are usually case-insensitive.
+
+use std::collections::HashMap;
+
+pub const CONFIG_SECTION: &str = "buck2";
+pub const CONFIG_KEY: &str = "check_case_collisions";
+
+pub fn enabled_by_default() -> bool {
+    cfg!(any(target_os = "macos", windows))
+}
+
+/// The key under which names which differ only by case are the same.
+pub fn case_fold(name: &str) -> String {
+    name.to_lowercase()
+}
+
+/// The first pair of names which differ only by case.
+pub fn find_case_collision<'a>(
+    names: impl IntoIterator<Item = &'a str>,
+) -> Option<(&'a str, &'a str)> {
+    let mut seen = HashMap::new();
+    for name in names {
+        if let Some(other) = seen.insert(case_fold(name), name) {
+            if other != name {
+                return Some((other, name));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_case_collision() {
+        assert_eq!(None, find_case_collision(["a", "b", "BUCK"]));
+        assert_eq!(
+            Some(("Makefile", "makefile")),
+            find_case_collision(["Makefile", "a", "makefile"])
+        );
+        assert_eq!(Some(("Ä", "ä")), find_case_collision(["Ä", "ä"]));
+    }
+}
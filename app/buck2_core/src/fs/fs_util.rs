@@ -48,7 +48,7 @@ where
 
 #[cfg(unix)]
 fn symlink_impl(original: &Path, link: &AbsPath) -> anyhow::Result<()> {
-    std::os::unix::fs::symlink(original, link.as_maybe_relativized()).map_err(|e| e.into())
+    std::os::unix::fs::symlink(original, link.as_os_path()).map_err(|e| e.into())
 }
 
 /// Create symlink on Windows.
@@ -58,20 +58,29 @@ fn symlink_impl(original: &Path, link: &AbsPath) -> anyhow::Result<()> {
 
     use common_path::common_path;
 
+    use crate::fs::windows_paths;
+
     fn permission_check(result: io::Result<()>) -> anyhow::Result<()> {
         match result {
             // Standard issue on Windows machines, so hint at the resolution, as it is not obvious.
             // Unfortunately this doesn't have an `ErrorKind`, so have to do it with substring matching.
-            Err(e) if e.to_string().contains("privilege is not held") => Err(anyhow::anyhow!(e)
-                .context(
-                    "Perhaps you need to turn on 'Developer Mode' in Windows to enable symlinks.",
-                )),
+            Err(e) if is_privilege_error(&e) => Err(anyhow::anyhow!(e).context(
+                "Perhaps you need to turn on 'Developer Mode' in Windows to enable symlinks, \
+                or set `buck2.windows_junctions` to use junctions for symlinks to directories.",
+            )),
             Err(e) => Err(e.into()),
             Ok(_) => Ok(()),
         }
     }
 
+    fn is_privilege_error(e: &io::Error) -> bool {
+        e.to_string().contains("privilege is not held")
+    }
+
     let link = link.as_path();
+    // The path used to create the link, which may need the extended-length prefix.
+    let os_link =
+        windows_paths::maybe_extended_length(link).map_or(Cow::Borrowed(link), Cow::Owned);
 
     // If original is a relative path, fix it up to be absolute
     let target_abspath = if original.is_absolute() {
@@ -112,13 +121,22 @@ fn symlink_impl(original: &Path, link: &AbsPath) -> anyhow::Result<()> {
     let target_metadata = target_canonical.metadata();
     match target_metadata {
         Ok(meta) if meta.is_dir() => {
-            permission_check(std::os::windows::fs::symlink_dir(&target_canonical, link))
+            match std::os::windows::fs::symlink_dir(&target_canonical, &os_link) {
+                Err(e) if is_privilege_error(&e) && windows_paths::junctions_enabled() => {
+                    // Junctions don't need the privilege.
+                    permission_check(windows_paths::create_junction(&target_canonical, &os_link))
+                }
+                res => permission_check(res),
+            }
         }
         Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
         _ => {
             // Either file or not existent. Default to file.
             // TODO(T144443238): This will cause issues if the file type turns out to be directory, fix this
-            permission_check(std::os::windows::fs::symlink_file(&target_canonical, link))
+            permission_check(std::os::windows::fs::symlink_file(
+                &target_canonical,
+                &os_link,
+            ))
         }
     }
 }
@@ -131,14 +149,14 @@ pub fn set_current_dir<P: AsRef<AbsPath>>(path: P) -> anyhow::Result<()> {
 
 pub fn create_dir_all<P: AsRef<AbsPath>>(path: P) -> anyhow::Result<()> {
     let _guard = IoCounterKey::MkDir.guard();
-    fs::create_dir_all(path.as_ref().as_maybe_relativized())
+    fs::create_dir_all(path.as_ref().as_os_path())
         .with_context(|| format!("create_dir_all({})", P::as_ref(&path).display()))?;
     Ok(())
 }
 
 pub fn create_dir<P: AsRef<AbsPath>>(path: P) -> anyhow::Result<()> {
     let _guard = IoCounterKey::MkDir.guard();
-    fs::create_dir(path.as_ref().as_maybe_relativized())
+    fs::create_dir(path.as_ref().as_os_path())
         .with_context(|| format!("create_dir({})", P::as_ref(&path).display()))?;
     Ok(())
 }
@@ -149,7 +167,7 @@ pub fn create_dir<P: AsRef<AbsPath>>(path: P) -> anyhow::Result<()> {
 pub fn create_dir_if_not_exists<P: AsRef<AbsPath>>(path: P) -> anyhow::Result<()> {
     let path = path.as_ref();
     let _guard = IoCounterKey::MkDir.guard();
-    let e = match fs::create_dir(path.as_maybe_relativized())
+    let e = match fs::create_dir(path.as_os_path())
         .with_context(|| format!("create_dir({})", path.display()))
     {
         Ok(()) => return Ok(()),
@@ -233,13 +251,13 @@ pub fn read_dir_if_exists<P: AsRef<AbsNormPath>>(path: P) -> anyhow::Result<Opti
 
 pub fn try_exists<P: AsRef<AbsPath>>(path: P) -> anyhow::Result<bool> {
     let _guard = IoCounterKey::Stat.guard();
-    fs::try_exists(path.as_ref().as_maybe_relativized())
+    fs::try_exists(path.as_ref().as_os_path())
         .with_context(|| format!("try_exists({})", P::as_ref(&path).display()))
 }
 
 pub fn remove_file<P: AsRef<AbsPath>>(path: P) -> anyhow::Result<()> {
     let _guard = IoCounterKey::Remove.guard();
-    remove_file_impl(path.as_ref().as_maybe_relativized())
+    remove_file_impl(path.as_ref().as_os_path())
         .with_context(|| format!("remove_file({})", P::as_ref(&path).display()))
 }
 
@@ -264,11 +282,7 @@ fn remove_file_impl(path: &Path) -> anyhow::Result<()> {
 
 pub fn copy<P: AsRef<AbsPath>, Q: AsRef<AbsPath>>(from: P, to: Q) -> anyhow::Result<u64> {
     let _guard = IoCounterKey::Copy.guard();
-    fs::copy(
-        from.as_ref().as_maybe_relativized(),
-        to.as_ref().as_maybe_relativized(),
-    )
-    .with_context(|| {
+    fs::copy(from.as_ref().as_os_path(), to.as_ref().as_os_path()).with_context(|| {
         format!(
             "copy(from={}, to={})",
             P::as_ref(&from).display(),
@@ -279,17 +293,13 @@ pub fn copy<P: AsRef<AbsPath>, Q: AsRef<AbsPath>>(from: P, to: Q) -> anyhow::Res
 
 pub fn read_link<P: AsRef<AbsPath>>(path: P) -> anyhow::Result<PathBuf> {
     let _guard = IoCounterKey::ReadLink.guard();
-    fs::read_link(path.as_ref().as_maybe_relativized())
+    fs::read_link(path.as_ref().as_os_path())
         .with_context(|| format!("read_link({})", P::as_ref(&path).display()))
 }
 
 pub fn rename<P: AsRef<AbsPath>, Q: AsRef<AbsPath>>(from: P, to: Q) -> anyhow::Result<()> {
     let _guard = IoCounterKey::Rename.guard();
-    fs::rename(
-        from.as_ref().as_maybe_relativized(),
-        to.as_ref().as_maybe_relativized(),
-    )
-    .with_context(|| {
+    fs::rename(from.as_ref().as_os_path(), to.as_ref().as_os_path()).with_context(|| {
         format!(
             "rename(from={}, to={})",
             P::as_ref(&from).display(),
@@ -301,26 +311,26 @@ pub fn rename<P: AsRef<AbsPath>, Q: AsRef<AbsPath>>(from: P, to: Q) -> anyhow::R
 
 pub fn write<P: AsRef<AbsPath>, C: AsRef<[u8]>>(path: P, contents: C) -> anyhow::Result<()> {
     let _guard = IoCounterKey::Write.guard();
-    fs::write(path.as_ref().as_maybe_relativized(), &contents)
+    fs::write(path.as_ref().as_os_path(), &contents)
         .with_context(|| format!("write({}, _)", P::as_ref(&path).display()))?;
     Ok(())
 }
 
 pub fn metadata<P: AsRef<AbsPath>>(path: P) -> anyhow::Result<fs::Metadata> {
     let _guard = IoCounterKey::Stat.guard();
-    fs::metadata(path.as_ref().as_maybe_relativized())
+    fs::metadata(path.as_ref().as_os_path())
         .with_context(|| format!("metadata({})", P::as_ref(&path).display()))
 }
 
 pub fn symlink_metadata<P: AsRef<AbsPath>>(path: P) -> anyhow::Result<fs::Metadata> {
     let _guard = IoCounterKey::Stat.guard();
-    fs::symlink_metadata(path.as_ref().as_maybe_relativized())
+    fs::symlink_metadata(path.as_ref().as_os_path())
         .with_context(|| format!("symlink_metadata({})", P::as_ref(&path).display()))
 }
 
 pub fn set_permissions<P: AsRef<AbsPath>>(path: P, perm: fs::Permissions) -> anyhow::Result<()> {
     let _guard = IoCounterKey::Chmod.guard();
-    fs::set_permissions(path.as_ref().as_maybe_relativized(), perm)
+    fs::set_permissions(path.as_ref().as_os_path(), perm)
         .with_context(|| format!("set_permissions({}, _)", P::as_ref(&path).display()))?;
     Ok(())
 }
@@ -349,7 +359,7 @@ pub fn set_executable<P: AsRef<AbsPath>>(path: P) -> anyhow::Result<()> {
 
 pub fn remove_dir_all<P: AsRef<AbsPath>>(path: P) -> anyhow::Result<()> {
     let _guard = IoCounterKey::RmDirAll.guard();
-    fs::remove_dir_all(path.as_ref().as_maybe_relativized())
+    fs::remove_dir_all(path.as_ref().as_os_path())
         .with_context(|| format!("remove_dir_all({})", P::as_ref(&path).display()))?;
     Ok(())
 }
@@ -359,7 +369,7 @@ pub fn symlink_metadata_if_exists<P: AsRef<AbsPath>>(
     path: P,
 ) -> anyhow::Result<Option<fs::Metadata>> {
     let _guard = IoCounterKey::Stat.guard();
-    match fs::symlink_metadata(path.as_ref().as_maybe_relativized()) {
+    match fs::symlink_metadata(path.as_ref().as_os_path()) {
         Ok(metadata) => Ok(Some(metadata)),
         Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
         Err(err) => {
@@ -371,7 +381,7 @@ pub fn symlink_metadata_if_exists<P: AsRef<AbsPath>>(
 /// Like fs::exists but gives you the metadata. More efficient than calling `symlink_metadata().ok()` (no anyhow, no backtrace) or `exists()` (one stat call).
 pub fn symlink_metadata_if_available<P: AsRef<AbsPath>>(path: P) -> Option<fs::Metadata> {
     let _guard = IoCounterKey::Stat.guard();
-    fs::symlink_metadata(path.as_ref().as_maybe_relativized()).ok()
+    fs::symlink_metadata(path.as_ref().as_os_path()).ok()
 }
 
 /// Remove whatever exists at `path`, be it a file, directory, pipe, broken symlink, etc.
@@ -399,20 +409,20 @@ pub fn remove_all<P: AsRef<AbsPath>>(path: P) -> anyhow::Result<()> {
 
 pub fn read<P: AsRef<AbsPath>>(path: P) -> anyhow::Result<Vec<u8>> {
     let _guard = IoCounterKey::Read.guard();
-    fs::read(path.as_ref().as_maybe_relativized())
+    fs::read(path.as_ref().as_os_path())
         .with_context(|| format!("read({})", P::as_ref(&path).display()))
 }
 
 pub fn read_to_string<P: AsRef<AbsPath>>(path: P) -> anyhow::Result<String> {
     let _guard = IoCounterKey::Read.guard();
-    fs::read_to_string(path.as_ref().as_maybe_relativized())
+    fs::read_to_string(path.as_ref().as_os_path())
         .with_context(|| format!("read_to_string({})", P::as_ref(&path).display()))
 }
 
 /// Read a file, if it exists. Returns `None` when the file does not exist.
 pub fn read_to_string_if_exists<P: AsRef<AbsPath>>(path: P) -> anyhow::Result<Option<String>> {
     let _guard = IoCounterKey::Read.guard();
-    match fs::read_to_string(path.as_ref().as_maybe_relativized()) {
+    match fs::read_to_string(path.as_ref().as_os_path()) {
         Ok(d) => Ok(Some(d)),
         Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
         Err(e) => Err(anyhow::Error::from(e).context(format!(
@@ -425,7 +435,7 @@ pub fn read_to_string_if_exists<P: AsRef<AbsPath>>(path: P) -> anyhow::Result<Op
 /// Read a file, if it exists. Returns `None` when the file does not exist.
 pub fn read_if_exists<P: AsRef<AbsPath>>(path: P) -> anyhow::Result<Option<Vec<u8>>> {
     let _guard = IoCounterKey::Read.guard();
-    match fs::read(path.as_ref().as_maybe_relativized()) {
+    match fs::read(path.as_ref().as_os_path()) {
         Ok(d) => Ok(Some(d)),
         Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
         Err(e) => Err(anyhow::Error::from(e)
@@ -461,7 +471,7 @@ pub fn simplified(path: &AbsPath) -> anyhow::Result<&AbsPath> {
 
 pub fn remove_dir<P: AsRef<AbsPath>>(path: P) -> anyhow::Result<()> {
     let _guard = IoCounterKey::RmDir.guard();
-    fs::remove_dir(path.as_ref().as_maybe_relativized())
+    fs::remove_dir(path.as_ref().as_os_path())
         .with_context(|| format!("remove_dir({})", P::as_ref(&path).display()))
 }
 
@@ -482,7 +492,7 @@ impl Write for FileWriteGuard {
 
 pub fn create_file<P: AsRef<AbsPath>>(path: P) -> anyhow::Result<FileWriteGuard> {
     let guard = IoCounterKey::Write.guard();
-    let file = File::create(path.as_ref().as_maybe_relativized())
+    let file = File::create(path.as_ref().as_os_path())
         .with_context(|| format!("create_file({})", P::as_ref(&path).display()))?;
     Ok(FileWriteGuard {
         file,
@@ -503,7 +513,7 @@ impl Read for FileReadGuard {
 
 pub fn open_file<P: AsRef<AbsPath>>(path: P) -> anyhow::Result<FileReadGuard> {
     let guard = IoCounterKey::Read.guard();
-    let file = File::open(path.as_ref().as_maybe_relativized())
+    let file = File::open(path.as_ref().as_os_path())
         .with_context(|| format!("open_file({})", P::as_ref(&path).display()))?;
     Ok(FileReadGuard {
         file,
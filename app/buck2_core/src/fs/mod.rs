@@ -10,9 +10,11 @@
 pub mod artifact_path_resolver;
 pub mod async_fs_util;
 pub mod buck_out_path;
+pub mod case_collisions;
 pub mod cwd;
 pub mod fs_util;
 pub mod paths;
 pub mod project;
 pub mod project_rel_path;
+pub mod windows_paths;
 pub mod working_dir;
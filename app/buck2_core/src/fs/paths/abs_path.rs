@@ -8,6 +8,7 @@
  */
 
 use std::borrow::Borrow;
+use std::borrow::Cow;
 use std::ffi::OsString;
 use std::fmt;
 use std::ops::Deref;
@@ -20,6 +21,7 @@ use derive_more::Display;
 use ref_cast::RefCast;
 
 use crate::fs::cwd;
+use crate::fs::windows_paths;
 
 #[derive(buck2_error::Error, Debug)]
 enum AbsPathError {
@@ -157,6 +159,12 @@ impl AbsPath {
         cwd::maybe_relativize(&self.0)
     }
 
+    /// The path to pass to the OS: relativized like `as_maybe_relativized`, or on Windows, with
+    /// the extended-length prefix if it's too long and `buck2.windows_long_paths` is set.
+    pub fn as_os_path(&self) -> Cow<'_, Path> {
+        windows_paths::os_path(&self.0, self.as_maybe_relativized())
+    }
+
     pub fn as_maybe_relativized_str(&self) -> anyhow::Result<&str> {
         Ok(cwd::maybe_relativize_str(self.to_str()?))
     }
@@ -0,0 +1,288 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Workarounds for the limits of Windows file systems, set by buckconfigs:
+//!
+//! * `buck2.windows_long_paths`: paths longer than `MAX_PATH` are passed to the OS with the
+//!   `\\?\` extended-length prefix, which lifts the limit.
+//! * `buck2.windows_junctions`: when creating a symlink to a directory fails because creating
+//!   symlinks needs a privilege (Developer Mode is off), a directory junction is created
+//!   instead. Junctions always point to absolute paths, and only work for directories.
+//!
+//! Both are no-ops on other platforms. They are configured per command, so the options used
+//! are those of the command the current task runs for. Outside of a command, both are off.
+
+use std::borrow::Cow;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use buck2_util::late_binding::LateBinding;
+
+/// The Windows path options of the command the current task runs for, if any.
+pub static CURRENT_COMMAND_WINDOWS_PATH_OPTIONS: LateBinding<
+    fn() -> Option<Arc<WindowsPathOptions>>,
+> = LateBinding::new("CURRENT_COMMAND_WINDOWS_PATH_OPTIONS");
+
+/// The Windows path options of a command, set from its buckconfigs.
+#[derive(Default)]
+pub struct WindowsPathOptions {
+    long_paths: AtomicBool,
+    junctions: AtomicBool,
+}
+
+impl WindowsPathOptions {
+    pub fn set(&self, long_paths: bool, junctions: bool) {
+        self.long_paths.store(long_paths, Ordering::Relaxed);
+        self.junctions.store(junctions, Ordering::Relaxed);
+    }
+
+    fn long_paths(&self) -> bool {
+        self.long_paths.load(Ordering::Relaxed)
+    }
+
+    fn junctions(&self) -> bool {
+        self.junctions.load(Ordering::Relaxed)
+    }
+}
+
+fn current_options() -> Option<Arc<WindowsPathOptions>> {
+    CURRENT_COMMAND_WINDOWS_PATH_OPTIONS
+        .get()
+        .ok()
+        .and_then(|current| current())
+}
+
+/// Paths at least this long can't be used without the extended-length prefix. `MAX_PATH` is
+/// 260, but directories are limited to 248 characters, to leave room for a file name.
+const MAX_PATH_WITHOUT_PREFIX: usize = 248;
+
+pub(crate) fn junctions_enabled() -> bool {
+    cfg!(windows) && current_options().map_or(false, |options| options.junctions())
+}
+
+/// `path`, with the extended-length prefix if it is needed and enabled.
+pub fn maybe_extended_length(path: &Path) -> Option<PathBuf> {
+    if !cfg!(windows) || !current_options().map_or(false, |options| options.long_paths()) {
+        return None;
+    }
+    extended_length_if_too_long(path)
+}
+
+fn extended_length_if_too_long(path: &Path) -> Option<PathBuf> {
+    let path = path.to_str()?;
+    if path.len() < MAX_PATH_WITHOUT_PREFIX {
+        return None;
+    }
+    extended_length(path).map(PathBuf::from)
+}
+
+/// Add the extended-length prefix to an absolute path. Paths given this way are not normalized
+/// by Windows, so they are normalized here: separators are made backslashes, and `.`, `..` and
+/// repeated separators are resolved. `None` for paths which have a prefix already, or which are
+/// not absolute.
+fn extended_length(path: &str) -> Option<String> {
+    if path.starts_with(r"\\?\") || path.starts_with(r"\\.\") {
+        return None;
+    }
+    let path = path.replace('/', "\\");
+    if let Some(unc) = path.strip_prefix(r"\\") {
+        // `..` can't go above the share.
+        let mut parts = unc.splitn(3, '\\');
+        let (server, share) = (parts.next()?, parts.next()?);
+        return Some(format!(
+            r"\\?\UNC\{}\{}{}",
+            server,
+            share,
+            normalize_components(parts.next().unwrap_or_default())
+        ));
+    }
+    let bytes = path.as_bytes();
+    if bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && &bytes[1..3] == b":\\" {
+        return Some(format!(
+            r"\\?\{}{}",
+            &path[..2],
+            normalize_components(&path[3..])
+        ));
+    }
+    None
+}
+
+/// The components of a relative path with backslash separators, with `.`, `..` and empty
+/// components resolved, each preceded by a backslash.
+fn normalize_components(path: &str) -> String {
+    let mut components = Vec::new();
+    for component in path.split('\\') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            c => components.push(c),
+        }
+    }
+    let mut normalized = String::with_capacity(path.len() + 1);
+    for component in components {
+        normalized.push('\\');
+        normalized.push_str(component);
+    }
+    if normalized.is_empty() {
+        normalized.push('\\');
+    }
+    normalized
+}
+
+/// The path to pass to the OS for `path`: `relativized`, unless it's too long to be used
+/// without the extended-length prefix.
+pub(crate) fn os_path<'a>(path: &'a Path, relativized: &'a Path) -> Cow<'a, Path> {
+    match maybe_extended_length(path) {
+        Some(extended) => Cow::Owned(extended),
+        None => Cow::Borrowed(relativized),
+    }
+}
+
+/// Create a directory junction at `link` pointing to the absolute path `target`.
+#[cfg(windows)]
+pub(crate) fn create_junction(target: &Path, link: &Path) -> std::io::Result<()> {
+    use std::ffi::OsStr;
+    use std::fs;
+    use std::io;
+    use std::os::windows::ffi::OsStrExt;
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::os::windows::io::AsRawHandle;
+    use std::ptr;
+
+    use winapi::um::ioapiset::DeviceIoControl;
+    use winapi::um::winbase::FILE_FLAG_BACKUP_SEMANTICS;
+    use winapi::um::winbase::FILE_FLAG_OPEN_REPARSE_POINT;
+    use winapi::um::winioctl::FSCTL_SET_REPARSE_POINT;
+    use winapi::um::winnt::IO_REPARSE_TAG_MOUNT_POINT;
+
+    let target = target.to_str().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, "junction target is not UTF-8")
+    })?;
+    let target = target.strip_prefix(r"\\?\").unwrap_or(target);
+    // The substitute name is the NT path of the target, the print name is what users see.
+    let substitute: Vec<u16> = OsStr::new(&format!(r"\??\{}", target))
+        .encode_wide()
+        .collect();
+    let print: Vec<u16> = OsStr::new(target).encode_wide().collect();
+
+    // `REPARSE_DATA_BUFFER` holding a `MountPointReparseBuffer`, whose path buffer holds both
+    // names, each followed by a NUL.
+    let path_buffer_len = (substitute.len() + print.len() + 2) * 2;
+    let mut data = Vec::with_capacity(16 + path_buffer_len);
+    data.extend(IO_REPARSE_TAG_MOUNT_POINT.to_le_bytes());
+    data.extend(
+        u16::try_from(8 + path_buffer_len)
+            .map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidInput, "junction target is too long")
+            })?
+            .to_le_bytes(),
+    );
+    data.extend(0u16.to_le_bytes());
+    data.extend(0u16.to_le_bytes());
+    data.extend(((substitute.len() * 2) as u16).to_le_bytes());
+    data.extend((((substitute.len() + 1) * 2) as u16).to_le_bytes());
+    data.extend(((print.len() * 2) as u16).to_le_bytes());
+    for c in substitute.iter().chain([&0]).chain(&print).chain([&0]) {
+        data.extend(c.to_le_bytes());
+    }
+
+    fs::create_dir(link)?;
+    let res = fs::OpenOptions::new()
+        .write(true)
+        .custom_flags(FILE_FLAG_OPEN_REPARSE_POINT | FILE_FLAG_BACKUP_SEMANTICS)
+        .open(link)
+        .and_then(|dir| {
+            let mut returned = 0;
+            // SAFETY: the buffer is valid for its length, and no output buffer is used.
+            let ok = unsafe {
+                DeviceIoControl(
+                    dir.as_raw_handle() as _,
+                    FSCTL_SET_REPARSE_POINT,
+                    data.as_mut_ptr() as _,
+                    data.len() as u32,
+                    ptr::null_mut(),
+                    0,
+                    &mut returned,
+                    ptr::null_mut(),
+                )
+            };
+            if ok == 0 {
+                Err(io::Error::last_os_error())
+            } else {
+                Ok(())
+            }
+        });
+    if res.is_err() {
+        let _ignore = fs::remove_dir(link);
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extended_length() {
+        assert_eq!(
+            Some(r"\\?\C:\foo\bar".to_owned()),
+            extended_length(r"C:\foo/bar")
+        );
+        assert_eq!(
+            Some(r"\\?\UNC\server\share\foo".to_owned()),
+            extended_length(r"\\server\share\foo")
+        );
+        assert_eq!(None, extended_length(r"\\?\C:\foo"));
+        assert_eq!(
+            Some(r"\\?\C:\foo\baz".to_owned()),
+            extended_length(r"C:\foo\.\bar\..\\baz\")
+        );
+        assert_eq!(
+            Some(r"\\?\C:\".to_owned()),
+            extended_length(r"C:\foo\..\..")
+        );
+        assert_eq!(
+            Some(r"\\?\UNC\server\share\bar".to_owned()),
+            extended_length(r"\\server\share\..\foo/../bar")
+        );
+        assert_eq!(None, extended_length(r"foo\bar"));
+        assert_eq!(None, extended_length("/foo/bar"));
+    }
+
+    #[test]
+    fn test_maybe_extended_length_disabled() {
+        let long = format!(r"C:\{}", "a".repeat(300));
+        assert_eq!(None, maybe_extended_length(Path::new(&long)));
+    }
+
+    #[test]
+    fn test_extended_length_if_too_long() {
+        let long = format!(r"C:\{}", "a".repeat(300));
+        assert_eq!(
+            Some(PathBuf::from(format!(r"\\?\{}", long))),
+            extended_length_if_too_long(Path::new(&long))
+        );
+        assert_eq!(None, extended_length_if_too_long(Path::new(r"C:\foo")));
+    }
+
+    #[test]
+    fn test_options_default_off() {
+        let options = WindowsPathOptions::default();
+        assert!(!options.long_paths());
+        assert!(!options.junctions());
+        options.set(true, false);
+        assert!(options.long_paths());
+        assert!(!options.junctions());
+    }
+}
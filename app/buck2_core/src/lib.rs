@@ -34,6 +34,7 @@ pub mod bzl;
 pub mod category;
 pub mod cells;
 pub mod configuration;
+pub mod deprecation;
 pub mod directory;
 pub mod env;
 pub mod execution_types;
@@ -7,18 +7,37 @@
  * of this source tree.
  */
 
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::MutexGuard;
 use std::sync::Once;
 
+use buck2_core::deprecation::Deprecation;
 use buck2_core::error::initialize;
 use buck2_core::error::reset_soft_error_counters;
+use buck2_core::error::CommandSoftErrors;
+use buck2_core::error::SoftErrorConfig;
 use buck2_core::error::StructuredErrorOptions;
+use buck2_core::error::CURRENT_COMMAND_SOFT_ERRORS;
 use buck2_core::is_open_source;
 use buck2_core::soft_error;
 
 static RESULT: Mutex<Vec<String>> = Mutex::new(Vec::new());
 
+thread_local! {
+    /// Stands in for the ambient event dispatcher of the daemon.
+    static CURRENT_COMMAND: RefCell<Option<Arc<CommandSoftErrors>>> = RefCell::new(None);
+}
+
+fn with_command<R>(command: &Arc<CommandSoftErrors>, f: impl FnOnce() -> R) -> R {
+    CURRENT_COMMAND.with(|current| *current.borrow_mut() = Some(command.clone()));
+    let res = f();
+    CURRENT_COMMAND.with(|current| *current.borrow_mut() = None);
+    res
+}
+
 fn mock_handler(
     category: &str,
     err: &anyhow::Error,
@@ -40,6 +59,8 @@ fn test_init() -> MutexGuard<'static, ()> {
     static ONCE: Once = Once::new();
     ONCE.call_once(|| {
         initialize(Box::new(mock_handler)).unwrap();
+        CURRENT_COMMAND_SOFT_ERRORS
+            .init(|| CURRENT_COMMAND.with(|current| current.borrow().clone()));
     });
 
     RESULT.lock().unwrap().clear();
@@ -100,3 +121,71 @@ fn test_reset_counters() {
         "Should be logged 10 more times"
     );
 }
+
+#[test]
+fn test_config_is_per_command() -> anyhow::Result<()> {
+    let _guard = test_init();
+
+    let failing = Arc::new(CommandSoftErrors::default());
+    failing.set_config(SoftErrorConfig::from_config_entries([(
+        "test_per_command",
+        "error",
+    )])?);
+    let logging = Arc::new(CommandSoftErrors::default());
+    logging.set_config(SoftErrorConfig::from_config_entries([(
+        "test_per_command",
+        "log",
+    )])?);
+
+    let hit = || soft_error!("test_per_command", anyhow::anyhow!("Message"));
+    assert!(with_command(&failing, hit).is_err());
+    assert!(with_command(&logging, hit).is_ok());
+    assert!(with_command(&logging, hit).is_ok());
+
+    assert_eq!(
+        BTreeMap::from([("test_per_command".to_owned(), 1)]),
+        failing.counts()
+    );
+    assert_eq!(
+        BTreeMap::from([("test_per_command".to_owned(), 2)]),
+        logging.counts()
+    );
+    Ok(())
+}
+
+#[test]
+fn test_deprecations_counted_per_command() -> anyhow::Result<()> {
+    let _guard = test_init();
+
+    let deprecation = Deprecation::new(Some("starlark_deprecated_test"), "Use `bar`", None)?;
+    let config = || SoftErrorConfig::from_config_entries([("starlark_deprecated_test", "log")]);
+    let first = Arc::new(CommandSoftErrors::default());
+    first.set_config(config()?);
+    let second = Arc::new(CommandSoftErrors::default());
+    second.set_config(config()?);
+
+    with_command(&first, || {
+        deprecation.report("`foo`", Some("a/BUCK:1".to_owned()))?;
+        deprecation.report("`foo`", Some("a/BUCK:1".to_owned()))?;
+        deprecation.report("`foo`", None)
+    })?;
+    with_command(&second, || {
+        deprecation.report("`foo`", Some("b/BUCK:2".to_owned()))
+    })?;
+
+    assert_eq!(
+        BTreeMap::from([(
+            "starlark_deprecated_test".to_owned(),
+            BTreeMap::from([("a/BUCK:1".to_owned(), 2), ("".to_owned(), 1)])
+        )]),
+        first.deprecation_call_site_counts()
+    );
+    assert_eq!(
+        BTreeMap::from([(
+            "starlark_deprecated_test".to_owned(),
+            BTreeMap::from([("b/BUCK:2".to_owned(), 1)])
+        )]),
+        second.deprecation_call_site_counts()
+    );
+    Ok(())
+}
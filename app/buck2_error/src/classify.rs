@@ -30,8 +30,11 @@ fn tag_rank(tag: ErrorTag) -> u32 {
         ErrorTag::StarlarkFail => line!(),
         ErrorTag::StarlarkStackOverflow => line!(),
         ErrorTag::Visibility => line!(),
+        ErrorTag::Attribute => line!(),
         ErrorTag::Analysis => line!(),
+        ErrorTag::Bxl => line!(),
         ErrorTag::WatchmanTimeout => line!(),
+        ErrorTag::Offline => line!(),
         ErrorTag::Http => line!(),
         ErrorTag::ServerStderrUnknown => line!(),
         ErrorTag::ServerStderrEmpty => line!(),
@@ -0,0 +1,155 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Stable codes identifying the kind of an error, printed to users with a link to documentation.
+//!
+//! Codes are derived from the tags, type and category of the error. Once published, a code must
+//! keep its meaning: add new codes instead of reusing old ones. Every code must be documented in
+//! `docs/users/error_codes.md`.
+
+use std::fmt;
+
+use crate::classify::best_tag;
+use crate::Category;
+use crate::ErrorTag;
+use crate::ErrorType;
+
+const DOCS_URL: &str = "https://buck2.build/docs/users/error_codes";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ErrorCode(&'static str);
+
+impl ErrorCode {
+    /// Error without a more specific code, caused by the user.
+    pub const USER: ErrorCode = ErrorCode("USER001");
+    /// Error without a more specific code, either caused by buck2 or of unknown origin.
+    pub const INFRA: ErrorCode = ErrorCode("INFRA001");
+
+    fn for_tag(tag: ErrorTag) -> Option<ErrorCode> {
+        let code = match tag {
+            ErrorTag::UnusedDefaultTag => return None,
+            ErrorTag::StarlarkFail => "STARLARK001",
+            ErrorTag::StarlarkStackOverflow => "STARLARK002",
+            ErrorTag::Analysis => "ANALYSIS001",
+            ErrorTag::Visibility => "ANALYSIS002",
+            ErrorTag::Attribute => "ATTR001",
+            ErrorTag::Bxl => "BXL001",
+            ErrorTag::WatchmanTimeout => "WATCHMAN001",
+            ErrorTag::Http => "HTTP001",
+            ErrorTag::Offline => "HTTP002",
+            ErrorTag::ClientGrpc => "CLIENT001",
+            ErrorTag::DaemonConnect => "CLIENT002",
+            ErrorTag::GrpcResponseMessageTooLarge => "CLIENT003",
+            ErrorTag::ServerStderrEmpty => "DAEMON001",
+            ErrorTag::ServerPanicked => "DAEMON002",
+            ErrorTag::ServerStackOverflow => "DAEMON003",
+            ErrorTag::ServerSegv => "DAEMON004",
+            ErrorTag::ServerJemallocAssert => "DAEMON005",
+            ErrorTag::ServerStderrUnknown => "DAEMON006",
+        };
+        Some(ErrorCode(code))
+    }
+
+    fn for_type(typ: ErrorType) -> Option<ErrorCode> {
+        let code = match typ {
+            ErrorType::UnusedDefault => return None,
+            ErrorType::DaemonIsBusy => "CLIENT004",
+            ErrorType::ActionCommandFailure => "ACTION001",
+            ErrorType::Watchman => "WATCHMAN002",
+            ErrorType::UserDeadlineExpired => "CLIENT005",
+            ErrorType::ActionTimeout => "ACTION002",
+        };
+        Some(ErrorCode(code))
+    }
+
+    /// The code of an error with these tags, type and category. The most interesting tag wins,
+    /// then the type, then the category.
+    pub fn new(
+        tags: impl IntoIterator<Item = ErrorTag>,
+        typ: Option<ErrorType>,
+        category: Option<Category>,
+    ) -> ErrorCode {
+        best_tag(tags)
+            .and_then(Self::for_tag)
+            .or_else(|| typ.and_then(Self::for_type))
+            .unwrap_or(match category {
+                Some(Category::User) => Self::USER,
+                Some(Category::Infra) | None => Self::INFRA,
+            })
+    }
+
+    pub fn as_str(self) -> &'static str {
+        self.0
+    }
+
+    /// Link to the documentation of this code.
+    pub fn docs_url(self) -> String {
+        format!("{}#{}", DOCS_URL, self.0.to_lowercase())
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_error_code() {
+        assert_eq!(
+            "ANALYSIS002",
+            ErrorCode::new(
+                [ErrorTag::Analysis, ErrorTag::Visibility],
+                None,
+                Some(Category::User)
+            )
+            .as_str()
+        );
+        assert_eq!(
+            "ACTION001",
+            ErrorCode::new([], Some(ErrorType::ActionCommandFailure), None).as_str()
+        );
+        assert_eq!(
+            "ACTION002",
+            ErrorCode::new([], Some(ErrorType::ActionTimeout), Some(Category::User)).as_str()
+        );
+        assert_eq!(
+            ErrorCode::USER,
+            ErrorCode::new([ErrorTag::UnusedDefaultTag], None, Some(Category::User))
+        );
+        assert_eq!(ErrorCode::INFRA, ErrorCode::new([], None, None));
+        assert_eq!(
+            "https://buck2.build/docs/users/error_codes#bxl001",
+            ErrorCode::new([ErrorTag::Bxl], None, None).docs_url()
+        );
+    }
+
+    #[test]
+    fn test_error_codes_unique() {
+        let mut codes = HashSet::new();
+        for tag in 0..1000 {
+            if let Some(code) = ErrorTag::from_i32(tag).and_then(ErrorCode::for_tag) {
+                assert!(codes.insert(code), "Duplicate code {}", code);
+            }
+        }
+        for typ in 0..100 {
+            if let Some(code) = ErrorType::from_i32(typ).and_then(ErrorCode::for_type) {
+                assert!(codes.insert(code), "Duplicate code {}", code);
+            }
+        }
+        assert!(codes.insert(ErrorCode::USER));
+        assert!(codes.insert(ErrorCode::INFRA));
+    }
+}
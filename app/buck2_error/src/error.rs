@@ -193,6 +193,15 @@ impl Error {
     pub fn best_tag(&self) -> Option<crate::ErrorTag> {
         best_tag(self.tags_unsorted())
     }
+
+    /// The stable code identifying the kind of this error.
+    pub fn error_code(&self) -> crate::ErrorCode {
+        crate::ErrorCode::new(
+            self.tags_unsorted(),
+            self.get_error_type(),
+            self.get_category(),
+        )
+    }
 }
 
 #[cfg(test)]
@@ -14,6 +14,7 @@
 
 mod any;
 pub mod classify;
+mod code;
 mod context;
 mod context_value;
 mod derive_tests;
@@ -24,6 +25,7 @@ mod source_location;
 
 use std::error::Request;
 
+pub use code::ErrorCode;
 pub use context::Context;
 /// A piece of metadata to indicate whether this error is an infra or user error.
 ///
@@ -25,7 +25,9 @@ pub mod read;
 pub mod stream_value;
 pub mod user_event_types;
 pub mod utils;
+pub mod validate;
 pub mod write;
+mod zstd_framed_writer;
 
 pub fn should_upload_log() -> anyhow::Result<bool> {
     if buck2_core::is_open_source() {
@@ -10,8 +10,10 @@
 use std::io;
 use std::pin::Pin;
 use std::str::FromStr;
+use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use std::task::Context;
 use std::task::Poll;
 use std::time::SystemTime;
@@ -26,6 +28,7 @@ use buck2_core::fs::paths::abs_path::AbsPath;
 use buck2_core::fs::paths::abs_path::AbsPathBuf;
 use buck2_events::BuckEvent;
 use buck2_wrapper_common::invocation_id::TraceId;
+use dupe::Dupe;
 use futures::stream::BoxStream;
 use futures::stream::Stream;
 use futures::stream::TryStreamExt;
@@ -89,6 +92,54 @@ mod counting_reader {
 
 use counting_reader::CountingReader;
 
+mod truncation_tolerant_reader {
+    use super::*;
+
+    #[pin_project]
+    pub struct TruncationTolerantReader<T> {
+        #[pin]
+        pub(super) inner: T,
+        pub(super) truncated: Arc<AtomicBool>,
+    }
+}
+
+use truncation_tolerant_reader::TruncationTolerantReader;
+
+/// Ends a compressed stream where it is truncated (e.g. because buck2 crashed while writing the
+/// log, or the log is still being written), instead of failing the read.
+impl<T> AsyncRead for TruncationTolerantReader<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.project();
+        match futures::ready!(this.inner.poll_read(cx, buf)) {
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                this.truncated.store(true, Ordering::Relaxed);
+                Poll::Ready(Ok(()))
+            }
+            res => Poll::Ready(res),
+        }
+    }
+}
+
+/// Ends the stream at the first error after the log was found to be truncated: that error is about
+/// the incomplete last event, and all the events before it are valid.
+fn stop_at_truncation<'a>(
+    events: impl Stream<Item = anyhow::Result<StreamValue>> + Send + 'a,
+    truncated: Arc<AtomicBool>,
+) -> BoxStream<'a, anyhow::Result<StreamValue>> {
+    events
+        .take_while(move |event| {
+            futures::future::ready(event.is_ok() || !truncated.load(Ordering::Relaxed))
+        })
+        .boxed()
+}
+
 impl<'a, T> CountingReader<'a, T> {
     fn new(inner: T, stats: Option<&'a AtomicUsize>) -> Self {
         Self { inner, stats }
@@ -182,7 +233,7 @@ impl EventLogPathBuf {
     ) -> anyhow::Result<(Invocation, BoxStream<'a, anyhow::Result<StreamValue>>)> {
         assert_eq!(self.encoding.mode, LogMode::Json);
 
-        let log_file = self.open(stats).await?;
+        let (log_file, truncated) = self.open(stats).await?;
         let log_file = BufReader::new(log_file);
         let mut log_lines = log_file.lines();
 
@@ -200,7 +251,7 @@ impl EventLogPathBuf {
                 .with_context(|| format!("Invalid line: {}", line.trim_end()))
         });
 
-        Ok((invocation, events.boxed()))
+        Ok((invocation, stop_at_truncation(events, truncated)))
     }
 
     async fn unpack_stream_protobuf<'a>(
@@ -209,7 +260,7 @@ impl EventLogPathBuf {
     ) -> anyhow::Result<(Invocation, BoxStream<'a, anyhow::Result<StreamValue>>)> {
         assert_eq!(self.encoding.mode, LogMode::Protobuf);
 
-        let log_file = self.open(stats).await?;
+        let (log_file, truncated) = self.open(stats).await?;
         let mut stream = FramedRead::new(log_file, ProtobufSplitter);
 
         let invocation = stream.try_next().await?.context("No invocation found")?;
@@ -225,6 +276,7 @@ impl EventLogPathBuf {
                 .transpose()
                 .context("Invalid TraceId")?
                 .unwrap_or_else(TraceId::null),
+            schema_version: invocation.schema_version,
         };
 
         let events = stream.and_then(|data| async move {
@@ -240,7 +292,7 @@ impl EventLogPathBuf {
             }
         });
 
-        Ok((invocation, events.boxed()))
+        Ok((invocation, stop_at_truncation(events, truncated)))
     }
 
     async fn unpack_stream_inner<'a>(
@@ -276,7 +328,11 @@ impl EventLogPathBuf {
         self.unpack_stream_inner(None).await
     }
 
-    async fn open<'a>(&self, stats: Option<&'a ReaderStats>) -> anyhow::Result<EventLogReader<'a>> {
+    /// Open the log, returning a flag set once the compressed stream is found to be truncated.
+    async fn open<'a>(
+        &self,
+        stats: Option<&'a ReaderStats>,
+    ) -> anyhow::Result<(EventLogReader<'a>, Arc<AtomicBool>)> {
         tracing::info!(
             "Open {} using encoding {:?}",
             self.path.display(),
@@ -291,6 +347,7 @@ impl EventLogPathBuf {
             None => (None, None),
         };
 
+        let truncated = Arc::new(AtomicBool::new(false));
         let file = async_fs_util::open(&self.path).await?;
         let file = CountingReader::new(file, compressed_bytes);
         let file = match self.encoding.compression {
@@ -298,16 +355,27 @@ impl EventLogPathBuf {
                 Box::new(CountingReader::new(file, decompressed_bytes)) as EventLogReader
             }
             Compression::Gzip => Box::new(CountingReader::new(
-                GzipDecoder::new(BufReader::new(file)),
-                decompressed_bytes,
-            )) as EventLogReader,
-            Compression::Zstd => Box::new(CountingReader::new(
-                ZstdDecoder::new(BufReader::new(file)),
+                TruncationTolerantReader {
+                    inner: GzipDecoder::new(BufReader::new(file)),
+                    truncated: truncated.dupe(),
+                },
                 decompressed_bytes,
             )) as EventLogReader,
+            Compression::Zstd => {
+                // The log is a sequence of zstd frames, see `ZstdFramedWriter`.
+                let mut decoder = ZstdDecoder::new(BufReader::new(file));
+                decoder.multiple_members(true);
+                Box::new(CountingReader::new(
+                    TruncationTolerantReader {
+                        inner: decoder,
+                        truncated: truncated.dupe(),
+                    },
+                    decompressed_bytes,
+                )) as EventLogReader
+            }
         };
 
-        Ok(file)
+        Ok((file, truncated))
     }
 
     pub async fn get_summary(&self) -> anyhow::Result<EventLogSummary> {
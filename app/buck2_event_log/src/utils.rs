@@ -129,6 +129,10 @@ pub(crate) enum Compression {
     Zstd,
 }
 
+/// The version of the event log format written by this buck2. Bump it when a change makes logs
+/// unreadable by older versions, e.g. an event changing meaning.
+pub const EVENT_LOG_SCHEMA_VERSION: u32 = 1;
+
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
 pub struct Invocation {
     pub command_line_args: Vec<String>,
@@ -140,6 +144,9 @@ pub struct Invocation {
     pub working_dir: String,
     #[serde(default = "TraceId::null")]
     pub trace_id: TraceId,
+    /// See `EVENT_LOG_SCHEMA_VERSION`, 0 for logs written before it was recorded.
+    #[serde(default)]
+    pub schema_version: u32,
 }
 
 impl Invocation {
@@ -182,6 +189,7 @@ mod tests {
             working_dir: "/Users/nga/dir45".to_owned(),
             expanded_command_line_args: Vec::new(),
             trace_id: TraceId::from_str("281d1c16-8930-40cd-8fc1-7d71355c20f5").unwrap(),
+            schema_version: 0,
         };
         assert_eq!(expected, line);
     }
@@ -0,0 +1,279 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Validation of a whole event log, for `buck2 log replay --strict`: check that this buck2 can
+//! read every event of the log, and report precisely where it can't.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use futures::StreamExt;
+
+use crate::read::EventLogPathBuf;
+use crate::stream_value::StreamValue;
+use crate::utils::Invocation;
+use crate::utils::EVENT_LOG_SCHEMA_VERSION;
+
+/// Something in an event log which this buck2 can't make sense of.
+#[derive(Debug, Eq, PartialEq)]
+pub struct EventLogIssue {
+    /// Index of the event in the log, starting from 1 after the header, or `None` for the header
+    /// and for the log as a whole.
+    pub event: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for EventLogIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.event {
+            Some(event) => write!(f, "event {}: {}", event, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Validator {
+    issues: Vec<EventLogIssue>,
+    trace_id: Option<String>,
+    open_spans: HashSet<u64>,
+    /// Index of the command result, which must be the last event.
+    result: Option<usize>,
+}
+
+impl Validator {
+    fn issue(&mut self, event: Option<usize>, message: impl Into<String>) {
+        self.issues.push(EventLogIssue {
+            event,
+            message: message.into(),
+        });
+    }
+
+    fn header(&mut self, invocation: &Invocation) {
+        if invocation.schema_version > EVENT_LOG_SCHEMA_VERSION {
+            self.issue(
+                None,
+                format!(
+                    "The log has schema version {}, but this buck2 only supports up to version {}",
+                    invocation.schema_version, EVENT_LOG_SCHEMA_VERSION
+                ),
+            );
+        }
+    }
+
+    fn value(&mut self, index: usize, value: anyhow::Result<StreamValue>) {
+        let index = Some(index);
+        let value = match value {
+            Ok(value) => value,
+            Err(e) => return self.issue(index, format!("{:#}", e)),
+        };
+        if let Some(result) = self.result {
+            self.issue(
+                index,
+                format!("Found after the command result (event {})", result),
+            );
+        }
+        match value {
+            StreamValue::Result(result) => {
+                if result.result.is_none() {
+                    self.issue(index, "Unknown command result type");
+                }
+                self.result = self.result.or(index);
+            }
+            StreamValue::PartialResult(result) => {
+                if result.partial_result.is_none() {
+                    self.issue(index, "Unknown partial result type");
+                }
+            }
+            StreamValue::Event(event) => self.event(index, &event),
+        }
+    }
+
+    fn event(&mut self, index: Option<usize>, event: &buck2_data::BuckEvent) {
+        if event.timestamp.is_none() {
+            self.issue(index, "Missing timestamp");
+        }
+        match &self.trace_id {
+            None => self.trace_id = Some(event.trace_id.clone()),
+            Some(trace_id) if *trace_id != event.trace_id => {
+                let message = format!(
+                    "Trace ID `{}` differs from the one of the first event `{}`",
+                    event.trace_id, trace_id
+                );
+                self.issue(index, message);
+            }
+            Some(_) => {}
+        }
+
+        match &event.data {
+            None => self.issue(index, "Unknown event type"),
+            Some(buck2_data::buck_event::Data::SpanStart(start)) => {
+                if start.data.is_none() {
+                    self.issue(index, "Unknown span start type");
+                }
+                if !self.open_spans.insert(event.span_id) {
+                    self.issue(index, format!("Span {} started twice", event.span_id));
+                }
+            }
+            Some(buck2_data::buck_event::Data::SpanEnd(end)) => {
+                if end.data.is_none() {
+                    self.issue(index, "Unknown span end type");
+                }
+                if !self.open_spans.remove(&event.span_id) {
+                    self.issue(
+                        index,
+                        format!("End of span {}, which wasn't started", event.span_id),
+                    );
+                }
+            }
+            Some(buck2_data::buck_event::Data::Instant(instant)) => {
+                if instant.data.is_none() {
+                    self.issue(index, "Unknown instant event type");
+                }
+            }
+            Some(buck2_data::buck_event::Data::Record(_)) => {}
+        }
+    }
+
+    fn finish(mut self) -> Vec<EventLogIssue> {
+        if self.result.is_none() {
+            self.issue(
+                None,
+                "The log ends without a command result (e.g. the command was interrupted)",
+            );
+        }
+        self.issues
+    }
+}
+
+/// Read the whole log and return everything in it this buck2 can't interpret, or an error if the
+/// log can't be opened at all.
+pub async fn validate_event_log(log: &EventLogPathBuf) -> anyhow::Result<Vec<EventLogIssue>> {
+    let (invocation, events) = log.unpack_stream().await?;
+    let mut validator = Validator::default();
+    validator.header(&invocation);
+
+    let mut events = std::pin::pin!(events);
+    let mut index = 0;
+    while let Some(value) = events.next().await {
+        index += 1;
+        validator.value(index, value);
+    }
+
+    Ok(validator.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use buck2_cli_proto::command_result;
+    use buck2_cli_proto::CommandResult;
+    use buck2_cli_proto::GenericResponse;
+    use buck2_data::CommandEnd;
+    use buck2_data::CommandStart;
+    use buck2_data::SpanEndEvent;
+    use buck2_data::SpanStartEvent;
+
+    use super::*;
+
+    fn event(span_id: u64, data: Option<buck2_data::buck_event::Data>) -> StreamValue {
+        StreamValue::Event(Box::new(buck2_data::BuckEvent {
+            timestamp: Some(SystemTime::now().into()),
+            trace_id: "7b797fa8-62f1-4123-85f9-875cd74b0a63".to_owned(),
+            span_id,
+            parent_id: 0,
+            data,
+        }))
+    }
+
+    fn command_start() -> StreamValue {
+        event(
+            1,
+            Some(
+                SpanStartEvent {
+                    data: Some(CommandStart::default().into()),
+                }
+                .into(),
+            ),
+        )
+    }
+
+    fn command_end() -> StreamValue {
+        event(
+            1,
+            Some(
+                SpanEndEvent {
+                    data: Some(CommandEnd::default().into()),
+                    ..Default::default()
+                }
+                .into(),
+            ),
+        )
+    }
+
+    fn result() -> StreamValue {
+        StreamValue::Result(Box::new(CommandResult {
+            result: Some(command_result::Result::GenericResponse(
+                GenericResponse::default(),
+            )),
+        }))
+    }
+
+    fn validate(values: Vec<anyhow::Result<StreamValue>>) -> Vec<String> {
+        let mut validator = Validator::default();
+        for (i, value) in values.into_iter().enumerate() {
+            validator.value(i + 1, value);
+        }
+        validator
+            .finish()
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_valid() {
+        assert!(validate(vec![Ok(command_start()), Ok(command_end()), Ok(result())]).is_empty());
+    }
+
+    #[test]
+    fn test_issues() {
+        assert_eq!(
+            vec![
+                "event 1: End of span 1, which wasn't started",
+                "event 2: Unknown event type",
+                "event 3: Invalid line: {",
+                "The log ends without a command result (e.g. the command was interrupted)",
+            ],
+            validate(vec![
+                Ok(command_end()),
+                Ok(event(0, None)),
+                Err(anyhow::anyhow!("Invalid line: {{")),
+            ])
+        );
+        assert_eq!(
+            vec!["event 3: Found after the command result (event 2)"],
+            validate(vec![Ok(command_start()), Ok(result()), Ok(command_end())])
+        );
+    }
+
+    #[test]
+    fn test_newer_version() {
+        let mut validator = Validator::default();
+        validator.header(&Invocation {
+            command_line_args: Vec::new(),
+            expanded_command_line_args: Vec::new(),
+            working_dir: "/".to_owned(),
+            trace_id: buck2_wrapper_common::invocation_id::TraceId::null(),
+            schema_version: EVENT_LOG_SCHEMA_VERSION + 1,
+        });
+        assert_eq!(None, validator.issues[0].event);
+    }
+}
@@ -19,7 +19,6 @@ use std::task::Poll;
 
 use anyhow::Context as _;
 use async_compression::tokio::write::GzipEncoder;
-use async_compression::tokio::write::ZstdEncoder;
 use buck2_cli_proto::*;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
 use buck2_core::fs::paths::abs_path::AbsPathBuf;
@@ -47,7 +46,9 @@ use crate::utils::EventLogErrors;
 use crate::utils::Invocation;
 use crate::utils::LogMode;
 use crate::utils::NoInference;
+use crate::utils::EVENT_LOG_SCHEMA_VERSION;
 use crate::wait_for_child_and_log;
+use crate::zstd_framed_writer::ZstdFramedWriter;
 use crate::FutureChildOutput;
 
 type EventLogWriter = Box<dyn AsyncWrite + Send + Sync + Unpin + 'static>;
@@ -183,6 +184,7 @@ impl<'a> WriteEventLog<'a> {
             expanded_command_line_args,
             working_dir: self.working_dir.to_string(),
             trace_id,
+            schema_version: EVENT_LOG_SCHEMA_VERSION,
         };
         self.write_ln(&[invocation]).await
     }
@@ -452,10 +454,10 @@ fn get_writer(
             CountingReader::new(file, bytes_written),
             async_compression::Level::Fastest,
         )) as EventLogWriter,
-        Compression::Zstd => Box::new(ZstdEncoder::with_quality(
-            CountingReader::new(file, bytes_written),
-            async_compression::Level::Default,
-        )) as EventLogWriter,
+        Compression::Zstd => Box::new(ZstdFramedWriter::new(CountingReader::new(
+            file,
+            bytes_written,
+        ))) as EventLogWriter,
     };
     Ok(NamedEventLogWriter {
         path,
@@ -539,6 +541,7 @@ impl SerializeForLog for Invocation {
             expanded_command_line_args: self.expanded_command_line_args.clone(),
             working_dir: self.working_dir.clone(),
             trace_id: Some(self.trace_id.to_string()),
+            schema_version: self.schema_version,
         };
         invocation.encode_length_delimited(buf)?;
         Ok(())
@@ -754,13 +757,8 @@ mod tests {
         assert_eq!(retrieved_event.data(), event.data());
 
         match encoding.compression {
-            Compression::Gzip => {
-                // TODO(nga): `tick` does not write gzip footer, so even after `tick`
-                //   generated file is not a valid gzip file.
-                // assert!(events.try_next().await.unwrap().is_none(), "expecting no more events");
-                assert!(events.try_next().await.is_err());
-            }
-            Compression::Zstd => {
+            Compression::Gzip | Compression::Zstd => {
+                // The stream isn't finished, which the reader tolerates.
                 assert!(
                     events.try_next().await.unwrap().is_none(),
                     "expecting no more events"
@@ -772,6 +770,45 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_truncated_log_zstd() -> anyhow::Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let log = EventLogPathBuf {
+            path: AbsPathBuf::try_from(tmp_dir.path().join("test_truncated_log.pb.zst")).unwrap(),
+            encoding: Encoding::PROTO_ZSTD,
+        };
+
+        let mut write_event_log = WriteEventLog::new_test(log.clone()).await?;
+        let event = make_event();
+        write_event_log.log_invocation(event.trace_id()?).await?;
+        write_event_log
+            .write_ln(&[StreamValueForWrite::Event(event.event())])
+            .await?;
+        write_event_log.flush_files().await?;
+        write_event_log
+            .write_ln(&[StreamValueForWrite::Event(make_event().event())])
+            .await?;
+        write_event_log.exit().await;
+
+        // Cut the end of the last frame, as if buck2 crashed while writing it.
+        let len = std::fs::metadata(&log.path)?.len();
+        std::fs::OpenOptions::new()
+            .write(true)
+            .open(&log.path)?
+            .set_len(len - 3)?;
+
+        let (_invocation, events) = log.unpack_stream().await?;
+        let events: Vec<StreamValue> = events.try_collect().await?;
+        match events.first() {
+            Some(StreamValue::Event(e)) => {
+                assert_eq!(BuckEvent::try_from(e.clone())?.data(), event.data())
+            }
+            _ => panic!("expecting the event written before the flush"),
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn test_stream_value_serialize_to_protobuf_length_delimited() {
         let event = make_event();
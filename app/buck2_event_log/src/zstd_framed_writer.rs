@@ -0,0 +1,143 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::io;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+
+use async_compression::tokio::write::ZstdEncoder;
+use tokio::io::AsyncWrite;
+
+/// Uncompressed size after which a new zstd frame is started.
+const ZSTD_FRAME_SIZE: usize = 1 << 20;
+
+/// Forwards everything but `shutdown`, which only flushes, so that finishing a zstd frame does not
+/// close the file.
+struct NoShutdown<W>(W);
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for NoShutdown<W> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+}
+
+/// Compresses with zstd as a sequence of frames of about `ZSTD_FRAME_SIZE` uncompressed bytes,
+/// which can each be decompressed on their own. This way, the frames of a log can be uploaded
+/// while the command runs, and if buck2 crashes, only the last frame of the log is incomplete.
+pub(crate) struct ZstdFramedWriter<W> {
+    /// Only `None` while a frame is being replaced.
+    encoder: Option<ZstdEncoder<NoShutdown<W>>>,
+    /// Uncompressed bytes written to the current frame.
+    frame_bytes: usize,
+}
+
+impl<W: AsyncWrite + Unpin> ZstdFramedWriter<W> {
+    pub(crate) fn new(inner: W) -> Self {
+        Self {
+            encoder: Some(Self::new_encoder(NoShutdown(inner))),
+            frame_bytes: 0,
+        }
+    }
+
+    fn new_encoder(inner: NoShutdown<W>) -> ZstdEncoder<NoShutdown<W>> {
+        ZstdEncoder::with_quality(inner, async_compression::Level::Default)
+    }
+
+    fn encoder(&mut self) -> Pin<&mut ZstdEncoder<NoShutdown<W>>> {
+        Pin::new(
+            self.encoder
+                .as_mut()
+                .expect("Encoder is only taken to be replaced"),
+        )
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for ZstdFramedWriter<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if this.frame_bytes >= ZSTD_FRAME_SIZE {
+            // Finish the frame, then start the next one on the same file.
+            futures::ready!(this.encoder().poll_shutdown(cx))?;
+            let inner = this
+                .encoder
+                .take()
+                .expect("Encoder is only taken to be replaced")
+                .into_inner();
+            this.encoder = Some(Self::new_encoder(inner));
+            this.frame_bytes = 0;
+        }
+        let bytes = futures::ready!(this.encoder().poll_write(cx, buf))?;
+        this.frame_bytes += bytes;
+        Poll::Ready(Ok(bytes))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().encoder().poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        futures::ready!(this.encoder().poll_shutdown(cx))?;
+        Pin::new(&mut this.encoder().get_mut().0).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_compression::tokio::bufread::ZstdDecoder;
+    use tokio::io::AsyncReadExt;
+    use tokio::io::AsyncWriteExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_frames() -> anyhow::Result<()> {
+        let data: Vec<u8> = (0..3 * ZSTD_FRAME_SIZE).map(|i| (i % 251) as u8).collect();
+
+        let mut compressed = Vec::new();
+        let mut writer = ZstdFramedWriter::new(&mut compressed);
+        for chunk in data.chunks(64 * 1024) {
+            writer.write_all(chunk).await?;
+        }
+        writer.shutdown().await?;
+        drop(writer);
+
+        // A decoder reading a single frame stops at the end of the first one.
+        let mut first_frame = Vec::new();
+        ZstdDecoder::new(&compressed[..])
+            .read_to_end(&mut first_frame)
+            .await?;
+        assert_eq!(&data[..ZSTD_FRAME_SIZE], &first_frame[..]);
+
+        let mut decoder = ZstdDecoder::new(&compressed[..]);
+        decoder.multiple_members(true);
+        let mut all = Vec::new();
+        decoder.read_to_end(&mut all).await?;
+        assert_eq!(data, all);
+
+        Ok(())
+    }
+}
@@ -242,8 +242,15 @@ pub fn display_event(event: &BuckEvent, opts: TargetDisplayOptions) -> anyhow::R
             Data::Load(load) => Ok(format!("{} -- evaluating build file", load.module_id)),
             Data::ExecutorStage(info) => {
                 let stage = info.stage.as_ref().context("executor stage is missing")?;
-                let stage = display_executor_stage(stage).context("unknown executor stage")?;
-                Ok(stage.into())
+                let label = display_executor_stage(stage).context("unknown executor stage")?;
+                match re_queue_estimate(stage) {
+                    Some(estimate) => Ok(format!(
+                        "{} (estimated {})",
+                        label,
+                        fmt_duration::fmt_duration(estimate, 1.0)
+                    )),
+                    None => Ok(label.into()),
+                }
             }
             Data::TestDiscovery(discovery) => Ok(format!(
                 "Test {} -- discovering tests",
@@ -476,6 +483,18 @@ pub fn display_executor_stage(
     Some(label)
 }
 
+/// How long RE estimated an action would stay queued, if this is an RE queue stage.
+fn re_queue_estimate(stage: &buck2_data::executor_stage_start::Stage) -> Option<Duration> {
+    use buck2_data::executor_stage_start::Stage;
+
+    match stage {
+        Stage::Re(buck2_data::ReStage {
+            stage: Some(buck2_data::re_stage::Stage::Queue(queue)),
+        }) => queue.estimated_queue_time_ms.map(Duration::from_millis),
+        _ => None,
+    }
+}
+
 #[derive(buck2_error::Error, Debug)]
 enum ParseEventError {
     #[error("Missing configured target label")]
@@ -65,9 +65,18 @@ where
             use buck2_data::buck_event::Data::*;
 
             match event.data() {
+                SpanStart(start) => {
+                    if let Some(span_id) = event.span_id() {
+                        self.re_state.span_start(span_id, start);
+                    }
+                }
                 SpanEnd(end) => {
                     use buck2_data::span_end_event::Data::*;
 
+                    if let Some(span_id) = event.span_id() {
+                        self.re_state.span_end(span_id);
+                    }
+
                     match end.data.as_ref().context("Missing `data` in SpanEnd")? {
                         ActionExecution(action_execution_end) => {
                             self.action_stats.update(action_execution_end);
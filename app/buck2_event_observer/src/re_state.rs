@@ -7,6 +7,10 @@
  * of this source tree.
  */
 
+use std::collections::HashMap;
+
+use buck2_events::span::SpanId;
+use dupe::Dupe;
 use superconsole::DrawMode;
 use superconsole::Line;
 use superconsole::Lines;
@@ -15,9 +19,70 @@ use crate::humanized::HumanizedBytes;
 use crate::humanized::HumanizedBytesPerSecond;
 use crate::two_snapshots::TwoSnapshots;
 
+/// Where an action that is using RE currently is in the RE scheduler.
+#[derive(Debug, Copy, Clone, Dupe, PartialEq, Eq)]
+pub enum ReActionStage {
+    UploadingInputs,
+    Queued,
+    WorkerDownloadingInputs,
+    Executing,
+    WorkerUploadingOutputs,
+    DownloadingOutputs,
+}
+
+impl ReActionStage {
+    const ALL: [ReActionStage; 6] = [
+        ReActionStage::UploadingInputs,
+        ReActionStage::Queued,
+        ReActionStage::WorkerDownloadingInputs,
+        ReActionStage::Executing,
+        ReActionStage::WorkerUploadingOutputs,
+        ReActionStage::DownloadingOutputs,
+    ];
+
+    fn from_span_start(start: &buck2_data::SpanStartEvent) -> Option<Self> {
+        use buck2_data::executor_stage_start::Stage;
+        use buck2_data::span_start_event::Data;
+
+        match start.data.as_ref()? {
+            Data::ReUpload(..) => Some(Self::UploadingInputs),
+            Data::ExecutorStage(stage) => match stage.stage.as_ref()? {
+                Stage::CacheHit(..) => Some(Self::DownloadingOutputs),
+                Stage::Re(re) => {
+                    use buck2_data::re_stage::Stage;
+
+                    match re.stage.as_ref()? {
+                        Stage::Queue(..) => Some(Self::Queued),
+                        Stage::WorkerDownload(..) => Some(Self::WorkerDownloadingInputs),
+                        Stage::Execute(..) => Some(Self::Executing),
+                        Stage::WorkerUpload(..) => Some(Self::WorkerUploadingOutputs),
+                        Stage::Download(..) => Some(Self::DownloadingOutputs),
+                        Stage::Unknown(..) | Stage::MaterializeFailedInputs(..) => None,
+                    }
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::UploadingInputs => "uploading inputs",
+            Self::Queued => "queued",
+            Self::WorkerDownloadingInputs => "worker fetching inputs",
+            Self::Executing => "executing",
+            Self::WorkerUploadingOutputs => "worker uploading outputs",
+            Self::DownloadingOutputs => "downloading outputs",
+        }
+    }
+}
+
 pub struct ReState {
     session_id: Option<String>,
     first_snapshot: Option<buck2_data::Snapshot>,
+    /// Open spans that correspond to an RE scheduler stage of some action.
+    open_stages: HashMap<SpanId, ReActionStage>,
 }
 
 impl ReState {
@@ -25,9 +90,46 @@ impl ReState {
         Self {
             session_id: None,
             first_snapshot: None,
+            open_stages: HashMap::new(),
+        }
+    }
+
+    pub fn span_start(&mut self, span_id: SpanId, start: &buck2_data::SpanStartEvent) {
+        if let Some(stage) = ReActionStage::from_span_start(start) {
+            self.open_stages.insert(span_id, stage);
         }
     }
 
+    pub fn span_end(&mut self, span_id: SpanId) {
+        self.open_stages.remove(&span_id);
+    }
+
+    /// Number of actions currently in a given RE scheduler stage.
+    pub fn actions_in_stage(&self, stage: ReActionStage) -> usize {
+        self.open_stages.values().filter(|s| **s == stage).count()
+    }
+
+    /// Render e.g. `RE actions: 12 queued, 40 executing, 3 downloading outputs`.
+    pub fn render_stages(&self) -> Option<String> {
+        if self.open_stages.is_empty() {
+            return None;
+        }
+
+        let parts: Vec<String> = ReActionStage::ALL
+            .iter()
+            .filter_map(|stage| {
+                let count = self.actions_in_stage(*stage);
+                if count == 0 {
+                    None
+                } else {
+                    Some(format!("{} {}", count, stage.label()))
+                }
+            })
+            .collect();
+
+        Some(format!("RE actions: {}", parts.join(", ")))
+    }
+
     pub fn add_re_session(&mut self, session: &buck2_data::RemoteExecutionSessionCreated) {
         self.session_id = Some(session.session_id.clone());
     }
@@ -204,14 +306,70 @@ impl ReState {
         detailed: bool,
         draw_mode: DrawMode,
     ) -> anyhow::Result<Lines> {
-        let header = match self.render_header(two_snapshots, draw_mode) {
-            Some(header) => header,
-            None => return Ok(Lines::new()),
-        };
-        let mut lines = vec![Line::unstyled(&header)?];
-        if detailed {
-            lines.extend(self.render_detailed(two_snapshots)?);
+        let mut lines = Vec::new();
+        if let Some(header) = self.render_header(two_snapshots, draw_mode) {
+            lines.push(Line::unstyled(&header)?);
+            if detailed {
+                lines.extend(self.render_detailed(two_snapshots)?);
+            }
+        }
+        // Where actions are in the scheduler is only interesting while the build is running.
+        if draw_mode == DrawMode::Normal {
+            if let Some(stages) = self.render_stages() {
+                lines.push(Line::unstyled(&stages)?);
+            }
         }
         Ok(Lines(lines))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn re_stage(stage: impl Into<buck2_data::re_stage::Stage>) -> buck2_data::SpanStartEvent {
+        buck2_data::SpanStartEvent {
+            data: Some(
+                buck2_data::ExecutorStageStart {
+                    stage: Some(
+                        buck2_data::ReStage {
+                            stage: Some(stage.into()),
+                        }
+                        .into(),
+                    ),
+                }
+                .into(),
+            ),
+        }
+    }
+
+    #[test]
+    fn test_stages() {
+        let mut state = ReState::new();
+        assert_eq!(state.render_stages(), None);
+
+        let queued = SpanId::next();
+        let executing = SpanId::next();
+        let upload = SpanId::next();
+        state.span_start(queued, &re_stage(buck2_data::ReQueue::default()));
+        state.span_start(executing, &re_stage(buck2_data::ReExecute::default()));
+        state.span_start(
+            upload,
+            &buck2_data::SpanStartEvent {
+                data: Some(buck2_data::ReUploadStart {}.into()),
+            },
+        );
+        assert_eq!(
+            state.render_stages().as_deref(),
+            Some("RE actions: 1 uploading inputs, 1 queued, 1 executing")
+        );
+
+        state.span_end(queued);
+        state.span_end(upload);
+        assert_eq!(state.actions_in_stage(ReActionStage::Queued), 0);
+        assert_eq!(
+            state.render_stages().as_deref(),
+            Some("RE actions: 1 executing")
+        );
+    }
+}
@@ -163,6 +163,15 @@ impl Verbosity {
         Self { items: array }
     }
 
+    /// This verbosity, plus what's needed to see the output of every action: stderr for
+    /// successful actions and full commands for failed ones.
+    pub fn with_action_output(self) -> Self {
+        let mut items: HashSet<VerbosityItem> = self.items.iter().flatten().copied().collect();
+        items.insert(VerbosityItem::Stderr);
+        items.insert(VerbosityItem::FullFailedCommand);
+        Self::from_items(items)
+    }
+
     /// If a verbosity setting is triggered at a particular level, does this verbosity trigger it
     fn has(self, required: VerbosityItem) -> bool {
         self.items.contains(&Some(required))
@@ -252,6 +261,17 @@ mod tests {
         assert!(!verbosity.print_success_stderr());
     }
 
+    #[test]
+    fn test_with_action_output() {
+        let verbosity = Verbosity::try_from_cli("1").unwrap().with_action_output();
+        assert!(verbosity.print_status());
+        assert!(verbosity.print_success_message());
+        assert!(verbosity.print_failure_full_command());
+        assert!(!verbosity.print_all_actions());
+        assert!(!verbosity.print_all_commands());
+        assert!(verbosity.print_success_stderr());
+    }
+
     #[test]
     fn test_more_than_one_level_throws_error() {
         let result = Verbosity::try_from_cli("0,1");
@@ -17,12 +17,18 @@ use std::cell::UnsafeCell;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::pin::Pin;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::task;
 use std::time::Duration;
 use std::time::Instant;
 use std::time::SystemTime;
 
+use buck2_core::error::CommandSoftErrors;
+use buck2_core::error::CURRENT_COMMAND_SOFT_ERRORS;
+use buck2_core::fs::windows_paths::WindowsPathOptions;
+use buck2_core::fs::windows_paths::CURRENT_COMMAND_WINDOWS_PATH_OPTIONS;
 use buck2_data::buck_event;
 use buck2_data::span_end_event;
 use buck2_data::span_start_event;
@@ -49,6 +55,44 @@ pub struct EventDispatcher {
     /// The sink to log events to.
     #[allocative(skip)] // TODO(nga): do not skip.
     sink: Arc<dyn EventSink>,
+    /// The soft errors of the command this dispatcher is for.
+    #[allocative(skip)]
+    soft_errors: Arc<CommandSoftErrors>,
+    /// Statistics of the command this dispatcher is for.
+    #[allocative(skip)]
+    counters: Arc<CommandCounters>,
+    /// The Windows path options of the command this dispatcher is for.
+    #[allocative(skip)]
+    windows_path_options: Arc<WindowsPathOptions>,
+}
+
+/// Statistics of a command reported in its build report, counted on its dispatcher so that
+/// commands running concurrently on the same daemon don't count each other's.
+#[derive(Default)]
+pub struct CommandCounters {
+    re_retries: AtomicU64,
+    expired_artifact_rebuilds: AtomicU64,
+}
+
+impl CommandCounters {
+    /// An RE execution was retried after a transient RE error.
+    pub fn record_re_retry(&self) {
+        self.re_retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn re_retries(&self) -> u64 {
+        self.re_retries.load(Ordering::Relaxed)
+    }
+
+    /// An action was executed again because its outputs expired in the CAS.
+    pub fn record_expired_artifact_rebuild(&self) {
+        self.expired_artifact_rebuilds
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn expired_artifact_rebuilds(&self) -> u64 {
+        self.expired_artifact_rebuilds.load(Ordering::Relaxed)
+    }
 }
 
 impl EventDispatcher {
@@ -57,6 +101,9 @@ impl EventDispatcher {
         EventDispatcher {
             trace_id,
             sink: Arc::new(sink),
+            soft_errors: Arc::new(CommandSoftErrors::default()),
+            counters: Arc::new(CommandCounters::default()),
+            windows_path_options: Arc::new(WindowsPathOptions::default()),
         }
     }
 
@@ -64,11 +111,31 @@ impl EventDispatcher {
         self.sink.dupe()
     }
 
+    /// The soft errors of the command this dispatcher is for. Soft errors raised while this
+    /// dispatcher is the ambient one are counted here, and handled according to its configuration.
+    pub fn soft_errors(&self) -> &Arc<CommandSoftErrors> {
+        &self.soft_errors
+    }
+
+    /// The statistics of the command this dispatcher is for.
+    pub fn counters(&self) -> &CommandCounters {
+        &self.counters
+    }
+
+    /// The Windows path options used by file system operations while this dispatcher is the
+    /// ambient one.
+    pub fn windows_path_options(&self) -> &WindowsPathOptions {
+        &self.windows_path_options
+    }
+
     /// Creates a new null Event Dispatcher that accepts events but does not write them anywhere.
     pub fn null() -> EventDispatcher {
         EventDispatcher {
             trace_id: TraceId::null(),
             sink: Arc::new(NullEventSink::new()),
+            soft_errors: Arc::new(CommandSoftErrors::default()),
+            counters: Arc::new(CommandCounters::default()),
+            windows_path_options: Arc::new(WindowsPathOptions::default()),
         }
     }
 
@@ -77,6 +144,9 @@ impl EventDispatcher {
         EventDispatcher {
             trace_id,
             sink: Arc::new(NullEventSink::new()),
+            soft_errors: Arc::new(CommandSoftErrors::default()),
+            counters: Arc::new(CommandCounters::default()),
+            windows_path_options: Arc::new(WindowsPathOptions::default()),
         }
     }
 
@@ -388,6 +458,16 @@ pub fn get_dispatcher_opt() -> Option<EventDispatcher> {
     }
 }
 
+pub(crate) fn init_current_command_soft_errors() {
+    CURRENT_COMMAND_SOFT_ERRORS
+        .init(|| get_dispatcher_opt().map(|dispatcher| dispatcher.soft_errors.dupe()));
+}
+
+pub(crate) fn init_current_command_windows_path_options() {
+    CURRENT_COMMAND_WINDOWS_PATH_OPTIONS
+        .init(|| get_dispatcher_opt().map(|dispatcher| dispatcher.windows_path_options.dupe()));
+}
+
 pub fn get_dispatcher() -> EventDispatcher {
     let enforce_event_dispatcher_set = buck2_env!("ENFORCE_DISPATCHER_SET", bool).unwrap();
 
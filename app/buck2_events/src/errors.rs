@@ -34,3 +34,29 @@ pub fn create_error_report(err: &buck2_error::Error) -> buck2_data::ErrorReport
         tags: err.tags().map(|t| *t as i32),
     }
 }
+
+/// The stable code of the error this report was created from.
+pub fn error_report_code(report: &buck2_data::ErrorReport) -> buck2_error::ErrorCode {
+    let category = report
+        .category
+        .and_then(buck2_data::error::ErrorCategory::from_i32)
+        .and_then(|c| match c {
+            buck2_data::error::ErrorCategory::User => Some(buck2_error::Category::User),
+            buck2_data::error::ErrorCategory::Infra => Some(buck2_error::Category::Infra),
+            buck2_data::error::ErrorCategory::UnusedDefaultCategory => None,
+        });
+    buck2_error::ErrorCode::new(
+        report
+            .tags
+            .iter()
+            .filter_map(|t| buck2_error::ErrorTag::from_i32(*t)),
+        report.typ.and_then(buck2_error::ErrorType::from_i32),
+        category,
+    )
+}
+
+/// Line printed after an error message to identify the error.
+pub fn error_report_code_line(report: &buck2_data::ErrorReport) -> String {
+    let code = error_report_code(report);
+    format!("Error code: {} (see {})", code, code.docs_url())
+}
@@ -49,6 +49,12 @@ use crate::sink::channel::ChannelEventSink;
 use crate::source::ChannelEventSource;
 use crate::span::SpanId;
 
+/// Initialize the late bindings defined by this crate.
+pub fn init_late_bindings() {
+    dispatch::init_current_command_soft_errors();
+    dispatch::init_current_command_windows_path_options();
+}
+
 /// An event that can be produced by Buck2. Events are points in time with additional metadata attached to them,
 /// depending on the nature of the event.
 ///
@@ -203,7 +209,7 @@ pub enum Event {
 }
 
 /// Statistics from this event sink on how messages were processed.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct EventSinkStats {
     /// Count of number of successful messages (e.g. those that have been processed by their downstream destination).
     pub successes: u64,
@@ -0,0 +1,878 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! A Sink streaming builds and tests to a Build Event Service (e.g. BuildBuddy or ResultStore),
+//! as the Build Event Protocol events Bazel would send: the command start, a result for each test,
+//! the requested targets with their outputs, and the command end.
+//!
+//! Each `build` or `test` command is one stream, uploaded while the command runs. Uploads may lag
+//! behind by one command at most: a command which finishes can return while its events are still
+//! uploading, but the next command waits for that upload (up to the upload timeout) when it starts.
+
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use anyhow::Context;
+use buck2_bes_proto::build_event_stream as bep;
+use buck2_bes_proto::build_event_stream::build_event::Payload;
+use buck2_bes_proto::build_event_stream::build_event_id;
+use buck2_bes_proto::build_event_stream::BuildEventId;
+use buck2_bes_proto::google::devtools::build::v1 as bes;
+use buck2_bes_proto::google::devtools::build::v1::publish_build_event_client::PublishBuildEventClient;
+use buck2_cli_proto::command_result;
+use buck2_cli_proto::CommandResult;
+use dupe::Dupe;
+use prost::Message;
+use tokio::sync::mpsc;
+use tokio::sync::watch;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tonic::metadata::Ascii;
+use tonic::metadata::MetadataKey;
+use tonic::metadata::MetadataValue;
+use tonic::transport::Channel;
+use tonic::transport::ClientTlsConfig;
+use tonic::transport::Endpoint;
+
+use crate::Event;
+use crate::EventSink;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(user)]
+enum BesError {
+    #[error("Invalid `[bes] address`: `{0}`. Expected `grpc://host:port` or `grpcs://host:port`")]
+    InvalidAddress(String),
+    #[error("The Build Event Service acknowledged {acked} of the {sent} events of the build")]
+    Incomplete { acked: i64, sent: i64 },
+}
+
+pub struct BesConfig {
+    /// `grpc://host:port`, or `grpcs://host:port` for TLS.
+    pub address: String,
+    /// Headers sent with the events, e.g. an API key.
+    pub headers: Vec<(String, String)>,
+    pub project_id: Option<String>,
+    /// How long the upload of a command may continue after the command finished.
+    pub upload_timeout: Duration,
+}
+
+pub struct BesUploader {
+    client: PublishBuildEventClient<Channel>,
+    headers: Arc<Vec<(MetadataKey<Ascii>, MetadataValue<Ascii>)>>,
+    project_id: String,
+    upload_timeout: Duration,
+    /// How many commands finished while their events are still uploading.
+    behind: Arc<watch::Sender<usize>>,
+}
+
+impl BesUploader {
+    pub fn new(config: BesConfig) -> anyhow::Result<BesUploader> {
+        let (uri, tls) = if let Some(host) = config.address.strip_prefix("grpcs://") {
+            (format!("https://{}", host), true)
+        } else if let Some(host) = config.address.strip_prefix("grpc://") {
+            (format!("http://{}", host), false)
+        } else {
+            return Err(BesError::InvalidAddress(config.address).into());
+        };
+        let mut endpoint = Endpoint::from_shared(uri)
+            .with_context(|| BesError::InvalidAddress(config.address.clone()))?;
+        if tls {
+            // We set the `tls-webpki-roots` feature so we'll get that default.
+            endpoint = endpoint.tls_config(ClientTlsConfig::new())?;
+        }
+
+        let headers = config
+            .headers
+            .iter()
+            .map(|(key, value)| {
+                let key = MetadataKey::<Ascii>::from_bytes(key.as_bytes())
+                    .with_context(|| format!("Invalid key in header: `{}: {}`", key, value))?;
+                let value = MetadataValue::try_from(value)
+                    .with_context(|| format!("Invalid value in header: `{}: {}`", key, value))?;
+                anyhow::Ok((key, value))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(BesUploader {
+            // Only connect when a build starts, not when the daemon does.
+            client: PublishBuildEventClient::new(endpoint.connect_lazy()),
+            headers: Arc::new(headers),
+            project_id: config.project_id.unwrap_or_default(),
+            upload_timeout: config.upload_timeout,
+            behind: Arc::new(watch::channel(0).0),
+        })
+    }
+
+    /// A sink uploading the events of one command, if it is a build or a test. Waits for the
+    /// uploads of the commands which already finished.
+    pub async fn invocation_sink(&self) -> BesSink {
+        let mut behind = self.behind.subscribe();
+        while *behind.borrow() > 0 {
+            if behind.changed().await.is_err() {
+                break;
+            }
+        }
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let finished = Arc::new(AtomicBool::new(false));
+        let upload = Upload {
+            client: self.client.clone(),
+            headers: self.headers.dupe(),
+            project_id: self.project_id.clone(),
+            upload_timeout: self.upload_timeout,
+        };
+        let behind = self.behind.dupe();
+        let task_finished = finished.dupe();
+        tokio::spawn(async move {
+            if let Err(e) = upload.run(receiver).await {
+                tracing::warn!("Error uploading to the Build Event Service: {:#}", e);
+            }
+            // The receiver is dropped, so the sink can't count this command after this.
+            if task_finished.swap(false, Ordering::AcqRel) {
+                behind.send_modify(|n| *n -= 1);
+            }
+        });
+
+        BesSink {
+            sender,
+            finished,
+            behind: self.behind.dupe(),
+        }
+    }
+}
+
+pub struct BesSink {
+    sender: mpsc::UnboundedSender<Event>,
+    /// Whether this command finished and is counted in `behind`, until its upload is done.
+    finished: Arc<AtomicBool>,
+    behind: Arc<watch::Sender<usize>>,
+}
+
+impl EventSink for BesSink {
+    fn send(&self, event: Event) {
+        match &event {
+            Event::Buck(buck) if !BuildEventStreamer::is_relevant(buck.data()) => return,
+            Event::Buck(..) => {}
+            Event::CommandResult(..) => {
+                // Count the command before the client gets the result and starts the next one.
+                self.behind.send_modify(|n| *n += 1);
+                self.finished.store(true, Ordering::Release);
+            }
+            Event::PartialResult(..) => return,
+        }
+        // Fails when the command is not uploaded.
+        if self.sender.send(event).is_err() && self.finished.swap(false, Ordering::AcqRel) {
+            self.behind.send_modify(|n| *n -= 1);
+        }
+    }
+}
+
+struct Upload {
+    client: PublishBuildEventClient<Channel>,
+    headers: Arc<Vec<(MetadataKey<Ascii>, MetadataValue<Ascii>)>>,
+    project_id: String,
+    upload_timeout: Duration,
+}
+
+impl Upload {
+    async fn run(self, mut events: mpsc::UnboundedReceiver<Event>) -> anyhow::Result<()> {
+        let mut streamer = BuildEventStreamer::default();
+        // Only open a stream once we know the command is a build or a test.
+        let started = loop {
+            let Some(event) = events.recv().await else {
+                return Ok(());
+            };
+            let started = streamer.handle(&event);
+            if streamer.state == StreamerState::Ignored {
+                return Ok(());
+            }
+            if !started.is_empty() {
+                break started;
+            }
+        };
+
+        let (requests, requests_receiver) = mpsc::unbounded_channel();
+        let mut publisher = Publisher {
+            stream_id: bes::StreamId {
+                build_id: streamer.trace_id.clone(),
+                invocation_id: streamer.trace_id.clone(),
+                component: bes::stream_id::BuildComponent::Tool as i32,
+            },
+            project_id: self.project_id.clone(),
+            sequence_number: 0,
+            requests,
+        };
+        publisher.publish_all(started);
+
+        let mut request = tonic::Request::new(UnboundedReceiverStream::new(requests_receiver));
+        for (key, value) in self.headers.iter() {
+            request.metadata_mut().insert(key.clone(), value.clone());
+        }
+        let mut client = self.client;
+        let acks = tokio::spawn(async move {
+            let mut acks = client
+                .publish_build_tool_event_stream(request)
+                .await
+                .context("Error opening the Build Event Service stream")?
+                .into_inner();
+            let mut acked = 0;
+            while let Some(ack) = acks.message().await? {
+                acked = ack.sequence_number;
+            }
+            anyhow::Ok(acked)
+        });
+
+        while streamer.state != StreamerState::Finished {
+            match events.recv().await {
+                Some(event) => publisher.publish_all(streamer.handle(&event)),
+                None => publisher.publish_all(streamer.finish(None)),
+            }
+        }
+        let sent = publisher.finish();
+
+        let acked = tokio::time::timeout(self.upload_timeout, acks)
+            .await
+            .with_context(|| {
+                format!(
+                    "Timed out after {}s uploading to the Build Event Service",
+                    self.upload_timeout.as_secs()
+                )
+            })???;
+        if acked != sent {
+            return Err(BesError::Incomplete { acked, sent }.into());
+        }
+        Ok(())
+    }
+}
+
+/// Numbers the events of a stream and sends them to the gRPC request stream.
+struct Publisher {
+    stream_id: bes::StreamId,
+    project_id: String,
+    sequence_number: i64,
+    requests: mpsc::UnboundedSender<bes::PublishBuildToolEventStreamRequest>,
+}
+
+impl Publisher {
+    fn publish(&mut self, event: bes::build_event::Event) {
+        self.sequence_number += 1;
+        // The upload has failed if this fails, and that is reported by the responses.
+        let _ignored = self.requests.send(bes::PublishBuildToolEventStreamRequest {
+            ordered_build_event: Some(bes::OrderedBuildEvent {
+                stream_id: Some(self.stream_id.clone()),
+                sequence_number: self.sequence_number,
+                event: Some(bes::BuildEvent {
+                    event_time: Some(SystemTime::now().into()),
+                    event: Some(event),
+                }),
+            }),
+            notification_keywords: Vec::new(),
+            project_id: self.project_id.clone(),
+        });
+    }
+
+    fn publish_all(&mut self, events: Vec<bep::BuildEvent>) {
+        for event in events {
+            self.publish(bes::build_event::Event::BazelEvent(prost_types::Any {
+                type_url: "type.googleapis.com/build_event_stream.BuildEvent".to_owned(),
+                value: event.encode_to_vec(),
+            }));
+        }
+    }
+
+    /// End the stream, and return the number of events sent.
+    fn finish(mut self) -> i64 {
+        self.publish(bes::build_event::Event::ComponentStreamFinished(
+            bes::build_event::BuildComponentStreamFinished {
+                r#type: bes::build_event::build_component_stream_finished::FinishType::Finished
+                    as i32,
+            },
+        ));
+        self.sequence_number
+    }
+}
+
+#[derive(Default, Debug, PartialEq, Eq)]
+enum StreamerState {
+    #[default]
+    NotStarted,
+    Started,
+    Finished,
+    /// Not a build or a test.
+    Ignored,
+}
+
+/// Turns the events of a command into Build Event Protocol events.
+///
+/// Each event must be announced as a child by an earlier one. The start announces the end and a
+/// first progress event, and each batch of events is preceded by a progress event which announces
+/// them and the next progress event, like Bazel does for events it can't otherwise announce.
+#[derive(Default)]
+struct BuildEventStreamer {
+    state: StreamerState,
+    trace_id: String,
+    /// The id of the next progress event, which the last one announced.
+    next_progress: i32,
+    /// The number of test results of each target and configuration, to number their runs.
+    test_runs: HashMap<(String, String), i32>,
+}
+
+impl BuildEventStreamer {
+    /// Whether the streamer uses these events, to only send those to the upload.
+    fn is_relevant(data: &buck2_data::buck_event::Data) -> bool {
+        match data {
+            buck2_data::buck_event::Data::SpanStart(start) => matches!(
+                start.data,
+                Some(buck2_data::span_start_event::Data::Command(..))
+            ),
+            buck2_data::buck_event::Data::Instant(instant) => matches!(
+                instant.data,
+                Some(buck2_data::instant_event::Data::TestResult(..))
+            ),
+            _ => false,
+        }
+    }
+
+    fn handle(&mut self, event: &Event) -> Vec<bep::BuildEvent> {
+        match (&self.state, event) {
+            (StreamerState::NotStarted, Event::Buck(event)) => match event.data() {
+                buck2_data::buck_event::Data::SpanStart(buck2_data::SpanStartEvent {
+                    data: Some(buck2_data::span_start_event::Data::Command(command)),
+                }) => self.start(event.timestamp(), &event.event().trace_id, command),
+                _ => Vec::new(),
+            },
+            (StreamerState::Started, Event::Buck(event)) => match event.data() {
+                buck2_data::buck_event::Data::Instant(buck2_data::InstantEvent {
+                    data: Some(buck2_data::instant_event::Data::TestResult(result)),
+                }) => self.test_result(event.timestamp(), result),
+                _ => Vec::new(),
+            },
+            (StreamerState::Started, Event::CommandResult(result)) => self.finish(Some(result)),
+            _ => Vec::new(),
+        }
+    }
+
+    fn start(
+        &mut self,
+        timestamp: SystemTime,
+        trace_id: &str,
+        command: &buck2_data::CommandStart,
+    ) -> Vec<bep::BuildEvent> {
+        let name = match command.data {
+            Some(buck2_data::command_start::Data::Build(..)) => "build",
+            Some(buck2_data::command_start::Data::Test(..)) => "test",
+            _ => {
+                self.state = StreamerState::Ignored;
+                return Vec::new();
+            }
+        };
+        self.state = StreamerState::Started;
+        self.trace_id = trace_id.to_owned();
+
+        vec![bep::BuildEvent {
+            id: Some(id(build_event_id::Id::Started(
+                build_event_id::BuildStartedId {},
+            ))),
+            children: vec![progress_id(0), build_finished_id()],
+            last_message: false,
+            payload: Some(Payload::Started(bep::BuildStarted {
+                uuid: trace_id.to_owned(),
+                start_time_millis: millis_since_epoch(timestamp),
+                build_tool_version: buck2_build_info::revision().unwrap_or_default().to_owned(),
+                command: name.to_owned(),
+                server_pid: std::process::id() as i64,
+                ..Default::default()
+            })),
+        }]
+    }
+
+    fn test_result(
+        &mut self,
+        timestamp: SystemTime,
+        result: &buck2_data::TestResult,
+    ) -> Vec<bep::BuildEvent> {
+        use buck2_data::TestStatus;
+
+        let status = match TestStatus::from_i32(result.status) {
+            Some(TestStatus::Pass) => bep::TestStatus::Passed,
+            Some(TestStatus::Fail | TestStatus::Fatal | TestStatus::ListingFailed) => {
+                bep::TestStatus::Failed
+            }
+            Some(TestStatus::Timeout) => bep::TestStatus::Timeout,
+            // Those are not results: the test didn't run, or runs again.
+            Some(
+                TestStatus::Skip
+                | TestStatus::Omitted
+                | TestStatus::Rerun
+                | TestStatus::ListingSuccess,
+            ) => return Vec::new(),
+            Some(TestStatus::Unknown | TestStatus::NotSetTestStatus) | None => {
+                bep::TestStatus::NoStatus
+            }
+        };
+
+        let (label, configuration) = target_label(result.target_label.as_ref());
+        let run = self
+            .test_runs
+            .entry((label.clone(), configuration.clone()))
+            .or_default();
+        *run += 1;
+
+        let duration = result
+            .duration
+            .clone()
+            .and_then(|d| Duration::try_from(d).ok())
+            .unwrap_or_default();
+        let test_action_output = if result.details.is_empty() {
+            Vec::new()
+        } else {
+            vec![bep::File {
+                name: "test.log".to_owned(),
+                file: Some(bep::file::File::Contents(
+                    result.details.clone().into_bytes(),
+                )),
+                ..Default::default()
+            }]
+        };
+
+        let event = bep::BuildEvent {
+            id: Some(id(build_event_id::Id::TestResult(
+                build_event_id::TestResultId {
+                    label,
+                    configuration: Some(build_event_id::ConfigurationId { id: configuration }),
+                    run: *run,
+                    shard: 1,
+                    attempt: 1,
+                },
+            ))),
+            children: Vec::new(),
+            last_message: false,
+            payload: Some(Payload::TestResult(bep::TestResult {
+                status: status as i32,
+                status_details: result.name.clone(),
+                test_attempt_start_millis_epoch: millis_since_epoch(
+                    timestamp.checked_sub(duration).unwrap_or(timestamp),
+                ),
+                test_attempt_duration_millis: duration.as_millis() as i64,
+                test_action_output,
+                ..Default::default()
+            })),
+        };
+        self.announced(vec![event], false)
+    }
+
+    /// The end of the stream, with the result of the command, or `None` if the command ended
+    /// without one.
+    ///
+    /// The result of a build doesn't say which targets failed, so the targets are reported as
+    /// completed with the status of the build.
+    fn finish(&mut self, result: Option<&CommandResult>) -> Vec<bep::BuildEvent> {
+        self.state = StreamerState::Finished;
+
+        let (completed, (exit_code_name, exit_code)) = match result.and_then(|r| r.result.as_ref())
+        {
+            Some(command_result::Result::BuildResponse(response)) => {
+                let success = response.errors.is_empty();
+                let completed = response
+                    .build_targets
+                    .iter()
+                    .map(|target| target_completed(target, &response.project_root, success))
+                    .collect();
+                let exit_code = if success {
+                    ("SUCCESS", 0)
+                } else {
+                    ("BUILD_FAILURE", 1)
+                };
+                (completed, exit_code)
+            }
+            Some(command_result::Result::TestResponse(response)) => {
+                let exit_code = if !response.errors.is_empty() {
+                    ("BUILD_FAILURE", 1)
+                } else if response.exit_code == Some(0) {
+                    ("SUCCESS", 0)
+                } else {
+                    ("TESTS_FAILED", 3)
+                };
+                (Vec::new(), exit_code)
+            }
+            Some(_) => (Vec::new(), ("BUILD_FAILURE", 1)),
+            None => (Vec::new(), ("INTERRUPTED", 8)),
+        };
+
+        let mut events = self.announced(completed, true);
+        events.push(bep::BuildEvent {
+            id: Some(build_finished_id()),
+            children: Vec::new(),
+            last_message: true,
+            payload: Some(Payload::Finished(bep::BuildFinished {
+                overall_success: exit_code == 0,
+                exit_code: Some(bep::build_finished::ExitCode {
+                    name: exit_code_name.to_owned(),
+                    code: exit_code,
+                }),
+                finish_time_millis: millis_since_epoch(SystemTime::now()),
+            })),
+        });
+        events
+    }
+
+    /// `events`, preceded by the progress event announcing them. That is the last progress event
+    /// if `last`, and otherwise it announces the next one.
+    fn announced(&mut self, events: Vec<bep::BuildEvent>, last: bool) -> Vec<bep::BuildEvent> {
+        let mut children: Vec<_> = events.iter().filter_map(|e| e.id.clone()).collect();
+        if !last {
+            children.push(progress_id(self.next_progress + 1));
+        }
+        let progress = bep::BuildEvent {
+            id: Some(progress_id(self.next_progress)),
+            children,
+            last_message: false,
+            payload: Some(Payload::Progress(bep::Progress::default())),
+        };
+        self.next_progress += 1;
+        std::iter::once(progress).chain(events).collect()
+    }
+}
+
+fn target_completed(
+    target: &buck2_cli_proto::BuildTarget,
+    project_root: &str,
+    success: bool,
+) -> bep::BuildEvent {
+    bep::BuildEvent {
+        id: Some(id(build_event_id::Id::TargetCompleted(
+            build_event_id::TargetCompletedId {
+                label: target.target.clone(),
+                configuration: Some(build_event_id::ConfigurationId {
+                    id: target.configuration.clone(),
+                }),
+                aspect: String::new(),
+            },
+        ))),
+        children: Vec::new(),
+        last_message: false,
+        payload: Some(Payload::Completed(bep::TargetComplete {
+            success,
+            target_kind: target
+                .target_rule_type_name
+                .as_ref()
+                .map(|rule| format!("{} rule", rule))
+                .unwrap_or_default(),
+            important_output: target
+                .outputs
+                .iter()
+                .map(|output| bep::File {
+                    name: output.path.clone(),
+                    file: Some(bep::file::File::Uri(format!(
+                        "file://{}/{}",
+                        project_root, output.path
+                    ))),
+                    ..Default::default()
+                })
+                .collect(),
+            tag: Vec::new(),
+        })),
+    }
+}
+
+fn target_label(label: Option<&buck2_data::ConfiguredTargetLabel>) -> (String, String) {
+    let target = label
+        .and_then(|l| l.label.as_ref())
+        .map(|l| format!("{}:{}", l.package, l.name))
+        .unwrap_or_default();
+    let configuration = label
+        .and_then(|l| l.configuration.as_ref())
+        .map(|c| c.full_name.clone())
+        .unwrap_or_default();
+    (target, configuration)
+}
+
+fn id(id: build_event_id::Id) -> BuildEventId {
+    BuildEventId { id: Some(id) }
+}
+
+fn progress_id(opaque_count: i32) -> BuildEventId {
+    id(build_event_id::Id::Progress(build_event_id::ProgressId {
+        opaque_count,
+    }))
+}
+
+fn build_finished_id() -> BuildEventId {
+    id(build_event_id::Id::BuildFinished(
+        build_event_id::BuildFinishedId {},
+    ))
+}
+
+fn millis_since_epoch(time: SystemTime) -> i64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use buck2_data::CommandStart;
+    use buck2_data::SpanStartEvent;
+    use buck2_wrapper_common::invocation_id::TraceId;
+
+    use super::*;
+    use crate::BuckEvent;
+
+    fn buck_event(data: buck2_data::buck_event::Data) -> Event {
+        Event::Buck(BuckEvent::new(
+            SystemTime::now(),
+            TraceId::new(),
+            None,
+            None,
+            data,
+        ))
+    }
+
+    fn command_start(data: buck2_data::command_start::Data) -> Event {
+        buck_event(
+            SpanStartEvent {
+                data: Some(
+                    CommandStart {
+                        data: Some(data),
+                        metadata: HashMap::new(),
+                    }
+                    .into(),
+                ),
+            }
+            .into(),
+        )
+    }
+
+    fn test_result(name: &str, status: buck2_data::TestStatus) -> Event {
+        buck_event(
+            buck2_data::InstantEvent {
+                data: Some(
+                    buck2_data::TestResult {
+                        name: name.to_owned(),
+                        status: status as i32,
+                        duration: Some(prost_types::Duration {
+                            seconds: 2,
+                            nanos: 0,
+                        }),
+                        details: String::new(),
+                        target_label: Some(buck2_data::ConfiguredTargetLabel {
+                            label: Some(buck2_data::TargetLabel {
+                                package: "root//foo".to_owned(),
+                                name: "test".to_owned(),
+                            }),
+                            configuration: Some(buck2_data::Configuration {
+                                full_name: "cfg".to_owned(),
+                            }),
+                            execution_configuration: None,
+                        }),
+                        msg: None,
+                    }
+                    .into(),
+                ),
+            }
+            .into(),
+        )
+    }
+
+    fn build_result(errors: bool) -> Event {
+        Event::CommandResult(Box::new(CommandResult {
+            result: Some(command_result::Result::BuildResponse(
+                buck2_cli_proto::BuildResponse {
+                    build_targets: vec![buck2_cli_proto::BuildTarget {
+                        target: "root//foo:bar".to_owned(),
+                        configuration: "cfg".to_owned(),
+                        outputs: vec![buck2_cli_proto::build_target::BuildOutput {
+                            path: "buck-out/bar".to_owned(),
+                            providers: None,
+                        }],
+                        target_rule_type_name: Some("genrule".to_owned()),
+                        ..Default::default()
+                    }],
+                    project_root: "/repo".to_owned(),
+                    errors: if errors {
+                        vec![buck2_data::ErrorReport::default()]
+                    } else {
+                        Vec::new()
+                    },
+                    ..Default::default()
+                },
+            )),
+        }))
+    }
+
+    /// Check that every event but the first was announced by an earlier one, and that every
+    /// announced event was sent.
+    fn assert_announced(events: &[bep::BuildEvent]) {
+        let mut announced = HashSet::new();
+        for (i, event) in events.iter().enumerate() {
+            let id = format!("{:?}", event.id);
+            if i > 0 {
+                assert!(announced.remove(&id), "not announced: {}", id);
+            }
+            announced.extend(event.children.iter().map(|c| format!("{:?}", Some(c))));
+        }
+        assert!(announced.is_empty(), "not sent: {:?}", announced);
+        assert!(events.last().unwrap().last_message);
+    }
+
+    #[test]
+    fn test_build() {
+        let mut streamer = BuildEventStreamer::default();
+        let mut events = streamer.handle(&command_start(buck2_data::BuildCommandStart {}.into()));
+        assert_eq!(StreamerState::Started, streamer.state);
+        match &events[0].payload {
+            Some(Payload::Started(started)) => {
+                assert_eq!("build", started.command);
+                assert_eq!(streamer.trace_id, started.uuid);
+            }
+            payload => panic!("{:?}", payload),
+        }
+
+        events.extend(streamer.handle(&build_result(false)));
+        assert_eq!(StreamerState::Finished, streamer.state);
+        assert_announced(&events);
+
+        let completed = events
+            .iter()
+            .find_map(|e| match &e.payload {
+                Some(Payload::Completed(completed)) => Some(completed),
+                _ => None,
+            })
+            .unwrap();
+        assert!(completed.success);
+        assert_eq!("genrule rule", completed.target_kind);
+        assert_eq!(
+            Some(bep::file::File::Uri("file:///repo/buck-out/bar".to_owned())),
+            completed.important_output[0].file
+        );
+        match &events.last().unwrap().payload {
+            Some(Payload::Finished(finished)) => {
+                assert_eq!(0, finished.exit_code.as_ref().unwrap().code);
+            }
+            payload => panic!("{:?}", payload),
+        }
+
+        // Nothing after the end.
+        assert!(streamer.handle(&build_result(false)).is_empty());
+    }
+
+    #[test]
+    fn test_failed_build() {
+        let mut streamer = BuildEventStreamer::default();
+        streamer.handle(&command_start(buck2_data::BuildCommandStart {}.into()));
+        let events = streamer.handle(&build_result(true));
+        match &events.last().unwrap().payload {
+            Some(Payload::Finished(finished)) => {
+                assert!(!finished.overall_success);
+                assert_eq!("BUILD_FAILURE", finished.exit_code.as_ref().unwrap().name);
+            }
+            payload => panic!("{:?}", payload),
+        }
+    }
+
+    #[test]
+    fn test_test_results() {
+        let mut streamer = BuildEventStreamer::default();
+        let mut events = streamer.handle(&command_start(buck2_data::TestCommandStart {}.into()));
+        events.extend(streamer.handle(&test_result("a", buck2_data::TestStatus::Pass)));
+        // Not a result.
+        assert!(
+            streamer
+                .handle(&test_result("b", buck2_data::TestStatus::Skip))
+                .is_empty()
+        );
+        events.extend(streamer.handle(&test_result("c", buck2_data::TestStatus::Fail)));
+        // The command ended without a result.
+        events.extend(streamer.finish(None));
+        assert_announced(&events);
+
+        let results: Vec<_> = events
+            .iter()
+            .filter_map(|e| match (&e.id, &e.payload) {
+                (
+                    Some(BuildEventId {
+                        id: Some(build_event_id::Id::TestResult(id)),
+                    }),
+                    Some(Payload::TestResult(result)),
+                ) => Some((id, result)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(2, results.len());
+        assert_eq!("root//foo:test", results[0].0.label);
+        assert_eq!((1, 2), (results[0].0.run, results[1].0.run));
+        assert_eq!(bep::TestStatus::Passed as i32, results[0].1.status);
+        assert_eq!(bep::TestStatus::Failed as i32, results[1].1.status);
+        assert_eq!("c", results[1].1.status_details);
+        assert_eq!(2000, results[1].1.test_attempt_duration_millis);
+
+        match &events.last().unwrap().payload {
+            Some(Payload::Finished(finished)) => {
+                assert_eq!("INTERRUPTED", finished.exit_code.as_ref().unwrap().name);
+            }
+            payload => panic!("{:?}", payload),
+        }
+    }
+
+    #[test]
+    fn test_other_commands_are_ignored() {
+        let mut streamer = BuildEventStreamer::default();
+        assert!(
+            streamer
+                .handle(&command_start(buck2_data::CleanCommandStart {}.into()))
+                .is_empty()
+        );
+        assert_eq!(StreamerState::Ignored, streamer.state);
+        assert!(streamer.handle(&build_result(false)).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sink_counts_finished_commands() {
+        let uploader = BesUploader::new(BesConfig {
+            address: "grpc://localhost:1".to_owned(),
+            headers: vec![("x-api-key".to_owned(), "secret".to_owned())],
+            project_id: None,
+            upload_timeout: Duration::from_secs(1),
+        })
+        .unwrap();
+        let mut behind = uploader.behind.subscribe();
+
+        // A command which is not uploaded is not waited for.
+        let sink = uploader.invocation_sink().await;
+        sink.send(command_start(buck2_data::CleanCommandStart {}.into()));
+        sink.send(build_result(false));
+        while *behind.borrow() > 0 {
+            behind.changed().await.unwrap();
+        }
+        drop(sink);
+
+        // A build is waited for until its upload (here, to nowhere) is done.
+        let sink = uploader.invocation_sink().await;
+        sink.send(command_start(buck2_data::BuildCommandStart {}.into()));
+        sink.send(build_result(false));
+        assert_eq!(1, *behind.borrow_and_update());
+        let next = tokio::time::timeout(Duration::from_secs(10), uploader.invocation_sink()).await;
+        assert!(next.is_ok());
+        assert_eq!(0, *behind.borrow());
+
+        assert!(
+            BesUploader::new(BesConfig {
+                address: "localhost:1".to_owned(),
+                headers: Vec::new(),
+                project_id: None,
+                upload_timeout: Duration::from_secs(1),
+            })
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,164 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! A Sink appending the events which would be sent to Scribe to a file, one JSON object per line,
+//! so that deployments without Scribe can collect the same telemetry (e.g. by shipping the file
+//! with a log forwarder).
+
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use anyhow::Context;
+
+use crate::sink::scribe::should_send_event;
+use crate::BuckEvent;
+use crate::Event;
+use crate::EventSink;
+use crate::EventSinkStats;
+use crate::EventSinkWithStats;
+
+pub struct JsonLinesSink {
+    /// Opened in append mode, so that the client and the daemon can share a file.
+    file: Mutex<File>,
+    successes: AtomicU64,
+    failures: AtomicU64,
+}
+
+impl JsonLinesSink {
+    pub fn new(path: &Path) -> anyhow::Result<JsonLinesSink> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Error opening telemetry file `{}`", path.display()))?;
+        Ok(JsonLinesSink {
+            file: Mutex::new(file),
+            successes: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+        })
+    }
+
+    pub async fn send_now(&self, event: BuckEvent) {
+        self.offer(event);
+    }
+
+    pub async fn send_messages_now(&self, events: Vec<BuckEvent>) {
+        for event in events {
+            self.offer(event);
+        }
+    }
+
+    pub fn offer(&self, event: BuckEvent) {
+        let proto: Box<buck2_data::BuckEvent> = event.into();
+        // Write each event with a single call, so that lines of concurrent processes don't mix.
+        let res = serde_json::to_string(&proto)
+            .map_err(anyhow::Error::from)
+            .and_then(|mut line| {
+                line.push('\n');
+                Ok(self.file.lock().unwrap().write_all(line.as_bytes())?)
+            });
+        match res {
+            Ok(()) => self.successes.fetch_add(1, Ordering::Relaxed),
+            Err(_) => self.failures.fetch_add(1, Ordering::Relaxed),
+        };
+    }
+}
+
+impl EventSink for JsonLinesSink {
+    fn send(&self, event: Event) {
+        match event {
+            Event::Buck(event) => {
+                if should_send_event(event.data()) {
+                    self.offer(event);
+                }
+            }
+            Event::CommandResult(..) => {}
+            Event::PartialResult(..) => {}
+        }
+    }
+}
+
+impl EventSinkWithStats for JsonLinesSink {
+    fn to_event_sync(self: Arc<Self>) -> Arc<dyn EventSink> {
+        self as _
+    }
+
+    fn stats(&self) -> EventSinkStats {
+        EventSinkStats {
+            successes: self.successes.load(Ordering::Relaxed),
+            failures_unknown: self.failures.load(Ordering::Relaxed),
+            ..EventSinkStats::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::time::SystemTime;
+
+    use buck2_data::CommandStart;
+    use buck2_data::SpanStartEvent;
+
+    use super::*;
+    use crate::TraceId;
+
+    fn span_start(data: Option<buck2_data::span_start_event::Data>) -> Event {
+        Event::Buck(BuckEvent::new(
+            SystemTime::now(),
+            TraceId::new(),
+            None,
+            None,
+            SpanStartEvent { data }.into(),
+        ))
+    }
+
+    #[test]
+    fn test_json_lines_sink() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("telemetry.jsonl");
+        let sink = JsonLinesSink::new(&path).unwrap();
+
+        let command = CommandStart {
+            data: None,
+            metadata: HashMap::new(),
+        };
+        sink.send(span_start(Some(command.clone().into())));
+        // Not a telemetry event.
+        sink.send(span_start(None));
+        sink.send(span_start(Some(command.into())));
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let events: Vec<buck2_data::BuckEvent> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(2, events.len());
+        for event in &events {
+            assert!(matches!(
+                &event.data,
+                Some(buck2_data::buck_event::Data::SpanStart(SpanStartEvent {
+                    data: Some(buck2_data::span_start_event::Data::Command(..))
+                }))
+            ));
+        }
+        assert_eq!(2, sink.stats().successes);
+
+        // Appends to the existing file.
+        let sink = JsonLinesSink::new(&path).unwrap();
+        sink.send(span_start(Some(CommandStart::default().into())));
+        assert_eq!(3, std::fs::read_to_string(&path).unwrap().lines().count());
+    }
+}
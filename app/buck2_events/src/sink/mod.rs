@@ -9,8 +9,11 @@
 
 //! Implementations of `[crate::EventSink]` that are useful in different situations. Buck2 primarily uses the `channel`
 //! sink during normal operation.
+pub mod bes;
 pub(crate) mod channel;
+pub mod json_lines;
 pub(crate) mod null;
+pub mod remote;
 pub mod scribe;
 pub(crate) mod smart_truncate_event;
 pub mod tee;
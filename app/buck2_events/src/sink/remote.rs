@@ -0,0 +1,102 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! The sink for telemetry: the events useful for analyzing builds across users, e.g. invocation
+//! records. They go to Scribe in Meta builds, and can be written to a file elsewhere.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use buck2_core::buck2_env;
+use fbinit::FacebookInit;
+
+use crate::sink::json_lines::JsonLinesSink;
+use crate::sink::scribe;
+use crate::sink::scribe::ThriftScribeSink;
+use crate::BuckEvent;
+use crate::Event;
+use crate::EventSink;
+use crate::EventSinkStats;
+use crate::EventSinkWithStats;
+
+pub enum RemoteEventSink {
+    Scribe(ThriftScribeSink),
+    JsonLines(JsonLinesSink),
+}
+
+impl RemoteEventSink {
+    // Send this event now, bypassing internal message queue.
+    pub async fn send_now(&self, event: BuckEvent) {
+        match self {
+            RemoteEventSink::Scribe(sink) => sink.send_now(event).await,
+            RemoteEventSink::JsonLines(sink) => sink.send_now(event).await,
+        }
+    }
+
+    // Send multiple events now, bypassing internal message queue.
+    pub async fn send_messages_now(&self, events: Vec<BuckEvent>) {
+        match self {
+            RemoteEventSink::Scribe(sink) => sink.send_messages_now(events).await,
+            RemoteEventSink::JsonLines(sink) => sink.send_messages_now(events).await,
+        }
+    }
+}
+
+impl EventSink for RemoteEventSink {
+    fn send(&self, event: Event) {
+        match self {
+            RemoteEventSink::Scribe(sink) => sink.send(event),
+            RemoteEventSink::JsonLines(sink) => sink.send(event),
+        }
+    }
+}
+
+impl EventSinkWithStats for RemoteEventSink {
+    fn to_event_sync(self: Arc<Self>) -> Arc<dyn EventSink> {
+        self as _
+    }
+
+    fn stats(&self) -> EventSinkStats {
+        match self {
+            RemoteEventSink::Scribe(sink) => sink.stats(),
+            RemoteEventSink::JsonLines(sink) => sink.stats(),
+        }
+    }
+}
+
+/// Create the telemetry sink, unless telemetry is disabled for this process. Events are
+/// appended to the file `BUCK2_TELEMETRY_FILE` if set, and sent to Scribe otherwise, in Meta
+/// builds only.
+///
+/// The parameters configure the Scribe client.
+pub fn new_remote_event_sink_if_enabled(
+    fb: FacebookInit,
+    buffer_size: usize,
+    retry_backoff: Duration,
+    retry_attempts: usize,
+    message_batch_size: Option<usize>,
+) -> anyhow::Result<Option<RemoteEventSink>> {
+    if !scribe::is_enabled() {
+        return Ok(None);
+    }
+    if let Some(path) = buck2_env!("BUCK2_TELEMETRY_FILE")? {
+        return Ok(Some(RemoteEventSink::JsonLines(JsonLinesSink::new(
+            Path::new(path),
+        )?)));
+    }
+    Ok(scribe::new_thrift_scribe_sink_if_enabled(
+        fb,
+        buffer_size,
+        retry_backoff,
+        retry_attempts,
+        message_batch_size,
+    )?
+    .map(RemoteEventSink::Scribe))
+}
@@ -29,6 +29,7 @@ mod fbcode {
     use prost::Message;
 
     use crate::metadata;
+    use crate::sink::scribe::should_send_event;
     use crate::sink::smart_truncate_event::smart_truncate_event;
     use crate::BuckEvent;
     use crate::Event;
@@ -193,67 +194,6 @@ mod fbcode {
             }
         }
     }
-
-    fn should_send_event(d: &buck2_data::buck_event::Data) -> bool {
-        use buck2_data::buck_event::Data;
-
-        match d {
-            Data::SpanStart(s) => {
-                use buck2_data::span_start_event::Data;
-
-                match &s.data {
-                    Some(Data::Command(..)) => true,
-                    None => false,
-                    _ => false,
-                }
-            }
-            Data::SpanEnd(s) => {
-                use buck2_data::span_end_event::Data;
-                use buck2_data::ActionExecutionKind;
-
-                match &s.data {
-                    Some(Data::Command(..)) => true,
-                    Some(Data::ActionExecution(a)) => {
-                        match ActionExecutionKind::from_i32(a.execution_kind) {
-                            // Not useful for most log analysis cases
-                            Some(ActionExecutionKind::Simple) => false,
-                            _ => true,
-                        }
-                    }
-                    Some(Data::Analysis(..)) => true,
-                    Some(Data::Load(..)) => true,
-                    Some(Data::CacheUpload(..)) => true,
-                    Some(Data::Materialization(..)) => true,
-                    Some(Data::TestDiscovery(..)) => true,
-                    Some(Data::TestEnd(..)) => true,
-                    None => false,
-                    _ => false,
-                }
-            }
-            Data::Instant(i) => {
-                use buck2_data::instant_event::Data;
-
-                match i.data {
-                    Some(Data::BuildGraphInfo(..)) => true,
-                    Some(Data::RageResult(..)) => true,
-                    Some(Data::ReSession(..)) => true,
-                    Some(Data::StructuredError(..)) => true,
-                    Some(Data::PersistEventLogSubprocess(..)) => true,
-                    None => false,
-                    _ => false,
-                }
-            }
-            Data::Record(r) => {
-                use buck2_data::record_event::Data;
-
-                match r.data {
-                    Some(Data::InvocationRecord(..)) => true,
-                    Some(Data::BuildGraphStats(..)) => true,
-                    None => false,
-                }
-            }
-        }
-    }
 }
 
 #[cfg(not(fbcode_build))]
@@ -290,6 +230,69 @@ mod fbcode {
 
 pub use fbcode::*;
 
+/// Whether an event is sent to Scribe (or to the sinks replacing it), as most events are only
+/// useful in the event log.
+pub(crate) fn should_send_event(d: &buck2_data::buck_event::Data) -> bool {
+    use buck2_data::buck_event::Data;
+
+    match d {
+        Data::SpanStart(s) => {
+            use buck2_data::span_start_event::Data;
+
+            match &s.data {
+                Some(Data::Command(..)) => true,
+                None => false,
+                _ => false,
+            }
+        }
+        Data::SpanEnd(s) => {
+            use buck2_data::span_end_event::Data;
+            use buck2_data::ActionExecutionKind;
+
+            match &s.data {
+                Some(Data::Command(..)) => true,
+                Some(Data::ActionExecution(a)) => {
+                    match ActionExecutionKind::from_i32(a.execution_kind) {
+                        // Not useful for most log analysis cases
+                        Some(ActionExecutionKind::Simple) => false,
+                        _ => true,
+                    }
+                }
+                Some(Data::Analysis(..)) => true,
+                Some(Data::Load(..)) => true,
+                Some(Data::CacheUpload(..)) => true,
+                Some(Data::Materialization(..)) => true,
+                Some(Data::TestDiscovery(..)) => true,
+                Some(Data::TestEnd(..)) => true,
+                None => false,
+                _ => false,
+            }
+        }
+        Data::Instant(i) => {
+            use buck2_data::instant_event::Data;
+
+            match i.data {
+                Some(Data::BuildGraphInfo(..)) => true,
+                Some(Data::RageResult(..)) => true,
+                Some(Data::ReSession(..)) => true,
+                Some(Data::StructuredError(..)) => true,
+                Some(Data::PersistEventLogSubprocess(..)) => true,
+                None => false,
+                _ => false,
+            }
+        }
+        Data::Record(r) => {
+            use buck2_data::record_event::Data;
+
+            match r.data {
+                Some(Data::InvocationRecord(..)) => true,
+                Some(Data::BuildGraphStats(..)) => true,
+                None => false,
+            }
+        }
+    }
+}
+
 fn new_thrift_scribe_sink_if_fbcode(
     fb: FacebookInit,
     buffer_size: usize,
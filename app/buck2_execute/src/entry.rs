@@ -13,6 +13,7 @@ use std::time::Instant;
 
 use anyhow::Context as _;
 use async_recursion::async_recursion;
+use buck2_common::digest_pool::spawn_digest;
 use buck2_common::file_ops::FileDigest;
 use buck2_common::file_ops::FileDigestConfig;
 use buck2_common::file_ops::FileMetadata;
@@ -29,9 +30,7 @@ use faccess::PathExt;
 use futures::future::try_join;
 use futures::future::try_join_all;
 use futures::Future;
-use once_cell::sync::Lazy;
 use pathdiff::diff_paths;
-use tokio::sync::Semaphore;
 
 use crate::directory::new_symlink;
 use crate::directory::ActionDirectoryBuilder;
@@ -182,16 +181,13 @@ fn build_file_metadata(
     digest_config: FileDigestConfig,
     blocking_executor: &dyn BlockingExecutor,
 ) -> impl Future<Output = anyhow::Result<(FileMetadata, HashingInfo)>> + '_ {
-    static SEMAPHORE: Lazy<Semaphore> = Lazy::new(|| Semaphore::new(100));
     let exec_path = disk_path.clone();
     let executable = blocking_executor.execute_io_inline(move || Ok(exec_path.executable()));
-    let file_digest =
-        tokio::task::spawn_blocking(move || FileDigest::from_file(&disk_path, digest_config));
+    let file_digest = spawn_digest(move || FileDigest::from_file(&disk_path, digest_config));
 
     async move {
-        let _permit = SEMAPHORE.acquire().await.unwrap();
         let hashing_start = Instant::now();
-        let file_digest = file_digest.await??;
+        let file_digest = file_digest.await?;
         let hashing_duration = HashingInfo::new(hashing_start.elapsed(), 1);
         let file_metadata = FileMetadata {
             digest: TrackedFileDigest::new(file_digest, digest_config.as_cas_digest_config()),
@@ -14,6 +14,9 @@ use anyhow::Context as _;
 use async_trait::async_trait;
 use buck2_core::buck2_env;
 use buck2_core::fs::project::ProjectRoot;
+use buck2_events::dispatch::get_dispatcher_opt;
+use buck2_events::dispatch::with_dispatcher;
+use buck2_events::dispatch::EventDispatcher;
 use buck2_futures::cancellation::CancellationContext;
 use buck2_util::threads::thread_spawn;
 use crossbeam_channel::unbounded;
@@ -72,6 +75,9 @@ pub trait IoRequest: Send + Sync + 'static {
 struct ThreadPoolIoRequest {
     io: Box<dyn IoRequest>,
     sender: oneshot::Sender<anyhow::Result<()>>,
+    /// The dispatcher of the command which requested the I/O, so that it runs with that
+    /// command's soft error and Windows path options.
+    dispatcher: Option<EventDispatcher>,
 }
 
 #[derive(Allocative)]
@@ -105,8 +111,16 @@ impl BuckBlockingExecutor {
             let command_receiver = command_receiver.clone();
             let fs = fs.dupe();
             thread_spawn(&format!("buck-io-{}", i), move || {
-                for ThreadPoolIoRequest { sender, io } in command_receiver.iter() {
-                    let res = io.execute(&fs);
+                for ThreadPoolIoRequest {
+                    sender,
+                    io,
+                    dispatcher,
+                } in command_receiver.iter()
+                {
+                    let res = match dispatcher {
+                        Some(dispatcher) => with_dispatcher(dispatcher, || io.execute(&fs)),
+                        None => io.execute(&fs),
+                    };
                     let _ignored = sender.send(res);
                 }
             })
@@ -144,7 +158,11 @@ impl BlockingExecutor for BuckBlockingExecutor {
 
         // Ignore errors sending as they'll translate to an error receiving once we drop the
         // sender.
-        let _ignored = self.command_sender.send(ThreadPoolIoRequest { io, sender });
+        let _ignored = self.command_sender.send(ThreadPoolIoRequest {
+            io,
+            sender,
+            dispatcher: get_dispatcher_opt(),
+        });
 
         cancellations
             .critical_section(|| async move { receiver.await.context("Pool shut down")? })
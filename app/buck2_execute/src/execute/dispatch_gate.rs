@@ -0,0 +1,129 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! A per-command switch that holds back new action executions.
+//!
+//! This is toggled from the interactive console so that a user can freeze a build that appears
+//! hung while they inspect it. Actions that are already running are not affected, and neither are
+//! other commands running on the same daemon.
+
+use std::sync::Arc;
+
+use dice::UserComputationData;
+use dupe::Dupe;
+use tokio::sync::watch;
+
+#[derive(Clone, Dupe)]
+pub struct DispatchGate(Arc<watch::Sender<bool>>);
+
+impl Default for DispatchGate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DispatchGate {
+    pub fn new() -> Self {
+        Self(Arc::new(watch::channel(false).0))
+    }
+
+    /// Whether new action executions are currently held back.
+    pub fn is_paused(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    /// Pause or resume dispatch of new actions. Returns the previous state.
+    pub fn set_paused(&self, paused: bool) -> bool {
+        self.0.send_replace(paused)
+    }
+
+    /// Flip the current state, returning the new one.
+    pub fn toggle(&self) -> bool {
+        let mut paused = false;
+        self.0.send_modify(|v| {
+            *v = !*v;
+            paused = *v;
+        });
+        paused
+    }
+
+    /// Wait until dispatch is not paused. Returns immediately in the common case.
+    pub async fn wait(&self) {
+        if !self.is_paused() {
+            return;
+        }
+
+        let mut receiver = self.0.subscribe();
+        while *receiver.borrow_and_update() {
+            if receiver.changed().await.is_err() {
+                // We hold a reference to the sender so this can't happen, but don't block forever
+                // if it did.
+                return;
+            }
+        }
+    }
+}
+
+pub trait SetDispatchGate {
+    fn set_dispatch_gate(&mut self, gate: DispatchGate);
+}
+
+pub trait HasDispatchGate {
+    /// The gate of the command this computation runs for. This is not set in tests.
+    fn get_dispatch_gate(&self) -> Option<DispatchGate>;
+}
+
+impl SetDispatchGate for UserComputationData {
+    fn set_dispatch_gate(&mut self, gate: DispatchGate) {
+        self.data.set(gate);
+    }
+}
+
+impl HasDispatchGate for UserComputationData {
+    fn get_dispatch_gate(&self) -> Option<DispatchGate> {
+        self.data.get::<DispatchGate>().ok().map(|gate| gate.dupe())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_pause_and_resume() {
+        let gate = DispatchGate::new();
+        assert!(!gate.set_paused(true));
+        assert!(gate.is_paused());
+
+        let waiter = tokio::spawn({
+            let gate = gate.dupe();
+            async move { gate.wait().await }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!waiter.is_finished());
+
+        assert!(!gate.toggle());
+        waiter.await.unwrap();
+        assert!(!gate.is_paused());
+    }
+
+    #[tokio::test]
+    async fn test_gates_are_independent() {
+        let paused = DispatchGate::new();
+        paused.set_paused(true);
+
+        let other = DispatchGate::new();
+        assert!(!other.is_paused());
+        tokio::time::timeout(Duration::from_secs(10), other.wait())
+            .await
+            .unwrap();
+    }
+}
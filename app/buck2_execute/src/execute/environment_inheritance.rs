@@ -7,7 +7,11 @@
  * of this source tree.
  */
 
+use std::collections::HashSet;
 use std::ffi::OsString;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::Mutex;
 use std::sync::OnceLock;
 
 use dupe::Dupe;
@@ -57,10 +61,10 @@ const ENV_ALLOW_LIST: &[&str] = &[
     "WINDIR",
 ];
 
-#[derive(Copy, Clone, Dupe, Debug)]
+#[derive(Clone, Dupe, Debug)]
 pub struct EnvironmentInheritance {
     clear: bool,
-    values: &'static [(&'static str, OsString)],
+    values: Arc<[(String, OsString)]>,
     exclusions: &'static [&'static str],
 }
 
@@ -72,23 +76,36 @@ impl EnvironmentInheritance {
 
         // We create this *once* since getenv is actually not cheap (being O(n) of the environment
         // size).
-        static TEST_CELL: OnceLock<Vec<(&'static str, OsString)>> = OnceLock::new();
+        static TEST_CELL: OnceLock<Arc<[(String, OsString)]>> = OnceLock::new();
 
         let values = TEST_CELL.get_or_init(|| {
             let mut ret = Vec::new();
             for list in allowlists.iter() {
                 for key in list.iter() {
                     if let Some(value) = std::env::var_os(key) {
-                        ret.push((*key, value));
+                        ret.push(((*key).to_owned(), value));
                     }
                 }
             }
-            ret
+            ret.into()
         });
 
         Self {
             clear: true,
-            values,
+            values: values.dupe(),
+            exclusions: &[],
+        }
+    }
+
+    /// Start from an empty environment, plus the values the daemon has for `keys`.
+    pub fn allowlist<'a>(keys: impl IntoIterator<Item = &'a str>) -> Self {
+        let values: Vec<_> = keys
+            .into_iter()
+            .filter_map(|key| Some((key.to_owned(), std::env::var_os(key)?)))
+            .collect();
+        Self {
+            clear: true,
+            values: values.into(),
             exclusions: &[],
         }
     }
@@ -98,7 +115,7 @@ impl EnvironmentInheritance {
     pub fn local_command_exclusions() -> Self {
         Self {
             clear: false,
-            values: &[],
+            values: Arc::new([]),
             exclusions: &[
                 "PYTHONPATH",
                 "PYTHONHOME",
@@ -111,14 +128,14 @@ impl EnvironmentInheritance {
 
     pub fn empty() -> Self {
         Self {
-            values: &[],
+            values: Arc::new([]),
             exclusions: &[],
             clear: true,
         }
     }
 
-    pub fn values(&self) -> impl Iterator<Item = (&'static str, &'static OsString)> {
-        self.values.iter().map(|(k, v)| (*k, v))
+    pub fn values(&self) -> impl Iterator<Item = (&str, &OsString)> {
+        self.values.iter().map(|(k, v)| (k.as_str(), v))
     }
 
     pub fn exclusions(&self) -> impl Iterator<Item = &'static str> {
@@ -129,3 +146,242 @@ impl EnvironmentInheritance {
         self.clear
     }
 }
+
+#[derive(Debug, buck2_error::Error)]
+enum EnvironmentPolicyError {
+    #[error(
+        "Invalid value for `build.action_env_policy`: `{0}`, expected `inherit`, `warn` or `hermetic`"
+    )]
+    #[buck2(user)]
+    InvalidMode(String),
+}
+
+/// What local build actions get of the environment of the daemon, set with
+/// `build.action_env_policy`. Inherited variables are not part of the action key, so they can
+/// make the same action produce different outputs on different machines.
+#[derive(Copy, Clone, Dupe, Debug, Default, PartialEq, Eq)]
+pub enum EnvironmentPolicyMode {
+    /// Inherit the whole environment (minus a few known bad variables).
+    #[default]
+    Inherit,
+    /// Inherit the whole environment, but warn about the variables which are inherited without
+    /// being allowlisted, as a first step to `Hermetic`.
+    Warn,
+    /// Start from an empty environment plus the allowlisted variables.
+    Hermetic,
+}
+
+impl FromStr for EnvironmentPolicyMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "inherit" => Ok(Self::Inherit),
+            "warn" => Ok(Self::Warn),
+            "hermetic" => Ok(Self::Hermetic),
+            _ => Err(EnvironmentPolicyError::InvalidMode(s.to_owned()).into()),
+        }
+    }
+}
+
+/// The environment policy of local build actions: the mode and the variables allowlisted by
+/// `build.action_env_allowlist`. Actions allowlist more variables with `env_inherit`.
+///
+/// A policy is created for each command, and remembers which variables it already warned about.
+#[derive(Clone, Dupe, Debug, Default)]
+pub struct EnvironmentPolicy {
+    mode: EnvironmentPolicyMode,
+    allowlist: Arc<Vec<String>>,
+    reported: Arc<Mutex<HashSet<String>>>,
+}
+
+impl EnvironmentPolicy {
+    pub fn new(mode: EnvironmentPolicyMode, allowlist: Vec<String>) -> Self {
+        Self {
+            mode,
+            allowlist: Arc::new(allowlist),
+            reported: Default::default(),
+        }
+    }
+
+    pub fn mode(&self) -> EnvironmentPolicyMode {
+        self.mode
+    }
+
+    fn is_allowed(&self, key: &str, env_inherit: &[String]) -> bool {
+        self.allowlist.iter().chain(env_inherit).any(|k| k == key)
+    }
+
+    /// The inheritance of a command which requested `inheritance`. The policy only applies to
+    /// commands which would inherit the environment of the daemon.
+    pub fn apply(
+        &self,
+        inheritance: Option<&EnvironmentInheritance>,
+        env_inherit: &[String],
+    ) -> Option<EnvironmentInheritance> {
+        match (self.mode, inheritance) {
+            (EnvironmentPolicyMode::Hermetic, None) => Some(self.hermetic(env_inherit)),
+            (EnvironmentPolicyMode::Hermetic, Some(inheritance)) if !inheritance.clear() => {
+                Some(self.hermetic(env_inherit))
+            }
+            (_, inheritance) => inheritance.cloned(),
+        }
+    }
+
+    fn hermetic(&self, env_inherit: &[String]) -> EnvironmentInheritance {
+        EnvironmentInheritance::allowlist(
+            self.allowlist
+                .iter()
+                .chain(env_inherit)
+                .map(|k| k.as_str())
+                .collect::<HashSet<_>>(),
+        )
+    }
+
+    /// The variables of the daemon a command which requested `inheritance` inherits without them
+    /// being allowlisted, ignoring those the command sets itself. Reported in `Warn` mode.
+    pub fn not_allowlisted<'a>(
+        &self,
+        inheritance: Option<&EnvironmentInheritance>,
+        env_inherit: &[String],
+        command_env: impl IntoIterator<Item = &'a str>,
+    ) -> Vec<String> {
+        if self.mode != EnvironmentPolicyMode::Warn || inheritance.is_some_and(|i| i.clear()) {
+            return Vec::new();
+        }
+        let command_env: HashSet<&str> = command_env.into_iter().collect();
+        let mut keys: Vec<String> = daemon_env_keys()
+            .iter()
+            .filter(|key| {
+                !self.is_allowed(key, env_inherit)
+                    && !command_env.contains(key.as_str())
+                    && !inheritance.is_some_and(|i| i.exclusions().any(|e| e == key.as_str()))
+            })
+            .cloned()
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    /// Like `not_allowlisted`, but only the variables which were not returned yet, so that each
+    /// variable is warned about once per command rather than once per action.
+    pub fn not_allowlisted_unreported<'a>(
+        &self,
+        inheritance: Option<&EnvironmentInheritance>,
+        env_inherit: &[String],
+        command_env: impl IntoIterator<Item = &'a str>,
+    ) -> Vec<String> {
+        let mut keys = self.not_allowlisted(inheritance, env_inherit, command_env);
+        if !keys.is_empty() {
+            let mut reported = self.reported.lock().unwrap();
+            keys.retain(|key| reported.insert(key.clone()));
+        }
+        keys
+    }
+}
+
+/// The names of the variables of the daemon environment, which doesn't change.
+fn daemon_env_keys() -> &'static [String] {
+    static KEYS: OnceLock<Vec<String>> = OnceLock::new();
+    KEYS.get_or_init(|| {
+        std::env::vars_os()
+            .filter_map(|(k, _)| k.into_string().ok())
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_mode_from_str() {
+        assert_eq!(
+            EnvironmentPolicyMode::Hermetic,
+            "hermetic".parse::<EnvironmentPolicyMode>().unwrap()
+        );
+        assert!("strict".parse::<EnvironmentPolicyMode>().is_err());
+    }
+
+    #[test]
+    fn test_hermetic_policy() {
+        let policy = EnvironmentPolicy::new(
+            EnvironmentPolicyMode::Hermetic,
+            vec!["BUCK2_TEST_ENV_POLICY_UNSET".to_owned()],
+        );
+        let inheritance = policy
+            .apply(
+                Some(&EnvironmentInheritance::local_command_exclusions()),
+                &[],
+            )
+            .unwrap();
+        assert!(inheritance.clear());
+        assert_eq!(0, inheritance.values().count());
+
+        // Commands which already start from a clean environment are left alone.
+        let inheritance = policy
+            .apply(Some(&EnvironmentInheritance::test_allowlist()), &[])
+            .unwrap();
+        assert_eq!(
+            EnvironmentInheritance::test_allowlist().values().count(),
+            inheritance.values().count()
+        );
+    }
+
+    #[test]
+    fn test_warn_policy_reports_once() {
+        let inheritance = EnvironmentInheritance::local_command_exclusions();
+        let Some(key) = daemon_env_keys()
+            .iter()
+            .find(|key| !inheritance.exclusions().any(|e| e == key.as_str()))
+            .cloned()
+        else {
+            return;
+        };
+        let policy = EnvironmentPolicy::new(EnvironmentPolicyMode::Warn, Vec::new());
+
+        assert!(
+            policy
+                .not_allowlisted_unreported(Some(&inheritance), &[], [])
+                .contains(&key)
+        );
+        assert!(
+            !policy
+                .not_allowlisted_unreported(Some(&inheritance), &[], [])
+                .contains(&key)
+        );
+        // Still inherited, just not reported again.
+        assert!(
+            policy
+                .not_allowlisted(Some(&inheritance), &[], [])
+                .contains(&key)
+        );
+
+        // Nothing is inherited from an environment which is cleared.
+        let policy = EnvironmentPolicy::new(EnvironmentPolicyMode::Warn, Vec::new());
+        assert!(
+            policy
+                .not_allowlisted_unreported(Some(&EnvironmentInheritance::empty()), &[], [])
+                .is_empty()
+        );
+        // Nor are the variables the command sets, or allowlists.
+        let policy = EnvironmentPolicy::new(EnvironmentPolicyMode::Warn, Vec::new());
+        assert!(
+            !policy
+                .not_allowlisted_unreported(None, &[], [key.as_str()])
+                .contains(&key)
+        );
+        assert!(
+            !policy
+                .not_allowlisted_unreported(None, &[key.clone()], [])
+                .contains(&key)
+        );
+    }
+
+    #[test]
+    fn test_inherit_policy() {
+        let policy = EnvironmentPolicy::default();
+        assert!(policy.apply(None, &[]).is_none());
+        assert!(policy.not_allowlisted(None, &[], []).is_empty());
+    }
+}
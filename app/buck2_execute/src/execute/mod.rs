@@ -16,6 +16,7 @@ pub mod claim;
 pub mod clean_output_paths;
 pub mod command_executor;
 pub mod dep_file_digest;
+pub mod dispatch_gate;
 pub mod environment_inheritance;
 pub mod inputs_directory;
 pub mod kind;
@@ -24,6 +25,7 @@ pub mod output;
 pub mod paths_with_digest;
 pub mod prepared;
 pub mod request;
+pub mod resource_pools;
 pub mod result;
 pub mod target;
 pub mod testing_dry_run;
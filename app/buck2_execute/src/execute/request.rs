@@ -17,6 +17,7 @@ use buck2_common::local_resource_state::LocalResourceState;
 use buck2_core::directory::DirectoryEntry;
 use buck2_core::directory::DirectoryIterator;
 use buck2_core::directory::FingerprintedDirectory;
+use buck2_core::execution_types::executor_config::CategoryExecutorPreference;
 use buck2_core::fs::artifact_path_resolver::ArtifactFs;
 use buck2_core::fs::buck_out_path::BuckOutPath;
 use buck2_core::fs::buck_out_path::BuckOutScratchPath;
@@ -196,6 +197,17 @@ impl ExecutorPreference {
     }
 }
 
+impl From<CategoryExecutorPreference> for ExecutorPreference {
+    fn from(preference: CategoryExecutorPreference) -> Self {
+        match preference {
+            CategoryExecutorPreference::LocalOnly => Self::LocalRequired,
+            CategoryExecutorPreference::PreferLocal => Self::LocalPreferred,
+            CategoryExecutorPreference::PreferRemote => Self::RemotePreferred,
+            CategoryExecutorPreference::RemoteOnly => Self::RemoteRequired,
+        }
+    }
+}
+
 pub struct CommandExecutionPaths {
     inputs: Vec<CommandExecutionInput>,
     outputs: IndexSet<CommandExecutionOutput>,
@@ -307,6 +319,9 @@ pub struct CommandExecutionRequest {
     pub outputs_cleanup: bool,
     /// What environment variables to inherit from the Buck2 daemon.
     local_environment_inheritance: Option<EnvironmentInheritance>,
+    /// Environment variables of the Buck2 daemon which this command is allowed to inherit under
+    /// the hermetic environment policy, in addition to the ones allowlisted by config.
+    env_inherit: Vec<String>,
     /// Whether this command should override the fallback-only behavior on an hybrid executor and
     /// thus always run as if the executor was full-hybrid, assuming it is capable.
     force_full_hybrid_if_capable: bool,
@@ -318,6 +333,9 @@ pub struct CommandExecutionRequest {
     /// Whether the executor should guarantee that the inodes for all inputs are unique (i.e. avoid
     /// hardlinking identical input files, for example)
     unique_input_inodes: bool,
+    /// Whether a local execution should vary what the command shouldn't depend on: the order of
+    /// its environment and the path of its temporary directory (`buck2 debug determinism`).
+    perturb_environment: bool,
     /// Remote dep file key, if the action has a dep file.
     /// If this key is set and remote dep file caching is enabled, it will be used to query the cache.
     pub remote_dep_file_key: Option<DepFileDigest>,
@@ -343,11 +361,13 @@ impl CommandExecutionRequest {
             prefetch_lossy_stderr: false,
             outputs_cleanup: true,
             local_environment_inheritance: None,
+            env_inherit: Vec::new(),
             force_full_hybrid_if_capable: false,
             disable_miniperf: false,
             required_local_resources: SortedSet::new(),
             worker: None,
             unique_input_inodes: false,
+            perturb_environment: false,
             remote_dep_file_key: None,
         }
     }
@@ -484,6 +504,15 @@ impl CommandExecutionRequest {
         self.local_environment_inheritance.as_ref()
     }
 
+    pub fn with_env_inherit(mut self, env_inherit: Vec<String>) -> Self {
+        self.env_inherit = env_inherit;
+        self
+    }
+
+    pub fn env_inherit(&self) -> &[String] {
+        &self.env_inherit
+    }
+
     pub fn with_force_full_hybrid_if_capable(mut self, force_full_hybrid_if_capable: bool) -> Self {
         self.force_full_hybrid_if_capable = force_full_hybrid_if_capable;
         self
@@ -528,6 +557,15 @@ impl CommandExecutionRequest {
     pub fn unique_input_inodes(&self) -> bool {
         self.unique_input_inodes
     }
+
+    pub fn with_perturb_environment(mut self, perturb_environment: bool) -> Self {
+        self.perturb_environment = perturb_environment;
+        self
+    }
+
+    pub fn perturb_environment(&self) -> bool {
+        self.perturb_environment
+    }
 }
 
 /// Is an output a file or a directory
@@ -684,3 +722,45 @@ impl ResolvedCommandExecutionOutput {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_and_category_executor_preference() {
+        let and = |action: ExecutorPreference, category: CategoryExecutorPreference| {
+            action.and(category.into())
+        };
+        assert!(matches!(
+            and(
+                ExecutorPreference::Default,
+                CategoryExecutorPreference::LocalOnly
+            ),
+            Ok(ExecutorPreference::LocalRequired)
+        ));
+        // The preference of the action wins over that of its category.
+        assert!(matches!(
+            and(
+                ExecutorPreference::RemotePreferred,
+                CategoryExecutorPreference::PreferLocal
+            ),
+            Ok(ExecutorPreference::RemotePreferred)
+        ));
+        // But not over a requirement.
+        assert!(matches!(
+            and(
+                ExecutorPreference::LocalPreferred,
+                CategoryExecutorPreference::RemoteOnly
+            ),
+            Ok(ExecutorPreference::RemoteRequired)
+        ));
+        assert!(
+            and(
+                ExecutorPreference::LocalRequired,
+                CategoryExecutorPreference::RemoteOnly
+            )
+            .is_err()
+        );
+    }
+}
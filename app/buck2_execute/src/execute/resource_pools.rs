@@ -0,0 +1,223 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Resource pools limit how many tests using the same hardware (a GPU, a device, a port) run at
+//! once. Their capacities are set in the root buckconfig:
+//!
+//! ```ini
+//! [test]
+//! resource_pools = gpu=2, device:pixel6=1
+//! ```
+//!
+//! and tests request units of them with `ExternalRunnerTestInfo(resource_pools = {"gpu": 1})`.
+//! A pool without a configured capacity has a capacity of one, so that it can be used as an
+//! exclusivity tag.
+//!
+//! The pools are held by the daemon, so that they are shared by all the `buck2 test` commands
+//! running on it, and are rebuilt when `test.resource_pools` changes. Tests holding units of the
+//! previous pools keep them until they finish.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use anyhow::Context;
+use dashmap::DashMap;
+use dice::UserComputationData;
+use dupe::Dupe;
+use indexmap::IndexMap;
+use tokio::sync::OwnedSemaphorePermit;
+use tokio::sync::Semaphore;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(user)]
+enum ResourcePoolsError {
+    #[error("Invalid `test.resource_pools` entry `{0}`, expected `<pool>=<capacity>`")]
+    InvalidEntry(String),
+    #[error(
+        "Test requires {units} units of resource pool `{pool}`, but its capacity is {capacity}"
+    )]
+    ExceedsCapacity {
+        pool: String,
+        units: u32,
+        capacity: u32,
+    },
+}
+
+/// The resource pools for one value of `test.resource_pools`.
+pub struct ResourcePools {
+    capacities: HashMap<String, u32>,
+    semaphores: DashMap<String, Arc<Semaphore>>,
+}
+
+/// Units of resource pools held by a test, released when dropped.
+pub struct ResourcePoolsPermits {
+    _permits: Vec<OwnedSemaphorePermit>,
+}
+
+impl ResourcePools {
+    /// Parse `test.resource_pools`.
+    pub fn from_config(config: Option<&str>) -> anyhow::Result<ResourcePools> {
+        let mut capacities = HashMap::new();
+        for entry in config
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|e| !e.is_empty())
+        {
+            let (pool, capacity) = entry
+                .rsplit_once('=')
+                .ok_or_else(|| ResourcePoolsError::InvalidEntry(entry.to_owned()))?;
+            let capacity: u32 = capacity
+                .trim()
+                .parse()
+                .ok()
+                .filter(|c| *c > 0)
+                .ok_or_else(|| ResourcePoolsError::InvalidEntry(entry.to_owned()))?;
+            capacities.insert(pool.trim().to_owned(), capacity);
+        }
+        Ok(ResourcePools {
+            capacities,
+            semaphores: DashMap::new(),
+        })
+    }
+
+    fn capacity(&self, pool: &str) -> u32 {
+        self.capacities.get(pool).copied().unwrap_or(1)
+    }
+
+    /// Wait until the given units of each pool are available, and take them.
+    pub async fn acquire(
+        &self,
+        requested: &IndexMap<&str, u32>,
+    ) -> anyhow::Result<ResourcePoolsPermits> {
+        // Acquire in a fixed order, so that tests requesting several pools can't deadlock.
+        let requested: BTreeMap<&str, u32> = requested.iter().map(|(k, v)| (*k, *v)).collect();
+        let mut permits = Vec::with_capacity(requested.len());
+        for (pool, units) in requested {
+            let capacity = self.capacity(pool);
+            if units > capacity {
+                return Err(ResourcePoolsError::ExceedsCapacity {
+                    pool: pool.to_owned(),
+                    units,
+                    capacity,
+                }
+                .into());
+            }
+            let semaphore = self
+                .semaphores
+                .entry(pool.to_owned())
+                .or_insert_with(|| Arc::new(Semaphore::new(capacity as usize)))
+                .clone();
+            permits.push(semaphore.acquire_many_owned(units).await?);
+        }
+        Ok(ResourcePoolsPermits { _permits: permits })
+    }
+}
+
+/// The resource pools of the daemon, along with the config they were built from.
+#[derive(Default)]
+pub struct DaemonResourcePools {
+    current: Mutex<Option<(Option<String>, Arc<ResourcePools>)>>,
+}
+
+impl DaemonResourcePools {
+    /// The pools for this value of `test.resource_pools`: the current ones if it is unchanged,
+    /// otherwise new ones, which replace them.
+    pub fn get(&self, config: Option<&str>) -> anyhow::Result<Arc<ResourcePools>> {
+        let mut current = self.current.lock().unwrap();
+        if let Some((current_config, pools)) = &*current {
+            if current_config.as_deref() == config {
+                return Ok(pools.dupe());
+            }
+        }
+        let pools =
+            Arc::new(ResourcePools::from_config(config).context("Invalid `test.resource_pools`")?);
+        *current = Some((config.map(str::to_owned), pools.dupe()));
+        Ok(pools)
+    }
+}
+
+pub trait SetResourcePools {
+    fn set_resource_pools(&mut self, pools: Arc<DaemonResourcePools>);
+}
+
+pub trait HasResourcePools {
+    fn get_resource_pools(&self) -> Arc<DaemonResourcePools>;
+}
+
+impl SetResourcePools for UserComputationData {
+    fn set_resource_pools(&mut self, pools: Arc<DaemonResourcePools>) {
+        self.data.set(pools);
+    }
+}
+
+impl HasResourcePools for UserComputationData {
+    fn get_resource_pools(&self) -> Arc<DaemonResourcePools> {
+        self.data
+            .get::<Arc<DaemonResourcePools>>()
+            .expect("DaemonResourcePools should be set")
+            .dupe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use indexmap::IndexMap;
+
+    use crate::execute::resource_pools::DaemonResourcePools;
+    use crate::execute::resource_pools::ResourcePools;
+
+    #[test]
+    fn test_from_config() -> anyhow::Result<()> {
+        let pools = ResourcePools::from_config(Some("gpu=2, device:pixel6 = 1"))?;
+        assert_eq!(2, pools.capacity("gpu"));
+        assert_eq!(1, pools.capacity("device:pixel6"));
+        assert_eq!(1, pools.capacity("port:8080"));
+
+        assert!(ResourcePools::from_config(None).is_ok());
+        assert!(ResourcePools::from_config(Some("gpu")).is_err());
+        assert!(ResourcePools::from_config(Some("gpu=0")).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_acquire() -> anyhow::Result<()> {
+        let pools = ResourcePools::from_config(Some("gpu=2"))?;
+        let requested = IndexMap::from([("gpu", 1), ("port:8080", 1)]);
+
+        let first = pools.acquire(&requested).await?;
+        let port = pools.semaphores.get("port:8080").unwrap().clone();
+        assert_eq!(0, port.available_permits());
+        drop(first);
+        assert_eq!(1, port.available_permits());
+
+        assert!(pools.acquire(&IndexMap::from([("gpu", 3)])).await.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_daemon_pools_rebuilt_on_config_change() -> anyhow::Result<()> {
+        let daemon = DaemonResourcePools::default();
+        let first = daemon.get(Some("gpu=2"))?;
+        assert!(Arc::ptr_eq(&first, &daemon.get(Some("gpu=2"))?));
+
+        let changed = daemon.get(Some("gpu=4"))?;
+        assert!(!Arc::ptr_eq(&first, &changed));
+        assert_eq!(4, changed.capacity("gpu"));
+        assert!(Arc::ptr_eq(&changed, &daemon.get(Some("gpu=4"))?));
+
+        assert!(daemon.get(Some("gpu")).is_err());
+        assert!(Arc::ptr_eq(&changed, &daemon.get(Some("gpu=4"))?));
+        Ok(())
+    }
+}
@@ -16,6 +16,7 @@ use std::time::SystemTime;
 
 use buck2_action_metadata_proto::RemoteDepFile;
 use buck2_core::fs::artifact_path_resolver::ArtifactFs;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
 use dupe::Dupe;
 use indexmap::IndexMap;
 
@@ -180,6 +181,9 @@ pub struct CommandExecutionResult {
     /// This is picked up from the action result's auxiliary metadata and
     /// is used to verify the dep file cache lookup result
     pub dep_file_metadata: Option<RemoteDepFile>,
+    /// Files of the project read by the command but not declared as its inputs, when
+    /// `build.trace_undeclared_inputs` is set.
+    pub undeclared_inputs: Vec<ProjectRelativePathBuf>,
 }
 
 impl CommandExecutionResult {
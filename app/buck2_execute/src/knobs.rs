@@ -9,6 +9,9 @@
 
 use dupe::Dupe;
 
+use crate::execute::environment_inheritance::EnvironmentPolicy;
+use crate::re::retry::ReRetryPolicy;
+
 /// Command-level config that can tweak how the executors work.
 #[derive(Clone, Dupe, Default)]
 pub struct ExecutorGlobalKnobs {
@@ -17,4 +20,14 @@ pub struct ExecutorGlobalKnobs {
     /// Whether to emit action keys to execution logs (thos are pretty verbose and omitted by
     /// default).
     pub log_action_keys: bool,
+
+    /// Whether to trace the files read by local commands, to report the ones which are not
+    /// declared as inputs.
+    pub trace_undeclared_inputs: bool,
+
+    /// What local build actions get of the environment of the daemon.
+    pub env_policy: EnvironmentPolicy,
+
+    /// How to retry RE executions which fail with transient errors.
+    pub re_retry_policy: ReRetryPolicy,
 }
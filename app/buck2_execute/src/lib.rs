@@ -26,5 +26,6 @@ pub mod execute;
 pub mod knobs;
 pub mod materialize;
 pub mod output_size;
+pub mod output_symlinks;
 pub mod path;
 pub mod re;
@@ -0,0 +1,210 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Cache of downloaded files shared by all the checkouts of a user, enabled by
+//! `http.download_cache`.
+//!
+//! Files are stored by checksum, as `<dir>/sha1/<hex>` and `<dir>/sha256/<hex>`. Files are only
+//! stored after their checksums were verified, and are written atomically, so that an entry is
+//! either complete or missing. Entries are verified again when they are used, and removed if they
+//! don't match, since the cache directory is outside of buck2's control. Since the cache is an
+//! optimization, errors using it are logged and ignored.
+
+use std::io::Read;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_http::HttpClient;
+use sha1::Digest;
+use sha1::Sha1;
+use sha2::Sha256;
+
+use crate::materialize::http::Checksum;
+
+pub struct DownloadCache {
+    dir: AbsNormPathBuf,
+}
+
+impl DownloadCache {
+    pub fn new(client: &HttpClient) -> Option<DownloadCache> {
+        let dir = client.download_cache_dir()?;
+        match AbsNormPathBuf::new(dir.to_owned()) {
+            Ok(dir) => Some(DownloadCache { dir }),
+            Err(e) => {
+                tracing::warn!("Invalid download cache directory: {:#}", e);
+                None
+            }
+        }
+    }
+
+    fn paths(&self, checksum: &Checksum) -> anyhow::Result<Vec<AbsNormPathBuf>> {
+        let mut paths = Vec::new();
+        // `Checksum` validated these are hex digests, so they are valid file names.
+        if let Some(sha256) = checksum.sha256() {
+            paths.push(self.dir.join_normalized(format!("sha256/{}", sha256))?);
+        }
+        if let Some(sha1) = checksum.sha1() {
+            paths.push(self.dir.join_normalized(format!("sha1/{}", sha1))?);
+        }
+        Ok(paths)
+    }
+
+    /// Size of the cached file with this checksum, if it's cached.
+    pub fn cached_len(&self, checksum: &Checksum) -> Option<u64> {
+        let paths = self.paths(checksum).ok()?;
+        paths
+            .iter()
+            .find_map(|path| fs_util::symlink_metadata(path).ok())
+            .map(|m| m.len())
+    }
+
+    /// Copy the cached file with this checksum to `dest`. Returns whether it was cached. An entry
+    /// which doesn't match the checksum is removed, and reported as not cached.
+    pub(crate) fn fetch(&self, checksum: &Checksum, dest: &AbsNormPath) -> bool {
+        let res: anyhow::Result<bool> = try {
+            let mut found = false;
+            for path in self.paths(checksum)? {
+                if fs_util::try_exists(&path)? {
+                    fs_util::copy(&path, dest)?;
+                    // Check the copy, so that the entry can't change after it was checked.
+                    if matches_checksum(dest, checksum)? {
+                        found = true;
+                        break;
+                    }
+                    tracing::warn!(
+                        "Removing download cache entry `{}`, which does not match its checksum",
+                        path
+                    );
+                    fs_util::remove_file(&path)?;
+                    fs_util::remove_file(dest)?;
+                }
+            }
+            found
+        };
+        res.unwrap_or_else(|e| {
+            tracing::warn!("Error reading the download cache: {:#}", e);
+            false
+        })
+    }
+
+    /// Store the file `src`, which has this checksum.
+    pub(crate) fn store(&self, checksum: &Checksum, src: &AbsNormPath) {
+        let res: anyhow::Result<()> = try {
+            for path in self.paths(checksum)? {
+                if fs_util::try_exists(&path)? {
+                    continue;
+                }
+                let dir = path.parent().expect("cache paths have a parent");
+                fs_util::create_dir_all(dir)?;
+                // Unique across concurrent stores in this daemon, and across daemons.
+                static NEXT_TMP: AtomicU64 = AtomicU64::new(0);
+                let tmp = dir.join_normalized(format!(
+                    ".{}.{}.tmp",
+                    std::process::id(),
+                    NEXT_TMP.fetch_add(1, Ordering::Relaxed)
+                ))?;
+                fs_util::copy(src, &tmp)?;
+                fs_util::rename(&tmp, &path)?;
+            }
+        };
+        if let Err(e) = res {
+            tracing::warn!("Error writing to the download cache: {:#}", e);
+        }
+    }
+}
+
+fn matches_checksum(path: &AbsNormPath, checksum: &Checksum) -> anyhow::Result<bool> {
+    let mut sha1 = checksum.sha1().map(|expected| (expected, Sha1::new()));
+    let mut sha256 = checksum.sha256().map(|expected| (expected, Sha256::new()));
+
+    let mut file = fs_util::open_file(path)?;
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        if let Some((_, hasher)) = &mut sha1 {
+            hasher.update(&buf[..n]);
+        }
+        if let Some((_, hasher)) = &mut sha256 {
+            hasher.update(&buf[..n]);
+        }
+    }
+
+    Ok(sha1.map_or(true, |(expected, hasher)| {
+        hex::encode(hasher.finalize()) == expected
+    }) && sha256.map_or(true, |(expected, hasher)| {
+        hex::encode(hasher.finalize()) == expected
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn test_store_and_fetch() -> anyhow::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let root = AbsNormPathBuf::new(tempdir.path().to_owned())?;
+        let cache = DownloadCache {
+            dir: root.join_normalized("cache")?,
+        };
+        let checksum = Checksum::Both {
+            sha1: Arc::from("8843d7f92416211de9ebb963ff4ce28125932878"),
+            sha256: Arc::from("c3ab8ff13720e8ad9047dd39466b3c8974e592c2fa383d4a3960714caef0c4f2"),
+        };
+
+        let src = root.join_normalized("src")?;
+        let dest = root.join_normalized("dest")?;
+        fs_util::write(&src, "foobar")?;
+        assert!(!cache.fetch(&checksum, &dest));
+        assert_eq!(None, cache.cached_len(&checksum));
+
+        cache.store(&checksum, &src);
+        assert_eq!(Some(6), cache.cached_len(&checksum));
+        assert!(cache.fetch(&checksum, &dest));
+        assert_eq!("foobar", fs_util::read_to_string(&dest)?);
+
+        // Found from either of the checksums.
+        let dest2 = root.join_normalized("dest2")?;
+        assert!(cache.fetch(&Checksum::Sha1(checksum.sha1().unwrap().into()), &dest2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_corrupted_entry_is_evicted() -> anyhow::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let root = AbsNormPathBuf::new(tempdir.path().to_owned())?;
+        let cache = DownloadCache {
+            dir: root.join_normalized("cache")?,
+        };
+        let checksum = Checksum::Sha256(Arc::from(
+            "c3ab8ff13720e8ad9047dd39466b3c8974e592c2fa383d4a3960714caef0c4f2",
+        ));
+
+        let src = root.join_normalized("src")?;
+        let dest = root.join_normalized("dest")?;
+        fs_util::write(&src, "foobar")?;
+        cache.store(&checksum, &src);
+
+        let entry = &cache.paths(&checksum)?[0];
+        fs_util::write(entry, "foobaz")?;
+        assert!(!cache.fetch(&checksum, &dest));
+        assert!(!fs_util::try_exists(entry)?);
+        assert!(!fs_util::try_exists(&dest)?);
+        assert_eq!(None, cache.cached_len(&checksum));
+        Ok(())
+    }
+}
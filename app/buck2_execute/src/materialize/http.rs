@@ -18,6 +18,7 @@ use buck2_common::cas_digest::DigestAlgorithmKind;
 use buck2_common::cas_digest::SHA1_SIZE;
 use buck2_common::cas_digest::SHA256_SIZE;
 use buck2_common::file_ops::FileDigest;
+use buck2_common::file_ops::FileDigestConfig;
 use buck2_common::file_ops::TrackedFileDigest;
 use buck2_core::fs::fs_util;
 use buck2_core::fs::project::ProjectRoot;
@@ -29,6 +30,7 @@ use buck2_http::HttpClient;
 use bytes::Bytes;
 use digest::DynDigest;
 use dupe::Dupe;
+use futures::future::Future;
 use futures::stream::Stream;
 use futures::StreamExt;
 use hyper::Response;
@@ -38,6 +40,7 @@ use sha2::Sha256;
 use smallvec::SmallVec;
 
 use crate::digest_config::DigestConfig;
+use crate::materialize::download_cache::DownloadCache;
 
 #[derive(Debug, Clone, Dupe, Allocative)]
 pub enum Checksum {
@@ -50,6 +53,8 @@ pub enum Checksum {
 enum DownloadFileError {
     #[error("Must pass in at least one checksum (e.g. `sha1 = ...`)")]
     MissingChecksum,
+    #[error("No URL to download from (internal error)")]
+    NoUrls,
     #[error("Invalid digest for `{digest_type}` argument, expected length of {expected_len} but got {}, digest `{digest}`", digest.len())]
     InvalidDigestLength {
         digest: String,
@@ -220,6 +225,23 @@ pub async fn http_download(
         fs_util::create_dir_all(fs.resolve(dir))?;
     }
 
+    let cache = DownloadCache::new(client);
+    if let Some(cache) = &cache {
+        if cache.fetch(checksum, &abs_path) {
+            if executable {
+                fs.set_executable(path)?;
+            }
+            let digest = FileDigest::from_file_disk(
+                &abs_path,
+                FileDigestConfig::build(digest_config.cas_digest_config()),
+            )?;
+            return Ok(TrackedFileDigest::new(
+                digest,
+                digest_config.cas_digest_config(),
+            ));
+        }
+    }
+
     Ok(http_retry(
         || async {
             let file = fs_util::create_file(&abs_path).map_err(HttpDownloadError::IoError)?;
@@ -242,6 +264,11 @@ pub async fn http_download(
             )
             .await?;
 
+            // Before making it executable, so that cache entries are never executable.
+            if let Some(cache) = &cache {
+                cache.store(checksum, &abs_path);
+            }
+
             if executable {
                 fs.set_executable(path)
                     .map_err(HttpDownloadError::IoError)?;
@@ -257,6 +284,40 @@ pub async fn http_download(
     .await?)
 }
 
+/// Run `f` on each of the mirrors `urls` in order until one succeeds, and return its result with
+/// the URL that succeeded. `f` is expected to retry transient errors itself (e.g. with
+/// `http_retry`), so that a mirror is only skipped once it's known not to work.
+pub async fn try_mirrors<'a, T, F, Fut>(
+    urls: &'a [Arc<str>],
+    f: F,
+) -> anyhow::Result<(T, &'a Arc<str>)>
+where
+    F: Fn(&'a Arc<str>) -> Fut,
+    Fut: Future<Output = anyhow::Result<T>>,
+{
+    let mut errors = Vec::new();
+    for url in urls {
+        match f(url).await {
+            Ok(v) => return Ok((v, url)),
+            Err(e) => {
+                if urls.len() == 1 {
+                    return Err(e);
+                }
+                tracing::warn!("Download from mirror `{}` failed: {:#}", url, e);
+                errors.push(e);
+            }
+        }
+    }
+    match errors.into_iter().next() {
+        Some(e) => Err(e.context(format!(
+            "Download failed from all {} mirrors: {}",
+            urls.len(),
+            urls.join(", ")
+        ))),
+        None => Err(DownloadFileError::NoUrls.into()),
+    }
+}
+
 /// Copy a stream into a writer while producing its digest and checksumming it.
 async fn copy_and_hash(
     url: &str,
@@ -433,4 +494,32 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_try_mirrors() -> anyhow::Result<()> {
+        let urls: Vec<Arc<str>> = vec![Arc::from("https://a"), Arc::from("https://b")];
+
+        let (v, url) = try_mirrors(&urls, |url| async move {
+            if &**url == "https://a" {
+                Err(anyhow::anyhow!("down"))
+            } else {
+                Ok(1)
+            }
+        })
+        .await?;
+        assert_eq!((1, "https://b"), (v, &**url));
+
+        let err = try_mirrors(&urls, |_| async {
+            anyhow::Result::<()>::Err(anyhow::anyhow!("down"))
+        })
+        .await
+        .unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("all 2 mirrors: https://a, https://b"),
+            "{:#}",
+            err
+        );
+
+        Ok(())
+    }
 }
@@ -672,6 +672,11 @@ pub trait DeferredMaterializerExtensions: Send + Sync {
     ) -> anyhow::Result<buck2_cli_proto::CleanStaleResponse>;
 
     async fn test_iter(&self, count: usize) -> anyhow::Result<String>;
+
+    /// Describe what the materializer knows about the artifact at `path`, or containing it:
+    /// its state, digest, and the recent commands that touched it.
+    async fn path_info(&self, path: ProjectRelativePathBuf) -> anyhow::Result<String>;
+
     async fn flush_all_access_times(&self) -> anyhow::Result<String>;
 
     /// Create a new DeferredMaterializerSubscription.
@@ -7,6 +7,7 @@
  * of this source tree.
  */
 
+pub mod download_cache;
 #[cfg(fbcode_build)]
 pub mod eden_api;
 pub mod http;
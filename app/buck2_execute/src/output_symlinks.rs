@@ -0,0 +1,273 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Validation of the symlinks in output directories. Relative symlinks are stored as such in
+//! the artifact (and the CAS), so they are only meaningful if they point inside the artifact:
+//! symlink farms emitted by packaging tools are fine, links to the rest of `buck-out` aren't.
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::fmt::Display;
+
+use buck2_core::directory::Directory;
+use buck2_core::directory::DirectoryEntry;
+use buck2_core::directory::DirectoryIterator;
+use buck2_core::soft_error;
+
+use crate::directory::ActionDirectoryBuilder;
+use crate::directory::ActionDirectoryMember;
+
+/// Like `MAXSYMLINKS` on Linux: a longer chain of symlinks is reported as a cycle.
+const MAX_SYMLINK_HOPS: usize = 40;
+
+#[derive(Debug, buck2_error::Error)]
+enum OutputSymlinkError {
+    #[error(
+        "Symlink `{link}` in output directory `{output}` points to `{target}`, which is outside of the output directory"
+    )]
+    #[buck2(user)]
+    Escapes {
+        output: String,
+        link: String,
+        target: String,
+    },
+    #[error("Symlink `{link}` in output directory `{output}` is part of a symlink cycle")]
+    #[buck2(user)]
+    Cycle { output: String, link: String },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum SymlinkProblem {
+    Escapes,
+    Cycle,
+}
+
+enum Node {
+    Dir,
+    File,
+    Symlink(String),
+    ExternalSymlink,
+}
+
+/// The entries of an output directory, by path relative to it.
+struct OutputTree {
+    nodes: HashMap<String, Node>,
+}
+
+impl OutputTree {
+    fn new(dir: &ActionDirectoryBuilder) -> Self {
+        let mut nodes = HashMap::new();
+        let mut walk = dir.unordered_walk();
+        while let Some((path, entry)) = walk.next() {
+            let node = match entry {
+                DirectoryEntry::Dir(_) => Node::Dir,
+                DirectoryEntry::Leaf(ActionDirectoryMember::File(_)) => Node::File,
+                DirectoryEntry::Leaf(ActionDirectoryMember::Symlink(s)) => {
+                    Node::Symlink(s.target().as_str().to_owned())
+                }
+                DirectoryEntry::Leaf(ActionDirectoryMember::ExternalSymlink(_)) => {
+                    Node::ExternalSymlink
+                }
+            };
+            nodes.insert(path.get().as_str().to_owned(), node);
+        }
+        Self { nodes }
+    }
+
+    /// The symlinks, sorted by path.
+    fn symlinks(&self) -> Vec<(&str, &str)> {
+        let mut symlinks: Vec<_> = self
+            .nodes
+            .iter()
+            .filter_map(|(path, node)| match node {
+                Node::Symlink(target) => Some((path.as_str(), target.as_str())),
+                _ => None,
+            })
+            .collect();
+        symlinks.sort();
+        symlinks
+    }
+
+    /// Follow the symlink at `link` within the tree. Dangling symlinks are fine, but the path
+    /// they point to must still be inside the tree.
+    fn check(&self, link: &str, target: &str) -> Result<(), SymlinkProblem> {
+        let mut current: Vec<&str> = link.split('/').collect();
+        current.pop();
+        let mut pending: VecDeque<&str> = target.split('/').collect();
+        let mut hops = 1;
+        // Whether the path so far exists in the tree. Past a file or a missing entry, the rest
+        // of the path can only be checked lexically.
+        let mut exists = true;
+
+        while let Some(component) = pending.pop_front() {
+            match component {
+                "" | "." => {}
+                ".." => {
+                    if current.pop().is_none() {
+                        return Err(SymlinkProblem::Escapes);
+                    }
+                }
+                name => {
+                    current.push(name);
+                    if !exists {
+                        continue;
+                    }
+                    match self.nodes.get(&current.join("/")) {
+                        Some(Node::Dir) => {}
+                        Some(Node::Symlink(target)) => {
+                            hops += 1;
+                            if hops > MAX_SYMLINK_HOPS {
+                                return Err(SymlinkProblem::Cycle);
+                            }
+                            current.pop();
+                            for component in target.split('/').rev() {
+                                pending.push_front(component);
+                            }
+                        }
+                        // Points outside of buck-out, which is allowed.
+                        Some(Node::ExternalSymlink) => return Ok(()),
+                        Some(Node::File) | None => exists = false,
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Validate the relative symlinks of the output directory `dir`: they must not escape it or be
+/// part of a cycle. Reported as soft errors for now, for the first symlink that doesn't.
+pub fn check_output_symlinks(
+    output: &dyn Display,
+    dir: &ActionDirectoryBuilder,
+) -> anyhow::Result<()> {
+    let tree = OutputTree::new(dir);
+    for (link, target) in tree.symlinks() {
+        match tree.check(link, target) {
+            Ok(()) => {}
+            Err(SymlinkProblem::Escapes) => {
+                soft_error!(
+                    "output_symlink_escapes",
+                    OutputSymlinkError::Escapes {
+                        output: output.to_string(),
+                        link: link.to_owned(),
+                        target: target.to_owned(),
+                    }
+                    .into()
+                )?;
+                return Ok(());
+            }
+            Err(SymlinkProblem::Cycle) => {
+                soft_error!(
+                    "output_symlink_cycle",
+                    OutputSymlinkError::Cycle {
+                        output: output.to_string(),
+                        link: link.to_owned(),
+                    }
+                    .into()
+                )?;
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use buck2_common::file_ops::FileMetadata;
+    use buck2_core::fs::project_rel_path::ProjectRelativePath;
+
+    use super::*;
+    use crate::digest_config::DigestConfig;
+    use crate::directory::insert_file;
+    use crate::directory::insert_symlink;
+    use crate::directory::Symlink;
+
+    fn tree(files: &[&str], symlinks: &[(&str, &str)]) -> anyhow::Result<OutputTree> {
+        let digest_config = DigestConfig::testing_default();
+        let mut builder = ActionDirectoryBuilder::empty();
+        for file in files {
+            insert_file(
+                &mut builder,
+                ProjectRelativePath::new(file)?,
+                FileMetadata::empty(digest_config.cas_digest_config()),
+            )?;
+        }
+        for (link, target) in symlinks {
+            insert_symlink(
+                &mut builder,
+                ProjectRelativePath::new(link)?,
+                Arc::new(Symlink::new((*target).into())),
+            )?;
+        }
+        Ok(OutputTree::new(&builder))
+    }
+
+    fn check(tree: &OutputTree, link: &str) -> Result<(), SymlinkProblem> {
+        let target = tree
+            .symlinks()
+            .into_iter()
+            .find(|(l, _)| *l == link)
+            .unwrap()
+            .1;
+        tree.check(link, target)
+    }
+
+    #[test]
+    fn test_symlink_farm() -> anyhow::Result<()> {
+        let tree = tree(
+            &["lib/libfoo.so.1", "bin/tool"],
+            &[
+                ("lib/libfoo.so", "libfoo.so.1"),
+                ("lib64", "lib"),
+                ("usr/lib/libfoo.so", "../../lib64/libfoo.so"),
+                ("bin/dangling", "../missing/file"),
+            ],
+        )?;
+        for (link, _) in tree.symlinks() {
+            assert_eq!(Ok(()), check(&tree, link));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_symlink_escapes() -> anyhow::Result<()> {
+        let tree = tree(
+            &["lib/libfoo.so"],
+            &[
+                ("up", ".."),
+                ("lib/other", "../../other-artifact/lib"),
+                ("lib64", "lib"),
+                ("through_link", "lib64/../../x"),
+                ("missing_then_up", "missing/../../x"),
+            ],
+        )?;
+        assert_eq!(Err(SymlinkProblem::Escapes), check(&tree, "up"));
+        assert_eq!(Err(SymlinkProblem::Escapes), check(&tree, "lib/other"));
+        // `lib64/..` is the root of the output, as `lib64` points to a directory of it.
+        assert_eq!(Err(SymlinkProblem::Escapes), check(&tree, "through_link"));
+        assert_eq!(
+            Err(SymlinkProblem::Escapes),
+            check(&tree, "missing_then_up")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_symlink_cycle() -> anyhow::Result<()> {
+        let tree = tree(&[], &[("a", "b"), ("b", "c/../a"), ("c/d", "x")])?;
+        assert_eq!(Err(SymlinkProblem::Cycle), check(&tree, "a"));
+        assert_eq!(Err(SymlinkProblem::Cycle), check(&tree, "b"));
+        assert_eq!(Ok(()), check(&tree, "c/d"));
+        Ok(())
+    }
+}
@@ -12,6 +12,7 @@ use std::time::Duration;
 
 use allocative::Allocative;
 use anyhow::Context;
+use buck2_common::cas_digest::DigestAlgorithmKind;
 use buck2_core::buck2_env;
 use buck2_core::execution_types::executor_config::RemoteExecutorDependency;
 use buck2_core::execution_types::executor_config::RemoteExecutorUseCase;
@@ -76,6 +77,8 @@ use crate::re::metadata::RemoteExecutionMetadataExt;
 use crate::re::stats::OpStats;
 use crate::re::stats::RemoteExecutionClientOpStats;
 use crate::re::stats::RemoteExecutionClientStats;
+use crate::re::throttle::BandwidthThrottle;
+use crate::re::uploader::PresentTrees;
 use crate::re::uploader::UploadStats;
 use crate::re::uploader::Uploader;
 
@@ -111,6 +114,7 @@ impl RemoteExecutionClient {
         logs_dir_path: Option<&AbsNormPath>,
         buck_out_path: &AbsNormPath,
         is_paranoid_mode: bool,
+        digest_config: DigestConfig,
     ) -> anyhow::Result<Self> {
         let client = RemoteExecutionClientImpl::new(
             fb,
@@ -119,6 +123,7 @@ impl RemoteExecutionClient {
             logs_dir_path,
             buck_out_path,
             is_paranoid_mode,
+            digest_config,
         )
         .await?;
 
@@ -144,6 +149,7 @@ impl RemoteExecutionClient {
         logs_dir_path: Option<&AbsNormPath>,
         buck_out_path: &AbsNormPath,
         is_paranoid_mode: bool,
+        digest_config: DigestConfig,
     ) -> anyhow::Result<Self> {
         // Loop happens times-1 times at most
         for i in 1..times {
@@ -154,6 +160,7 @@ impl RemoteExecutionClient {
                 logs_dir_path,
                 buck_out_path,
                 is_paranoid_mode,
+                digest_config,
             )
             .await
             {
@@ -175,6 +182,7 @@ impl RemoteExecutionClient {
             logs_dir_path,
             buck_out_path,
             is_paranoid_mode,
+            digest_config,
         )
         .await
     }
@@ -404,6 +412,11 @@ struct RemoteExecutionClientImpl {
     /// How many files to kick off downloading concurrently for one request. This should be smaller
     /// than the files semaphore to ensure we can actually *acquire* that semaphore.
     download_chunk_size: usize,
+    #[allocative(skip)]
+    upload_throttle: Option<BandwidthThrottle>,
+    #[allocative(skip)]
+    download_throttle: Option<BandwidthThrottle>,
+    present_trees: PresentTrees,
 }
 
 fn re_platform(x: &RE::Platform) -> remote_execution::TPlatform {
@@ -417,6 +430,58 @@ fn re_platform(x: &RE::Platform) -> remote_execution::TPlatform {
     }
 }
 
+#[derive(Debug, buck2_error::Error)]
+#[buck2(user)]
+enum DigestFunctionError {
+    #[error(
+        "The RE backend doesn't support `{algorithm}`, which Buck2 uses for {used_for}. It supports: {}. Set `[buck2] digest_algorithms` (and `source_digest_algorithm`, if set) to one of those.",
+        .supported.join(", ")
+    )]
+    Unsupported {
+        algorithm: DigestAlgorithmKind,
+        used_for: &'static str,
+        supported: Vec<&'static str>,
+    },
+}
+
+/// Check that the backend accepts the digests Buck2 computes: those of action digests, outputs,
+/// and source files. `supported` is empty if the backend didn't say.
+#[cfg_attr(fbcode_build, allow(dead_code))]
+fn check_digest_functions(
+    supported: &[&'static str],
+    digest_config: DigestConfig,
+) -> anyhow::Result<()> {
+    if supported.is_empty() {
+        return Ok(());
+    }
+
+    let cas_digest_config = digest_config.cas_digest_config();
+    for (algorithm, used_for) in [
+        (
+            cas_digest_config.preferred_algorithm(),
+            "actions and outputs",
+        ),
+        (
+            cas_digest_config
+                .source_files_config()
+                .preferred_algorithm(),
+            "source files",
+        ),
+    ] {
+        let algorithm = algorithm.kind();
+        if !supported.iter().any(|s| *s == algorithm.to_string()) {
+            return Err(DigestFunctionError::Unsupported {
+                algorithm,
+                used_for,
+                supported: supported.to_vec(),
+            }
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
 impl RemoteExecutionClientImpl {
     async fn new(
         fb: FacebookInit,
@@ -425,12 +490,17 @@ impl RemoteExecutionClientImpl {
         maybe_logs_dir_path: Option<&AbsNormPath>,
         buck_out_path: &AbsNormPath,
         is_paranoid_mode: bool,
+        digest_config: DigestConfig,
     ) -> anyhow::Result<Self> {
         tracing::info!("Creating a new RE client");
 
         let res: anyhow::Result<Self> = try {
-            let download_concurrency =
-                buck2_env!("BUCK2_RE_DOWNLOAD_CONCURRENCY", type=usize, default=256)?;
+            let transfer_limits = static_metadata.transfer_limits();
+
+            let download_concurrency = match transfer_limits.max_concurrent_downloads {
+                Some(download_concurrency) => download_concurrency,
+                None => buck2_env!("BUCK2_RE_DOWNLOAD_CONCURRENCY", type=usize, default=256)?,
+            };
 
             // Split things up into smaller chunks.
             let download_chunk_size = std::cmp::max(download_concurrency / 8, 1);
@@ -655,6 +725,8 @@ impl RemoteExecutionClientImpl {
                 re_client_config.disable_fallocate = static_metadata.disable_fallocate;
                 // TODO(ndmitchell): For now, we just drop RE log messages, but ideally we'd put them in our log stream.
                 let logger = slog::Logger::root(slog::Discard, slog::o!());
+                // The digest functions the backend supports are not exposed by this client.
+                let _unused = digest_config;
                 // TODO T179215751: If RE client fails we don't get the RE session ID and we can't find the RE logs.
                 // Better to generate the RE session ID ourselves and pass it to the RE client.
                 REClientBuilder::new(fb)
@@ -668,7 +740,9 @@ impl RemoteExecutionClientImpl {
             let client = {
                 let _unused = (fb, maybe_logs_dir_path, buck_out_path, is_paranoid_mode);
 
-                REClientBuilder::build_and_connect(&static_metadata.0).await?
+                let client = REClientBuilder::build_and_connect(&static_metadata.0).await?;
+                check_digest_functions(client.supported_digest_functions(), digest_config)?;
+                client
             };
 
             Self {
@@ -677,6 +751,17 @@ impl RemoteExecutionClientImpl {
                 cas_semaphore: Arc::new(Semaphore::new(static_metadata.cas_semaphore_size())),
                 download_files_semapore: Arc::new(Semaphore::new(download_concurrency)),
                 download_chunk_size,
+                upload_throttle: BandwidthThrottle::new(
+                    transfer_limits.max_upload_bytes_per_second,
+                ),
+                download_throttle: BandwidthThrottle::new(
+                    transfer_limits.max_download_bytes_per_second,
+                ),
+                present_trees: PresentTrees::new(buck2_env!(
+                    "BUCK2_RE_PRESENT_TREES_CAPACITY",
+                    type=usize,
+                    default=100_000
+                )?),
             }
         };
 
@@ -733,9 +818,10 @@ impl RemoteExecutionClientImpl {
     ) -> anyhow::Result<UploadStats> {
         // Actually upload to CAS
         let _cas = self.cas_semaphore.acquire().await;
-        Uploader::upload(
+        let stats = Uploader::upload(
             fs,
             self.client().get_cas_client(),
+            &self.present_trees,
             materializer,
             dir_path,
             input_dir,
@@ -743,7 +829,11 @@ impl RemoteExecutionClientImpl {
             use_case,
             digest_config,
         )
-        .await
+        .await?;
+        if let Some(throttle) = &self.upload_throttle {
+            throttle.throttle(stats.bytes_uploaded).await;
+        }
+        Ok(stats)
     }
 
     async fn upload_files_and_directories(
@@ -810,6 +900,14 @@ impl RemoteExecutionClientImpl {
             manager: &mut CommandExecutionManager,
             re_max_queue_time: Option<Duration>,
         ) -> anyhow::Result<ResponseOrStateChange> {
+            let has_queue_estimate = matches!(
+                &report_stage,
+                re_stage::Stage::Queue(ReQueue {
+                    estimated_queue_time_ms: Some(_),
+                    ..
+                })
+            );
+
             executor_stage_async(
                 buck2_data::ReStage {
                     stage: Some(report_stage),
@@ -843,7 +941,7 @@ impl RemoteExecutionClientImpl {
 
                         // TODO: This should be one block up?
                         if let Some(re_max_queue_time) = re_max_queue_time {
-                            if let Some(info) = event.metadata.task_info {
+                            if let Some(info) = &event.metadata.task_info {
                                 let est = u64::try_from(info.estimated_queue_time_ms)
                                     .context("estimated_queue_time_ms from RE is negative")?;
                                 let queue_time = Duration::from_millis(est);
@@ -853,6 +951,15 @@ impl RemoteExecutionClientImpl {
                                 }
                             }
                         }
+
+                        // Report the queue stage again once RE tells us how long it expects the
+                        // action to be queued for, so that the console can show it.
+                        if previous_stage == Stage::QUEUED
+                            && !has_queue_estimate
+                            && event.metadata.task_info.is_some()
+                        {
+                            return Ok(ResponseOrStateChange::Present(event));
+                        }
                     }
                 },
             )
@@ -864,9 +971,13 @@ impl RemoteExecutionClientImpl {
             action_digest: String,
             platform: &remote_execution::Platform,
             action_key: &Option<String>,
+            estimated_queue_time_ms: Option<u64>,
         ) -> re_stage::Stage {
             match stage {
-                Stage::QUEUED => re_stage::Stage::Queue(ReQueue { action_digest }),
+                Stage::QUEUED => re_stage::Stage::Queue(ReQueue {
+                    action_digest,
+                    estimated_queue_time_ms,
+                }),
                 Stage::MATERIALIZING_INPUT => {
                     re_stage::Stage::WorkerDownload(ReWorkerDownload { action_digest })
                 }
@@ -911,6 +1022,7 @@ impl RemoteExecutionClientImpl {
         // this doesn't give us an ExecuteResponse then this is case #1 again so we also fail.
         let action_digest_str = action_digest.to_string();
         let mut exe_stage = Stage::QUEUED;
+        let mut estimated_queue_time_ms = None;
 
         loop {
             let progress_response = wait_for_response_or_stage_change(
@@ -921,6 +1033,7 @@ impl RemoteExecutionClientImpl {
                     action_digest_str.clone(),
                     platform,
                     &action_key,
+                    estimated_queue_time_ms,
                 ),
                 manager,
                 re_max_queue_time,
@@ -941,6 +1054,12 @@ impl RemoteExecutionClientImpl {
 
             // Change the stage
             exe_stage = progress_response.stage;
+            // A negative estimate is clamped rather than dropped, so that the stage is kept as
+            // having an estimate instead of being reported again on every progress event.
+            estimated_queue_time_ms = progress_response
+                .metadata
+                .task_info
+                .map(|info| info.estimated_queue_time_ms.max(0) as u64);
         }
     }
 
@@ -1103,6 +1222,14 @@ impl RemoteExecutionClientImpl {
                 .await
                 .context("Failed to acquire download_files_semapore")?;
 
+            if let Some(throttle) = &self.download_throttle {
+                let bytes = chunk
+                    .iter()
+                    .map(|f| f.named_digest.digest.size_in_bytes.max(0) as u64)
+                    .sum();
+                throttle.throttle(bytes).await;
+            }
+
             self.client()
                 .get_cas_client()
                 .download(
@@ -1207,8 +1334,31 @@ fn chunks<T>(v: Vec<T>, chunk_size: usize) -> impl Iterator<Item = Vec<T>> {
 
 #[cfg(test)]
 mod tests {
+    use buck2_common::cas_digest::DigestAlgorithm;
+
     use super::*;
 
+    #[test]
+    fn test_check_digest_functions() {
+        let sha256 = DigestConfig::leak_new(vec![DigestAlgorithm::Sha256], None).unwrap();
+        assert!(check_digest_functions(&[], sha256).is_ok());
+        assert!(check_digest_functions(&["SHA1", "SHA256"], sha256).is_ok());
+        assert!(check_digest_functions(&["SHA1"], sha256).is_err());
+
+        let blake3_sources = DigestConfig::leak_new(
+            vec![DigestAlgorithm::Sha1, DigestAlgorithm::Blake3],
+            Some(DigestAlgorithm::Blake3),
+        )
+        .unwrap();
+        assert!(check_digest_functions(&["SHA1", "BLAKE3"], blake3_sources).is_ok());
+        let err = check_digest_functions(&["SHA1", "SHA256"], blake3_sources).unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("`BLAKE3`, which Buck2 uses for source files"),
+            "{:#}",
+            err
+        );
+    }
+
     #[test]
     fn test_chunks_skips() {
         assert_eq!(chunks(Vec::<usize>::new(), 1).next(), None);
@@ -92,6 +92,7 @@ struct RemoteExecutionConfig {
     buck_out_path: AbsNormPathBuf,
     /// Whether Buck is running in paranoid mode.
     is_paranoid_mode: bool,
+    digest_config: DigestConfig,
 }
 
 impl RemoteExecutionConfig {
@@ -104,6 +105,7 @@ impl RemoteExecutionConfig {
             self.logs_dir_path.as_deref(),
             &self.buck_out_path,
             self.is_paranoid_mode,
+            self.digest_config,
         )
         .await
     }
@@ -206,6 +208,7 @@ impl ReConnectionManager {
         logs_dir_path: Option<AbsNormPathBuf>,
         buck_out_path: AbsNormPathBuf,
         is_paranoid_mode: bool,
+        digest_config: DigestConfig,
     ) -> Self {
         Self {
             data: RwLock::new(Weak::new()),
@@ -217,6 +220,7 @@ impl ReConnectionManager {
                 logs_dir_path,
                 buck_out_path,
                 is_paranoid_mode,
+                digest_config,
             },
         }
     }
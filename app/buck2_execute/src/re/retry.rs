@@ -0,0 +1,138 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Retries of RE executions which fail because of the RE infrastructure (e.g. the scheduler
+//! being unavailable), as opposed to the action itself failing.
+
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use buck2_events::dispatch::get_dispatcher_opt;
+use dupe::Dupe;
+use remote_execution::REClientError;
+use remote_execution::TCode;
+
+/// The longest we wait between two attempts, however many attempts were made.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Whether an RE error code is transient, so that executing the same action again may succeed.
+pub fn is_retriable_re_code(code: &TCode) -> bool {
+    *code == TCode::UNAVAILABLE
+        || *code == TCode::RESOURCE_EXHAUSTED
+        || *code == TCode::ABORTED
+        || *code == TCode::INTERNAL
+}
+
+/// Whether an error returned by the RE client is transient. Errors which don't come from RE
+/// (e.g. failing to read an input) are not.
+pub fn is_retriable_re_error(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<REClientError>()
+        .map_or(false, |e| is_retriable_re_code(&e.code))
+}
+
+/// How to retry RE executions failing with transient errors, shared by all the actions of a
+/// command.
+#[derive(Clone, Dupe)]
+pub struct ReRetryPolicy {
+    /// Retries of a single action, 0 to not retry.
+    max_retries: u32,
+    /// Delay before the first retry, doubled for every following one.
+    initial_backoff: Duration,
+    /// Retries left for the whole command, so that an RE outage doesn't retry every action.
+    budget: Arc<AtomicU64>,
+}
+
+impl Default for ReRetryPolicy {
+    fn default() -> Self {
+        Self::new(0, Duration::from_secs(1), 0)
+    }
+}
+
+impl ReRetryPolicy {
+    pub fn new(max_retries: u32, initial_backoff: Duration, budget: u64) -> Self {
+        Self {
+            max_retries,
+            initial_backoff,
+            budget: Arc::new(AtomicU64::new(budget)),
+        }
+    }
+
+    /// How long to wait before retrying an action which already had `retries` retries, or `None`
+    /// if it shouldn't be retried. Consumes the budget of the command, and counts the retry on the
+    /// dispatcher of the command.
+    pub fn next_retry(&self, retries: u32) -> Option<Duration> {
+        if retries >= self.max_retries {
+            return None;
+        }
+        self.budget
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |b| b.checked_sub(1))
+            .ok()?;
+        if let Some(dispatcher) = get_dispatcher_opt() {
+            dispatcher.counters().record_re_retry();
+        }
+        Some(
+            self.initial_backoff
+                .saturating_mul(1 << retries.min(16))
+                .min(MAX_BACKOFF),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_events::dispatch::with_dispatcher;
+    use buck2_events::dispatch::EventDispatcher;
+
+    use super::*;
+
+    #[test]
+    fn test_next_retry() {
+        let policy = ReRetryPolicy::new(3, Duration::from_secs(1), 4);
+        assert_eq!(Some(Duration::from_secs(1)), policy.next_retry(0));
+        assert_eq!(Some(Duration::from_secs(2)), policy.next_retry(1));
+        assert_eq!(Some(Duration::from_secs(4)), policy.next_retry(2));
+        assert_eq!(None, policy.next_retry(3));
+        // The budget is shared by all actions.
+        assert_eq!(Some(Duration::from_secs(1)), policy.dupe().next_retry(0));
+        assert_eq!(None, policy.next_retry(0));
+
+        assert_eq!(None, ReRetryPolicy::default().next_retry(0));
+    }
+
+    #[test]
+    fn test_retries_counted_per_command() {
+        let policy = ReRetryPolicy::new(3, Duration::from_secs(1), 4);
+        let first = EventDispatcher::null();
+        let second = EventDispatcher::null();
+        with_dispatcher(first.dupe(), || {
+            policy.next_retry(0);
+            policy.next_retry(1);
+        });
+        with_dispatcher(second.dupe(), || policy.next_retry(0));
+        assert_eq!(2, first.counters().re_retries());
+        assert_eq!(1, second.counters().re_retries());
+    }
+
+    #[test]
+    fn test_is_retriable() {
+        assert!(is_retriable_re_code(&TCode::UNAVAILABLE));
+        assert!(!is_retriable_re_code(&TCode::INVALID_ARGUMENT));
+        assert!(is_retriable_re_error(
+            &anyhow::Error::new(REClientError {
+                message: "unavailable".to_owned(),
+                code: TCode::UNAVAILABLE,
+            })
+            .context("RE: execution")
+        ));
+        assert!(!is_retriable_re_error(&anyhow::anyhow!("Missing input")));
+    }
+}
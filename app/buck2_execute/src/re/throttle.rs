@@ -0,0 +1,68 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+/// Limits the average bandwidth of CAS transfers. Transfers reserve their size, and wait until
+/// the transfers which reserved before them would have completed at the configured rate.
+///
+/// The sizes of uploads are only known once they are done (as only missing blobs are uploaded),
+/// so they are accounted for afterwards, which delays the following uploads instead.
+pub(crate) struct BandwidthThrottle {
+    bytes_per_second: u64,
+    /// When the transfers reserved so far are done.
+    next_free: Mutex<Instant>,
+}
+
+impl BandwidthThrottle {
+    pub(crate) fn new(bytes_per_second: Option<u64>) -> Option<Self> {
+        Some(Self {
+            bytes_per_second: bytes_per_second.filter(|b| *b > 0)?,
+            next_free: Mutex::new(Instant::now()),
+        })
+    }
+
+    /// Reserve `bytes`, returning when the transfer can start.
+    fn reserve(&self, bytes: u64, now: Instant) -> Instant {
+        let mut next_free = self.next_free.lock().unwrap();
+        let start = (*next_free).max(now);
+        *next_free = start + Duration::from_secs_f64(bytes as f64 / self.bytes_per_second as f64);
+        start
+    }
+
+    pub(crate) async fn throttle(&self, bytes: u64) {
+        let start = self.reserve(bytes, Instant::now());
+        tokio::time::sleep_until(start.into()).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserve() {
+        assert!(BandwidthThrottle::new(None).is_none());
+        assert!(BandwidthThrottle::new(Some(0)).is_none());
+
+        let throttle = BandwidthThrottle::new(Some(100)).unwrap();
+        let now = Instant::now();
+        assert_eq!(now, throttle.reserve(200, now));
+        assert_eq!(now + Duration::from_secs(2), throttle.reserve(50, now));
+        assert_eq!(
+            now + Duration::from_millis(2500),
+            throttle.reserve(0, now + Duration::from_secs(1))
+        );
+        // Idle time isn't saved up for later transfers.
+        let later = now + Duration::from_secs(10);
+        assert_eq!(later, throttle.reserve(100, later));
+    }
+}
@@ -10,7 +10,9 @@
 use std::collections::HashSet;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::sync::Mutex;
 
+use allocative::Allocative;
 use anyhow::Context;
 use buck2_common::cas_digest::TrackedCasDigest;
 use buck2_common::file_ops::FileDigest;
@@ -24,10 +26,12 @@ use buck2_core::execution_types::executor_config::RemoteExecutorUseCase;
 use buck2_core::fs::project::ProjectRoot;
 use buck2_core::fs::project_rel_path::ProjectRelativePath;
 use buck2_core::soft_error;
+use chrono::DateTime;
 use chrono::Duration;
 use chrono::Utc;
 use futures::FutureExt;
 use gazebo::prelude::*;
+use linked_hash_map::LinkedHashMap;
 use remote_execution::GetDigestsTtlRequest;
 use remote_execution::InlinedBlobWithDigest;
 use remote_execution::NamedDigest;
@@ -58,9 +62,123 @@ pub struct UploadStats {
 
 pub struct Uploader {}
 
+/// Until when the whole tree of an input directory is known to be in the CAS, by use case and
+/// directory digest.
+///
+/// Input directories are interned, so a large source directory (e.g. a sysroot) used by
+/// thousands of actions has the same digest in all of them. Once all of its contents were found
+/// in the CAS, the next actions skip the directory instead of walking and querying all of it.
+///
+/// This is owned by an RE client, so entries are specific to the RE instance it talks to. Only
+/// the expiry of a tree is recorded, not its contents, so this is not a representation of the
+/// merkle tree: it only saves the queries. An entry expires with the first of the contents of its
+/// tree, and the least recently used entries are evicted past `capacity`.
+#[derive(Allocative)]
+pub struct PresentTrees {
+    #[allocative(skip)]
+    trees: Mutex<LinkedHashMap<(RemoteExecutorUseCase, FileDigest), DateTime<Utc>>>,
+    capacity: usize,
+}
+
+impl PresentTrees {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            trees: Mutex::new(LinkedHashMap::new()),
+            capacity,
+        }
+    }
+
+    /// Until when the tree of `digest` is in the CAS, if that's after `ttl_deadline`.
+    fn present_until(
+        &self,
+        use_case: RemoteExecutorUseCase,
+        digest: &FileDigest,
+        ttl_deadline: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        let mut trees = self.trees.lock().unwrap();
+        let key = (use_case, digest.dupe());
+        let expires = *trees.get_refresh(&key)?;
+        if expires > ttl_deadline {
+            Some(expires)
+        } else {
+            trees.remove(&key);
+            None
+        }
+    }
+
+    fn insert(&self, use_case: RemoteExecutorUseCase, digest: FileDigest, expires: DateTime<Utc>) {
+        let mut trees = self.trees.lock().unwrap();
+        trees.insert((use_case, digest), expires);
+        while trees.len() > self.capacity {
+            trees.pop_front();
+        }
+    }
+
+    /// Collect the digests under `dir` which need checking, skipping the trees known to be
+    /// present.
+    fn collect_input_digests<'a>(
+        &self,
+        use_case: RemoteExecutorUseCase,
+        dir: &'a dyn FingerprintedDirectory<ActionDirectoryMember, TrackedFileDigest>,
+        ttl_deadline: DateTime<Utc>,
+        input_digests: &mut HashSet<&'a TrackedFileDigest>,
+    ) {
+        if self
+            .present_until(use_case, dir.fingerprint().data(), ttl_deadline)
+            .is_some()
+        {
+            return;
+        }
+        if dir.fingerprint().expires() <= ttl_deadline {
+            input_digests.insert(dir.fingerprint());
+        }
+        for (_, entry) in dir.fingerprinted_entries() {
+            match entry {
+                DirectoryEntry::Dir(d) => {
+                    self.collect_input_digests(use_case, d, ttl_deadline, input_digests)
+                }
+                DirectoryEntry::Leaf(ActionDirectoryMember::File(f)) => {
+                    if f.digest.expires() <= ttl_deadline {
+                        input_digests.insert(&f.digest);
+                    }
+                }
+                DirectoryEntry::Leaf(..) => {}
+            }
+        }
+    }
+
+    /// Record until when the trees under `dir` are in the CAS, which is when the first of their
+    /// contents expires, and return it for `dir`.
+    fn record(
+        &self,
+        use_case: RemoteExecutorUseCase,
+        dir: &dyn FingerprintedDirectory<ActionDirectoryMember, TrackedFileDigest>,
+        ttl_deadline: DateTime<Utc>,
+    ) -> DateTime<Utc> {
+        if let Some(expires) = self.present_until(use_case, dir.fingerprint().data(), ttl_deadline)
+        {
+            return expires;
+        }
+        let mut expires = dir.fingerprint().expires();
+        for (_, entry) in dir.fingerprinted_entries() {
+            let entry_expires = match entry {
+                DirectoryEntry::Dir(d) => self.record(use_case, d, ttl_deadline),
+                DirectoryEntry::Leaf(ActionDirectoryMember::File(f)) => f.digest.expires(),
+                DirectoryEntry::Leaf(..) => continue,
+            };
+            expires = expires.min(entry_expires);
+        }
+        if expires > ttl_deadline {
+            self.insert(use_case, dir.fingerprint().data().dupe(), expires);
+        }
+        expires
+    }
+}
+
 impl Uploader {
     async fn find_missing<'a>(
         client: &REClient,
+        present_trees: &PresentTrees,
         input_dir: &'a ActionImmutableDirectory,
         blobs: &'a ActionBlobs,
         use_case: &RemoteExecutorUseCase,
@@ -82,22 +200,12 @@ impl Uploader {
         let mut input_digests = blobs.keys().collect::<HashSet<_>>();
         let digest_ttls = {
             // Collect the digests we need to upload
-            for entry in input_dir.fingerprinted_unordered_walk().without_paths() {
-                let digest = match entry {
-                    DirectoryEntry::Dir(d) => d.fingerprint(),
-                    DirectoryEntry::Leaf(ActionDirectoryMember::File(f)) => &f.digest,
-                    DirectoryEntry::Leaf(..) => continue,
-                };
-
-                if digest.expires() <= ttl_deadline {
-                    input_digests.insert(digest);
-                }
-            }
-
-            let root_dir_digest = input_dir.fingerprint();
-            if root_dir_digest.expires() <= ttl_deadline {
-                input_digests.insert(root_dir_digest);
-            }
+            present_trees.collect_input_digests(
+                *use_case,
+                input_dir,
+                ttl_deadline,
+                &mut input_digests,
+            );
 
             // Find out which ones are missing
             let request = GetDigestsTtlRequest {
@@ -160,12 +268,17 @@ impl Uploader {
             }
         }
 
+        if upload_blobs.is_empty() && missing_digests.is_empty() {
+            present_trees.record(*use_case, input_dir, ttl_deadline);
+        }
+
         Ok((upload_blobs, missing_digests))
     }
 
     pub async fn upload(
         fs: &ProjectRoot,
         client: &REClient,
+        present_trees: &PresentTrees,
         materializer: &Arc<dyn Materializer>,
         dir_path: &ProjectRelativePath,
         input_dir: &ActionImmutableDirectory,
@@ -173,8 +286,15 @@ impl Uploader {
         use_case: RemoteExecutorUseCase,
         digest_config: DigestConfig,
     ) -> anyhow::Result<UploadStats> {
-        let (mut upload_blobs, mut missing_digests) =
-            Self::find_missing(client, input_dir, blobs, &use_case, digest_config).await?;
+        let (mut upload_blobs, mut missing_digests) = Self::find_missing(
+            client,
+            present_trees,
+            input_dir,
+            blobs,
+            &use_case,
+            digest_config,
+        )
+        .await?;
 
         if upload_blobs.is_empty() && missing_digests.is_empty() {
             return Ok(UploadStats::default());
@@ -457,3 +577,59 @@ fn add_injected_missing_digests<'a>(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(content: &str) -> FileDigest {
+        FileDigest::from_content(
+            content.as_bytes(),
+            DigestConfig::testing_default().cas_digest_config(),
+        )
+    }
+
+    #[test]
+    fn test_present_trees_expire() {
+        let trees = PresentTrees::new(10);
+        let use_case = RemoteExecutorUseCase::buck2_default();
+        let now = Utc::now();
+        trees.insert(use_case, digest("a"), now + Duration::seconds(60));
+        assert!(trees.present_until(use_case, &digest("a"), now).is_some());
+        assert!(
+            trees
+                .present_until(use_case, &digest("a"), now + Duration::seconds(120))
+                .is_none()
+        );
+        // The expired entry was dropped.
+        assert!(trees.present_until(use_case, &digest("a"), now).is_none());
+    }
+
+    #[test]
+    fn test_present_trees_by_use_case() {
+        let trees = PresentTrees::new(10);
+        let now = Utc::now();
+        trees.insert(
+            RemoteExecutorUseCase::buck2_default(),
+            digest("a"),
+            now + Duration::seconds(60),
+        );
+        let other = RemoteExecutorUseCase::new("other".to_owned());
+        assert!(trees.present_until(other, &digest("a"), now).is_none());
+    }
+
+    #[test]
+    fn test_present_trees_evict_least_recently_used() {
+        let trees = PresentTrees::new(2);
+        let use_case = RemoteExecutorUseCase::buck2_default();
+        let now = Utc::now();
+        let expires = now + Duration::seconds(60);
+        trees.insert(use_case, digest("a"), expires);
+        trees.insert(use_case, digest("b"), expires);
+        assert!(trees.present_until(use_case, &digest("a"), now).is_some());
+        trees.insert(use_case, digest("c"), expires);
+        assert!(trees.present_until(use_case, &digest("a"), now).is_some());
+        assert!(trees.present_until(use_case, &digest("b"), now).is_none());
+        assert!(trees.present_until(use_case, &digest("c"), now).is_some());
+    }
+}
@@ -0,0 +1,226 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Streaming of the output of local commands while they run, for the actions selected with
+//! `--show-action-output`, which is useful to debug actions which hang or take a long time.
+
+use std::sync::Arc;
+
+use buck2_events::dispatch::get_dispatcher_opt;
+use buck2_events::dispatch::EventDispatcher;
+use buck2_execute::execute::target::CommandExecutionTarget;
+use buck2_forkserver::run::OutputStream;
+use dupe::Dupe;
+
+/// The actions whose output is streamed: each selector is either an action category (e.g.
+/// `cxx_compile`) or the label of the target owning the action (e.g. `cell//foo:bar`).
+#[derive(Clone, Dupe, Default)]
+pub struct ActionOutputFilter {
+    categories: Arc<Vec<String>>,
+    /// The package and name of the selected target labels.
+    labels: Arc<Vec<(String, String)>>,
+}
+
+impl ActionOutputFilter {
+    pub fn new(selectors: Vec<String>) -> Self {
+        let mut categories = Vec::new();
+        let mut labels = Vec::new();
+        for selector in selectors {
+            match selector.rsplit_once(':') {
+                Some((package, name)) if package.contains("//") => {
+                    labels.push((package.to_owned(), name.to_owned()))
+                }
+                _ => categories.push(selector),
+            }
+        }
+        Self {
+            categories: Arc::new(categories),
+            labels: Arc::new(labels),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.categories.is_empty() && self.labels.is_empty()
+    }
+
+    fn matches(&self, category: &str, owner: Option<&buck2_data::TargetLabel>) -> bool {
+        self.categories.iter().any(|c| c == category)
+            || owner.map_or(false, |owner| {
+                self.labels
+                    .iter()
+                    .any(|(package, name)| *package == owner.package && *name == owner.name)
+            })
+    }
+
+    /// A streamer for the output of `target`, if it is selected.
+    pub(crate) fn streamer(
+        &self,
+        target: &dyn CommandExecutionTarget,
+    ) -> Option<ActionOutputStreamer> {
+        if self.is_empty() {
+            return None;
+        }
+        let name = target.as_proto_action_name();
+        let owner = owner_label(target.as_proto_action_key().owner);
+        if !self.matches(&name.category, owner.as_ref()) {
+            return None;
+        }
+        Some(ActionOutputStreamer {
+            dispatcher: get_dispatcher_opt()?,
+            name,
+            owner: owner.map_or_else(String::new, |owner| {
+                format!("{}:{}", owner.package, owner.name)
+            }),
+            stdout: LineBuffer::default(),
+            stderr: LineBuffer::default(),
+        })
+    }
+}
+
+/// The unconfigured label of the target owning an action, if it is owned by a target.
+fn owner_label(owner: Option<buck2_data::action_key::Owner>) -> Option<buck2_data::TargetLabel> {
+    use buck2_data::action_key::Owner;
+
+    match owner? {
+        Owner::TargetLabel(label)
+        | Owner::TestTargetLabel(label)
+        | Owner::LocalResourceSetup(label) => label.label,
+        Owner::AnonTarget(anon) => anon.name,
+        Owner::BxlKey(_) => None,
+    }
+}
+
+/// Holds the output of a command until a line is complete.
+#[derive(Default)]
+struct LineBuffer {
+    buffer: Vec<u8>,
+}
+
+impl LineBuffer {
+    /// Add output, returning the complete lines.
+    fn push(&mut self, bytes: &[u8]) -> Option<String> {
+        self.buffer.extend_from_slice(bytes);
+        let end = self.buffer.iter().rposition(|b| *b == b'\n')? + 1;
+        let lines: Vec<u8> = self.buffer.drain(..end).collect();
+        Some(String::from_utf8_lossy(&lines).into_owned())
+    }
+
+    /// The output of an incomplete last line.
+    fn finish(&mut self) -> Option<String> {
+        if self.buffer.is_empty() {
+            return None;
+        }
+        let rest = std::mem::take(&mut self.buffer);
+        Some(String::from_utf8_lossy(&rest).into_owned())
+    }
+}
+
+/// Emits the output of a command as `ActionOutput` events, line by line.
+pub(crate) struct ActionOutputStreamer {
+    dispatcher: EventDispatcher,
+    name: buck2_data::ActionName,
+    owner: String,
+    stdout: LineBuffer,
+    stderr: LineBuffer,
+}
+
+impl ActionOutputStreamer {
+    pub(crate) fn output(&mut self, stream: OutputStream, bytes: &[u8]) {
+        match stream {
+            OutputStream::Stdout => {
+                if let Some(lines) = self.stdout.push(bytes) {
+                    self.emit(lines, String::new());
+                }
+            }
+            OutputStream::Stderr => {
+                if let Some(lines) = self.stderr.push(bytes) {
+                    self.emit(String::new(), lines);
+                }
+            }
+        }
+    }
+
+    /// Emit what is left once the command has finished.
+    pub(crate) fn finish(&mut self) {
+        if let Some(rest) = self.stdout.finish() {
+            self.emit(rest, String::new());
+        }
+        if let Some(rest) = self.stderr.finish() {
+            self.emit(String::new(), rest);
+        }
+    }
+
+    fn emit(&self, stdout: String, stderr: String) {
+        self.dispatcher.instant_event(buck2_data::ActionOutput {
+            name: Some(self.name.clone()),
+            owner: self.owner.clone(),
+            stdout,
+            stderr,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_buffer() {
+        let mut buffer = LineBuffer::default();
+        assert_eq!(None, buffer.push(b"compiling"));
+        assert_eq!(
+            Some("compiling a\nb\n".to_owned()),
+            buffer.push(b" a\nb\nc")
+        );
+        assert_eq!(None, buffer.push(b""));
+        assert_eq!(Some("c\n".to_owned()), buffer.push(b"\n"));
+        assert_eq!(None, buffer.finish());
+        assert_eq!(None, buffer.push(b"d"));
+        assert_eq!(Some("d".to_owned()), buffer.finish());
+    }
+
+    fn label(package: &str, name: &str) -> buck2_data::TargetLabel {
+        buck2_data::TargetLabel {
+            package: package.to_owned(),
+            name: name.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_filter() {
+        let filter =
+            ActionOutputFilter::new(vec!["cxx_compile".to_owned(), "root//foo:bar".to_owned()]);
+        assert!(filter.matches("cxx_compile", Some(&label("root//baz", "qux"))));
+        assert!(filter.matches("cxx_compile", None));
+        assert!(filter.matches("genrule", Some(&label("root//foo", "bar"))));
+        assert!(!filter.matches("genrule", Some(&label("root//foo", "baz"))));
+        assert!(!filter.matches("genrule", None));
+        assert!(
+            !ActionOutputFilter::default().matches("cxx_compile", Some(&label("root//foo", "bar")))
+        );
+    }
+
+    #[test]
+    fn test_owner_label() {
+        let configured = buck2_data::ConfiguredTargetLabel {
+            label: Some(label("root//foo", "bar")),
+            configuration: Some(buck2_data::Configuration {
+                full_name: "cfg (with parens)".to_owned(),
+            }),
+            execution_configuration: None,
+        };
+        assert_eq!(
+            Some(label("root//foo", "bar")),
+            owner_label(Some(buck2_data::action_key::Owner::TestTargetLabel(
+                configured
+            )))
+        );
+        assert_eq!(None, owner_label(None));
+    }
+}
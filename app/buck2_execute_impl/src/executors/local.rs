@@ -23,14 +23,17 @@ use buck2_common::file_ops::FileDigestConfig;
 use buck2_common::liveliness_observer::LivelinessObserver;
 use buck2_common::liveliness_observer::LivelinessObserverExt;
 use buck2_common::local_resource_state::LocalResourceHolder;
+use buck2_core::directory::DirectoryEntry;
 use buck2_core::fs::artifact_path_resolver::ArtifactFs;
 use buck2_core::fs::fs_util;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
 use buck2_core::fs::paths::abs_path::AbsPath;
+use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
 use buck2_core::fs::project_rel_path::ProjectRelativePath;
 use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
 use buck2_core::tag_error;
 use buck2_core::tag_result;
+use buck2_events::dispatch::console_warning;
 use buck2_events::dispatch::get_dispatcher_opt;
 use buck2_events::dispatch::EventDispatcher;
 use buck2_execute::artifact_value::ArtifactValue;
@@ -62,11 +65,13 @@ use buck2_execute::execute::result::CommandExecutionResult;
 use buck2_execute::knobs::ExecutorGlobalKnobs;
 use buck2_execute::materialize::materializer::MaterializationError;
 use buck2_execute::materialize::materializer::Materializer;
+use buck2_execute::output_symlinks::check_output_symlinks;
 use buck2_forkserver::client::ForkserverClient;
-use buck2_forkserver::run::gather_output;
+use buck2_forkserver::run::gather_output_streaming;
 use buck2_forkserver::run::maybe_absolutize_exe;
 use buck2_forkserver::run::timeout_into_cancellation;
 use buck2_forkserver::run::GatherOutputStatus;
+use buck2_forkserver::run::OutputListener;
 use buck2_futures::cancellable_future::CancellationObserver;
 use buck2_futures::cancellation::CancellationContext;
 use buck2_util::process::background_command;
@@ -81,8 +86,12 @@ use host_sharing::host_sharing::HostSharingGuard;
 use host_sharing::HostSharingBroker;
 use host_sharing::HostSharingRequirements;
 use indexmap::IndexMap;
+use rand::seq::SliceRandom;
 use tracing::info;
 
+use crate::executors::action_output::ActionOutputFilter;
+use crate::executors::action_output::ActionOutputStreamer;
+use crate::executors::undeclared_inputs;
 use crate::executors::worker::WorkerHandle;
 use crate::executors::worker::WorkerPool;
 
@@ -108,6 +117,7 @@ pub struct LocalExecutor {
     knobs: ExecutorGlobalKnobs,
     #[allow(unused)]
     worker_pool: Option<Arc<WorkerPool>>,
+    show_action_output: ActionOutputFilter,
 }
 
 impl LocalExecutor {
@@ -120,6 +130,7 @@ impl LocalExecutor {
         forkserver: Option<ForkserverClient>,
         knobs: ExecutorGlobalKnobs,
         worker_pool: Option<Arc<WorkerPool>>,
+        show_action_output: ActionOutputFilter,
     ) -> Self {
         Self {
             artifact_fs,
@@ -130,6 +141,7 @@ impl LocalExecutor {
             forkserver,
             knobs,
             worker_pool,
+            show_action_output,
         }
     }
 
@@ -145,6 +157,7 @@ impl LocalExecutor {
         env_inheritance: Option<&'a EnvironmentInheritance>,
         liveliness_observer: impl LivelinessObserver + 'static,
         disable_miniperf: bool,
+        on_output: OutputListener<'a>,
     ) -> impl futures::future::Future<
         Output = anyhow::Result<(GatherOutputStatus, Vec<u8>, Vec<u8>)>,
     > + Send
@@ -169,13 +182,14 @@ impl LocalExecutor {
                             env_inheritance,
                             liveliness_observer,
                             self.knobs.enable_miniperf && !disable_miniperf,
+                            on_output,
                         )
                         .await
                     }
 
                     #[cfg(not(unix))]
                     {
-                        let _unused = (forkserver, disable_miniperf);
+                        let _unused = (forkserver, disable_miniperf, on_output);
                         Err(anyhow::anyhow!("Forkserver is not supported off-UNIX"))
                     }
                 }
@@ -200,7 +214,7 @@ impl LocalExecutor {
                     let cancellation =
                         select(timeout.boxed(), alive.boxed()).map(|r| r.factor_first().0);
 
-                    gather_output(cmd, cancellation).await
+                    gather_output_streaming(cmd, cancellation, on_output).await
                 }
                 .with_context(|| format!("Failed to gather output from command: {}", exe)),
             }
@@ -216,6 +230,7 @@ impl LocalExecutor {
         cancellations: &CancellationContext<'_>,
         digest_config: DigestConfig,
         local_resource_holders: &[LocalResourceHolder],
+        mut output_streamer: Option<ActionOutputStreamer>,
     ) -> CommandExecutionResult {
         let args = &request.all_args_vec();
         if args.is_empty() {
@@ -303,10 +318,27 @@ impl LocalExecutor {
 
         let tmpdirs = if let Some(scratch_path) = scratch_path {
             // For the $TMPDIR - important it is absolute
-            scratch_path_abs = self.artifact_fs.fs().resolve(scratch_path);
+            scratch_path_abs = if request.perturb_environment() {
+                // A directory whose path differs with every execution. It's in the scratch
+                // directory, so it is cleaned like it.
+                let tmp = self.artifact_fs.fs().resolve(&scratch_path.join(
+                    ForwardRelativePath::unchecked_new(&format!(
+                        "tmp-{:08x}",
+                        rand::random::<u32>()
+                    )),
+                ));
+                if let Err(e) = fs_util::create_dir_all(&tmp) {
+                    return manager.error("create_perturbed_tmpdir_failed", e);
+                }
+                tmp
+            } else {
+                self.artifact_fs.fs().resolve(scratch_path)
+            };
 
             if cfg!(windows) {
                 const MAX_PATH: usize = 260;
+                // `buck2.windows_long_paths` does not apply here: many tools don't support
+                // extended-length paths in these variables.
                 if scratch_path_abs.as_os_str().len() > MAX_PATH {
                     return manager.error(
                         "scratch_dir_too_long",
@@ -348,7 +380,7 @@ impl LocalExecutor {
         };
         let build_id: &str = &dispatcher.trace_id().to_string();
 
-        let iter_env = || {
+        let ordered_env = || {
             tmpdirs
                 .iter()
                 .map(|(k, v)| (*k, StrOrOsStr::from(*v)))
@@ -368,10 +400,53 @@ impl LocalExecutor {
                     StrOrOsStr::from(build_id),
                 )))
         };
+        // Processes get their environment sorted, but workers get it in this order.
+        let mut env: Vec<(&str, StrOrOsStr)> = ordered_env().collect();
+        if request.perturb_environment() {
+            env.shuffle(&mut rand::thread_rng());
+        }
+        let iter_env = || env.iter().copied();
         let liveliness_observer = manager.liveliness_observer.dupe().and(cancellation);
 
+        let env_policy = &self.knobs.env_policy;
+        let not_allowlisted = env_policy.not_allowlisted_unreported(
+            request.local_environment_inheritance(),
+            request.env_inherit(),
+            iter_env().map(|(k, _)| k),
+        );
+        if !not_allowlisted.is_empty() {
+            dispatcher.console_warning(format!(
+                "Local actions inherit environment variables which are not allowlisted by \
+                `build.action_env_allowlist` or the `env_inherit` of their action: {}",
+                not_allowlisted.join(", ")
+            ));
+        }
+        let env_inheritance = env_policy.apply(
+            request.local_environment_inheritance(),
+            request.env_inherit(),
+        );
+
         let (worker, manager) = self.initialize_worker(request, manager, dispatcher).await?;
 
+        // Trace the files the command reads, to find its undeclared inputs. The trace is written
+        // to the scratch directory, which is cleaned before each execution.
+        let trace = match scratch_path {
+            Some(scratch_path) if self.knobs.trace_undeclared_inputs && worker.is_none() => {
+                Some(self.artifact_fs.fs().resolve(scratch_path).join(
+                    ForwardRelativePath::unchecked_new(undeclared_inputs::TRACE_FILE_NAME),
+                ))
+            }
+            _ => None,
+        };
+        let traced_args;
+        let exec_args = match &trace {
+            Some(trace) => {
+                traced_args = undeclared_inputs::traced_args(trace, args);
+                &traced_args
+            }
+            None => args,
+        };
+
         let execution_kind = match worker {
             None => CommandExecutionKind::Local {
                 digest: action_digest.dupe(),
@@ -427,17 +502,27 @@ impl LocalExecutor {
                         .collect();
                     Ok(worker.exec_cmd(request.args(), env).await)
                 } else {
-                    self.exec(
-                        &args[0],
-                        &args[1..],
-                        env,
-                        request.working_directory(),
-                        request.timeout(),
-                        request.local_environment_inheritance(),
-                        liveliness_observer,
-                        request.disable_miniperf(),
-                    )
-                    .await
+                    let r = self
+                        .exec(
+                            &exec_args[0],
+                            &exec_args[1..],
+                            env,
+                            request.working_directory(),
+                            request.timeout(),
+                            env_inheritance.as_ref(),
+                            liveliness_observer,
+                            request.disable_miniperf(),
+                            &mut |stream, bytes| {
+                                if let Some(streamer) = &mut output_streamer {
+                                    streamer.output(stream, bytes);
+                                }
+                            },
+                        )
+                        .await;
+                    if let Some(streamer) = &mut output_streamer {
+                        streamer.finish();
+                    }
+                    r
                 };
 
                 let execution_time = execution_start.elapsed();
@@ -487,7 +572,37 @@ impl LocalExecutor {
                 timing.hashed_artifacts_count = hashing_time.hashed_artifacts_count;
 
                 if exit_code == 0 {
-                    manager.success(execution_kind, outputs, std_streams, timing)
+                    let undeclared = match &trace {
+                        Some(trace) => {
+                            let working_directory = match request.working_directory() {
+                                Some(d) => self.root.join(d),
+                                None => self.root.clone(),
+                            };
+                            match undeclared_inputs::undeclared_inputs(
+                                trace,
+                                &self.root,
+                                &working_directory,
+                                request,
+                                scratch_path.as_deref(),
+                            ) {
+                                Ok(undeclared) => undeclared,
+                                Err(e) => {
+                                    // The command itself succeeded, so don't fail it because
+                                    // its trace could not be checked.
+                                    console_warning(format!(
+                                        "Failed to check the undeclared inputs of `{}`: {:#}",
+                                        request.args().join(" "),
+                                        e
+                                    ));
+                                    Vec::new()
+                                }
+                            }
+                        }
+                        None => Vec::new(),
+                    };
+                    let mut result = manager.success(execution_kind, outputs, std_streams, timing);
+                    result.undeclared_inputs = undeclared;
+                    result
                 } else {
                     let manager = check_inputs(
                         manager,
@@ -562,6 +677,9 @@ impl LocalExecutor {
             total_hashing_time += hashing_info.hashing_duration;
             total_hashed_outputs += hashing_info.hashed_artifacts_count;
             if let Some(entry) = entry {
+                if let DirectoryEntry::Dir(dir) = &entry {
+                    check_output_symlinks(&path, dir)?;
+                }
                 insert_entry(&mut builder, &path, entry)?;
                 entries.push((output.cloned(), path));
             }
@@ -724,11 +842,13 @@ impl PreparedCommandExecutor for LocalExecutor {
 
         let PreparedCommand {
             request,
-            target: _,
+            target,
             prepared_action,
             digest_config,
         } = command;
 
+        let output_streamer = self.show_action_output.streamer(*target);
+
         let local_resource_holders = executor_stage_async(
             {
                 let a = buck2_data::AcquireLocalResource {};
@@ -775,6 +895,7 @@ impl PreparedCommandExecutor for LocalExecutor {
                     cancellations,
                     *digest_config,
                     &local_resource_holders,
+                    output_streamer,
                 )
             })
             .await
@@ -1089,6 +1210,7 @@ mod unix {
         env_inheritance: Option<&EnvironmentInheritance>,
         liveliness_observer: impl LivelinessObserver + 'static,
         enable_miniperf: bool,
+        on_output: OutputListener<'_>,
     ) -> anyhow::Result<(GatherOutputStatus, Vec<u8>, Vec<u8>)> {
         let exe = exe.as_ref();
 
@@ -1109,7 +1231,11 @@ mod unix {
         };
         apply_local_execution_environment(&mut req, working_directory, env, env_inheritance);
         forkserver
-            .execute(req, async move { liveliness_observer.while_alive().await })
+            .execute_streaming(
+                req,
+                async move { liveliness_observer.while_alive().await },
+                on_output,
+            )
             .await
     }
 
@@ -1175,6 +1301,7 @@ mod tests {
     use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
     use buck2_execute::execute::blocking::testing::DummyBlockingExecutor;
     use buck2_execute::materialize::nodisk::NoDiskMaterializer;
+    use buck2_forkserver::run::gather_output;
     use host_sharing::HostSharingStrategy;
 
     use super::*;
@@ -1290,6 +1417,7 @@ mod tests {
             None,
             ExecutorGlobalKnobs::default(),
             None,
+            ActionOutputFilter::default(),
         );
 
         Ok((executor, temp.path().root().to_buf(), temp))
@@ -1310,6 +1438,7 @@ mod tests {
                 None,
                 NoopLivelinessObserver::create(),
                 false,
+                &mut |_, _| {},
             )
             .await?;
         assert!(matches!(status, GatherOutputStatus::Finished { exit_code, .. } if exit_code == 0));
@@ -1346,6 +1475,7 @@ mod tests {
                 Some(&EnvironmentInheritance::empty()),
                 NoopLivelinessObserver::create(),
                 false,
+                &mut |_, _| {},
             )
             .await?;
         assert!(matches!(status, GatherOutputStatus::Finished { exit_code, .. } if exit_code == 0));
@@ -9,6 +9,7 @@
 
 pub mod action_cache;
 pub mod action_cache_upload_permission_checker;
+pub mod action_output;
 pub mod caching;
 pub(crate) mod empty_action_result;
 pub mod hybrid;
@@ -16,4 +17,5 @@ pub mod local;
 pub mod re;
 pub mod stacked;
 pub mod to_re_platform;
+pub mod undeclared_inputs;
 pub mod worker;
@@ -41,6 +41,8 @@ use buck2_execute::re::action_identity::ReActionIdentity;
 use buck2_execute::re::client::ExecuteResponseOrCancelled;
 use buck2_execute::re::manager::ManagedRemoteExecutionClient;
 use buck2_execute::re::remote_action_result::RemoteActionResult;
+use buck2_execute::re::retry::is_retriable_re_code;
+use buck2_execute::re::retry::is_retriable_re_error;
 use buck2_futures::cancellation::CancellationContext;
 use dupe::Dupe;
 use futures::FutureExt;
@@ -138,21 +140,47 @@ impl ReExecutor {
         let identity =
             ReActionIdentity::new(action, self.re_action_key.as_deref(), request.paths());
 
-        let execute_response = self
-            .re_client
-            .execute(
-                action_digest.dupe(),
-                platform,
-                dependencies,
-                self.re_use_case,
-                &identity,
-                &mut manager,
-                self.skip_cache_read,
-                self.skip_cache_write,
-                self.re_max_queue_time_ms.map(Duration::from_millis),
-                &self.knobs,
-            )
-            .await;
+        let mut retries = 0;
+        let execute_response = loop {
+            let execute_response = self
+                .re_client
+                .execute(
+                    action_digest.dupe(),
+                    platform,
+                    dependencies,
+                    self.re_use_case,
+                    &identity,
+                    &mut manager,
+                    self.skip_cache_read,
+                    self.skip_cache_write,
+                    self.re_max_queue_time_ms.map(Duration::from_millis),
+                    &self.knobs,
+                )
+                .await;
+
+            let retriable = match &execute_response {
+                Ok(ExecuteResponseOrCancelled::Response(response)) => {
+                    is_retriable_re_code(&response.error.code)
+                }
+                Ok(ExecuteResponseOrCancelled::Cancelled) => false,
+                Err(e) => is_retriable_re_error(e),
+            };
+            let backoff = if retriable {
+                self.knobs.re_retry_policy.next_retry(retries)
+            } else {
+                None
+            };
+            let Some(backoff) = backoff else {
+                break execute_response;
+            };
+            info!(
+                "Retrying RE execution of `{}` in {}ms after a transient error",
+                action_digest,
+                backoff.as_millis(),
+            );
+            tokio::time::sleep(backoff).await;
+            retries += 1;
+        };
 
         let response = match execute_response {
             Ok(ExecuteResponseOrCancelled::Response(result)) => result,
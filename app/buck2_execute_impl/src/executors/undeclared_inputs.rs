@@ -0,0 +1,302 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Detection of the files inside the project which local commands read without declaring them
+//! as inputs, enabled by `build.trace_undeclared_inputs`.
+//!
+//! Commands are run under `strace`, so this is only supported on Linux. Only the files opened or
+//! executed by path relative to the working directory or by absolute path are checked.
+
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use buck2_common::file_ops::TrackedFileDigest;
+use buck2_core::directory::Directory;
+use buck2_core::directory::DirectoryEntry;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+use buck2_core::fs::project_rel_path::ProjectRelativePath;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use buck2_execute::directory::ActionDirectoryMember;
+use buck2_execute::execute::request::CommandExecutionRequest;
+
+/// Name of the trace, in the scratch directory of the command.
+pub(crate) const TRACE_FILE_NAME: &str = "buck2_file_access.strace";
+
+#[derive(Debug, buck2_error::Error)]
+enum UndeclaredInputsError {
+    #[error("Tracing commands is only supported on Linux")]
+    UnsupportedPlatform,
+    #[error("`strace` was not found on the `PATH`")]
+    NoStrace,
+}
+
+/// Check that commands can be traced, which needs Linux and `strace`. To be checked once when
+/// `build.trace_undeclared_inputs` is set, rather than failing every command.
+pub fn check_supported() -> anyhow::Result<()> {
+    if !cfg!(target_os = "linux") {
+        return Err(UndeclaredInputsError::UnsupportedPlatform.into());
+    }
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    if !std::env::split_paths(&path).any(|dir| dir.join("strace").is_file()) {
+        return Err(UndeclaredInputsError::NoStrace.into());
+    }
+    Ok(())
+}
+
+/// Command line running `args` under `strace`, writing the trace to `trace`.
+pub(crate) fn traced_args(trace: &AbsNormPath, args: &[String]) -> Vec<String> {
+    let mut traced = vec![
+        "strace".to_owned(),
+        "-f".to_owned(),
+        "-qq".to_owned(),
+        "-e".to_owned(),
+        "trace=%file".to_owned(),
+        "-o".to_owned(),
+        trace.to_string(),
+        "--".to_owned(),
+    ];
+    traced.extend(args.iter().cloned());
+    traced
+}
+
+/// Read the trace written for a command, then delete it, and return the files of the project the
+/// command read which are not among its inputs, outputs, or scratch directory.
+pub(crate) fn undeclared_inputs(
+    trace: &AbsNormPath,
+    root: &AbsNormPath,
+    working_directory: &AbsNormPath,
+    request: &CommandExecutionRequest,
+    scratch: Option<&ProjectRelativePath>,
+) -> anyhow::Result<Vec<ProjectRelativePathBuf>> {
+    let contents = fs_util::read_to_string(trace)?;
+    fs_util::remove_file(trace)?;
+
+    let mut undeclared = BTreeSet::new();
+    for path in parse_trace(&contents) {
+        let path = normalize(&working_directory.as_path().join(path));
+        let Ok(rel) = path.strip_prefix(root.as_path()) else {
+            continue;
+        };
+        let Ok(rel) = ProjectRelativePathBuf::try_from(rel.to_owned()) else {
+            continue;
+        };
+        if scratch.map_or(false, |scratch| rel.starts_with(scratch))
+            || request
+                .paths()
+                .output_paths()
+                .iter()
+                .any(|(output, _)| rel.starts_with(output))
+            || is_declared(request.paths().input_directory(), &rel)
+        {
+            continue;
+        }
+        // Listing a directory is not tracked, and the file may have been a temporary one.
+        match fs_util::symlink_metadata_if_exists(&path)? {
+            Some(meta) if !meta.is_dir() => {
+                undeclared.insert(rel);
+            }
+            _ => {}
+        }
+    }
+    Ok(undeclared.into_iter().collect())
+}
+
+/// Resolve `.` and `..` without accessing the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            c => normalized.push(c),
+        }
+    }
+    normalized
+}
+
+/// Whether `path` is in the inputs of a command.
+fn is_declared(
+    inputs: &dyn Directory<ActionDirectoryMember, TrackedFileDigest>,
+    path: &ProjectRelativePath,
+) -> bool {
+    let mut dir = inputs;
+    for name in path.iter() {
+        match dir.get(name) {
+            None => return false,
+            Some(DirectoryEntry::Dir(d)) => dir = d,
+            // A file, or a symlink the path goes through.
+            Some(DirectoryEntry::Leaf(_)) => return true,
+        }
+    }
+    true
+}
+
+/// System calls which read a file given by path, and whose first argument is this path.
+const READ_CALLS: &[&str] = &["open(", "execve("];
+
+/// Paths of the files read successfully in a trace written by `strace -f`, in order.
+fn parse_trace(trace: &str) -> Vec<String> {
+    let mut paths = Vec::new();
+    // Calls interrupted by another process, by pid.
+    let mut unfinished = HashMap::new();
+    for line in trace.lines() {
+        let (pid, call) = match line.split_once(' ') {
+            Some((pid, call)) if pid.bytes().all(|c| c.is_ascii_digit()) => {
+                (pid, call.trim_start())
+            }
+            _ => ("", line),
+        };
+
+        let (path, rest) = if call.starts_with("<... ") {
+            match unfinished.remove(pid) {
+                Some(path) => (path, call),
+                None => continue,
+            }
+        } else if let Some(args) = call.strip_prefix("openat(AT_FDCWD, ") {
+            match read_path(args) {
+                Some(path) => path,
+                None => continue,
+            }
+        } else if let Some(args) = READ_CALLS.iter().find_map(|c| call.strip_prefix(c)) {
+            match read_path(args) {
+                Some(path) => path,
+                None => continue,
+            }
+        } else {
+            continue;
+        };
+
+        if rest.contains("O_WRONLY") || rest.contains("O_CREAT") {
+            continue;
+        }
+        if rest.ends_with("<unfinished ...>") {
+            unfinished.insert(pid, path);
+            continue;
+        }
+        let succeeded = rest
+            .rsplit_once(" = ")
+            .map_or(false, |(_, result)| !result.starts_with('-'));
+        if succeeded {
+            paths.push(path);
+        }
+    }
+    paths
+}
+
+/// Parse the quoted path at the start of `args`, as escaped by `strace`, returning it and the
+/// rest of `args`.
+fn read_path(args: &str) -> Option<(String, &str)> {
+    let bytes = args.strip_prefix('"')?.as_bytes();
+    let mut path = Vec::new();
+    let mut i = 0;
+    loop {
+        match *bytes.get(i)? {
+            b'"' => {
+                return Some((String::from_utf8_lossy(&path).into_owned(), &args[i + 2..]));
+            }
+            b'\\' => {
+                i += 1;
+                match *bytes.get(i)? {
+                    b'n' => path.push(b'\n'),
+                    b't' => path.push(b'\t'),
+                    d @ b'0'..=b'7' => {
+                        // Octal escape of up to 3 digits.
+                        let mut value = d - b'0';
+                        for _ in 0..2 {
+                            match bytes.get(i + 1) {
+                                Some(d @ b'0'..=b'7') if value < 0o40 => {
+                                    value = value * 8 + (d - b'0');
+                                    i += 1;
+                                }
+                                _ => break,
+                            }
+                        }
+                        path.push(value);
+                    }
+                    c => path.push(c),
+                }
+            }
+            c => path.push(c),
+        }
+        i += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_common::file_ops::FileMetadata;
+    use buck2_execute::digest_config::DigestConfig;
+    use buck2_execute::directory::insert_file;
+    use buck2_execute::directory::ActionDirectoryBuilder;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_trace() {
+        let trace = [
+            r#"100 execve("/usr/bin/cc", ["cc", "-c", "a.c"], 0x7ffd /* 3 vars */) = 0"#,
+            r#"100 openat(AT_FDCWD, "a.c", O_RDONLY|O_CLOEXEC) = 3"#,
+            r#"100 openat(AT_FDCWD, "missing.h", O_RDONLY) = -1 ENOENT (No such file or directory)"#,
+            r#"100 openat(AT_FDCWD, "a.o", O_WRONLY|O_CREAT|O_TRUNC, 0666) = 4"#,
+            r#"101 open("/src/b\"c.h", O_RDONLY <unfinished ...>"#,
+            r#"100 openat(AT_FDCWD, "/src/caf\303\251.h", O_RDONLY) = 5"#,
+            r#"101 <... open resumed>)            = 6"#,
+            r#"101 openat(3, "ignored.h", O_RDONLY) = 7"#,
+            r#"101 +++ exited with 0 +++"#,
+        ]
+        .join("\n");
+        assert_eq!(
+            vec![
+                "/usr/bin/cc".to_owned(),
+                "a.c".to_owned(),
+                "/src/café.h".to_owned(),
+                "/src/b\"c.h".to_owned(),
+            ],
+            parse_trace(&trace)
+        );
+    }
+
+    #[test]
+    fn test_is_declared() -> anyhow::Result<()> {
+        let digest_config = DigestConfig::testing_default();
+        let mut builder = ActionDirectoryBuilder::empty();
+        insert_file(
+            &mut builder,
+            ProjectRelativePath::new("foo/bar.h")?,
+            FileMetadata::empty(digest_config.cas_digest_config()),
+        )?;
+        let inputs = builder.fingerprint(digest_config.as_directory_serializer());
+
+        assert!(is_declared(&inputs, ProjectRelativePath::new("foo/bar.h")?));
+        assert!(is_declared(&inputs, ProjectRelativePath::new("foo")?));
+        assert!(!is_declared(
+            &inputs,
+            ProjectRelativePath::new("foo/baz.h")?
+        ));
+        assert!(!is_declared(
+            &inputs,
+            ProjectRelativePath::new("baz/bar.h")?
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            PathBuf::from("/repo/foo/b.h"),
+            normalize(Path::new("/repo/foo/./bar/../b.h"))
+        );
+    }
+}
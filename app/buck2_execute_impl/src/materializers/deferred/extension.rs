@@ -17,6 +17,7 @@ use anyhow::Context as _;
 use async_trait::async_trait;
 use buck2_core::directory::DirectoryEntry;
 use buck2_core::fs::fs_util;
+use buck2_core::fs::project_rel_path::ProjectRelativePath;
 use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
 use buck2_events::dispatch::get_dispatcher;
 use buck2_execute::directory::ActionDirectoryMember;
@@ -329,6 +330,104 @@ impl<T> ExtensionCommand<T> for TestIter {
     }
 }
 
+#[derive(Derivative)]
+#[derivative(Debug)]
+struct PathInfo {
+    path: ProjectRelativePathBuf,
+    sender: Sender<String>,
+}
+
+impl<T> ExtensionCommand<T> for PathInfo {
+    fn execute(self: Box<Self>, processor: &mut DeferredMaterializerCommandProcessor<T>) {
+        let _ignored = self.sender.send(path_info(processor, &self.path));
+    }
+}
+
+/// The output of `buck2 audit deferred-materializer path`.
+pub(super) fn path_info<T>(
+    processor: &DeferredMaterializerCommandProcessor<T>,
+    path: &ProjectRelativePath,
+) -> String {
+    let mut out = String::new();
+
+    writeln!(&mut out, "path: {}", path).unwrap();
+
+    let mut path_iter = path.iter();
+    match processor.tree.prefix_get(&mut path_iter) {
+        None => {
+            writeln!(&mut out, "state: not known to the materializer").unwrap();
+        }
+        Some(data) => {
+            // The components of `path` not consumed by `prefix_get` are inside the artifact.
+            let inner: Vec<_> = path_iter.collect();
+            let artifact = path
+                .iter()
+                .take(path.iter().count() - inner.len())
+                .map(|c| c.as_str())
+                .collect::<Vec<_>>()
+                .join("/");
+            if !inner.is_empty() {
+                writeln!(&mut out, "artifact: {}", artifact).unwrap();
+            }
+
+            match &data.stage {
+                ArtifactMaterializationStage::Declared { entry, method } => {
+                    writeln!(&mut out, "state: declared").unwrap();
+                    writeln!(&mut out, "method: {}", method).unwrap();
+                    match entry {
+                        DirectoryEntry::Dir(dir) => {
+                            writeln!(&mut out, "digest: {}", dir.fingerprint()).unwrap();
+                        }
+                        DirectoryEntry::Leaf(member) => {
+                            writeln!(&mut out, "entry: {}", member).unwrap();
+                        }
+                    }
+                }
+                ArtifactMaterializationStage::Materialized {
+                    metadata,
+                    last_access_time,
+                    active,
+                } => {
+                    writeln!(&mut out, "state: materialized").unwrap();
+                    writeln!(&mut out, "last access time: {:?}", last_access_time).unwrap();
+                    writeln!(&mut out, "declared by this daemon: {}", active).unwrap();
+                    match &metadata.0 {
+                        DirectoryEntry::Dir(meta) => {
+                            writeln!(&mut out, "digest: {}", meta.fingerprint).unwrap();
+                            writeln!(&mut out, "size: {}", meta.total_size).unwrap();
+                        }
+                        DirectoryEntry::Leaf(member) => {
+                            writeln!(&mut out, "entry: {}", member).unwrap();
+                        }
+                    }
+                }
+            }
+
+            let processing = match &data.processing {
+                Processing::Done(..) => "done",
+                Processing::Active {
+                    future: ProcessingFuture::Materializing(..),
+                    ..
+                } => "materializing",
+                Processing::Active {
+                    future: ProcessingFuture::Cleaning(..),
+                    ..
+                } => "cleaning",
+            };
+            writeln!(&mut out, "processing: {}", processing).unwrap();
+
+            // Commands are logged with the `Debug` of their paths, which quotes them.
+            let needle = format!("\"{}\"", artifact);
+            writeln!(&mut out, "recent commands:").unwrap();
+            for command in processor.log_buffer.iter().filter(|c| c.contains(&needle)) {
+                writeln!(&mut out, "  {}", command).unwrap();
+            }
+        }
+    }
+
+    out
+}
+
 #[derive(Derivative)]
 #[derivative(Debug)]
 struct FlushAccessTimes {
@@ -430,6 +529,14 @@ impl<T: IoHandler> DeferredMaterializerExtensions for DeferredMaterializerAccess
         receiver.await.context("No response from materializer")
     }
 
+    async fn path_info(&self, path: ProjectRelativePathBuf) -> anyhow::Result<String> {
+        let (sender, receiver) = oneshot::channel();
+        self.command_sender.send(MaterializerCommand::Extension(
+            Box::new(PathInfo { path, sender }) as _,
+        ))?;
+        receiver.await.context("No response from materializer")
+    }
+
     async fn flush_all_access_times(&self) -> anyhow::Result<String> {
         let (sender, receiver) = oneshot::channel();
         self.command_sender
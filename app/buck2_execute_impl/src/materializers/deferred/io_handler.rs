@@ -21,6 +21,7 @@ use buck2_core::directory::unordered_entry_walk;
 use buck2_core::directory::DirectoryEntry;
 use buck2_core::fs::project::ProjectRoot;
 use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use buck2_events::dispatch::with_dispatcher_async;
 use buck2_events::dispatch::EventDispatcher;
 use buck2_execute::digest::CasDigestFromReExt;
 use buck2_execute::digest::CasDigestToReExt;
@@ -354,8 +355,11 @@ impl IoHandler for DefaultIoHandler {
                 _ => None,
             },
         };
-        event_dispatcher
-            .span_async(materialization_start, async move {
+        // Run under the dispatcher of the command which requested the materialization, so that
+        // the files are written with its options.
+        with_dispatcher_async(
+            event_dispatcher.dupe(),
+            event_dispatcher.span_async(materialization_start, async move {
                 let path_string = path.as_str().to_owned();
                 let mut stat = MaterializationStat {
                     file_count: 0,
@@ -378,8 +382,9 @@ impl IoHandler for DefaultIoHandler {
                         method: Some(method.to_proto() as i32),
                     },
                 )
-            })
-            .await?;
+            }),
+        )
+        .await?;
         Ok(())
     }
 
@@ -1079,6 +1079,10 @@ impl LogBuffer {
             self.inner.push_back(item);
         }
     }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.inner.iter().map(|s| s.as_str())
+    }
 }
 
 impl std::fmt::Display for LogBuffer {
@@ -98,6 +98,7 @@ mod state_machine {
     use tokio::time::Duration as TokioDuration;
 
     use super::*;
+    use crate::materializers::deferred::extension::path_info;
 
     #[derive(Debug, Eq, PartialEq)]
     enum Op {
@@ -349,6 +350,60 @@ mod state_machine {
         .await
     }
 
+    #[tokio::test]
+    async fn test_path_info() -> anyhow::Result<()> {
+        ignore_stack_overflow_checks_for_future(async {
+            let (mut dm, _) = make_processor(Default::default());
+            let digest_config = dm.io.digest_config();
+
+            let path = make_path("foo/bar");
+            dm.declare(
+                &path,
+                ArtifactValue::file(digest_config.empty_file()),
+                Box::new(ArtifactMaterializationMethod::Test),
+            );
+            dm.log_buffer.push(format!("Declare({:?}, _, _)", path));
+
+            let info = path_info(&dm, &path);
+            assert!(info.contains("state: declared\n"), "{}", info);
+            assert!(info.contains("method: Test\n"), "{}", info);
+            assert!(!info.contains("artifact:"), "{}", info);
+            assert!(
+                info.contains("recent commands:\n  Declare(\"foo/bar\", _, _)\n"),
+                "{}",
+                info
+            );
+
+            let info = path_info(&dm, &make_path("foo/bar/baz"));
+            assert!(info.contains("artifact: foo/bar\n"), "{}", info);
+            assert!(info.contains("state: declared\n"), "{}", info);
+
+            let info = path_info(&dm, &make_path("foo"));
+            assert!(
+                info.contains("state: not known to the materializer\n"),
+                "{}",
+                info
+            );
+
+            let res = dm
+                .materialize_artifact(&path, EventDispatcher::null())
+                .context("Expected a future")?
+                .await;
+            dm.materialization_finished(
+                path.clone(),
+                Utc::now(),
+                dm.version_tracker.current(),
+                res,
+            );
+            let info = path_info(&dm, &path);
+            assert!(info.contains("state: materialized\n"), "{}", info);
+            assert!(info.contains("processing: done\n"), "{}", info);
+
+            Ok(())
+        })
+        .await
+    }
+
     fn make_artifact_value_with_symlink_dep(
         target_path: &ProjectRelativePathBuf,
         target_from_symlink: &RelativePathBuf,
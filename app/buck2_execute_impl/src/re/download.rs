@@ -42,6 +42,7 @@ use buck2_execute::execute::request::CommandExecutionRequest;
 use buck2_execute::execute::result::CommandExecutionResult;
 use buck2_execute::materialize::materializer::CasDownloadInfo;
 use buck2_execute::materialize::materializer::Materializer;
+use buck2_execute::output_symlinks::check_output_symlinks;
 use buck2_execute::re::manager::ManagedRemoteExecutionClient;
 use buck2_execute::re::remote_action_result::RemoteActionResult;
 use buck2_futures::cancellation::CancellationContext;
@@ -312,6 +313,7 @@ impl CasDownloader<'_> {
 
         for (dir, tree) in output_spec.output_directories().iter().zip(trees) {
             let entry = re_tree_to_directory(&tree, &expires, self.digest_config)?;
+            check_output_symlinks(&dir.path, &entry)?;
             input_dir.insert(
                 re_forward_path(dir.path.as_str())?,
                 DirectoryEntry::Dir(entry),
@@ -15,6 +15,7 @@ use anyhow::Context;
 use async_trait::async_trait;
 use buck2_common::ignores::ignore_set::IgnoreSet;
 use buck2_common::legacy_configs::LegacyBuckConfig;
+use buck2_common::source_digest_cache::SourceDigestCache;
 use buck2_core::cells::name::CellName;
 use buck2_core::cells::CellResolver;
 use buck2_core::fs::project::ProjectRoot;
@@ -37,22 +38,26 @@ pub trait FileWatcher: Allocative + Send + Sync + 'static {
 impl dyn FileWatcher {
     /// Create a new FileWatcher. Note that this is not async, since it's called during daemon
     /// startup and shouldn't be doing any work that could warrant suspending.
+    ///
+    /// `source_digest_cache` is only used by the Watchman file watcher (see
+    /// [`uses_watchman`](Self::uses_watchman)), which keeps it up to date with the changes.
     pub fn new(
         project_root: &ProjectRoot,
         root_config: &LegacyBuckConfig,
         cells: CellResolver,
         ignore_specs: HashMap<CellName, IgnoreSet>,
+        source_digest_cache: Option<Arc<SourceDigestCache>>,
     ) -> anyhow::Result<Arc<dyn FileWatcher>> {
-        let default = if is_open_source() {
-            "notify"
-        } else {
-            "watchman"
-        };
-
-        match root_config.get("buck2", "file_watcher").unwrap_or(default) {
+        match Self::name(root_config) {
             "watchman" => Ok(Arc::new(
-                WatchmanFileWatcher::new(project_root.root(), root_config, cells, ignore_specs)
-                    .context("Creating watchman file watcher")?,
+                WatchmanFileWatcher::new(
+                    project_root.root(),
+                    root_config,
+                    cells,
+                    ignore_specs,
+                    source_digest_cache,
+                )
+                .context("Creating watchman file watcher")?,
             )),
             "notify" => Ok(Arc::new(
                 NotifyFileWatcher::new(project_root, cells, ignore_specs)
@@ -65,4 +70,18 @@ impl dyn FileWatcher {
             other => Err(anyhow::anyhow!("Invalid buck2.file_watcher: {}", other)),
         }
     }
+
+    /// Whether the file watcher created by [`new`](Self::new) is the Watchman one.
+    pub fn uses_watchman(root_config: &LegacyBuckConfig) -> bool {
+        Self::name(root_config) == "watchman"
+    }
+
+    fn name(root_config: &LegacyBuckConfig) -> &str {
+        let default = if is_open_source() {
+            "notify"
+        } else {
+            "watchman"
+        };
+        root_config.get("buck2", "file_watcher").unwrap_or(default)
+    }
 }
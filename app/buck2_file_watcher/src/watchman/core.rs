@@ -210,6 +210,15 @@ pub trait SyncableQueryProcessor: Send + Sync {
         mergebase: &Option<String>,
         watchman_version: Option<String>,
     ) -> anyhow::Result<(Self::Output, Self::Payload)>;
+
+    /// The clock and mergebase up to which a previous instance of the processor processed the
+    /// changes, if the query should resume from there rather than start with a fresh instance.
+    fn resume_from(&self) -> Option<(ClockSpec, Option<String>)> {
+        None
+    }
+
+    /// Called once all the changes up to `clock` were processed.
+    fn on_synced(&mut self, _clock: &ClockSpec, _mergebase: &Option<String>) {}
 }
 
 /// commands to be sent to the SyncableQueryHandler.
@@ -273,6 +282,11 @@ where
         let mut client = None;
         if let Err(e) = self.reconnect(&mut client).await {
             tracing::warn!("Connecting to Watchman failed (will re-attempt): {:#}", e);
+        } else if let Some((clock, mergebase)) = self.processor.resume_from() {
+            // Reconnecting later resets the clock again, so a failed query doesn't resume from
+            // there.
+            self.last_clock = clock;
+            self.last_mergebase = mergebase;
         };
 
         loop {
@@ -344,6 +358,7 @@ where
             ),
         };
 
+        self.processor.on_synced(&clock, &new_mergebase);
         self.last_mergebase = new_mergebase;
         self.last_clock = clock;
 
@@ -17,6 +17,8 @@ use async_trait::async_trait;
 use buck2_common::dice::file_ops::FileChangeTracker;
 use buck2_common::ignores::ignore_set::IgnoreSet;
 use buck2_common::legacy_configs::LegacyBuckConfig;
+use buck2_common::source_digest_cache::SourceDigestCache;
+use buck2_common::source_digest_cache::SourceDigestCacheClock;
 use buck2_core::cells::name::CellName;
 use buck2_core::cells::CellResolver;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
@@ -25,9 +27,11 @@ use buck2_core::rollout_percentage::RolloutPercentage;
 use buck2_events::dispatch::span_async;
 use buck2_util::process::async_background_command;
 use dice::DiceTransactionUpdater;
+use dupe::Dupe;
 use tracing::info;
 use tracing::warn;
 use watchman_client::expr::Expr;
+use watchman_client::prelude::ClockSpec;
 use watchman_client::prelude::Connector;
 use watchman_client::prelude::FileType;
 
@@ -47,6 +51,7 @@ struct WatchmanQueryProcessor {
     report_global_rev: bool,
     last_mergebase: Option<String>,
     last_mergebase_global_rev: Option<u64>,
+    source_digest_cache: Option<Arc<SourceDigestCache>>,
 }
 
 /// Used in process_one_change
@@ -71,6 +76,9 @@ impl WatchmanQueryProcessor {
             watchman_version,
         );
 
+        let mut changed_files = Vec::new();
+        let mut changed_dirs = Vec::new();
+
         for ev in events {
             // If the path is invalid, then walk up all the way until you find a valid dir to
             // invalidate listings. We don't need to invalidate the file itself, as we can't
@@ -91,9 +99,33 @@ impl WatchmanQueryProcessor {
                 }
             };
 
+            if self.source_digest_cache.is_some() {
+                // Regardless of the ignores: the cache is keyed by the paths read, whichever cell
+                // they are in.
+                match &event {
+                    ChangeEvent::Watchman(WatchmanEvent {
+                        kind: WatchmanKind::File,
+                        ..
+                    }) => changed_files.push(path.to_buf()),
+                    ChangeEvent::Watchman(WatchmanEvent {
+                        kind: WatchmanKind::Directory,
+                        event: WatchmanEventType::Modify,
+                        ..
+                    }) => {}
+                    // What is under a symlink is not cached, but a symlink may replace a directory.
+                    ChangeEvent::Watchman(_) | ChangeEvent::SyntheticDirectoryChange => {
+                        changed_dirs.push(path.to_buf())
+                    }
+                }
+            }
+
             self.process_one_change(path, event, &mut handler, &mut stats)?;
         }
 
+        if let Some(source_digest_cache) = &self.source_digest_cache {
+            source_digest_cache.invalidate(changed_files, changed_dirs);
+        }
+
         let stats = stats.finish();
         handler.write_to_dice(&mut ctx)?;
 
@@ -274,6 +306,10 @@ impl SyncableQueryProcessor for WatchmanQueryProcessor {
 
         self.last_mergebase = mergebase.clone();
 
+        if let Some(source_digest_cache) = &self.source_digest_cache {
+            source_digest_cache.clear();
+        }
+
         if let Some(hash) = self.last_mergebase.as_ref() {
             if self.report_global_rev {
                 self.last_mergebase_global_rev = try_fetch_global_rev(hash).await;
@@ -303,6 +339,36 @@ impl SyncableQueryProcessor for WatchmanQueryProcessor {
             ctx,
         ))
     }
+
+    fn resume_from(&self) -> Option<(ClockSpec, Option<String>)> {
+        let clock = self.source_digest_cache.as_ref()?.saved_clock()?;
+        Some((
+            ClockSpec::StringClock(clock.clock.clone()),
+            clock.mergebase.clone(),
+        ))
+    }
+
+    fn on_synced(&mut self, clock: &ClockSpec, mergebase: &Option<String>) {
+        let source_digest_cache = match &self.source_digest_cache {
+            Some(source_digest_cache) => source_digest_cache,
+            None => return,
+        };
+        let clock = match clock {
+            ClockSpec::StringClock(clock) => clock.clone(),
+            // Not returned for queries since a clock.
+            ClockSpec::UnixTimestamp(_) => return,
+        };
+        source_digest_cache.synced(SourceDigestCacheClock {
+            clock,
+            mergebase: mergebase.clone(),
+        });
+        let source_digest_cache = source_digest_cache.dupe();
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = source_digest_cache.flush() {
+                warn!("Error writing the source digest cache: {:#}", e);
+            }
+        });
+    }
 }
 
 #[derive(Allocative)]
@@ -321,6 +387,7 @@ impl WatchmanFileWatcher {
         root_config: &LegacyBuckConfig,
         cells: CellResolver,
         ignore_specs: HashMap<CellName, IgnoreSet>,
+        source_digest_cache: Option<Arc<SourceDigestCache>>,
     ) -> anyhow::Result<Self> {
         let watchman_merge_base = root_config
             .get("project", "watchman_merge_base")
@@ -350,6 +417,7 @@ impl WatchmanFileWatcher {
                 report_global_rev,
                 last_mergebase: None,
                 last_mergebase_global_rev: None,
+                source_digest_cache,
             }),
             watchman_merge_base,
         )?;
@@ -28,6 +28,7 @@ use tonic::Request;
 use crate::convert::decode_event_stream;
 use crate::run::decode_command_event_stream;
 use crate::run::GatherOutputStatus;
+use crate::run::OutputListener;
 
 #[derive(Clone, Dupe, Allocative)]
 pub struct ForkserverClient {
@@ -92,6 +93,20 @@ impl ForkserverClient {
         req: buck2_forkserver_proto::CommandRequest,
         cancel: C,
     ) -> anyhow::Result<(GatherOutputStatus, Vec<u8>, Vec<u8>)>
+    where
+        C: Future<Output = ()> + Send + 'static,
+    {
+        self.execute_streaming(req, cancel, &mut |_, _| {}).await
+    }
+
+    /// Like [`execute`](Self::execute), but also passes the output to `on_output` while the
+    /// command runs.
+    pub async fn execute_streaming<C>(
+        &self,
+        req: buck2_forkserver_proto::CommandRequest,
+        cancel: C,
+        on_output: OutputListener<'_>,
+    ) -> anyhow::Result<(GatherOutputStatus, Vec<u8>, Vec<u8>)>
     where
         C: Future<Output = ()> + Send + 'static,
     {
@@ -123,7 +138,7 @@ impl ForkserverClient {
             .context("Error dispatching command to Forkserver")?
             .into_inner();
         let stream = decode_event_stream(stream);
-        decode_command_event_stream(stream).await
+        decode_command_event_stream(stream, on_output).await
     }
 
     pub async fn set_log_filter(&self, log_filter: String) -> anyhow::Result<()> {
@@ -70,6 +70,16 @@ impl From<DecodedStatus> for GatherOutputStatus {
     }
 }
 
+/// The stream of a command which some of its output was written to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// Called with the output of a command as it is produced, in addition to it being gathered.
+pub type OutputListener<'a> = &'a mut (dyn FnMut(OutputStream, &[u8]) + Send);
+
 #[derive(Debug)]
 pub(crate) enum CommandEvent {
     Stdout(Bytes),
@@ -269,6 +279,7 @@ where
 
 pub(crate) async fn decode_command_event_stream<S>(
     stream: S,
+    on_output: OutputListener<'_>,
 ) -> anyhow::Result<(GatherOutputStatus, Vec<u8>, Vec<u8>)>
 where
     S: Stream<Item = anyhow::Result<CommandEvent>>,
@@ -280,8 +291,14 @@ where
 
     while let Some(event) = stream.try_next().await? {
         match event {
-            CommandEvent::Stdout(bytes) => stdout.extend(&bytes),
-            CommandEvent::Stderr(bytes) => stderr.extend(&bytes),
+            CommandEvent::Stdout(bytes) => {
+                on_output(OutputStream::Stdout, &bytes);
+                stdout.extend(&bytes)
+            }
+            CommandEvent::Stderr(bytes) => {
+                on_output(OutputStream::Stderr, &bytes);
+                stderr.extend(&bytes)
+            }
             CommandEvent::Exit(exit) => return Ok((exit, stdout, stderr)),
         }
     }
@@ -295,6 +312,18 @@ pub async fn gather_output<T>(
     cmd: Command,
     cancellation: T,
 ) -> anyhow::Result<(GatherOutputStatus, Vec<u8>, Vec<u8>)>
+where
+    T: Future<Output = anyhow::Result<GatherOutputStatus>> + Send,
+{
+    gather_output_streaming(cmd, cancellation, &mut |_, _| {}).await
+}
+
+/// Like [`gather_output`], but also passes the output to `on_output` while the command runs.
+pub async fn gather_output_streaming<T>(
+    cmd: Command,
+    cancellation: T,
+    on_output: OutputListener<'_>,
+) -> anyhow::Result<(GatherOutputStatus, Vec<u8>, Vec<u8>)>
 where
     T: Future<Output = anyhow::Result<GatherOutputStatus>> + Send,
 {
@@ -310,7 +339,7 @@ where
         DefaultKillProcess::default(),
         true,
     )?;
-    decode_command_event_stream(stream).await
+    decode_command_event_stream(stream, on_output).await
 }
 
 /// Dependency injection for kill. We use this in testing.
@@ -692,7 +721,8 @@ mod tests {
             true,
         )?;
 
-        let (status, _stdout, _stderr) = decode_command_event_stream(stream).await?;
+        let (status, _stdout, _stderr) =
+            decode_command_event_stream(stream, &mut |_, _| {}).await?;
         assert!(matches!(status, GatherOutputStatus::TimedOut(..)));
 
         assert!(*killed.lock().unwrap());
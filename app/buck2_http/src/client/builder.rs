@@ -8,10 +8,12 @@
  */
 
 use std::path::Path;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Context;
+use dupe::Dupe;
 use hyper::client::HttpConnector;
 use hyper::service::Service;
 use hyper::Body;
@@ -28,6 +30,7 @@ use tokio_rustls::TlsConnector;
 
 use super::HttpClient;
 use super::RequestClient;
+use crate::host_policy::HostPolicy;
 use crate::proxy;
 use crate::stats::HttpNetworkStats;
 use crate::tls;
@@ -63,6 +66,8 @@ pub struct HttpClientBuilder {
     supports_vpnless: bool,
     http2: bool,
     timeout_config: Option<TimeoutConfig>,
+    host_policy: HostPolicy,
+    download_cache_dir: Option<Arc<PathBuf>>,
 }
 
 impl HttpClientBuilder {
@@ -101,6 +106,8 @@ impl HttpClientBuilder {
             supports_vpnless: false,
             http2: true,
             timeout_config: None,
+            host_policy: HostPolicy::default(),
+            download_cache_dir: None,
         })
     }
 
@@ -208,6 +215,24 @@ impl HttpClientBuilder {
         self.supports_vpnless
     }
 
+    pub fn with_host_policy(&mut self, host_policy: HostPolicy) -> &mut Self {
+        self.host_policy = host_policy;
+        self
+    }
+
+    pub fn host_policy(&self) -> &HostPolicy {
+        &self.host_policy
+    }
+
+    pub fn with_download_cache_dir(&mut self, dir: Option<PathBuf>) -> &mut Self {
+        self.download_cache_dir = dir.map(Arc::new);
+        self
+    }
+
+    pub fn download_cache_dir(&self) -> Option<&Path> {
+        self.download_cache_dir.as_deref().map(|p| p.as_path())
+    }
+
     fn build_inner(&self) -> Arc<dyn RequestClient> {
         match (self.proxies.as_slice(), &self.timeout_config) {
             // Construct x2p unix socket client.
@@ -287,6 +312,8 @@ impl HttpClientBuilder {
             max_redirects: self.max_redirects,
             supports_vpnless: self.supports_vpnless,
             http2: self.http2,
+            host_policy: self.host_policy.dupe(),
+            download_cache_dir: self.download_cache_dir.dupe(),
             stats: HttpNetworkStats::new(),
         }
     }
@@ -7,6 +7,8 @@
  * of this source tree.
  */
 
+use std::path::Path;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use allocative::Allocative;
@@ -28,6 +30,7 @@ use hyper::Response;
 use tokio::io::AsyncReadExt;
 use tokio_util::io::StreamReader;
 
+use crate::host_policy::HostPolicy;
 use crate::redirect::PendingRequest;
 use crate::redirect::RedirectEngine;
 use crate::stats::CountingStream;
@@ -48,6 +51,8 @@ pub struct HttpClient {
     max_redirects: Option<usize>,
     supports_vpnless: bool,
     http2: bool,
+    host_policy: HostPolicy,
+    download_cache_dir: Option<Arc<PathBuf>>,
     stats: HttpNetworkStats,
 }
 
@@ -113,6 +118,7 @@ impl HttpClient {
         &self,
         mut request: Request<Bytes>,
     ) -> Result<Response<BoxStream<hyper::Result<Bytes>>>, HttpError> {
+        self.host_policy.check(request.uri())?;
         let uri = request.uri().to_string();
         let now = tokio::time::Instant::now();
 
@@ -197,6 +203,15 @@ impl HttpClient {
     pub fn http2(&self) -> bool {
         self.http2
     }
+
+    pub fn host_policy(&self) -> &HostPolicy {
+        &self.host_policy
+    }
+
+    /// Directory of the user-level cache of downloaded files, shared by all checkouts, if enabled.
+    pub fn download_cache_dir(&self) -> Option<&Path> {
+        self.download_cache_dir.as_deref().map(|p| p.as_path())
+    }
 }
 
 /// Trait wrapper around a hyper::Client because hyper::Client is parameterized by
@@ -0,0 +1,132 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Which hosts the http client may talk to, set by the `http.offline` and `http.allowed_hosts`
+//! buckconfigs. Checked for every request, including redirects.
+
+use std::sync::Arc;
+
+use allocative::Allocative;
+use dupe::Dupe;
+use http::Uri;
+
+use crate::HttpError;
+
+#[derive(Allocative, Clone, Dupe, Debug, Default, PartialEq, Eq)]
+pub struct HostPolicy {
+    offline: bool,
+    /// When set, only these hosts and their subdomains are allowed.
+    allowed_hosts: Option<Arc<[String]>>,
+}
+
+impl HostPolicy {
+    pub fn new(offline: bool, allowed_hosts: Option<Vec<String>>) -> HostPolicy {
+        HostPolicy {
+            offline,
+            allowed_hosts: allowed_hosts.map(|hosts| {
+                hosts
+                    .into_iter()
+                    .map(|h| h.trim().trim_start_matches('.').to_ascii_lowercase())
+                    .filter(|h| !h.is_empty())
+                    .collect()
+            }),
+        }
+    }
+
+    fn is_host_allowed(&self, host: &str) -> bool {
+        match &self.allowed_hosts {
+            None => true,
+            Some(allowed) => {
+                let host = host.to_ascii_lowercase();
+                allowed.iter().any(|a| {
+                    host == *a
+                        || host
+                            .strip_suffix(a.as_str())
+                            .map_or(false, |prefix| prefix.ends_with('.'))
+                })
+            }
+        }
+    }
+
+    pub(crate) fn check(&self, uri: &Uri) -> Result<(), HttpError> {
+        if self.offline {
+            return Err(HttpError::Offline {
+                uri: uri.to_string(),
+            });
+        }
+        let host = uri.host().unwrap_or_default();
+        if !self.is_host_allowed(host) {
+            return Err(HttpError::HostNotAllowed {
+                uri: uri.to_string(),
+                host: host.to_owned(),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(policy: &HostPolicy, uri: &str) -> bool {
+        policy.check(&uri.parse().unwrap()).is_ok()
+    }
+
+    #[test]
+    fn test_default_allows_everything() {
+        assert!(check(
+            &HostPolicy::default(),
+            "https://example.com/a.tar.gz"
+        ));
+    }
+
+    #[test]
+    fn test_offline() {
+        let policy = HostPolicy::new(true, None);
+        assert!(!check(&policy, "https://example.com/a.tar.gz"));
+    }
+
+    #[test]
+    fn test_offline_error_tag() {
+        let tags = |policy: &HostPolicy| {
+            buck2_error::Error::from(
+                policy
+                    .check(&"https://example.com/a".parse().unwrap())
+                    .unwrap_err(),
+            )
+            .tags()
+        };
+        assert_eq!(
+            vec![buck2_error::ErrorTag::Offline],
+            tags(&HostPolicy::new(true, None))
+        );
+        assert_eq!(
+            Vec::<buck2_error::ErrorTag>::new(),
+            tags(&HostPolicy::new(
+                false,
+                Some(vec!["mirror.corp".to_owned()])
+            ))
+        );
+    }
+
+    #[test]
+    fn test_allowed_hosts() {
+        let policy = HostPolicy::new(
+            false,
+            Some(vec!["mirror.corp".to_owned(), " .Example.com".to_owned()]),
+        );
+        assert!(check(&policy, "https://mirror.corp/a.tar.gz"));
+        assert!(check(&policy, "https://example.com/a.tar.gz"));
+        assert!(check(&policy, "https://dl.EXAMPLE.com:8080/a.tar.gz"));
+        assert!(!check(&policy, "https://badexample.com/a.tar.gz"));
+        assert!(!check(&policy, "https://github.com/a.tar.gz"));
+        assert!(!check(&policy, "https://mirror.corp.evil.com/a.tar.gz"));
+    }
+}
@@ -13,6 +13,7 @@
 use hyper::StatusCode;
 
 mod client;
+mod host_policy;
 mod proxy;
 mod redirect;
 pub mod retries;
@@ -23,6 +24,7 @@ mod x2p;
 pub use client::to_bytes;
 pub use client::HttpClient;
 pub use client::HttpClientBuilder;
+pub use host_policy::HostPolicy;
 
 fn http_error_label(status: StatusCode) -> &'static str {
     if status.is_server_error() {
@@ -76,6 +78,12 @@ pub enum HttpError {
     #[error("HTTP: Timed out while making request to URI: {uri} after {duration} seconds.")]
     #[buck2(infra)]
     Timeout { uri: String, duration: u64 },
+    #[error("HTTP: Request to {uri} not allowed, the http client is offline (`http.offline`)")]
+    #[buck2(user, tag = Offline)]
+    Offline { uri: String },
+    #[error("HTTP: Request to {uri} not allowed, host `{host}` is not in `http.allowed_hosts`")]
+    #[buck2(user)]
+    HostNotAllowed { uri: String, host: String },
     #[error("While making request to {uri} via x2p")]
     X2P {
         uri: String,
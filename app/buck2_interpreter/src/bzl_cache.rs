@@ -0,0 +1,717 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Evaluated `.bzl` modules, kept on disk so that a new daemon doesn't evaluate again the modules
+//! evaluated by the previous ones.
+//!
+//! There is no serialized form of functions, rules or providers, so only the modules whose values
+//! are data (`None`, bools, ints, floats, strings, and lists, tuples, dicts and structs of those),
+//! or values exported by the modules they load, or globals, are stored. The other modules are
+//! evaluated as usual.
+//!
+//! An entry is found by the path and content of its module, and the globals available to `.bzl`
+//! files, so that a buck2 with different native symbols misses. It is only used if the modules it
+//! loads have the same digest as when it was stored, and the buckconfigs its evaluation read have
+//! the same values. The digest of a module (see [`module_digest`]) covers its entry, the digests of
+//! the modules it loads and the buckconfigs it read, so a change to a module loaded indirectly
+//! misses too.
+//!
+//! To check this holds in practice, `verify_percentage` percent of the hits are evaluated anyway.
+//! A mismatch is reported with the soft error `bzl_cache_mismatch`.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use anyhow::Context;
+use buck2_common::legacy_configs::view::LegacyBuckConfigView;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_core::fs::paths::file_name::FileName;
+use dice::DiceData;
+use dice::DiceDataBuilder;
+use dupe::Dupe;
+use parking_lot::Mutex;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+use starlark::environment::FrozenModule;
+use starlark::environment::Globals;
+use starlark::environment::Module;
+use starlark::values::dict::AllocDict;
+use starlark::values::dict::DictRef;
+use starlark::values::float::StarlarkFloat;
+use starlark::values::list::AllocList;
+use starlark::values::list::ListRef;
+use starlark::values::structs::AllocStruct;
+use starlark::values::structs::StructRef;
+use starlark::values::tuple::AllocTuple;
+use starlark::values::tuple::TupleRef;
+use starlark::values::FrozenValue;
+use starlark::values::UnpackValue;
+use starlark::values::Value;
+use starlark::values::ValueIdentity;
+
+use crate::error::BuckStarlarkError;
+use crate::file_loader::LoadedModule;
+use crate::file_loader::LoadedModules;
+
+/// Hand-maintained version of the format of the entries. Bump it when changing it.
+pub const BZL_CACHE_VERSION: u64 = 1;
+
+const VERSIONS_FILE_NAME: &str = "versions.json";
+
+/// Lists, tuples, dicts and structs nested deeper than this are not cached.
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, buck2_error::Error)]
+enum BzlCacheError {
+    #[error("The cached module has `{0}`, which is not exported by the modules it loads")]
+    MissingLoadedValue(String),
+    #[error("The cached module has the global `{0}`, which does not exist")]
+    MissingGlobal(String),
+    #[error("The cached module has a struct with the field `{0}` twice")]
+    DuplicateStructField(String),
+}
+
+/// A value of a cached module.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+enum CachedValue {
+    None,
+    Bool(bool),
+    Int(i64),
+    /// The bits of the float, which can't all be represented in JSON.
+    Float(u64),
+    String(String),
+    List(Vec<CachedValue>),
+    Tuple(Vec<CachedValue>),
+    Dict(Vec<(CachedValue, CachedValue)>),
+    Struct(Vec<(String, CachedValue)>),
+    /// The value exported as `name` by the loaded module `module`.
+    Loaded {
+        module: String,
+        name: String,
+    },
+    /// The global `name`.
+    Global(String),
+}
+
+/// The names of a module and their values.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct CachedModule {
+    docstring: Option<String>,
+    /// The names with whether they are exported, and their values.
+    names: Vec<(String, bool, CachedValue)>,
+}
+
+/// The values which cached modules refer to instead of storing them: the values exported by the
+/// loaded modules, and the globals.
+struct Referenceable<'v> {
+    values: HashMap<ValueIdentity<'v>, CachedValue>,
+}
+
+impl<'v> Referenceable<'v> {
+    fn new(loaded_modules: &'v LoadedModules, globals: &'v Globals) -> Referenceable<'v> {
+        let mut values = HashMap::new();
+        for (name, value) in globals.iter() {
+            values.insert(
+                value.to_value().identity(),
+                CachedValue::Global(name.to_owned()),
+            );
+        }
+        // Values of loaded modules win over globals, in case a module re-exports a global.
+        for (path, module) in loaded_modules.map.iter() {
+            let env = module.env();
+            for name in env.names() {
+                if let Ok(Some(value)) = env.get_option(name.as_str()) {
+                    values.insert(
+                        value.value().to_value().identity(),
+                        CachedValue::Loaded {
+                            module: path.to_string(),
+                            name: name.as_str().to_owned(),
+                        },
+                    );
+                }
+            }
+        }
+        Referenceable { values }
+    }
+
+    fn cache(&self, value: Value<'v>, depth: usize) -> Option<CachedValue> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        match value.get_type() {
+            "NoneType" => return Some(CachedValue::None),
+            "bool" => return value.unpack_bool().map(CachedValue::Bool),
+            // Ints which don't fit in 64 bits are not cached.
+            "int" => return i64::unpack_value(value).map(CachedValue::Int),
+            "float" => {
+                return value
+                    .downcast_ref::<StarlarkFloat>()
+                    .map(|f| CachedValue::Float(f.0.to_bits()));
+            }
+            "string" => {
+                return value
+                    .unpack_str()
+                    .map(|s| CachedValue::String(s.to_owned()));
+            }
+            _ => {}
+        }
+        if let Some(reference) = self.values.get(&value.identity()) {
+            return Some(reference.clone());
+        }
+        if let Some(list) = ListRef::from_value(value) {
+            return Some(CachedValue::List(self.cache_all(list.content(), depth)?));
+        }
+        if let Some(tuple) = TupleRef::from_value(value) {
+            return Some(CachedValue::Tuple(self.cache_all(tuple.content(), depth)?));
+        }
+        if let Some(dict) = DictRef::from_value(value) {
+            return Some(CachedValue::Dict(
+                dict.iter()
+                    .map(|(k, v)| Some((self.cache(k, depth + 1)?, self.cache(v, depth + 1)?)))
+                    .collect::<Option<_>>()?,
+            ));
+        }
+        if let Some(s) = StructRef::from_value(value) {
+            return Some(CachedValue::Struct(
+                s.iter()
+                    .map(|(k, v)| Some((k.as_str().to_owned(), self.cache(v, depth + 1)?)))
+                    .collect::<Option<_>>()?,
+            ));
+        }
+        None
+    }
+
+    fn cache_all(&self, values: &[Value<'v>], depth: usize) -> Option<Vec<CachedValue>> {
+        values.iter().map(|v| self.cache(*v, depth + 1)).collect()
+    }
+}
+
+impl CachedModule {
+    /// The cached form of `module`, evaluated with `loaded_modules` and `globals`, or `None` if
+    /// it has values which can't be cached.
+    pub fn new(
+        module: &FrozenModule,
+        loaded_modules: &LoadedModules,
+        globals: &Globals,
+    ) -> Option<CachedModule> {
+        let referenceable = Referenceable::new(loaded_modules, globals);
+        let exported: HashSet<_> = module.names().map(|name| name.as_str()).collect();
+        let mut names = module
+            .all_names_and_values()
+            .map(|(name, value)| {
+                Some((
+                    name.as_str().to_owned(),
+                    exported.contains(name.as_str()),
+                    referenceable.cache(value.to_value(), 0)?,
+                ))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        names.sort_by(|a, b| a.0.cmp(&b.0));
+        Some(CachedModule {
+            docstring: module.docstring().map(|s| s.to_owned()),
+            names,
+        })
+    }
+
+    /// Sets the names of the cached module in `env`, which was created to evaluate the module
+    /// with the same `loaded_modules` and `globals` as when it was cached.
+    pub fn restore(
+        &self,
+        env: &Module,
+        loaded_modules: &LoadedModules,
+        globals: &Globals,
+    ) -> anyhow::Result<()> {
+        let loaded_modules: HashMap<String, _> = loaded_modules
+            .map
+            .iter()
+            .map(|(path, module)| (path.to_string(), module))
+            .collect();
+        let globals: HashMap<&str, _> = globals.iter().collect();
+        for (name, exported, value) in &self.names {
+            let value = restore_value(value, env, &loaded_modules, &globals)?;
+            if *exported {
+                env.set(name, value);
+            } else {
+                env.set_private(env.frozen_heap().alloc_str(name), value);
+            }
+        }
+        if let Some(docstring) = &self.docstring {
+            env.set_docstring(docstring.clone());
+        }
+        Ok(())
+    }
+}
+
+fn restore_value<'v>(
+    value: &CachedValue,
+    env: &'v Module,
+    loaded_modules: &HashMap<String, &LoadedModule>,
+    globals: &HashMap<&str, FrozenValue>,
+) -> anyhow::Result<Value<'v>> {
+    let heap = env.heap();
+    let restore_all = |values: &[CachedValue]| -> anyhow::Result<Vec<Value<'v>>> {
+        values
+            .iter()
+            .map(|v| restore_value(v, env, loaded_modules, globals))
+            .collect()
+    };
+    Ok(match value {
+        CachedValue::None => Value::new_none(),
+        CachedValue::Bool(b) => Value::new_bool(*b),
+        CachedValue::Int(i) => heap.alloc(*i),
+        CachedValue::Float(bits) => heap.alloc(StarlarkFloat(f64::from_bits(*bits))),
+        CachedValue::String(s) => heap.alloc(s.as_str()),
+        CachedValue::List(items) => heap.alloc(AllocList(restore_all(items)?)),
+        CachedValue::Tuple(items) => heap.alloc(AllocTuple(restore_all(items)?)),
+        CachedValue::Dict(items) => {
+            let mut pairs = Vec::with_capacity(items.len());
+            for (k, v) in items {
+                let k = restore_value(k, env, loaded_modules, globals)?;
+                // Checked here, since allocating the dict panics on unhashable keys.
+                k.get_hashed().map_err(BuckStarlarkError::new)?;
+                pairs.push((k, restore_value(v, env, loaded_modules, globals)?));
+            }
+            heap.alloc(AllocDict(pairs))
+        }
+        CachedValue::Struct(fields) => {
+            let mut seen = HashSet::new();
+            let mut restored = Vec::with_capacity(fields.len());
+            for (k, v) in fields {
+                if !seen.insert(k.as_str()) {
+                    return Err(BzlCacheError::DuplicateStructField(k.clone()).into());
+                }
+                restored.push((k.as_str(), restore_value(v, env, loaded_modules, globals)?));
+            }
+            heap.alloc(AllocStruct(restored))
+        }
+        CachedValue::Loaded { module, name } => {
+            let value = loaded_modules
+                .get(module)
+                .and_then(|m| m.env().get_option(name).ok().flatten())
+                .ok_or_else(|| BzlCacheError::MissingLoadedValue(format!("{}:{}", module, name)))?;
+            value.owned_value(env.frozen_heap())
+        }
+        CachedValue::Global(name) => Value::new_frozen(
+            *globals
+                .get(name.as_str())
+                .ok_or_else(|| BzlCacheError::MissingGlobal(name.clone()))?,
+        ),
+    })
+}
+
+/// A buckconfig read by the evaluation of a module.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BuckconfigRead {
+    /// Whether it was read from the root cell, with `read_root_config`.
+    root: bool,
+    section: String,
+    key: String,
+    value: Option<String>,
+}
+
+/// The buckconfigs read by the evaluation of a module.
+#[derive(Default, Debug)]
+pub struct BuckconfigReads(Mutex<Vec<BuckconfigRead>>);
+
+impl BuckconfigReads {
+    pub fn into_reads(self) -> Vec<BuckconfigRead> {
+        self.0.into_inner()
+    }
+}
+
+/// A buckconfig view recording the reads into `reads`, to be stored with the module.
+#[derive(Debug)]
+pub struct RecordingBuckConfigView<'a> {
+    root: bool,
+    inner: &'a dyn LegacyBuckConfigView,
+    reads: &'a BuckconfigReads,
+}
+
+impl<'a> RecordingBuckConfigView<'a> {
+    pub fn new(
+        root: bool,
+        inner: &'a dyn LegacyBuckConfigView,
+        reads: &'a BuckconfigReads,
+    ) -> Self {
+        RecordingBuckConfigView { root, inner, reads }
+    }
+}
+
+impl LegacyBuckConfigView for RecordingBuckConfigView<'_> {
+    fn get(&self, section: &str, key: &str) -> anyhow::Result<Option<Arc<str>>> {
+        let value = self.inner.get(section, key)?;
+        self.reads.0.lock().push(BuckconfigRead {
+            root: self.root,
+            section: section.to_owned(),
+            key: key.to_owned(),
+            value: value.as_deref().map(|v| v.to_owned()),
+        });
+        Ok(value)
+    }
+}
+
+/// An evaluated module, stored on disk.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BzlCacheEntry {
+    /// The paths of the loaded modules, with their digests.
+    deps: Vec<(String, String)>,
+    configs: Vec<BuckconfigRead>,
+    pub module: CachedModule,
+}
+
+impl BzlCacheEntry {
+    /// `None` if one of the loaded modules has no digest.
+    pub fn new(
+        loaded_modules: &LoadedModules,
+        configs: Vec<BuckconfigRead>,
+        module: CachedModule,
+    ) -> Option<BzlCacheEntry> {
+        Some(BzlCacheEntry {
+            deps: dep_digests(loaded_modules)?,
+            configs,
+            module,
+        })
+    }
+
+    /// Whether this entry is the module evaluated with `loaded_modules` and these buckconfigs.
+    pub fn is_valid(
+        &self,
+        loaded_modules: &LoadedModules,
+        buckconfig: &dyn LegacyBuckConfigView,
+        root_buckconfig: &dyn LegacyBuckConfigView,
+    ) -> anyhow::Result<bool> {
+        if dep_digests(loaded_modules).as_ref() != Some(&self.deps) {
+            return Ok(false);
+        }
+        for read in &self.configs {
+            let view = if read.root {
+                root_buckconfig
+            } else {
+                buckconfig
+            };
+            if view.get(&read.section, &read.key)?.as_deref() != read.value.as_deref() {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    pub fn configs(&self) -> &[BuckconfigRead] {
+        &self.configs
+    }
+}
+
+fn dep_digests(loaded_modules: &LoadedModules) -> Option<Vec<(String, String)>> {
+    loaded_modules
+        .map
+        .iter()
+        .map(|(path, module)| Some((path.to_string(), module.cache_digest()?.to_owned())))
+        .collect()
+}
+
+/// The digest of the module with the entry key `key`, evaluated with `loaded_modules` and having
+/// read `configs`, or `None` if one of the loaded modules has no digest.
+pub fn module_digest(
+    key: &str,
+    loaded_modules: &LoadedModules,
+    configs: &[BuckconfigRead],
+) -> Option<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(key);
+    for (path, digest) in dep_digests(loaded_modules)? {
+        hasher.update([0]);
+        hasher.update(path);
+        hasher.update([0]);
+        hasher.update(digest);
+    }
+    for read in configs {
+        hasher.update([1, read.root as u8]);
+        hasher.update(&read.section);
+        hasher.update([0]);
+        hasher.update(&read.key);
+        match &read.value {
+            Some(value) => {
+                hasher.update([1]);
+                hasher.update(value);
+            }
+            None => hasher.update([0]),
+        }
+    }
+    Some(hex::encode(hasher.finalize()))
+}
+
+/// A digest of the names, types and signatures of `globals`, so that modules evaluated with other
+/// globals are not used.
+pub fn globals_fingerprint(globals: &Globals) -> anyhow::Result<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(globals.describe());
+    hasher.update(serde_json::to_string(&globals.documentation())?);
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// The evaluated modules stored on disk.
+pub struct BzlCache {
+    dir: AbsNormPathBuf,
+    verify_percentage: u32,
+}
+
+impl BzlCache {
+    /// Opens the cache in `dir`, deleting its entries if they were written with other `versions`.
+    pub fn open(
+        dir: &AbsNormPath,
+        versions: HashMap<String, String>,
+        verify_percentage: u32,
+    ) -> anyhow::Result<BzlCache> {
+        let versions_path = dir.join(FileName::unchecked_new(VERSIONS_FILE_NAME));
+        let stored_versions = fs_util::read_to_string_if_exists(&versions_path)?
+            .and_then(|s| serde_json::from_str::<HashMap<String, String>>(&s).ok());
+        if stored_versions.as_ref() != Some(&versions) {
+            fs_util::remove_all(dir)?;
+            fs_util::create_dir_all(dir)?;
+            fs_util::write(&versions_path, serde_json::to_string(&versions)?)?;
+        }
+        Ok(BzlCache {
+            dir: dir.to_buf(),
+            verify_percentage,
+        })
+    }
+
+    /// The key of the entry of the module `path` with `content`, evaluated with the globals with
+    /// the fingerprint `globals_fingerprint`.
+    pub fn key(path: &str, content: &str, globals_fingerprint: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(path);
+        hasher.update([0]);
+        hasher.update(content);
+        hasher.update([0]);
+        hasher.update(globals_fingerprint);
+        hex::encode(hasher.finalize())
+    }
+
+    fn entry_path(&self, key: &str) -> AbsNormPathBuf {
+        self.dir
+            .join(FileName::unchecked_new(&format!("{}.json", key)))
+    }
+
+    /// The entry stored for `key`. Entries which can't be read are treated as missing.
+    pub fn get(&self, key: &str) -> Option<BzlCacheEntry> {
+        let res: anyhow::Result<Option<BzlCacheEntry>> =
+            (|| match fs_util::read_if_exists(self.entry_path(key))? {
+                Some(data) => Ok(Some(serde_json::from_slice(&data)?)),
+                None => Ok(None),
+            })();
+        match res {
+            Ok(entry) => entry,
+            Err(e) => {
+                tracing::warn!("Error reading the bzl cache entry `{}`: {:#}", key, e);
+                None
+            }
+        }
+    }
+
+    /// Stores `entry` for `key`. Failures are only logged, the module just won't be cached.
+    pub fn put(&self, key: &str, entry: &BzlCacheEntry) {
+        let res: anyhow::Result<()> = (|| {
+            let path = self.entry_path(key);
+            // Written to a temporary file first, so that a concurrent or interrupted write never
+            // leaves a partial entry.
+            let tmp_path = self.dir.join(FileName::unchecked_new(&format!(
+                "{}.{}.tmp",
+                key,
+                rand::random::<u64>()
+            )));
+            fs_util::write(&tmp_path, serde_json::to_vec(entry)?)?;
+            fs_util::rename(&tmp_path, &path).context("Error renaming the bzl cache entry")?;
+            Ok(())
+        })();
+        if let Err(e) = res {
+            tracing::warn!("Error writing the bzl cache entry `{}`: {:#}", key, e);
+        }
+    }
+
+    /// Removes the entry for `key`, after it was found to be wrong.
+    pub fn remove(&self, key: &str) {
+        if let Err(e) = fs_util::remove_file(self.entry_path(key)) {
+            tracing::warn!("Error removing the bzl cache entry `{}`: {:#}", key, e);
+        }
+    }
+
+    /// Whether a hit should be evaluated anyway, to check the cache.
+    pub fn should_verify(&self) -> bool {
+        self.verify_percentage > 0 && rand::random::<u32>() % 100 < self.verify_percentage
+    }
+}
+
+pub trait HasBzlCache {
+    /// The cache of evaluated modules, if enabled.
+    fn get_bzl_cache(&self) -> Option<Arc<BzlCache>>;
+}
+
+pub trait SetBzlCache {
+    fn set_bzl_cache(&mut self, cache: Option<Arc<BzlCache>>);
+}
+
+struct BzlCacheHolder(Option<Arc<BzlCache>>);
+
+impl HasBzlCache for DiceData {
+    fn get_bzl_cache(&self) -> Option<Arc<BzlCache>> {
+        self.get::<BzlCacheHolder>()
+            .ok()
+            .and_then(|holder| holder.0.as_ref().map(|cache| cache.dupe()))
+    }
+}
+
+impl SetBzlCache for DiceDataBuilder {
+    fn set_bzl_cache(&mut self, cache: Option<Arc<BzlCache>>) {
+        self.set(BzlCacheHolder(cache))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use buck2_core::bzl::ImportPath;
+    use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+    use starlark::environment::Globals;
+    use starlark::environment::LibraryExtension;
+    use starlark::environment::Module;
+    use starlark::eval::Evaluator;
+    use starlark::syntax::AstModule;
+    use starlark::syntax::Dialect;
+    use starlark::values::structs::StructRef;
+    use starlark_map::ordered_map::OrderedMap;
+
+    use crate::bzl_cache::BzlCache;
+    use crate::bzl_cache::BzlCacheEntry;
+    use crate::bzl_cache::CachedModule;
+    use crate::error::BuckStarlarkError;
+    use crate::file_loader::LoadedModule;
+    use crate::file_loader::LoadedModules;
+    use crate::paths::module::OwnedStarlarkModulePath;
+
+    fn eval(content: &str, loaded_modules: &LoadedModules, globals: &Globals) -> Module {
+        let module = Module::new();
+        for loaded in loaded_modules.map.values() {
+            module.import_public_symbols(loaded.env());
+        }
+        let ast = AstModule::parse("test.bzl", content.to_owned(), &Dialect::Extended).unwrap();
+        Evaluator::new(&module).eval_module(ast, globals).unwrap();
+        module
+    }
+
+    fn loaded_modules(globals: &Globals) -> LoadedModules {
+        let path = OwnedStarlarkModulePath::LoadFile(ImportPath::testing_new("root//:defs.bzl"));
+        let env = eval("def f():\n    pass\n", &LoadedModules::default(), globals)
+            .freeze()
+            .unwrap();
+        let mut map = OrderedMap::new();
+        map.insert(
+            path.clone(),
+            LoadedModule::with_cache_digest(path, LoadedModules::default(), env, "d".to_owned()),
+        );
+        LoadedModules { map }
+    }
+
+    #[test]
+    fn test_cached_module_round_trip() {
+        let globals = Globals::extended_by(&[LibraryExtension::StructType]);
+        let loaded_modules = loaded_modules(&globals);
+        let module = eval(
+            "\"\"\"Docs.\"\"\"\nA = [1, 2.5, -0.0, \"x\", None, True]\nB = {\"a\": (1, 2)}\nC = struct(f = f, n = len)\n_D = A\n",
+            &loaded_modules,
+            &globals,
+        )
+        .freeze()
+        .unwrap();
+
+        let cached = CachedModule::new(&module, &loaded_modules, &globals).unwrap();
+        let cached: CachedModule =
+            serde_json::from_str(&serde_json::to_string(&cached).unwrap()).unwrap();
+
+        let env = Module::new();
+        for loaded in loaded_modules.map.values() {
+            env.import_public_symbols(loaded.env());
+        }
+        cached.restore(&env, &loaded_modules, &globals).unwrap();
+        let restored = env.freeze().unwrap();
+
+        assert_eq!(
+            Some(&cached),
+            CachedModule::new(&restored, &loaded_modules, &globals).as_ref()
+        );
+        assert_eq!(Some("Docs."), restored.docstring());
+        assert_eq!(
+            module.get("A").unwrap().value().to_repr(),
+            restored.get("A").unwrap().value().to_repr()
+        );
+        // `f` is the function of the loaded module, not a copy.
+        let f = loaded_modules
+            .map
+            .values()
+            .next()
+            .unwrap()
+            .env()
+            .get("f")
+            .unwrap();
+        let c = restored.get("C").unwrap();
+        let (_, c_f) = StructRef::from_value(c.value().to_value())
+            .unwrap()
+            .iter()
+            .find(|(k, _)| k.as_str() == "f")
+            .unwrap();
+        assert!(c_f.ptr_eq(f.value().to_value()));
+        // Loaded names stay private.
+        assert!(restored.get("_D").is_ok());
+        assert!(restored.get("f").is_err());
+    }
+
+    #[test]
+    fn test_cached_module_not_data() {
+        let globals = Globals::extended_by(&[LibraryExtension::StructType]);
+        let loaded_modules = loaded_modules(&globals);
+        let module = eval("def g():\n    pass\n", &loaded_modules, &globals)
+            .freeze()
+            .unwrap();
+        assert_eq!(None, CachedModule::new(&module, &loaded_modules, &globals));
+    }
+
+    #[test]
+    fn test_bzl_cache() {
+        let globals = Globals::extended_by(&[LibraryExtension::StructType]);
+        let temp = tempfile::tempdir().unwrap();
+        let dir = AbsNormPathBuf::new(temp.path().join("bzl")).unwrap();
+        let versions = |v: &str| HashMap::from([("version".to_owned(), v.to_owned())]);
+        let loaded_modules = loaded_modules(&globals);
+        let module = eval("A = 1\n", &loaded_modules, &globals).freeze().unwrap();
+        let entry = BzlCacheEntry::new(
+            &loaded_modules,
+            Vec::new(),
+            CachedModule::new(&module, &loaded_modules, &globals).unwrap(),
+        )
+        .unwrap();
+
+        let key = BzlCache::key("root//:a.bzl", "A = 1\n", "globals");
+        let cache = BzlCache::open(&dir, versions("1"), 0).unwrap();
+        assert!(cache.get(&key).is_none());
+        cache.put(&key, &entry);
+        assert_eq!(entry.module, cache.get(&key).unwrap().module);
+
+        let cache = BzlCache::open(&dir, versions("1"), 0).unwrap();
+        assert!(cache.get(&key).is_some());
+        // Entries written with other versions are dropped.
+        let cache = BzlCache::open(&dir, versions("2"), 0).unwrap();
+        assert!(cache.get(&key).is_none());
+    }
+}
@@ -0,0 +1,57 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! The cell mapping visible from a cell, as returned by `cells()` in build files and by
+//! `ctx.cells()` in BXL.
+
+use buck2_common::legacy_configs::external_cells::EXTERNAL_CELLS_DIR;
+use buck2_core::cells::cell_root_path::CellRootPath;
+use buck2_core::cells::name::CellName;
+use buck2_core::cells::CellResolver;
+use gazebo::prelude::*;
+use starlark::values::dict::AllocDict;
+use starlark::values::structs::AllocStruct;
+use starlark::values::Heap;
+use starlark::values::Value;
+
+fn is_external(root: &CellRootPath) -> bool {
+    root.as_str()
+        .strip_prefix(EXTERNAL_CELLS_DIR)
+        .map_or(false, |rest| rest.starts_with('/'))
+}
+
+/// A dict from the aliases visible from `cell`, sorted, to structs with:
+///
+/// * `name`: the name of the cell the alias points to,
+/// * `root`: the root of that cell, relative to the project root,
+/// * `external`: whether the cell is an external cell, fetched by Buck2.
+pub fn visible_cells<'v>(
+    heap: &'v Heap,
+    cell_resolver: &CellResolver,
+    cell: CellName,
+) -> anyhow::Result<Value<'v>> {
+    let mut aliases: Vec<_> = cell_resolver
+        .get(cell)?
+        .cell_alias_resolver()
+        .mappings()
+        .collect();
+    aliases.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+    let cells = aliases.into_try_map(|(alias, name)| {
+        let root = cell_resolver.get(name)?.path();
+        anyhow::Ok((
+            alias.as_str(),
+            heap.alloc(AllocStruct([
+                ("name", heap.alloc(name.as_str())),
+                ("root", heap.alloc(root.as_str())),
+                ("external", Value::new_bool(is_external(root))),
+            ])),
+        ))
+    })?;
+    Ok(heap.alloc(AllocDict(cells)))
+}
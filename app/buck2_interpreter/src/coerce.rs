@@ -7,6 +7,7 @@
  * of this source tree.
  */
 
+use buck2_core::package::PackageLabel;
 use buck2_core::target::label::TargetLabel;
 use buck2_util::late_binding::LateBinding;
 use starlark::eval::Evaluator;
@@ -14,3 +15,9 @@ use starlark::eval::Evaluator;
 pub static COERCE_TARGET_LABEL: LateBinding<
     fn(&mut Evaluator, &str) -> anyhow::Result<TargetLabel>,
 > = LateBinding::new("COERCE_TARGET_LABEL");
+
+/// Parse a target label relative to a package, e.g. `:foo` or `bar:foo`. `//bar:foo` is in the
+/// cell of the package.
+pub static RESOLVE_TARGET_LABEL: LateBinding<
+    fn(&mut Evaluator, PackageLabel, &str) -> anyhow::Result<TargetLabel>,
+> = LateBinding::new("RESOLVE_TARGET_LABEL");
@@ -0,0 +1,73 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use anyhow::Context;
+use buck2_core::deprecation::Deprecation;
+use starlark::eval::Evaluator;
+
+/// A global which is still registered, but deprecated.
+pub struct DeprecatedGlobal {
+    pub name: &'static str,
+    /// A global which can be used instead, with the same arguments. `buck2 starlark lint --fix`
+    /// renames uses to it.
+    pub replacement: Option<&'static str>,
+    /// A category starting with `starlark_deprecated`, or the default one if `None`.
+    pub category: Option<&'static str>,
+    pub message: &'static str,
+    /// `YYYY-MM-DD`.
+    pub removal_date: Option<&'static str>,
+}
+
+impl DeprecatedGlobal {
+    pub fn deprecation(&self) -> anyhow::Result<Deprecation> {
+        Deprecation::new(self.category, self.message, self.removal_date)
+    }
+}
+
+/// The deprecation list: every deprecated global, whatever globals it is registered in.
+///
+/// `buck2 starlark lint` reports the uses of those which are registered in the globals of the
+/// linted file, and the implementation of each reports its uses at runtime by calling
+/// [`report_deprecated_global`].
+pub static DEPRECATED_GLOBALS: &[DeprecatedGlobal] = &[];
+
+/// Report a use of the deprecated global `name`, at the call site being evaluated. To be called
+/// first thing by the implementation of a global registered in a `GlobalsBuilder`, after adding
+/// it to [`DEPRECATED_GLOBALS`]:
+///
+/// ```ignore
+/// DeprecatedGlobal {
+///     name: "old_glob",
+///     replacement: Some("glob"),
+///     category: None,
+///     message: "Use `glob`",
+///     removal_date: Some("2024-06-30"),
+/// },
+///
+/// fn old_glob(...) {
+///     report_deprecated_global(eval, "old_glob")?;
+///     ...
+/// }
+/// ```
+pub fn report_deprecated_global(eval: &Evaluator, name: &str) -> anyhow::Result<()> {
+    DEPRECATED_GLOBALS
+        .iter()
+        .find(|global| global.name == name)
+        .with_context(|| {
+            format!(
+                "Global `{}` is not in the deprecation list (internal error)",
+                name
+            )
+        })?
+        .deprecation()?
+        .report(
+            &format!("Function `{}`", name),
+            eval.call_stack_top_location().map(|l| l.to_string()),
+        )
+}
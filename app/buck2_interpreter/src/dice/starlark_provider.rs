@@ -17,6 +17,7 @@ use starlark::environment::Module;
 use starlark::eval::Evaluator;
 
 use crate::dice::starlark_debug::HasStarlarkDebugger;
+use crate::evaluation_stacks::InFlightEvaluation;
 use crate::factory::StarlarkEvaluatorProvider;
 use crate::starlark_debug::StarlarkDebugController;
 use crate::starlark_profiler::StarlarkProfilerOrInstrumentation;
@@ -28,7 +29,8 @@ use crate::starlark_profiler::StarlarkProfilerOrInstrumentation;
 /// async context and allows us to do things like the block_in_place required
 /// when debugging.
 ///
-/// The description is used for the thread name when debugging.
+/// The description is used for the thread name when debugging, and in the state dumps of the
+/// daemon.
 ///
 /// The provided closure will be invoked and passed an appropriate
 /// StarlarkEvaluatorProvider.
@@ -48,11 +50,13 @@ pub async fn with_starlark_eval_provider<'a, D: Deref<Target = DiceComputations<
         Some(v) => Some(v.start_eval(&description).await?),
         None => None,
     };
+    let in_flight = InFlightEvaluation::new(description);
 
     struct EvalProvider<'a, 'b> {
         profiler: &'a mut StarlarkProfilerOrInstrumentation<'b>,
         debugger: Option<Box<dyn StarlarkDebugController>>,
         starlark_max_callstack_size: Option<usize>,
+        in_flight: InFlightEvaluation,
     }
 
     impl StarlarkEvaluatorProvider for EvalProvider<'_, '_> {
@@ -64,6 +68,7 @@ pub async fn with_starlark_eval_provider<'a, D: Deref<Target = DiceComputations<
             if let Some(stack_size) = self.starlark_max_callstack_size {
                 eval.set_max_callstack_size(stack_size)?;
             }
+            eval.set_call_stack_probe(self.in_flight.probe());
 
             let is_profiling_enabled = self.profiler.initialize(&mut eval)?;
             if let Some(v) = &mut self.debugger {
@@ -86,6 +91,7 @@ pub async fn with_starlark_eval_provider<'a, D: Deref<Target = DiceComputations<
             profiler: profiler_instrumentation,
             debugger,
             starlark_max_callstack_size,
+            in_flight,
         };
 
         // If we're debugging, we need to move this to a tokio blocking task.
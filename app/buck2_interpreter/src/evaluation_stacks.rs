@@ -0,0 +1,170 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! The Starlark evaluations in progress in the daemon, and their call stacks on request, for the
+//! state dump of a build which appears hung.
+//!
+//! Evaluations can only record their call stack from their own thread, so a capture requests it,
+//! then waits a little for each evaluation to record it at its next function call.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use parking_lot::Mutex;
+use starlark::eval::CallStack;
+use starlark::eval::CallStackProbe;
+
+static EVALUATIONS: Mutex<BTreeMap<u64, Arc<EvaluationState>>> =
+    parking_lot::const_mutex(BTreeMap::new());
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+/// The last capture requested. Evaluations record their call stack while theirs is older.
+static REQUESTED: AtomicU64 = AtomicU64::new(0);
+
+struct EvaluationState {
+    description: String,
+    start: Instant,
+    /// The last capture this evaluation recorded its call stack for.
+    recorded: AtomicU64,
+    call_stack: Mutex<Option<String>>,
+}
+
+/// Registers an evaluation for as long as it is alive.
+pub struct InFlightEvaluation {
+    id: u64,
+    state: Arc<EvaluationState>,
+}
+
+impl InFlightEvaluation {
+    pub fn new(description: String) -> InFlightEvaluation {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let state = Arc::new(EvaluationState {
+            description,
+            start: Instant::now(),
+            recorded: AtomicU64::new(REQUESTED.load(Ordering::Relaxed)),
+            call_stack: Mutex::new(None),
+        });
+        EVALUATIONS.lock().insert(id, state.clone());
+        InFlightEvaluation { id, state }
+    }
+
+    /// The probe to set on the evaluator, see `Evaluator::set_call_stack_probe`.
+    pub fn probe(&self) -> Box<dyn CallStackProbe + Send + Sync> {
+        Box::new(Probe(self.state.clone()))
+    }
+}
+
+impl Drop for InFlightEvaluation {
+    fn drop(&mut self) {
+        EVALUATIONS.lock().remove(&self.id);
+    }
+}
+
+struct Probe(Arc<EvaluationState>);
+
+impl CallStackProbe for Probe {
+    fn requested(&self) -> bool {
+        self.0.recorded.load(Ordering::Relaxed) < REQUESTED.load(Ordering::Relaxed)
+    }
+
+    fn record(&self, call_stack: CallStack) {
+        *self.0.call_stack.lock() = Some(call_stack.to_string());
+        self.0
+            .recorded
+            .store(REQUESTED.load(Ordering::Relaxed), Ordering::Release);
+    }
+}
+
+pub struct EvaluationStack {
+    pub description: String,
+    pub elapsed: Duration,
+    /// `None` if the evaluation made no function call within the timeout (e.g. it is running
+    /// a native function, or waiting to be polled).
+    pub call_stack: Option<String>,
+}
+
+/// The evaluations in progress, oldest first, with their call stacks recorded within `timeout`.
+pub async fn capture_evaluation_stacks(timeout: Duration) -> Vec<EvaluationStack> {
+    let capture = REQUESTED.fetch_add(1, Ordering::Relaxed) + 1;
+    let recorded = |state: &EvaluationState| state.recorded.load(Ordering::Acquire) >= capture;
+
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline && !EVALUATIONS.lock().values().all(|s| recorded(s)) {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let now = Instant::now();
+    EVALUATIONS
+        .lock()
+        .values()
+        .map(|state| EvaluationStack {
+            description: state.description.clone(),
+            elapsed: now.duration_since(state.start),
+            call_stack: if recorded(state) {
+                state.call_stack.lock().clone()
+            } else {
+                None
+            },
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use starlark::environment::Globals;
+    use starlark::environment::Module;
+    use starlark::eval::Evaluator;
+    use starlark::syntax::AstModule;
+    use starlark::syntax::Dialect;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_capture_evaluation_stacks() {
+        let evaluation = InFlightEvaluation::new("test evaluation".to_owned());
+        let capture = tokio::spawn(capture_evaluation_stacks(Duration::from_secs(10)));
+        // Wait for the capture to be requested before evaluating.
+        while !evaluation.probe().requested() {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        eval.set_call_stack_probe(evaluation.probe());
+        let ast = AstModule::parse(
+            "stacks.bzl",
+            "def f():\n  return 1\nf()\n".to_owned(),
+            &Dialect::Extended,
+        )
+        .unwrap();
+        eval.eval_module(ast, &Globals::standard()).unwrap();
+
+        let stacks = capture.await.unwrap();
+        let stack = stacks
+            .iter()
+            .find(|s| s.description == "test evaluation")
+            .unwrap();
+        assert!(
+            stack.call_stack.as_ref().unwrap().contains("stacks.bzl:3"),
+            "{:?}",
+            stack.call_stack
+        );
+
+        drop(evaluation);
+        assert!(
+            capture_evaluation_stacks(Duration::ZERO)
+                .await
+                .iter()
+                .all(|s| s.description != "test evaluation")
+        );
+    }
+}
@@ -0,0 +1,134 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Fingerprints of the host tools listed in `buck2.host_toolchains`, e.g. the `cc` used by
+//! system toolchains. They are exposed in `host_info()`, so that rules can make their actions
+//! depend on them: otherwise upgrading a tool on the host doesn't invalidate anything.
+//!
+//! Probing only looks at the file system, it doesn't run the tools, so it is cheap enough to be
+//! done for every command.
+
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use allocative::Allocative;
+
+/// A tool found on the host.
+#[derive(Debug, PartialEq, Eq, Clone, Allocative)]
+pub struct HostToolchainInfo {
+    /// The path of the tool, with symlinks resolved.
+    pub path: String,
+    /// Changes when the tool is replaced: its size and modification time.
+    pub fingerprint: String,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Allocative)]
+pub struct HostToolchains {
+    /// By tool name, `None` for tools which are not found.
+    tools: Vec<(String, Option<HostToolchainInfo>)>,
+}
+
+impl HostToolchains {
+    /// Probe `tools`, which are either absolute paths or names looked up in the `PATH`.
+    pub fn probe(tools: &[String]) -> Self {
+        let path_var = std::env::var_os("PATH").unwrap_or_default();
+        Self::probe_in(tools, &path_var)
+    }
+
+    fn probe_in(tools: &[String], path_var: &OsStr) -> Self {
+        let mut tools: Vec<_> = tools
+            .iter()
+            .map(|tool| (tool.clone(), Self::probe_tool(tool, path_var)))
+            .collect();
+        tools.sort_by(|(a, _), (b, _)| a.cmp(b));
+        tools.dedup_by(|(a, _), (b, _)| a == b);
+        Self { tools }
+    }
+
+    fn probe_tool(tool: &str, path_var: &OsStr) -> Option<HostToolchainInfo> {
+        let tool_path = Path::new(tool);
+        let found = if tool_path.is_absolute() {
+            tool_path.exists().then(|| tool_path.to_path_buf())
+        } else {
+            Self::find_in_path(tool, path_var)
+        }?;
+        let path = fs::canonicalize(found).ok()?;
+        let metadata = fs::metadata(&path).ok()?;
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|m| m.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_nanos());
+        Some(HostToolchainInfo {
+            path: path.to_string_lossy().into_owned(),
+            fingerprint: format!("{}-{}", metadata.len(), modified),
+        })
+    }
+
+    fn find_in_path(tool: &str, path_var: &OsStr) -> Option<PathBuf> {
+        for dir in std::env::split_paths(path_var) {
+            let candidate = dir.join(tool);
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+            if cfg!(windows) {
+                let candidate = candidate.with_extension("exe");
+                if candidate.is_file() {
+                    return Some(candidate);
+                }
+            }
+        }
+        None
+    }
+
+    /// The probed tools, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Option<&HostToolchainInfo>)> {
+        self.tools
+            .iter()
+            .map(|(name, info)| (name.as_str(), info.as_ref()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let bin = dir.path().join("bin");
+        fs::create_dir(&bin)?;
+        fs::write(bin.join("cc"), "#!/bin/sh\n")?;
+        let path_var = std::env::join_paths([dir.path().join("missing"), bin.clone()])?;
+
+        let absolute = bin.join("cc").to_string_lossy().into_owned();
+        let toolchains = HostToolchains::probe_in(
+            &["cc".to_owned(), "javac".to_owned(), absolute.clone()],
+            &path_var,
+        );
+        let tools: Vec<_> = toolchains.iter().collect();
+        assert_eq!(3, tools.len());
+
+        let cc = tools.iter().find(|(n, _)| *n == "cc").unwrap().1.unwrap();
+        assert_eq!(
+            fs::canonicalize(bin.join("cc"))?.to_string_lossy(),
+            cc.path.as_str()
+        );
+        assert!(cc.fingerprint.starts_with("10-"));
+        assert_eq!(
+            Some(cc),
+            tools.iter().find(|(n, _)| *n == absolute).unwrap().1
+        );
+        assert_eq!(None, tools.iter().find(|(n, _)| *n == "javac").unwrap().1);
+        Ok(())
+    }
+}
@@ -7,6 +7,7 @@
  * of this source tree.
  */
 
+pub mod host_toolchains;
 pub mod xcode;
 
 use allocative::Allocative;
@@ -78,6 +78,8 @@ struct LoadedModuleData {
     loaded_modules: LoadedModules,
     #[derivative(Debug = "ignore")]
     env: FrozenModule,
+    /// The digest of the module in the bzl cache, when it is enabled.
+    cache_digest: Option<String>,
 }
 
 impl LoadedModule {
@@ -90,9 +92,29 @@ impl LoadedModule {
             path,
             loaded_modules,
             env,
+            cache_digest: None,
         }))
     }
 
+    /// A module with its digest in the bzl cache (see [`crate::bzl_cache::module_digest`]).
+    pub fn with_cache_digest(
+        path: OwnedStarlarkModulePath,
+        loaded_modules: LoadedModules,
+        env: FrozenModule,
+        cache_digest: String,
+    ) -> Self {
+        Self(Arc::new(LoadedModuleData {
+            path,
+            loaded_modules,
+            env,
+            cache_digest: Some(cache_digest),
+        }))
+    }
+
+    pub fn cache_digest(&self) -> Option<&str> {
+        self.0.cache_digest.as_deref()
+    }
+
     pub fn loaded_modules(&self) -> &LoadedModules {
         &self.0.loaded_modules
     }
@@ -109,6 +131,11 @@ impl LoadedModule {
         &self.0.env
     }
 
+    /// Whether `self` and `other` are the same evaluation of the module.
+    pub fn ptr_eq(&self, other: &LoadedModule) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
     /// Returned `FrozenValue` is owned by `self.0.env`.
     pub fn extra_globals_from_prelude_for_buck_files(
         &self,
@@ -22,10 +22,14 @@ extern crate starlark;
 pub mod anon_targets;
 pub mod build_context;
 pub mod bxl;
+pub mod bzl_cache;
+pub mod cells;
 pub mod cfg_constructor;
 pub mod coerce;
+pub mod deprecation;
 pub mod dice;
 pub mod error;
+pub mod evaluation_stacks;
 pub mod extra;
 pub mod factory;
 pub mod file_loader;
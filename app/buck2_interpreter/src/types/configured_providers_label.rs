@@ -14,6 +14,7 @@ use buck2_core::provider::label::ConfiguredProvidersLabel;
 use buck2_core::provider::label::NonDefaultProvidersName;
 use buck2_core::provider::label::ProvidersLabel;
 use buck2_core::provider::label::ProvidersName;
+use buck2_core::target::label::TargetLabel;
 use derive_more::Display;
 use dupe::Dupe;
 use serde::Serialize;
@@ -26,6 +27,7 @@ use starlark::environment::GlobalsBuilder;
 use starlark::environment::Methods;
 use starlark::environment::MethodsBuilder;
 use starlark::environment::MethodsStatic;
+use starlark::eval::Evaluator;
 use starlark::typing::Ty;
 use starlark::values::starlark_value;
 use starlark::values::starlark_value_as_type::StarlarkValueAsType;
@@ -39,6 +41,9 @@ use starlark::values::Value;
 use crate::types::cell_path::StarlarkCellPath;
 use crate::types::cell_root::CellRoot;
 use crate::types::project_root::ProjectRoot;
+use crate::types::target_label::label_parent;
+use crate::types::target_label::label_with_name;
+use crate::types::target_label::resolve_label;
 use crate::types::target_label::StarlarkConfiguredTargetLabel;
 use crate::types::target_label::StarlarkTargetLabel;
 
@@ -46,6 +51,14 @@ impl StarlarkConfiguredProvidersLabel {
     pub fn label(&self) -> &ConfiguredProvidersLabel {
         &self.label
     }
+
+    /// The default providers of `target`, with the configuration of this label.
+    fn with_target(&self, target: TargetLabel) -> StarlarkConfiguredProvidersLabel {
+        StarlarkConfiguredProvidersLabel::new(ConfiguredProvidersLabel::new(
+            target.configure_pair(self.label.target().cfg_pair().dupe()),
+            ProvidersName::Default,
+        ))
+    }
 }
 
 /// Container for `ConfiguredProvidersLabel` that gives users access to things like package, cell, etc. This can also be properly stringified by our forthcoming `CommandLine` object
@@ -189,6 +202,37 @@ fn configured_label_methods(builder: &mut MethodsBuilder) {
             (*this.label.target()).dupe(),
         ))
     }
+
+    /// Returns the label of the target `name` in the same package, with the same configuration
+    /// and no sub target. For `ctx.label` of `root//foo:bar`, `ctx.label.with_name("qux")` is
+    /// `root//foo:qux`.
+    fn with_name(
+        this: &StarlarkConfiguredProvidersLabel,
+        #[starlark(require = pos)] name: &str,
+    ) -> anyhow::Result<StarlarkConfiguredProvidersLabel> {
+        let target = label_with_name(this.label.target().unconfigured(), name)?;
+        Ok(this.with_target(target))
+    }
+
+    /// Returns the label of the target with the same name in the parent package, with the same
+    /// configuration and no sub target. Fails for packages at the root of a cell.
+    fn parent(
+        this: &StarlarkConfiguredProvidersLabel,
+    ) -> anyhow::Result<StarlarkConfiguredProvidersLabel> {
+        let target = label_parent(this.label.target().unconfigured())?;
+        Ok(this.with_target(target))
+    }
+
+    /// Resolves a label relative to the package of this label, like `TargetLabel.resolve`, with
+    /// the same configuration and no sub target.
+    fn resolve<'v>(
+        this: &StarlarkConfiguredProvidersLabel,
+        #[starlark(require = pos)] label: &str,
+        eval: &mut Evaluator<'v, '_>,
+    ) -> anyhow::Result<StarlarkConfiguredProvidersLabel> {
+        let target = resolve_label(this.label.target().unconfigured(), label, eval)?;
+        Ok(this.with_target(target))
+    }
 }
 
 impl StarlarkProvidersLabel {
@@ -17,4 +17,5 @@ pub mod provider;
 pub mod regex;
 pub mod rule;
 pub mod target_label;
+pub mod time;
 pub mod transition;
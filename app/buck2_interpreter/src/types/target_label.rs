@@ -11,6 +11,7 @@ use std::hash::Hash;
 
 use allocative::Allocative;
 use anyhow::Context;
+use buck2_core::package::PackageLabel;
 use buck2_core::provider::label::ConfiguredProvidersLabel;
 use buck2_core::provider::label::NonDefaultProvidersName;
 use buck2_core::provider::label::ProviderName;
@@ -18,6 +19,7 @@ use buck2_core::provider::label::ProvidersLabel;
 use buck2_core::provider::label::ProvidersName;
 use buck2_core::target::configured_target_label::ConfiguredTargetLabel;
 use buck2_core::target::label::TargetLabel;
+use buck2_core::target::name::TargetNameRef;
 use derive_more::Display;
 use derive_more::From;
 use dupe::Dupe;
@@ -29,6 +31,7 @@ use starlark::environment::GlobalsBuilder;
 use starlark::environment::Methods;
 use starlark::environment::MethodsBuilder;
 use starlark::environment::MethodsStatic;
+use starlark::eval::Evaluator;
 use starlark::typing::Ty;
 use starlark::values::list::AllocList;
 use starlark::values::list::ListRef;
@@ -40,12 +43,47 @@ use starlark::values::Value;
 use starlark::values::ValueError;
 use starlark::values::ValueLike;
 
+use crate::coerce::RESOLVE_TARGET_LABEL;
 use crate::starlark::values::AllocValue;
 use crate::types::cell_path::StarlarkCellPath;
 use crate::types::configuration::StarlarkConfiguration;
 use crate::types::configured_providers_label::StarlarkConfiguredProvidersLabel;
 use crate::types::configured_providers_label::StarlarkProvidersLabel;
 
+#[derive(Debug, buck2_error::Error)]
+#[buck2(user)]
+enum TargetLabelError {
+    #[error("Package `{0}` is the root of its cell and has no parent package")]
+    NoParentPackage(PackageLabel),
+}
+
+/// The target `name` in the package of `label`.
+pub(crate) fn label_with_name(label: &TargetLabel, name: &str) -> anyhow::Result<TargetLabel> {
+    Ok(TargetLabel::new(label.pkg(), TargetNameRef::new(name)?))
+}
+
+/// The target with the name of `label` in the parent package.
+pub(crate) fn label_parent(label: &TargetLabel) -> anyhow::Result<TargetLabel> {
+    let pkg = label.pkg();
+    let parent = pkg
+        .as_cell_path()
+        .parent()
+        .ok_or_else(|| TargetLabelError::NoParentPackage(pkg.dupe()))?;
+    Ok(TargetLabel::new(
+        PackageLabel::from_cell_path(parent),
+        label.name(),
+    ))
+}
+
+/// `relative` resolved against the package of `label`.
+pub(crate) fn resolve_label(
+    label: &TargetLabel,
+    relative: &str,
+    eval: &mut Evaluator,
+) -> anyhow::Result<TargetLabel> {
+    (RESOLVE_TARGET_LABEL.get()?)(eval, label.pkg(), relative)
+}
+
 #[derive(
     Clone,
     Dupe,
@@ -151,6 +189,39 @@ fn label_methods(builder: &mut MethodsBuilder) {
             providers_name,
         )))
     }
+
+    /// Returns the label of the target `name` in the same package, e.g. `root//foo:qux` for
+    /// `root//foo:bar` and `"qux"`.
+    fn with_name(
+        this: &StarlarkTargetLabel,
+        #[starlark(require = pos)] name: &str,
+    ) -> anyhow::Result<StarlarkTargetLabel> {
+        Ok(StarlarkTargetLabel::new(label_with_name(
+            &this.label,
+            name,
+        )?))
+    }
+
+    /// Returns the label of the target with the same name in the parent package, e.g.
+    /// `root//foo:bar` for `root//foo/baz:bar`. Fails for packages at the root of a cell.
+    fn parent(this: &StarlarkTargetLabel) -> anyhow::Result<StarlarkTargetLabel> {
+        Ok(StarlarkTargetLabel::new(label_parent(&this.label)?))
+    }
+
+    /// Resolves a label relative to the package of this label, as `deps` of a target in that
+    /// package would be: for `root//foo:bar`, `":qux"` is `root//foo:qux`, `"baz:qux"` is
+    /// `root//foo/baz:qux` and `"//qux:qux"` is `root//qux:qux`.
+    fn resolve<'v>(
+        this: &StarlarkTargetLabel,
+        #[starlark(require = pos)] label: &str,
+        eval: &mut Evaluator<'v, '_>,
+    ) -> anyhow::Result<StarlarkTargetLabel> {
+        Ok(StarlarkTargetLabel::new(resolve_label(
+            &this.label,
+            label,
+            eval,
+        )?))
+    }
 }
 
 #[derive(
@@ -274,6 +345,41 @@ fn configured_label_methods(builder: &mut MethodsBuilder) {
             ConfiguredProvidersLabel::new(this.label().dupe(), providers_name),
         ))
     }
+
+    /// Returns the label of the target `name` in the same package, with the same configuration.
+    fn with_name(
+        this: &StarlarkConfiguredTargetLabel,
+        #[starlark(require = pos)] name: &str,
+    ) -> anyhow::Result<StarlarkConfiguredTargetLabel> {
+        let target = label_with_name(this.label.unconfigured(), name)?;
+        Ok(StarlarkConfiguredTargetLabel::new(
+            target.configure_pair(this.label.cfg_pair().dupe()),
+        ))
+    }
+
+    /// Returns the label of the target with the same name in the parent package, with the same
+    /// configuration. Fails for packages at the root of a cell.
+    fn parent(
+        this: &StarlarkConfiguredTargetLabel,
+    ) -> anyhow::Result<StarlarkConfiguredTargetLabel> {
+        let target = label_parent(this.label.unconfigured())?;
+        Ok(StarlarkConfiguredTargetLabel::new(
+            target.configure_pair(this.label.cfg_pair().dupe()),
+        ))
+    }
+
+    /// Resolves a label relative to the package of this label, like `TargetLabel.resolve`, with
+    /// the same configuration.
+    fn resolve<'v>(
+        this: &StarlarkConfiguredTargetLabel,
+        #[starlark(require = pos)] label: &str,
+        eval: &mut Evaluator<'v, '_>,
+    ) -> anyhow::Result<StarlarkConfiguredTargetLabel> {
+        let target = resolve_label(this.label.unconfigured(), label, eval)?;
+        Ok(StarlarkConfiguredTargetLabel::new(
+            target.configure_pair(this.label.cfg_pair().dupe()),
+        ))
+    }
 }
 
 pub fn value_to_providers_name<'v>(subtarget_name: Value<'v>) -> anyhow::Result<ProvidersName> {
@@ -0,0 +1,456 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! The `time` module: `timestamp` and `duration` values, with microsecond precision.
+//!
+//! Everything here is hermetic: timestamps are only created from strings or numbers, never from
+//! the clock. Reading the clock is only possible in BXL, with `utc_now()`.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::hash::Hash;
+
+use allocative::Allocative;
+use chrono::DateTime;
+use chrono::NaiveDate;
+use chrono::SecondsFormat;
+use chrono::TimeZone;
+use chrono::Utc;
+use starlark::any::ProvidesStaticType;
+use starlark::collections::StarlarkHasher;
+use starlark::environment::GlobalsBuilder;
+use starlark::environment::Methods;
+use starlark::environment::MethodsBuilder;
+use starlark::environment::MethodsStatic;
+use starlark::starlark_module;
+use starlark::starlark_simple_value;
+use starlark::values::starlark_value;
+use starlark::values::AllocValue;
+use starlark::values::Heap;
+use starlark::values::NoSerialize;
+use starlark::values::StarlarkValue;
+use starlark::values::Value;
+use starlark::values::ValueError;
+
+const MICROS_PER_SEC: i64 = 1_000_000;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(user)]
+enum TimeError {
+    #[error("Invalid ISO 8601 timestamp `{0}`, expected e.g. `2023-04-05T06:07:08Z`")]
+    InvalidTimestamp(String),
+    #[error("Invalid duration `{0}`, expected e.g. `1h 30m`")]
+    InvalidDuration(String),
+    #[error("Time out of range")]
+    OutOfRange,
+}
+
+/// A point in time, as microseconds since the Unix epoch.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    ProvidesStaticType,
+    NoSerialize,
+    Allocative
+)]
+pub struct StarlarkTimestamp(i64);
+
+/// A signed amount of time, in microseconds.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    ProvidesStaticType,
+    NoSerialize,
+    Allocative
+)]
+pub struct StarlarkDuration(i64);
+
+starlark_simple_value!(StarlarkTimestamp);
+starlark_simple_value!(StarlarkDuration);
+
+impl StarlarkTimestamp {
+    /// The current time. This is not hermetic, and must only be exposed to BXL.
+    pub fn utc_now() -> Self {
+        StarlarkTimestamp(Utc::now().timestamp_micros())
+    }
+
+    fn parse_iso8601(s: &str) -> anyhow::Result<Self> {
+        let micros = match DateTime::parse_from_rfc3339(s) {
+            Ok(time) => time.timestamp_micros(),
+            Err(_) => NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .ok_or_else(|| TimeError::InvalidTimestamp(s.to_owned()))?
+                .timestamp_micros(),
+        };
+        Ok(StarlarkTimestamp(micros))
+    }
+
+    fn from_unix_seconds(seconds: f64) -> anyhow::Result<Self> {
+        let micros = (seconds * MICROS_PER_SEC as f64).round();
+        if !micros.is_finite() || micros.abs() >= i64::MAX as f64 {
+            return Err(TimeError::OutOfRange.into());
+        }
+        Ok(StarlarkTimestamp(micros as i64))
+    }
+
+    fn to_datetime(self) -> anyhow::Result<DateTime<Utc>> {
+        let seconds = self.0.div_euclid(MICROS_PER_SEC);
+        let nanos = self.0.rem_euclid(MICROS_PER_SEC) as u32 * 1000;
+        Utc.timestamp_opt(seconds, nanos)
+            .single()
+            .ok_or_else(|| TimeError::OutOfRange.into())
+    }
+
+    fn format_iso8601(self) -> anyhow::Result<String> {
+        Ok(self
+            .to_datetime()?
+            .to_rfc3339_opts(SecondsFormat::AutoSi, true))
+    }
+
+    fn checked_add(self, duration: StarlarkDuration) -> anyhow::Result<Self> {
+        Ok(StarlarkTimestamp(
+            self.0
+                .checked_add(duration.0)
+                .ok_or(TimeError::OutOfRange)?,
+        ))
+    }
+}
+
+impl StarlarkDuration {
+    fn parse(s: &str) -> anyhow::Result<Self> {
+        let (negative, unsigned) = match s.trim().strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.trim()),
+        };
+        let duration = humantime::parse_duration(unsigned)
+            .map_err(|_| TimeError::InvalidDuration(s.to_owned()))?;
+        let micros = i64::try_from(duration.as_micros()).map_err(|_| TimeError::OutOfRange)?;
+        Ok(StarlarkDuration(if negative { -micros } else { micros }))
+    }
+
+    fn checked_add(self, other: StarlarkDuration) -> anyhow::Result<Self> {
+        Ok(StarlarkDuration(
+            self.0.checked_add(other.0).ok_or(TimeError::OutOfRange)?,
+        ))
+    }
+
+    fn checked_mul(self, factor: i64) -> anyhow::Result<Self> {
+        Ok(StarlarkDuration(
+            self.0.checked_mul(factor).ok_or(TimeError::OutOfRange)?,
+        ))
+    }
+
+    fn checked_neg(self) -> anyhow::Result<Self> {
+        Ok(StarlarkDuration(
+            self.0.checked_neg().ok_or(TimeError::OutOfRange)?,
+        ))
+    }
+}
+
+impl Display for StarlarkTimestamp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.format_iso8601() {
+            Ok(s) => write!(f, "{}", s),
+            Err(_) => write!(f, "<timestamp {}us>", self.0),
+        }
+    }
+}
+
+impl Display for StarlarkDuration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.0 < 0 {
+            write!(f, "-")?;
+        }
+        let duration = std::time::Duration::from_micros(self.0.unsigned_abs());
+        write!(f, "{}", humantime::format_duration(duration))
+    }
+}
+
+fn to_starlark<'v, T: AllocValue<'v>>(
+    heap: &'v Heap,
+    value: anyhow::Result<T>,
+) -> starlark::Result<Value<'v>> {
+    Ok(heap.alloc(value?))
+}
+
+#[starlark_value(type = "timestamp")]
+impl<'v> StarlarkValue<'v> for StarlarkTimestamp {
+    fn get_methods() -> Option<&'static Methods> {
+        static RES: MethodsStatic = MethodsStatic::new();
+        RES.methods(timestamp_methods)
+    }
+
+    fn collect_repr(&self, collector: &mut String) {
+        collector.push_str(&format!("timestamp(\"{}\")", self));
+    }
+
+    fn equals(&self, other: Value<'v>) -> starlark::Result<bool> {
+        Ok(other.downcast_ref::<Self>() == Some(self))
+    }
+
+    fn compare(&self, other: Value<'v>) -> starlark::Result<Ordering> {
+        match other.downcast_ref::<Self>() {
+            Some(other) => Ok(self.cmp(other)),
+            None => ValueError::unsupported_with(self, "compare", other),
+        }
+    }
+
+    fn write_hash(&self, hasher: &mut StarlarkHasher) -> starlark::Result<()> {
+        self.hash(hasher);
+        Ok(())
+    }
+
+    fn add(&self, rhs: Value<'v>, heap: &'v Heap) -> Option<starlark::Result<Value<'v>>> {
+        let rhs = rhs.downcast_ref::<StarlarkDuration>()?;
+        Some(to_starlark(heap, self.checked_add(*rhs)))
+    }
+
+    fn radd(&self, lhs: Value<'v>, heap: &'v Heap) -> Option<starlark::Result<Value<'v>>> {
+        self.add(lhs, heap)
+    }
+
+    fn sub(&self, other: Value<'v>, heap: &'v Heap) -> starlark::Result<Value<'v>> {
+        if let Some(other) = other.downcast_ref::<StarlarkDuration>() {
+            to_starlark(heap, other.checked_neg().and_then(|d| self.checked_add(d)))
+        } else if let Some(other) = other.downcast_ref::<StarlarkTimestamp>() {
+            to_starlark(
+                heap,
+                self.0
+                    .checked_sub(other.0)
+                    .map(StarlarkDuration)
+                    .ok_or_else(|| TimeError::OutOfRange.into()),
+            )
+        } else {
+            ValueError::unsupported_with(self, "-", other)
+        }
+    }
+}
+
+#[starlark_value(type = "duration")]
+impl<'v> StarlarkValue<'v> for StarlarkDuration {
+    fn get_methods() -> Option<&'static Methods> {
+        static RES: MethodsStatic = MethodsStatic::new();
+        RES.methods(duration_methods)
+    }
+
+    fn collect_repr(&self, collector: &mut String) {
+        collector.push_str(&format!("duration(\"{}\")", self));
+    }
+
+    fn to_bool(&self) -> bool {
+        self.0 != 0
+    }
+
+    fn equals(&self, other: Value<'v>) -> starlark::Result<bool> {
+        Ok(other.downcast_ref::<Self>() == Some(self))
+    }
+
+    fn compare(&self, other: Value<'v>) -> starlark::Result<Ordering> {
+        match other.downcast_ref::<Self>() {
+            Some(other) => Ok(self.cmp(other)),
+            None => ValueError::unsupported_with(self, "compare", other),
+        }
+    }
+
+    fn write_hash(&self, hasher: &mut StarlarkHasher) -> starlark::Result<()> {
+        self.hash(hasher);
+        Ok(())
+    }
+
+    fn minus(&self, heap: &'v Heap) -> starlark::Result<Value<'v>> {
+        to_starlark(heap, self.checked_neg())
+    }
+
+    fn add(&self, rhs: Value<'v>, heap: &'v Heap) -> Option<starlark::Result<Value<'v>>> {
+        // `duration + timestamp` falls through to `timestamp.radd`.
+        let rhs = rhs.downcast_ref::<StarlarkDuration>()?;
+        Some(to_starlark(heap, self.checked_add(*rhs)))
+    }
+
+    fn sub(&self, other: Value<'v>, heap: &'v Heap) -> starlark::Result<Value<'v>> {
+        match other.downcast_ref::<StarlarkDuration>() {
+            Some(other) => to_starlark(heap, other.checked_neg().and_then(|d| self.checked_add(d))),
+            None => ValueError::unsupported_with(self, "-", other),
+        }
+    }
+
+    fn mul(&self, rhs: Value<'v>, heap: &'v Heap) -> Option<starlark::Result<Value<'v>>> {
+        let factor = rhs.unpack_i32()?;
+        Some(to_starlark(heap, self.checked_mul(factor.into())))
+    }
+
+    fn rmul(&self, lhs: Value<'v>, heap: &'v Heap) -> Option<starlark::Result<Value<'v>>> {
+        self.mul(lhs, heap)
+    }
+}
+
+#[starlark_module]
+fn timestamp_methods(builder: &mut MethodsBuilder) {
+    /// The timestamp in ISO 8601 format, in UTC, e.g. `2023-04-05T06:07:08Z`.
+    fn format_iso8601(this: &StarlarkTimestamp) -> anyhow::Result<String> {
+        this.format_iso8601()
+    }
+
+    /// Seconds since the Unix epoch, as a float.
+    fn unix_seconds(this: &StarlarkTimestamp) -> anyhow::Result<f64> {
+        Ok(this.0 as f64 / MICROS_PER_SEC as f64)
+    }
+
+    /// Milliseconds since the Unix epoch, rounded down.
+    fn unix_millis(this: &StarlarkTimestamp) -> anyhow::Result<i64> {
+        Ok(this.0.div_euclid(1000))
+    }
+}
+
+#[starlark_module]
+fn duration_methods(builder: &mut MethodsBuilder) {
+    /// The duration in seconds, as a float.
+    fn seconds(this: &StarlarkDuration) -> anyhow::Result<f64> {
+        Ok(this.0 as f64 / MICROS_PER_SEC as f64)
+    }
+
+    /// The duration in milliseconds, rounded towards zero.
+    fn millis(this: &StarlarkDuration) -> anyhow::Result<i64> {
+        Ok(this.0 / 1000)
+    }
+}
+
+#[starlark_module]
+fn time_members(builder: &mut GlobalsBuilder) {
+    /// Parse an ISO 8601 timestamp with a time zone, like `2023-04-05T06:07:08.5+02:00`, or a
+    /// date, like `2023-04-05`, which is midnight UTC.
+    fn parse_iso8601(s: &str) -> anyhow::Result<StarlarkTimestamp> {
+        StarlarkTimestamp::parse_iso8601(s)
+    }
+
+    /// The timestamp `seconds` (an int or a float) after the Unix epoch.
+    fn from_unix(seconds: f64) -> anyhow::Result<StarlarkTimestamp> {
+        StarlarkTimestamp::from_unix_seconds(seconds)
+    }
+
+    /// Parse a duration like `1h 30m`, `90s`, `250ms` or `-2d`.
+    fn parse_duration(s: &str) -> anyhow::Result<StarlarkDuration> {
+        StarlarkDuration::parse(s)
+    }
+
+    /// A duration from its parts, e.g. `time.duration(hours = 1, minutes = 30)`.
+    fn duration(
+        #[starlark(require = named, default = 0)] days: i64,
+        #[starlark(require = named, default = 0)] hours: i64,
+        #[starlark(require = named, default = 0)] minutes: i64,
+        #[starlark(require = named, default = 0)] seconds: i64,
+        #[starlark(require = named, default = 0)] millis: i64,
+    ) -> anyhow::Result<StarlarkDuration> {
+        let unit = |amount: i64, micros: i64| StarlarkDuration(micros).checked_mul(amount);
+        [
+            unit(days, 24 * 60 * 60 * MICROS_PER_SEC)?,
+            unit(hours, 60 * 60 * MICROS_PER_SEC)?,
+            unit(minutes, 60 * MICROS_PER_SEC)?,
+            unit(seconds, MICROS_PER_SEC)?,
+            unit(millis, 1000)?,
+        ]
+        .into_iter()
+        .try_fold(StarlarkDuration(0), StarlarkDuration::checked_add)
+    }
+}
+
+pub fn register_time(globals: &mut GlobalsBuilder) {
+    globals.struct_("time", time_members);
+}
+
+#[cfg(test)]
+mod tests {
+    use starlark::assert::Assert;
+
+    use crate::types::time::register_time;
+
+    #[test]
+    fn test_timestamps() {
+        let mut a = Assert::new();
+        a.globals_add(register_time);
+        a.eq(
+            "time.parse_iso8601('2023-04-05T06:07:08+02:00').format_iso8601()",
+            "'2023-04-05T04:07:08Z'",
+        );
+        a.eq(
+            "str(time.parse_iso8601('2023-04-05T06:07:08.25Z'))",
+            "'2023-04-05T06:07:08.250Z'",
+        );
+        a.eq(
+            "time.parse_iso8601('2023-04-05')",
+            "time.parse_iso8601('2023-04-05T00:00:00Z')",
+        );
+        a.eq("time.from_unix(1.5).unix_millis()", "1500");
+        a.eq(
+            "time.from_unix(0).format_iso8601()",
+            "'1970-01-01T00:00:00Z'",
+        );
+        a.eq("time.from_unix(-1).unix_seconds()", "-1.0");
+        a.is_true("time.from_unix(1) < time.from_unix(2)");
+        a.fail(
+            "time.parse_iso8601('yesterday')",
+            "Invalid ISO 8601 timestamp",
+        );
+    }
+
+    #[test]
+    fn test_durations() {
+        let mut a = Assert::new();
+        a.globals_add(register_time);
+        a.eq(
+            "time.parse_duration('1h 30m')",
+            "time.duration(hours = 1, minutes = 30)",
+        );
+        a.eq("str(time.parse_duration('90s'))", "'1m 30s'");
+        a.eq("str(time.parse_duration('-2d'))", "'-2days'");
+        a.eq("time.duration(millis = 1500).seconds()", "1.5");
+        a.eq("time.duration(seconds = -1, millis = 500).millis()", "-500");
+        a.eq("repr(time.duration(seconds = 2))", "'duration(\"2s\")'");
+        a.is_true("not time.duration()");
+        a.fail("time.parse_duration('soon')", "Invalid duration");
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let mut a = Assert::new();
+        a.globals_add(register_time);
+        a.pass(
+            r#"
+t = time.parse_iso8601("2023-04-05T06:07:08Z")
+d = time.duration(hours = 1)
+assert_eq((t + d).format_iso8601(), "2023-04-05T07:07:08Z")
+assert_eq(d + t, t + d)
+assert_eq((t - d).format_iso8601(), "2023-04-05T05:07:08Z")
+assert_eq((t + d) - t, d)
+assert_eq(t - (t + d), -d)
+assert_eq(d * 3, time.duration(hours = 3))
+assert_eq(2 * d, d + d)
+assert_eq(d - d, time.duration())
+assert_true(d > time.duration(minutes = 59))
+assert_eq(len({t: 1, t + time.duration(): 2}), 1)
+"#,
+        );
+        a.fail("time.from_unix(0) + 1", "not supported");
+    }
+}
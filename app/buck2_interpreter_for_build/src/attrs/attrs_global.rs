@@ -11,9 +11,14 @@ use std::sync::Arc;
 
 use allocative::Allocative;
 use anyhow::Context as _;
+use buck2_core::deprecation::Deprecation;
+use buck2_core::pattern::pattern_type::TargetPatternExtra;
+use buck2_core::pattern::ParsedPattern;
 use buck2_core::plugins::PluginKindSet;
 use buck2_core::provider::label::ProvidersLabel;
+use buck2_core::target::label::TargetLabel;
 use buck2_interpreter::coerce::COERCE_TARGET_LABEL;
+use buck2_interpreter::coerce::RESOLVE_TARGET_LABEL;
 use buck2_interpreter::types::provider::callable::ValueAsProviderCallableLike;
 use buck2_interpreter::types::transition::transition_id_from_value;
 use buck2_node::attrs::attr::Attribute;
@@ -64,6 +69,10 @@ enum AttrError {
     OptionDefaultNone(String),
     #[error("`attrs.default_only` argument must have a default")]
     DefaultOnlyMustHaveDefault,
+    #[error(
+        "`attrs.deprecated` argument can't be `attrs.default_only`, it has no value to deprecate"
+    )]
+    DeprecatedDefaultOnly,
 }
 
 pub(crate) trait AttributeExt {
@@ -137,6 +146,30 @@ pub(crate) fn init_coerce_target_label() {
         .init(|eval, value| get_attr_coercion_context(eval)?.coerce_target_label(value))
 }
 
+#[derive(Debug, buck2_error::Error)]
+#[buck2(user)]
+enum ResolveTargetLabelError {
+    #[error("Expected a target label, got a pattern: `{0}`")]
+    ExpectedTarget(String),
+}
+
+pub(crate) fn init_resolve_target_label() {
+    RESOLVE_TARGET_LABEL.init(|eval, package, value| {
+        let build_context = BuildContext::from_context(eval)?;
+        match ParsedPattern::<TargetPatternExtra>::parsed_opt_absolute(
+            value,
+            Some(package.as_cell_path()),
+            package.cell_name(),
+            build_context.cell_info().cell_resolver(),
+        )? {
+            ParsedPattern::Target(package, target_name, TargetPatternExtra) => {
+                Ok(TargetLabel::new(package, target_name.as_ref()))
+            }
+            _ => Err(ResolveTargetLabelError::ExpectedTarget(value.to_owned()).into()),
+        }
+    })
+}
+
 #[derive(Debug, buck2_error::Error)]
 enum DepError {
     #[error(
@@ -436,6 +469,35 @@ fn attr_module(registry: &mut MethodsBuilder) {
         )))
     }
 
+    /// Marks an attribute as deprecated. Targets which give it a value still work, but each
+    /// produces a soft error of the given `category` (by default `starlark_deprecated`, and
+    /// otherwise starting with it), with the message and the date it will be removed on.
+    /// The uses are counted per call site in the build report, and
+    /// `[soft_errors] <category> = error` makes them errors.
+    ///
+    /// ```python
+    /// attrs.deprecated(
+    ///     attrs.bool(default = False),
+    ///     message = "Use `link_style` instead",
+    ///     removal_date = "2024-06-30",
+    ///     category = "starlark_deprecated_link_whole",
+    /// )
+    /// ```
+    fn deprecated<'v>(
+        #[starlark(this)] _this: Value<'v>,
+        #[starlark(require = pos)] inner: &StarlarkAttribute,
+        #[starlark(require = named)] message: &str,
+        #[starlark(require = named)] removal_date: Option<&str>,
+        #[starlark(require = named)] category: Option<&str>,
+    ) -> anyhow::Result<StarlarkAttribute> {
+        let attr = inner.clone_attribute();
+        if attr.is_default_only() {
+            return Err(AttrError::DeprecatedDefaultOnly.into());
+        }
+        let deprecation = Deprecation::new(category, message, removal_date)?;
+        Ok(StarlarkAttribute::new(attr.with_deprecation(deprecation)))
+    }
+
     /// Takes a target (as per `deps`) and passes a `label` to the rule.
     /// Validates that the target exists, but does not introduce a dependency on it.
     fn label<'v>(
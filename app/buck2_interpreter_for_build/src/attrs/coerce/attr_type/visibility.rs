@@ -14,19 +14,21 @@ use buck2_node::attrs::coercion_context::AttrCoercionContext;
 use buck2_node::attrs::configurable::AttrIsConfigurable;
 use buck2_node::visibility::VisibilityPattern;
 use buck2_node::visibility::VisibilityWithinViewBuilder;
+use starlark::values::type_repr::StarlarkTypeRepr;
 use starlark::values::Value;
 
 use crate::attrs::coerce::attr_type::list::coerce_list;
 use crate::attrs::coerce::attr_type::ty_maybe_select::TyMaybeSelect;
 use crate::attrs::coerce::attr_type::AttrTypeExt;
 use crate::attrs::coerce::AttrTypeCoerce;
+use crate::interpreter::functions::visibility_group::StarlarkVisibilityGroup;
 use crate::interpreter::selector::StarlarkSelector;
 
 #[derive(Debug, buck2_error::Error)]
 enum VisibilityAttrTypeCoerceError {
     #[error("Visibility attribute is not configurable (internal error)")]
     AttrTypeNotConfigurable,
-    #[error("Visibility must be a list of string, got `{0}`")]
+    #[error("Visibility must be a list of strings and visibility groups, got `{0}`")]
     WrongType(String),
     #[error("Visibility attribute is not configurable (i.e. cannot use `select()`): `{0}`")]
     NotConfigurable(String),
@@ -48,7 +50,10 @@ impl AttrTypeCoerce for VisibilityAttrType {
     }
 
     fn starlark_type(&self) -> TyMaybeSelect {
-        AttrType::list(AttrType::string()).starlark_type()
+        TyMaybeSelect::List(Box::new(TyMaybeSelect::Union(vec![
+            AttrType::string().starlark_type(),
+            TyMaybeSelect::Basic(<&StarlarkVisibilityGroup>::starlark_type_repr()),
+        ])))
     }
 }
 
@@ -67,20 +72,29 @@ pub(crate) fn parse_visibility_with_view(
     };
 
     let mut builder = VisibilityWithinViewBuilder::with_capacity(list.len());
+    let mut add = |item: &str| -> anyhow::Result<()> {
+        if item == VisibilityPattern::PUBLIC {
+            // TODO(cjhopman): We should probably enforce that this is the only entry.
+            builder.add_public();
+        } else {
+            builder.add(VisibilityPattern(ctx.coerce_target_pattern(item)?));
+        }
+        Ok(())
+    };
     for item in list {
+        if let Some(group) = StarlarkVisibilityGroup::from_value(*item) {
+            for pattern in group.patterns() {
+                add(pattern)?;
+            }
+            continue;
+        }
         let Some(item) = item.unpack_str() else {
             if StarlarkSelector::from_value(*item).is_some() {
                 return Err(VisibilityAttrTypeCoerceError::NotConfigurable(attr.to_repr()).into());
             }
             return Err(VisibilityAttrTypeCoerceError::WrongType(attr.to_repr()).into());
         };
-
-        if item == VisibilityPattern::PUBLIC {
-            // TODO(cjhopman): We should probably enforce that this is the only entry.
-            builder.add_public();
-        } else {
-            builder.add(VisibilityPattern(ctx.coerce_target_pattern(item)?));
-        }
+        add(item)?;
     }
     Ok(builder)
 }
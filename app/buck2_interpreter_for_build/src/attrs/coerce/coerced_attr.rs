@@ -142,6 +142,7 @@ mod tests {
     use std::collections::BTreeMap;
     use std::sync::Arc;
 
+    use allocative::Allocative;
     use buck2_core::configuration::config_setting::ConfigSettingData;
     use buck2_core::configuration::constraints::ConstraintKey;
     use buck2_core::configuration::constraints::ConstraintValue;
@@ -157,6 +158,7 @@ mod tests {
     use buck2_node::attrs::coerced_attr::CoercedSelector;
     use buck2_node::attrs::configuration_context::AttrConfigurationContext;
     use buck2_node::attrs::fmt_context::AttrFmtContext;
+    use buck2_node::select_resolver::SelectResolverImpl;
     use buck2_util::arc_str::ArcSlice;
     use buck2_util::arc_str::ArcStr;
     use dupe::Dupe;
@@ -223,6 +225,17 @@ mod tests {
     fn select_the_most_specific() {
         struct SelectTestConfigurationContext {
             settings: BTreeMap<TargetLabel, ConfigSettingData>,
+            select_resolver: Option<PreferFirstKey>,
+        }
+
+        /// Resolves ambiguous selects to the first matching key.
+        #[derive(Debug, Allocative)]
+        struct PreferFirstKey;
+
+        impl SelectResolverImpl for PreferFirstKey {
+            fn resolve(&self, keys: &[&TargetLabel]) -> anyhow::Result<Option<TargetLabel>> {
+                Ok(keys.first().map(|k| (*k).dupe()))
+            }
         }
 
         impl AttrConfigurationContext for SelectTestConfigurationContext {
@@ -251,6 +264,12 @@ mod tests {
             ) -> &OrderedMap<Arc<TransitionId>, Arc<TransitionApplied>> {
                 panic!("not used in test")
             }
+
+            fn select_resolver(&self) -> Option<&dyn SelectResolverImpl> {
+                self.select_resolver
+                    .as_ref()
+                    .map(|r| r as &dyn SelectResolverImpl)
+            }
         }
 
         fn constraint_key(t: &str) -> ConstraintKey {
@@ -271,7 +290,8 @@ mod tests {
         let linux_arm64 = TargetLabel::testing_parse("config//:linux-arm64");
         let linux_x86_64 = TargetLabel::testing_parse("config//:linux-x86_64");
 
-        let ctx = SelectTestConfigurationContext {
+        let mut ctx = SelectTestConfigurationContext {
+            select_resolver: None,
             settings: BTreeMap::from_iter([
                 (
                     linux.dupe(),
@@ -342,6 +362,42 @@ mod tests {
                 .unwrap_err()
                 .to_string()
         );
+
+        // Conflicting keys resolved by the select resolver.
+        ctx.select_resolver = Some(PreferFirstKey);
+        assert_eq!(
+            Some(&literal_true()),
+            CoercedAttr::select_the_most_specific(&ctx, &*select_entries).unwrap()
+        );
+        ctx.select_resolver = None;
+
+        // The keys chosen by nested and concatenated selects.
+        let select = |entries: Vec<(TargetLabel, CoercedAttr)>, default: Option<CoercedAttr>| {
+            CoercedAttr::Selector(Box::new(
+                CoercedSelector::new(ArcSlice::from(entries), default).unwrap(),
+            ))
+        };
+        let attr = CoercedAttr::Concat(Box::new([
+            select(
+                vec![(
+                    linux.dupe(),
+                    select(vec![(linux_x86_64.dupe(), literal_str())], None),
+                )],
+                None,
+            ),
+            select(
+                vec![(TargetLabel::testing_parse("config//:macos"), literal_str())],
+                Some(literal_str()),
+            ),
+        ]));
+        assert_eq!(
+            vec![
+                "config//:linux".to_owned(),
+                "config//:linux-x86_64".to_owned(),
+                "DEFAULT".to_owned()
+            ],
+            attr.resolved_select_keys(&ctx).unwrap()
+        );
     }
 
     #[test]
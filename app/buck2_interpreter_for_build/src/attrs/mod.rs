@@ -7,7 +7,8 @@
  * of this source tree.
  */
 
-use anyhow::Context;
+use buck2_error::Context;
+use buck2_error::ErrorTag;
 use buck2_node::attrs::attr::Attribute;
 use buck2_node::attrs::attr::CoercedValue;
 use buck2_node::attrs::coercion_context::AttrCoercionContext;
@@ -54,28 +55,8 @@ impl AttributeCoerceExt for Attribute {
         coercer_ctx: &dyn AttrCoercionContext,
         value: Value<'v>,
     ) -> anyhow::Result<CoercedValue> {
-        if self.is_default_only() {
-            if value.is_none() {
-                return Ok(CoercedValue::Default);
-            } else {
-                return Err(CoercionError::DefaultOnly(value.to_string()).into());
-            }
-        }
-
-        match self.default() {
-            default if !value.is_none() => self
-                .coercer()
-                .coerce_with_default(configurable, coercer_ctx, value, default.map(|x| &**x))
-                .map(CoercedValue::Custom)
-                .with_context(|| {
-                    format!(
-                        "Error coercing attribute `{}` of type `{}`",
-                        param_name, self
-                    )
-                }),
-            Some(_) => Ok(CoercedValue::Default),
-            None => Err(AttrCoerceError::MissingMandatoryParameter(param_name.to_owned()).into()),
-        }
+        coerce_attribute(self, param_name, configurable, coercer_ctx, value)
+            .tag(ErrorTag::Attribute)
     }
 
     fn docstring(&self) -> Option<DocString> {
@@ -86,3 +67,34 @@ impl AttributeCoerceExt for Attribute {
         self.coercer().starlark_type()
     }
 }
+
+fn coerce_attribute<'v>(
+    attr: &Attribute,
+    param_name: &str,
+    configurable: AttrIsConfigurable,
+    coercer_ctx: &dyn AttrCoercionContext,
+    value: Value<'v>,
+) -> anyhow::Result<CoercedValue> {
+    if attr.is_default_only() {
+        if value.is_none() {
+            return Ok(CoercedValue::Default);
+        } else {
+            return Err(CoercionError::DefaultOnly(value.to_string()).into());
+        }
+    }
+
+    match attr.default() {
+        default if !value.is_none() => attr
+            .coercer()
+            .coerce_with_default(configurable, coercer_ctx, value, default.map(|x| &**x))
+            .map(CoercedValue::Custom)
+            .with_context(|| {
+                format!(
+                    "Error coercing attribute `{}` of type `{}`",
+                    param_name, attr
+                )
+            }),
+        Some(_) => Ok(CoercedValue::Default),
+        None => Err(AttrCoerceError::MissingMandatoryParameter(param_name.to_owned()).into()),
+    }
+}
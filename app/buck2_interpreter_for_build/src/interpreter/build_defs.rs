@@ -7,6 +7,8 @@
  * of this source tree.
  */
 
+use std::time::Instant;
+
 use starlark::environment::GlobalsBuilder;
 use starlark::environment::LibraryExtension;
 use starlark::eval::Evaluator;
@@ -60,10 +62,13 @@ pub(crate) fn register_path(builder: &mut GlobalsBuilder) {
         exclude: UnpackListOrTuple<String>,
         eval: &mut Evaluator<'v, '_>,
     ) -> anyhow::Result<ValueOfUnchecked<'v, ListOf<'v, String>>> {
+        let start = Instant::now();
         let extra = ModuleInternals::from_context(eval, "glob")?;
         let spec = GlobSpec::new(&include.items, &exclude.items)?;
         let res = extra.resolve_glob(&spec).map(|path| path.as_str());
-        Ok(ValueOfUnchecked::new(eval.heap().alloc(AllocList(res))))
+        let res = eval.heap().alloc(AllocList(res));
+        extra.record_glob(start.elapsed());
+        Ok(ValueOfUnchecked::new(res))
     }
 
     /// `package_name()` can only be called in buildfiles (e.g. BUCK files) or PACKAGE files, and returns the name of the package.
@@ -14,6 +14,7 @@ use std::sync::Arc;
 use allocative::Allocative;
 use buck2_common::package_listing::listing::PackageListing;
 use buck2_core::build_file_path::BuildFilePath;
+use buck2_interpreter::extra::host_toolchains::HostToolchains;
 use buck2_interpreter::extra::xcode::XcodeVersionInfo;
 use buck2_interpreter::extra::InterpreterHostArchitecture;
 use buck2_interpreter::extra::InterpreterHostPlatform;
@@ -21,6 +22,8 @@ use buck2_interpreter::file_loader::LoadedModules;
 use buck2_interpreter::package_imports::ImplicitImport;
 use buck2_interpreter::paths::module::StarlarkModulePath;
 use buck2_interpreter::prelude_path::PreludePath;
+use buck2_node::metadata::map::MetadataMap;
+use buck2_node::metadata::value::MetadataValue;
 use buck2_node::super_package::SuperPackage;
 use dupe::Dupe;
 use starlark::environment::Globals;
@@ -98,6 +101,7 @@ impl BuildInterpreterConfiguror {
         host_platform: InterpreterHostPlatform,
         host_architecture: InterpreterHostArchitecture,
         host_xcode_version: Option<XcodeVersionInfo>,
+        host_toolchains: HostToolchains,
         record_target_call_stack: bool,
         skip_targets_with_duplicate_names: bool,
         configure_build_file_globals: fn(&mut GlobalsBuilder),
@@ -108,7 +112,12 @@ impl BuildInterpreterConfiguror {
     ) -> anyhow::Result<Arc<Self>> {
         Ok(Arc::new(Self {
             prelude_import,
-            host_info: HostInfo::new(host_platform, host_architecture, host_xcode_version),
+            host_info: HostInfo::new(
+                host_platform,
+                host_architecture,
+                host_xcode_version,
+                host_toolchains,
+            ),
             record_target_call_stack,
             skip_targets_with_duplicate_names,
             configure_build_file_globals: ConfigureGlobalsFn(configure_build_file_globals),
@@ -203,6 +212,14 @@ impl BuildInterpreterConfiguror {
         );
 
         let imports = loaded_modules.imports().cloned().collect();
+        let package_values = MetadataMap::new(
+            super_package
+                .package_values()
+                .package_values_json()?
+                .into_iter()
+                .map(|(k, v)| (k, MetadataValue::new(v)))
+                .collect(),
+        );
 
         Ok(ModuleInternals::new(
             attr_coercer,
@@ -213,6 +230,7 @@ impl BuildInterpreterConfiguror {
             skip_targets_with_duplicate_names,
             package_listing,
             super_package,
+            package_values,
         ))
     }
 
@@ -17,6 +17,7 @@ use buck2_common::dice::file_ops::DiceFileOps;
 use buck2_common::file_ops::FileOps;
 use buck2_common::legacy_configs::dice::HasLegacyConfigs;
 use buck2_common::legacy_configs::dice::LegacyBuckConfigOnDice;
+use buck2_common::legacy_configs::view::LegacyBuckConfigView;
 use buck2_common::package_boundary::HasPackageBoundaryExceptions;
 use buck2_common::package_listing::dice::DicePackageListingResolver;
 use buck2_common::package_listing::listing::PackageListing;
@@ -24,10 +25,17 @@ use buck2_core::build_file_path::BuildFilePath;
 use buck2_core::cells::build_file_cell::BuildFileCell;
 use buck2_core::cells::name::CellName;
 use buck2_core::package::PackageLabel;
+use buck2_core::soft_error;
 use buck2_error::Context;
 use buck2_events::dispatch::span;
 use buck2_events::dispatch::span_async;
 use buck2_futures::cancellation::CancellationContext;
+use buck2_interpreter::bzl_cache::module_digest;
+use buck2_interpreter::bzl_cache::BuckconfigReads;
+use buck2_interpreter::bzl_cache::BzlCache;
+use buck2_interpreter::bzl_cache::BzlCacheEntry;
+use buck2_interpreter::bzl_cache::HasBzlCache;
+use buck2_interpreter::bzl_cache::RecordingBuckConfigView;
 use buck2_interpreter::dice::starlark_provider::with_starlark_eval_provider;
 use buck2_interpreter::error::BuckStarlarkError;
 use buck2_interpreter::file_loader::LoadedModule;
@@ -39,6 +47,7 @@ use buck2_interpreter::paths::package::PackageFilePath;
 use buck2_interpreter::paths::path::StarlarkPath;
 use buck2_interpreter::starlark_profiler::StarlarkProfilerOrInstrumentation;
 use buck2_node::nodes::eval_result::EvaluationResult;
+use buck2_node::nodes::eval_result::EvaluationResultWithStats;
 use buck2_node::super_package::SuperPackage;
 use derive_more::Display;
 use dice::DiceComputations;
@@ -68,6 +77,10 @@ enum DiceCalculationDelegateError {
     EvalModuleError(String),
     #[error("Error checking starlark stack size")]
     CheckStarlarkStackSizeError,
+    #[error(
+        "The bzl cache has `{0}` with other values than its evaluation. The entry is replaced."
+    )]
+    BzlCacheMismatch(String),
 }
 
 #[async_trait]
@@ -204,13 +217,12 @@ impl<'c, 'd: 'c> DiceCalculationDelegate<'c, 'd> {
             .await
     }
 
-    async fn parse_file(&self, starlark_path: StarlarkPath<'_>) -> anyhow::Result<ParseResult> {
-        let content = <dyn FileOps>::read_file(
+    async fn read_file(&self, starlark_path: StarlarkPath<'_>) -> anyhow::Result<String> {
+        <dyn FileOps>::read_file(
             &DiceFileOps(self.ctx),
             starlark_path.path().as_ref().as_ref(),
         )
-        .await?;
-        self.configs.parse(starlark_path, content)
+        .await
     }
 
     async fn eval_deps(
@@ -240,7 +252,16 @@ impl<'c, 'd: 'c> DiceCalculationDelegate<'c, 'd> {
         &'a self,
         starlark_file: StarlarkPath<'_>,
     ) -> anyhow::Result<(AstModule, ModuleDeps)> {
-        let ParseData(ast, imports) = self.parse_file(starlark_file).await??;
+        let content = self.read_file(starlark_file).await?;
+        self.prepare_eval_of_content(starlark_file, content).await
+    }
+
+    async fn prepare_eval_of_content(
+        &self,
+        starlark_file: StarlarkPath<'_>,
+        content: String,
+    ) -> anyhow::Result<(AstModule, ModuleDeps)> {
+        let ParseData(ast, imports) = self.configs.parse(starlark_file, content)??;
         let fut = self.eval_deps(&imports);
         let deps = LoadCycleDescriptor::guard_this(self.ctx, fut).await???;
         Ok((ast, deps))
@@ -266,38 +287,139 @@ impl<'c, 'd: 'c> DiceCalculationDelegate<'c, 'd> {
         &self,
         starlark_file: StarlarkModulePath<'_>,
     ) -> anyhow::Result<LoadedModule> {
-        let (ast, deps) = self.prepare_eval(starlark_file.into()).await?;
+        let content = self.read_file(starlark_file.into()).await?;
+        let cache = match (starlark_file, self.ctx.global_data().get_bzl_cache()) {
+            (StarlarkModulePath::LoadFile(_), Some(cache)) => {
+                let key = BzlCache::key(
+                    &starlark_file.to_string(),
+                    &content,
+                    self.configs.bzl_globals_fingerprint()?,
+                );
+                Some((cache, key))
+            }
+            // Only `.bzl` files are cached.
+            _ => None,
+        };
+        let (ast, deps) = self
+            .prepare_eval_of_content(starlark_file.into(), content)
+            .await?;
         let loaded_modules = deps.get_loaded_modules();
         let buckconfig = self.get_legacy_buck_config_for_starlark().await?;
         let root_buckconfig = self.ctx.get_legacy_root_config_on_dice().await?;
 
-        with_starlark_eval_provider(
-            self.ctx,
-            &mut StarlarkProfilerOrInstrumentation::disabled(),
-            format!("load:{}", &starlark_file),
-            move |provider, _| {
-                let evaluation = self
-                    .configs
-                    .eval_module(
-                        starlark_file,
-                        &buckconfig,
-                        &root_buckconfig,
-                        ast,
-                        loaded_modules.clone(),
-                        provider,
-                    )
-                    .with_context(|| {
-                        DiceCalculationDelegateError::EvalModuleError(starlark_file.to_string())
-                    })?;
-
-                Ok(LoadedModule::new(
-                    OwnedStarlarkModulePath::new(starlark_file),
-                    loaded_modules,
-                    evaluation,
-                ))
+        let cached = match &cache {
+            Some((cache, key)) => match cache.get(key) {
+                Some(entry)
+                    if entry.is_valid(&loaded_modules, &buckconfig, &root_buckconfig)? =>
+                {
+                    Some(entry)
+                }
+                _ => None,
             },
-        )
-        .await
+            None => None,
+        };
+        if let (Some((cache, key)), Some(entry), StarlarkModulePath::LoadFile(import)) =
+            (&cache, &cached, starlark_file)
+        {
+            if !cache.should_verify() {
+                let digest = module_digest(key, &loaded_modules, entry.configs());
+                match (
+                    digest,
+                    self.configs
+                        .restore_cached_module(import, &loaded_modules, &entry.module),
+                ) {
+                    (Some(digest), Ok(env)) => {
+                        return Ok(LoadedModule::with_cache_digest(
+                            OwnedStarlarkModulePath::new(starlark_file),
+                            loaded_modules,
+                            env,
+                            digest,
+                        ));
+                    }
+                    (_, Err(e)) => {
+                        tracing::warn!(
+                            "Error restoring `{}` from the bzl cache: {:#}",
+                            starlark_file,
+                            e
+                        );
+                    }
+                    (None, Ok(_)) => {}
+                }
+            }
+        }
+
+        let config_reads = BuckconfigReads::default();
+        let record_configs = cache.is_some();
+        let evaluation = {
+            let loaded_modules = loaded_modules.clone();
+            let config_reads = &config_reads;
+            with_starlark_eval_provider(
+                self.ctx,
+                &mut StarlarkProfilerOrInstrumentation::disabled(),
+                format!("load:{}", &starlark_file),
+                move |provider, _| {
+                    let recording_buckconfig =
+                        RecordingBuckConfigView::new(false, &buckconfig, config_reads);
+                    let recording_root_buckconfig =
+                        RecordingBuckConfigView::new(true, &root_buckconfig, config_reads);
+                    let (buckconfig, root_buckconfig): (
+                        &dyn LegacyBuckConfigView,
+                        &dyn LegacyBuckConfigView,
+                    ) = if record_configs {
+                        (&recording_buckconfig, &recording_root_buckconfig)
+                    } else {
+                        (&buckconfig, &root_buckconfig)
+                    };
+                    self.configs
+                        .eval_module(
+                            starlark_file,
+                            buckconfig,
+                            root_buckconfig,
+                            ast,
+                            loaded_modules,
+                            provider,
+                        )
+                        .with_context(|| {
+                            DiceCalculationDelegateError::EvalModuleError(starlark_file.to_string())
+                        })
+                },
+            )
+            .await?
+        };
+
+        let path = OwnedStarlarkModulePath::new(starlark_file);
+        let (cache, key) = match &cache {
+            Some(x) => x,
+            None => return Ok(LoadedModule::new(path, loaded_modules, evaluation)),
+        };
+        let configs = config_reads.into_reads();
+        let digest = module_digest(key, &loaded_modules, &configs);
+        let entry = self
+            .configs
+            .cache_module(&evaluation, &loaded_modules)
+            .and_then(|module| BzlCacheEntry::new(&loaded_modules, configs, module));
+        match (&entry, &cached) {
+            (Some(entry), Some(cached)) if entry.module == cached.module => {}
+            (entry, Some(_)) => {
+                let _ignore = soft_error!(
+                    "bzl_cache_mismatch",
+                    DiceCalculationDelegateError::BzlCacheMismatch(starlark_file.to_string())
+                        .into()
+                );
+                match entry {
+                    Some(entry) => cache.put(key, entry),
+                    None => cache.remove(key),
+                }
+            }
+            (Some(entry), None) => cache.put(key, entry),
+            (None, None) => {}
+        }
+        Ok(match digest {
+            Some(digest) => {
+                LoadedModule::with_cache_digest(path, loaded_modules, evaluation, digest)
+            }
+            None => LoadedModule::new(path, loaded_modules, evaluation),
+        })
     }
 
     /// Eval parent `PACKAGE` file for given `PACKAGE` file.
@@ -543,6 +665,17 @@ impl<'c, 'd: 'c> DiceCalculationDelegate<'c, 'd> {
         package: PackageLabel,
         profiler_instrumentation: &mut StarlarkProfilerOrInstrumentation<'_>,
     ) -> buck2_error::Result<Arc<EvaluationResult>> {
+        self.eval_build_file_with_stats(package, profiler_instrumentation)
+            .await
+            .map(|rs| Arc::new(rs.result))
+    }
+
+    /// Like `eval_build_file`, but also returns statistics about the evaluation.
+    pub async fn eval_build_file_with_stats(
+        &self,
+        package: PackageLabel,
+        profiler_instrumentation: &mut StarlarkProfilerOrInstrumentation<'_>,
+    ) -> buck2_error::Result<EvaluationResultWithStats> {
         self.check_starlark_stack_size().await?;
 
         let listing = self.resolve_package_listing(package.dupe()).await?;
@@ -595,10 +728,9 @@ impl<'c, 'd: 'c> DiceCalculationDelegate<'c, 'd> {
                         .as_ref()
                         .map(|rs| rs.starlark_peak_allocated_bytes)
                         .unwrap_or(0);
-                    let result = result_with_stats.map(|rs| rs.result);
 
                     (
-                        result,
+                        result_with_stats,
                         buck2_data::LoadBuildFileEnd {
                             module_id,
                             cell: cell_str,
@@ -610,7 +742,6 @@ impl<'c, 'd: 'c> DiceCalculationDelegate<'c, 'd> {
             },
         )
         .await
-        .map(Arc::new)
         .map_err(buck2_error::Error::from)
     }
 }
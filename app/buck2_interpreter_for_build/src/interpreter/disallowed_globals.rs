@@ -0,0 +1,312 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Globals a cell doesn't allow its files to use, e.g. with
+//!
+//! ```ini
+//! [buildfile]
+//! disallowed_globals = soft_error, glob
+//! ```
+//!
+//! in the buckconfig of the cell, or the only ones it allows them to use, e.g. with
+//!
+//! ```ini
+//! [buildfile]
+//! allowed_globals = native, rule, attrs, glob
+//! ```
+//!
+//! They apply to the globals registered by buck2 and to the members of `native`, in the `BUCK`,
+//! `PACKAGE`, `.bzl` and `.bxl` files of the cell. The builtins of the Starlark language (e.g.
+//! `len` or `struct`) are always allowed.
+//!
+//! A file using a disallowed global fails before it is evaluated. Scopes are resolved like the
+//! compiler does, so a local variable with the name of a global is not a use of it. The uses
+//! which can't be found statically (e.g. `getattr(native, "glob")`) fail when called instead:
+//! the module shadows these globals with private variables, which the files loading it can't
+//! see.
+
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use allocative::Allocative;
+use buck2_common::legacy_configs::view::LegacyBuckConfigView;
+use buck2_core::cells::name::CellName;
+use starlark::analysis::global_uses;
+use starlark::any::ProvidesStaticType;
+use starlark::codemap::FileSpan;
+use starlark::environment::Globals;
+use starlark::environment::Module;
+use starlark::eval::Arguments;
+use starlark::eval::Evaluator;
+use starlark::starlark_simple_value;
+use starlark::syntax::AstModule;
+use starlark::values::starlark_value;
+use starlark::values::structs::AllocStruct;
+use starlark::values::structs::StructRef;
+use starlark::values::NoSerialize;
+use starlark::values::StarlarkValue;
+use starlark::values::Value;
+
+use crate::interpreter::build_defs::starlark_library_extensions_for_buck2;
+
+const SECTION: &str = "buildfile";
+const DISALLOWED_KEY: &str = "disallowed_globals";
+const ALLOWED_KEY: &str = "allowed_globals";
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(user)]
+enum DisallowedGlobalsError {
+    #[error(
+        "`{name}` is not allowed in the files of cell `{cell}`, see `{}.{}` in its buckconfig",
+        SECTION,
+        .key
+    )]
+    Disallowed {
+        name: String,
+        cell: CellName,
+        key: &'static str,
+    },
+    #[error(
+        "{location}: `{name}` is not allowed in the files of cell `{cell}`, see `{}.{}` in its buckconfig",
+        SECTION,
+        .key
+    )]
+    Used {
+        name: String,
+        cell: CellName,
+        key: &'static str,
+        location: FileSpan,
+    },
+    #[error(
+        "Only one of `{}.{}` and `{}.{}` may be set in the buckconfig of cell `{}`",
+        SECTION,
+        DISALLOWED_KEY,
+        SECTION,
+        ALLOWED_KEY,
+        .0
+    )]
+    BothSet(CellName),
+}
+
+#[derive(Debug, Clone, Allocative)]
+enum Restriction {
+    /// These globals are disallowed.
+    Disallowed(Vec<String>),
+    /// Only these globals (and the Starlark builtins) are allowed.
+    Allowed(HashSet<String>),
+}
+
+/// The globals disallowed in the files of a cell.
+#[derive(Debug, Clone, Allocative)]
+pub(crate) struct DisallowedGlobals {
+    cell: CellName,
+    restriction: Restriction,
+}
+
+/// The builtins of the Starlark language, which an allowlist doesn't need to list.
+fn starlark_builtins() -> &'static HashSet<String> {
+    static BUILTINS: OnceLock<HashSet<String>> = OnceLock::new();
+    BUILTINS.get_or_init(|| {
+        Globals::extended_by(starlark_library_extensions_for_buck2())
+            .names()
+            .map(|name| name.as_str().to_owned())
+            .collect()
+    })
+}
+
+impl DisallowedGlobals {
+    pub(crate) fn from_config(
+        cell: CellName,
+        config: &dyn LegacyBuckConfigView,
+    ) -> anyhow::Result<Option<DisallowedGlobals>> {
+        fn names(value: &str) -> impl Iterator<Item = String> + '_ {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::to_owned)
+        }
+
+        let restriction = match (
+            config.get(SECTION, DISALLOWED_KEY)?,
+            config.get(SECTION, ALLOWED_KEY)?,
+        ) {
+            (Some(_), Some(_)) => return Err(DisallowedGlobalsError::BothSet(cell).into()),
+            (Some(disallowed), None) => {
+                let names: Vec<String> = names(&disallowed).collect();
+                if names.is_empty() {
+                    return Ok(None);
+                }
+                Restriction::Disallowed(names)
+            }
+            // An empty allowlist still allows the Starlark builtins.
+            (None, Some(allowed)) => Restriction::Allowed(names(&allowed).collect()),
+            (None, None) => return Ok(None),
+        };
+        Ok(Some(DisallowedGlobals { cell, restriction }))
+    }
+
+    fn key(&self) -> &'static str {
+        match self.restriction {
+            Restriction::Disallowed(_) => DISALLOWED_KEY,
+            Restriction::Allowed(_) => ALLOWED_KEY,
+        }
+    }
+
+    /// The disallowed globals and members of `native` (as `native.<name>`) which a module with
+    /// `globals` could use.
+    fn disallowed_names(&self, globals: &Globals) -> HashSet<String> {
+        match &self.restriction {
+            Restriction::Disallowed(names) => names
+                .iter()
+                .flat_map(|name| [name.clone(), format!("native.{}", name)])
+                .collect(),
+            Restriction::Allowed(_) => {
+                let mut names: HashSet<String> = globals
+                    .names()
+                    .map(|name| name.as_str())
+                    .filter(|name| self.is_disallowed(name))
+                    .map(str::to_owned)
+                    .collect();
+                if let Some((_, native)) = globals.iter().find(|(name, _)| *name == "native") {
+                    if let Some(native) = StructRef::from_value(native.to_value()) {
+                        names.extend(
+                            native
+                                .iter()
+                                .map(|(name, _)| name.as_str())
+                                .filter(|name| self.is_disallowed(name))
+                                .map(|name| format!("native.{}", name)),
+                        );
+                    }
+                }
+                names
+            }
+        }
+    }
+
+    /// Fail if the module `ast` uses one of the disallowed globals or members of `native`.
+    pub(crate) fn check(&self, ast: &AstModule, globals: &Globals) -> anyhow::Result<()> {
+        match global_uses(ast, &self.disallowed_names(globals))
+            .into_iter()
+            .next()
+        {
+            None => Ok(()),
+            Some((location, name)) => Err(DisallowedGlobalsError::Used {
+                name,
+                cell: self.cell,
+                key: self.key(),
+                location,
+            }
+            .into()),
+        }
+    }
+
+    /// Shadow the disallowed globals and members of `native` visible in `env`, a module being
+    /// created, by private values which fail when called with an error explaining why.
+    pub(crate) fn apply(&self, env: &Module, globals: &Globals) -> anyhow::Result<()> {
+        let names: Vec<String> = match &self.restriction {
+            Restriction::Disallowed(names) => names
+                .iter()
+                .filter(|name| {
+                    globals.names().any(|n| n.as_str() == name.as_str()) || env.get(name).is_some()
+                })
+                .cloned()
+                .collect(),
+            Restriction::Allowed(_) => globals
+                .names()
+                .map(|name| name.as_str())
+                .filter(|name| self.is_disallowed(name))
+                .map(str::to_owned)
+                .collect(),
+        };
+        for name in &names {
+            env.set_private(
+                env.frozen_heap().alloc_str(name),
+                self.alloc_disallowed(env, name),
+            );
+        }
+
+        if let Some(native) = env.get("native") {
+            if let Some(native) = StructRef::from_value(native) {
+                if native
+                    .iter()
+                    .any(|(name, _)| self.is_disallowed(name.as_str()))
+                {
+                    let members: Vec<(String, Value)> = native
+                        .iter()
+                        .map(|(name, value)| {
+                            let name = name.as_str();
+                            if self.is_disallowed(name) {
+                                (name.to_owned(), self.alloc_disallowed(env, name))
+                            } else {
+                                (name.to_owned(), value)
+                            }
+                        })
+                        .collect();
+                    env.set_private(
+                        env.frozen_heap().alloc_str("native"),
+                        env.heap().alloc(AllocStruct(members)),
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn is_disallowed(&self, name: &str) -> bool {
+        match &self.restriction {
+            Restriction::Disallowed(names) => names.iter().any(|n| n == name),
+            Restriction::Allowed(names) => {
+                !names.contains(name) && !starlark_builtins().contains(name)
+            }
+        }
+    }
+
+    fn alloc_disallowed<'v>(&self, env: &'v Module, name: &str) -> Value<'v> {
+        env.heap().alloc(DisallowedGlobal {
+            name: name.to_owned(),
+            cell: self.cell,
+            key: self.key(),
+        })
+    }
+}
+
+/// Replaces a global in the files of a cell which disallows it.
+#[derive(
+    Debug,
+    derive_more::Display,
+    ProvidesStaticType,
+    NoSerialize,
+    Allocative
+)]
+#[display(fmt = "<disallowed {}>", name)]
+struct DisallowedGlobal {
+    name: String,
+    cell: CellName,
+    key: &'static str,
+}
+
+starlark_simple_value!(DisallowedGlobal);
+
+#[starlark_value(type = "disallowed_global")]
+impl<'v> StarlarkValue<'v> for DisallowedGlobal {
+    fn invoke(
+        &self,
+        _me: Value<'v>,
+        _args: &Arguments<'v, '_>,
+        _eval: &mut Evaluator<'v, '_>,
+    ) -> starlark::Result<Value<'v>> {
+        Err(anyhow::Error::from(DisallowedGlobalsError::Disallowed {
+            name: self.name.clone(),
+            cell: self.cell,
+            key: self.key,
+        })
+        .into())
+    }
+}
@@ -0,0 +1,43 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use buck2_interpreter::cells::visible_cells;
+use starlark::environment::GlobalsBuilder;
+use starlark::eval::Evaluator;
+use starlark::starlark_module;
+use starlark::values::Value;
+
+use crate::interpreter::build_context::BuildContext;
+
+#[starlark_module]
+pub fn register_cells(globals: &mut GlobalsBuilder) {
+    /// The cells visible from the cell of the `BUCK` file that started evaluation of this code,
+    /// as a read-only dict from cell alias to a struct with the `name` of the cell, its `root`
+    /// relative to the project root, and whether it is an `external` cell.
+    ///
+    /// For example, with `root = .` and `prelude = prelude` in `[repositories]`:
+    ///
+    /// ```python
+    /// cells()["prelude"].name == "prelude"
+    /// cells()["prelude"].root == "prelude"
+    /// cells()["prelude"].external == False
+    /// ```
+    ///
+    /// This is useful for macros that produce path mappings (e.g. for IDEs) without
+    /// hardcoding the layout of the cells.
+    #[starlark(speculative_exec_safe)]
+    fn cells<'v>(eval: &mut Evaluator<'v, '_>) -> anyhow::Result<Value<'v>> {
+        let cell_info = BuildContext::from_context(eval)?.cell_info();
+        visible_cells(
+            eval.heap(),
+            cell_info.cell_resolver(),
+            cell_info.name().name(),
+        )
+    }
+}
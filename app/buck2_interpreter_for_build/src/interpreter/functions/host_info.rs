@@ -8,6 +8,7 @@
  */
 
 use allocative::Allocative;
+use buck2_interpreter::extra::host_toolchains::HostToolchains;
 use buck2_interpreter::extra::xcode::XcodeVersionInfo;
 use buck2_interpreter::extra::InterpreterHostArchitecture;
 use buck2_interpreter::extra::InterpreterHostPlatform;
@@ -29,6 +30,7 @@ fn new_host_info(
     host_platform: InterpreterHostPlatform,
     host_architecture: InterpreterHostArchitecture,
     xcode_info: Option<&XcodeVersionInfo>,
+    toolchains: &HostToolchains,
 ) -> OwnedFrozenValue {
     let heap = FrozenHeap::new();
 
@@ -88,6 +90,24 @@ fn new_host_info(
         )
     };
 
+    let toolchains: Vec<(&str, FrozenValue)> = toolchains
+        .iter()
+        .map(|(name, info)| match info {
+            Some(info) => (
+                name,
+                new_struct(
+                    &heap,
+                    &[
+                        ("path", heap.alloc(info.path.as_str())),
+                        ("fingerprint", heap.alloc(info.fingerprint.as_str())),
+                    ],
+                ),
+            ),
+            None => (name, FrozenValue::new_none()),
+        })
+        .collect();
+    let toolchains = heap.alloc(AllocStruct(toolchains));
+
     let info = new_struct(
         &heap,
         &[
@@ -98,6 +118,7 @@ fn new_host_info(
             // is quick, cheap and Buck v1 compatible.
             ("buck2", FrozenValue::new_bool(true)),
             ("xcode", xcode),
+            ("toolchains", toolchains),
         ],
     );
 
@@ -132,8 +153,16 @@ pub fn register_host_info(builder: &mut GlobalsBuilder) {
     ///         is_x86_64=True|False,
     ///         is_unknown=True|False,
     ///     ),
+    ///     toolchains=struct(
+    ///         <tool>=struct(path=str, fingerprint=str)|None,
+    ///     ),
     /// )
     /// ```
+    ///
+    /// `toolchains` has a field for each tool listed in `buck2.host_toolchains`, e.g. `cc`, which
+    /// is `None` if the tool is not found. Its `fingerprint` changes when the tool is replaced,
+    /// so rules using host tools can add it to their actions (e.g. in an environment variable)
+    /// for them to run again when the host toolchain changes.
     #[starlark(speculative_exec_safe)]
     fn host_info<'v>(
         eval: &mut Evaluator<'v, '_>,
@@ -151,10 +180,11 @@ pub fn register_host_info(builder: &mut GlobalsBuilder) {
 #[derive(Derivative, Clone, Debug, Allocative)]
 #[derivative(PartialEq)]
 pub struct HostInfo {
-    // These first three fields are for equality only, otherwise not used
+    // These first four fields are for equality only, otherwise not used
     platform: InterpreterHostPlatform,
     arch: InterpreterHostArchitecture,
     xcode: Option<XcodeVersionInfo>,
+    toolchains: HostToolchains,
     // The actual value which we ignore for equality, which is OK because of above
     #[derivative(PartialEq = "ignore")]
     value: OwnedFrozenValue,
@@ -165,12 +195,14 @@ impl HostInfo {
         platform: InterpreterHostPlatform,
         arch: InterpreterHostArchitecture,
         xcode: Option<XcodeVersionInfo>,
+        toolchains: HostToolchains,
     ) -> Self {
-        let value = new_host_info(platform, arch, xcode.as_ref());
+        let value = new_host_info(platform, arch, xcode.as_ref(), &toolchains);
         Self {
             platform,
             arch,
             xcode,
+            toolchains,
             value,
         }
     }
@@ -7,12 +7,15 @@
  * of this source tree.
  */
 
+pub mod cells;
 pub(crate) mod dedupe;
 pub mod host_info;
 pub mod load_symbols;
 pub mod read_config;
+pub(crate) mod paths;
 pub(crate) mod regex;
 pub mod sha256;
 pub mod soft_error;
 pub mod starlark;
+pub(crate) mod visibility_group;
 pub mod warning;
@@ -0,0 +1,355 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! The `paths` module: helpers for path strings, which understand both `/` and `\` as separators,
+//! and drive letters (`C:/`) and UNC prefixes (`//server`) as roots, regardless of the host.
+//! Paths returned by `normalize` and `relativize` always use `/`.
+
+use starlark::environment::GlobalsBuilder;
+use starlark::starlark_module;
+use starlark::values::tuple::UnpackTuple;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(user)]
+enum PathsError {
+    #[error("Cannot relativize `{0}` against `{1}`: they have different roots")]
+    DifferentRoots(String, String),
+    #[error("Cannot relativize `{0}` against `{1}`: `{1}` goes up past `{0}`")]
+    StartOutsidePath(String, String),
+    #[error("Extension `{0}` must be empty or start with `.`")]
+    InvalidExtension(String),
+}
+
+fn is_separator(c: char) -> bool {
+    c == '/' || c == '\\'
+}
+
+/// Split a path into its root, with `/` as separator, and the rest of the path. The root is
+/// empty for relative paths, `/`, `//` (UNC), `C:/`, or `C:` (relative to the current
+/// directory of drive `C:`).
+fn split_root(path: &str) -> (String, &str) {
+    let bytes = path.as_bytes();
+    if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+        let drive = &path[..2];
+        return match path[2..].strip_prefix(is_separator) {
+            Some(rest) => (format!("{}/", drive), rest),
+            None => (drive.to_owned(), &path[2..]),
+        };
+    }
+    match path.strip_prefix(is_separator) {
+        Some(rest) => match rest.strip_prefix(is_separator) {
+            Some(unc) if !unc.starts_with(is_separator) => ("//".to_owned(), unc),
+            _ => ("/".to_owned(), rest.trim_start_matches(is_separator)),
+        },
+        None => (String::new(), path),
+    }
+}
+
+fn is_absolute_path(path: &str) -> bool {
+    split_root(path).0.ends_with('/')
+}
+
+/// The root of `path` and its components, without `.` and with `..` resolved where possible.
+fn components(path: &str) -> (String, Vec<&str>) {
+    let (root, rest) = split_root(path);
+    let mut parts: Vec<&str> = Vec::new();
+    for part in rest.split(is_separator) {
+        match part {
+            "" | "." => {}
+            ".." => match parts.last() {
+                Some(last) if *last != ".." => {
+                    parts.pop();
+                }
+                // `..` of the root is the root.
+                _ if root.ends_with('/') => {}
+                _ => parts.push(".."),
+            },
+            _ => parts.push(part),
+        }
+    }
+    (root, parts)
+}
+
+fn normalize_path(path: &str) -> String {
+    let (root, parts) = components(path);
+    if root.is_empty() && parts.is_empty() {
+        ".".to_owned()
+    } else {
+        root + &parts.join("/")
+    }
+}
+
+fn join_paths<'a>(parts: impl IntoIterator<Item = &'a str>) -> String {
+    let mut joined = String::new();
+    for part in parts {
+        if is_absolute_path(part) {
+            joined = part.to_owned();
+        } else {
+            if !joined.is_empty() && !joined.ends_with(is_separator) {
+                joined.push('/');
+            }
+            joined.push_str(part);
+        }
+    }
+    joined
+}
+
+fn relativize_path(path: &str, start: &str) -> anyhow::Result<String> {
+    let (path_root, path_parts) = components(path);
+    let (start_root, start_parts) = components(start);
+    if !path_root.eq_ignore_ascii_case(&start_root) {
+        return Err(PathsError::DifferentRoots(path.to_owned(), start.to_owned()).into());
+    }
+    let common = path_parts
+        .iter()
+        .zip(&start_parts)
+        .take_while(|(p, s)| p == s)
+        .count();
+    if start_parts[common..].contains(&"..") {
+        return Err(PathsError::StartOutsidePath(path.to_owned(), start.to_owned()).into());
+    }
+    let parts: Vec<&str> = std::iter::repeat("..")
+        .take(start_parts.len() - common)
+        .chain(path_parts[common..].iter().copied())
+        .collect();
+    if parts.is_empty() {
+        Ok(".".to_owned())
+    } else {
+        Ok(parts.join("/"))
+    }
+}
+
+/// The length of the root of `path`, as written in `path`.
+fn root_len(path: &str) -> usize {
+    path.len() - split_root(path).1.len()
+}
+
+fn path_basename(path: &str) -> &str {
+    let rest = &path[root_len(path)..];
+    rest.rsplit(is_separator).next().unwrap_or(rest)
+}
+
+fn path_dirname(path: &str) -> &str {
+    let root_len = root_len(path);
+    let rest = &path[root_len..];
+    match rest.rfind(is_separator) {
+        Some(i) => &path[..root_len + rest[..i].trim_end_matches(is_separator).len()],
+        None => &path[..root_len],
+    }
+}
+
+/// Split the extension, including its `.`, off `path`. Leading dots of the file name, like in
+/// `.bashrc`, don't start an extension.
+fn split_path_extension(path: &str) -> (&str, &str) {
+    let name = path_basename(path);
+    let stem = name.trim_start_matches('.');
+    match stem.rfind('.') {
+        Some(i) => path.split_at(path.len() - (stem.len() - i)),
+        None => (path, ""),
+    }
+}
+
+pub fn register_paths(globals: &mut GlobalsBuilder) {
+    globals.struct_("paths", paths_members);
+}
+
+/// Helpers for path strings. They accept both `/` and `\` as separators, and drive letters
+/// (`C:/`) or UNC prefixes (`//server`) as roots of absolute paths, so that they behave the same
+/// on all hosts. Paths they build use `/` as separator.
+#[starlark_module]
+fn paths_members(globals: &mut GlobalsBuilder) {
+    /// Join path components, inserting `/` between them where needed. An absolute component
+    /// discards all the previous ones.
+    ///
+    /// ```python
+    /// paths.join("foo", "bar/", "baz") == "foo/bar/baz"
+    /// paths.join("foo", "/bar") == "/bar"
+    /// ```
+    fn join(#[starlark(args)] parts: UnpackTuple<&str>) -> anyhow::Result<String> {
+        Ok(join_paths(parts.items))
+    }
+
+    /// Normalize a path lexically: use `/` as separator, remove redundant separators and `.`
+    /// components, and resolve `..` components where possible. Symlinks are not taken into
+    /// account.
+    ///
+    /// ```python
+    /// paths.normalize("foo//./bar/../baz") == "foo/baz"
+    /// paths.normalize("..\\foo") == "../foo"
+    /// paths.normalize("/../foo") == "/foo"
+    /// paths.normalize("foo/..") == "."
+    /// ```
+    fn normalize(#[starlark(require = pos)] path: &str) -> anyhow::Result<String> {
+        Ok(normalize_path(path))
+    }
+
+    /// The path of `path` relative to `start`. Both must be absolute, or both relative (to the
+    /// same directory). Fails if they have different roots, or if `start` goes up past `path`.
+    ///
+    /// ```python
+    /// paths.relativize("foo/bar/baz", "foo") == "bar/baz"
+    /// paths.relativize("foo/bar", "foo/baz/qux") == "../../bar"
+    /// paths.relativize("C:/foo", "c:/") == "foo"
+    /// ```
+    fn relativize(
+        #[starlark(require = pos)] path: &str,
+        #[starlark(require = pos)] start: &str,
+    ) -> anyhow::Result<String> {
+        relativize_path(path, start)
+    }
+
+    /// Whether `path` is absolute: starts with a separator or a drive letter and a separator.
+    fn is_absolute(#[starlark(require = pos)] path: &str) -> anyhow::Result<bool> {
+        Ok(is_absolute_path(path))
+    }
+
+    /// The last component of `path`, empty if `path` ends with a separator.
+    ///
+    /// ```python
+    /// paths.basename("foo/bar.txt") == "bar.txt"
+    /// paths.basename("C:\\foo") == "foo"
+    /// ```
+    fn basename(#[starlark(require = pos)] path: &str) -> anyhow::Result<String> {
+        Ok(path_basename(path).to_owned())
+    }
+
+    /// `path` without its last component and the separators before it.
+    ///
+    /// ```python
+    /// paths.dirname("foo/bar.txt") == "foo"
+    /// paths.dirname("/foo") == "/"
+    /// paths.dirname("foo") == ""
+    /// ```
+    fn dirname(#[starlark(require = pos)] path: &str) -> anyhow::Result<String> {
+        Ok(path_dirname(path).to_owned())
+    }
+
+    /// Split `path` into the path without extension and the extension, including its `.`.
+    /// Leading dots of the file name don't start an extension.
+    ///
+    /// ```python
+    /// paths.split_extension("foo/bar.tar.gz") == ("foo/bar.tar", ".gz")
+    /// paths.split_extension("foo/.bashrc") == ("foo/.bashrc", "")
+    /// ```
+    fn split_extension(#[starlark(require = pos)] path: &str) -> anyhow::Result<(String, String)> {
+        let (stem, extension) = split_path_extension(path);
+        Ok((stem.to_owned(), extension.to_owned()))
+    }
+
+    /// The extension of `path`, including its `.`, or an empty string.
+    fn extension(#[starlark(require = pos)] path: &str) -> anyhow::Result<String> {
+        Ok(split_path_extension(path).1.to_owned())
+    }
+
+    /// Replace the extension of `path` with `extension`, which must be empty or start with `.`.
+    ///
+    /// ```python
+    /// paths.replace_extension("foo/bar.c", ".o") == "foo/bar.o"
+    /// paths.replace_extension("foo/bar.c", "") == "foo/bar"
+    /// paths.replace_extension("foo/bar", ".o") == "foo/bar.o"
+    /// ```
+    fn replace_extension(
+        #[starlark(require = pos)] path: &str,
+        #[starlark(require = pos)] extension: &str,
+    ) -> anyhow::Result<String> {
+        if !extension.is_empty() && !extension.starts_with('.') {
+            return Err(PathsError::InvalidExtension(extension.to_owned()).into());
+        }
+        Ok(format!("{}{}", split_path_extension(path).0, extension))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use starlark::assert::Assert;
+
+    use crate::interpreter::functions::paths::register_paths;
+
+    #[test]
+    fn test_join() {
+        let mut a = Assert::new();
+        a.globals_add(register_paths);
+        a.eq("paths.join('foo', 'bar/', 'baz')", "'foo/bar/baz'");
+        a.eq("paths.join('foo', '/bar', 'baz')", "'/bar/baz'");
+        a.eq("paths.join('foo', 'C:\\\\bar')", "'C:\\\\bar'");
+        a.eq("paths.join('foo\\\\', 'bar')", "'foo\\\\bar'");
+        a.eq("paths.join('', 'foo')", "'foo'");
+        a.eq("paths.join('foo', '')", "'foo/'");
+        a.eq("paths.join()", "''");
+    }
+
+    #[test]
+    fn test_normalize() {
+        let mut a = Assert::new();
+        a.globals_add(register_paths);
+        a.eq("paths.normalize('foo//./bar/../baz/')", "'foo/baz'");
+        a.eq("paths.normalize('../foo/../../bar')", "'../../bar'");
+        a.eq("paths.normalize('/../foo')", "'/foo'");
+        a.eq("paths.normalize('foo/..')", "'.'");
+        a.eq("paths.normalize('')", "'.'");
+        a.eq("paths.normalize('C:\\\\foo\\\\..\\\\bar')", "'C:/bar'");
+        a.eq(
+            "paths.normalize('\\\\\\\\server\\\\share')",
+            "'//server/share'",
+        );
+        a.eq("paths.normalize('///foo')", "'/foo'");
+    }
+
+    #[test]
+    fn test_relativize() {
+        let mut a = Assert::new();
+        a.globals_add(register_paths);
+        a.eq("paths.relativize('foo/bar/baz', 'foo')", "'bar/baz'");
+        a.eq("paths.relativize('foo/bar', 'foo/baz/qux')", "'../../bar'");
+        a.eq("paths.relativize('foo', 'foo/')", "'.'");
+        a.eq("paths.relativize('../foo', '..')", "'foo'");
+        a.eq("paths.relativize('C:\\\\foo', 'c:/')", "'foo'");
+        a.fail("paths.relativize('/foo', 'foo')", "different roots");
+        a.fail("paths.relativize('foo', '../bar')", "goes up past");
+    }
+
+    #[test]
+    fn test_components() {
+        let mut a = Assert::new();
+        a.globals_add(register_paths);
+        a.eq("paths.is_absolute('/foo')", "True");
+        a.eq("paths.is_absolute('C:\\\\foo')", "True");
+        a.eq("paths.is_absolute('C:foo')", "False");
+        a.eq("paths.is_absolute('foo/bar')", "False");
+        a.eq("paths.basename('foo/bar.txt')", "'bar.txt'");
+        a.eq("paths.basename('foo\\\\bar')", "'bar'");
+        a.eq("paths.basename('foo/')", "''");
+        a.eq("paths.basename('C:foo')", "'foo'");
+        a.eq("paths.dirname('foo//bar')", "'foo'");
+        a.eq("paths.dirname('/foo')", "'/'");
+        a.eq("paths.dirname('C:\\\\foo')", "'C:\\\\'");
+        a.eq("paths.dirname('foo')", "''");
+    }
+
+    #[test]
+    fn test_extensions() {
+        let mut a = Assert::new();
+        a.globals_add(register_paths);
+        a.eq(
+            "paths.split_extension('foo/bar.tar.gz')",
+            "('foo/bar.tar', '.gz')",
+        );
+        a.eq("paths.split_extension('foo.d/bar')", "('foo.d/bar', '')");
+        a.eq("paths.split_extension('.bashrc')", "('.bashrc', '')");
+        a.eq("paths.split_extension('..foo.txt')", "('..foo', '.txt')");
+        a.eq("paths.extension('foo.txt')", "'.txt'");
+        a.eq("paths.replace_extension('foo/bar.c', '.o')", "'foo/bar.o'");
+        a.eq("paths.replace_extension('foo/bar.c', '')", "'foo/bar'");
+        a.eq("paths.replace_extension('foo/bar', '.o')", "'foo/bar.o'");
+        a.fail(
+            "paths.replace_extension('foo.c', 'o')",
+            "must be empty or start with",
+        );
+    }
+}
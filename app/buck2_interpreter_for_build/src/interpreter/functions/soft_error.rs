@@ -33,7 +33,9 @@ enum SoftErrorError {
 pub fn register_soft_error(builder: &mut GlobalsBuilder) {
     /// Produce an error that will become a hard error at some point in the future, but
     /// for now is a warning which is logged to the server.
-    /// In the open source version of Buck2 this function always results in an error.
+    /// In the open source version of Buck2 this function results in an error, unless
+    /// the category (or `default`) is set to `log` or `warn` in the `[soft_errors]`
+    /// buckconfig section.
     ///
     /// Called passing a stable key (must be `snake_case` and start with `starlark_`,
     /// used for consistent reporting) and an arbitrary message (used for debugging).
@@ -0,0 +1,144 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::fmt;
+
+use allocative::Allocative;
+use buck2_node::visibility::VisibilityPattern;
+use starlark::any::ProvidesStaticType;
+use starlark::environment::GlobalsBuilder;
+use starlark::starlark_module;
+use starlark::starlark_simple_value;
+use starlark::values::list_or_tuple::UnpackListOrTuple;
+use starlark::values::starlark_value;
+use starlark::values::NoSerialize;
+use starlark::values::StarlarkValue;
+use starlark_map::small_set::SmallSet;
+
+#[derive(Debug, buck2_error::Error)]
+enum VisibilityGroupError {
+    #[error(
+        "Visibility group pattern `{0}` must be absolute (e.g. `//foo/...` or `cell//foo:bar`), as the group can be used from any package"
+    )]
+    #[buck2(user)]
+    RelativePattern(String),
+}
+
+/// A group of visibility patterns, created with `visibility_group()`. Groups can be used in
+/// `visibility` and `within_view` lists, where they stand for the patterns they contain.
+#[derive(Debug, ProvidesStaticType, NoSerialize, Allocative)]
+pub(crate) struct StarlarkVisibilityGroup {
+    /// The patterns of the group and of the groups it includes, without duplicates.
+    patterns: Vec<String>,
+}
+
+starlark_simple_value!(StarlarkVisibilityGroup);
+
+#[starlark_value(type = "visibility_group")]
+impl<'v> StarlarkValue<'v> for StarlarkVisibilityGroup {}
+
+impl fmt::Display for StarlarkVisibilityGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "visibility_group([")?;
+        for (i, pattern) in self.patterns.iter().enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "\"{}\"", pattern)?;
+        }
+        write!(f, "])")
+    }
+}
+
+impl StarlarkVisibilityGroup {
+    fn new<'a>(
+        patterns: Vec<String>,
+        includes: impl IntoIterator<Item = &'a StarlarkVisibilityGroup>,
+    ) -> anyhow::Result<Self> {
+        for pattern in &patterns {
+            if pattern != VisibilityPattern::PUBLIC
+                && (pattern.starts_with(':') || !pattern.contains("//"))
+            {
+                return Err(VisibilityGroupError::RelativePattern(pattern.clone()).into());
+            }
+        }
+        let mut all = SmallSet::new();
+        for group in includes {
+            all.extend(group.patterns.iter().cloned());
+        }
+        all.extend(patterns);
+        Ok(StarlarkVisibilityGroup {
+            patterns: all.into_iter().collect(),
+        })
+    }
+
+    pub(crate) fn patterns(&self) -> impl Iterator<Item = &str> {
+        self.patterns.iter().map(|p| p.as_str())
+    }
+}
+
+#[starlark_module]
+pub(crate) fn register_visibility_group(builder: &mut GlobalsBuilder) {
+    /// Define a group of visibility patterns, which can be used in `visibility` and `within_view`
+    /// lists in place of the patterns it contains. This allows to maintain central allowlists
+    /// in a `.bzl` file instead of repeating many patterns in every target.
+    ///
+    /// * `patterns`: absolute target patterns, e.g. `//foo/...`, or `PUBLIC`
+    /// * `includes`: other groups whose patterns are part of this group
+    ///
+    /// Groups are resolved when they are defined, so using them has no cost compared to
+    /// listing their patterns.
+    ///
+    /// ```python
+    /// # groups.bzl
+    /// CORE = visibility_group(patterns = ["//core/..."])
+    /// CORE_AND_TOOLS = visibility_group(patterns = ["//tools/..."], includes = [CORE])
+    ///
+    /// # BUCK
+    /// load("//:groups.bzl", "CORE_AND_TOOLS")
+    /// cxx_library(name = "lib", visibility = [CORE_AND_TOOLS, "//app:main"])
+    /// ```
+    fn visibility_group<'v>(
+        #[starlark(require = named, default = UnpackListOrTuple::default())]
+        patterns: UnpackListOrTuple<String>,
+        #[starlark(require = named, default = UnpackListOrTuple::default())]
+        includes: UnpackListOrTuple<&'v StarlarkVisibilityGroup>,
+    ) -> anyhow::Result<StarlarkVisibilityGroup> {
+        StarlarkVisibilityGroup::new(patterns.items, includes.items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use starlark::assert::Assert;
+
+    use crate::interpreter::functions::visibility_group::register_visibility_group;
+
+    #[test]
+    fn test_visibility_group() {
+        let mut a = Assert::new();
+        a.globals_add(register_visibility_group);
+        a.pass(
+            r#"
+core = visibility_group(patterns = ["//core/...", "cell//lib:lib"])
+tools = visibility_group(patterns = ["//tools/...", "//core/..."], includes = [core])
+assert_eq(str(tools), 'visibility_group(["//core/...", "cell//lib:lib", "//tools/..."])')
+assert_eq(str(visibility_group(includes = [tools, core])), str(tools))
+            "#,
+        );
+        a.fail(
+            r#"visibility_group(patterns = [":foo"])"#,
+            "must be absolute",
+        );
+        a.fail(
+            r#"visibility_group(patterns = ["foo/..."])"#,
+            "must be absolute",
+        );
+    }
+}
@@ -16,18 +16,22 @@ use buck2_common::dice::cells::HasCellResolver;
 use buck2_common::legacy_configs::dice::HasLegacyConfigs;
 use buck2_common::legacy_configs::view::LegacyBuckConfigsView;
 use buck2_core::cells::build_file_cell::BuildFileCell;
+use buck2_core::cells::name::CellName;
 use buck2_core::cells::CellResolver;
 use buck2_futures::cancellation::CancellationContext;
+use buck2_interpreter::bzl_cache::globals_fingerprint;
 use buck2_interpreter::dice::starlark_types::GetStarlarkTypes;
 use buck2_interpreter::file_type::StarlarkFileType;
 use dice::DiceComputations;
 use dice::Key;
 use dupe::Dupe;
+use once_cell::sync::OnceCell;
 use starlark::environment::Globals;
 
 use crate::interpreter::cell_info::InterpreterCellInfo;
 use crate::interpreter::configuror::BuildInterpreterConfiguror;
 use crate::interpreter::context::HasInterpreterContext;
+use crate::interpreter::disallowed_globals::DisallowedGlobals;
 
 /// Information shared across interpreters. Contains no cell-specific
 /// information.
@@ -37,6 +41,9 @@ pub struct GlobalInterpreterState {
 
     pub(crate) cell_configs: HashMap<BuildFileCell, InterpreterCellInfo>,
 
+    /// Globals the files of cells may not use, for the cells which restrict them.
+    pub(crate) disallowed_globals: HashMap<CellName, DisallowedGlobals>,
+
     /// The GlobalEnvironment contains all the globally available symbols
     /// (primarily starlark stdlib and Buck-provided functions) that should
     /// be available in a build file.
@@ -63,6 +70,9 @@ pub struct GlobalInterpreterState {
 
     /// Static typechecking for bzl and bxl files.
     pub unstable_typecheck: bool,
+
+    /// Fingerprint of `extension_file_global_env` for the bzl cache, computed on first use.
+    extension_file_globals_fingerprint: OnceCell<String>,
 }
 
 impl GlobalInterpreterState {
@@ -81,15 +91,20 @@ impl GlobalInterpreterState {
         let bxl_file_global_env = interpreter_configuror.bxl_file_globals();
 
         let mut cell_configs = HashMap::new();
-        for (cell_name, _config) in legacy_configs.iter() {
+        let mut disallowed_globals = HashMap::new();
+        for (cell_name, config) in legacy_configs.iter() {
             cell_configs.insert(
                 BuildFileCell::new(cell_name),
                 InterpreterCellInfo::new(BuildFileCell::new(cell_name), cell_resolver.dupe())?,
             );
+            if let Some(disallowed) = DisallowedGlobals::from_config(cell_name, config)? {
+                disallowed_globals.insert(cell_name, disallowed);
+            }
         }
         Ok(Self {
             cell_resolver,
             cell_configs,
+            disallowed_globals,
             build_file_global_env,
             package_file_global_env,
             extension_file_global_env,
@@ -97,6 +112,7 @@ impl GlobalInterpreterState {
             configuror: interpreter_configuror,
             disable_starlark_types,
             unstable_typecheck,
+            extension_file_globals_fingerprint: OnceCell::new(),
         })
     }
 
@@ -104,6 +120,12 @@ impl GlobalInterpreterState {
         &self.configuror
     }
 
+    pub(crate) fn extension_file_globals_fingerprint(&self) -> anyhow::Result<&str> {
+        Ok(self
+            .extension_file_globals_fingerprint
+            .get_or_try_init(|| globals_fingerprint(&self.extension_file_global_env))?)
+    }
+
     pub fn globals_for_file_type(&self, file_type: StarlarkFileType) -> &Globals {
         match file_type {
             StarlarkFileType::Buck => &self.build_file_global_env,
@@ -18,18 +18,22 @@ use buck2_interpreter::types::cell_root::register_cell_root;
 use buck2_interpreter::types::configured_providers_label::register_providers_label;
 use buck2_interpreter::types::regex::register_buck_regex;
 use buck2_interpreter::types::target_label::register_target_label;
+use buck2_interpreter::types::time::register_time;
 use starlark::environment::GlobalsBuilder;
 
 use crate::attrs::attrs_global::register_attrs;
 use crate::interpreter::build_defs::register_path;
+use crate::interpreter::functions::cells::register_cells;
 use crate::interpreter::functions::dedupe::register_dedupe;
 use crate::interpreter::functions::host_info::register_host_info;
 use crate::interpreter::functions::load_symbols::register_load_symbols;
+use crate::interpreter::functions::paths::register_paths;
 use crate::interpreter::functions::read_config::register_read_config;
 use crate::interpreter::functions::regex::register_regex;
 use crate::interpreter::functions::sha256::register_sha256;
 use crate::interpreter::functions::soft_error::register_soft_error;
 use crate::interpreter::functions::starlark::register_set_starlark_peak_allocated_byte_limit;
+use crate::interpreter::functions::visibility_group::register_visibility_group;
 use crate::interpreter::functions::warning::register_warning;
 use crate::interpreter::natives::register_module_natives;
 use crate::interpreter::selector::register_select;
@@ -50,11 +54,15 @@ pub fn register_universal_natives(builder: &mut GlobalsBuilder) {
     register_module_natives(builder);
     register_host_info(builder);
     register_read_config(builder);
+    register_cells(builder);
     register_read_package_value(builder);
     register_soft_error(builder);
     register_package_natives(builder);
     register_warning(builder);
+    register_visibility_group(builder);
     register_regex(builder);
+    register_paths(builder);
+    register_time(builder);
     register_buck_regex(builder);
     register_load_symbols(builder);
     register_rule_function(builder);
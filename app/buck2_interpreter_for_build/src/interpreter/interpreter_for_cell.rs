@@ -13,6 +13,7 @@
 
 use std::cell::RefCell;
 use std::sync::Arc;
+use std::time::Instant;
 
 use allocative::Allocative;
 use anyhow::Context;
@@ -26,6 +27,7 @@ use buck2_core::cells::CellAliasResolver;
 use buck2_core::soft_error;
 use buck2_event_observer::humanized::HumanizedBytes;
 use buck2_events::dispatch::get_dispatcher;
+use buck2_interpreter::bzl_cache::CachedModule;
 use buck2_interpreter::error::BuckStarlarkError;
 use buck2_interpreter::factory::StarlarkEvaluatorProvider;
 use buck2_interpreter::file_loader::InterpreterFileLoader;
@@ -52,6 +54,7 @@ use starlark::codemap::FileSpan;
 use starlark::environment::FrozenModule;
 use starlark::environment::Module;
 use starlark::syntax::AstModule;
+use starlark::values::Heap;
 use starlark::values::OwnedFrozenRef;
 
 use crate::interpreter::build_context::BuildContext;
@@ -60,6 +63,9 @@ use crate::interpreter::bzl_eval_ctx::BzlEvalCtx;
 use crate::interpreter::cell_info::InterpreterCellInfo;
 use crate::interpreter::extra_value::InterpreterExtraValue;
 use crate::interpreter::global_interpreter_state::GlobalInterpreterState;
+use crate::interpreter::load_limits::LoadLimits;
+use crate::interpreter::load_limits::CHECK_EVERY_CALLS;
+use crate::interpreter::load_limits::CHECK_EVERY_STMTS;
 use crate::interpreter::module_internals::ModuleInternals;
 use crate::interpreter::package_file_extra::FrozenPackageFileExtra;
 use crate::super_package::eval_ctx::PackageFileEvalCtx;
@@ -488,6 +494,28 @@ impl InterpreterForCell {
         let globals = self
             .global_state
             .globals_for_file_type(extra_context.file_type());
+        if let Some(disallowed) = self
+            .global_state
+            .disallowed_globals
+            .get(&self.cell_names.resolve_self())
+        {
+            disallowed.check(&ast, globals)?;
+            disallowed.apply(env, globals)?;
+        }
+        let load_limits = match import {
+            StarlarkPath::BuildFile(build_file) => {
+                LoadLimits::from_config(build_file, root_buckconfig)?
+            }
+            _ => None,
+        };
+        let check_load_limits = |calls: u64, heap: &Heap| match &load_limits {
+            Some(load_limits) => load_limits.check(calls, heap),
+            None => Ok(()),
+        };
+        let check_deadline = || match &load_limits {
+            Some(load_limits) => load_limits.check_deadline(),
+            None => Ok(()),
+        };
         let file_loader =
             InterpreterFileLoader::new(loaded_modules, Arc::new(self.load_resolver(import)));
         let cell_info = self.get_cell_config(import.build_file_cell());
@@ -506,6 +534,16 @@ impl InterpreterForCell {
         {
             let (mut eval, is_profiling_enabled_by_provider) = eval_provider.make(env)?;
             is_profiling_enabled = is_profiling_enabled_by_provider;
+            // Don't fail evaluations which are profiled, so that the profile of an evaluation
+            // exceeding its budgets can be collected.
+            if let Some(load_limits) = load_limits.as_ref().filter(|_| !is_profiling_enabled) {
+                if load_limits.has_calls_or_heap_budget() {
+                    eval.set_call_check(CHECK_EVERY_CALLS, &check_load_limits)?;
+                }
+                if load_limits.has_time_budget() {
+                    eval.set_stmt_check(CHECK_EVERY_STMTS, &check_deadline)?;
+                }
+            }
             eval.enable_static_typechecking(unstable_typecheck);
             eval.set_print_handler(&print);
             eval.set_loader(&file_loader);
@@ -568,6 +606,40 @@ impl InterpreterForCell {
         env.freeze()
     }
 
+    /// The fingerprint of the globals of `.bzl` files, for the bzl cache.
+    pub(crate) fn bzl_globals_fingerprint(&self) -> anyhow::Result<&str> {
+        self.global_state.extension_file_globals_fingerprint()
+    }
+
+    /// The form of the evaluated `.bzl` module `env` stored in the bzl cache, if it can be stored.
+    pub(crate) fn cache_module(
+        &self,
+        env: &FrozenModule,
+        loaded_modules: &LoadedModules,
+    ) -> Option<CachedModule> {
+        CachedModule::new(
+            env,
+            loaded_modules,
+            &self.global_state.extension_file_global_env,
+        )
+    }
+
+    /// The `.bzl` module `import` from the bzl cache, instead of evaluating it.
+    pub(crate) fn restore_cached_module(
+        self: &Arc<Self>,
+        import: &ImportPath,
+        loaded_modules: &LoadedModules,
+        cached: &CachedModule,
+    ) -> anyhow::Result<FrozenModule> {
+        let env = self.create_env(StarlarkPath::LoadFile(import), loaded_modules)?;
+        cached.restore(
+            &env,
+            loaded_modules,
+            &self.global_state.extension_file_global_env,
+        )?;
+        env.freeze()
+    }
+
     pub(crate) fn eval_package_file(
         self: &Arc<Self>,
         package_file_path: &PackageFilePath,
@@ -645,6 +717,7 @@ impl InterpreterForCell {
             package_boundary_exception,
             &loaded_modules,
         )?;
+        let start = Instant::now();
         let (build_ctx, is_profiling_enabled) = self.eval(
             &env,
             ast,
@@ -655,8 +728,10 @@ impl InterpreterForCell {
             eval_provider,
             unstable_typecheck,
         )?;
+        let evaluation_duration = start.elapsed();
 
         let internals = build_ctx.additional.into_build()?;
+        let (glob_count, glob_duration) = internals.glob_stats();
         let starlark_peak_allocated_bytes = env.heap().peak_allocated_bytes() as u64;
         let starlark_peak_mem_check_enabled = !is_profiling_enabled
             && root_buckconfig
@@ -692,11 +767,17 @@ impl InterpreterForCell {
             Ok(EvaluationResultWithStats {
                 result: EvaluationResult::from(internals),
                 starlark_peak_allocated_bytes,
+                glob_count,
+                glob_duration,
+                evaluation_duration,
             })
         } else {
             Ok(EvaluationResultWithStats {
                 result: EvaluationResult::from(internals),
                 starlark_peak_allocated_bytes,
+                glob_count,
+                glob_duration,
+                evaluation_duration,
             })
         }
     }
@@ -0,0 +1,231 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Budgets of the evaluation of a build file, so that a runaway macro fails the loading of its
+//! package instead of wedging the daemon. Configured in the root buckconfig, e.g.
+//!
+//! ```ini
+//! [buck2]
+//! starlark_build_file_max_calls = 100000000
+//! starlark_build_file_max_heap_bytes = 4294967296
+//! starlark_build_file_max_time_ms = 60000
+//! ```
+//!
+//! The calls and heap are checked every [`CHECK_EVERY_CALLS`] Starlark function calls, and the
+//! deadline every [`CHECK_EVERY_STMTS`] statements, so that loops which call nothing are stopped
+//! too. None are checked when profiling, so that the profile of an evaluation exceeding a budget
+//! can be collected.
+//!
+//! Only function calls are counted, including calls of built-in functions: iterations of a loop
+//! which calls nothing are not. Whether the time budget is exceeded depends on the load of the
+//! machine, so it should be set well above the time the slowest build file takes.
+
+use std::time::Duration;
+use std::time::Instant;
+
+use buck2_common::legacy_configs::view::LegacyBuckConfigView;
+use buck2_core::build_file_path::BuildFilePath;
+use buck2_event_observer::humanized::HumanizedBytes;
+use starlark::values::Heap;
+
+use crate::interpreter::interpreter_for_cell::get_starlark_warning_link;
+
+const SECTION: &str = "buck2";
+const MAX_CALLS_KEY: &str = "starlark_build_file_max_calls";
+const MAX_HEAP_BYTES_KEY: &str = "starlark_build_file_max_heap_bytes";
+const MAX_TIME_MS_KEY: &str = "starlark_build_file_max_time_ms";
+
+/// How often the budgets of calls and heap are checked.
+pub(crate) const CHECK_EVERY_CALLS: u64 = 1000;
+/// How often the deadline is checked.
+pub(crate) const CHECK_EVERY_STMTS: u64 = 1000;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(user)]
+enum LoadLimitsError {
+    #[error(
+        "Evaluation of {0} made over {1} function calls, exceeding the limit `{}.{}`. \
+        To find what makes the calls, run `buck2 profile loading --mode=time-flame -o flame {2}:` \
+        (see {3})",
+        SECTION,
+        MAX_CALLS_KEY
+    )]
+    Calls(BuildFilePath, u64, String, String),
+    #[error(
+        "Evaluation of {0} allocated {1} on the Starlark heap, exceeding the limit {2} of `{}.{}`. \
+        To find what allocates it, run `buck2 profile loading --mode=heap-summary-allocated -o heap.csv {3}:` \
+        (see {4})",
+        SECTION,
+        MAX_HEAP_BYTES_KEY
+    )]
+    Heap(
+        BuildFilePath,
+        HumanizedBytes,
+        HumanizedBytes,
+        String,
+        String,
+    ),
+    #[error(
+        "Evaluation of {0} took over {1:.3}s, exceeding the limit `{}.{}`. \
+        To find what takes the time, run `buck2 profile loading --mode=time-flame -o flame {2}:` \
+        (see {3})",
+        SECTION,
+        MAX_TIME_MS_KEY
+    )]
+    Time(BuildFilePath, f64, String, String),
+}
+
+/// The budgets of the evaluation of one build file.
+pub(crate) struct LoadLimits {
+    build_file: BuildFilePath,
+    max_calls: Option<u64>,
+    max_heap_bytes: Option<u64>,
+    max_time: Option<Duration>,
+    start: Instant,
+}
+
+impl LoadLimits {
+    /// `None` if no budget is configured.
+    pub(crate) fn from_config(
+        build_file: &BuildFilePath,
+        root_buckconfig: &dyn LegacyBuckConfigView,
+    ) -> anyhow::Result<Option<LoadLimits>> {
+        let max_calls = root_buckconfig.parse::<u64>(SECTION, MAX_CALLS_KEY)?;
+        let max_heap_bytes = root_buckconfig.parse::<u64>(SECTION, MAX_HEAP_BYTES_KEY)?;
+        let max_time = root_buckconfig
+            .parse::<u64>(SECTION, MAX_TIME_MS_KEY)?
+            .map(Duration::from_millis);
+        if max_calls.is_none() && max_heap_bytes.is_none() && max_time.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(LoadLimits {
+            build_file: build_file.clone(),
+            max_calls,
+            max_heap_bytes,
+            max_time,
+            start: Instant::now(),
+        }))
+    }
+
+    pub(crate) fn has_calls_or_heap_budget(&self) -> bool {
+        self.max_calls.is_some() || self.max_heap_bytes.is_some()
+    }
+
+    pub(crate) fn has_time_budget(&self) -> bool {
+        self.max_time.is_some()
+    }
+
+    pub(crate) fn check_deadline(&self) -> anyhow::Result<()> {
+        self.check_elapsed(self.start.elapsed())
+    }
+
+    fn check_elapsed(&self, elapsed: Duration) -> anyhow::Result<()> {
+        if let Some(max_time) = self.max_time {
+            if elapsed > max_time {
+                return Err(LoadLimitsError::Time(
+                    self.build_file.clone(),
+                    max_time.as_secs_f64(),
+                    self.build_file.package().to_string(),
+                    get_starlark_warning_link().to_owned(),
+                )
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check(&self, calls: u64, heap: &Heap) -> anyhow::Result<()> {
+        self.check_values(calls, heap.allocated_bytes() as u64)
+    }
+
+    fn check_values(&self, calls: u64, heap_bytes: u64) -> anyhow::Result<()> {
+        let package = || self.build_file.package().to_string();
+        let link = || get_starlark_warning_link().to_owned();
+        if let Some(max_calls) = self.max_calls {
+            if calls > max_calls {
+                return Err(LoadLimitsError::Calls(
+                    self.build_file.clone(),
+                    max_calls,
+                    package(),
+                    link(),
+                )
+                .into());
+            }
+        }
+        if let Some(max_heap_bytes) = self.max_heap_bytes {
+            if heap_bytes > max_heap_bytes {
+                return Err(LoadLimitsError::Heap(
+                    self.build_file.clone(),
+                    HumanizedBytes::fixed_width(heap_bytes),
+                    HumanizedBytes::fixed_width(max_heap_bytes),
+                    package(),
+                    link(),
+                )
+                .into());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use std::time::Instant;
+
+    use buck2_core::build_file_path::BuildFilePath;
+
+    use crate::interpreter::load_limits::LoadLimits;
+
+    #[test]
+    fn test_check_values() {
+        let build_file = BuildFilePath::testing_new("root//foo:BUCK");
+        let limits = LoadLimits {
+            build_file,
+            max_calls: Some(1000),
+            max_heap_bytes: Some(1 << 20),
+            max_time: None,
+            start: Instant::now(),
+        };
+        assert!(limits.check_values(1000, 1 << 20).is_ok());
+
+        let err = limits.check_values(2000, 0).unwrap_err().to_string();
+        assert!(err.contains("starlark_build_file_max_calls"), "{}", err);
+        assert!(err.contains("buck2 profile loading"), "{}", err);
+        assert!(err.contains("root//foo:"), "{}", err);
+
+        let err = limits.check_values(0, 2 << 20).unwrap_err().to_string();
+        assert!(
+            err.contains("starlark_build_file_max_heap_bytes"),
+            "{}",
+            err
+        );
+        assert!(err.contains("heap-summary-allocated"), "{}", err);
+    }
+
+    #[test]
+    fn test_check_elapsed() {
+        let build_file = BuildFilePath::testing_new("root//foo:BUCK");
+        let limits = LoadLimits {
+            build_file,
+            max_calls: None,
+            max_heap_bytes: None,
+            max_time: Some(Duration::from_secs(10)),
+            start: Instant::now(),
+        };
+        assert!(limits.check_elapsed(Duration::from_secs(10)).is_ok());
+        let err = limits
+            .check_elapsed(Duration::from_secs(11))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("starlark_build_file_max_time_ms"), "{}", err);
+        assert!(err.contains("10.000s"), "{}", err);
+        assert!(err.contains("time-flame"), "{}", err);
+    }
+}
@@ -17,6 +17,7 @@ pub mod configuror;
 pub mod context;
 pub mod cycles;
 pub mod dice_calculation_delegate;
+pub(crate) mod disallowed_globals;
 mod extra_value;
 pub mod functions;
 pub mod global_interpreter_state;
@@ -24,6 +25,7 @@ pub mod globals;
 pub mod globspec;
 pub mod interpreter_for_cell;
 pub mod interpreter_setup;
+pub(crate) mod load_limits;
 pub mod module_internals;
 pub mod natives;
 pub mod package_file_calculation;
@@ -7,12 +7,14 @@
  * of this source tree.
  */
 
+use std::cell::Cell;
 use std::cell::RefCell;
 use std::cell::RefMut;
 use std::fmt;
 use std::fmt::Debug;
 use std::mem;
 use std::sync::Arc;
+use std::time::Duration;
 
 use buck2_common::package_listing::listing::PackageListing;
 use buck2_core::build_file_path::BuildFilePath;
@@ -21,6 +23,7 @@ use buck2_core::package::package_relative_path::PackageRelativePath;
 use buck2_core::target::name::TargetNameRef;
 use buck2_events::dispatch::console_message;
 use buck2_interpreter::package_imports::ImplicitImport;
+use buck2_node::metadata::map::MetadataMap;
 use buck2_node::nodes::eval_result::EvaluationResult;
 use buck2_node::nodes::targets_map::TargetsMap;
 use buck2_node::nodes::targets_map::TargetsMapRecordError;
@@ -28,6 +31,7 @@ use buck2_node::nodes::unconfigured::TargetNode;
 use buck2_node::package::Package;
 use buck2_node::super_package::SuperPackage;
 use dupe::Dupe;
+use dupe::OptionDupedExt;
 use starlark::environment::FrozenModule;
 use starlark::values::OwnedFrozenValue;
 
@@ -87,6 +91,10 @@ pub struct ModuleInternals {
     /// The files owned by this directory. Is `None` for .bzl files.
     package_listing: PackageListing,
     pub(crate) super_package: SuperPackage,
+    /// The values of `super_package`, recorded in the targets.
+    package_values: MetadataMap,
+    /// Number of `glob` calls made by this file, and the time spent in them.
+    glob_stats: Cell<(u64, Duration)>,
 }
 
 #[derive(Debug)]
@@ -126,6 +134,7 @@ impl ModuleInternals {
         skip_targets_with_duplicate_names: bool,
         package_listing: PackageListing,
         super_package: SuperPackage,
+        package_values: MetadataMap,
     ) -> Self {
         Self {
             attr_coercion_context,
@@ -137,6 +146,8 @@ impl ModuleInternals {
             skip_targets_with_duplicate_names,
             package_listing,
             super_package,
+            package_values,
+            glob_stats: Cell::new((0, Duration::ZERO)),
         }
     }
 
@@ -184,6 +195,10 @@ impl ModuleInternals {
                             package: Arc::new(Package {
                                 buildfile_path: self.buildfile_path.dupe(),
                                 oncall,
+                                package_values: self.package_values.clone(),
+                                visibility: self.super_package.visibility().dupe(),
+                                within_view: self.super_package.within_view().dupe(),
+                                select_resolver: self.super_package.select_resolver().duped(),
                             }),
                             recorder: TargetsRecorder::new(),
                         });
@@ -226,6 +241,16 @@ impl ModuleInternals {
     ) -> impl Iterator<Item = &'a PackageRelativePath> {
         spec.resolve_glob(self.package_listing.files())
     }
+
+    pub(crate) fn record_glob(&self, duration: Duration) {
+        let (count, total) = self.glob_stats.get();
+        self.glob_stats.set((count + 1, total + duration));
+    }
+
+    /// Number of `glob` calls made so far, and the time spent in them.
+    pub(crate) fn glob_stats(&self) -> (u64, Duration) {
+        self.glob_stats.get()
+    }
 }
 
 // Records the targets declared when evaluating a build file.
@@ -49,6 +49,8 @@ use crate::super_package::package_value::StarlarkPackageValue;
 #[display(fmt = "{:?}", "self")]
 pub struct PackageFileExtra<'v> {
     pub cfg_constructor: OnceCell<Value<'v>>,
+    pub(crate) platform_resolver: OnceCell<Value<'v>>,
+    pub(crate) select_resolver: OnceCell<Value<'v>>,
     pub(crate) package_values: RefCell<SmallMap<MetadataKey, StarlarkPackageValue<'v>>>,
 }
 
@@ -56,9 +58,13 @@ unsafe impl<'v> Trace<'v> for PackageFileExtra<'v> {
     fn trace(&mut self, tracer: &Tracer<'v>) {
         let PackageFileExtra {
             cfg_constructor,
+            platform_resolver,
+            select_resolver,
             package_values,
         } = self;
         cfg_constructor.trace(tracer);
+        platform_resolver.trace(tracer);
+        select_resolver.trace(tracer);
         for (k, v) in package_values.get_mut().iter_mut() {
             fn assert_static<T: 'static>(_t: &T) {}
             assert_static(k);
@@ -77,6 +83,8 @@ unsafe impl<'v> Trace<'v> for PackageFileExtra<'v> {
 #[display(fmt = "{:?}", "self")]
 pub struct FrozenPackageFileExtra {
     pub(crate) cfg_constructor: Option<FrozenValue>,
+    pub(crate) platform_resolver: Option<FrozenValue>,
+    pub(crate) select_resolver: Option<FrozenValue>,
     pub(crate) package_values: SmallMap<MetadataKey, FrozenStarlarkPackageValue>,
 }
 
@@ -101,9 +109,13 @@ impl<'v> Freeze for PackageFileExtra<'v> {
     fn freeze(self, freezer: &Freezer) -> anyhow::Result<Self::Frozen> {
         let PackageFileExtra {
             cfg_constructor,
+            platform_resolver,
+            select_resolver,
             package_values,
         } = self;
         let cfg_constructor = cfg_constructor.into_inner().freeze(freezer)?;
+        let platform_resolver = platform_resolver.into_inner().freeze(freezer)?;
+        let select_resolver = select_resolver.into_inner().freeze(freezer)?;
         let package_values = package_values
             .into_inner()
             .into_iter_hashed()
@@ -116,6 +128,8 @@ impl<'v> Freeze for PackageFileExtra<'v> {
             .collect::<anyhow::Result<SmallMap<MetadataKey, FrozenStarlarkPackageValue>>>()?;
         Ok(FrozenPackageFileExtra {
             cfg_constructor,
+            platform_resolver,
+            select_resolver,
             package_values,
         })
     }
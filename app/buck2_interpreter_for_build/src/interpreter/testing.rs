@@ -23,6 +23,7 @@ use buck2_core::cells::name::CellName;
 use buck2_core::cells::*;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
 use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use buck2_interpreter::extra::host_toolchains::HostToolchains;
 use buck2_interpreter::extra::InterpreterHostArchitecture;
 use buck2_interpreter::extra::InterpreterHostPlatform;
 use buck2_interpreter::factory::StarlarkPassthroughProvider;
@@ -204,6 +205,7 @@ impl Tester {
                     InterpreterHostPlatform::Linux,
                     InterpreterHostArchitecture::X86_64,
                     None,
+                    HostToolchains::default(),
                     false,
                     false,
                     |_| {},
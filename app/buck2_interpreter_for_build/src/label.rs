@@ -141,4 +141,57 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn labels_can_be_derived() -> anyhow::Result<()> {
+        crate::init_late_bindings();
+        let mut tester = Tester::new()?;
+        tester.additional_globals(label_creator);
+        tester.run_starlark_bzl_test(indoc!(
+            r#"
+            def test():
+                t = target_label("root//foo/bar:baz")
+                assert_eq(target_label("root//foo/bar:qux"), t.with_name("qux"))
+                assert_eq(target_label("root//foo:baz"), t.parent())
+                assert_eq(target_label("root//:baz"), t.parent().parent())
+                assert_eq(target_label("root//foo/bar:qux"), t.resolve(":qux"))
+                assert_eq(target_label("root//foo/bar/sub:qux"), t.resolve("sub:qux"))
+                assert_eq(target_label("root//other:qux"), t.resolve("//other:qux"))
+
+                l = label("root//foo/bar:baz[something]")
+                assert_eq(label("root//foo/bar:qux"), l.with_name("qux"))
+                assert_eq(label("root//foo:baz"), l.parent())
+                assert_eq(label("root//foo/bar:qux"), l.resolve(":qux"))
+            "#
+        ))?;
+
+        let mut tester = Tester::new()?;
+        tester.additional_globals(label_creator);
+        let no_parent = indoc!(
+            r#"
+            def test():
+                target_label("root//:baz").parent()
+            "#
+        );
+        expect_error(
+            tester.run_starlark_bzl_test(no_parent),
+            no_parent,
+            "is the root of its cell",
+        );
+
+        let mut tester = Tester::new()?;
+        tester.additional_globals(label_creator);
+        let invalid_name = indoc!(
+            r#"
+            def test():
+                target_label("root//foo:baz").with_name("a:b")
+            "#
+        );
+        expect_error(
+            tester.run_starlark_bzl_test(invalid_name),
+            invalid_name,
+            "Invalid target name",
+        );
+        Ok(())
+    }
 }
@@ -8,6 +8,7 @@
  */
 
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt;
 use std::fmt::Display;
 use std::sync::Arc;
@@ -21,6 +22,8 @@ use buck2_interpreter::types::rule::FROZEN_PROMISE_ARTIFACT_MAPPINGS_GET_IMPL;
 use buck2_interpreter::types::rule::FROZEN_RULE_GET_IMPL;
 use buck2_interpreter::types::transition::transition_id_from_value;
 use buck2_node::attrs::attr::Attribute;
+use buck2_node::attrs::display::AttrDisplayWithContextExt;
+use buck2_node::attrs::inspect_options::AttrInspectOptions;
 use buck2_node::attrs::spec::AttributeSpec;
 use buck2_node::nodes::unconfigured::RuleKind;
 use buck2_node::nodes::unconfigured::TargetNode;
@@ -34,6 +37,7 @@ use itertools::Itertools;
 use starlark::any::ProvidesStaticType;
 use starlark::docs::DocFunction;
 use starlark::docs::DocItem;
+use starlark::docs::DocParam;
 use starlark::docs::DocStringKind;
 use starlark::environment::GlobalsBuilder;
 use starlark::eval::Arguments;
@@ -219,15 +223,37 @@ impl<'v> RuleCallable<'v> {
             .borrow()
             .as_ref()
             .map_or_else(|| "unbound_rule".to_owned(), |rt| rt.name.clone());
-        // TODO(nmj): These return 'None' for default values right now. It's going to take some
-        //            refactoring to get that pulled out of the attributespec
         let parameters_spec = self.attributes.signature(name);
 
         let parameter_types = self.attributes.starlark_types();
         let parameter_docs = self.attributes.docstrings();
+        let mut params = parameters_spec.documentation(parameter_types, parameter_docs);
+
+        // The signature only knows that attributes with defaults are optional, so fill in the
+        // actual default values from the attribute spec.
+        let defaults: HashMap<&str, String> = self
+            .attributes
+            .attr_specs()
+            .filter_map(|(name, _idx, attr)| {
+                Some((name, attr.default()?.as_display_no_ctx().to_string()))
+            })
+            .collect();
+        for param in &mut params {
+            if let DocParam::Arg {
+                name,
+                default_value: Some(default_value),
+                ..
+            } = param
+            {
+                if let Some(default) = defaults.get(name.as_str()) {
+                    *default_value = default.clone();
+                }
+            }
+        }
+
         let function_docs = DocFunction::from_docstring(
             DocStringKind::Starlark,
-            parameters_spec.documentation(parameter_types, parameter_docs),
+            params,
             Ty::none(),
             self.docs.as_deref(),
             None,
@@ -406,6 +432,18 @@ impl<'v> StarlarkValue<'v> for FrozenRuleCallable {
                     self.ignore_attrs_for_profiling,
                     call_stack,
                 )?;
+                for a in target_node.attrs(AttrInspectOptions::DefinedOnly) {
+                    if let Some(deprecation) = a.attr.deprecation() {
+                        deprecation.report(
+                            &format!(
+                                "Attribute `{}` of rule `{}`",
+                                a.name,
+                                self.rule.rule_type.name()
+                            ),
+                            eval.call_stack_top_location().map(|l| l.to_string()),
+                        )?;
+                    }
+                }
                 internals.record(target_node)?;
                 Ok(Value::new_none())
             })
@@ -11,9 +11,13 @@ use starlark::environment::GlobalsBuilder;
 
 use crate::super_package::package::register_package_function;
 use crate::super_package::package_value::register_write_package_value;
+use crate::super_package::platform_resolver::register_set_platform_resolver;
+use crate::super_package::select_resolver::register_set_select_resolver;
 
 /// Globals for `PACKAGE` files and `bzl` files included from `PACKAGE` files.
 pub fn register_package_natives(globals: &mut GlobalsBuilder) {
     register_package_function(globals);
     register_write_package_value(globals);
+    register_set_platform_resolver(globals);
+    register_set_select_resolver(globals);
 }
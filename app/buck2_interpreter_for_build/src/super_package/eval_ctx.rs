@@ -12,10 +12,13 @@ use std::sync::Arc;
 
 use buck2_interpreter::paths::package::PackageFilePath;
 use buck2_node::cfg_constructor::CfgConstructorImpl;
+use buck2_node::platform_resolver::PlatformResolverImpl;
+use buck2_node::select_resolver::SelectResolver;
 use buck2_node::super_package::SuperPackage;
 use buck2_node::visibility::VisibilitySpecification;
 use buck2_node::visibility::WithinViewSpecification;
 use dupe::Dupe;
+use dupe::OptionDupedExt;
 use starlark::values::OwnedFrozenRef;
 use starlark::values::OwnedFrozenValue;
 use starlark_map::small_map::SmallMap;
@@ -24,6 +27,8 @@ use crate::interpreter::package_file_extra::FrozenPackageFileExtra;
 use crate::interpreter::package_file_extra::MAKE_CFG_CONSTRUCTOR;
 use crate::super_package::package_value::OwnedFrozenStarlarkPackageValue;
 use crate::super_package::package_value::SuperPackageValuesImpl;
+use crate::super_package::platform_resolver::StarlarkPlatformResolver;
+use crate::super_package::select_resolver::StarlarkSelectResolver;
 
 #[derive(Debug, Default)]
 pub(crate) struct PackageFileVisibilityFields {
@@ -59,11 +64,51 @@ impl PackageFileEvalCtx {
         Ok(Some(make_cfg_constructor(cfg_constructor)?))
     }
 
+    fn platform_resolver(
+        &self,
+        extra: Option<&OwnedFrozenRef<FrozenPackageFileExtra>>,
+    ) -> Option<Arc<dyn PlatformResolverImpl>> {
+        // Only the root `PACKAGE` file can set it, and other packages inherit it.
+        let (Some(extra), Some(platform_resolver)) = (
+            extra,
+            extra.and_then(|extra| extra.as_ref().platform_resolver),
+        ) else {
+            return self.parent.platform_resolver().duped();
+        };
+        let platform_resolver = unsafe {
+            // SAFETY: field belongs to the same heap.
+            OwnedFrozenValue::new(extra.owner().dupe(), platform_resolver)
+        };
+        Some(Arc::new(StarlarkPlatformResolver::new(platform_resolver)))
+    }
+
+    fn select_resolver(
+        &self,
+        extra: Option<&OwnedFrozenRef<FrozenPackageFileExtra>>,
+    ) -> Option<SelectResolver> {
+        // Only the root `PACKAGE` file can set it, and other packages inherit it.
+        let (Some(extra), Some(select_resolver)) = (
+            extra,
+            extra.and_then(|extra| extra.as_ref().select_resolver),
+        ) else {
+            return self.parent.select_resolver().duped();
+        };
+        let select_resolver = unsafe {
+            // SAFETY: field belongs to the same heap.
+            OwnedFrozenValue::new(extra.owner().dupe(), select_resolver)
+        };
+        Some(SelectResolver(Arc::new(StarlarkSelectResolver::new(
+            select_resolver,
+        ))))
+    }
+
     pub(crate) fn build_super_package(
         self,
         extra: Option<OwnedFrozenRef<FrozenPackageFileExtra>>,
     ) -> anyhow::Result<SuperPackage> {
         let cfg_constructor = Self::cfg_constructor(extra.as_ref())?;
+        let platform_resolver = self.platform_resolver(extra.as_ref());
+        let select_resolver = self.select_resolver(extra.as_ref());
 
         let package_values = match &extra {
             None => SmallMap::new(),
@@ -103,6 +148,8 @@ impl PackageFileEvalCtx {
             visibility,
             within_view,
             cfg_constructor,
+            platform_resolver,
+            select_resolver,
         ))
     }
 }
@@ -11,3 +11,5 @@ pub mod defs;
 pub(crate) mod eval_ctx;
 pub(crate) mod package;
 pub mod package_value;
+pub(crate) mod platform_resolver;
+pub(crate) mod select_resolver;
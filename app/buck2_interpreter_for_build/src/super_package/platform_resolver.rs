@@ -0,0 +1,161 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use allocative::Allocative;
+use async_trait::async_trait;
+use buck2_common::dice::cells::HasCellResolver;
+use buck2_core::cells::cell_path::CellPathRef;
+use buck2_core::cells::paths::CellRelativePath;
+use buck2_core::target::label::TargetLabel;
+use buck2_events::dispatch::get_dispatcher;
+use buck2_interpreter::dice::starlark_provider::with_starlark_eval_provider;
+use buck2_interpreter::error::BuckStarlarkError;
+use buck2_interpreter::paths::package::PackageFilePath;
+use buck2_interpreter::print_handler::EventDispatcherPrintHandler;
+use buck2_interpreter::starlark_profiler::StarlarkProfilerOrInstrumentation;
+use buck2_interpreter::types::target_label::StarlarkTargetLabel;
+use buck2_node::platform_resolver::PlatformResolverImpl;
+use buck2_node::super_package::SuperPackage;
+use dice::DiceComputations;
+use dupe::Dupe;
+use starlark::environment::GlobalsBuilder;
+use starlark::environment::Module;
+use starlark::eval::Evaluator;
+use starlark::starlark_module;
+use starlark::values::none::NoneType;
+use starlark::values::OwnedFrozenValue;
+use starlark::values::Value;
+use starlark_map::small_map::SmallMap;
+
+use crate::interpreter::build_context::BuildContext;
+use crate::interpreter::build_context::PerFileTypeContext;
+use crate::interpreter::package_file_extra::PackageFileExtra;
+
+#[derive(Debug, buck2_error::Error)]
+enum PlatformResolverError {
+    #[error(
+        "`set_platform_resolver()` can only be called from the repository root `PACKAGE` file"
+    )]
+    NotPackageRoot,
+    #[error("`set_platform_resolver()` can only be called at most once")]
+    AlreadyRegistered,
+    #[error(
+        "Platform resolver must return a target label, a string or `None`, got `{0}` for `{1}`"
+    )]
+    #[buck2(user)]
+    WrongReturnType(String, TargetLabel),
+}
+
+/// Function registered with `set_platform_resolver()`.
+#[derive(Debug, Allocative)]
+pub(crate) struct StarlarkPlatformResolver {
+    resolver: OwnedFrozenValue,
+}
+
+impl StarlarkPlatformResolver {
+    pub(crate) fn new(resolver: OwnedFrozenValue) -> Self {
+        Self { resolver }
+    }
+}
+
+#[async_trait]
+impl PlatformResolverImpl for StarlarkPlatformResolver {
+    async fn resolve(
+        &self,
+        ctx: &mut DiceComputations<'_>,
+        target: &TargetLabel,
+        super_package: &SuperPackage,
+    ) -> anyhow::Result<Option<TargetLabel>> {
+        let cell_resolver = ctx.get_cell_resolver().await?;
+        let package_values: SmallMap<String, serde_json::Value> = super_package
+            .package_values()
+            .package_values_json()?
+            .into_iter()
+            .map(|(k, v)| (k.as_str().to_owned(), v))
+            .collect();
+        let print = EventDispatcherPrintHandler(get_dispatcher());
+        with_starlark_eval_provider(
+            ctx,
+            &mut StarlarkProfilerOrInstrumentation::disabled(),
+            format!("platform_resolver:{}", target),
+            |provider, _| {
+                let module = Module::new();
+                let (mut eval, _) = provider.make(&module)?;
+                eval.set_print_handler(&print);
+                let args = [
+                    (
+                        "label",
+                        module.heap().alloc(StarlarkTargetLabel::new(target.dupe())),
+                    ),
+                    (
+                        "cell",
+                        module.heap().alloc(target.pkg().cell_name().as_str()),
+                    ),
+                    ("package_values", module.heap().alloc(package_values)),
+                ];
+                let platform = eval
+                    .eval_function(self.resolver.value(), &[], &args)
+                    .map_err(BuckStarlarkError::new)?;
+                if platform.is_none() {
+                    Ok(None)
+                } else if let Some(label) = StarlarkTargetLabel::from_value(platform) {
+                    Ok(Some(label.label().dupe()))
+                } else if let Some(label) = platform.unpack_str() {
+                    Ok(Some(TargetLabel::parse(
+                        label,
+                        cell_resolver.root_cell(),
+                        &cell_resolver,
+                    )?))
+                } else {
+                    Err(
+                        PlatformResolverError::WrongReturnType(platform.to_repr(), target.dupe())
+                            .into(),
+                    )
+                }
+            },
+        )
+        .await
+    }
+}
+
+#[starlark_module]
+pub(crate) fn register_set_platform_resolver(globals: &mut GlobalsBuilder) {
+    /// Register a function computing the default target platform of targets which don't set
+    /// `default_target_platform`, e.g. to pick platforms by directory.
+    ///
+    /// This function can only be called from the repository root `PACKAGE` file.
+    ///
+    /// The function is called with named parameters `label` (the target label), `cell`
+    /// (the cell name) and `package_values` (a dict of the `PACKAGE` values of the target's
+    /// package), and returns a platform target label, as a label or a string, or `None` to use
+    /// `parser.target_platform_detector_spec`.
+    fn set_platform_resolver<'v>(
+        #[starlark(require = pos)] resolver: Value<'v>,
+        eval: &mut Evaluator<'v, '_>,
+    ) -> anyhow::Result<NoneType> {
+        let build_context = BuildContext::from_context(eval)?;
+        let ctx = match &build_context.additional {
+            PerFileTypeContext::Package(ctx) => ctx,
+            _ => return Err(PlatformResolverError::NotPackageRoot.into()),
+        };
+        if ctx.path
+            != PackageFilePath::for_dir(CellPathRef::new(
+                build_context.cell_info().cell_resolver().root_cell(),
+                CellRelativePath::empty(),
+            ))
+        {
+            return Err(PlatformResolverError::NotPackageRoot.into());
+        }
+        let package_file_extra: &PackageFileExtra = PackageFileExtra::get_or_init(eval)?;
+        if package_file_extra.platform_resolver.set(resolver).is_err() {
+            return Err(PlatformResolverError::AlreadyRegistered.into());
+        }
+        Ok(NoneType)
+    }
+}
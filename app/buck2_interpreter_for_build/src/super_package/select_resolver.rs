@@ -0,0 +1,141 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use allocative::Allocative;
+use buck2_core::cells::cell_path::CellPathRef;
+use buck2_core::cells::paths::CellRelativePath;
+use buck2_core::target::label::TargetLabel;
+use buck2_events::dispatch::get_dispatcher;
+use buck2_interpreter::error::BuckStarlarkError;
+use buck2_interpreter::paths::package::PackageFilePath;
+use buck2_interpreter::print_handler::EventDispatcherPrintHandler;
+use buck2_interpreter::types::target_label::StarlarkTargetLabel;
+use buck2_node::select_resolver::SelectResolverImpl;
+use dupe::Dupe;
+use itertools::Itertools;
+use starlark::environment::GlobalsBuilder;
+use starlark::environment::Module;
+use starlark::eval::Evaluator;
+use starlark::starlark_module;
+use starlark::values::list::AllocList;
+use starlark::values::none::NoneType;
+use starlark::values::OwnedFrozenValue;
+use starlark::values::Value;
+
+use crate::interpreter::build_context::BuildContext;
+use crate::interpreter::build_context::PerFileTypeContext;
+use crate::interpreter::package_file_extra::PackageFileExtra;
+
+#[derive(Debug, buck2_error::Error)]
+enum SelectResolverError {
+    #[error("`set_select_resolver()` can only be called from the repository root `PACKAGE` file")]
+    NotPackageRoot,
+    #[error("`set_select_resolver()` can only be called at most once")]
+    AlreadyRegistered,
+    #[error(
+        "Select resolver must return one of the keys it is given, as a target label or a string, \
+        or `None`, got `{0}` for keys {1}"
+    )]
+    #[buck2(user)]
+    WrongReturnType(String, String),
+}
+
+/// Function registered with `set_select_resolver()`.
+#[derive(Debug, Allocative)]
+pub(crate) struct StarlarkSelectResolver {
+    resolver: OwnedFrozenValue,
+}
+
+impl StarlarkSelectResolver {
+    pub(crate) fn new(resolver: OwnedFrozenValue) -> Self {
+        Self { resolver }
+    }
+}
+
+impl SelectResolverImpl for StarlarkSelectResolver {
+    fn resolve(&self, keys: &[&TargetLabel]) -> anyhow::Result<Option<TargetLabel>> {
+        let print = EventDispatcherPrintHandler(get_dispatcher());
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        eval.set_print_handler(&print);
+        let keys_value = module.heap().alloc(AllocList(
+            keys.iter().map(|k| StarlarkTargetLabel::new((*k).dupe())),
+        ));
+        let chosen = eval
+            .eval_function(self.resolver.value(), &[keys_value], &[])
+            .map_err(BuckStarlarkError::new)?;
+        if chosen.is_none() {
+            return Ok(None);
+        }
+        let chosen_key = if let Some(label) = StarlarkTargetLabel::from_value(chosen) {
+            keys.iter().find(|k| **k == label.label())
+        } else if let Some(label) = chosen.unpack_str() {
+            keys.iter().find(|k| k.to_string() == label)
+        } else {
+            None
+        };
+        match chosen_key {
+            Some(key) => Ok(Some((*key).dupe())),
+            None => Err(SelectResolverError::WrongReturnType(
+                chosen.to_repr(),
+                keys.iter().map(|k| format!("`{}`", k)).join(", "),
+            )
+            .into()),
+        }
+    }
+}
+
+#[starlark_module]
+pub(crate) fn register_set_select_resolver(globals: &mut GlobalsBuilder) {
+    /// Register a function resolving the `select()`s with several matching conditions none of
+    /// which is more specific than all the others, which are otherwise errors. For example, to
+    /// prefer conditions by an explicit priority list:
+    ///
+    /// ```python
+    /// PRIORITY = ["root//config:asan", "root//config:linux"]
+    ///
+    /// def _resolve_select(keys):
+    ///     keys = [str(k) for k in keys]
+    ///     for key in PRIORITY:
+    ///         if key in keys:
+    ///             return key
+    ///     return None
+    ///
+    /// set_select_resolver(_resolve_select)
+    /// ```
+    ///
+    /// This function can only be called from the repository root `PACKAGE` file.
+    ///
+    /// The function is called with the target labels of the matching conditions, in the order
+    /// they are written in the `select()`, and returns one of them, as a label or a string, or
+    /// `None` to fail as if no resolver was registered.
+    fn set_select_resolver<'v>(
+        #[starlark(require = pos)] resolver: Value<'v>,
+        eval: &mut Evaluator<'v, '_>,
+    ) -> anyhow::Result<NoneType> {
+        let build_context = BuildContext::from_context(eval)?;
+        let ctx = match &build_context.additional {
+            PerFileTypeContext::Package(ctx) => ctx,
+            _ => return Err(SelectResolverError::NotPackageRoot.into()),
+        };
+        if ctx.path
+            != PackageFilePath::for_dir(CellPathRef::new(
+                build_context.cell_info().cell_resolver().root_cell(),
+                CellRelativePath::empty(),
+            ))
+        {
+            return Err(SelectResolverError::NotPackageRoot.into());
+        }
+        let package_file_extra: &PackageFileExtra = PackageFileExtra::get_or_init(eval)?;
+        if package_file_extra.select_resolver.set(resolver).is_err() {
+            return Err(SelectResolverError::AlreadyRegistered.into());
+        }
+        Ok(NoneType)
+    }
+}
@@ -0,0 +1,29 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use buck2_interpreter_for_build::interpreter::functions::cells::register_cells;
+use buck2_interpreter_for_build::interpreter::testing::Tester;
+use indoc::indoc;
+
+#[test]
+fn test_cells() -> anyhow::Result<()> {
+    let mut tester = Tester::new().unwrap();
+    tester.additional_globals(register_cells);
+    tester.run_starlark_test(indoc!(
+        r#"
+            def test():
+                assert_eq(["root"], list(cells().keys()))
+                root = cells()["root"]
+                assert_eq("root", root.name)
+                assert_eq("", root.root)
+                assert_eq(False, root.external)
+            "#
+    ))?;
+    Ok(())
+}
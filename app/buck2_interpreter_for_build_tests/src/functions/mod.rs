@@ -7,6 +7,7 @@
  * of this source tree.
  */
 
+mod cells;
 mod host_info;
 mod load_symbols;
 mod read_config;
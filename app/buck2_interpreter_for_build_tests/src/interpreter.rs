@@ -528,3 +528,159 @@ fn test_oncall() -> anyhow::Result<()> {
     );
     Ok(())
 }
+
+#[test]
+fn test_disallowed_globals() -> anyhow::Result<()> {
+    let mut tester = Tester::with_cells(
+        buck2_interpreter_for_build::interpreter::testing::cells(Some(indoc!(
+            r#"
+            [buildfile]
+                disallowed_globals = read_config, sha256
+        "#
+        )))
+        .unwrap(),
+    )
+    .unwrap();
+    tester.run_starlark_bzl_test(indoc!(
+        r#"
+        def test():
+            assert_eq(3, len("abc"))
+        "#
+    ))?;
+    tester.run_starlark_bzl_test_expecting_error(
+        indoc!(
+            r#"
+            def test():
+                read_config("config", "key")
+            "#
+        ),
+        "`read_config` is not allowed in the files of cell `root`, see `buildfile.disallowed_globals`",
+    );
+    Ok(())
+}
+
+#[test]
+fn test_disallowed_globals_fail_before_evaluation() -> anyhow::Result<()> {
+    let mut tester = Tester::with_cells(
+        buck2_interpreter_for_build::interpreter::testing::cells(Some(indoc!(
+            r#"
+            [buildfile]
+                disallowed_globals = read_config
+        "#
+        )))
+        .unwrap(),
+    )
+    .unwrap();
+
+    // Never called, but used.
+    let err = tester
+        .add_import(
+            &ImportPath::testing_new("root//some/package:unused.bzl"),
+            indoc!(
+                r#"
+                def f():
+                    return native.read_config("config", "key")
+                "#
+            ),
+        )
+        .err()
+        .expect("Expected the module to fail");
+    assert!(
+        format!("{:#}", err).contains("`native.read_config` is not allowed"),
+        "{:#}",
+        err
+    );
+
+    // A local of the same name isn't the global, and the global isn't exported.
+    let loaded = tester.add_import(
+        &ImportPath::testing_new("root//some/package:local.bzl"),
+        indoc!(
+            r#"
+            def f(read_config):
+                return read_config
+            "#
+        ),
+    )?;
+    assert!(loaded.env().get("read_config").is_err());
+    assert!(
+        loaded
+            .env()
+            .names()
+            .all(|name| name.as_str() != "read_config")
+    );
+    Ok(())
+}
+
+#[test]
+fn test_allowed_globals() -> anyhow::Result<()> {
+    let mut tester = Tester::with_cells(
+        buck2_interpreter_for_build::interpreter::testing::cells(Some(indoc!(
+            r#"
+            [buildfile]
+                allowed_globals = assert_eq, read_config
+        "#
+        )))
+        .unwrap(),
+    )
+    .unwrap();
+    // The Starlark builtins don't need to be allowed.
+    tester.run_starlark_bzl_test(indoc!(
+        r#"
+        def test():
+            assert_eq(None, read_config("config", "key"))
+            assert_eq(3, len("abc"))
+        "#
+    ))?;
+    tester.run_starlark_bzl_test_expecting_error(
+        indoc!(
+            r#"
+            def test():
+                sha256("abc")
+            "#
+        ),
+        "`sha256` is not allowed in the files of cell `root`, see `buildfile.allowed_globals`",
+    );
+
+    // A local of the same name in another scope doesn't hide the use of the global.
+    let err = tester
+        .add_import(
+            &ImportPath::testing_new("root//some/package:scopes.bzl"),
+            indoc!(
+                r#"
+                def f(sha256):
+                    return sha256
+                x = sha256("abc")
+                "#
+            ),
+        )
+        .err()
+        .expect("Expected the module to fail");
+    assert!(format!("{:#}", err).contains("scopes.bzl:3"), "{:#}", err);
+    Ok(())
+}
+
+#[test]
+fn test_build_file_time_budget() {
+    let mut tester = Tester::with_cells(
+        buck2_interpreter_for_build::interpreter::testing::cells(Some(indoc!(
+            r#"
+            [buck2]
+                starlark_build_file_max_time_ms = 0
+        "#
+        )))
+        .unwrap(),
+    )
+    .unwrap();
+    // The loop calls nothing, so only the deadline can stop it.
+    tester.run_starlark_test_expecting_error(
+        indoc!(
+            r#"
+            def test():
+                x = 0
+                for i in range(100000000):
+                    x += i
+            "#
+        ),
+        "exceeding the limit `buck2.starlark_build_file_max_time_ms`",
+    );
+}
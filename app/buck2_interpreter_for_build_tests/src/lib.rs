@@ -18,6 +18,7 @@ mod rule;
 pub mod select;
 mod super_package;
 mod tests;
+mod transition;
 mod uncategorized;
 mod uncategorized_2;
 
@@ -27,5 +28,6 @@ fn init_late_bindings_for_test() {
     fn init() {
         buck2_interpreter_for_build::init_late_bindings();
         buck2_build_api::init_late_bindings();
+        buck2_transition::init_late_bindings();
     }
 }
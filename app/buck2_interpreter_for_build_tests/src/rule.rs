@@ -9,6 +9,7 @@
 
 use buck2_build_api::interpreter::rule_defs::transitive_set::transitive_set_definition::register_transitive_set;
 use buck2_core::bzl::ImportPath;
+use buck2_core::is_open_source;
 use buck2_interpreter::file_loader::LoadedModules;
 use buck2_interpreter_for_build::attrs::attrs_global::register_attrs;
 use buck2_interpreter_for_build::interpreter::testing::Tester;
@@ -287,6 +288,53 @@ fn option_allows_none() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn deprecated_attrs() -> anyhow::Result<()> {
+    let prefix = indoc!(
+        r#"
+        def impl(ctx):
+            pass
+
+        foo_binary = rule(
+            impl=impl,
+            attrs={
+                "old": attrs.deprecated(
+                    attrs.string(default=""),
+                    message="Use `new`",
+                    removal_date="2024-06-30",
+                    category="starlark_deprecated_old",
+                ),
+            },
+        )
+
+        def test():
+        "#
+    );
+
+    let mut tester = rule_tester();
+    tester.run_starlark_test(&format!("{}\n    foo_binary(name='t1')", prefix))?;
+    if is_open_source() {
+        // Soft errors are errors in open source, unless configured.
+        let mut tester = rule_tester();
+        tester.run_starlark_test_expecting_error(
+            &format!("{}\n    foo_binary(name='t1', old='x')", prefix),
+            "Attribute `old` of rule `foo_binary` is deprecated and will be removed on 2024-06-30: Use `new`",
+        );
+    }
+
+    let mut tester = rule_tester();
+    tester.run_starlark_test_expecting_error(
+        "def test():\n attrs.deprecated(attrs.string(), message = 'm', category = 'starlark_old')",
+        "must start with `starlark_deprecated`",
+    );
+    let mut tester = rule_tester();
+    tester.run_starlark_test_expecting_error(
+        "def test():\n attrs.deprecated(attrs.string(), message = 'm', removal_date = 'June')",
+        "must be formatted as `YYYY-MM-DD`",
+    );
+    Ok(())
+}
+
 #[test]
 fn returns_documentation() -> anyhow::Result<()> {
     let bzl = indoc::indoc!(
@@ -345,37 +393,45 @@ fn returns_documentation() -> anyhow::Result<()> {
 
     // Grab the default parameters that are inserted into every rule.
     let empty_spec = AttributeSpec::from(vec![], false)?;
-    let mut params = empty_spec
+    let internal_params = empty_spec
         .signature("foo_binary".to_owned())
         .documentation(empty_spec.starlark_types(), empty_spec.docstrings());
-    params.extend(vec![
+    let user_params = vec![
         arg("any", Ty::any(), None),
-        arg("arg", Ty::string(), Some("_")),
-        arg("bool", Ty::bool(), Some("_")),
-        arg("default_only", Ty::string(), Some("_")),
-        arg("dep", Ty::string(), Some("_")),
-        arg("dict", Ty::dict(Ty::string(), Ty::bool()), Some("_")),
-        arg("list", Ty::list(Ty::string()), Some("_")),
-        arg("one_of", Ty::union2(Ty::bool(), Ty::string()), Some("_")),
-        arg("option", Ty::union2(Ty::none(), Ty::string()), Some("_")),
+        arg("arg", Ty::string(), Some("\"arg\"")),
+        arg("bool", Ty::bool(), Some("True")),
+        arg("default_only", Ty::string(), Some("\"default_only\"")),
+        arg("dep", Ty::string(), Some("\"root//:dep\"")),
+        arg(
+            "dict",
+            Ty::dict(Ty::string(), Ty::bool()),
+            Some("{\"dict\": True}"),
+        ),
+        arg("list", Ty::list(Ty::string()), Some("[\"list\"]")),
+        arg("one_of", Ty::union2(Ty::bool(), Ty::string()), Some("\"\"")),
+        arg("option", Ty::union2(Ty::none(), Ty::string()), Some("None")),
         arg("query", Ty::string(), None),
-        arg("source", Ty::string(), Some("_")),
-        arg("string", Ty::string(), Some("_")),
-        arg("tuple", Ty::tuple2(Ty::bool(), Ty::string()), Some("_")),
-    ]);
+        arg("source", Ty::string(), Some("\"root//:src\"")),
+        arg("string", Ty::string(), Some("\"string\"")),
+        arg(
+            "tuple",
+            Ty::tuple2(Ty::bool(), Ty::string()),
+            Some("(True,\"some string\")"),
+        ),
+    ];
 
-    let expected_docs = DocItem::Function(DocFunction {
+    let expected_docs = DocFunction {
         docs: DocString::from_docstring(
             DocStringKind::Starlark,
             "Summary for foo_binary\n\nDetails for foo_binary",
         ),
-        params,
+        params: Vec::new(),
         ret: DocReturn {
             docs: None,
             typ: Ty::none(),
         },
         as_type: None,
-    });
+    };
 
     let tester = rule_tester();
     let res = tester.eval_import(
@@ -390,8 +446,41 @@ fn returns_documentation() -> anyhow::Result<()> {
         .value()
         .documentation()
         .unwrap();
-
+    let DocItem::Function(mut docs) = docs else {
+        panic!("Expected function docs, got {:?}", docs);
+    };
+    let params = std::mem::take(&mut docs.params);
     assert_eq!(expected_docs, docs);
 
+    // The signature alone only knows which parameters have a default (rendered `_`), the actual
+    // values come from the attributes.
+    fn hide_default(param: &DocParam) -> DocParam {
+        match param.clone() {
+            DocParam::Arg {
+                name,
+                docs,
+                typ,
+                default_value,
+            } => DocParam::Arg {
+                name,
+                docs,
+                typ,
+                default_value: default_value.map(|_| "_".to_owned()),
+            },
+            param => param,
+        }
+    }
+    let (internal, user) = params.split_at(internal_params.len());
+    assert_eq!(
+        internal_params,
+        internal.iter().map(hide_default).collect::<Vec<_>>()
+    );
+    assert!(internal.iter().any(|p| matches!(
+        p,
+        DocParam::Arg { name, default_value: Some(default), .. }
+            if name == "default_target_platform" && default == "None"
+    )));
+    assert_eq!(user_params, user);
+
     Ok(())
 }
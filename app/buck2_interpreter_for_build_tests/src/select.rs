@@ -7,8 +7,29 @@
  * of this source tree.
  */
 
+use buck2_core::configuration::config_setting::ConfigSettingData;
+use buck2_core::configuration::data::ConfigurationData;
+use buck2_core::configuration::pair::ConfigurationNoExec;
+use buck2_core::execution_types::execution::ExecutionPlatformResolution;
+use buck2_core::fs::project::ProjectRootTemp;
+use buck2_core::plugins::PluginLists;
+use buck2_core::target::label::TargetLabel;
 use buck2_interpreter_for_build::interpreter::testing::Tester;
+use buck2_node::attrs::attr_type::string::StringLiteral;
+use buck2_node::attrs::configured_attr::ConfiguredAttr;
+use buck2_node::attrs::inspect_options::AttrInspectOptions;
+use buck2_node::configuration::resolved::ConfigurationNode;
+use buck2_node::configuration::resolved::ConfigurationSettingKey;
+use buck2_node::configuration::resolved::ResolvedConfiguration;
+use buck2_node::nodes::configured::ConfiguredTargetNode;
+use buck2_node::nodes::frontend::TargetGraphCalculation;
+use buck2_node::nodes::unconfigured::TargetNode;
+use dupe::Dupe;
 use indoc::indoc;
+use starlark_map::ordered_map::OrderedMap;
+use starlark_map::unordered_map::UnorderedMap;
+
+use crate::tests::calculation;
 
 #[test]
 fn test_select_funcs() {
@@ -116,3 +137,165 @@ def test():
         ))
         .unwrap();
 }
+
+/// Configures `node` in a configuration where the config settings `matching` match and the
+/// config settings `not_matching` don't.
+pub(crate) fn configure(
+    node: TargetNode,
+    matching: &[&str],
+    not_matching: &[&str],
+) -> ConfiguredTargetNode {
+    let cfg = ConfigurationData::testing_new();
+    let settings = matching
+        .iter()
+        .map(|label| (label, true))
+        .chain(not_matching.iter().map(|label| (label, false)))
+        .map(|(label, matches)| {
+            let label = TargetLabel::testing_parse(label);
+            (
+                ConfigurationSettingKey(label.dupe()),
+                ConfigurationNode::new(
+                    cfg.dupe(),
+                    label,
+                    ConfigSettingData {
+                        constraints: Default::default(),
+                        buckconfigs: Default::default(),
+                    },
+                    matches,
+                ),
+            )
+        });
+    ConfiguredTargetNode::new(
+        node.label().configure(cfg.dupe()),
+        node,
+        ResolvedConfiguration::new(
+            ConfigurationNoExec::new(cfg),
+            UnorderedMap::from_iter(settings),
+        ),
+        OrderedMap::new(),
+        ExecutionPlatformResolution::unspecified(),
+        Vec::new(),
+        Vec::new(),
+        OrderedMap::new(),
+        PluginLists::new(),
+    )
+}
+
+#[tokio::test]
+async fn test_resolved_selects() {
+    let fs = ProjectRootTemp::new().unwrap();
+    fs.write_file(
+        "rules.bzl",
+        r#"
+simple = rule(
+    impl = lambda ctx: fail(),
+    attrs = {
+        "flags": attrs.list(attrs.string()),
+        "srcs": attrs.list(attrs.string()),
+    },
+)
+"#,
+    );
+    fs.write_file(
+        "pkg/BUCK",
+        r#"
+load("//:rules.bzl", "simple")
+simple(
+    name = "a",
+    flags = select({
+        "//config:linux": select({
+            "//config:x86_64": ["-x86_64"],
+            "DEFAULT": [],
+        }),
+        "DEFAULT": [],
+    }) + select({
+        "//config:macos": ["-macos"],
+        "DEFAULT": ["-other"],
+    }),
+    srcs = ["a.c"],
+)
+"#,
+    );
+
+    let mut ctx = calculation(&fs).await;
+    let node = ctx
+        .get_target_node(&TargetLabel::testing_parse("root//pkg:a"))
+        .await
+        .unwrap();
+
+    let node = configure(
+        node,
+        &["root//config:linux", "root//config:x86_64"],
+        &["root//config:macos"],
+    );
+    assert_eq!(
+        vec![(
+            "flags",
+            vec![
+                "root//config:linux".to_owned(),
+                "root//config:x86_64".to_owned(),
+                "DEFAULT".to_owned(),
+            ]
+        )],
+        node.resolved_selects()
+    );
+}
+
+#[tokio::test]
+async fn test_configured_selects_are_shared() {
+    let fs = ProjectRootTemp::new().unwrap();
+    fs.write_file(
+        "rules.bzl",
+        r#"
+simple = rule(
+    impl = lambda ctx: fail(),
+    attrs = {
+        "flags": attrs.list(attrs.string()),
+    },
+)
+"#,
+    );
+    fs.write_file(
+        "pkg/BUCK",
+        r#"
+load("//:rules.bzl", "simple")
+FLAGS = select({
+    "//config:linux": ["-linux"],
+    "DEFAULT": ["-other"],
+})
+simple(name = "a", flags = FLAGS)
+simple(name = "b", flags = ["-b"] + FLAGS)
+simple(name = "c", flags = FLAGS)
+"#,
+    );
+
+    let mut ctx = calculation(&fs).await;
+    let mut flags = Vec::new();
+    for name in ["a", "b", "c"] {
+        let node = ctx
+            .get_target_node(&TargetLabel::testing_parse(&format!("root//pkg:{}", name)))
+            .await
+            .unwrap();
+        let node = configure(node, &["root//config:linux"], &[]);
+        let first = node.get("flags", AttrInspectOptions::All).unwrap().value;
+        let second = node.get("flags", AttrInspectOptions::All).unwrap().value;
+        assert_eq!(first, second);
+        flags.push(match first {
+            ConfiguredAttr::List(list) => list.0,
+            _ => panic!("expected a list"),
+        });
+    }
+
+    let strings = |list: &[ConfiguredAttr]| {
+        list.iter()
+            .map(|x| match x {
+                ConfiguredAttr::String(StringLiteral(s)) => s.to_string(),
+                _ => panic!("expected a string"),
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(vec!["-linux"], strings(&flags[0]));
+    assert_eq!(vec!["-b", "-linux"], strings(&flags[1]));
+    // Nodes resolving the select to the same value share it.
+    assert_eq!(flags[0].as_ptr(), flags[2].as_ptr());
+}
@@ -9,3 +9,5 @@
 
 mod package_function;
 mod package_value;
+mod platform_resolver;
+mod select_resolver;
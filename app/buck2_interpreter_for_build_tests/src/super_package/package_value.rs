@@ -12,11 +12,17 @@
 use buck2_core::cells::build_file_cell::BuildFileCell;
 use buck2_core::fs::project::ProjectRootTemp;
 use buck2_core::package::PackageLabel;
+use buck2_core::target::label::TargetLabel;
 use buck2_interpreter::starlark_profiler::StarlarkProfilerOrInstrumentation;
 use buck2_interpreter_for_build::interpreter::dice_calculation_delegate::HasCalculationDelegate;
 use buck2_node::attrs::display::AttrDisplayWithContextExt;
 use buck2_node::attrs::inspect_options::AttrInspectOptions;
+use buck2_node::nodes::attributes::PACKAGE_VALUES;
+use buck2_node::nodes::attributes::PACKAGE_VISIBILITY;
+use buck2_node::nodes::attributes::PACKAGE_WITHIN_VIEW;
 use buck2_node::nodes::frontend::TargetGraphCalculation;
+use buck2_node::visibility::VisibilitySpecification;
+use buck2_node::visibility::WithinViewSpecification;
 use indoc::indoc;
 
 use crate::tests::calculation;
@@ -384,3 +390,59 @@ async fn test_read_parent_package_value_is_suggested_in_bzl_file() {
         err
     );
 }
+
+#[tokio::test]
+async fn test_package_on_target_node() {
+    let fs = ProjectRootTemp::new().unwrap();
+
+    fs.write_file("rules.bzl", RULES);
+    fs.write_file(
+        "headphones/PACKAGE",
+        indoc!(
+            r#"
+                package(
+                    visibility = ["//aaa/..."],
+                    within_view = ["//bbb/..."],
+                )
+                write_package_value("aaa.bbb", "ccc")
+        "#
+        ),
+    );
+    fs.write_file(
+        "headphones/BUCK",
+        indoc!(
+            r#"
+                load("//:rules.bzl", "rrr")
+                rrr(
+                    name = "headphones",
+                    value = "",
+                )
+        "#
+        ),
+    );
+
+    let mut ctx = calculation(&fs).await;
+    let target_node = ctx
+        .get_target_node(&TargetLabel::testing_parse("root//headphones:headphones"))
+        .await
+        .unwrap();
+
+    let package = target_node.package();
+    assert_eq!(
+        serde_json::json!({"aaa.bbb": "ccc"}),
+        package.package_values.to_value()
+    );
+    assert_eq!(
+        VisibilitySpecification::testing_parse(&["root//aaa/..."]),
+        package.visibility
+    );
+    assert_eq!(
+        WithinViewSpecification(VisibilitySpecification::testing_parse(&["root//bbb/..."]).0),
+        package.within_view
+    );
+
+    let special_attrs: Vec<_> = target_node.special_attrs().map(|(k, _)| k).collect();
+    for attr in [PACKAGE_VALUES, PACKAGE_VISIBILITY, PACKAGE_WITHIN_VIEW] {
+        assert!(special_attrs.contains(&attr), "{:?}", special_attrs);
+    }
+}
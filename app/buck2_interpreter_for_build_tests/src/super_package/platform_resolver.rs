@@ -0,0 +1,119 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use buck2_core::fs::project::ProjectRootTemp;
+use buck2_core::package::PackageLabel;
+use buck2_core::target::label::TargetLabel;
+use buck2_node::nodes::frontend::TargetGraphCalculation;
+use indoc::indoc;
+
+use crate::tests::calculation;
+
+const RULES_BZL: &str = r#"
+simple = rule(
+    impl = lambda ctx: fail(),
+    attrs = {},
+)
+"#;
+
+#[tokio::test]
+async fn test_platform_resolver() {
+    let fs = ProjectRootTemp::new().unwrap();
+
+    fs.write_file("rules.bzl", RULES_BZL);
+    fs.write_file(
+        "PACKAGE",
+        indoc!(
+            r#"
+                def _resolve_platform(label, cell, package_values):
+                    if cell == "root" and label.package.startswith("mobile"):
+                        return "//platforms:" + package_values.get("platform.name", "mobile")
+                    return None
+
+                set_platform_resolver(_resolve_platform)
+            "#
+        ),
+    );
+    fs.write_file(
+        "mobile/PACKAGE",
+        "write_package_value('platform.name', 'android')",
+    );
+    fs.write_file(
+        "mobile/BUCK",
+        "load('//:rules.bzl', 'simple')\nsimple(name = 'a')",
+    );
+    fs.write_file(
+        "server/BUCK",
+        "load('//:rules.bzl', 'simple')\nsimple(name = 'b')",
+    );
+
+    let mut ctx = calculation(&fs).await;
+
+    let mobile = ctx
+        .get_interpreter_results(PackageLabel::testing_parse("root//mobile"))
+        .await
+        .unwrap();
+    let resolver = mobile.super_package().platform_resolver().unwrap();
+    assert_eq!(
+        Some(TargetLabel::testing_parse("root//platforms:android")),
+        resolver
+            .resolve(
+                &mut ctx,
+                &TargetLabel::testing_parse("root//mobile:a"),
+                mobile.super_package(),
+            )
+            .await
+            .unwrap()
+    );
+
+    let server = ctx
+        .get_interpreter_results(PackageLabel::testing_parse("root//server"))
+        .await
+        .unwrap();
+    // Inherited from the root `PACKAGE` file.
+    let resolver = server.super_package().platform_resolver().unwrap();
+    assert_eq!(
+        None,
+        resolver
+            .resolve(
+                &mut ctx,
+                &TargetLabel::testing_parse("root//server:b"),
+                server.super_package(),
+            )
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_platform_resolver_not_root() {
+    let fs = ProjectRootTemp::new().unwrap();
+
+    fs.write_file("rules.bzl", RULES_BZL);
+    fs.write_file(
+        "mobile/PACKAGE",
+        "set_platform_resolver(lambda label, cell, package_values: None)",
+    );
+    fs.write_file(
+        "mobile/BUCK",
+        "load('//:rules.bzl', 'simple')\nsimple(name = 'a')",
+    );
+
+    let mut ctx = calculation(&fs).await;
+
+    let err = ctx
+        .get_interpreter_results(PackageLabel::testing_parse("root//mobile"))
+        .await
+        .unwrap_err();
+    assert!(
+        format!("{:?}", err).contains("can only be called from the repository root"),
+        "{:?}",
+        err
+    );
+}
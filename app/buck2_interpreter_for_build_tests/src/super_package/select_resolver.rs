@@ -0,0 +1,90 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use buck2_core::fs::project::ProjectRootTemp;
+use buck2_core::target::label::TargetLabel;
+use buck2_node::nodes::frontend::TargetGraphCalculation;
+
+use crate::select::configure;
+use crate::tests::calculation;
+
+const RULES_BZL: &str = r#"
+simple = rule(
+    impl = lambda ctx: fail(),
+    attrs = {
+        "flags": attrs.list(attrs.string()),
+    },
+)
+"#;
+
+const BUCK: &str = r#"
+load("//:rules.bzl", "simple")
+simple(
+    name = "a",
+    flags = select({
+        "//config:linux": ["-linux"],
+        "//config:asan": ["-asan"],
+    }),
+)
+"#;
+
+#[tokio::test]
+async fn test_select_resolver() {
+    let fs = ProjectRootTemp::new().unwrap();
+
+    fs.write_file("rules.bzl", RULES_BZL);
+    fs.write_file(
+        "PACKAGE",
+        r#"
+def _resolve_select(keys):
+    for key in keys:
+        if str(key) == "root//config:asan":
+            return key
+    return None
+
+set_select_resolver(_resolve_select)
+"#,
+    );
+    fs.write_file("pkg/BUCK", BUCK);
+
+    let mut ctx = calculation(&fs).await;
+
+    let a = ctx
+        .get_target_node(&TargetLabel::testing_parse("root//pkg:a"))
+        .await
+        .unwrap();
+    assert!(a.package().select_resolver.is_some());
+
+    let a = configure(a, &["root//config:linux", "root//config:asan"], &[]);
+    assert_eq!(
+        vec![("flags", vec!["root//config:asan".to_owned()])],
+        a.resolved_selects()
+    );
+}
+
+#[tokio::test]
+async fn test_select_resolver_not_root() {
+    let fs = ProjectRootTemp::new().unwrap();
+
+    fs.write_file("rules.bzl", RULES_BZL);
+    fs.write_file("pkg/PACKAGE", "set_select_resolver(lambda keys: None)");
+    fs.write_file("pkg/BUCK", BUCK);
+
+    let mut ctx = calculation(&fs).await;
+
+    let err = ctx
+        .get_target_node(&TargetLabel::testing_parse("root//pkg:a"))
+        .await
+        .unwrap_err();
+    assert!(
+        format!("{:?}", err).contains("can only be called from the repository root"),
+        "{:?}",
+        err
+    );
+}
@@ -7,6 +7,8 @@
  * of this source tree.
  */
 
+use std::time::Instant;
+
 use buck2_build_api::interpreter::rule_defs::register_rule_defs;
 use buck2_common::dice::cells::SetCellResolver;
 use buck2_common::dice::data::testing::SetTestingIoProvider;
@@ -14,6 +16,7 @@ use buck2_common::legacy_configs::dice::SetLegacyConfigs;
 use buck2_common::legacy_configs::LegacyBuckConfig;
 use buck2_common::legacy_configs::LegacyBuckConfigs;
 use buck2_core::bzl::ImportPath;
+use buck2_core::cells::build_file_cell::BuildFileCell;
 use buck2_core::cells::cell_root_path::CellRootPathBuf;
 use buck2_core::cells::name::CellName;
 use buck2_core::cells::CellResolver;
@@ -25,12 +28,15 @@ use buck2_interpreter::dice::starlark_debug::SetStarlarkDebugger;
 use buck2_interpreter::dice::starlark_profiler::SetStarlarkProfilerInstrumentation;
 use buck2_interpreter::dice::starlark_profiler::StarlarkProfilerConfiguration;
 use buck2_interpreter::dice::starlark_types::SetStarlarkTypes;
+use buck2_interpreter::extra::host_toolchains::HostToolchains;
 use buck2_interpreter::extra::InterpreterHostArchitecture;
 use buck2_interpreter::extra::InterpreterHostPlatform;
 use buck2_interpreter::load_module::InterpreterCalculation;
+use buck2_interpreter::starlark_profiler::StarlarkProfilerOrInstrumentation;
 use buck2_interpreter_for_build::attrs::attrs_global::register_attrs;
 use buck2_interpreter_for_build::interpreter::configuror::BuildInterpreterConfiguror;
 use buck2_interpreter_for_build::interpreter::context::SetInterpreterContext;
+use buck2_interpreter_for_build::interpreter::dice_calculation_delegate::HasCalculationDelegate;
 use buck2_interpreter_for_build::rule::register_rule_function;
 use buck2_interpreter_for_build::super_package::defs::register_package_natives;
 use buck2_interpreter_for_build::super_package::package_value::register_read_package_value;
@@ -80,6 +86,7 @@ pub(crate) async fn calculation(fs: &ProjectRootTemp) -> DiceTransaction {
             InterpreterHostPlatform::Linux,
             InterpreterHostArchitecture::X86_64,
             None,
+            HostToolchains::default(),
             false,
             false,
             register_read_package_value,
@@ -246,3 +253,75 @@ async fn test_eval_build_file() {
 
     assert_eq!(vec!["invoke_some-exported", "java"], target_names);
 }
+
+#[tokio::test]
+async fn test_eval_build_file_stats() {
+    let fs = ProjectRootTemp::new().unwrap();
+
+    fs.write_file(
+        "rules.bzl",
+        indoc!(
+            r#"
+                def _impl(ctx):
+                    return DefaultInfo()
+
+                def _spin():
+                    n = 0
+                    for i in range(2000000):
+                        n += i
+                    return n
+
+                _unused = _spin()
+
+                java_library = rule(
+                    impl = _impl,
+                    attrs = {
+                        "srcs": attrs.list(attrs.string()),
+                    },
+                )
+        "#
+        ),
+    );
+    fs.write_file("pkg/file1.java", "");
+    fs.write_file("pkg/file2.java", "");
+    fs.write_file(
+        "pkg/BUCK",
+        indoc!(
+            r#"
+                load("//rules.bzl", "java_library")
+
+                java_library(
+                    name = "java",
+                    srcs = glob(["*.java"]) + glob(["*.kt"]),
+                )
+            "#
+        ),
+    );
+
+    let ctx = calculation(&fs).await;
+
+    let package = PackageLabel::testing_parse("root//pkg");
+    let calculation = ctx
+        .get_interpreter_calculator(package.cell_name(), BuildFileCell::new(package.cell_name()))
+        .await
+        .unwrap();
+    let start = Instant::now();
+    let stats = calculation
+        .eval_build_file_with_stats(
+            package.dupe(),
+            &mut StarlarkProfilerOrInstrumentation::disabled(),
+        )
+        .await
+        .unwrap();
+    let total = start.elapsed();
+
+    assert_eq!(2, stats.glob_count);
+    assert!(stats.glob_duration <= stats.evaluation_duration);
+    // Loading `rules.bzl` is much slower than evaluating the `BUCK` file, and is not counted.
+    assert!(
+        stats.evaluation_duration < total / 2,
+        "{:?} {:?}",
+        stats.evaluation_duration,
+        total
+    );
+}
@@ -0,0 +1,123 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use buck2_core::bzl::ImportPath;
+use buck2_interpreter::functions::transition::REGISTER_TRANSITION;
+use buck2_interpreter_for_build::interpreter::testing::Tester;
+use indoc::indoc;
+
+fn tester() -> Tester {
+    let mut tester = Tester::new().unwrap();
+    tester.additional_globals(|globals| (REGISTER_TRANSITION.get().unwrap())(globals));
+    tester
+}
+
+const TRANSITIONS: &str = r#"
+def _impl(platform, refs):
+    return platform
+
+def _impl_with_attrs(platform, refs, attrs):
+    return platform
+
+os = transition(impl = _impl, refs = {"os": "root//config:os"})
+cpu = transition(
+    impl = _impl_with_attrs,
+    refs = {"cpu": "root//config:cpu", "os": "root//config:os"},
+    attrs = ["flavor"],
+)
+split = transition(impl = _impl, refs = {}, split = True)
+other_os = transition(impl = _impl, refs = {"os": "root//other:os"})
+"#;
+
+#[test]
+fn test_composed_transition() {
+    let mut tester = tester();
+    tester
+        .add_import(
+            &ImportPath::testing_new("root//transitions:defs.bzl"),
+            TRANSITIONS,
+        )
+        .unwrap();
+    tester
+        .run_starlark_bzl_test(indoc!(
+            r#"
+            load("//transitions:defs.bzl", "cpu", "os")
+
+            def _impl(platform, refs):
+                return platform
+
+            local = transition(impl = _impl, refs = {})
+
+            # Transitions from this module and loaded ones, and composed ones.
+            os_cpu = composed_transition([os, cpu])
+            os_cpu_local = composed_transition((os_cpu, local))
+
+            # Composed transitions can be used wherever transitions can.
+            r = rule(impl = lambda ctx: [], attrs = {}, cfg = os_cpu_local)
+
+            def test():
+                assert_eq("transition", type(os_cpu))
+                assert_eq("transition", type(os_cpu_local))
+            "#
+        ))
+        .unwrap();
+}
+
+#[test]
+fn test_composed_transition_errors() {
+    let mut tester = tester();
+    tester
+        .add_import(
+            &ImportPath::testing_new("root//transitions:defs.bzl"),
+            TRANSITIONS,
+        )
+        .unwrap();
+    tester.run_starlark_bzl_test_expecting_error(
+        indoc!(
+            r#"
+            def test():
+                composed_transition([])
+            "#
+        ),
+        "expects at least one transition",
+    );
+    tester.run_starlark_bzl_test_expecting_error(
+        indoc!(
+            r#"
+            load("//transitions:defs.bzl", "os")
+
+            def test():
+                composed_transition([os, "not a transition"])
+            "#
+        ),
+        "expects a list of transitions, got `\"not a transition\"`",
+    );
+    tester.run_starlark_bzl_test_expecting_error(
+        indoc!(
+            r#"
+            load("//transitions:defs.bzl", "os", "split")
+
+            def test():
+                composed_transition([os, split])
+            "#
+        ),
+        "Split transitions cannot be composed",
+    );
+    tester.run_starlark_bzl_test_expecting_error(
+        indoc!(
+            r#"
+            load("//transitions:defs.bzl", "os", "other_os")
+
+            def test():
+                composed_transition([os, other_os])
+            "#
+        ),
+        "different targets for the ref `os`: `root//config:os` and `root//other:os`",
+    );
+}
@@ -12,6 +12,7 @@ use std::fmt::Display;
 use std::sync::Arc;
 
 use allocative::Allocative;
+use buck2_core::deprecation::Deprecation;
 
 use crate::attrs::attr_type::AttrType;
 use crate::attrs::coerced_attr::CoercedAttr;
@@ -34,6 +35,8 @@ pub struct Attribute {
     /// The coercer to take this parameter's value from Starlark value -> an
     /// internal representation
     coercer: AttrType,
+    /// Set for attributes which should no longer be given a value.
+    deprecation: Option<Arc<Deprecation>>,
 }
 
 impl Attribute {
@@ -45,6 +48,7 @@ impl Attribute {
             },
             doc: doc.to_owned(),
             coercer,
+            deprecation: None,
         }
     }
 
@@ -53,6 +57,7 @@ impl Attribute {
             default: AttributeDefault::DefaultOnly(default),
             doc: doc.to_owned(),
             coercer,
+            deprecation: None,
         }
     }
 
@@ -75,6 +80,17 @@ impl Attribute {
     pub fn doc(&self) -> &str {
         &self.doc
     }
+
+    pub fn with_deprecation(self, deprecation: Deprecation) -> Self {
+        Attribute {
+            deprecation: Some(Arc::new(deprecation)),
+            ..self
+        }
+    }
+
+    pub fn deprecation(&self) -> Option<&Deprecation> {
+        self.deprecation.as_deref()
+    }
 }
 
 impl Display for Attribute {
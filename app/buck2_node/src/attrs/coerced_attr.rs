@@ -74,6 +74,11 @@ enum SelectError {
     )]
     #[buck2(user)]
     TwoKeysDoNotRefineEachOther(String, String),
+    #[error(
+        "The select resolver chose `{0}`, which is not one of the matching keys of the select: {1}"
+    )]
+    #[buck2(user)]
+    ResolverChoseNonMatchingKey(String, String),
     #[error("concat with no items (internal error)")]
     ConcatEmpty,
     #[error("duplicate key `{0}` in `select()`")]
@@ -481,6 +486,13 @@ impl CoercedAttr {
         ctx: &dyn AttrConfigurationContext,
         select_entries: &'a [(TargetLabel, CoercedAttr)],
     ) -> anyhow::Result<Option<&'a CoercedAttr>> {
+        Ok(Self::select_the_most_specific_entry(ctx, select_entries)?.map(|(_k, v)| v))
+    }
+
+    fn select_the_most_specific_entry<'a>(
+        ctx: &dyn AttrConfigurationContext,
+        select_entries: &'a [(TargetLabel, CoercedAttr)],
+    ) -> anyhow::Result<Option<(&'a TargetLabel, &'a CoercedAttr)>> {
         let mut matching: Option<(&TargetLabel, &ConfigSettingData, &CoercedAttr)> = None;
         for (k, v) in select_entries {
             matching = match (ctx.matches(k), matching) {
@@ -492,34 +504,125 @@ impl CoercedAttr {
                     } else if prev_conf.refines(conf) {
                         Some((prev_k, prev_conf, prev_v))
                     } else {
-                        return Err(SelectError::TwoKeysDoNotRefineEachOther(
-                            prev_k.to_string(),
-                            k.to_string(),
-                        )
-                        .into());
+                        return Self::resolve_ambiguous_select(ctx, select_entries, prev_k, k)
+                            .map(Some);
                     }
                 }
             }
         }
-        Ok(matching.map(|(_k, _conf, v)| v))
+        Ok(matching.map(|(k, _conf, v)| (k, v)))
+    }
+
+    /// When the matching keys `k1` and `k2` of a select don't refine each other, ask the select
+    /// resolver to choose among all the matching keys.
+    fn resolve_ambiguous_select<'a>(
+        ctx: &dyn AttrConfigurationContext,
+        select_entries: &'a [(TargetLabel, CoercedAttr)],
+        k1: &TargetLabel,
+        k2: &TargetLabel,
+    ) -> anyhow::Result<(&'a TargetLabel, &'a CoercedAttr)> {
+        let ambiguous = || SelectError::TwoKeysDoNotRefineEachOther(k1.to_string(), k2.to_string());
+        let Some(resolver) = ctx.select_resolver() else {
+            return Err(ambiguous().into());
+        };
+        let matching: Vec<&(TargetLabel, CoercedAttr)> = select_entries
+            .iter()
+            .filter(|(k, _)| ctx.matches(k).is_some())
+            .collect();
+        let keys: Vec<&TargetLabel> = matching.iter().map(|(k, _)| k).collect();
+        let Some(chosen) = resolver.resolve(&keys)? else {
+            return Err(ambiguous().into());
+        };
+        match matching.into_iter().find(|(k, _)| *k == chosen) {
+            Some((k, v)) => Ok((k, v)),
+            None => Err(SelectError::ResolverChoseNonMatchingKey(
+                chosen.to_string(),
+                keys.iter().map(|k| k.to_string()).join(", "),
+            )
+            .into()),
+        }
     }
 
     fn select<'a>(
         ctx: &dyn AttrConfigurationContext,
         select: &'a CoercedSelector,
     ) -> anyhow::Result<&'a CoercedAttr> {
+        Ok(Self::select_entry(ctx, select)?.1)
+    }
+
+    fn select_entry<'a>(
+        ctx: &dyn AttrConfigurationContext,
+        select: &'a CoercedSelector,
+    ) -> anyhow::Result<(CoercedSelectorKeyRef<'a>, &'a CoercedAttr)> {
         let CoercedSelector { entries, default } = select;
-        if let Some(v) = Self::select_the_most_specific(ctx, entries)? {
-            Ok(v)
+        if let Some((k, v)) = Self::select_the_most_specific_entry(ctx, entries)? {
+            Ok((CoercedSelectorKeyRef::Target(k), v))
         } else {
-            default.as_ref().ok_or_else(|| {
-                SelectError::MissingDefault(
+            match default {
+                Some(default) => Ok((CoercedSelectorKeyRef::Default, default)),
+                None => Err(SelectError::MissingDefault(
                     ctx.cfg().cfg().dupe(),
                     entries.iter().map(|(k, _)| k).duped().collect(),
                 )
-                .into()
-            })
+                .into()),
+            }
+        }
+    }
+
+    /// The keys of the conditions chosen by the `select()`s of this attribute in the provided
+    /// context, `DEFAULT` for the default, in the order the `select()`s appear. Only the
+    /// `select()`s within the chosen branches are resolved.
+    pub fn resolved_select_keys(
+        &self,
+        ctx: &dyn AttrConfigurationContext,
+    ) -> anyhow::Result<Vec<String>> {
+        fn go(
+            attr: &CoercedAttr,
+            ctx: &dyn AttrConfigurationContext,
+            keys: &mut Vec<String>,
+        ) -> anyhow::Result<()> {
+            match attr {
+                CoercedAttr::Selector(s) => {
+                    let (key, value) = CoercedAttr::select_entry(ctx, s)?;
+                    keys.push(match key {
+                        CoercedSelectorKeyRef::Target(k) => k.to_string(),
+                        CoercedSelectorKeyRef::Default => "DEFAULT".to_owned(),
+                    });
+                    go(value, ctx, keys)
+                }
+                CoercedAttr::Concat(items) => {
+                    for item in &**items {
+                        go(item, ctx, keys)?;
+                    }
+                    Ok(())
+                }
+                CoercedAttr::List(list) => {
+                    for item in list.iter() {
+                        go(item, ctx, keys)?;
+                    }
+                    Ok(())
+                }
+                CoercedAttr::Tuple(tuple) => {
+                    for item in tuple.iter() {
+                        go(item, ctx, keys)?;
+                    }
+                    Ok(())
+                }
+                CoercedAttr::Dict(dict) => {
+                    for (k, v) in dict.iter() {
+                        go(k, ctx, keys)?;
+                        go(v, ctx, keys)?;
+                    }
+                    Ok(())
+                }
+                CoercedAttr::OneOf(box l, _) => go(l, ctx, keys),
+                _ => Ok(()),
+            }
         }
+
+        let mut keys = Vec::new();
+        go(self, ctx, &mut keys)?;
+        Ok(keys)
     }
 
     /// Returns the "configured" representation of the attribute in the provided context.
@@ -25,6 +25,8 @@ use starlark_map::sorted_map::SortedMap;
 
 use crate::configuration::resolved::ConfigurationSettingKeyRef;
 use crate::configuration::resolved::ResolvedConfiguration;
+use crate::select_resolver::SelectResolver;
+use crate::select_resolver::SelectResolverImpl;
 
 #[derive(Debug, buck2_error::Error)]
 pub enum PlatformConfigurationError {
@@ -51,6 +53,12 @@ pub trait AttrConfigurationContext {
     /// using current node configuration as input.
     fn resolved_transitions(&self) -> &OrderedMap<Arc<TransitionId>, Arc<TransitionApplied>>;
 
+    /// Resolves the `select()`s with several matching conditions none of which is more specific
+    /// than all the others. Such `select()`s are errors without one.
+    fn select_resolver(&self) -> Option<&dyn SelectResolverImpl> {
+        None
+    }
+
     fn configure_target(&self, label: &ProvidersLabel) -> ConfiguredProvidersLabel {
         label.configure_pair(self.cfg().cfg_pair().dupe())
     }
@@ -103,6 +111,7 @@ pub struct AttrConfigurationContextImpl<'b> {
     toolchain_cfg: ConfigurationWithExec,
     resolved_transitions: &'b OrderedMap<Arc<TransitionId>, Arc<TransitionApplied>>,
     platform_cfgs: &'b OrderedMap<TargetLabel, ConfigurationData>,
+    select_resolver: Option<&'b SelectResolver>,
 }
 
 impl<'b> AttrConfigurationContextImpl<'b> {
@@ -111,6 +120,7 @@ impl<'b> AttrConfigurationContextImpl<'b> {
         exec_cfg: ConfigurationNoExec,
         resolved_transitions: &'b OrderedMap<Arc<TransitionId>, Arc<TransitionApplied>>,
         platform_cfgs: &'b OrderedMap<TargetLabel, ConfigurationData>,
+        select_resolver: Option<&'b SelectResolver>,
     ) -> AttrConfigurationContextImpl<'b> {
         AttrConfigurationContextImpl {
             resolved_cfg,
@@ -118,6 +128,7 @@ impl<'b> AttrConfigurationContextImpl<'b> {
             exec_cfg,
             resolved_transitions,
             platform_cfgs,
+            select_resolver,
         }
     }
 }
@@ -152,4 +163,8 @@ impl<'b> AttrConfigurationContext for AttrConfigurationContextImpl<'b> {
     fn resolved_transitions(&self) -> &OrderedMap<Arc<TransitionId>, Arc<TransitionApplied>> {
         self.resolved_transitions
     }
+
+    fn select_resolver(&self) -> Option<&dyn SelectResolverImpl> {
+        self.select_resolver.map(|r| &*r.0)
+    }
 }
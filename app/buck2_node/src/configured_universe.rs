@@ -18,6 +18,7 @@ use buck2_core::pattern::pattern_type::PatternType;
 use buck2_core::pattern::pattern_type::TargetPatternExtra;
 use buck2_core::pattern::PackageSpec;
 use buck2_core::provider::label::ConfiguredProvidersLabel;
+use buck2_core::target::configured_target_label::ConfiguredTargetLabel;
 use buck2_core::target::label::TargetLabel;
 use buck2_core::target::name::TargetNameRef;
 use buck2_events::dispatch::span;
@@ -67,6 +68,7 @@ impl<'a> CqueryUniverseInner<'a> {
 
     fn build_inner(
         universe: &'a TargetSet<ConfiguredTargetNode>,
+        excluded: &ResolvedPattern<TargetPatternExtra>,
     ) -> anyhow::Result<CqueryUniverseInner<'a>> {
         let mut targets: BTreeMap<
             PackageLabel,
@@ -75,6 +77,9 @@ impl<'a> CqueryUniverseInner<'a> {
 
         configured_node_visit_all_deps(universe.iter().map(|t| t.as_ref()), |target| {
             let label = target.label();
+            if is_excluded(excluded, label) {
+                return;
+            }
             let package_targets: &mut _ = targets
                 .entry(label.pkg().dupe())
                 .or_insert_with(BTreeMap::new);
@@ -94,6 +99,19 @@ impl<'a> CqueryUniverseInner<'a> {
     }
 }
 
+fn is_excluded(
+    excluded: &ResolvedPattern<TargetPatternExtra>,
+    label: &ConfiguredTargetLabel,
+) -> bool {
+    match excluded.specs.get(&label.pkg()) {
+        None => false,
+        Some(PackageSpec::All) => true,
+        Some(PackageSpec::Targets(names)) => names
+            .iter()
+            .any(|(name, extra)| name.as_ref() == label.name() && extra.matches_cfg(label.cfg())),
+    }
+}
+
 impl CqueryUniverse {
     pub fn len(&self) -> usize {
         self.data
@@ -113,9 +131,18 @@ impl CqueryUniverse {
     }
 
     pub fn build(universe: &TargetSet<ConfiguredTargetNode>) -> anyhow::Result<CqueryUniverse> {
+        Self::build_excluding(universe, &ResolvedPattern::new())
+    }
+
+    /// The universe rooted at `universe`, without the targets matching `excluded`. Deps of the
+    /// excluded targets are still part of the universe.
+    pub fn build_excluding(
+        universe: &TargetSet<ConfiguredTargetNode>,
+        excluded: &ResolvedPattern<TargetPatternExtra>,
+    ) -> anyhow::Result<CqueryUniverse> {
         span(buck2_data::CqueryUniverseBuildStart {}, || {
             let r = SelfRef::try_new(universe.clone(), |universe| {
-                CqueryUniverseInner::build_inner(universe)
+                CqueryUniverseInner::build_inner(universe, excluded)
             })
             .map(|data| CqueryUniverse { data });
             (r, buck2_data::CqueryUniverseBuildEnd {})
@@ -249,6 +276,7 @@ mod tests {
     use buck2_core::package::PackageLabel;
     use buck2_core::pattern::pattern_type::ConfigurationPredicate;
     use buck2_core::pattern::pattern_type::ConfiguredProvidersPatternExtra;
+    use buck2_core::pattern::pattern_type::TargetPatternExtra;
     use buck2_core::pattern::PackageSpec;
     use buck2_core::provider::label::ConfiguredProvidersLabel;
     use buck2_core::provider::label::NonDefaultProvidersName;
@@ -337,4 +365,34 @@ mod tests {
             )))
         );
     }
+
+    #[tokio::test]
+    async fn test_build_excluding() {
+        let node = |label: &str| {
+            ConfiguredTargetNode::testing_new(
+                ConfiguredTargetLabel::testing_parse(label, ConfigurationData::testing_new()),
+                "idris_library",
+            )
+        };
+        let roots =
+            TargetSet::from_iter([node("foo//bar:baz"), node("foo//bar:qux"), node("foo//x:y")]);
+
+        let mut excluded = ResolvedPattern::new();
+        excluded.add_target(
+            PackageLabel::testing_parse("foo//bar"),
+            TargetName::unchecked_new("qux"),
+            TargetPatternExtra,
+        );
+        let universe = CqueryUniverse::build_excluding(&roots, &excluded).unwrap();
+        assert_eq!(2, universe.len());
+        assert!(
+            universe
+                .iter()
+                .all(|node| node.label().name().as_str() != "qux")
+        );
+
+        excluded.add_package(PackageLabel::testing_parse("foo//x"));
+        let universe = CqueryUniverse::build_excluding(&roots, &excluded).unwrap();
+        assert_eq!(1, universe.len());
+    }
 }
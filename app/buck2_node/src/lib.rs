@@ -20,10 +20,12 @@ pub mod metadata;
 pub mod nodes;
 pub mod package;
 pub mod package_values_calculation;
+pub mod platform_resolver;
 pub mod provider_id_set;
 pub mod query;
 pub mod rule;
 pub mod rule_type;
+pub mod select_resolver;
 pub mod super_package;
 pub mod target_calculation;
 pub mod visibility;
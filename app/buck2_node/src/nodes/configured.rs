@@ -62,11 +62,16 @@ use crate::nodes::attributes::DEPS;
 use crate::nodes::attributes::EXECUTION_PLATFORM;
 use crate::nodes::attributes::ONCALL;
 use crate::nodes::attributes::PACKAGE;
+use crate::nodes::attributes::PACKAGE_VALUES;
+use crate::nodes::attributes::PACKAGE_VISIBILITY;
+use crate::nodes::attributes::PACKAGE_WITHIN_VIEW;
 use crate::nodes::attributes::PLUGINS;
 use crate::nodes::attributes::TARGET_CONFIGURATION;
 use crate::nodes::attributes::TYPE;
+use crate::nodes::configured_selects::ConfiguredSelects;
 use crate::nodes::unconfigured::RuleKind;
 use crate::nodes::unconfigured::TargetNode;
+use crate::package::Package;
 use crate::provider_id_set::ProviderIdSet;
 use crate::rule_type::RuleType;
 use crate::rule_type::StarlarkRuleType;
@@ -185,6 +190,7 @@ struct ConfiguredTargetNodeData {
     // TODO(JakobDegen): Consider saving some memory by using a more tset like representation of
     // the plugin lists
     plugin_lists: PluginLists,
+    configured_selects: ConfiguredSelects,
 }
 
 impl Debug for ConfiguredTargetNodeData {
@@ -198,6 +204,14 @@ impl Debug for ConfiguredTargetNodeData {
 impl ConfiguredTargetNode {
     /// Creates a minimal ConfiguredTargetNode. Some operations may unexpectedly fail.
     pub fn testing_new(name: ConfiguredTargetLabel, rule_type: &str) -> Self {
+        Self::testing_new_with_deps(name, rule_type, Vec::new())
+    }
+
+    pub fn testing_new_with_deps(
+        name: ConfiguredTargetLabel,
+        rule_type: &str,
+        deps: Vec<ConfiguredTargetNode>,
+    ) -> Self {
         use crate::nodes::unconfigured::testing::TargetNodeExt;
 
         let rule_type = RuleType::Starlark(Arc::new(StarlarkRuleType {
@@ -215,7 +229,7 @@ impl ConfiguredTargetNode {
             ),
             OrderedMap::new(),
             execution_platform_resolution,
-            Vec::new(),
+            deps,
             Vec::new(),
             OrderedMap::new(),
             PluginLists::new(),
@@ -242,6 +256,7 @@ impl ConfiguredTargetNode {
             all_deps: ConfiguredTargetNodeDeps::new(deps, exec_deps),
             platform_cfgs,
             plugin_lists,
+            configured_selects: ConfiguredSelects::default(),
         })))
     }
 
@@ -297,6 +312,7 @@ impl ConfiguredTargetNode {
                 plugin_lists: transitioned_node.plugin_lists().clone(),
                 all_deps: ConfiguredTargetNodeDeps::new(vec![transitioned_node], vec![]),
                 platform_cfgs: OrderedMap::new(),
+                configured_selects: ConfiguredSelects::default(),
             },
         ))))
     }
@@ -432,6 +448,11 @@ impl ConfiguredTargetNode {
         self.as_ref().oncall()
     }
 
+    #[inline]
+    pub fn package(&self) -> &Package {
+        self.as_ref().package()
+    }
+
     pub fn attrs<'a>(
         &'a self,
         opts: AttrInspectOptions,
@@ -447,6 +468,12 @@ impl ConfiguredTargetNode {
         self.as_ref().get(attr, opts)
     }
 
+    /// The keys chosen by the `select()`s of each attribute with any, see
+    /// [`CoercedAttr::resolved_select_keys`].
+    pub fn resolved_selects(&self) -> Vec<(&str, Vec<String>)> {
+        self.as_ref().resolved_selects()
+    }
+
     pub fn call_stack(&self) -> Option<String> {
         match &self.0.target_node {
             TargetNodeOrForward::TargetNode(n) => n.call_stack(),
@@ -611,6 +638,7 @@ impl<'a> ConfiguredTargetNodeRef<'a> {
             self.0.get().execution_platform_resolution.cfg(),
             &self.0.get().resolved_transition_configurations,
             &self.0.get().platform_cfgs,
+            self.package().select_resolver.as_ref(),
         )
     }
 
@@ -618,6 +646,10 @@ impl<'a> ConfiguredTargetNodeRef<'a> {
         self.0.get().target_node.oncall()
     }
 
+    pub fn package(self) -> &'a Package {
+        self.0.get().target_node.package()
+    }
+
     pub fn special_attrs(self) -> impl Iterator<Item = (&'a str, ConfiguredAttr)> {
         let typ_attr = ConfiguredAttr::String(StringLiteral(self.rule_type().name().into()));
         let deps_attr = ConfiguredAttr::List(
@@ -644,6 +676,18 @@ impl<'a> ConfiguredTargetNodeRef<'a> {
                     Some(x) => ConfiguredAttr::String(StringLiteral(ArcStr::from(x))),
                 },
             ),
+            (
+                PACKAGE_VALUES,
+                ConfiguredAttr::Metadata(self.package().package_values.clone()),
+            ),
+            (
+                PACKAGE_VISIBILITY,
+                ConfiguredAttr::Visibility(self.package().visibility.dupe()),
+            ),
+            (
+                PACKAGE_WITHIN_VIEW,
+                ConfiguredAttr::WithinView(self.package().within_view.dupe()),
+            ),
             (
                 TARGET_CONFIGURATION,
                 ConfiguredAttr::String(StringLiteral(ArcStr::from(self.0.label.cfg().to_string()))),
@@ -666,17 +710,74 @@ impl<'a> ConfiguredTargetNodeRef<'a> {
         self,
         opts: AttrInspectOptions,
     ) -> impl Iterator<Item = ConfiguredAttrFull<'a>> + 'a {
-        self.0.get().target_node.attrs(opts).map(move |a| {
-            a.configure(&self.attr_configuration_context())
-                .expect("checked attr configuration in constructor")
-        })
+        self.0
+            .get()
+            .target_node
+            .attrs(opts)
+            .map(move |a| self.configure_attr(a))
     }
 
     pub fn get(self, attr: &str, opts: AttrInspectOptions) -> Option<ConfiguredAttrFull<'a>> {
-        self.0.get().target_node.attr_or_none(attr, opts).map(|v| {
-            v.configure(&self.attr_configuration_context())
-                .expect("checked attr configuration in constructor")
-        })
+        self.0
+            .get()
+            .target_node
+            .attr_or_none(attr, opts)
+            .map(|a| self.configure_attr(a))
+    }
+
+    fn configure_attr(self, a: CoercedAttrFull<'a>) -> ConfiguredAttrFull<'a> {
+        if ConfiguredSelects::has_select(a.value) {
+            let configured = self
+                .0
+                .get()
+                .configured_selects
+                .get(a.value, || self.configure_selects());
+            if let Some(value) = configured {
+                return ConfiguredAttrFull {
+                    name: a.name,
+                    attr: a.attr,
+                    value: value.clone(),
+                };
+            }
+        }
+        a.configure(&self.attr_configuration_context())
+            .expect("checked attr configuration in constructor")
+    }
+
+    fn configure_selects(self) -> Vec<(usize, ConfiguredAttr)> {
+        let ctx = self.attr_configuration_context();
+        self.0
+            .get()
+            .target_node
+            .attrs(AttrInspectOptions::All)
+            .filter(|a| ConfiguredSelects::has_select(a.value))
+            .map(|a| {
+                let configured = a
+                    .configure(&ctx)
+                    .expect("checked attr configuration in constructor");
+                (ConfiguredSelects::key(a.value), configured.value)
+            })
+            .collect()
+    }
+
+    pub fn resolved_selects(self) -> Vec<(&'a str, Vec<String>)> {
+        let ctx = self.attr_configuration_context();
+        self.0
+            .get()
+            .target_node
+            .attrs(AttrInspectOptions::All)
+            .filter_map(|a| {
+                let keys = a
+                    .value
+                    .resolved_select_keys(&ctx)
+                    .expect("checked attr configuration in constructor");
+                if keys.is_empty() {
+                    None
+                } else {
+                    Some((a.name, keys))
+                }
+            })
+            .collect()
     }
 
     pub fn inputs(self) -> impl Iterator<Item = CellPath> + 'a {
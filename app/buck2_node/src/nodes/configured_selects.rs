@@ -0,0 +1,159 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! The configured values of the attributes of a configured target node which use `select()`.
+//!
+//! Configured attributes are created on demand, which for most attributes is a cheap copy. An
+//! attribute with a `select()` resolves it on every access though, and the attributes of a node
+//! are read several times (analysis, target hash, queries), so a node configures those attributes
+//! the first time any attribute is read and keeps them.
+//!
+//! Targets defined by the same macro often resolve a `select()` to the same value, so the lists,
+//! tuples and dicts kept by nodes are interned, and all those nodes share one copy. Interned values
+//! are never freed, so at most `BUCK2_CONFIGURED_SELECTS_MAX_INTERNED` values are interned.
+
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use allocative::Allocative;
+use buck2_core::buck2_env;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use once_cell::sync::OnceCell;
+
+use crate::attrs::coerced_attr::CoercedAttr;
+use crate::attrs::configured_attr::ConfiguredAttr;
+
+const DEFAULT_MAX_INTERNED: usize = 1 << 20;
+
+/// The configured attributes with a `select()`, keyed by the address of their coerced value, which
+/// the node owns. Doesn't take part in the equality or the hash of the node, since it is derived
+/// from the rest of it.
+#[derive(Default, Allocative)]
+pub(crate) struct ConfiguredSelects(OnceCell<Box<[(usize, ConfiguredAttr)]>>);
+
+impl PartialEq for ConfiguredSelects {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for ConfiguredSelects {}
+
+impl Hash for ConfiguredSelects {
+    fn hash<H: Hasher>(&self, _state: &mut H) {}
+}
+
+impl ConfiguredSelects {
+    /// Whether the configured value of `value` is kept by the node.
+    pub(crate) fn has_select(value: &CoercedAttr) -> bool {
+        matches!(value, CoercedAttr::Selector(_) | CoercedAttr::Concat(_))
+    }
+
+    pub(crate) fn key(value: &CoercedAttr) -> usize {
+        value as *const CoercedAttr as usize
+    }
+
+    /// The configured value of `value`, configuring all the attributes with a `select()` with
+    /// `configure` on the first call.
+    pub(crate) fn get(
+        &self,
+        value: &CoercedAttr,
+        configure: impl FnOnce() -> Vec<(usize, ConfiguredAttr)>,
+    ) -> Option<&ConfiguredAttr> {
+        let key = Self::key(value);
+        self.0
+            .get_or_init(|| {
+                configure()
+                    .into_iter()
+                    .map(|(key, value)| (key, intern(value)))
+                    .collect()
+            })
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| value)
+    }
+}
+
+static INTERNED: Lazy<DashMap<ConfiguredAttr, ()>> = Lazy::new(DashMap::new);
+static INTERNED_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+fn max_interned() -> usize {
+    static MAX_INTERNED: Lazy<usize> = Lazy::new(|| {
+        // An invalid value only loses the sharing, which is not worth failing the node for.
+        buck2_env!("BUCK2_CONFIGURED_SELECTS_MAX_INTERNED", type=usize)
+            .ok()
+            .flatten()
+            .unwrap_or(DEFAULT_MAX_INTERNED)
+    });
+    *MAX_INTERNED
+}
+
+/// A value equal to `value` sharing its allocation with the equal values interned before.
+fn intern(value: ConfiguredAttr) -> ConfiguredAttr {
+    let shared = match &value {
+        ConfiguredAttr::List(list) => !list.0.is_empty(),
+        ConfiguredAttr::Tuple(tuple) => !tuple.0.is_empty(),
+        ConfiguredAttr::Dict(dict) => !dict.0.is_empty(),
+        _ => false,
+    };
+    if !shared {
+        return value;
+    }
+    if let Some(interned) = INTERNED.get(&value) {
+        return interned.key().clone();
+    }
+    if INTERNED_COUNT.load(Ordering::Relaxed) >= max_interned() {
+        return value;
+    }
+    let interned = INTERNED.entry(value).or_insert_with(|| {
+        INTERNED_COUNT.fetch_add(1, Ordering::Relaxed);
+    });
+    interned.key().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::attrs::attr_type::list::ListLiteral;
+    use crate::attrs::attr_type::string::StringLiteral;
+    use crate::attrs::configured_attr::ConfiguredAttr;
+    use crate::nodes::configured_selects::intern;
+
+    fn list(items: &[&str]) -> ConfiguredAttr {
+        ConfiguredAttr::List(ListLiteral(
+            items
+                .iter()
+                .map(|s| ConfiguredAttr::String(StringLiteral((*s).into())))
+                .collect::<Vec<_>>()
+                .into(),
+        ))
+    }
+
+    fn list_ptr(value: &ConfiguredAttr) -> *const ConfiguredAttr {
+        match value {
+            ConfiguredAttr::List(list) => list.0.as_ptr(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_intern() {
+        let a = intern(list(&["-DFOO", "-DBAR"]));
+        let b = intern(list(&["-DFOO", "-DBAR"]));
+        let c = intern(list(&["-DFOO"]));
+        assert_eq!(a, b);
+        assert_eq!(list_ptr(&a), list_ptr(&b));
+        assert_ne!(a, c);
+        assert_ne!(list_ptr(&a), list_ptr(&c));
+
+        assert_eq!(ConfiguredAttr::Int(1), intern(ConfiguredAttr::Int(1)));
+    }
+}
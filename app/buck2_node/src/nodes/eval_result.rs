@@ -11,6 +11,7 @@ use std::collections::BTreeMap;
 use std::fmt::Display;
 use std::fmt::Write;
 use std::sync::Arc;
+use std::time::Duration;
 
 use allocative::Allocative;
 use buck2_core::build_file_path::BuildFilePath;
@@ -236,6 +237,13 @@ pub struct EvaluationResultWithStats {
     pub result: EvaluationResult,
     // Peak allocated memory in starlark mutable heap during evaluation of BUCK file
     pub starlark_peak_allocated_bytes: u64,
+    /// Number of `glob` calls made while evaluating the BUCK file.
+    pub glob_count: u64,
+    /// Time spent in `glob` calls.
+    pub glob_duration: Duration,
+    /// Time spent evaluating the BUCK file, including `glob` calls, but not loading the
+    /// modules it imports or listing the package.
+    pub evaluation_duration: Duration,
 }
 
 #[derive(Debug)]
@@ -12,6 +12,7 @@ pub mod configured_frontend;
 pub mod configured_node_ref;
 pub mod configured_node_visit_all_deps;
 pub mod configured_ref;
+pub(crate) mod configured_selects;
 pub mod eval_result;
 pub mod frontend;
 pub mod lookup;
@@ -54,6 +55,12 @@ pub mod attributes {
     /// The package values for the package this target belongs to.
     pub static PACKAGE_VALUES: &str = "buck.package_values";
 
+    /// The visibility set by `PACKAGE` files for the package this target belongs to.
+    pub static PACKAGE_VISIBILITY: &str = "buck.package_visibility";
+
+    /// The `within_view` set by `PACKAGE` files for the package this target belongs to.
+    pub static PACKAGE_WITHIN_VIEW: &str = "buck.package_within_view";
+
     /// The plugin lists on the node. This includes all plugins, regardless of whether they're
     /// propagated or actually used.
     pub static PLUGINS: &str = "buck.plugins";
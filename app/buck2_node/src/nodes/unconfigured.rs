@@ -41,6 +41,9 @@ use crate::nodes::attributes::CONFIGURATION_DEPS;
 use crate::nodes::attributes::DEPS;
 use crate::nodes::attributes::ONCALL;
 use crate::nodes::attributes::PACKAGE;
+use crate::nodes::attributes::PACKAGE_VALUES;
+use crate::nodes::attributes::PACKAGE_VISIBILITY;
+use crate::nodes::attributes::PACKAGE_WITHIN_VIEW;
 use crate::nodes::attributes::TYPE;
 use crate::package::Package;
 use crate::rule::Rule;
@@ -125,6 +128,11 @@ impl TargetNodeData {
         self.package.oncall.as_ref().map(|x| x.as_str())
     }
 
+    /// The package data, including what `PACKAGE` files applied to it.
+    pub fn package(&self) -> &Package {
+        &self.package
+    }
+
     pub fn call_stack(&self) -> Option<String> {
         self.call_stack.as_ref().map(|s| s.to_string())
     }
@@ -466,6 +474,18 @@ impl<'a> TargetNodeRef<'a> {
                     Some(x) => CoercedAttr::String(StringLiteral(ArcStr::from(x))),
                 },
             ),
+            (
+                PACKAGE_VALUES,
+                CoercedAttr::Metadata(self.package().package_values.clone()),
+            ),
+            (
+                PACKAGE_VISIBILITY,
+                CoercedAttr::Visibility(self.package().visibility.dupe()),
+            ),
+            (
+                PACKAGE_WITHIN_VIEW,
+                CoercedAttr::WithinView(self.package().within_view.dupe()),
+            ),
         ]
         .into_iter()
     }
@@ -607,6 +627,7 @@ pub mod testing {
     use crate::attrs::values::AttrValues;
     use crate::nodes::targets_map::TargetsMap;
     use crate::rule_type::RuleType;
+    use crate::visibility::WithinViewSpecification;
 
     pub trait TargetNodeExt {
         fn testing_new(
@@ -661,6 +682,10 @@ pub mod testing {
                 Arc::new(Package {
                     buildfile_path,
                     oncall: None,
+                    package_values: MetadataMap::default(),
+                    visibility: VisibilitySpecification::default(),
+                    within_view: WithinViewSpecification::default(),
+                    select_resolver: None,
                 }),
                 label,
                 attributes,
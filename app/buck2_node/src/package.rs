@@ -12,14 +12,24 @@ use std::sync::Arc;
 use allocative::Allocative;
 use buck2_core::build_file_path::BuildFilePath;
 
+use crate::metadata::map::MetadataMap;
+use crate::select_resolver::SelectResolver;
+use crate::visibility::VisibilitySpecification;
+use crate::visibility::WithinViewSpecification;
+
 /// Package-specific data for `TargetNode`.
-///
-/// (Note this has nothing to do with `PACKAGE` files which are not implemented
-/// at the moment of writing.)
 #[derive(Debug, Hash, Allocative, Eq, PartialEq)]
 pub struct Package {
     /// The build file which defined this target, e.g. `fbcode//foo/bar/TARGETS`
     pub buildfile_path: Arc<BuildFilePath>,
     /// The oncall attribute, if set
     pub oncall: Option<Arc<String>>,
+    /// The values set by `PACKAGE` files for this package, including package modifiers.
+    pub package_values: MetadataMap,
+    /// The visibility set by `PACKAGE` files, which targets extend.
+    pub visibility: VisibilitySpecification,
+    /// The `within_view` set by `PACKAGE` files, which targets extend.
+    pub within_view: WithinViewSpecification,
+    /// Resolves ambiguous `select()`s, set by `set_select_resolver()` in the root `PACKAGE` file.
+    pub select_resolver: Option<SelectResolver>,
 }
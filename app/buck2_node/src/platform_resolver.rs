@@ -0,0 +1,31 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::fmt::Debug;
+
+use allocative::Allocative;
+use async_trait::async_trait;
+use buck2_core::target::label::TargetLabel;
+use dice::DiceComputations;
+
+use crate::super_package::SuperPackage;
+
+/// Function registered with `set_platform_resolver()` in the repository root `PACKAGE` file,
+/// which computes the default target platform of targets without `default_target_platform`.
+#[async_trait]
+pub trait PlatformResolverImpl: Send + Sync + Debug + Allocative {
+    /// Returns the default target platform of `target`, or `None` to fall back to
+    /// `parser.target_platform_detector_spec`.
+    async fn resolve(
+        &self,
+        ctx: &mut DiceComputations<'_>,
+        target: &TargetLabel,
+        super_package: &SuperPackage,
+    ) -> anyhow::Result<Option<TargetLabel>>;
+}
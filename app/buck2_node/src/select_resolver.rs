@@ -0,0 +1,46 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::sync::Arc;
+
+use allocative::Allocative;
+use buck2_core::target::label::TargetLabel;
+use dupe::Dupe;
+
+/// Function registered with `set_select_resolver()` in the repository root `PACKAGE` file,
+/// which resolves the `select()`s with several matching conditions none of which is more
+/// specific than all the others.
+pub trait SelectResolverImpl: Send + Sync + Debug + Allocative {
+    /// Returns the key to choose among `keys`, the matching conditions of the select in the
+    /// order they were written, or `None` to fail as if no resolver was registered.
+    fn resolve(&self, keys: &[&TargetLabel]) -> anyhow::Result<Option<TargetLabel>>;
+}
+
+/// The select resolver of a package, compared by identity.
+#[derive(Debug, Clone, Dupe, Allocative)]
+pub struct SelectResolver(pub Arc<dyn SelectResolverImpl>);
+
+impl PartialEq for SelectResolver {
+    fn eq(&self, other: &Self) -> bool {
+        // The select resolver is inherited from the root package, so it is
+        // the same object unless the root `PACKAGE` file was evaluated again.
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for SelectResolver {}
+
+impl Hash for SelectResolver {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (Arc::as_ptr(&self.0) as *const () as usize).hash(state)
+    }
+}
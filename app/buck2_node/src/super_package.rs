@@ -14,6 +14,8 @@ use dupe::Dupe;
 
 use crate::cfg_constructor::CfgConstructorImpl;
 use crate::metadata::super_package_values::SuperPackageValues;
+use crate::platform_resolver::PlatformResolverImpl;
+use crate::select_resolver::SelectResolver;
 use crate::visibility::VisibilitySpecification;
 use crate::visibility::WithinViewSpecification;
 
@@ -24,6 +26,10 @@ pub(crate) struct SuperPackageData {
     within_view: WithinViewSpecification,
     /// Set only for the repo root package.
     cfg_constructor: Option<Arc<dyn CfgConstructorImpl>>,
+    /// Set in the repo root package, and inherited by all packages.
+    platform_resolver: Option<Arc<dyn PlatformResolverImpl>>,
+    /// Set in the repo root package, and inherited by all packages.
+    select_resolver: Option<SelectResolver>,
 }
 
 /// Contents of a `PACKAGE` file merged with contents of containing `PACKAGE` files.
@@ -37,12 +43,16 @@ impl SuperPackage {
         visibility: VisibilitySpecification,
         within_view: WithinViewSpecification,
         cfg_constructor: Option<Arc<dyn CfgConstructorImpl>>,
+        platform_resolver: Option<Arc<dyn PlatformResolverImpl>>,
+        select_resolver: Option<SelectResolver>,
     ) -> SuperPackage {
         SuperPackage(Arc::new(SuperPackageData {
             package_values,
             visibility,
             within_view,
             cfg_constructor,
+            platform_resolver,
+            select_resolver,
         }))
     }
 
@@ -52,6 +62,8 @@ impl SuperPackage {
             VisibilitySpecification::default(),
             WithinViewSpecification::default(),
             None,
+            None,
+            None,
         )
     }
 
@@ -70,6 +82,14 @@ impl SuperPackage {
     pub fn cfg_constructor(&self) -> Option<&Arc<dyn CfgConstructorImpl>> {
         self.0.cfg_constructor.as_ref()
     }
+
+    pub fn platform_resolver(&self) -> Option<&Arc<dyn PlatformResolverImpl>> {
+        self.0.platform_resolver.as_ref()
+    }
+
+    pub fn select_resolver(&self) -> Option<&SelectResolver> {
+        self.0.select_resolver.as_ref()
+    }
 }
 
 impl PartialEq for SuperPackage {
@@ -79,21 +99,35 @@ impl PartialEq for SuperPackage {
             visibility: this_visibility,
             within_view: this_within_view,
             cfg_constructor: this_cfg_constructor,
+            platform_resolver: this_platform_resolver,
+            select_resolver: this_select_resolver,
         } = &*self.0;
         let SuperPackageData {
             package_values: other_values,
             visibility: other_visibility,
             within_view: other_within_view,
             cfg_constructor: other_cfg_constructor,
+            platform_resolver: other_platform_resolver,
+            select_resolver: other_select_resolver,
         } = &*other.0;
-        (this_visibility, this_within_view) == (other_visibility, other_within_view) && {
-            // If either package values are not empty, we cannot compare them
-            // because we cannot reliably compare arbitrary Starlark values.
-            // So if either package values are not empty, we consider super package not equal.
-            this_values.is_empty() && other_values.is_empty()
+        (this_visibility, this_within_view, this_select_resolver)
+            == (other_visibility, other_within_view, other_select_resolver)
+            && {
+                // If either package values are not empty, we cannot compare them
+                // because we cannot reliably compare arbitrary Starlark values.
+                // So if either package values are not empty, we consider super package not equal.
+                this_values.is_empty() && other_values.is_empty()
             &&
                 // Same logic for cfg constructors.
                 this_cfg_constructor.is_none() && other_cfg_constructor.is_none()
-        }
+            &&
+                // The platform resolver is inherited from the root package, so it is
+                // the same object unless the root `PACKAGE` file was evaluated again.
+                match (this_platform_resolver, other_platform_resolver) {
+                    (None, None) => true,
+                    (Some(this), Some(other)) => Arc::ptr_eq(this, other),
+                    _ => false,
+                }
+            }
     }
 }
@@ -36,6 +36,33 @@ pub fn starlark_profiler_configuration_from_request(
         Profiler::Bytecode => ProfileMode::Bytecode,
         Profiler::BytecodePairs => ProfileMode::BytecodePairs,
         Profiler::Typecheck => ProfileMode::Typecheck,
+        Profiler::HeapRetention => {
+            // The retention report is computed from the frozen heaps of regular analysis
+            // results, so nothing needs to be instrumented.
+            return match req.profile_opts.as_ref().expect("Missing profile opts") {
+                ProfileOpts::TargetProfile(opts)
+                    if opts.action == buck2_cli_proto::target_profile::Action::Analysis as i32 =>
+                {
+                    Ok(StarlarkProfilerConfiguration::None)
+                }
+                _ => Err(anyhow::anyhow!(
+                    "Heap retention report is only supported when profiling analysis"
+                )),
+            };
+        }
+        Profiler::LoadingBreakdown => {
+            // Like the retention report, this is computed without instrumenting Starlark.
+            return match req.profile_opts.as_ref().expect("Missing profile opts") {
+                ProfileOpts::TargetProfile(opts)
+                    if opts.action == buck2_cli_proto::target_profile::Action::Loading as i32 =>
+                {
+                    Ok(StarlarkProfilerConfiguration::None)
+                }
+                _ => Err(anyhow::anyhow!(
+                    "Loading breakdown is only supported when profiling loading"
+                )),
+            };
+        }
     };
 
     match req.profile_opts.as_ref().expect("Missing profile opts") {
@@ -14,6 +14,7 @@ use std::sync::Arc;
 use buck2_build_api::query::oneshot::CqueryOwnerBehavior;
 use buck2_common::events::HasEvents;
 use buck2_common::global_cfg_options::GlobalCfgOptions;
+use buck2_common::pattern::resolve::ResolvedPattern;
 use buck2_core::fs::project_rel_path::ProjectRelativePath;
 use buck2_events::dispatch::console_message;
 use buck2_node::configured_universe::CqueryUniverse;
@@ -60,12 +61,27 @@ impl CqueryEvaluator<'_, '_> {
                     }
                     // In the absence of a user-provided target universe, we use the target
                     // literals in the cquery as the universe.
-                    resolve_literals_in_universe(&self.dice_query_delegate, self.dice_query_delegate.query_data().dupe(), &literals, &literals)
+                    resolve_literals_in_universe(&self.dice_query_delegate, self.dice_query_delegate.query_data().dupe(), &literals, &literals, &[], false)
                         .await?
                 }
                 Some(universe) => {
-                    resolve_literals_in_universe(&self.dice_query_delegate, self.dice_query_delegate.query_data().dupe(), &literals, universe)
-                        .await?
+                    // Patterns prefixed with `-` are subtracted from the universe.
+                    let mut included = Vec::new();
+                    let mut excluded = Vec::new();
+                    for pattern in universe {
+                        match pattern.as_ref().strip_prefix('-') {
+                            Some(pattern) => excluded.push(pattern),
+                            None => included.push(pattern.as_ref()),
+                        }
+                    }
+                    if included.is_empty() {
+                        // Only subtractions: subtract from the default universe.
+                        resolve_literals_in_universe(&self.dice_query_delegate, self.dice_query_delegate.query_data().dupe(), &literals, &literals, &excluded, false)
+                            .await?
+                    } else {
+                        resolve_literals_in_universe(&self.dice_query_delegate, self.dice_query_delegate.query_data().dupe(), &literals, &included, &excluded, true)
+                            .await?
+                    }
                 }
             };
             Ok(CqueryEnvironment::new(
@@ -112,12 +128,16 @@ pub(crate) async fn get_cquery_evaluator<'a, 'c: 'a, 'd>(
 }
 
 // This will first resolve the universe to configured nodes and then gather all
-// the deps. From there, it resolves the literals to any matching nodes in the universe deps.
+// the deps, except those matching `excluded`. From there, it resolves the literals to any
+// matching nodes in the universe deps. With `report_outside_universe`, literals which match
+// nothing in the universe are reported, since their result is silently empty otherwise.
 async fn resolve_literals_in_universe<L: AsRef<str>, U: AsRef<str>>(
     dice_query_delegate: &DiceQueryDelegate<'_, '_>,
     query_literals: Arc<DiceQueryData>,
     literals: &[L],
     universe: &[U],
+    excluded: &[&str],
+    report_outside_universe: bool,
 ) -> anyhow::Result<(
     CqueryUniverse,
     PreresolvedQueryLiterals<ConfiguredTargetNode>,
@@ -129,7 +149,15 @@ async fn resolve_literals_in_universe<L: AsRef<str>, U: AsRef<str>>(
         .eval_literals(&refs, dice_query_delegate.ctx())
         .await?;
 
-    let universe = CqueryUniverse::build(&universe_resolved)?;
+    let excluded = if excluded.is_empty() {
+        ResolvedPattern::new()
+    } else {
+        dice_query_delegate
+            .resolve_target_patterns(excluded)
+            .await?
+    };
+
+    let universe = CqueryUniverse::build_excluding(&universe_resolved, &excluded)?;
 
     // capture a reference so the ref can be moved into the future below.
     let universe_ref = &universe;
@@ -143,7 +171,16 @@ async fn resolve_literals_in_universe<L: AsRef<str>, U: AsRef<str>>(
             let lit = lit.as_ref();
             let result: anyhow::Result<_> = try {
                 let resolved_pattern = dice_query_delegate.resolve_target_patterns(&[lit]).await?;
-                universe_ref.get(&resolved_pattern)
+                let targets = universe_ref.get(&resolved_pattern);
+                if report_outside_universe && targets.is_empty() {
+                    console_message(format!(
+                        "Target pattern `{}` matches no targets in the target universe, \
+                        so it resolves to an empty set.\n\
+                        Consider adding it to `--target-universe`",
+                        lit
+                    ));
+                }
+                targets
             };
 
             (lit.to_owned(), result.map_err(buck2_error::Error::from))
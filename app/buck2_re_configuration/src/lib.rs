@@ -21,6 +21,36 @@ static BUCK2_RE_CLIENT_CFG_SECTION: &str = "buck2_re_client";
 pub trait RemoteExecutionStaticMetadataImpl: Sized {
     fn from_legacy_config(legacy_config: &LegacyBuckConfig) -> anyhow::Result<Self>;
     fn cas_semaphore_size(&self) -> usize;
+    fn transfer_limits(&self) -> &ReTransferLimits;
+}
+
+/// Caps on CAS transfers, e.g. so that builds don't saturate a slow office link. Uploads and
+/// downloads are limited separately.
+#[derive(Clone, Debug, Default, Allocative)]
+pub struct ReTransferLimits {
+    /// How many uploads can run concurrently.
+    pub max_concurrent_uploads: Option<usize>,
+    /// How many files can be downloading concurrently.
+    pub max_concurrent_downloads: Option<usize>,
+    /// Upload bandwidth, in bytes per second.
+    pub max_upload_bytes_per_second: Option<u64>,
+    /// Download bandwidth, in bytes per second.
+    pub max_download_bytes_per_second: Option<u64>,
+}
+
+impl ReTransferLimits {
+    pub fn from_legacy_config(legacy_config: &LegacyBuckConfig) -> anyhow::Result<Self> {
+        Ok(Self {
+            max_concurrent_uploads: legacy_config
+                .parse(BUCK2_RE_CLIENT_CFG_SECTION, "max_concurrent_uploads")?,
+            max_concurrent_downloads: legacy_config
+                .parse(BUCK2_RE_CLIENT_CFG_SECTION, "max_concurrent_downloads")?,
+            max_upload_bytes_per_second: legacy_config
+                .parse(BUCK2_RE_CLIENT_CFG_SECTION, "max_upload_bytes_per_second")?,
+            max_download_bytes_per_second: legacy_config
+                .parse(BUCK2_RE_CLIENT_CFG_SECTION, "max_download_bytes_per_second")?,
+        })
+    }
 }
 
 #[allow(unused)]
@@ -65,6 +95,8 @@ mod fbcode {
         // ttl management
         pub minimal_blob_ttl_seconds: Option<i64>,
         pub disable_fallocate: bool,
+
+        pub transfer_limits: ReTransferLimits,
     }
 
     impl RemoteExecutionStaticMetadataImpl for RemoteExecutionStaticMetadata {
@@ -141,11 +173,18 @@ mod fbcode {
                 disable_fallocate: legacy_config
                     .parse(BUCK2_RE_CLIENT_CFG_SECTION, "disable_fallocate")?
                     .unwrap_or(false),
+                transfer_limits: ReTransferLimits::from_legacy_config(legacy_config)?,
             })
         }
 
         fn cas_semaphore_size(&self) -> usize {
-            self.cas_connection_count as usize * 30
+            self.transfer_limits
+                .max_concurrent_uploads
+                .unwrap_or(self.cas_connection_count as usize * 30)
+        }
+
+        fn transfer_limits(&self) -> &ReTransferLimits {
+            &self.transfer_limits
         }
     }
 }
@@ -166,8 +205,14 @@ mod not_fbcode {
         }
 
         fn cas_semaphore_size(&self) -> usize {
-            // FIXME: make this configurable?
-            1024
+            self.0
+                .transfer_limits
+                .max_concurrent_uploads
+                .unwrap_or(1024)
+        }
+
+        fn transfer_limits(&self) -> &ReTransferLimits {
+            &self.0.transfer_limits
         }
     }
 }
@@ -206,6 +251,8 @@ pub struct Buck2OssReConfiguration {
     pub capabilities: Option<bool>,
     /// The instance name to use in requests.
     pub instance_name: Option<String>,
+    /// Caps on CAS transfers.
+    pub transfer_limits: ReTransferLimits,
 }
 
 #[derive(Clone, Debug, Default, Allocative)]
@@ -259,6 +306,7 @@ impl Buck2OssReConfiguration {
                 .unwrap_or_default(), // Empty list is as good None.
             capabilities: legacy_config.parse(BUCK2_RE_CLIENT_CFG_SECTION, "capabilities")?,
             instance_name: legacy_config.parse(BUCK2_RE_CLIENT_CFG_SECTION, "instance_name")?,
+            transfer_limits: ReTransferLimits::from_legacy_config(legacy_config)?,
         })
     }
 }
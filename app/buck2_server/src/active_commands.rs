@@ -20,6 +20,7 @@ use buck2_event_observer::span_tracker::Roots;
 use buck2_events::dispatch::EventDispatcher;
 use buck2_events::span::SpanId;
 use buck2_events::BuckEvent;
+use buck2_execute::execute::dispatch_gate::DispatchGate;
 use buck2_wrapper_common::invocation_id::TraceId;
 use dupe::Dupe;
 use once_cell::sync::Lazy;
@@ -86,24 +87,44 @@ impl ActiveCommandHandle {
     pub fn state(&self) -> &ActiveCommandState {
         self.state.as_ref()
     }
+
+    pub fn dispatcher(&self) -> &EventDispatcher {
+        &self.dispatcher
+    }
 }
 
 pub struct ActiveCommandDropGuard {
     trace_id: TraceId,
+    dispatch_gate: DispatchGate,
+}
+
+impl ActiveCommandDropGuard {
+    pub fn dispatch_gate(&self) -> &DispatchGate {
+        &self.dispatch_gate
+    }
 }
 
 impl Drop for ActiveCommandDropGuard {
     fn drop(&mut self) {
         ACTIVE_COMMANDS.lock().remove(&self.trace_id);
+        // DICE computations started by this command may outlive it and be shared with later
+        // commands, so they must not stay held back once the command that paused them is gone.
+        self.dispatch_gate.set_paused(false);
     }
 }
 
 /// A handle to the stats for this command. We use this to broadcast state about this command.
 pub struct ActiveCommandState {
-    #[allow(unused)]
     pub argv: Vec<String>,
 
     spans: Mutex<SpansSnapshot>,
+
+    /// The events that opened the root spans that are currently running. This is only read when
+    /// rendering a stack dump.
+    open_roots: Mutex<HashMap<SpanId, Arc<BuckEvent>>>,
+
+    /// Holds back new actions of this command when paused from the console.
+    dispatch_gate: DispatchGate,
 }
 
 impl ActiveCommandState {
@@ -111,10 +132,23 @@ impl ActiveCommandState {
         *self.spans.lock()
     }
 
+    /// The events that opened currently running root spans, oldest first.
+    pub fn open_roots(&self) -> Vec<Arc<BuckEvent>> {
+        let mut roots: Vec<_> = self.open_roots.lock().values().cloned().collect();
+        roots.sort_by_key(|e| e.timestamp());
+        roots
+    }
+
+    pub fn dispatch_gate(&self) -> &DispatchGate {
+        &self.dispatch_gate
+    }
+
     fn new(argv: Vec<String>) -> Self {
         Self {
             argv,
             spans: Mutex::new(SpansSnapshot::default()),
+            open_roots: Mutex::new(HashMap::new()),
+            dispatch_gate: DispatchGate::new(),
         }
     }
 }
@@ -169,6 +203,10 @@ impl ActiveCommandStateWriter {
 
                 if is_root {
                     self.roots.insert(span_id, false, RootData::new(buck_event));
+                    self.shared
+                        .open_roots
+                        .lock()
+                        .insert(span_id, Arc::new(buck_event.clone()));
                     changed = true;
                 } else {
                     self.non_roots.insert(span_id);
@@ -182,6 +220,7 @@ impl ActiveCommandStateWriter {
 
                 // If it's a root, then we increment closed.
                 if self.roots.remove(span_id).is_some() {
+                    self.shared.open_roots.lock().remove(&span_id);
                     self.closed += 1;
                     changed = true;
                 } else {
@@ -266,7 +305,10 @@ impl ActiveCommand {
         }
 
         Self {
-            guard: ActiveCommandDropGuard { trace_id },
+            guard: ActiveCommandDropGuard {
+                trace_id,
+                dispatch_gate: state.dispatch_gate().dupe(),
+            },
             daemon_shutdown_channel: receiver,
             state: ActiveCommandStateWriter::new(state),
         }
@@ -275,6 +317,7 @@ impl ActiveCommand {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
     use std::time::SystemTime;
 
     use super::*;
@@ -307,6 +350,7 @@ mod tests {
                 pending: 0
             }
         );
+        assert_eq!(writer.shared.open_roots().len(), 1);
 
         writer.peek_event(&BuckEvent::new(
             SystemTime::now(),
@@ -369,6 +413,7 @@ mod tests {
                 pending: 0
             }
         );
+        assert!(writer.shared.open_roots().is_empty());
 
         writer.peek_event(&BuckEvent::new(
             SystemTime::now(),
@@ -407,4 +452,34 @@ mod tests {
             }
         );
     }
+
+    #[tokio::test]
+    async fn test_dispatch_gate_released_when_command_ends() {
+        let paused = ActiveCommand::new(
+            &EventDispatcher::null_sink_with_trace(TraceId::new()),
+            &ClientContext::default(),
+        );
+        let gate = paused.guard.dispatch_gate().dupe();
+        gate.set_paused(true);
+
+        // An action started by the paused command.
+        let waiter = tokio::spawn(async move { gate.wait().await });
+
+        // Other commands are not affected by the pause.
+        let next = ActiveCommand::new(
+            &EventDispatcher::null_sink_with_trace(TraceId::new()),
+            &ClientContext::default(),
+        );
+        assert!(!next.guard.dispatch_gate().is_paused());
+        tokio::time::timeout(Duration::from_secs(10), next.guard.dispatch_gate().wait())
+            .await
+            .unwrap();
+
+        // Once the paused command is gone, its actions run again.
+        drop(paused);
+        tokio::time::timeout(Duration::from_secs(10), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+    }
 }
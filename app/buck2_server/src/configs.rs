@@ -13,6 +13,7 @@ use anyhow::Context;
 use buck2_cli_proto::config_override::ConfigType;
 use buck2_cli_proto::ConfigOverride;
 use buck2_common::legacy_configs::cells::BuckConfigBasedCells;
+use buck2_common::legacy_configs::snapshot::ConfigSnapshot;
 use buck2_common::legacy_configs::LegacyBuckConfigs;
 use buck2_common::legacy_configs::LegacyConfigCmdArg;
 use buck2_core::cells::CellResolver;
@@ -43,17 +44,24 @@ pub fn get_legacy_config_args<'a, Iter: IntoIterator<Item = &'a ConfigOverride>>
         .collect::<anyhow::Result<Vec<LegacyConfigCmdArg>>>()
 }
 
-/// Read the configs, returning the cell resolver and the legacy configs
+/// Read the configs, returning the cell resolver and the legacy configs. With `replay_config`,
+/// the configs of the cells come from that snapshot instead of their buckconfig files.
 pub fn parse_legacy_cells(
     config_overrides: &[LegacyConfigCmdArg],
     cwd: &ProjectRelativePath,
     fs: &ProjectRoot,
+    replay_config: Option<&ConfigSnapshot>,
 ) -> anyhow::Result<(CellResolver, LegacyBuckConfigs, HashSet<AbsNormPathBuf>)> {
     // TODO: We do not need to reparse _all_ configs, instead we just need to
     // overlay any custom configs for the current build command on top of
     // the base configs derived from the config files. This requires us to
     // store the base configs + overlaid ones separately, so we can cheaply
     // recompose.
-    let res = BuckConfigBasedCells::parse_with_config_args(fs, config_overrides, cwd)?;
+    let res = match replay_config {
+        Some(snapshot) => {
+            BuckConfigBasedCells::parse_with_snapshot(fs, snapshot, config_overrides, cwd)?
+        }
+        None => BuckConfigBasedCells::parse_with_config_args(fs, config_overrides, cwd)?,
+    };
     Ok((res.cell_resolver, res.configs_by_name, res.config_paths))
 }
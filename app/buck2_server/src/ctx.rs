@@ -13,6 +13,7 @@ use std::io::BufWriter;
 use std::marker::PhantomData;
 use std::ops::Deref;
 use std::sync::Arc;
+use std::time::Duration;
 
 use allocative::Allocative;
 use anyhow::Context;
@@ -20,6 +21,7 @@ use async_trait::async_trait;
 use buck2_build_api::actions::execute::dice_data::set_fallback_executor_config;
 use buck2_build_api::actions::execute::dice_data::SetCommandExecutor;
 use buck2_build_api::actions::execute::dice_data::SetReClient;
+use buck2_build_api::actions::execute::undeclared_inputs::UndeclaredInputsAllowlist;
 use buck2_build_api::actions::impls::run_action_knobs::HasRunActionKnobs;
 use buck2_build_api::actions::impls::run_action_knobs::RunActionKnobs;
 use buck2_build_api::build::HasCreateUnhashedSymlinkLock;
@@ -44,14 +46,18 @@ use buck2_common::http::SetHttpClient;
 use buck2_common::invocation_paths::InvocationPaths;
 use buck2_common::io::trace::TracingIoProvider;
 use buck2_common::legacy_configs::dice::HasLegacyConfigs;
+use buck2_common::legacy_configs::external_cells::parse_external_cells;
+use buck2_common::legacy_configs::snapshot::ConfigSnapshot;
 use buck2_common::legacy_configs::LegacyBuckConfig;
 use buck2_common::legacy_configs::LegacyBuckConfigs;
 use buck2_common::legacy_configs::LegacyConfigCmdArg;
 use buck2_configured::calculation::ConfiguredGraphCycleDescriptor;
 use buck2_core::async_once_cell::AsyncOnceCell;
 use buck2_core::cells::CellResolver;
+use buck2_core::error::SoftErrorConfig;
 use buck2_core::execution_types::executor_config::CommandExecutorConfig;
 use buck2_core::facebook_only;
+use buck2_core::fs::buck_out_path::BuckOutPathScheme;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
 use buck2_core::fs::paths::file_name::FileName;
@@ -68,12 +74,21 @@ use buck2_events::dispatch::EventDispatcher;
 use buck2_events::metadata;
 use buck2_execute::execute::blocking::BlockingExecutor;
 use buck2_execute::execute::blocking::SetBlockingExecutor;
+use buck2_execute::execute::dispatch_gate::DispatchGate;
+use buck2_execute::execute::dispatch_gate::SetDispatchGate;
+use buck2_execute::execute::environment_inheritance::EnvironmentPolicy;
+use buck2_execute::execute::environment_inheritance::EnvironmentPolicyMode;
+use buck2_execute::execute::resource_pools::DaemonResourcePools;
+use buck2_execute::execute::resource_pools::SetResourcePools;
 use buck2_execute::knobs::ExecutorGlobalKnobs;
 use buck2_execute::materialize::materializer::Materializer;
 use buck2_execute::materialize::materializer::SetMaterializer;
 use buck2_execute::re::client::RemoteExecutionClient;
 use buck2_execute::re::manager::ReConnectionHandle;
 use buck2_execute::re::manager::ReConnectionObserver;
+use buck2_execute::re::retry::ReRetryPolicy;
+use buck2_execute_impl::executors::action_output::ActionOutputFilter;
+use buck2_execute_impl::executors::undeclared_inputs;
 use buck2_execute_impl::executors::worker::WorkerPool;
 use buck2_execute_impl::low_pass_filter::LowPassFilter;
 use buck2_execute_impl::re::paranoid_download::ParanoidDownloader;
@@ -84,6 +99,7 @@ use buck2_futures::cancellation::ExplicitCancellationContext;
 use buck2_http::HttpClient;
 use buck2_interpreter::dice::starlark_debug::SetStarlarkDebugger;
 use buck2_interpreter::dice::starlark_profiler::StarlarkProfilerConfiguration;
+use buck2_interpreter::extra::host_toolchains::HostToolchains;
 use buck2_interpreter::extra::xcode::XcodeVersionInfo;
 use buck2_interpreter::extra::InterpreterHostArchitecture;
 use buck2_interpreter::extra::InterpreterHostPlatform;
@@ -122,9 +138,12 @@ use crate::daemon::common::parse_concurrency;
 use crate::daemon::common::CommandExecutorFactory;
 use crate::daemon::state::DaemonStateData;
 use crate::dice_tracker::BuckDiceTracker;
+use crate::external_cells::fetch_external_cells;
 use crate::heartbeat_guard::HeartbeatGuard;
 use crate::host_info;
 use crate::snapshot::SnapshotCollector;
+use crate::soft_error_history;
+use crate::soft_error_history::SoftErrorHistory;
 
 #[derive(Debug, buck2_error::Error)]
 enum DaemonCommunicationError {
@@ -144,7 +163,7 @@ pub struct BaseServerCommandContext {
     /// Underlying data that isn't command-level.
     pub(crate) daemon: Arc<DaemonStateData>,
     /// Removes this command from the set of active commands when dropped.
-    pub _drop_guard: ActiveCommandDropGuard,
+    pub drop_guard: ActiveCommandDropGuard,
     /// Spawner
     pub spawner: Arc<BuckSpawner>,
 }
@@ -189,6 +208,7 @@ pub struct ServerCommandContext<'a> {
 
     pub buck_out_dir: ProjectRelativePathBuf,
     isolation_prefix: FileNameBuf,
+    soft_error_history_path: AbsNormPathBuf,
 
     /// Common build options associated with this command.
     build_options: Option<CommonBuildOptions>,
@@ -297,7 +317,18 @@ impl<'a> ServerCommandContext<'a> {
             project_root: base_context.project_root.clone(),
             working_dir: working_dir_project_relative.to_buf().into(),
             reuse_current_config: client_context.reuse_current_config,
+            config_snapshot: client_context
+                .config_snapshot
+                .as_ref()
+                .map(|p| AbsNormPathBuf::new(p.into()))
+                .transpose()?,
+            replay_config: client_context
+                .replay_config
+                .as_ref()
+                .map(|p| AbsNormPathBuf::new(p.into()))
+                .transpose()?,
             config_overrides,
+            http_client: base_context.daemon.http_client.dupe(),
             loaded_cell_configs: AsyncOnceCell::new(),
         });
 
@@ -316,6 +347,7 @@ impl<'a> ServerCommandContext<'a> {
             starlark_profiler_instrumentation_override,
             buck_out_dir: paths.buck_out_dir(),
             isolation_prefix: paths.isolation.clone(),
+            soft_error_history_path: paths.soft_error_history_path(),
             build_options: build_options.cloned(),
             cell_configs_loader,
             record_target_call_stacks: client_context.target_call_stacks,
@@ -367,6 +399,7 @@ impl<'a> ServerCommandContext<'a> {
 
         if let Some(build_options) = self.build_options.as_ref() {
             run_action_knobs.eager_dep_files = build_options.eager_dep_files;
+            run_action_knobs.offline = build_options.offline;
         }
 
         let concurrency = self
@@ -419,6 +452,14 @@ impl<'a> ServerCommandContext<'a> {
                 .build_options
                 .as_ref()
                 .map_or(false, |opts| opts.materialize_failed_inputs),
+            show_action_output: ActionOutputFilter::new(
+                self.build_options
+                    .as_ref()
+                    .map_or_else(Vec::new, |opts| opts.show_action_output.clone()),
+            ),
+            soft_error_history_path: self.soft_error_history_path.clone(),
+            dispatch_gate: self.base_context.drop_guard.dispatch_gate().dupe(),
+            resource_pools: self.base_context.daemon.resource_pools.dupe(),
         }
     }
 
@@ -460,7 +501,12 @@ struct CellConfigLoader {
     working_dir: ProjectRelativePathBuf,
     /// Reuses build config from the previous invocation if there is one
     reuse_current_config: bool,
+    /// Writes the loaded configs to this file (`--config-snapshot`)
+    config_snapshot: Option<AbsNormPathBuf>,
+    /// Loads the configs from this snapshot instead of the buckconfig files (`--replay-config`)
+    replay_config: Option<AbsNormPathBuf>,
     config_overrides: Vec<LegacyConfigCmdArg>,
+    http_client: HttpClient,
     loaded_cell_configs: AsyncOnceCell<
         buck2_error::Result<(CellResolver, LegacyBuckConfigs, HashSet<AbsNormPathBuf>)>,
     >,
@@ -473,35 +519,71 @@ impl CellConfigLoader {
     ) -> buck2_error::Result<(CellResolver, LegacyBuckConfigs, HashSet<AbsNormPathBuf>)> {
         self.loaded_cell_configs
             .get_or_init(async move {
-                if self.reuse_current_config {
-                    // If there is a previous command and --reuse-current-config is set, then the old config is used, ignoring any overrides.
-                    if dice_ctx.bad_dice().is_cell_resolver_key_set().await?
-                        && dice_ctx.is_legacy_configs_key_set().await?
-                    {
-                        if !self.config_overrides.is_empty() {
-                            warn!(
-                                "Found config overrides while using --reuse-current-config flag. Ignoring overrides [{}] and using current config instead",
-                                truncate_container(self.config_overrides.iter().map(|o| o.to_string()), 200),
-                            );
-                        }
-                        return buck2_error::Ok((
-                            dice_ctx.bad_dice().get_cell_resolver().await?,
-                            dice_ctx.get_legacy_configs().await?,
-                            HashSet::new(),
-                        ));
-                    } else {
-                        // If there is no previous command but the flag was set, then the flag is ignored, the command behaves as if there isn't the reuse config flag.
-                        warn!(
-                            "--reuse-current-config flag was set, but there was no previous invocation detected. Ignoring --reuse-current-config flag"
-                        );
-                    }
+                let (cell_resolver, legacy_configs, config_paths) = self.load(dice_ctx).await?;
+                if let Some(config_snapshot) = &self.config_snapshot {
+                    ConfigSnapshot::new(&cell_resolver, &legacy_configs)?.write(config_snapshot)?;
                 }
-                parse_legacy_cells(&self.config_overrides, &self.working_dir, &self.project_root)
-                    .map_err(buck2_error::Error::from)
+                buck2_error::Ok((cell_resolver, legacy_configs, config_paths))
             })
             .await
             .clone()
     }
+
+    async fn load(
+        &self,
+        dice_ctx: &DiceComputations<'_>,
+    ) -> buck2_error::Result<(CellResolver, LegacyBuckConfigs, HashSet<AbsNormPathBuf>)> {
+        if self.reuse_current_config {
+            // If there is a previous command and --reuse-current-config is set, then the old config is used, ignoring any overrides.
+            if dice_ctx.bad_dice().is_cell_resolver_key_set().await?
+                && dice_ctx.is_legacy_configs_key_set().await?
+            {
+                if !self.config_overrides.is_empty() {
+                    warn!(
+                        "Found config overrides while using --reuse-current-config flag. Ignoring overrides [{}] and using current config instead",
+                        truncate_container(
+                            self.config_overrides.iter().map(|o| o.to_string()),
+                            200
+                        ),
+                    );
+                }
+                return buck2_error::Ok((
+                    dice_ctx.bad_dice().get_cell_resolver().await?,
+                    dice_ctx.get_legacy_configs().await?,
+                    HashSet::new(),
+                ));
+            } else {
+                // If there is no previous command but the flag was set, then the flag is ignored, the command behaves as if there isn't the reuse config flag.
+                warn!(
+                    "--reuse-current-config flag was set, but there was no previous invocation detected. Ignoring --reuse-current-config flag"
+                );
+            }
+        }
+        let replay_config = self
+            .replay_config
+            .as_ref()
+            .map(|path| ConfigSnapshot::read(path))
+            .transpose()?;
+        let (cell_resolver, legacy_configs, config_paths) = parse_legacy_cells(
+            &self.config_overrides,
+            &self.working_dir,
+            &self.project_root,
+            replay_config.as_ref(),
+        )?;
+        let external_cells = parse_external_cells(legacy_configs.get(cell_resolver.root_cell())?)?;
+        if fetch_external_cells(&self.project_root, &external_cells, &self.http_client).await? {
+            // The configs of the cells just fetched could not be read before.
+            parse_legacy_cells(
+                &self.config_overrides,
+                &self.working_dir,
+                &self.project_root,
+                replay_config.as_ref(),
+            )
+            .map_err(buck2_error::Error::from)
+        } else {
+            Ok((cell_resolver, legacy_configs, config_paths))
+        }
+    }
 }
 
 struct DiceCommandDataProvider {
@@ -526,6 +608,10 @@ struct DiceCommandDataProvider {
     paranoid: Option<ParanoidDownloader>,
     spawner: Arc<BuckSpawner>,
     materialize_failed_inputs: bool,
+    show_action_output: ActionOutputFilter,
+    soft_error_history_path: AbsNormPathBuf,
+    dispatch_gate: DispatchGate,
+    resource_pools: Arc<DaemonResourcePools>,
 }
 
 #[async_trait]
@@ -563,17 +649,79 @@ impl DiceDataProvider for DiceCommandDataProvider {
             .unwrap_or_else(RolloutPercentage::always)
             .roll();
 
+        let trace_undeclared_inputs = root_config
+            .parse::<bool>("build", "trace_undeclared_inputs")?
+            .unwrap_or(false)
+            && match undeclared_inputs::check_supported() {
+                Ok(()) => true,
+                Err(e) => {
+                    self.events.console_warning(format!(
+                        "Ignoring `build.trace_undeclared_inputs`: {:#}",
+                        e
+                    ));
+                    false
+                }
+            };
+
         let log_configured_graph_size = root_config
             .parse::<bool>("buck2", "log_configured_graph_size")?
             .unwrap_or(false);
 
+        let env_policy = EnvironmentPolicy::new(
+            root_config
+                .parse::<EnvironmentPolicyMode>("build", "action_env_policy")?
+                .unwrap_or_default(),
+            root_config
+                .parse_list::<String>("build", "action_env_allowlist")?
+                .unwrap_or_default(),
+        );
+
+        let re_retry_policy = ReRetryPolicy::new(
+            root_config
+                .parse::<u32>("buck2_re_client", "execute_retries")?
+                .unwrap_or(0),
+            Duration::from_millis(
+                root_config
+                    .parse::<u64>("buck2_re_client", "execute_retry_initial_backoff_ms")?
+                    .unwrap_or(1000),
+            ),
+            root_config
+                .parse::<u64>("buck2_re_client", "execute_retry_budget")?
+                .unwrap_or(100),
+        );
+
         let persistent_worker_shutdown_timeout_s = root_config
             .parse::<u32>("build", "persistent_worker_shutdown_timeout_s")?
             .or(Some(10));
 
+        let mut soft_error_config = SoftErrorConfig::from_config_entries(
+            root_config
+                .get_section(SoftErrorConfig::SECTION)
+                .into_iter()
+                .flat_map(|section| section.iter())
+                .map(|(key, value)| (key, value.as_str())),
+        )?;
+        if let Some(days) = soft_error_config.promote_after_days {
+            soft_error_config.promoted = SoftErrorHistory::read(&self.soft_error_history_path)?
+                .promoted(days, soft_error_history::now_seconds());
+        }
+        self.events.soft_errors().set_config(soft_error_config);
+
+        self.events.windows_path_options().set(
+            root_config
+                .parse::<bool>("buck2", "windows_long_paths")?
+                .unwrap_or(false),
+            root_config
+                .parse::<bool>("buck2", "windows_junctions")?
+                .unwrap_or(false),
+        );
+
         let executor_global_knobs = ExecutorGlobalKnobs {
             enable_miniperf,
             log_action_keys,
+            trace_undeclared_inputs,
+            env_policy,
+            re_retry_policy,
         };
 
         let host_sharing_broker =
@@ -603,6 +751,14 @@ impl DiceDataProvider for DiceCommandDataProvider {
         run_action_knobs.use_network_action_output_cache |= root_config
             .parse::<bool>("buck2", "use_network_action_output_cache")?
             .unwrap_or(false);
+        if let Some(allowlist) =
+            root_config.parse_list::<String>("build", "undeclared_inputs_allowlist")?
+        {
+            run_action_knobs.undeclared_inputs_allowlist =
+                UndeclaredInputsAllowlist::new(allowlist);
+        }
+        run_action_knobs.write_action_max_in_memory_bytes =
+            root_config.parse::<u64>("build", "write_action_max_in_memory_bytes")?;
 
         let mut data = UserComputationData {
             data,
@@ -639,6 +795,7 @@ impl DiceDataProvider for DiceCommandDataProvider {
             worker_pool,
             self.paranoid.dupe(),
             self.materialize_failed_inputs,
+            self.show_action_output.dupe(),
         )));
         data.set_blocking_executor(self.blocking_executor.dupe());
         data.set_http_client(self.http_client.dupe());
@@ -649,6 +806,8 @@ impl DiceDataProvider for DiceCommandDataProvider {
         data.set_starlark_debugger_handle(self.starlark_debugger.clone().map(|v| Box::new(v) as _));
         data.set_keep_going(self.keep_going);
         data.set_critical_path_backend(critical_path_backend);
+        data.set_dispatch_gate(self.dispatch_gate.dupe());
+        data.set_resource_pools(self.resource_pools.dupe());
         data.spawner = self.spawner.dupe();
 
         let tags = vec![
@@ -701,11 +860,19 @@ impl DiceUpdater for DiceCommandUpdater {
         // TODO(cjhopman): The CellResolver and the legacy configs shouldn't be leaves on the graph. This should
         // just be setting the config overrides and host platform override as leaves on the graph.
 
+        let host_toolchains = HostToolchains::probe(
+            &legacy_configs
+                .get(cell_resolver.root_cell())?
+                .parse_list::<String>("buck2", "host_toolchains")?
+                .unwrap_or_default(),
+        );
+
         let configuror = BuildInterpreterConfiguror::new(
             Some(prelude_path(&cell_resolver)?),
             self.interpreter_platform,
             self.interpreter_architecture,
             self.interpreter_xcode_version.clone(),
+            host_toolchains,
             self.record_target_call_stacks,
             self.skip_targets_with_duplicate_names,
             register_universal_natives,
@@ -718,7 +885,11 @@ impl DiceUpdater for DiceCommandUpdater {
         let (mut ctx, mergebase) = self.file_watcher.sync(ctx).await?;
         user_data.set_mergebase(mergebase);
 
-        ctx.set_buck_out_path(Some(self.buck_out_dir.clone()))?;
+        let buck_out_path_scheme = legacy_configs
+            .get(cell_resolver.root_cell())?
+            .parse::<BuckOutPathScheme>("buck2", "buck_out_path_scheme")?
+            .unwrap_or_default();
+        ctx.set_buck_out_path_with_scheme(Some(self.buck_out_dir.clone()), buck_out_path_scheme)?;
 
         setup_interpreter(
             &mut ctx,
@@ -16,6 +16,8 @@ use buck2_build_api::actions::execute::dice_data::HasCommandExecutor;
 use buck2_cli_proto::client_context::HostPlatformOverride;
 use buck2_cli_proto::common_build_options::ExecutionStrategy;
 use buck2_core::buck2_env;
+use buck2_core::execution_types::executor_config::ActionExecutorPreferences;
+use buck2_core::execution_types::executor_config::ActionTimeouts;
 use buck2_core::execution_types::executor_config::CacheUploadBehavior;
 use buck2_core::execution_types::executor_config::CommandExecutorConfig;
 use buck2_core::execution_types::executor_config::CommandGenerationOptions;
@@ -43,6 +45,7 @@ use buck2_execute::re::manager::ReConnectionHandle;
 use buck2_execute_impl::executors::action_cache::ActionCacheChecker;
 use buck2_execute_impl::executors::action_cache::RemoteDepFileCacheChecker;
 use buck2_execute_impl::executors::action_cache_upload_permission_checker::ActionCacheUploadPermissionChecker;
+use buck2_execute_impl::executors::action_output::ActionOutputFilter;
 use buck2_execute_impl::executors::caching::CacheUploader;
 use buck2_execute_impl::executors::hybrid::HybridExecutor;
 use buck2_execute_impl::executors::local::LocalExecutor;
@@ -88,6 +91,7 @@ pub struct CommandExecutorFactory {
     worker_pool: Arc<WorkerPool>,
     paranoid: Option<ParanoidDownloader>,
     materialize_failed_inputs: bool,
+    show_action_output: ActionOutputFilter,
     /// Cache permission checks per command.
     cache_upload_permission_checker: Arc<ActionCacheUploadPermissionChecker>,
 }
@@ -109,6 +113,7 @@ impl CommandExecutorFactory {
         worker_pool: Arc<WorkerPool>,
         paranoid: Option<ParanoidDownloader>,
         materialize_failed_inputs: bool,
+        show_action_output: ActionOutputFilter,
     ) -> Self {
         let cache_upload_permission_checker = Arc::new(ActionCacheUploadPermissionChecker::new(
             re_connection.get_client(),
@@ -129,6 +134,7 @@ impl CommandExecutorFactory {
             worker_pool,
             paranoid,
             materialize_failed_inputs,
+            show_action_output,
             cache_upload_permission_checker,
         }
     }
@@ -158,6 +164,7 @@ impl HasCommandExecutor for CommandExecutorFactory {
                 self.forkserver.dupe(),
                 self.executor_global_knobs.dupe(),
                 worker_pool,
+                self.show_action_output.dupe(),
             )
         };
 
@@ -461,6 +468,8 @@ pub fn get_default_executor_config(host_platform: HostPlatformOverride) -> Comma
             path_separator: get_default_path_separator(host_platform),
             output_paths_behavior: Default::default(),
         },
+        default_timeouts: ActionTimeouts::default(),
+        executor_preferences: ActionExecutorPreferences::default(),
     }
 }
 
@@ -43,6 +43,7 @@ pub(crate) fn dice_dump(
         DiceDumpFormat::Tsv => dice_dump_tsv(dice, path),
         DiceDumpFormat::Bincode => dice_dump_bincode(dice, path),
         DiceDumpFormat::JsonPretty => dice_dump_json_pretty(dice, path),
+        DiceDumpFormat::Stats => dice_dump_stats(dice, path),
     }
 }
 
@@ -140,3 +141,16 @@ fn dice_dump_json_pretty(dice: &Arc<Dice>, path: &Path) -> anyhow::Result<()> {
     dice.serialize_serde(&mut writer)?;
     Ok(())
 }
+
+/// Number of keys listed in the most invalidated keys of the stats dump.
+const STATS_TOP_KEYS: usize = 100;
+
+fn dice_dump_stats(dice: &Arc<Dice>, path: &Path) -> anyhow::Result<()> {
+    let path = path.to_path_buf();
+    std::fs::create_dir_all(path.parent().unwrap()).context("Failed to create directory")?;
+    let out =
+        File::create(&path).context(format!("Failed to open DICE stats dumpfile {:?}", &path))?;
+
+    serde_json::to_writer_pretty(BufWriter::new(out), &dice.graph_stats(STATS_TOP_KEYS))?;
+    Ok(())
+}
@@ -9,11 +9,14 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use allocative::Allocative;
 use anyhow::Context;
 use buck2_common::invocation_paths::InvocationPaths;
 use buck2_common::legacy_configs::LegacyBuckConfig;
+use buck2_common::source_digest_cache::SourceDigestCache;
+use buck2_common::source_digest_cache::DB_SCHEMA_VERSION as SOURCE_DIGEST_CACHE_SCHEMA_VERSION;
 use buck2_core::fs::fs_util;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
 use buck2_core::fs::paths::file_name::FileName;
@@ -26,12 +29,33 @@ use buck2_execute_impl::materializers::deferred::DeferredMaterializerConfigs;
 use buck2_execute_impl::materializers::sqlite::MaterializerState;
 use buck2_execute_impl::materializers::sqlite::MaterializerStateSqliteDb;
 use buck2_execute_impl::materializers::sqlite::DB_SCHEMA_VERSION;
+use buck2_file_watcher::file_watcher::FileWatcher;
+use buck2_interpreter::bzl_cache::BzlCache;
+use buck2_interpreter::bzl_cache::BZL_CACHE_VERSION;
+use dupe::Dupe;
 
 use crate::daemon::server::BuckdServerInitPreferences;
 
+const SOURCE_DIGEST_CACHE_FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, buck2_error::Error)]
+enum DiskStateError {
+    #[buck2(user)]
+    #[error("`buck2.{0}` must be at most 100, got {1}")]
+    VerifyPercentageTooLarge(&'static str, u32),
+}
+
 #[derive(Allocative)]
 pub struct DiskStateOptions {
     pub sqlite_materializer_state: bool,
+    /// Whether to reuse the digests of source files computed by previous daemons.
+    pub source_digest_cache: bool,
+    /// The percentage of the digests served by the source digest cache which are checked.
+    pub source_digest_cache_verify_percentage: u32,
+    /// Whether to reuse the `.bzl` modules evaluated by previous daemons.
+    pub bzl_cache: bool,
+    /// The percentage of the modules served by the bzl cache which are evaluated anyway.
+    pub bzl_cache_verify_percentage: u32,
     // In future, this will include the config for dep files on disk
 }
 
@@ -40,6 +64,20 @@ impl DiskStateOptions {
         root_config: &LegacyBuckConfig,
         materialization_method: MaterializationMethod,
     ) -> anyhow::Result<Self> {
+        // The cache relies on Watchman to know which files changed while no daemon was running.
+        let source_digest_cache = <dyn FileWatcher>::uses_watchman(root_config)
+            && root_config
+                .parse::<RolloutPercentage>("buck2", "source_digest_cache")?
+                .unwrap_or_else(RolloutPercentage::never)
+                .roll();
+        let source_digest_cache_verify_percentage =
+            verify_percentage(root_config, "source_digest_cache_verify_percentage")?;
+        let bzl_cache = root_config
+            .parse::<RolloutPercentage>("buck2", "bzl_cache")?
+            .unwrap_or_else(RolloutPercentage::never)
+            .roll();
+        let bzl_cache_verify_percentage =
+            verify_percentage(root_config, "bzl_cache_verify_percentage")?;
         let sqlite_materializer_state = matches!(
             // We can only enable materializer state on sqlite if you use deferred materializer
             materialization_method,
@@ -50,10 +88,22 @@ impl DiskStateOptions {
             .roll();
         Ok(Self {
             sqlite_materializer_state,
+            source_digest_cache,
+            source_digest_cache_verify_percentage,
+            bzl_cache,
+            bzl_cache_verify_percentage,
         })
     }
 }
 
+fn verify_percentage(root_config: &LegacyBuckConfig, key: &'static str) -> anyhow::Result<u32> {
+    let percentage = root_config.parse::<u32>("buck2", key)?.unwrap_or(0);
+    if percentage > 100 {
+        return Err(DiskStateError::VerifyPercentageTooLarge(key, percentage).into());
+    }
+    Ok(percentage)
+}
+
 pub(crate) async fn maybe_initialize_materializer_sqlite_db(
     options: &DiskStateOptions,
     paths: InvocationPaths,
@@ -116,6 +166,111 @@ pub(crate) async fn maybe_initialize_materializer_sqlite_db(
     Ok((Some(db), materializer_state))
 }
 
+pub(crate) async fn maybe_initialize_source_digest_cache(
+    options: &DiskStateOptions,
+    paths: &InvocationPaths,
+    io_executor: Arc<dyn BlockingExecutor>,
+    fs: ProjectRoot,
+    digest_config: DigestConfig,
+) -> anyhow::Result<Option<Arc<SourceDigestCache>>> {
+    let dir = paths.source_digest_cache_path();
+
+    if !options.source_digest_cache {
+        // Entries are not invalidated while the cache is disabled. The clock would still catch the
+        // changes if it was enabled again, but most entries would be stale by then.
+        io_executor
+            .execute_io_inline(|| fs.remove_path_recursive(&dir))
+            .await?;
+        return Ok(None);
+    }
+
+    let mut versions = HashMap::from([
+        (
+            "schema_version".to_owned(),
+            SOURCE_DIGEST_CACHE_SCHEMA_VERSION.to_string(),
+        ),
+        (
+            "digest_config".to_owned(),
+            digest_config.cas_digest_config().to_string(),
+        ),
+    ]);
+    // Digests computed by another buck2 are not trusted, in case it computed them wrong.
+    if let Some(revision) = buck2_events::metadata::collect().remove("buck2_revision") {
+        versions.insert("buck2_revision".to_owned(), revision);
+    }
+
+    let verify_percentage = options.source_digest_cache_verify_percentage;
+    let cache = io_executor
+        .dupe()
+        .execute_io_inline(|| {
+            SourceDigestCache::open(
+                &dir,
+                versions,
+                digest_config.cas_digest_config(),
+                verify_percentage,
+            )
+        })
+        .await?;
+    let cache = Arc::new(cache);
+
+    // The file watcher writes the cache after each sync. Write it periodically too, so that the
+    // digests computed by the last command of a daemon are kept.
+    let weak_cache = Arc::downgrade(&cache);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SOURCE_DIGEST_CACHE_FLUSH_INTERVAL);
+        loop {
+            interval.tick().await;
+            let cache = match weak_cache.upgrade() {
+                Some(cache) => cache,
+                None => return,
+            };
+            let res = io_executor.execute_io_inline(|| cache.flush()).await;
+            if let Err(e) = res {
+                tracing::warn!("Error writing the source digest cache: {:#}", e);
+            }
+        }
+    });
+
+    Ok(Some(cache))
+}
+
+pub(crate) async fn maybe_initialize_bzl_cache(
+    options: &DiskStateOptions,
+    paths: &InvocationPaths,
+    io_executor: Arc<dyn BlockingExecutor>,
+    fs: ProjectRoot,
+) -> anyhow::Result<Option<Arc<BzlCache>>> {
+    let dir = paths.bzl_cache_path();
+
+    if !options.bzl_cache {
+        // Entries are keyed by the content of their modules, so they would still be right if the
+        // cache was enabled again, but they would waste space meanwhile.
+        io_executor
+            .execute_io_inline(|| fs.remove_path_recursive(&dir))
+            .await?;
+        return Ok(None);
+    }
+
+    let mut metadata = buck2_events::metadata::collect();
+    let mut versions =
+        HashMap::from([("schema_version".to_owned(), BZL_CACHE_VERSION.to_string())]);
+    // Native symbols are also covered by the fingerprint of the globals in the keys, but their
+    // implementation is not.
+    if let Some(revision) = metadata.remove("buck2_revision") {
+        versions.insert("buck2_revision".to_owned(), revision);
+    }
+    // Modules can read the host with `host_info()`.
+    if let Some(hostname) = metadata.remove("hostname") {
+        versions.insert("hostname".to_owned(), hostname);
+    }
+
+    let verify_percentage = options.bzl_cache_verify_percentage;
+    let cache = io_executor
+        .execute_io_inline(|| BzlCache::open(&dir, versions, verify_percentage))
+        .await?;
+    Ok(Some(Arc::new(cache)))
+}
+
 // Once we start storing disk state in the cache directory, we need to make sure
 // buck2 always deletes the cache directory if the cache is disabled.
 // Otherwise, buck-out state can diverge from the state of on-disk cache when
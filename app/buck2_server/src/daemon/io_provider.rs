@@ -14,6 +14,7 @@ use buck2_common::io::fs::FsIoProvider;
 use buck2_common::io::trace::TracingIoProvider;
 use buck2_common::io::IoProvider;
 use buck2_common::legacy_configs::LegacyBuckConfig;
+use buck2_common::source_digest_cache::SourceDigestCache;
 use buck2_core::fs::project::ProjectRoot;
 
 pub async fn create_io_provider(
@@ -22,6 +23,7 @@ pub async fn create_io_provider(
     root_config: &LegacyBuckConfig,
     cas_digest_config: CasDigestConfig,
     trace_io: bool,
+    source_digest_cache: Option<Arc<SourceDigestCache>>,
 ) -> anyhow::Result<Arc<dyn IoProvider>> {
     #[cfg(fbcode_build)]
     {
@@ -51,11 +53,15 @@ pub async fn create_io_provider(
 
     let _allow_unused = (fb, root_config);
 
+    // Eden has the digests of source files already, so the source digest cache is only used here.
+    let mut fs_io = FsIoProvider::new(project_fs, cas_digest_config);
+    if let Some(source_digest_cache) = source_digest_cache {
+        fs_io = fs_io.with_source_digest_cache(source_digest_cache);
+    }
+
     if trace_io {
-        Ok(Arc::new(TracingIoProvider::new(Box::new(
-            FsIoProvider::new(project_fs, cas_digest_config),
-        ))))
+        Ok(Arc::new(TracingIoProvider::new(Box::new(fs_io))))
     } else {
-        Ok(Arc::new(FsIoProvider::new(project_fs, cas_digest_config)))
+        Ok(Arc::new(fs_io))
     }
 }
@@ -9,9 +9,15 @@
 
 //! Daemon-only panic hooks.
 //!
-//! This module sets up a panic hook to send the panic message to open CLIs.
+//! This module sets up a panic hook to write a crash report, and to send the panic message to open
+//! CLIs.
 
 use std::env::temp_dir;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
 use std::panic;
 use std::panic::PanicInfo;
 use std::path::Path;
@@ -22,13 +28,23 @@ use std::time::Duration;
 use std::time::SystemTime;
 
 use buck2_cli_proto::unstable_dice_dump_request::DiceDumpFormat;
+use buck2_common::daemon_dir::DAEMON_CRASH_REPORT_PREFIX;
 use buck2_core::buck2_env;
+use buck2_event_observer::display::display_event;
+use buck2_event_observer::display::TargetDisplayOptions;
 use buck2_wrapper_common::invocation_id::TraceId;
 
+use crate::active_commands::try_active_commands;
 use crate::daemon::dice_dump::tar_dice_dump;
 
-pub trait DaemonStatePanicDiceDump: Send + Sync + 'static {
+pub trait DaemonStatePanic: Send + Sync + 'static {
     fn dice_dump(&self, path: &Path, format: DiceDumpFormat) -> anyhow::Result<()>;
+
+    /// The DICE keys being computed, one per line.
+    fn dice_keys_currently_running(&self) -> Vec<String>;
+
+    /// The file the daemon's stderr, including its logs, is written to.
+    fn buckd_stderr(&self) -> Option<PathBuf>;
 }
 
 fn get_panic_dump_dir() -> PathBuf {
@@ -58,7 +74,7 @@ async fn remove_old_panic_dumps() -> anyhow::Result<()> {
 }
 
 /// Initializes the panic hook.
-pub fn initialize(daemon_state: Arc<dyn DaemonStatePanicDiceDump>) {
+pub fn initialize(daemon_state: Arc<dyn DaemonStatePanic>) {
     let hook = panic::take_hook();
     panic::set_hook(Box::new(move |info| {
         daemon_panic_hook(&daemon_state, info);
@@ -72,21 +88,134 @@ pub fn initialize(daemon_state: Arc<dyn DaemonStatePanicDiceDump>) {
 /// This cell prevents a circular set of panics if this happens.
 static ALREADY_DUMPED_DICE: OnceLock<()> = OnceLock::new();
 
-fn daemon_panic_hook(daemon_state: &Arc<dyn DaemonStatePanicDiceDump>, info: &PanicInfo) {
+/// Only the first panic gets a crash report: later ones are likely caused by the first one, and
+/// the daemon is aborting anyway.
+static ALREADY_WROTE_CRASH_REPORT: OnceLock<()> = OnceLock::new();
+
+fn daemon_panic_hook(daemon_state: &Arc<dyn DaemonStatePanic>, info: &PanicInfo) {
+    let panic_id = TraceId::new();
+    if ALREADY_WROTE_CRASH_REPORT.set(()).is_ok() {
+        write_crash_report(daemon_state, info, &panic_id);
+    }
     if !buck2_core::is_open_source()
         && buck2_env!("BUCK2_DICE_DUMP_ON_PANIC", bool).unwrap_or_default()
         && ALREADY_DUMPED_DICE.set(()).is_ok()
     {
-        let panic_id = TraceId::new();
         maybe_dice_dump(daemon_state, info, &panic_id);
     }
 }
 
-fn maybe_dice_dump(
-    daemon_state: &Arc<dyn DaemonStatePanicDiceDump>,
+/// How much of the end of the daemon's stderr to copy to the crash report.
+const STDERR_TAIL_BYTES: u64 = 64 * 1024;
+
+/// How long to wait for DICE to list the keys being computed. DICE may be the one which panicked,
+/// with its locks held, in which case we give up rather than hang the panicking thread.
+const DICE_KEYS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Write what the daemon was doing when it panicked to `buck2-dumps/crash-<id>` in the temp
+/// directory:
+///
+/// - `panic.txt`: the panic message, its location and a backtrace.
+/// - `commands.txt`: the commands running, and their open spans.
+/// - `dice_keys.txt`: the DICE keys being computed.
+/// - `buckd.stderr.tail`: the end of the daemon's stderr, which contains its logs.
+///
+/// Buck2 doesn't write minidumps: the backtrace and core dumps (when enabled) cover the native
+/// state, and this report adds the build state they don't show. The path is printed to stderr
+/// with `DAEMON_CRASH_REPORT_PREFIX`, so that clients, which show the daemon's stderr when it
+/// disconnects, can point to it.
+fn write_crash_report(
+    daemon_state: &Arc<dyn DaemonStatePanic>,
     info: &PanicInfo,
-    panic_id: &TraceId,
+    crash_id: &TraceId,
 ) {
+    let dir = get_panic_dump_dir().join(format!("crash-{}", crash_id));
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        eprintln!(
+            "Failed to create crash report directory `{}`: {}",
+            dir.display(),
+            e
+        );
+        return;
+    }
+
+    let panic = format!(
+        "{}\n\n{}\n",
+        info,
+        std::backtrace::Backtrace::force_capture()
+    );
+    let files = [
+        ("panic.txt", panic),
+        ("commands.txt", active_commands_report()),
+        ("dice_keys.txt", dice_keys_report(daemon_state)),
+        (
+            "buckd.stderr.tail",
+            daemon_state
+                .buckd_stderr()
+                .and_then(|path| stderr_tail(&path).ok())
+                .unwrap_or_default(),
+        ),
+    ];
+    for (name, contents) in files {
+        if let Err(e) = std::fs::write(dir.join(name), contents) {
+            eprintln!("Failed to write crash report file `{}`: {}", name, e);
+        }
+    }
+
+    eprintln!(
+        "{}`{}` (crash id {})",
+        DAEMON_CRASH_REPORT_PREFIX,
+        dir.display(),
+        crash_id
+    );
+}
+
+fn active_commands_report() -> String {
+    // The panicking thread may hold the lock of the active commands, so only try to take it.
+    let commands = match try_active_commands() {
+        Some(commands) => commands,
+        None => return "<active commands are locked>\n".to_owned(),
+    };
+    let mut report = String::new();
+    for (trace_id, handle) in commands {
+        let state = handle.state();
+        let _ignored = writeln!(report, "{} {}", trace_id, state.argv.join(" "));
+        for event in state.open_roots() {
+            let description = display_event(&event, TargetDisplayOptions::for_log())
+                .unwrap_or_else(|e| format!("<{:#}>", e));
+            let _ignored = writeln!(report, "    {}", description);
+        }
+    }
+    report
+}
+
+fn dice_keys_report(daemon_state: &Arc<dyn DaemonStatePanic>) -> String {
+    let (tx, rx) = std::sync::mpsc::channel();
+    let daemon_state = daemon_state.clone();
+    let spawned = std::thread::Builder::new()
+        .name("buck2-crash-report".to_owned())
+        .spawn(move || {
+            let _ignored = tx.send(daemon_state.dice_keys_currently_running());
+        });
+    if spawned.is_err() {
+        return "<failed to spawn a thread>\n".to_owned();
+    }
+    match rx.recv_timeout(DICE_KEYS_TIMEOUT) {
+        Ok(keys) => keys.into_iter().map(|k| k + "\n").collect(),
+        Err(_) => "<timed out listing DICE keys>\n".to_owned(),
+    }
+}
+
+fn stderr_tail(path: &Path) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(STDERR_TAIL_BYTES)))?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail)?;
+    Ok(String::from_utf8_lossy(&tail).into_owned())
+}
+
+fn maybe_dice_dump(daemon_state: &Arc<dyn DaemonStatePanic>, info: &PanicInfo, panic_id: &TraceId) {
     let is_dice_panic = info.location().map_or(false, |loc| {
         loc.file().split(&['/', '\\']).any(|x| x == "dice")
     });
@@ -123,3 +252,56 @@ fn maybe_dice_dump(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestDaemonState(Vec<String>);
+
+    impl DaemonStatePanic for TestDaemonState {
+        fn dice_dump(&self, _path: &Path, _format: DiceDumpFormat) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn dice_keys_currently_running(&self) -> Vec<String> {
+            self.0.clone()
+        }
+
+        fn buckd_stderr(&self) -> Option<PathBuf> {
+            None
+        }
+    }
+
+    #[test]
+    fn test_dice_keys_report() {
+        let daemon_state: Arc<dyn DaemonStatePanic> = Arc::new(TestDaemonState(vec![
+            "0\tComputing\tFoo\tfoo".to_owned(),
+            "0\tComputing\tBar\tbar".to_owned(),
+        ]));
+        assert_eq!(
+            "0\tComputing\tFoo\tfoo\n0\tComputing\tBar\tbar\n",
+            dice_keys_report(&daemon_state)
+        );
+    }
+
+    #[test]
+    fn test_stderr_tail() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let short = dir.path().join("short");
+        std::fs::write(&short, "some logs\n").unwrap();
+        assert_eq!("some logs\n", stderr_tail(&short).unwrap());
+
+        let long = dir.path().join("long");
+        let mut contents = "a".repeat(10);
+        contents.push_str(&"b".repeat(STDERR_TAIL_BYTES as usize));
+        std::fs::write(&long, contents).unwrap();
+        assert_eq!(
+            "b".repeat(STDERR_TAIL_BYTES as usize),
+            stderr_tail(&long).unwrap()
+        );
+
+        assert!(stderr_tail(&dir.path().join("missing")).is_err());
+    }
+}
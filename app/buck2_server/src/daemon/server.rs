@@ -52,6 +52,7 @@ use buck2_execute_impl::materializers::sqlite::MaterializerStateIdentity;
 use buck2_futures::cancellation::ExplicitCancellationContext;
 use buck2_futures::drop::DropTogether;
 use buck2_futures::spawn::spawn_cancellable;
+use buck2_interpreter::bzl_cache::BzlCache;
 use buck2_interpreter::dice::starlark_profiler::StarlarkProfilerConfiguration;
 use buck2_profile::starlark_profiler_configuration_from_request;
 use buck2_server_ctx::bxl::BXL_SERVER_COMMANDS;
@@ -62,7 +63,9 @@ use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
 use buck2_server_ctx::streaming_request_handler::StreamingRequestHandler;
 use buck2_server_ctx::test_command::TEST_COMMAND;
 use buck2_server_starlark_debug::run::run_dap_server_command;
+use buck2_util::process_stats::process_stats;
 use buck2_util::threads::thread_spawn;
+use buck2_wrapper_common::invocation_id::TraceId;
 use dice::DetectCycles;
 use dice::Dice;
 use dice::WhichDice;
@@ -89,6 +92,8 @@ use tonic::Request;
 use tonic::Response;
 use tonic::Status;
 
+use crate::active_commands::active_commands;
+use crate::active_commands::broadcast_instant_event;
 use crate::active_commands::ActiveCommand;
 use crate::active_commands::ActiveCommandStateWriter;
 use crate::clean_stale::clean_stale_command;
@@ -101,6 +106,8 @@ use crate::lsp::run_lsp_server_command;
 use crate::new_generic::new_generic_command;
 use crate::snapshot;
 use crate::snapshot::SnapshotCollector;
+use crate::soft_error_history;
+use crate::stack_dump;
 use crate::subscription::run_subscription_server_command;
 use crate::trace_io::trace_io_command;
 
@@ -156,11 +163,13 @@ impl BuckdServerInitPreferences {
         &self,
         io: Arc<dyn IoProvider>,
         digest_config: DigestConfig,
+        bzl_cache: Option<Arc<BzlCache>>,
         root_config: &LegacyBuckConfig,
     ) -> anyhow::Result<Arc<Dice>> {
         configure_dice_for_buck(
             io,
             digest_config,
+            bzl_cache,
             Some(root_config),
             self.detect_cycles,
             self.which_dice,
@@ -445,6 +454,12 @@ impl BuckdServer {
 
                         func(&context, PartialResultDispatcher::new(dispatch.dupe()), req).await?
                     };
+                    if let Err(e) = soft_error_history::record_soft_errors(
+                        &daemon_state.paths.soft_error_history_path(),
+                        dispatch.soft_errors(),
+                    ) {
+                        tracing::warn!("Failed to record soft errors: {:#}", e);
+                    }
                     dispatch.command_result(result_to_command_result(result));
                 }
                 .boxed()
@@ -828,6 +843,14 @@ impl DaemonApi for BuckdServer {
                     .as_ref()
                     .ok()
                     .map(|state| state.http_client.http2()),
+                rss_bytes: process_stats().rss_bytes,
+                active_commands: active_commands()
+                    .iter()
+                    .map(|(trace_id, handle)| ActiveCommandStatus {
+                        trace_id: trace_id.to_string(),
+                        argv: handle.state().argv.clone(),
+                    })
+                    .collect(),
                 ..Default::default()
             };
             Ok(base)
@@ -1388,6 +1411,54 @@ impl DaemonApi for BuckdServer {
         )
         .await
     }
+
+    async fn console_control(
+        &self,
+        req: Request<ConsoleControlRequest>,
+    ) -> Result<Response<ConsoleControlResponse>, Status> {
+        use buck2_cli_proto::console_control_request::Action;
+
+        let req = req.into_inner();
+        let action = Action::from_i32(req.action)
+            .ok_or_else(|| Status::invalid_argument("Invalid console control action"))?;
+
+        let trace_id: TraceId = req
+            .trace_id
+            .parse()
+            .map_err(|_| Status::invalid_argument("Invalid trace id"))?;
+        let command = active_commands()
+            .get(&trace_id)
+            .cloned()
+            .ok_or_else(|| Status::not_found(format!("No active command `{}`", trace_id)))?;
+        let dispatch_gate = command.state().dispatch_gate();
+
+        match action {
+            Action::ToggleActionDispatch => {
+                let message = if dispatch_gate.toggle() {
+                    "Action dispatch paused, running actions will continue (press `s` to resume)"
+                } else {
+                    "Action dispatch resumed"
+                };
+                command
+                    .dispatcher()
+                    .instant_event(buck2_data::ConsoleMessage {
+                        message: message.to_owned(),
+                    });
+            }
+            Action::DumpStacks => {
+                broadcast_instant_event(&stack_dump::collect_stack_dump().await);
+                command
+                    .dispatcher()
+                    .instant_event(buck2_data::ConsoleMessage {
+                        message: "Daemon state dump written to the event log".to_owned(),
+                    });
+            }
+        }
+
+        Ok(Response::new(ConsoleControlResponse {
+            action_dispatch_paused: dispatch_gate.is_paused(),
+        }))
+    }
 }
 
 /// Options to configure the execution of a oneshot command (i.e. what happens in `oneshot()`).
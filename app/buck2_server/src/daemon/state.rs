@@ -9,6 +9,7 @@
 
 use std::collections::HashMap;
 use std::path::Path;
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
@@ -20,12 +21,15 @@ use buck2_build_api::spawner::BuckSpawner;
 use buck2_cli_proto::unstable_dice_dump_request::DiceDumpFormat;
 use buck2_common::cas_digest::DigestAlgorithm;
 use buck2_common::cas_digest::DigestAlgorithmKind;
+use buck2_common::digest_pool::init_digest_pool;
 use buck2_common::ignores::ignore_set::IgnoreSet;
 use buck2_common::invocation_paths::InvocationPaths;
+use buck2_common::invocation_roots::home_buck_downloads_dir;
 use buck2_common::io::IoProvider;
 use buck2_common::legacy_configs::cells::BuckConfigBasedCells;
 use buck2_common::legacy_configs::init::DaemonStartupConfig;
 use buck2_common::legacy_configs::init::Timeout;
+use buck2_common::legacy_configs::LegacyBuckConfig;
 use buck2_core::buck2_env;
 use buck2_core::cells::name::CellName;
 use buck2_core::facebook_only;
@@ -36,13 +40,17 @@ use buck2_core::is_open_source;
 use buck2_core::rollout_percentage::RolloutPercentage;
 use buck2_core::tag_result;
 use buck2_events::dispatch::EventDispatcher;
-use buck2_events::sink::scribe;
+use buck2_events::sink::bes::BesConfig;
+use buck2_events::sink::bes::BesUploader;
+use buck2_events::sink::remote::new_remote_event_sink_if_enabled;
 use buck2_events::sink::tee::TeeSink;
 use buck2_events::source::ChannelEventSource;
+use buck2_events::EventSink;
 use buck2_events::EventSinkWithStats;
 use buck2_execute::digest_config::DigestConfig;
 use buck2_execute::execute::blocking::BlockingExecutor;
 use buck2_execute::execute::blocking::BuckBlockingExecutor;
+use buck2_execute::execute::resource_pools::DaemonResourcePools;
 use buck2_execute::materialize::materializer::MaterializationMethod;
 use buck2_execute::materialize::materializer::Materializer;
 use buck2_execute::re::manager::ReConnectionManager;
@@ -57,8 +65,10 @@ use buck2_execute_impl::materializers::sqlite::MaterializerStateSqliteDb;
 use buck2_execute_impl::re::paranoid_download::ParanoidDownloader;
 use buck2_file_watcher::file_watcher::FileWatcher;
 use buck2_forkserver::client::ForkserverClient;
+use buck2_http::HostPolicy;
 use buck2_http::HttpClient;
 use buck2_http::HttpClientBuilder;
+use buck2_re_configuration::HttpHeader;
 use buck2_re_configuration::RemoteExecutionStaticMetadata;
 use buck2_re_configuration::RemoteExecutionStaticMetadataImpl;
 use buck2_server_ctx::concurrency::ConcurrencyHandler;
@@ -74,11 +84,13 @@ use crate::active_commands::ActiveCommandDropGuard;
 use crate::ctx::BaseServerCommandContext;
 use crate::daemon::check_working_dir;
 use crate::daemon::disk_state::delete_unknown_disk_state;
+use crate::daemon::disk_state::maybe_initialize_bzl_cache;
 use crate::daemon::disk_state::maybe_initialize_materializer_sqlite_db;
+use crate::daemon::disk_state::maybe_initialize_source_digest_cache;
 use crate::daemon::disk_state::DiskStateOptions;
 use crate::daemon::forkserver::maybe_launch_forkserver;
 use crate::daemon::io_provider::create_io_provider;
-use crate::daemon::panic::DaemonStatePanicDiceDump;
+use crate::daemon::panic::DaemonStatePanic;
 use crate::daemon::server::BuckdServerInitPreferences;
 
 /// For a buckd process there is a single DaemonState created at startup and never destroyed.
@@ -135,6 +147,10 @@ pub struct DaemonStateData {
     #[allocative(skip)]
     pub scribe_sink: Option<Arc<dyn EventSinkWithStats>>,
 
+    /// Uploads builds and tests to a Build Event Service, if `[bes] address` is set.
+    #[allocative(skip)]
+    pub bes_uploader: Option<Arc<BesUploader>>,
+
     /// Whether or not to hash all commands
     pub hash_all_commands: bool,
 
@@ -171,6 +187,10 @@ pub struct DaemonStateData {
 
     /// Spawner
     pub spawner: Arc<BuckSpawner>,
+
+    /// Resource pools shared by the tests of all commands.
+    #[allocative(skip)]
+    pub resource_pools: Arc<DaemonResourcePools>,
 }
 
 impl DaemonStateData {
@@ -184,10 +204,19 @@ impl DaemonStateData {
     }
 }
 
-impl DaemonStatePanicDiceDump for DaemonStateData {
+impl DaemonStatePanic for DaemonStateData {
     fn dice_dump(&self, path: &Path, format: DiceDumpFormat) -> anyhow::Result<()> {
         self.dice_dump(path, format)
     }
+
+    fn dice_keys_currently_running(&self) -> Vec<String> {
+        self.dice_manager.unsafe_dice().keys_currently_running()
+    }
+
+    fn buckd_stderr(&self) -> Option<PathBuf> {
+        let daemon_dir = self.paths.daemon_dir().ok()?;
+        Some(daemon_dir.buckd_stderr().into_path_buf())
+    }
 }
 
 impl DaemonState {
@@ -284,6 +313,8 @@ impl DaemonState {
                 DigestConfig::leak_new(digest_algorithms, preferred_source_algorithm)
                     .context("Error initializing DigestConfig")?;
 
+            init_digest_pool(root_config.parse("buck2", "digest_threads")?)?;
+
             // TODO(rafaelc): merge configs from all cells once they are consistent
             let static_metadata = Arc::new(RemoteExecutionStaticMetadata::from_legacy_config(
                 root_config,
@@ -348,6 +379,23 @@ impl DaemonState {
                 }
             };
 
+            let source_digest_cache = maybe_initialize_source_digest_cache(
+                &disk_state_options,
+                &paths,
+                blocking_executor.dupe() as Arc<dyn BlockingExecutor>,
+                fs.dupe(),
+                digest_config,
+            )
+            .await?;
+
+            let bzl_cache = maybe_initialize_bzl_cache(
+                &disk_state_options,
+                &paths,
+                blocking_executor.dupe() as Arc<dyn BlockingExecutor>,
+                fs.dupe(),
+            )
+            .await?;
+
             let (io, _, (materializer_db, materializer_state)) = futures::future::try_join3(
                 create_io_provider(
                     fb,
@@ -355,6 +403,7 @@ impl DaemonState {
                     root_config,
                     digest_config.cas_digest_config(),
                     init_ctx.enable_trace_io,
+                    source_digest_cache.dupe(),
                 ),
                 (blocking_executor.dupe() as Arc<dyn BlockingExecutor>).execute_io_inline(|| {
                     // Using `execute_io_inline` is just out of convenience.
@@ -390,6 +439,7 @@ impl DaemonState {
                 Some(paths.re_logs_dir()),
                 paths.buck_out_path(),
                 init_ctx.daemon_startup_config.paranoid,
+                digest_config,
             ));
             let materializer = Self::create_materializer(
                 fb,
@@ -411,7 +461,7 @@ impl DaemonState {
                 maybe_launch_forkserver(root_config, &paths.forkserver_state_dir()).await?;
 
             let dice = init_ctx
-                .construct_dice(io.dupe(), digest_config, root_config)
+                .construct_dice(io.dupe(), digest_config, bzl_cache, root_config)
                 .await?;
 
             // TODO(cjhopman): We want to use Expr::True here, but we need to workaround
@@ -423,6 +473,7 @@ impl DaemonState {
                 root_config,
                 cells.dupe(),
                 ignore_specs,
+                source_digest_cache,
             )
             .with_context(|| {
                 format!(
@@ -463,6 +514,9 @@ impl DaemonState {
             )
             .context("failed to init scribe sink")?;
 
+            let bes_uploader =
+                Self::init_bes_uploader(root_config).context("failed to init BES uploader")?;
+
             let enable_restarter = root_config
                 .parse::<RolloutPercentage>("buck2", "restarter")?
                 .unwrap_or_else(RolloutPercentage::never)
@@ -493,6 +547,7 @@ impl DaemonState {
                 materializer,
                 forkserver,
                 scribe_sink,
+                bes_uploader,
                 hash_all_commands,
                 use_network_action_output_cache,
                 disk_state_options,
@@ -503,6 +558,7 @@ impl DaemonState {
                 http_client,
                 paranoid,
                 spawner: Arc::new(BuckSpawner::new(daemon_state_data_rt)),
+                resource_pools: Arc::new(DaemonResourcePools::default()),
             }))
         })
         .await?
@@ -595,7 +651,7 @@ impl DaemonState {
         message_batch_size: Option<usize>,
     ) -> anyhow::Result<Option<Arc<dyn EventSinkWithStats>>> {
         facebook_only();
-        scribe::new_thrift_scribe_sink_if_enabled(
+        new_remote_event_sink_if_enabled(
             fb,
             buffer_size,
             retry_backoff,
@@ -605,8 +661,29 @@ impl DaemonState {
         .map(|maybe_scribe| maybe_scribe.map(|scribe| Arc::new(scribe) as _))
     }
 
+    fn init_bes_uploader(
+        root_config: &LegacyBuckConfig,
+    ) -> anyhow::Result<Option<Arc<BesUploader>>> {
+        let Some(address) = root_config.get("bes", "address") else {
+            return Ok(None);
+        };
+        let headers = root_config
+            .parse_list::<HttpHeader>("bes", "http_headers")?
+            .unwrap_or_default()
+            .into_map(|h| (h.key, h.value));
+        let upload_timeout =
+            Duration::from_secs(root_config.parse("bes", "upload_timeout_s")?.unwrap_or(60));
+        Ok(Some(Arc::new(BesUploader::new(BesConfig {
+            address: address.to_owned(),
+            headers,
+            project_id: root_config.get("bes", "project_id").map(|s| s.to_owned()),
+            upload_timeout,
+        })?)))
+    }
+
     /// Prepares an event stream for a request by bootstrapping an event source and EventDispatcher pair. The given
-    /// EventDispatcher will log to the returned EventSource and (optionally) to Scribe if enabled via buckconfig.
+    /// EventDispatcher will log to the returned EventSource, (optionally) to Scribe if enabled via buckconfig, and
+    /// to the Build Event Service if `[bes]` is configured.
     pub async fn prepare_events(
         &self,
         trace_id: TraceId,
@@ -615,12 +692,15 @@ impl DaemonState {
         facebook_only();
         let (events, sink) = buck2_events::create_source_sink_pair();
         let data = self.data()?;
-        let dispatcher = if let Some(scribe_sink) = data.scribe_sink.dupe() {
-            EventDispatcher::new(trace_id, TeeSink::new(scribe_sink.to_event_sync(), sink))
-        } else {
-            EventDispatcher::new(trace_id, sink)
-        };
-        Ok((events, dispatcher))
+        let mut sink: Arc<dyn EventSink> = Arc::new(sink);
+        if let Some(scribe_sink) = data.scribe_sink.dupe() {
+            sink = Arc::new(TeeSink::new(scribe_sink.to_event_sync(), sink));
+        }
+        if let Some(bes_uploader) = &data.bes_uploader {
+            // First, so that the command is counted as uploading before the client gets its result.
+            sink = Arc::new(TeeSink::new(bes_uploader.invocation_sink().await, sink));
+        }
+        Ok((events, EventDispatcher::new(trace_id, sink)))
     }
 
     /// Prepares a ServerCommandContext for processing a complex command (that accesses the dice computation graph, for example).
@@ -670,6 +750,11 @@ impl DaemonState {
                 "sqlite-materializer-state:{}",
                 data.disk_state_options.sqlite_materializer_state
             ),
+            format!(
+                "source-digest-cache:{}",
+                data.disk_state_options.source_digest_cache
+            ),
+            format!("bzl-cache:{}", data.disk_state_options.bzl_cache),
             format!("paranoid:{}", data.paranoid.is_some()),
         ];
 
@@ -687,7 +772,7 @@ impl DaemonState {
             project_root: self.paths.project_root().clone(),
             events: dispatcher,
             daemon: data.dupe(), // FIXME: Remove the duplicative fields.
-            _drop_guard: drop_guard,
+            drop_guard,
             spawner: data.spawner.dupe(),
         })
     }
@@ -816,6 +901,13 @@ fn http_client_from_startup_config(
     };
     builder.with_max_redirects(config.http.max_redirects.unwrap_or(DEFAULT_MAX_REDIRECTS));
     builder.with_http2(config.http.http2);
+    builder.with_host_policy(HostPolicy::new(
+        config.http.offline,
+        config.http.allowed_hosts.clone(),
+    ));
+    if config.http.download_cache {
+        builder.with_download_cache_dir(Some(home_buck_downloads_dir()?.into_path_buf()));
+    }
     match config.http.connect_timeout() {
         Timeout::Value(d) => {
             builder.with_connect_timeout(Some(d));
@@ -925,4 +1017,34 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_from_startup_config_host_policy() -> anyhow::Result<()> {
+        let config = parse(
+            &[(
+                "/config",
+                indoc!(
+                    r#"
+                    [http]
+                    allowed_hosts = mirror.example.com, example.org
+                    "#,
+                ),
+            )],
+            "/config",
+        )?;
+        let startup_config = DaemonStartupConfig::new(&config)?;
+        let builder = http_client_from_startup_config(&startup_config)?;
+        assert_eq!(
+            &HostPolicy::new(
+                false,
+                Some(vec![
+                    "mirror.example.com".to_owned(),
+                    "example.org".to_owned()
+                ])
+            ),
+            builder.host_policy()
+        );
+
+        Ok(())
+    }
 }
@@ -0,0 +1,224 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Fetching of the external cells declared in the root buckconfig, see
+//! `buck2_common::legacy_configs::external_cells`.
+
+use std::process::Stdio;
+
+use anyhow::Context;
+use buck2_common::legacy_configs::external_cells::ExternalCell;
+use buck2_common::legacy_configs::external_cells::ExternalCellOrigin;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_core::fs::project::ProjectRoot;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use buck2_http::HttpClient;
+use buck2_util::process::async_background_command;
+use futures::StreamExt;
+use sha2::Digest;
+use sha2::Sha256;
+use tokio::io::AsyncWriteExt;
+
+#[derive(Debug, buck2_error::Error)]
+enum ExternalCellFetchError {
+    #[error("`git {0}` failed: {1}")]
+    Git(String, String),
+    #[error("Archive `{url}` has SHA256 `{actual}`, but `{expected}` was expected")]
+    HashMismatch {
+        url: String,
+        expected: String,
+        actual: String,
+    },
+    #[error("Unsupported archive `{0}`, expected a `.tar.gz`, `.tgz` or `.tar` URL")]
+    UnsupportedArchive(String),
+    #[error("Directory `{0}` does not exist in archive `{1}`")]
+    MissingStripPrefix(String, String),
+}
+
+/// Fetch the external cells which are not fetched yet. Returns whether anything was fetched.
+pub(crate) async fn fetch_external_cells(
+    project_root: &ProjectRoot,
+    cells: &[ExternalCell],
+    http_client: &HttpClient,
+) -> anyhow::Result<bool> {
+    let mut fetched = false;
+    for cell in cells {
+        let path = cell.path();
+        let dest = project_root.resolve(&path);
+        if fs_util::try_exists(&dest)? {
+            continue;
+        }
+
+        tracing::info!("Fetching external cell `{}`", cell.alias);
+        // Fetch to a temporary directory and rename it at the end, so that an interrupted fetch is
+        // never mistaken for a complete one. The directory is unique, so that concurrent fetches
+        // (e.g. by daemons of different isolation dirs) don't write to the same files.
+        let tmp = project_root.resolve(&ProjectRelativePathBuf::unchecked_new(format!(
+            "{}.tmp-{}-{:016x}",
+            path,
+            std::process::id(),
+            rand::random::<u64>(),
+        )));
+        fs_util::create_dir_all(&tmp)?;
+
+        let res = fetch_to(&tmp, &dest, &cell.origin, http_client).await;
+        if let Err(e) = fs_util::remove_all(&tmp) {
+            tracing::warn!("Failed to remove `{}`: {:#}", tmp, e);
+        }
+        res?;
+        fetched = true;
+    }
+    Ok(fetched)
+}
+
+/// Fetch a cell in the empty directory `tmp`, and move it to `dest`.
+async fn fetch_to(
+    tmp: &AbsNormPathBuf,
+    dest: &AbsNormPath,
+    origin: &ExternalCellOrigin,
+    http_client: &HttpClient,
+) -> anyhow::Result<()> {
+    let contents = tmp.join_normalized("contents")?;
+    fs_util::create_dir_all(&contents)?;
+
+    let root = match origin {
+        ExternalCellOrigin::Git { origin, commit } => {
+            fetch_git(&contents, origin, commit).await?;
+            contents
+        }
+        ExternalCellOrigin::HttpArchive {
+            url,
+            sha256,
+            strip_prefix,
+        } => {
+            let archive = tmp.join_normalized("archive")?;
+            fetch_http_archive(&archive, &contents, url, sha256, http_client).await?;
+            match strip_prefix {
+                Some(prefix) => {
+                    let root = contents.join_normalized(prefix)?;
+                    if !fs_util::try_exists(&root)? {
+                        return Err(ExternalCellFetchError::MissingStripPrefix(
+                            prefix.clone(),
+                            url.clone(),
+                        )
+                        .into());
+                    }
+                    root
+                }
+                None => contents,
+            }
+        }
+    };
+
+    // The rename is atomic, and fails if another fetch of the same cell finished first, in which
+    // case its copy is used.
+    if let Err(e) = fs_util::rename(&root, dest) {
+        if !fs_util::try_exists(dest)? {
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+async fn git(dir: &AbsNormPath, args: &[&str]) -> anyhow::Result<()> {
+    let output = async_background_command("git")
+        .current_dir(dir)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .with_context(|| format!("Error running `git {}`", args.join(" ")))?;
+    if !output.status.success() {
+        return Err(ExternalCellFetchError::Git(
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+async fn fetch_git(dir: &AbsNormPath, origin: &str, commit: &str) -> anyhow::Result<()> {
+    git(dir, &["init", "--quiet"]).await?;
+    // Fetching a single commit by hash also verifies the contents.
+    git(dir, &["fetch", "--quiet", "--depth", "1", origin, commit]).await?;
+    git(dir, &["checkout", "--quiet", "--detach", "FETCH_HEAD"]).await?;
+    // The history is not needed, and the cell should only contain the sources.
+    fs_util::remove_all(dir.join_normalized(".git")?)?;
+    Ok(())
+}
+
+/// Download the archive at `url` to the file `archive`, verify it, and extract it to `dir`.
+async fn fetch_http_archive(
+    archive: &AbsNormPathBuf,
+    dir: &AbsNormPathBuf,
+    url: &str,
+    sha256: &str,
+    http_client: &HttpClient,
+) -> anyhow::Result<()> {
+    let gzip = if url.ends_with(".tar.gz") || url.ends_with(".tgz") {
+        true
+    } else if url.ends_with(".tar") {
+        false
+    } else {
+        return Err(ExternalCellFetchError::UnsupportedArchive(url.to_owned()).into());
+    };
+
+    let mut body = http_client
+        .get(url)
+        .await
+        .with_context(|| format!("Error downloading `{}`", url))?
+        .into_body();
+    // Stream to disk rather than keeping the archive in memory, archives can be large.
+    let mut file = tokio::fs::File::create(archive)
+        .await
+        .with_context(|| format!("Error creating `{}`", archive))?;
+    let mut hasher = Sha256::new();
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.with_context(|| format!("Error downloading `{}`", url))?;
+        hasher.update(&chunk);
+        file.write_all(&chunk)
+            .await
+            .with_context(|| format!("Error writing `{}`", archive))?;
+    }
+    file.flush()
+        .await
+        .with_context(|| format!("Error writing `{}`", archive))?;
+    drop(file);
+
+    let actual: String = hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    if actual != sha256 {
+        return Err(ExternalCellFetchError::HashMismatch {
+            url: url.to_owned(),
+            expected: sha256.to_owned(),
+            actual,
+        }
+        .into());
+    }
+
+    let archive = archive.clone();
+    let dir = dir.clone();
+    tokio::task::spawn_blocking(move || {
+        let file = std::io::BufReader::new(std::fs::File::open(&archive)?);
+        if gzip {
+            tar::Archive::new(flate2::read::GzDecoder::new(file)).unpack(&dir)
+        } else {
+            tar::Archive::new(file).unpack(&dir)
+        }
+    })
+    .await?
+    .with_context(|| format!("Error extracting `{}`", url))?;
+    Ok(())
+}
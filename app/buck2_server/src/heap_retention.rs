@@ -0,0 +1,241 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Report of which frozen Starlark heaps keep the most memory alive.
+//!
+//! Frozen heaps keep alive the heaps they reference (e.g. the analysis heap of a target keeps
+//! alive the heaps of its dependencies' providers, and of the `.bzl` modules it loaded). We build
+//! the dominator tree of that graph, so that every heap is attributed to the closest heap through
+//! which all the paths to it go, and render it with the retained size of every subtree.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use buck2_event_observer::humanized::HumanizedBytes;
+use buck2_util::dominators::dominators;
+use dupe::Dupe;
+use starlark::values::FrozenHeapRef;
+
+/// Heaps retaining less than this fraction of the total are folded into one line.
+const MIN_RETAINED_FRACTION: f64 = 0.001;
+
+/// Number of value types to list for every heap.
+const TOP_TYPES: usize = 5;
+
+#[derive(Default)]
+pub(crate) struct HeapRetentionGraph {
+    roots: Vec<FrozenHeapRef>,
+    names: HashMap<FrozenHeapRef, String>,
+}
+
+impl HeapRetentionGraph {
+    /// Add a heap from which retention is computed, e.g. the analysis heap of a profiled target.
+    pub(crate) fn add_root(&mut self, heap: &FrozenHeapRef, name: String) {
+        self.add_name(heap, name);
+        self.roots.push(heap.dupe());
+    }
+
+    /// Name a heap so that it can be recognized in the report. The first name wins.
+    pub(crate) fn add_name(&mut self, heap: &FrozenHeapRef, name: String) {
+        self.names.entry(heap.dupe()).or_insert(name);
+    }
+
+    pub(crate) fn report(&self) -> HeapRetentionReport<'_> {
+        // Node 0 is a virtual root pointing to all the roots.
+        let mut heaps = vec![FrozenHeapRef::default()];
+        let mut index = HashMap::new();
+        let mut succs: Vec<Vec<usize>> = vec![Vec::new()];
+
+        let mut queue = Vec::new();
+        for root in &self.roots {
+            let i = intern(root, &mut heaps, &mut index, &mut succs, &mut queue);
+            succs[0].push(i);
+        }
+        while let Some(i) = queue.pop() {
+            let refs: Vec<_> = heaps[i].refs().cloned().collect();
+            for r in refs {
+                let j = intern(&r, &mut heaps, &mut index, &mut succs, &mut queue);
+                succs[i].push(j);
+            }
+        }
+
+        let (postorder, idom) = dominators(&succs);
+
+        let self_bytes: Vec<u64> = heaps.iter().map(|h| h.allocated_bytes() as u64).collect();
+        let mut retained_bytes = self_bytes.clone();
+        let mut children = vec![Vec::new(); heaps.len()];
+        // A node's immediate dominator is its ancestor in the DFS tree, so it comes later in
+        // postorder, and all of a node's children are done by the time we reach it.
+        for &n in &postorder {
+            if n != 0 {
+                retained_bytes[idom[n]] += retained_bytes[n];
+                children[idom[n]].push(n);
+            }
+        }
+        for c in &mut children {
+            c.sort_by_key(|&n| std::cmp::Reverse(retained_bytes[n]));
+        }
+
+        HeapRetentionReport {
+            heaps,
+            names: &self.names,
+            self_bytes,
+            retained_bytes,
+            children,
+        }
+    }
+}
+
+fn intern(
+    heap: &FrozenHeapRef,
+    heaps: &mut Vec<FrozenHeapRef>,
+    index: &mut HashMap<FrozenHeapRef, usize>,
+    succs: &mut Vec<Vec<usize>>,
+    queue: &mut Vec<usize>,
+) -> usize {
+    *index.entry(heap.dupe()).or_insert_with(|| {
+        heaps.push(heap.dupe());
+        succs.push(Vec::new());
+        queue.push(heaps.len() - 1);
+        heaps.len() - 1
+    })
+}
+
+pub(crate) struct HeapRetentionReport<'a> {
+    heaps: Vec<FrozenHeapRef>,
+    names: &'a HashMap<FrozenHeapRef, String>,
+    self_bytes: Vec<u64>,
+    retained_bytes: Vec<u64>,
+    children: Vec<Vec<usize>>,
+}
+
+impl<'a> HeapRetentionReport<'a> {
+    /// Total bytes retained by all the roots.
+    pub(crate) fn total_retained_bytes(&self) -> u64 {
+        self.retained_bytes[0]
+    }
+
+    pub(crate) fn render(&self) -> String {
+        let mut out = String::new();
+        writeln!(
+            out,
+            "Total retained: {}",
+            HumanizedBytes::new(self.total_retained_bytes())
+        )
+        .unwrap();
+        let min_retained = (self.total_retained_bytes() as f64 * MIN_RETAINED_FRACTION) as u64;
+        self.render_children(0, 0, min_retained, &mut out);
+        out
+    }
+
+    fn render_children(&self, node: usize, depth: usize, min_retained: u64, out: &mut String) {
+        let indent = "  ".repeat(depth);
+        let mut folded = 0;
+        let mut folded_bytes = 0;
+        for &child in &self.children[node] {
+            if self.retained_bytes[child] < min_retained {
+                folded += 1;
+                folded_bytes += self.retained_bytes[child];
+                continue;
+            }
+            let name = self
+                .names
+                .get(&self.heaps[child])
+                .map_or("<unnamed heap>", |s| s.as_str());
+            writeln!(
+                out,
+                "{}{} retained, {} self: {}",
+                indent,
+                HumanizedBytes::new(self.retained_bytes[child]),
+                HumanizedBytes::new(self.self_bytes[child]),
+                name
+            )
+            .unwrap();
+            for (ty, count, bytes) in self.top_types(child) {
+                writeln!(
+                    out,
+                    "{}    {} in {} `{}`",
+                    indent,
+                    HumanizedBytes::new(bytes),
+                    count,
+                    ty
+                )
+                .unwrap();
+            }
+            self.render_children(child, depth + 1, min_retained, out);
+        }
+        if folded != 0 {
+            writeln!(
+                out,
+                "{}{} retained by {} smaller heaps",
+                indent,
+                HumanizedBytes::new(folded_bytes),
+                folded
+            )
+            .unwrap();
+        }
+    }
+
+    fn top_types(&self, node: usize) -> Vec<(String, usize, u64)> {
+        let mut types: Vec<_> = self.heaps[node]
+            .allocated_summary()
+            .summary()
+            .into_iter()
+            .map(|(ty, (count, bytes))| (ty, count, bytes as u64))
+            .collect();
+        types.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+        types.truncate(TOP_TYPES);
+        types
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use starlark::values::FrozenHeap;
+
+    use super::*;
+
+    fn heap(contents: &str, refs: &[&FrozenHeapRef]) -> FrozenHeapRef {
+        let heap = FrozenHeap::new();
+        heap.alloc_str(contents);
+        for r in refs {
+            heap.add_reference(r);
+        }
+        heap.into_ref()
+    }
+
+    #[test]
+    fn test_shared_heap_is_attributed_to_common_dominator() {
+        let shared = heap(&"x".repeat(10000), &[]);
+        let own = heap(&"y".repeat(1000), &[]);
+        let a = heap("a", &[&shared, &own]);
+        let b = heap("b", &[&shared]);
+
+        let mut graph = HeapRetentionGraph::default();
+        graph.add_root(&a, "a".to_owned());
+        graph.add_root(&b, "b".to_owned());
+        graph.add_name(&shared, "shared.bzl".to_owned());
+        graph.add_name(&own, "own.bzl".to_owned());
+        let report = graph.report();
+
+        let total: u64 = [&shared, &own, &a, &b]
+            .iter()
+            .map(|h| h.allocated_bytes() as u64)
+            .sum();
+        assert_eq!(total, report.total_retained_bytes());
+
+        let rendered = report.render();
+        let lines: Vec<_> = rendered.lines().collect();
+        // `shared` is reachable from both roots so it is not attributed to either of them.
+        let shared_line = lines.iter().find(|l| l.contains("shared.bzl")).unwrap();
+        assert!(!shared_line.starts_with(' '), "{}", rendered);
+        let own_line = lines.iter().find(|l| l.contains("own.bzl")).unwrap();
+        assert!(own_line.starts_with("  "), "{}", rendered);
+    }
+}
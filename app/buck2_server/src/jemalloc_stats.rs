@@ -14,6 +14,9 @@ use buck2_common::memory::allocator_stats;
 pub struct AllocatorStats {
     pub bytes_active: Option<u64>,
     pub bytes_allocated: Option<u64>,
+    pub bytes_resident: Option<u64>,
+    pub bytes_retained: Option<u64>,
+    pub bytes_metadata: Option<u64>,
 }
 
 pub fn get_allocator_stats() -> anyhow::Result<AllocatorStats> {
@@ -35,12 +38,21 @@ pub fn get_allocator_stats() -> anyhow::Result<AllocatorStats> {
 
     let mut bytes_active = None;
     let mut bytes_allocated = None;
+    let mut bytes_resident = None;
+    let mut bytes_retained = None;
+    let mut bytes_metadata = None;
     set(&alloc_stats, "active", &mut bytes_active)?;
     set(&alloc_stats, "allocated", &mut bytes_allocated)?;
+    set(&alloc_stats, "resident", &mut bytes_resident)?;
+    set(&alloc_stats, "retained", &mut bytes_retained)?;
+    set(&alloc_stats, "metadata", &mut bytes_metadata)?;
 
     Ok(AllocatorStats {
         bytes_active,
         bytes_allocated,
+        bytes_resident,
+        bytes_retained,
+        bytes_metadata,
     })
 }
 
@@ -56,6 +68,12 @@ mod tests {
             if let Ok(alloc_stats) = get_allocator_stats() {
                 assert!(alloc_stats.bytes_active.is_some());
                 assert!(alloc_stats.bytes_allocated.is_some());
+                assert!(alloc_stats.bytes_resident.is_some());
+                assert!(alloc_stats.bytes_retained.is_some());
+                assert!(alloc_stats.bytes_metadata.is_some());
+                // jemalloc documents `allocated <= active <= resident`.
+                assert!(alloc_stats.bytes_allocated <= alloc_stats.bytes_active);
+                assert!(alloc_stats.bytes_active <= alloc_stats.bytes_resident);
                 return Ok(());
             }
             return Err(anyhow::anyhow!("Allocator stats not found"));
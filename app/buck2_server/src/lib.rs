@@ -20,7 +20,9 @@ mod configs;
 mod ctx;
 pub mod daemon;
 mod dice_tracker;
+mod external_cells;
 mod file_status;
+mod heap_retention;
 mod heartbeat_guard;
 mod host_info;
 mod jemalloc_stats;
@@ -30,5 +32,7 @@ mod net_io;
 pub(crate) mod new_generic;
 pub mod profile;
 mod snapshot;
+mod soft_error_history;
+mod stack_dump;
 mod subscription;
 mod trace_io;
@@ -21,6 +21,7 @@ use buck2_common::file_ops::FileOps;
 use buck2_common::package_listing::dice::DicePackageListingResolver;
 use buck2_core::bzl::ImportPath;
 use buck2_core::cells::build_file_cell::BuildFileCell;
+use buck2_core::cells::cell_path::CellPath;
 use buck2_core::cells::cell_path::CellPathRef;
 use buck2_core::cells::CellResolver;
 use buck2_core::fs::paths::abs_path::AbsPath;
@@ -61,6 +62,7 @@ use starlark::analysis::find_call_name::AstModuleFindCallName;
 use starlark::codemap::Span;
 use starlark::docs::Doc;
 use starlark::docs::DocItem;
+use starlark::docs::DocMember;
 use starlark::docs::DocModule;
 use starlark::docs::Identifier;
 use starlark::docs::Location;
@@ -175,6 +177,8 @@ struct DocsCache {
     global_urls: HashMap<String, LspUrl>,
     /// Mapping of starlark: urls to a synthesized starlark representation.
     native_starlark_files: HashMap<LspUrl, String>,
+    /// Docs of the global functions and properties, used for hover and signature help.
+    environment: DocModule,
 }
 
 #[derive(buck2_error::Error, Debug)]
@@ -244,7 +248,21 @@ impl DocsCache {
     ) -> anyhow::Result<Self> {
         let mut global_urls = HashMap::with_capacity(builtin_symbols.len());
         let mut native_starlark_files = HashMap::new();
+        let mut environment = DocModule::default();
         for doc in builtin_symbols {
+            match &doc.item {
+                DocItem::Function(f) => {
+                    environment
+                        .members
+                        .insert(doc.id.name.clone(), DocMember::Function(f.clone()));
+                }
+                DocItem::Property(p) => {
+                    environment
+                        .members
+                        .insert(doc.id.name.clone(), DocMember::Property(p.clone()));
+                }
+                DocItem::Module(_) | DocItem::Object(_) => {}
+            }
             let url = match &doc.id.location {
                 Some(l) => location_lookup(l).await?,
                 None => {
@@ -276,6 +294,7 @@ impl DocsCache {
         Ok(Self {
             global_urls,
             native_starlark_files,
+            environment,
         })
     }
 
@@ -288,6 +307,10 @@ impl DocsCache {
     fn url_for_symbol(&self, symbol: &str) -> Option<&LspUrl> {
         self.global_urls.get(symbol)
     }
+
+    fn environment(&self) -> &DocModule {
+        &self.environment
+    }
 }
 
 #[derive(Debug, buck2_error::Error)]
@@ -377,6 +400,24 @@ enum BuckLspContextError {
     /// The scheme provided was not correct or supported.
     #[error("Url `{}` was expected to be of type `{}`", .1, .0)]
     WrongScheme(String, LspUrl),
+    #[error("Path `{}` is not a file that can be loaded", .0)]
+    NotAFile(CellPath),
+}
+
+/// Render `target` as it should be written in a `load()` in `current_file`: relative to the
+/// directory of `current_file` if possible, otherwise relative to the cell, or with the cell name
+/// if the files are in different cells.
+fn render_load_path(target: CellPathRef, current_file: CellPathRef) -> anyhow::Result<String> {
+    let (Some(dir), Some(file)) = (target.path().parent(), target.path().file_name()) else {
+        return Err(BuckLspContextError::NotAFile(target.to_owned()).into());
+    };
+    if target.cell() != current_file.cell() {
+        Ok(format!("{}//{}:{}", target.cell(), dir, file))
+    } else if current_file.path().parent() == Some(dir) {
+        Ok(format!(":{}", file))
+    } else {
+        Ok(format!("//{}:{}", dir, file))
+    }
 }
 
 impl<'a> BuckLspContext<'a> {
@@ -415,13 +456,19 @@ impl<'a> BuckLspContext<'a> {
             .await?
     }
 
-    async fn import_path(&self, path: &Path) -> anyhow::Result<OwnedStarlarkModulePath> {
+    /// Find the cell, and the path within it, of an absolute path. This works for files in any
+    /// cell of the project, not just the one the LSP was started from.
+    async fn cell_path(&self, path: &Path) -> anyhow::Result<CellPath> {
         let abs_path = AbsPath::new(path)?;
         let relative_path = self.fs.relativize_any(abs_path)?;
         let cell_resolver = self
             .with_dice_ctx(|mut dice_ctx| async move { dice_ctx.get_cell_resolver().await })
             .await?;
-        let cell_path = cell_resolver.get_cell_path(&relative_path)?;
+        cell_resolver.get_cell_path(&relative_path)
+    }
+
+    async fn import_path(&self, path: &Path) -> anyhow::Result<OwnedStarlarkModulePath> {
+        let cell_path = self.cell_path(path).await?;
 
         match path.extension() {
             Some(e) if e == "bxl" => Ok(OwnedStarlarkModulePath::BxlFile(BxlFilePath::new(
@@ -758,15 +805,48 @@ impl<'a> LspContext for BuckLspContext<'a> {
 
     fn render_as_load(
         &self,
-        _target: &LspUrl,
-        _current_file: &LspUrl,
+        target: &LspUrl,
+        current_file: &LspUrl,
         _workspace_root: Option<&Path>,
     ) -> anyhow::Result<String> {
-        Err(anyhow::anyhow!("Not yet implemented, render_as_load"))
+        let dispatcher = self.server_ctx.events().dupe();
+        self.runtime
+            .block_on(with_dispatcher_async(dispatcher, async {
+                match (target, current_file) {
+                    (LspUrl::File(target), LspUrl::File(current_file)) => {
+                        let target = self.cell_path(target).await?;
+                        let current_file = self.cell_path(current_file).await?;
+                        render_load_path(target.as_ref(), current_file.as_ref())
+                    }
+                    (LspUrl::File(_), _) => Err(ResolveLoadError::WrongScheme(
+                        "file://".to_owned(),
+                        current_file.clone(),
+                    )
+                    .into()),
+                    _ => Err(
+                        ResolveLoadError::WrongScheme("file://".to_owned(), target.clone()).into(),
+                    ),
+                }
+            }))
     }
 
     fn get_environment(&self, _uri: &LspUrl) -> DocModule {
-        DocModule::default()
+        let dispatcher = self.server_ctx.events().dupe();
+        self.runtime
+            .block_on(with_dispatcher_async(dispatcher, async {
+                let docs_cache = self
+                    .with_dice_ctx(|dice_ctx| async {
+                        self.docs_cache_manager.get_cache(dice_ctx).await
+                    })
+                    .await;
+                match docs_cache {
+                    Ok(docs_cache) => docs_cache.environment().clone(),
+                    Err(e) => {
+                        tracing::warn!("Failed to get the global environment: {:#}", e);
+                        DocModule::default()
+                    }
+                }
+            }))
     }
 }
 
@@ -926,6 +1006,7 @@ fn handle_outgoing_lsp_message(
 
 #[cfg(test)]
 mod tests {
+    use buck2_core::cells::cell_path::CellPathRef;
     use lsp_types::Url;
     use maplit::hashmap;
     use starlark::docs::Doc;
@@ -935,6 +1016,7 @@ mod tests {
     use starlark::docs::Location;
     use starlark_lsp::server::LspUrl;
 
+    use crate::lsp::render_load_path;
     use crate::lsp::DocsCache;
     use crate::lsp::DOCS_DIRECTORY_KEY;
 
@@ -996,7 +1078,29 @@ mod tests {
             &LspUrl::try_from(Url::parse("file:/usr/local/dir/prelude.bzl")?)?,
             cache.url_for_symbol("prelude_function").unwrap()
         );
+        assert_eq!(3, cache.environment().members.len());
+
+        Ok(())
+    }
 
+    #[test]
+    fn renders_load_paths() -> anyhow::Result<()> {
+        let current_file = CellPathRef::testing_new("root//foo/BUCK");
+        assert_eq!(
+            ":defs.bzl",
+            render_load_path(CellPathRef::testing_new("root//foo/defs.bzl"), current_file)?
+        );
+        assert_eq!(
+            "//bar:defs.bzl",
+            render_load_path(CellPathRef::testing_new("root//bar/defs.bzl"), current_file)?
+        );
+        assert_eq!(
+            "prelude//cxx:defs.bzl",
+            render_load_path(
+                CellPathRef::testing_new("prelude//cxx/defs.bzl"),
+                current_file
+            )?
+        );
         Ok(())
     }
 }
@@ -19,6 +19,7 @@ pub(crate) async fn new_generic_command(
     context: &ServerCommandContext<'_>,
     req: buck2_cli_proto::NewGenericRequestMessage,
 ) -> anyhow::Result<buck2_cli_proto::NewGenericResponseMessage> {
+    let client_context = req.context.context("Missing client context")?;
     let req = req.new_generic_request;
     let req: NewGenericRequest =
         serde_json::from_str(&req).context("Could not deserialize `NewGenericRequest`")?;
@@ -29,6 +30,18 @@ pub(crate) async fn new_generic_command(
         NewGenericRequest::DebugEval(e) => NewGenericResponse::DebugEval(
             OTHER_SERVER_COMMANDS.get()?.debug_eval(context, e).await?,
         ),
+        NewGenericRequest::ChangedTargets(e) => NewGenericResponse::ChangedTargets(
+            OTHER_SERVER_COMMANDS
+                .get()?
+                .changed_targets(context, e)
+                .await?,
+        ),
+        NewGenericRequest::DebugDeterminism(e) => NewGenericResponse::DebugDeterminism(
+            OTHER_SERVER_COMMANDS
+                .get()?
+                .debug_determinism(context, &client_context, e)
+                .await?,
+        ),
     };
     let resp = serde_json::to_string(&resp).context("Could not serialize `NewGenericResponse`")?;
     Ok(buck2_cli_proto::NewGenericResponseMessage {
@@ -7,32 +7,45 @@
  * of this source tree.
  */
 
+use std::collections::HashSet;
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Instant;
 
 use anyhow::Context as _;
 use async_trait::async_trait;
 use buck2_analysis::analysis::calculation::profile_analysis;
 use buck2_analysis::analysis::calculation::profile_analysis_recursively;
+use buck2_build_api::analysis::calculation::RuleAnalysisCalculation;
 use buck2_cli_proto::profile_request::ProfileOpts;
+use buck2_cli_proto::profile_request::Profiler;
 use buck2_cli_proto::target_profile::Action;
 use buck2_cli_proto::ClientContext;
 use buck2_common::dice::cells::HasCellResolver;
 use buck2_common::dice::file_ops::DiceFileOps;
 use buck2_common::global_cfg_options::GlobalCfgOptions;
+use buck2_common::package_listing::dice::DicePackageListingResolver;
 use buck2_common::pattern::resolve::resolve_target_patterns;
 use buck2_core::cells::build_file_cell::BuildFileCell;
+use buck2_core::configuration::compatibility::MaybeCompatible;
+use buck2_core::fs::fs_util;
 use buck2_core::fs::paths::abs_path::AbsPath;
 use buck2_core::package::PackageLabel;
 use buck2_core::pattern::pattern_type::TargetPatternExtra;
 use buck2_core::pattern::PackageSpec;
-use buck2_core::target::label::TargetLabel;
+use buck2_core::pattern::ParsedPattern;
+use buck2_core::target::configured_target_label::ConfiguredTargetLabel;
 use buck2_futures::spawn::spawn_cancellable;
 use buck2_interpreter::dice::starlark_profiler::StarlarkProfilerConfiguration;
+use buck2_interpreter::load_module::InterpreterCalculation;
 use buck2_interpreter::starlark_profiler::StarlarkProfileDataAndStats;
 use buck2_interpreter::starlark_profiler::StarlarkProfiler;
 use buck2_interpreter::starlark_profiler::StarlarkProfilerOrInstrumentation;
 use buck2_interpreter_for_build::interpreter::dice_calculation_delegate::HasCalculationDelegate;
+use buck2_node::load_patterns::load_patterns;
+use buck2_node::load_patterns::MissingTargetBehavior;
+use buck2_node::nodes::configured_frontend::ConfiguredTargetNodeCalculation;
+use buck2_node::rule_type::RuleType;
 use buck2_node::target_calculation::ConfiguredTargetCalculation;
 use buck2_profile::get_profile_response;
 use buck2_profile::starlark_profiler_configuration_from_request;
@@ -45,35 +58,42 @@ use buck2_server_ctx::template::run_server_command;
 use buck2_server_ctx::template::ServerCommandTemplate;
 use dice::DiceTransaction;
 use dupe::Dupe;
+use dupe::IterDupedExt;
 use futures::future::FutureExt;
 
+use crate::heap_retention::HeapRetentionGraph;
+
 async fn generate_profile_analysis(
     mut ctx: DiceTransaction,
-    package: PackageLabel,
-    spec: PackageSpec<TargetPatternExtra>,
+    parsed_patterns: Vec<ParsedPattern<TargetPatternExtra>>,
     global_cfg_options: GlobalCfgOptions,
     profile_mode: &StarlarkProfilerConfiguration,
 ) -> anyhow::Result<Arc<StarlarkProfileDataAndStats>> {
-    let (target, TargetPatternExtra) = match spec {
-        PackageSpec::Targets(targets) => one(targets).context("Invalid targets"),
-        PackageSpec::All => Err(anyhow::Error::msg("Cannot use a package")),
-    }
-    .context("Did not find exactly one target")?;
-
-    let label = TargetLabel::new(package.dupe(), target.as_ref());
-
-    let configured_target = ctx
-        .get_configured_target(&label, &global_cfg_options)
-        .await?;
+    let configured_targets =
+        configured_targets(&mut ctx, parsed_patterns, &global_cfg_options).await?;
 
     match profile_mode {
         StarlarkProfilerConfiguration::ProfileLastAnalysis(profile_mode) => {
-            profile_analysis(&mut ctx, &configured_target, profile_mode)
-                .await
-                .context("Analysis failed")
+            let profiles = ctx
+                .try_compute_join(&configured_targets, |ctx, target| {
+                    async move {
+                        profile_analysis(ctx, target, profile_mode)
+                            .await
+                            .with_context(|| format!("Analysis of `{}` failed", target))
+                    }
+                    .boxed()
+                })
+                .await?;
+
+            // As for loading, some profile modes cannot be merged, so only merge if we have to.
+            if profiles.len() == 1 {
+                return Ok(profiles.into_iter().next().unwrap());
+            }
+
+            StarlarkProfileDataAndStats::merge(profiles.iter().map(|x| &**x)).map(Arc::new)
         }
         StarlarkProfilerConfiguration::ProfileAnalysisRecursively(_) => {
-            profile_analysis_recursively(&mut ctx, &configured_target)
+            profile_analysis_recursively(&mut ctx, &configured_targets)
                 .await
                 .context("Recursive profile analysis failed")
                 .map(Arc::new)
@@ -82,6 +102,234 @@ async fn generate_profile_analysis(
     }
 }
 
+async fn configured_targets(
+    ctx: &mut DiceTransaction,
+    parsed_patterns: Vec<ParsedPattern<TargetPatternExtra>>,
+    global_cfg_options: &GlobalCfgOptions,
+) -> anyhow::Result<Vec<ConfiguredTargetLabel>> {
+    let loaded = load_patterns(ctx, parsed_patterns, MissingTargetBehavior::Fail).await?;
+
+    let mut labels = Vec::new();
+    for (_package, result) in loaded.into_iter() {
+        labels.extend(result?.into_values().map(|node| node.label().dupe()));
+    }
+    if labels.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Target patterns did not match any targets to profile"
+        ));
+    }
+
+    ctx.try_compute_join(labels, |ctx, label| {
+        async move { ctx.get_configured_target(&label, global_cfg_options).await }.boxed()
+    })
+    .await
+}
+
+async fn generate_heap_retention_report(
+    mut ctx: DiceTransaction,
+    parsed_patterns: Vec<ParsedPattern<TargetPatternExtra>>,
+    global_cfg_options: GlobalCfgOptions,
+    recursive: bool,
+    output: &AbsPath,
+) -> anyhow::Result<buck2_cli_proto::ProfileResponse> {
+    let start = Instant::now();
+
+    let targets = configured_targets(&mut ctx, parsed_patterns, &global_cfg_options).await?;
+    let roots = ctx
+        .try_compute_join(&targets, |ctx, target| {
+            async move {
+                ctx.get_configured_target_node(target)
+                    .await?
+                    .require_compatible()
+            }
+            .boxed()
+        })
+        .await?;
+
+    // Name the heaps of all the dependencies too, as that is most of what a target retains.
+    let mut nodes = Vec::new();
+    let mut visited = HashSet::new();
+    let mut stack = roots.clone();
+    while let Some(node) = stack.pop() {
+        if visited.insert(node.label().dupe()) {
+            stack.extend(node.deps().duped());
+            nodes.push(node);
+        }
+    }
+
+    let analyses = ctx
+        .try_compute_join(&nodes, |ctx, node| {
+            async move { ctx.get_analysis_result(node.label()).await }.boxed()
+        })
+        .await?;
+
+    let mut rule_files = HashSet::new();
+    for node in &nodes {
+        if let RuleType::Starlark(rule_type) = node.rule_type() {
+            rule_files.insert(rule_type.import_path.clone());
+        }
+    }
+    let mut modules = ctx
+        .try_compute_join(&rule_files, |ctx, path| {
+            async move { ctx.get_loaded_module_from_import_path(path).await }.boxed()
+        })
+        .await?;
+
+    let mut graph = HeapRetentionGraph::default();
+    let root_labels: HashSet<_> = roots.iter().map(|node| node.label()).collect();
+    for (node, analysis) in nodes.iter().zip(analyses) {
+        let MaybeCompatible::Compatible(analysis) = analysis else {
+            continue;
+        };
+        let heap = analysis.providers().value().owner();
+        if recursive || root_labels.contains(node.label()) {
+            graph.add_root(heap, node.label().to_string());
+        } else {
+            graph.add_name(heap, node.label().to_string());
+        }
+    }
+    let mut visited_modules = HashSet::new();
+    while let Some(module) = modules.pop() {
+        if visited_modules.insert(module.path().to_string()) {
+            graph.add_name(module.env().frozen_heap(), module.path().to_string());
+            modules.extend(module.loaded_modules().map.values().duped());
+        }
+    }
+
+    let report = graph.report();
+    fs_util::write(output, report.render()).context("Failed to write heap retention report")?;
+
+    Ok(buck2_cli_proto::ProfileResponse {
+        elapsed: Some(start.elapsed().try_into()?),
+        total_retained_bytes: report.total_retained_bytes(),
+    })
+}
+
+async fn parse_patterns(
+    server_ctx: &dyn ServerCommandContextTrait,
+    ctx: &mut DiceTransaction,
+    client_ctx: &ClientContext,
+    target_patterns: &[buck2_data::TargetPattern],
+) -> anyhow::Result<(GlobalCfgOptions, Vec<ParsedPattern<TargetPatternExtra>>)> {
+    let global_cfg_options =
+        global_cfg_options_from_client_context(client_ctx, server_ctx, ctx).await?;
+
+    let parsed_patterns = parse_patterns_from_cli_args::<TargetPatternExtra>(
+        ctx,
+        target_patterns,
+        server_ctx.working_dir(),
+    )
+    .await?;
+
+    Ok((global_cfg_options, parsed_patterns))
+}
+
+/// Where the time went when loading one `BUCK` file.
+#[derive(serde::Serialize)]
+struct PackageLoadingStats {
+    package: String,
+    /// Time to assemble the list of files owned by the package.
+    listing_ms: u64,
+    /// Time spent in `glob` calls.
+    glob_ms: u64,
+    glob_calls: u64,
+    /// Time evaluating the file, excluding `glob` calls, i.e. mostly macros.
+    evaluation_ms: u64,
+    /// Time parsing the file and loading the modules it imports, and any other time not spent
+    /// evaluating it.
+    imports_ms: u64,
+    total_ms: u64,
+    targets: usize,
+}
+
+async fn generate_package_loading_stats(
+    ctx: &DiceTransaction,
+    package: PackageLabel,
+    spec: PackageSpec<TargetPatternExtra>,
+) -> anyhow::Result<PackageLoadingStats> {
+    match spec {
+        PackageSpec::Targets(..) => {
+            return Err(anyhow::Error::msg("Must use a package"));
+        }
+        PackageSpec::All => {}
+    }
+
+    let start = Instant::now();
+    DicePackageListingResolver(&mut ctx.dupe())
+        .resolve_package_listing_uncached(package.dupe())
+        .await?;
+    let listing = start.elapsed();
+
+    let calculation = ctx
+        .get_interpreter_calculator(package.cell_name(), BuildFileCell::new(package.cell_name()))
+        .await?;
+
+    let start = Instant::now();
+    let result = calculation
+        .eval_build_file_with_stats(
+            package.dupe(),
+            &mut StarlarkProfilerOrInstrumentation::disabled(),
+        )
+        .await?;
+    let load = start.elapsed();
+
+    Ok(PackageLoadingStats {
+        package: package.to_string(),
+        listing_ms: listing.as_millis() as u64,
+        glob_ms: result.glob_duration.as_millis() as u64,
+        glob_calls: result.glob_count,
+        evaluation_ms: result
+            .evaluation_duration
+            .saturating_sub(result.glob_duration)
+            .as_millis() as u64,
+        imports_ms: load.saturating_sub(result.evaluation_duration).as_millis() as u64,
+        total_ms: (listing + load).as_millis() as u64,
+        targets: result.result.targets().len(),
+    })
+}
+
+async fn generate_loading_breakdown(
+    ctx: DiceTransaction,
+    parsed_patterns: Vec<ParsedPattern<TargetPatternExtra>>,
+    output: &AbsPath,
+) -> anyhow::Result<buck2_cli_proto::ProfileResponse> {
+    let start = Instant::now();
+
+    let cells = ctx.get_cell_resolver().await?;
+    let resolved = resolve_target_patterns(&cells, &parsed_patterns, &DiceFileOps(&ctx)).await?;
+
+    let ctx = &ctx;
+    let ctx_data = ctx.per_transaction_data();
+    let mut stats =
+        futures::future::try_join_all(resolved.specs.into_iter().map(|(package, spec)| {
+            let ctx = ctx.dupe();
+            spawn_cancellable(
+                move |_cancel| {
+                    async move { generate_package_loading_stats(&ctx, package, spec).await }.boxed()
+                },
+                &*ctx_data.spawner,
+                ctx_data,
+            )
+            .into_drop_cancel()
+        }))
+        .await?;
+
+    // Slowest first, which is what people are looking for.
+    stats.sort_by(|a, b| {
+        b.total_ms
+            .cmp(&a.total_ms)
+            .then_with(|| a.package.cmp(&b.package))
+    });
+
+    fs_util::write(output, serde_json::to_string_pretty(&stats)?)
+        .context("Failed to write loading breakdown")?;
+
+    Ok(buck2_cli_proto::ProfileResponse {
+        elapsed: Some(start.elapsed().try_into()?),
+        total_retained_bytes: 0,
+    })
+}
+
 async fn generate_profile_loading(
     ctx: &DiceTransaction,
     package: PackageLabel,
@@ -156,6 +404,29 @@ impl ServerCommandTemplate for ProfileServerCommand {
                     .as_ref()
                     .context("Missing client context")?;
 
+                if self.req.profiler == Profiler::HeapRetention as i32 {
+                    let mut ctx = ctx;
+                    let (global_cfg_options, parsed_patterns) =
+                        parse_patterns(server_ctx, &mut ctx, context, &opts.target_patterns)
+                            .await?;
+                    return generate_heap_retention_report(
+                        ctx,
+                        parsed_patterns,
+                        global_cfg_options,
+                        opts.recursive,
+                        output,
+                    )
+                    .await;
+                }
+
+                if self.req.profiler == Profiler::LoadingBreakdown as i32 {
+                    let mut ctx = ctx;
+                    let (_, parsed_patterns) =
+                        parse_patterns(server_ctx, &mut ctx, context, &opts.target_patterns)
+                            .await?;
+                    return generate_loading_breakdown(ctx, parsed_patterns, output).await;
+                }
+
                 let profile_data = generate_profile(
                     server_ctx,
                     ctx,
@@ -192,25 +463,17 @@ async fn generate_profile(
 ) -> anyhow::Result<Arc<StarlarkProfileDataAndStats>> {
     let cells = ctx.get_cell_resolver().await?;
 
-    let global_cfg_options =
-        global_cfg_options_from_client_context(client_ctx, server_ctx, &mut ctx).await?;
-
-    let parsed_patterns = parse_patterns_from_cli_args::<TargetPatternExtra>(
-        &mut ctx,
-        target_patterns,
-        server_ctx.working_dir(),
-    )
-    .await?;
-
-    let resolved = resolve_target_patterns(&cells, &parsed_patterns, &DiceFileOps(&ctx)).await?;
+    let (global_cfg_options, parsed_patterns) =
+        parse_patterns(server_ctx, &mut ctx, client_ctx, target_patterns).await?;
 
     match action {
         Action::Analysis => {
-            let (package, spec) = one(resolved.specs)
-                .context("Error: profiling analysis requires exactly one target pattern")?;
-            generate_profile_analysis(ctx, package, spec, global_cfg_options, profile_mode).await
+            generate_profile_analysis(ctx, parsed_patterns, global_cfg_options, profile_mode).await
         }
         Action::Loading => {
+            let resolved =
+                resolve_target_patterns(&cells, &parsed_patterns, &DiceFileOps(&ctx)).await?;
+
             let ctx = &ctx;
             let ctx_data = ctx.per_transaction_data();
 
@@ -242,12 +505,3 @@ async fn generate_profile(
         }
     }
 }
-
-fn one<T>(it: impl IntoIterator<Item = T>) -> anyhow::Result<T> {
-    let mut it = it.into_iter();
-    let val = it.next().context("No value found")?;
-    if it.next().is_some() {
-        return Err(anyhow::Error::msg("More than one value found"));
-    }
-    Ok(val)
-}
@@ -11,6 +11,8 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use anyhow::Context as _;
+use buck2_build_api::analysis::shared_strings::analysis_shared_strings_stats;
+use buck2_build_api::interpreter::rule_defs::provider::builtin::default_info::shared_empty_default_info_stats;
 use buck2_core::io_counters::IoCounterKey;
 use buck2_events::EventSinkStats;
 use buck2_execute::re::manager::ReConnectionManager;
@@ -46,6 +48,7 @@ impl SnapshotCollector {
         self.add_http_metrics(&mut snapshot);
         self.add_io_metrics(&mut snapshot);
         self.add_dice_metrics(&mut snapshot);
+        self.add_analysis_metrics(&mut snapshot);
         self.add_materializer_metrics(&mut snapshot);
         self.add_sink_metrics(&mut snapshot);
         self.add_net_io_metrics(&mut snapshot);
@@ -143,6 +146,16 @@ impl SnapshotCollector {
         snapshot.dice_active_transaction_count = metrics.active_transaction_count;
     }
 
+    fn add_analysis_metrics(&self, snapshot: &mut buck2_data::Snapshot) {
+        if let Some(stats) = analysis_shared_strings_stats() {
+            snapshot.analysis_shared_strings_bytes = stats.allocated_bytes;
+            snapshot.analysis_shared_strings_saved_bytes = stats.saved_bytes;
+        }
+        let (uses, saved_bytes) = shared_empty_default_info_stats();
+        snapshot.analysis_shared_empty_default_infos = uses;
+        snapshot.analysis_shared_empty_default_infos_saved_bytes = saved_bytes;
+    }
+
     fn add_materializer_metrics(&self, snapshot: &mut buck2_data::Snapshot) {
         self.daemon.materializer.add_snapshot_stats(snapshot);
     }
@@ -215,6 +228,9 @@ impl SnapshotCollector {
         if let Some(alloc_stats) = allocator_stats {
             snapshot.malloc_bytes_active = alloc_stats.bytes_active;
             snapshot.malloc_bytes_allocated = alloc_stats.bytes_allocated;
+            snapshot.malloc_bytes_resident = alloc_stats.bytes_resident;
+            snapshot.malloc_bytes_retained = alloc_stats.bytes_retained;
+            snapshot.malloc_bytes_metadata = alloc_stats.bytes_metadata;
         }
 
         if let Some(UnixSystemStats {
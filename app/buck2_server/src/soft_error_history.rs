@@ -0,0 +1,133 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Record of when each soft error category was last hit, used to promote categories which have
+//! not been hit in a while to hard errors (`soft_errors.promote_after_days`), so that they don't
+//! regress.
+
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::time::SystemTime;
+
+use anyhow::Context;
+use buck2_core::error::CommandSoftErrors;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+use serde::Deserialize;
+use serde::Serialize;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub(crate) struct SoftErrorHistory {
+    /// Last time each category was hit, in seconds since the epoch.
+    last_hit: BTreeMap<String, u64>,
+}
+
+impl SoftErrorHistory {
+    pub(crate) fn read(path: &AbsNormPath) -> anyhow::Result<Self> {
+        // A missing file is normal after a clean.
+        match fs_util::read_to_string_if_exists(path)? {
+            Some(contents) => Ok(serde_json::from_str(&contents)
+                .with_context(|| format!("Error parsing soft error history `{}`", path))?),
+            None => Ok(Self::default()),
+        }
+    }
+
+    fn write(&self, path: &AbsNormPath) -> anyhow::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs_util::create_dir_all(dir)?;
+        }
+        fs_util::write(path, serde_json::to_vec(self)?)
+    }
+
+    /// Note that the categories with a non-zero count were hit at `now`.
+    ///
+    /// Categories which are already promoted are not updated: they were hit because they are
+    /// errors now, and should stay errors.
+    fn record(&mut self, counts: &BTreeMap<String, u64>, promoted: &HashSet<String>, now: u64) {
+        for (category, count) in counts {
+            if *count != 0 && !promoted.contains(category) {
+                self.last_hit.insert(category.clone(), now);
+            }
+        }
+    }
+
+    /// Categories which have been seen, but not hit for at least `after_days`.
+    pub(crate) fn promoted(&self, after_days: u64, now: u64) -> HashSet<String> {
+        let threshold = after_days.saturating_mul(SECONDS_PER_DAY);
+        self.last_hit
+            .iter()
+            .filter(|(_, last_hit)| now.saturating_sub(**last_hit) >= threshold)
+            .map(|(category, _)| category.clone())
+            .collect()
+    }
+}
+
+pub(crate) fn now_seconds() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Save the soft errors hit by the command which just finished.
+pub(crate) fn record_soft_errors(
+    path: &AbsNormPath,
+    soft_errors: &CommandSoftErrors,
+) -> anyhow::Result<()> {
+    let counts = soft_errors.counts();
+    if counts.values().all(|count| *count == 0) {
+        return Ok(());
+    }
+    let mut history = SoftErrorHistory::read(path)?;
+    history.record(&counts, &soft_errors.promoted(), now_seconds());
+    history.write(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_promoted() {
+        let day = SECONDS_PER_DAY;
+        let mut history = SoftErrorHistory::default();
+        history.record(
+            &BTreeMap::from([("old".to_owned(), 1), ("unused".to_owned(), 0)]),
+            &HashSet::new(),
+            0,
+        );
+        history.record(
+            &BTreeMap::from([("recent".to_owned(), 2)]),
+            &HashSet::new(),
+            9 * day,
+        );
+
+        assert_eq!(
+            HashSet::from(["old".to_owned()]),
+            history.promoted(7, 10 * day)
+        );
+        assert_eq!(
+            HashSet::from(["old".to_owned(), "recent".to_owned()]),
+            history.promoted(0, 10 * day)
+        );
+    }
+
+    #[test]
+    fn test_promoted_stays_promoted() {
+        let day = SECONDS_PER_DAY;
+        let mut history = SoftErrorHistory::default();
+        history.record(&BTreeMap::from([("x".to_owned(), 1)]), &HashSet::new(), 0);
+        let promoted = history.promoted(7, 10 * day);
+        assert_eq!(HashSet::from(["x".to_owned()]), promoted);
+
+        history.record(&BTreeMap::from([("x".to_owned(), 1)]), &promoted, 10 * day);
+        assert_eq!(promoted, history.promoted(7, 11 * day));
+    }
+}
@@ -0,0 +1,266 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Collects a snapshot of what the daemon is doing, for when a build appears to be hung.
+//!
+//! This lists the daemon's threads with the state the OS reports for them and their backtraces,
+//! the Starlark evaluations in progress with their call stacks, and the spans (actions, analyses,
+//! loads, ...) each command has open.
+
+use std::time::Duration;
+use std::time::SystemTime;
+
+use buck2_event_observer::display::display_event;
+use buck2_event_observer::display::TargetDisplayOptions;
+use buck2_event_observer::fmt_duration::fmt_duration;
+use buck2_interpreter::evaluation_stacks::capture_evaluation_stacks;
+
+use crate::active_commands::active_commands;
+
+/// How long evaluations get to record their call stack.
+const STARLARK_STACKS_TIMEOUT: Duration = Duration::from_secs(1);
+
+pub(crate) async fn collect_stack_dump() -> buck2_data::DaemonStackDump {
+    let now = SystemTime::now();
+
+    let commands = active_commands()
+        .iter()
+        .map(|(trace_id, handle)| {
+            let state = handle.state();
+            let open_spans = state
+                .open_roots()
+                .iter()
+                .map(|event| {
+                    let description = display_event(event, TargetDisplayOptions::for_log())
+                        .unwrap_or_else(|e| format!("<{:#}>", e));
+                    let elapsed = now.duration_since(event.timestamp()).unwrap_or_default();
+                    format!("{} [{}]", description, fmt_duration(elapsed, 1.0))
+                })
+                .collect();
+            buck2_data::daemon_stack_dump::Command {
+                trace_id: trace_id.to_string(),
+                argv: state.argv.clone(),
+                open_spans,
+                action_dispatch_paused: state.dispatch_gate().is_paused(),
+            }
+        })
+        .collect();
+
+    let starlark_evaluations = capture_evaluation_stacks(STARLARK_STACKS_TIMEOUT)
+        .await
+        .into_iter()
+        .map(|e| buck2_data::daemon_stack_dump::StarlarkEvaluation {
+            description: e.description,
+            elapsed_ms: e.elapsed.as_millis() as u64,
+            call_stack: e.call_stack,
+        })
+        .collect();
+
+    // Signalling each thread in turn blocks, so don't do it on a runtime thread.
+    let threads = match tokio::task::spawn_blocking(threads).await {
+        Ok(threads) => threads,
+        Err(e) => {
+            tracing::warn!("Failed to collect daemon threads: {:#}", e);
+            Vec::new()
+        }
+    };
+
+    buck2_data::DaemonStackDump {
+        threads,
+        commands,
+        starlark_evaluations,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn threads() -> Vec<buck2_data::daemon_stack_dump::Thread> {
+    use std::fs;
+
+    let entries = match fs::read_dir("/proc/self/task") {
+        Ok(entries) => entries,
+        Err(e) => {
+            tracing::warn!("Failed to list daemon threads: {:#}", e);
+            return Vec::new();
+        }
+    };
+
+    let mut threads: Vec<_> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let tid: libc::pid_t = path.file_name()?.to_str()?.parse().ok()?;
+            let name = fs::read_to_string(path.join("comm")).ok()?;
+            let stat = fs::read_to_string(path.join("stat")).ok()?;
+            // The thread name in `stat` is parenthesized and may contain spaces, so skip past it.
+            let state = stat.rsplit_once(") ")?.1.split(' ').next()?.to_owned();
+            let wchan = fs::read_to_string(path.join("wchan"))
+                .ok()
+                .filter(|w| !w.is_empty() && w != "0");
+            Some(buck2_data::daemon_stack_dump::Thread {
+                name: name.trim_end().to_owned(),
+                state,
+                wchan,
+                backtrace: signal_backtrace::thread_backtrace(tid),
+            })
+        })
+        .collect();
+    threads.sort_by(|a, b| a.name.cmp(&b.name));
+    threads
+}
+
+#[cfg(not(target_os = "linux"))]
+fn threads() -> Vec<buck2_data::daemon_stack_dump::Thread> {
+    Vec::new()
+}
+
+/// Backtraces of other threads, taken by signalling them: the signal handler walks the stack of
+/// the thread it runs on. Only the instruction pointers are collected in the handler, and they
+/// are symbolized afterwards, since that allocates.
+#[cfg(target_os = "linux")]
+mod signal_backtrace {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::AtomicI32;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::sync::Once;
+    use std::time::Duration;
+    use std::time::Instant;
+
+    use parking_lot::Mutex;
+
+    const MAX_FRAMES: usize = 128;
+    /// How long a thread gets to handle the signal, e.g. while it is in uninterruptible sleep.
+    const TIMEOUT: Duration = Duration::from_millis(100);
+
+    #[allow(clippy::declare_interior_mutable_const)]
+    const NO_FRAME: AtomicUsize = AtomicUsize::new(0);
+    static FRAMES: [AtomicUsize; MAX_FRAMES] = [NO_FRAME; MAX_FRAMES];
+    static FRAME_COUNT: AtomicUsize = AtomicUsize::new(0);
+    /// The thread which should record its backtrace, so that a thread which handles the signal
+    /// after its timeout doesn't overwrite the backtrace of the next one.
+    static TARGET: AtomicI32 = AtomicI32::new(0);
+    static DONE: AtomicBool = AtomicBool::new(false);
+    /// The above are shared by all dumps, so only one may run at a time.
+    static DUMP: Mutex<()> = parking_lot::const_mutex(());
+
+    fn signal() -> libc::c_int {
+        libc::SIGRTMIN() + 5
+    }
+
+    extern "C" fn handler(_signal: libc::c_int) {
+        let tid = unsafe { libc::syscall(libc::SYS_gettid) } as libc::pid_t;
+        if TARGET.load(Ordering::Acquire) != tid {
+            return;
+        }
+        let mut count = 0;
+        // SAFETY: this doesn't allocate, and only one dump runs at a time.
+        unsafe {
+            backtrace::trace_unsynchronized(|frame| {
+                FRAMES[count].store(frame.ip() as usize, Ordering::Relaxed);
+                count += 1;
+                count < MAX_FRAMES
+            });
+        }
+        FRAME_COUNT.store(count, Ordering::Relaxed);
+        DONE.store(true, Ordering::Release);
+    }
+
+    fn install_handler() -> bool {
+        static INSTALL: Once = Once::new();
+        static INSTALLED: AtomicBool = AtomicBool::new(false);
+        INSTALL.call_once(|| unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = handler as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESTART;
+            libc::sigemptyset(&mut action.sa_mask);
+            if libc::sigaction(signal(), &action, std::ptr::null_mut()) == 0 {
+                INSTALLED.store(true, Ordering::Relaxed);
+            } else {
+                tracing::warn!(
+                    "Failed to install the backtrace signal handler: {}",
+                    std::io::Error::last_os_error()
+                );
+            }
+        });
+        INSTALLED.load(Ordering::Relaxed)
+    }
+
+    /// The symbolized backtrace of thread `tid` of this process, innermost frame first, or empty
+    /// if it could not be taken.
+    pub(super) fn thread_backtrace(tid: libc::pid_t) -> Vec<String> {
+        if !install_handler() {
+            return Vec::new();
+        }
+        let _guard = DUMP.lock();
+
+        DONE.store(false, Ordering::Relaxed);
+        TARGET.store(tid, Ordering::Release);
+        let sent = unsafe { libc::syscall(libc::SYS_tgkill, libc::getpid(), tid, signal()) };
+        if sent != 0 {
+            // The thread has exited.
+            TARGET.store(0, Ordering::Release);
+            return Vec::new();
+        }
+        let deadline = Instant::now() + TIMEOUT;
+        while !DONE.load(Ordering::Acquire) && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        TARGET.store(0, Ordering::Release);
+        if !DONE.load(Ordering::Acquire) {
+            return Vec::new();
+        }
+
+        FRAMES[..FRAME_COUNT.load(Ordering::Relaxed)]
+            .iter()
+            .map(|ip| {
+                let ip = ip.load(Ordering::Relaxed);
+                let mut name = None;
+                backtrace::resolve(ip as *mut libc::c_void, |symbol| {
+                    if name.is_none() {
+                        name = symbol.name().map(|n| n.to_string());
+                    }
+                });
+                name.unwrap_or_else(|| format!("{:#x}", ip))
+            })
+            .collect()
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[inline(never)]
+        fn wait_for_backtrace(
+            tid: &std::sync::mpsc::Sender<libc::pid_t>,
+            done: &std::sync::mpsc::Receiver<()>,
+        ) {
+            tid.send(unsafe { libc::syscall(libc::SYS_gettid) } as libc::pid_t)
+                .unwrap();
+            done.recv().unwrap();
+        }
+
+        #[test]
+        fn test_thread_backtrace() {
+            let (tid_sender, tid_receiver) = std::sync::mpsc::channel();
+            let (done_sender, done_receiver) = std::sync::mpsc::channel();
+            let thread =
+                std::thread::spawn(move || wait_for_backtrace(&tid_sender, &done_receiver));
+            let tid = tid_receiver.recv().unwrap();
+
+            let backtrace = thread_backtrace(tid);
+            done_sender.send(()).unwrap();
+            thread.join().unwrap();
+
+            assert!(
+                backtrace.iter().any(|f| f.contains("wait_for_backtrace")),
+                "{:#?}",
+                backtrace
+            );
+        }
+    }
+}
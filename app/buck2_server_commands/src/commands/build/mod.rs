@@ -23,6 +23,10 @@ use buck2_build_api::actions::artifact::get_artifact_fs::GetArtifactFs;
 use buck2_build_api::build;
 use buck2_build_api::build::build_report::generate_build_report;
 use buck2_build_api::build::build_report::BuildReportOpts;
+use buck2_build_api::build::download_outputs::DownloadOutputs;
+use buck2_build_api::build::graph_limits::GraphLimits;
+use buck2_build_api::build::graph_limits::GraphLimitsChecker;
+use buck2_build_api::build::speculative::SpeculativeBuilds;
 use buck2_build_api::build::BuildEvent;
 use buck2_build_api::build::BuildTargetResult;
 use buck2_build_api::build::ConfiguredBuildEvent;
@@ -51,7 +55,6 @@ use buck2_core::package::PackageLabel;
 use buck2_core::pattern::pattern_type::ConfiguredProvidersPatternExtra;
 use buck2_core::pattern::pattern_type::ProvidersPatternExtra;
 use buck2_core::pattern::PackageSpec;
-use buck2_core::pattern::ParsedPattern;
 use buck2_core::provider::label::ProvidersLabel;
 use buck2_core::provider::label::ProvidersName;
 use buck2_core::target::label::TargetLabel;
@@ -69,7 +72,8 @@ use buck2_server_ctx::ctx::ServerCommandContextTrait;
 use buck2_server_ctx::partial_result_dispatcher::NoPartialResult;
 use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
 use buck2_server_ctx::pattern::global_cfg_options_from_client_context;
-use buck2_server_ctx::pattern::parse_patterns_from_cli_args;
+use buck2_server_ctx::pattern::parse_patterns_with_modifiers_from_cli_args;
+use buck2_server_ctx::pattern::PatternWithModifiers;
 use buck2_server_ctx::template::run_server_command;
 use buck2_server_ctx::template::ServerCommandTemplate;
 use dice::DiceComputations;
@@ -83,6 +87,7 @@ use itertools::Either;
 use itertools::Itertools;
 use serde::ser::SerializeSeq;
 use serde::ser::Serializer;
+use starlark_map::small_map::SmallMap;
 
 use crate::commands::build::result_report::ResultReporter;
 use crate::commands::build::result_report::ResultReporterOptions;
@@ -138,6 +143,13 @@ impl ServerCommandTemplate for BuildServerCommand {
     }
 }
 
+#[derive(Debug, buck2_error::Error)]
+enum BuildCommandError {
+    #[error("Modifiers (`?<modifier>`) cannot be used with `--target-universe`")]
+    #[buck2(user)]
+    ModifiersWithTargetUniverse,
+}
+
 enum TargetResolutionConfig {
     /// Resolve using target platform.
     Default(GlobalCfgOptions),
@@ -220,12 +232,34 @@ async fn build(
     let global_cfg_options =
         global_cfg_options_from_client_context(client_ctx, server_ctx, &mut ctx).await?;
 
-    let parsed_patterns: Vec<ParsedPattern<ConfiguredProvidersPatternExtra>> =
-        parse_patterns_from_cli_args(&mut ctx, &request.target_patterns, cwd).await?;
-    server_ctx.log_target_pattern(&parsed_patterns);
+    let parsed_patterns: Vec<PatternWithModifiers<ConfiguredProvidersPatternExtra>> =
+        parse_patterns_with_modifiers_from_cli_args(&mut ctx, &request.target_patterns, cwd)
+            .await?;
+    server_ctx.log_target_pattern(
+        &parsed_patterns
+            .iter()
+            .map(|p| p.pattern.clone())
+            .collect::<Vec<_>>(),
+    );
 
-    let resolved_pattern: ResolvedPattern<ConfiguredProvidersPatternExtra> =
-        resolve_target_patterns(&cell_resolver, &parsed_patterns, &DiceFileOps(&ctx)).await?;
+    // Patterns with the same `?<modifier>` suffix are configured together.
+    let mut patterns_by_modifiers: SmallMap<Vec<String>, Vec<_>> = SmallMap::new();
+    for PatternWithModifiers { pattern, modifiers } in parsed_patterns {
+        patterns_by_modifiers
+            .entry(modifiers)
+            .or_default()
+            .push(pattern);
+    }
+    if !request.target_universe.is_empty() && patterns_by_modifiers.keys().any(|m| !m.is_empty()) {
+        return Err(BuildCommandError::ModifiersWithTargetUniverse.into());
+    }
+
+    let mut resolved_patterns = Vec::with_capacity(patterns_by_modifiers.len());
+    for (modifiers, patterns) in patterns_by_modifiers {
+        let resolved_pattern: ResolvedPattern<ConfiguredProvidersPatternExtra> =
+            resolve_target_patterns(&cell_resolver, &patterns, &DiceFileOps(&ctx)).await?;
+        resolved_patterns.push((modifiers, resolved_pattern));
+    }
 
     let target_resolution_config: TargetResolutionConfig = if request.target_universe.is_empty() {
         TargetResolutionConfig::Default(global_cfg_options)
@@ -244,6 +278,10 @@ async fn build(
         Materializations::from_i32(request.final_artifact_materializations)
             .with_context(|| "Invalid final_artifact_materializations")
             .unwrap();
+    let final_artifact_materializations =
+        DownloadOutputs::from_config(&ctx, cell_resolver.root_cell())
+            .await?
+            .final_artifact_materializations(final_artifact_materializations);
     let materialization_context =
         ConvertMaterializationContext::from(final_artifact_materializations);
 
@@ -256,9 +294,15 @@ async fn build(
         .await?
         .unwrap_or_default();
 
+    let graph_limits = GraphLimits::from_config(&ctx, cell_resolver.root_cell()).await?;
+    let graph_limits =
+        (!graph_limits.is_empty()).then(|| Arc::new(GraphLimitsChecker::new(graph_limits)));
+    let speculative_builds =
+        SpeculativeBuilds::from_config(&ctx, cell_resolver.root_cell()).await?;
+
     let build_result = build_targets(
         &ctx,
-        resolved_pattern,
+        resolved_patterns,
         target_resolution_config,
         build_providers,
         &materialization_context,
@@ -266,6 +310,8 @@ async fn build(
         MissingTargetBehavior::from_skip(build_opts.skip_missing_targets),
         build_opts.skip_incompatible_targets,
         want_configured_graph_size,
+        graph_limits,
+        speculative_builds,
     )
     .await?;
 
@@ -328,7 +374,7 @@ async fn process_build_result(
             &cell_resolver,
             fs,
             cwd,
-            server_ctx.events().trace_id(),
+            server_ctx.events(),
             &build_result.configured,
             &build_result.other_errors,
         )?)
@@ -401,7 +447,10 @@ async fn process_build_result(
 
 async fn build_targets(
     ctx: &DiceComputations<'_>,
-    spec: ResolvedPattern<ConfiguredProvidersPatternExtra>,
+    specs: Vec<(
+        Vec<String>,
+        ResolvedPattern<ConfiguredProvidersPatternExtra>,
+    )>,
     target_resolution_config: TargetResolutionConfig,
     build_providers: Arc<BuildProviders>,
     materialization_context: &MaterializationContext,
@@ -409,34 +458,54 @@ async fn build_targets(
     missing_target_behavior: MissingTargetBehavior,
     skip_incompatible_targets: bool,
     want_configured_graph_size: bool,
+    graph_limits: Option<Arc<GraphLimitsChecker>>,
+    speculative_builds: Option<Arc<SpeculativeBuilds>>,
 ) -> anyhow::Result<BuildTargetResult> {
     let stream = match target_resolution_config {
         TargetResolutionConfig::Default(global_cfg_options) => {
-            let spec = spec.convert_pattern().context(
-                "Cannot build with explicit configurations when universe is not specified",
-            )?;
-            build_targets_with_global_target_platform(
-                ctx,
-                spec,
-                global_cfg_options,
-                build_providers,
-                materialization_context,
-                missing_target_behavior,
-                skip_incompatible_targets,
-                want_configured_graph_size,
-            )
+            let specs = specs
+                .into_iter()
+                .map(|(modifiers, spec)| {
+                    let spec = spec.convert_pattern().context(
+                        "Cannot build with explicit configurations when universe is not specified",
+                    )?;
+                    anyhow::Ok((global_cfg_options.with_extra_modifiers(&modifiers), spec))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            futures::stream::iter(specs.into_iter().map(|(global_cfg_options, spec)| {
+                build_targets_with_global_target_platform(
+                    ctx,
+                    spec,
+                    global_cfg_options,
+                    build_providers.dupe(),
+                    materialization_context,
+                    missing_target_behavior,
+                    skip_incompatible_targets,
+                    want_configured_graph_size,
+                    graph_limits.dupe(),
+                    speculative_builds.dupe(),
+                )
+            }))
+            .flatten_unordered(None)
             .left_stream()
         }
-        TargetResolutionConfig::Universe(universe) => build_targets_in_universe(
-            ctx,
-            spec,
-            universe,
-            build_providers,
-            materialization_context,
-            want_configured_graph_size,
-        )
-        .map(BuildEvent::Configured)
-        .right_stream(),
+        TargetResolutionConfig::Universe(universe) => {
+            futures::stream::iter(specs.into_iter().map(|(_modifiers, spec)| {
+                build_targets_in_universe(
+                    ctx,
+                    spec,
+                    &universe,
+                    build_providers.dupe(),
+                    materialization_context,
+                    want_configured_graph_size,
+                    graph_limits.dupe(),
+                    speculative_builds.dupe(),
+                )
+            }))
+            .flatten_unordered(None)
+            .map(BuildEvent::Configured)
+            .right_stream()
+        }
     };
 
     BuildTargetResult::collect_stream(stream, fail_fast).await
@@ -445,10 +514,12 @@ async fn build_targets(
 fn build_targets_in_universe<'a>(
     ctx: &'a DiceComputations,
     spec: ResolvedPattern<ConfiguredProvidersPatternExtra>,
-    universe: CqueryUniverse,
+    universe: &CqueryUniverse,
     build_providers: Arc<BuildProviders>,
     materialization_context: &'a MaterializationContext,
     want_configured_graph_size: bool,
+    graph_limits: Option<Arc<GraphLimitsChecker>>,
+    speculative_builds: Option<Arc<SpeculativeBuilds>>,
 ) -> impl Stream<Item = ConfiguredBuildEvent> + Unpin + 'a {
     let providers_to_build = build_providers_to_providers_to_build(&build_providers);
     let provider_labels = universe.get_provider_labels(&spec);
@@ -456,6 +527,8 @@ fn build_targets_in_universe<'a>(
         .into_iter()
         .map(|p| {
             let providers_to_build = providers_to_build.clone();
+            let graph_limits = graph_limits.dupe();
+            let speculative_builds = speculative_builds.dupe();
             async move {
                 build::build_configured_label(
                     ctx,
@@ -465,6 +538,8 @@ fn build_targets_in_universe<'a>(
                     build::BuildConfiguredLabelOptions {
                         skippable: false,
                         want_configured_graph_size,
+                        graph_limits,
+                        speculative_builds,
                     },
                 )
                 .await
@@ -483,6 +558,8 @@ fn build_targets_with_global_target_platform<'a>(
     missing_target_behavior: MissingTargetBehavior,
     skip_incompatible_targets: bool,
     want_configured_graph_size: bool,
+    graph_limits: Option<Arc<GraphLimitsChecker>>,
+    speculative_builds: Option<Arc<SpeculativeBuilds>>,
 ) -> impl Stream<Item = BuildEvent> + Unpin + 'a {
     futures::stream::iter(spec.specs.into_iter().map(move |(package, spec)| {
         build_targets_for_spec(
@@ -495,6 +572,8 @@ fn build_targets_with_global_target_platform<'a>(
             missing_target_behavior,
             skip_incompatible_targets,
             want_configured_graph_size,
+            graph_limits.dupe(),
+            speculative_builds.dupe(),
         )
         .boxed()
         .flatten_stream()
@@ -511,6 +590,8 @@ struct TargetBuildSpec {
     // the target platform).
     skippable: bool,
     want_configured_graph_size: bool,
+    graph_limits: Option<Arc<GraphLimitsChecker>>,
+    speculative_builds: Option<Arc<SpeculativeBuilds>>,
 }
 
 fn build_providers_to_providers_to_build(build_providers: &BuildProviders) -> ProvidersToBuild {
@@ -542,6 +623,8 @@ async fn build_targets_for_spec<'a>(
     missing_target_behavior: MissingTargetBehavior,
     skip_incompatible_targets: bool,
     want_configured_graph_size: bool,
+    graph_limits: Option<Arc<GraphLimitsChecker>>,
+    speculative_builds: Option<Arc<SpeculativeBuilds>>,
 ) -> impl Stream<Item = BuildEvent> + 'a {
     let skippable = match spec {
         PackageSpec::Targets(..) => skip_incompatible_targets,
@@ -607,6 +690,8 @@ async fn build_targets_for_spec<'a>(
             global_cfg_options: global_cfg_options.dupe(),
             skippable,
             want_configured_graph_size,
+            graph_limits: graph_limits.dupe(),
+            speculative_builds: speculative_builds.dupe(),
         })
         .collect();
 
@@ -662,6 +747,8 @@ async fn build_target<'a>(
         build::BuildConfiguredLabelOptions {
             skippable: spec.skippable,
             want_configured_graph_size: spec.want_configured_graph_size,
+            graph_limits: spec.graph_limits,
+            speculative_builds: spec.speculative_builds,
         },
     )
     .await
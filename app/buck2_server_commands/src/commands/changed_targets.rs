@@ -0,0 +1,371 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Server-side implementation of `buck2 changed-targets`: find the targets impacted by a set of
+//! changed files.
+//!
+//! A target is directly impacted if one of its files changed, its build file (or a `PACKAGE` file
+//! above it) changed, one of the `.bzl` files loaded by them changed transitively, or one of its
+//! dependencies is in a deleted package. Everything which depends on an impacted target is
+//! impacted too. A buckconfig change impacts everything.
+//!
+//! Only the current state of the repository is loaded, so a file deleted from a glob is found by
+//! its directory: the whole enclosing package is impacted.
+
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::path::Path;
+
+use buck2_cli_proto::new_generic::ChangedTargetsRequest;
+use buck2_cli_proto::new_generic::ChangedTargetsResponse;
+use buck2_common::dice::cells::HasCellResolver;
+use buck2_core::bzl::ImportPath;
+use buck2_core::cells::cell_path::CellPath;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_path::AbsPath;
+use buck2_core::fs::paths::file_name::FileNameBuf;
+use buck2_core::package::PackageLabel;
+use buck2_core::pattern::pattern_type::TargetPatternExtra;
+use buck2_core::target::label::TargetLabel;
+use buck2_interpreter::load_module::InterpreterCalculation;
+use buck2_interpreter::load_module::INTERPRETER_CALCULATION_IMPL;
+use buck2_interpreter::paths::package::PackageFilePath;
+use buck2_node::load_patterns::load_patterns;
+use buck2_node::load_patterns::MissingTargetBehavior;
+use buck2_node::nodes::frontend::TargetGraphCalculation;
+use buck2_server_ctx::ctx::ServerCommandContextTrait;
+use buck2_server_ctx::ctx::ServerCommandDiceContext;
+use buck2_server_ctx::pattern::parse_patterns_from_cli_args;
+use dupe::Dupe;
+use dupe::IterDupedExt;
+use gazebo::prelude::SliceExt;
+
+/// Changed files, by what they can impact.
+#[derive(Default, Debug)]
+struct Changes {
+    /// A buckconfig file changed.
+    config: bool,
+    /// Packages whose build file changed.
+    packages: HashSet<PackageLabel>,
+    /// Packages whose build file was deleted.
+    deleted_packages: BTreeSet<PackageLabel>,
+    /// Changed `.bzl` and `PACKAGE` files.
+    starlark: HashSet<CellPath>,
+    /// Other changed files, which may be sources of targets.
+    files: HashSet<CellPath>,
+    /// Other deleted files.
+    deleted_files: Vec<CellPath>,
+}
+
+fn is_config_file(path: &CellPath) -> bool {
+    let Some(name) = path.path().file_name() else {
+        return false;
+    };
+    let name = name.as_str();
+    name == ".buckconfig"
+        || name.starts_with(".buckconfig.")
+        || name.ends_with(".bcfg")
+        || path
+            .path()
+            .parent()
+            .and_then(|dir| dir.file_name())
+            .map_or(false, |dir| dir.as_str() == ".buckconfig.d")
+}
+
+impl Changes {
+    fn add(&mut self, path: CellPath, buildfiles: &[FileNameBuf], exists: bool) {
+        let name = match path.path().file_name() {
+            Some(name) => name.as_str(),
+            None => return,
+        };
+        if is_config_file(&path) {
+            self.config = true;
+        } else if buildfiles.iter().any(|b| b.as_str() == name) {
+            let package = PackageLabel::from_cell_path(
+                path.parent()
+                    .expect("a file name implies a parent directory"),
+            );
+            if exists {
+                self.packages.insert(package);
+            } else {
+                self.deleted_packages.insert(package);
+            }
+        } else if name.ends_with(".bzl") || name == PackageFilePath::PACKAGE_FILE_NAME.as_str() {
+            self.starlark.insert(path);
+        } else if exists {
+            self.files.insert(path);
+        } else {
+            self.deleted_files.push(path);
+        }
+    }
+}
+
+/// The nodes which are `roots` or reach one of them through `edges`.
+fn reverse_closure<T: Hash + Eq + Clone>(
+    edges: &HashMap<T, Vec<T>>,
+    roots: impl IntoIterator<Item = T>,
+) -> HashSet<T> {
+    let mut rdeps: HashMap<&T, Vec<&T>> = HashMap::new();
+    for (node, deps) in edges {
+        for dep in deps {
+            rdeps.entry(dep).or_default().push(node);
+        }
+    }
+
+    let mut result = HashSet::new();
+    let mut todo: Vec<T> = roots.into_iter().collect();
+    while let Some(node) = todo.pop() {
+        if result.contains(&node) {
+            continue;
+        }
+        if let Some(users) = rdeps.get(&node) {
+            todo.extend(users.iter().map(|x| (*x).clone()));
+        }
+        result.insert(node);
+    }
+    result
+}
+
+pub(crate) async fn changed_targets_command(
+    context: &dyn ServerCommandContextTrait,
+    req: ChangedTargetsRequest,
+) -> anyhow::Result<ChangedTargetsResponse> {
+    context
+        .with_dice_ctx(|server_ctx, mut ctx| async move {
+            let cell_resolver = ctx.get_cell_resolver().await?;
+            let fs = server_ctx.project_root();
+
+            let mut changes = Changes::default();
+            for path in &req.changed_files {
+                let path = AbsPath::new(Path::new(path))?;
+                let exists = fs_util::try_exists(path)?;
+                let path = cell_resolver.get_cell_path_from_abs_path(path, fs)?;
+                let buildfiles = cell_resolver.get(path.cell())?.buildfiles();
+                changes.add(path, buildfiles, exists);
+            }
+
+            let universe = if req.universe.is_empty() {
+                vec!["//...".to_owned()]
+            } else {
+                req.universe
+            };
+            let universe = universe.map(|value| buck2_data::TargetPattern {
+                value: value.clone(),
+            });
+            let patterns = parse_patterns_from_cli_args::<TargetPatternExtra>(
+                &mut ctx,
+                &universe,
+                server_ctx.working_dir(),
+            )
+            .await?;
+            let loaded = load_patterns(&mut ctx, patterns, MissingTargetBehavior::Fail).await?;
+
+            let mut packages = Vec::new();
+            for (package, targets) in loaded.iter_loaded_targets_by_package() {
+                packages.push((package, targets?));
+            }
+
+            // Packages which are impacted as a whole.
+            let mut changed_packages = changes.packages.clone();
+            let package_set: HashSet<PackageLabel> =
+                packages.iter().map(|(package, _)| package.dupe()).collect();
+            for path in &changes.deleted_files {
+                if let Some(package) = path
+                    .ancestors()
+                    .map(PackageLabel::from_cell_path)
+                    .find(|package| package_set.contains(package))
+                {
+                    changed_packages.insert(package);
+                }
+            }
+
+            if !changes.starlark.is_empty() {
+                // Walk all the `.bzl` files loaded by the packages, and by the `PACKAGE` files above
+                // them, to find the ones which load a changed file.
+                let mut package_roots: Vec<(PackageLabel, Vec<ImportPath>)> = Vec::new();
+                let mut package_files: HashMap<PackageFilePath, Vec<ImportPath>> = HashMap::new();
+                for (package, _) in &packages {
+                    let mut roots = ctx
+                        .get_interpreter_results(package.dupe())
+                        .await?
+                        .imports()
+                        .to_vec();
+                    let mut package_file_changed = false;
+                    let mut path = Some(PackageFilePath::for_dir(package.as_cell_path()));
+                    while let Some(x) = path {
+                        package_file_changed |= changes.starlark.contains(x.path());
+                        if !package_files.contains_key(&x) {
+                            let imports = INTERPRETER_CALCULATION_IMPL
+                                .get()?
+                                .get_package_file_deps(&mut ctx, &x)
+                                .await?
+                                .unwrap_or_default();
+                            package_files.insert(x.clone(), imports);
+                        }
+                        roots.extend(package_files[&x].iter().cloned());
+                        path = x.parent_package_file();
+                    }
+                    if package_file_changed {
+                        changed_packages.insert(package.dupe());
+                    }
+                    package_roots.push((package.dupe(), roots));
+                }
+
+                let mut imports: HashMap<ImportPath, Vec<ImportPath>> = HashMap::new();
+                let mut todo: Vec<ImportPath> = package_roots
+                    .iter()
+                    .flat_map(|(_, roots)| roots.iter().cloned())
+                    .collect();
+                while let Some(import) = todo.pop() {
+                    if imports.contains_key(&import) {
+                        continue;
+                    }
+                    let deps: Vec<ImportPath> = ctx
+                        .get_loaded_module_from_import_path(&import)
+                        .await?
+                        .imports()
+                        .cloned()
+                        .collect();
+                    todo.extend(deps.iter().cloned());
+                    imports.insert(import, deps);
+                }
+                let changed_imports = reverse_closure(
+                    &imports,
+                    imports
+                        .keys()
+                        .filter(|import| changes.starlark.contains(import.path()))
+                        .cloned()
+                        .collect::<Vec<_>>(),
+                );
+
+                for (package, roots) in package_roots {
+                    if roots.iter().any(|import| changed_imports.contains(import)) {
+                        changed_packages.insert(package);
+                    }
+                }
+            }
+
+            let mut graph: HashMap<TargetLabel, Vec<TargetLabel>> = HashMap::new();
+            let mut impacted = Vec::new();
+            for (package, targets) in &packages {
+                let package_changed = changes.config || changed_packages.contains(package);
+                for node in targets {
+                    let deps: Vec<TargetLabel> = node.deps().duped().collect();
+                    if package_changed
+                        || node.inputs().any(|input| changes.files.contains(&input))
+                        || deps
+                            .iter()
+                            .any(|dep| changes.deleted_packages.contains(&dep.pkg()))
+                    {
+                        impacted.push(node.label().dupe());
+                    }
+                    graph.insert(node.label().dupe(), deps);
+                }
+            }
+
+            let mut targets: Vec<TargetLabel> =
+                reverse_closure(&graph, impacted).into_iter().collect();
+            targets.sort();
+            Ok(ChangedTargetsResponse {
+                targets: targets.map(|target| target.to_string()),
+                deleted_packages: changes
+                    .deleted_packages
+                    .iter()
+                    .map(|package| package.to_string())
+                    .collect(),
+                config_changed: changes.config,
+            })
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buildfiles() -> Vec<FileNameBuf> {
+        vec![FileNameBuf::unchecked_new("BUCK")]
+    }
+
+    #[test]
+    fn test_changes_add() {
+        let mut changes = Changes::default();
+        for (path, exists) in [
+            ("root//foo/BUCK", true),
+            ("root//gone/BUCK", false),
+            ("root//defs/rules.bzl", true),
+            ("root//foo/PACKAGE", true),
+            ("root//foo/src.c", true),
+            ("root//foo/old.c", false),
+        ] {
+            changes.add(CellPath::testing_new(path), &buildfiles(), exists);
+        }
+        assert!(!changes.config);
+        assert_eq!(
+            HashSet::from([PackageLabel::testing_parse("root//foo")]),
+            changes.packages
+        );
+        assert_eq!(
+            BTreeSet::from([PackageLabel::testing_parse("root//gone")]),
+            changes.deleted_packages
+        );
+        assert_eq!(
+            HashSet::from([
+                CellPath::testing_new("root//defs/rules.bzl"),
+                CellPath::testing_new("root//foo/PACKAGE"),
+            ]),
+            changes.starlark
+        );
+        assert_eq!(
+            HashSet::from([CellPath::testing_new("root//foo/src.c")]),
+            changes.files
+        );
+        assert_eq!(
+            vec![CellPath::testing_new("root//foo/old.c")],
+            changes.deleted_files
+        );
+
+        for path in [
+            "root//.buckconfig",
+            "root//.buckconfig.local",
+            "root//mode/dev.bcfg",
+            "root//.buckconfig.d/extra",
+        ] {
+            let mut changes = Changes::default();
+            changes.add(CellPath::testing_new(path), &buildfiles(), true);
+            assert!(changes.config, "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_reverse_closure() {
+        let a = TargetLabel::testing_parse("root//:a");
+        let b = TargetLabel::testing_parse("root//:b");
+        let c = TargetLabel::testing_parse("root//:c");
+        let d = TargetLabel::testing_parse("root//:d");
+        // `a` depends on `b` which depends on `c`; `d` is unrelated.
+        let graph = HashMap::from([
+            (a.dupe(), vec![b.dupe()]),
+            (b.dupe(), vec![c.dupe()]),
+            (c.dupe(), vec![]),
+            (d.dupe(), vec![]),
+        ]);
+        assert_eq!(
+            HashSet::from([a.dupe(), b.dupe()]),
+            reverse_closure(&graph, [b.dupe()])
+        );
+        assert_eq!(
+            HashSet::from([a.dupe(), b.dupe(), c.dupe(), d.dupe()]),
+            reverse_closure(&graph, [c, d])
+        );
+        assert!(reverse_closure(&graph, []).is_empty());
+    }
+}
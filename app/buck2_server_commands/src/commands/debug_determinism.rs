@@ -0,0 +1,343 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Server-side implementation of `buck2 debug determinism`: execute the actions producing the
+//! outputs of some targets a second time, and compare the outputs of both executions.
+//!
+//! The second execution is local and bypasses the caches, and like any local execution it starts
+//! from clean output and scratch directories. It also varies what the action shouldn't depend on:
+//! its temporary directory is at a new path, and its environment is in a random order (which only
+//! workers see: processes get their environment sorted).
+//!
+//! When the outputs differ, both versions are copied to `buck-out/v2/determinism/{first,second}`,
+//! then the outputs of the first execution are restored, since they are the ones the rest of the
+//! build knows about.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashSet;
+
+use buck2_build_api::actions::artifact::get_artifact_fs::GetArtifactFs;
+use buck2_build_api::actions::calculation::action_input_keys;
+use buck2_build_api::actions::calculation::reexecute_action;
+use buck2_build_api::actions::calculation::ActionCalculation;
+use buck2_build_api::analysis::calculation::RuleAnalysisCalculation;
+use buck2_cli_proto::new_generic::DebugDeterminismRequest;
+use buck2_cli_proto::new_generic::DebugDeterminismResponse;
+use buck2_cli_proto::new_generic::NondeterministicAction;
+use buck2_cli_proto::new_generic::NondeterministicOutput;
+use buck2_cli_proto::ClientContext;
+use buck2_core::directory::Directory;
+use buck2_core::directory::DirectoryEntry;
+use buck2_core::directory::DirectoryIterator;
+use buck2_core::fs::artifact_path_resolver::ArtifactFs;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::project::ProjectRoot;
+use buck2_core::fs::project_rel_path::ProjectRelativePath;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use buck2_core::pattern::pattern_type::TargetPatternExtra;
+use buck2_core::provider::label::ProvidersLabel;
+use buck2_execute::artifact_value::ArtifactValue;
+use buck2_execute::directory::ActionDirectoryEntry;
+use buck2_execute::directory::ActionDirectoryMember;
+use buck2_execute::directory::ActionSharedDirectory;
+use buck2_execute::materialize::materializer::CopiedArtifact;
+use buck2_execute::materialize::materializer::Materializer;
+use buck2_futures::cancellation::CancellationContext;
+use buck2_node::load_patterns::load_patterns;
+use buck2_node::load_patterns::MissingTargetBehavior;
+use buck2_node::target_calculation::ConfiguredTargetCalculation;
+use buck2_server_ctx::ctx::ServerCommandContextTrait;
+use buck2_server_ctx::ctx::ServerCommandDiceContext;
+use buck2_server_ctx::pattern::global_cfg_options_from_client_context;
+use buck2_server_ctx::pattern::parse_patterns_from_cli_args;
+use dupe::Dupe;
+use gazebo::prelude::SliceExt;
+
+/// Where the outputs are copied, relative to the buck-out root.
+const DETERMINISM_DIR: &str = "determinism";
+
+pub(crate) async fn debug_determinism_command(
+    context: &dyn ServerCommandContextTrait,
+    client_ctx: &ClientContext,
+    req: DebugDeterminismRequest,
+) -> anyhow::Result<DebugDeterminismResponse> {
+    context
+        .with_dice_ctx(|server_ctx, mut ctx| async move {
+            let patterns = req.patterns.map(|value| buck2_data::TargetPattern {
+                value: value.clone(),
+            });
+            let patterns = parse_patterns_from_cli_args::<TargetPatternExtra>(
+                &mut ctx,
+                &patterns,
+                server_ctx.working_dir(),
+            )
+            .await?;
+            let loaded = load_patterns(&mut ctx, patterns, MissingTargetBehavior::Fail).await?;
+            let global_cfg_options =
+                global_cfg_options_from_client_context(client_ctx, server_ctx, &mut ctx).await?;
+
+            let mut todo = Vec::new();
+            for node in loaded.iter_loaded_targets() {
+                let label = ProvidersLabel::default_for(node?.label().dupe());
+                let label = ctx
+                    .get_configured_provider_label(&label, &global_cfg_options)
+                    .await?;
+                let providers = ctx.get_providers(&label).await?.require_compatible()?;
+                providers
+                    .provider_collection()
+                    .default_info()
+                    .for_each_default_output_artifact_only(&mut |artifact| {
+                        todo.extend(artifact.action_key().cloned());
+                        Ok(())
+                    })?;
+            }
+
+            let artifact_fs = ctx.get_artifact_fs().await?;
+            let materializer = server_ctx.materializer();
+
+            // Only keep the outputs of the last check.
+            let dir = determinism_dir(&artifact_fs);
+            materializer.invalidate(dir.clone()).await?;
+            fs_util::remove_all(server_ctx.project_root().resolve(&dir))?;
+
+            let mut response = DebugDeterminismResponse {
+                checked: 0,
+                skipped: Vec::new(),
+                nondeterministic: Vec::new(),
+            };
+            let mut seen = HashSet::new();
+            while let Some(key) = todo.pop() {
+                let action = ctx.get_action(&key).await?;
+                if !seen.insert(action.key().dupe()) {
+                    continue;
+                }
+
+                let first = ctx.build_action(action.key().dupe()).await?;
+                if req.transitive {
+                    todo.extend(action_input_keys(&mut ctx, &action).await?);
+                }
+
+                let mut first_copies = Vec::new();
+                for (path, value) in first.iter() {
+                    let path = artifact_fs.resolve_build(path);
+                    materializer.ensure_materialized(vec![path.clone()]).await?;
+                    let copy = copy_path(&artifact_fs, "first", &path)?;
+                    declare_copy(&*materializer, &path, copy.clone(), value).await?;
+                    first_copies.push((path, copy, value));
+                }
+
+                let second = match reexecute_action(&mut ctx, &action).await {
+                    Ok(Some(second)) => second,
+                    Ok(None) => {
+                        for (_, copy, _) in first_copies {
+                            discard_copy(&*materializer, server_ctx.project_root(), copy).await?;
+                        }
+                        response.skipped.push(action.to_string());
+                        continue;
+                    }
+                    Err(e) => {
+                        // The failed execution may have deleted or partially written the outputs,
+                        // so put back those of the first execution before giving up.
+                        for (path, copy, value) in first_copies {
+                            let restored =
+                                declare_copy(&*materializer, &copy, path.clone(), value).await;
+                            if let Err(restore_error) = restored {
+                                tracing::warn!(
+                                    "Failed to restore `{}` from `{}`: {:#}",
+                                    path,
+                                    copy,
+                                    restore_error
+                                );
+                                continue;
+                            }
+                            discard_copy(&*materializer, server_ctx.project_root(), copy).await?;
+                        }
+                        return Err(e.context(format!("Error re-executing `{}`", action)));
+                    }
+                };
+                response.checked += 1;
+
+                let mut outputs = Vec::new();
+                for ((path, first_copy, first_value), buck_out_path) in
+                    first_copies.into_iter().zip(first.iter().map(|(p, _)| p))
+                {
+                    let second_value = second.get(buck_out_path);
+                    if second_value == Some(first_value) {
+                        discard_copy(&*materializer, server_ctx.project_root(), first_copy).await?;
+                        continue;
+                    }
+
+                    let second_copy = copy_path(&artifact_fs, "second", &path)?;
+                    let files = match second_value {
+                        Some(second_value) => {
+                            declare_copy(&*materializer, &path, second_copy.clone(), second_value)
+                                .await?;
+                            differing_files(first_value, second_value)
+                        }
+                        None => Vec::new(),
+                    };
+                    // Restore the first execution's output.
+                    declare_copy(&*materializer, &first_copy, path.clone(), first_value).await?;
+
+                    outputs.push(NondeterministicOutput {
+                        path: path.to_string(),
+                        first: first_copy.to_string(),
+                        second: second_copy.to_string(),
+                        files,
+                    });
+                }
+                if !outputs.is_empty() {
+                    response.nondeterministic.push(NondeterministicAction {
+                        action: action.to_string(),
+                        outputs,
+                    });
+                }
+            }
+            Ok(response)
+        })
+        .await
+}
+
+fn determinism_dir(artifact_fs: &ArtifactFs) -> ProjectRelativePathBuf {
+    ProjectRelativePathBuf::unchecked_new(format!(
+        "{}/{}",
+        artifact_fs.buck_out_path_resolver().root(),
+        DETERMINISM_DIR
+    ))
+}
+
+/// Where to copy the output at `path` for the execution `run`.
+fn copy_path(
+    artifact_fs: &ArtifactFs,
+    run: &str,
+    path: &ProjectRelativePath,
+) -> anyhow::Result<ProjectRelativePathBuf> {
+    let rel = path.strip_prefix(artifact_fs.buck_out_path_resolver().root())?;
+    Ok(ProjectRelativePathBuf::unchecked_new(format!(
+        "{}/{}/{}",
+        determinism_dir(artifact_fs),
+        run,
+        rel
+    )))
+}
+
+/// Copy `src`, whose value is `value`, to `dest`.
+async fn declare_copy(
+    materializer: &dyn Materializer,
+    src: &ProjectRelativePath,
+    dest: ProjectRelativePathBuf,
+    value: &ArtifactValue,
+) -> anyhow::Result<()> {
+    let entry = value.entry().dupe().map_dir(|d| d.as_immutable());
+    materializer
+        .declare_copy(
+            dest.clone(),
+            value.dupe(),
+            vec![CopiedArtifact::new(src.to_buf(), dest.clone(), entry)],
+            CancellationContext::never_cancelled(),
+        )
+        .await?;
+    materializer.ensure_materialized(vec![dest]).await
+}
+
+/// Delete a copy which is not needed.
+async fn discard_copy(
+    materializer: &dyn Materializer,
+    project_root: &ProjectRoot,
+    copy: ProjectRelativePathBuf,
+) -> anyhow::Result<()> {
+    materializer.invalidate(copy.clone()).await?;
+    fs_util::remove_all(project_root.resolve(&copy))
+}
+
+/// Files which differ between two values of an output, relative to the output, sorted.
+fn differing_files(first: &ArtifactValue, second: &ArtifactValue) -> Vec<String> {
+    let first = leaves(first.entry());
+    let second = leaves(second.entry());
+    let paths: BTreeSet<&String> = first.keys().chain(second.keys()).collect();
+    paths
+        .into_iter()
+        .filter(|path| first.get(*path) != second.get(*path))
+        .cloned()
+        .collect()
+}
+
+/// Files and symlinks of an output, by path relative to the output.
+fn leaves(
+    entry: &ActionDirectoryEntry<ActionSharedDirectory>,
+) -> BTreeMap<String, &ActionDirectoryMember> {
+    match entry {
+        DirectoryEntry::Leaf(member) => BTreeMap::from([(String::new(), member)]),
+        DirectoryEntry::Dir(dir) => dir
+            .unordered_walk()
+            .with_paths()
+            .filter_map(|(path, entry)| match entry {
+                DirectoryEntry::Leaf(member) => Some((path.to_string(), member)),
+                DirectoryEntry::Dir(_) => None,
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_common::file_ops::FileMetadata;
+    use buck2_common::file_ops::TrackedFileDigest;
+    use buck2_execute::digest_config::DigestConfig;
+    use buck2_execute::directory::insert_file;
+    use buck2_execute::directory::ActionDirectoryBuilder;
+    use buck2_execute::directory::INTERNER;
+
+    use super::*;
+
+    fn file(content: &str, digest_config: DigestConfig) -> FileMetadata {
+        FileMetadata {
+            digest: TrackedFileDigest::from_content(
+                content.as_bytes(),
+                digest_config.cas_digest_config(),
+            ),
+            is_executable: false,
+        }
+    }
+
+    fn dir(files: &[(&str, &str)], digest_config: DigestConfig) -> anyhow::Result<ArtifactValue> {
+        let mut builder = ActionDirectoryBuilder::empty();
+        for (path, content) in files {
+            insert_file(
+                &mut builder,
+                ProjectRelativePath::new(path)?,
+                file(content, digest_config),
+            )?;
+        }
+        Ok(ArtifactValue::dir(
+            builder
+                .fingerprint(digest_config.as_directory_serializer())
+                .shared(&*INTERNER),
+        ))
+    }
+
+    #[test]
+    fn test_differing_files() -> anyhow::Result<()> {
+        let digest_config = DigestConfig::testing_default();
+
+        let first = ArtifactValue::file(file("a", digest_config));
+        let second = ArtifactValue::file(file("b", digest_config));
+        assert_eq!(vec![String::new()], differing_files(&first, &second));
+        assert!(differing_files(&first, &first).is_empty());
+
+        let first = dir(&[("a", "a"), ("sub/b", "b"), ("sub/c", "c")], digest_config)?;
+        let second = dir(&[("a", "a"), ("sub/b", "B"), ("sub/d", "d")], digest_config)?;
+        assert_eq!(
+            vec!["sub/b".to_owned(), "sub/c".to_owned(), "sub/d".to_owned()],
+            differing_files(&first, &second)
+        );
+        Ok(())
+    }
+}
@@ -8,8 +8,13 @@
  */
 
 use async_trait::async_trait;
+use buck2_cli_proto::new_generic::ChangedTargetsRequest;
+use buck2_cli_proto::new_generic::ChangedTargetsResponse;
+use buck2_cli_proto::new_generic::DebugDeterminismRequest;
+use buck2_cli_proto::new_generic::DebugDeterminismResponse;
 use buck2_cli_proto::new_generic::DebugEvalRequest;
 use buck2_cli_proto::new_generic::DebugEvalResponse;
+use buck2_cli_proto::ClientContext;
 use buck2_server_ctx::ctx::ServerCommandContextTrait;
 use buck2_server_ctx::other_server_commands::OtherServerCommands;
 use buck2_server_ctx::other_server_commands::OTHER_SERVER_COMMANDS;
@@ -17,7 +22,9 @@ use buck2_server_ctx::partial_result_dispatcher::NoPartialResult;
 use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
 
 use crate::commands::build::build_command;
+use crate::commands::changed_targets::changed_targets_command;
 use crate::commands::ctargets::configured_targets_command;
+use crate::commands::debug_determinism::debug_determinism_command;
 use crate::commands::debug_eval::debug_eval_command;
 use crate::commands::install::install_command;
 use crate::commands::query::aquery::aquery_command;
@@ -101,6 +108,21 @@ impl OtherServerCommands for OtherServerCommandsInstance {
     ) -> anyhow::Result<DebugEvalResponse> {
         debug_eval_command(ctx, req).await
     }
+    async fn changed_targets(
+        &self,
+        ctx: &dyn ServerCommandContextTrait,
+        req: ChangedTargetsRequest,
+    ) -> anyhow::Result<ChangedTargetsResponse> {
+        changed_targets_command(ctx, req).await
+    }
+    async fn debug_determinism(
+        &self,
+        ctx: &dyn ServerCommandContextTrait,
+        client_ctx: &ClientContext,
+        req: DebugDeterminismRequest,
+    ) -> anyhow::Result<DebugDeterminismResponse> {
+        debug_determinism_command(ctx, client_ctx, req).await
+    }
 }
 
 pub(crate) fn init_other_server_commands() {
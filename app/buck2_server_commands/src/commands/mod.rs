@@ -8,7 +8,9 @@
  */
 
 pub mod build;
+pub mod changed_targets;
 pub mod ctargets;
+pub mod debug_determinism;
 pub mod debug_eval;
 pub(crate) mod init_commands;
 pub mod install;
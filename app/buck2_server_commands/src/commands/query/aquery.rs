@@ -11,8 +11,10 @@ use std::io::Write;
 
 use anyhow::Context;
 use async_trait::async_trait;
+use buck2_build_api::actions::analysis_v2::action_graph_container;
 use buck2_build_api::actions::query::ActionQueryNode;
 use buck2_build_api::query::oneshot::QUERY_FRONTEND;
+use buck2_cli_proto::aquery_request::AnalysisV2Format;
 use buck2_common::dice::cells::HasCellResolver;
 use buck2_query::query::syntax::simple::eval::values::QueryEvaluationResult;
 use buck2_server_ctx::ctx::ServerCommandContextTrait;
@@ -31,6 +33,14 @@ impl QueryCommandTarget for ActionQueryNode {
         None
     }
 
+    fn resolved_selects(&self) -> Vec<(&str, Vec<String>)> {
+        Vec::new()
+    }
+
+    fn label_and_configuration(&self) -> (String, Option<String>) {
+        (self.node_key().to_string(), None)
+    }
+
     fn attr_to_string_alternate(&self, attr: &Self::Attr<'_>) -> String {
         format!("{:#}", attr)
     }
@@ -95,6 +105,7 @@ async fn aquery(
         &cell_resolver,
         &request.output_attributes,
         request.unstable_output_format,
+        &request.output_template,
     )?;
 
     let buck2_cli_proto::AqueryRequest {
@@ -121,6 +132,24 @@ async fn aquery(
         )
         .await?;
 
+    let analysis_v2_format = AnalysisV2Format::from_i32(request.analysis_v2_format)
+        .context("Invalid value of `analysis_v2_format` (internal error)")?;
+    if analysis_v2_format != AnalysisV2Format::None {
+        let nodes = match query_result {
+            QueryEvaluationResult::Single(value) => value.try_into_targets()?,
+            QueryEvaluationResult::Multiple(results) => results.merged()?.try_into_targets()?,
+        };
+        let container = action_graph_container(nodes.iter())?;
+        match analysis_v2_format {
+            AnalysisV2Format::Proto => stdout.write_all(&container.encode_proto())?,
+            _ => {
+                serde_json::to_writer_pretty(&mut stdout, &container)?;
+                writeln!(stdout)?;
+            }
+        }
+        return Ok(buck2_cli_proto::AqueryResponse {});
+    }
+
     match query_result {
         QueryEvaluationResult::Single(targets) => {
             output_configuration
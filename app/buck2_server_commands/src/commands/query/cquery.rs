@@ -46,6 +46,17 @@ impl QueryCommandTarget for ConfiguredTargetNode {
         ConfiguredTargetNode::call_stack(self)
     }
 
+    fn resolved_selects(&self) -> Vec<(&str, Vec<String>)> {
+        ConfiguredTargetNode::resolved_selects(self)
+    }
+
+    fn label_and_configuration(&self) -> (String, Option<String>) {
+        (
+            self.label().unconfigured().to_string(),
+            Some(self.label().cfg().to_string()),
+        )
+    }
+
     fn attr_to_string_alternate(&self, attr: &Self::Attr<'_>) -> String {
         format!(
             "{:#}",
@@ -127,7 +138,9 @@ async fn cquery(
         &cell_resolver,
         &request.output_attributes,
         request.unstable_output_format,
-    )?;
+        &request.output_template,
+    )?
+    .with_resolved_selects(request.show_selects);
 
     let CqueryRequest {
         query,
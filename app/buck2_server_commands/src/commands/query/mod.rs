@@ -19,4 +19,7 @@ enum QueryCommandError {
         "query result was a set of files and one or more --output-attribute was requested, but files have not attributes"
     )]
     FileSetHasNoAttributes,
+    #[error("--output-attribute can't be used with --output-template")]
+    #[buck2(user)]
+    AttributesWithTemplate,
 }
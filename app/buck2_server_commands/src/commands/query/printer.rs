@@ -9,6 +9,7 @@
 
 #![allow(clippy::drop_non_drop)] // FIXME?
 
+use std::collections::BTreeMap;
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::io::Write;
@@ -18,6 +19,9 @@ use buck2_build_api::actions::query::ActionQueryNode;
 use buck2_build_api::actions::query::PRINT_ACTION_NODE;
 use buck2_build_api::interpreter::rule_defs::provider::collection::FrozenProviderCollectionValue;
 use buck2_cli_proto::QueryOutputFormat;
+use buck2_common::output_template::OutputTemplate;
+use buck2_common::output_template::TemplateValue;
+use buck2_common::output_template::TemplateVariableKind;
 use buck2_core::cells::CellResolver;
 use buck2_core::configuration::compatibility::MaybeCompatible;
 use buck2_query::query::environment::QueryTarget;
@@ -61,8 +65,15 @@ pub(crate) struct QueryResultPrinter<'a> {
     resolver: &'a CellResolver,
     attributes: Option<RegexSet>,
     output_format: QueryOutputFormat,
+    template: Option<OutputTemplate>,
+    resolved_selects: bool,
 }
 
+const QUERY_TEMPLATE_VARIABLES: &[(&str, TemplateVariableKind)] = &[
+    ("label", TemplateVariableKind::String),
+    ("config", TemplateVariableKind::String),
+];
+
 struct TargetSetJsonPrinter<'a, T: QueryTarget> {
     value: Vec<PrintableQueryTarget<'a, T>>,
     is_complex: bool,
@@ -71,15 +82,23 @@ struct TargetSetJsonPrinter<'a, T: QueryTarget> {
 impl<'a, T: QueryTarget> TargetSetJsonPrinter<'a, T> {
     async fn new(
         target_call_stacks: bool,
+        resolved_selects: bool,
         print_providers: ShouldPrintProviders<'a, T>,
         attributes: &'a Option<RegexSet>,
         targets: &'a TargetSet<T>,
     ) -> anyhow::Result<TargetSetJsonPrinter<'a, T>> {
         Ok(TargetSetJsonPrinter {
-            value: printable_targets(targets, print_providers, attributes, target_call_stacks)
-                .await?,
+            value: printable_targets(
+                targets,
+                print_providers,
+                attributes,
+                target_call_stacks,
+                resolved_selects,
+            )
+            .await?,
             is_complex: attributes.is_some()
                 || target_call_stacks
+                || resolved_selects
                 || print_providers.unpack_yes().is_some(),
         })
     }
@@ -90,6 +109,7 @@ struct PrintableQueryTarget<'a, T: QueryTarget> {
     attributes: &'a Option<RegexSet>,
     providers: Option<FrozenProviderCollectionValue>,
     target_call_stacks: bool,
+    resolved_selects: bool,
 }
 
 impl<'a, T: QueryTarget> PrintableQueryTarget<'a, T> {
@@ -102,7 +122,7 @@ impl<'a, T: QueryCommandTarget> Display for PrintableQueryTarget<'a, T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.value.node_key())?;
 
-        if self.target_call_stacks || self.providers.is_some() {
+        if self.target_call_stacks || self.resolved_selects || self.providers.is_some() {
             writeln!(f)?;
         }
 
@@ -117,6 +137,12 @@ impl<'a, T: QueryCommandTarget> Display for PrintableQueryTarget<'a, T> {
             }
         }
 
+        if self.resolved_selects {
+            for (attr, keys) in self.value.resolved_selects() {
+                writeln!(f, "  {}: {}", attr, keys.join(", "))?;
+            }
+        }
+
         if let Some(providers) = &self.providers {
             use std::fmt::Write;
             write!(
@@ -170,6 +196,12 @@ impl<'a, T: QueryCommandTarget> Serialize for PrintableQueryTarget<'a, T> {
             map.serialize_entry("buck.target_call_stack", &self.value.call_stack())?;
         }
 
+        if self.resolved_selects {
+            let resolved_selects: BTreeMap<_, _> =
+                self.value.resolved_selects().into_iter().collect();
+            map.serialize_entry("buck.resolved_selects", &resolved_selects)?;
+        }
+
         if let Some(providers) = &self.providers {
             map.serialize_entry("buck.providers", providers)?;
         }
@@ -221,12 +253,14 @@ impl<'a> QueryResultPrinter<'a> {
         resolver: &'a CellResolver,
         attributes: &[String],
         output_format: i32,
+        output_template: &str,
     ) -> anyhow::Result<Self> {
         Self::from_options(
             resolver,
             attributes,
             QueryOutputFormat::from_i32(output_format)
                 .expect("cli should send a valid output_format enum"),
+            output_template,
         )
     }
 
@@ -234,7 +268,20 @@ impl<'a> QueryResultPrinter<'a> {
         resolver: &'a CellResolver,
         attributes: &[String],
         output_format: QueryOutputFormat,
+        output_template: &str,
     ) -> anyhow::Result<Self> {
+        let template = if output_format == QueryOutputFormat::Template {
+            if !attributes.is_empty() {
+                return Err(QueryCommandError::AttributesWithTemplate.into());
+            }
+            Some(OutputTemplate::parse(
+                output_template,
+                QUERY_TEMPLATE_VARIABLES,
+            )?)
+        } else {
+            None
+        };
+
         let output_format = match (output_format, attributes.is_empty()) {
             // following buck1's behavior, if any attributes are requested we use json output instead of list output
             (QueryOutputFormat::Default, false) => QueryOutputFormat::Json,
@@ -251,9 +298,30 @@ impl<'a> QueryResultPrinter<'a> {
             resolver,
             attributes,
             output_format,
+            template,
+            resolved_selects: false,
         })
     }
 
+    /// Also print the keys chosen by the `select()`s of each attribute of the targets.
+    pub fn with_resolved_selects(self, resolved_selects: bool) -> Self {
+        Self {
+            resolved_selects,
+            ..self
+        }
+    }
+
+    fn render_template(&self, buffer: &mut String, label: &str, config: Option<&str>) {
+        if let Some(template) = &self.template {
+            template.render(buffer, |name| {
+                TemplateValue::String(match name {
+                    "label" => label,
+                    _ => config.unwrap_or_default(),
+                })
+            });
+        }
+    }
+
     pub async fn print_multi_output<'b, T: QueryCommandTarget, W: std::io::Write>(
         &self,
         mut output: W,
@@ -279,6 +347,7 @@ impl<'a> QueryResultPrinter<'a> {
                                 &arg,
                                 &TargetSetJsonPrinter::new(
                                     target_call_stacks,
+                                    self.resolved_selects,
                                     print_providers,
                                     &self.attributes,
                                     &targets,
@@ -330,9 +399,14 @@ impl<'a> QueryResultPrinter<'a> {
         match result {
             QueryEvaluationValue::TargetSet(targets) => match self.output_format {
                 QueryOutputFormat::Default => {
-                    for target in
-                        printable_targets(&targets, print_providers, &self.attributes, call_stack)
-                            .await?
+                    for target in printable_targets(
+                        &targets,
+                        print_providers,
+                        &self.attributes,
+                        call_stack,
+                        self.resolved_selects,
+                    )
+                    .await?
                     {
                         writeln!(&mut output, "{}", target)?;
                     }
@@ -341,6 +415,7 @@ impl<'a> QueryResultPrinter<'a> {
                     let mut ser = serde_json::Serializer::pretty(&mut output);
                     TargetSetJsonPrinter::new(
                         call_stack,
+                        self.resolved_selects,
                         print_providers,
                         &self.attributes,
                         &targets,
@@ -369,6 +444,14 @@ impl<'a> QueryResultPrinter<'a> {
                         &mut output,
                     )?;
                 }
+                QueryOutputFormat::Template => {
+                    let mut buffer = String::new();
+                    for target in targets.iter() {
+                        let (label, config) = target.label_and_configuration();
+                        self.render_template(&mut buffer, &label, config.as_deref());
+                    }
+                    output.write_all(buffer.as_bytes())?;
+                }
             },
             QueryEvaluationValue::FileSet(files) => {
                 if self.attributes.is_some() {
@@ -401,6 +484,14 @@ impl<'a> QueryResultPrinter<'a> {
                     QueryOutputFormat::DotCompact => {
                         unimplemented!("dot_compact output for files not implemented yet")
                     }
+                    QueryOutputFormat::Template => {
+                        let mut buffer = String::new();
+                        for file in files.iter() {
+                            let path = self.resolver.resolve_path(file.as_ref())?.to_string();
+                            self.render_template(&mut buffer, &path, None);
+                        }
+                        output.write_all(buffer.as_bytes())?;
+                    }
                 }
             }
         }
@@ -414,6 +505,7 @@ async fn printable_targets<'a, T: QueryTarget>(
     print_providers: ShouldPrintProviders<'a, T>,
     attributes: &'a Option<RegexSet>,
     target_call_stacks: bool,
+    resolved_selects: bool,
 ) -> anyhow::Result<Vec<PrintableQueryTarget<'a, T>>> {
     futures::future::join_all(targets.iter().map(|t| {
         let print_providers = &print_providers;
@@ -422,6 +514,7 @@ async fn printable_targets<'a, T: QueryTarget>(
                 value: t,
                 attributes,
                 target_call_stacks,
+                resolved_selects,
                 providers: match print_providers {
                     ShouldPrintProviders::No => None,
                     ShouldPrintProviders::Yes(lookup) => {
@@ -454,6 +547,7 @@ async fn print_action_node(
         cell_resolver,
         output_attributes,
         unstable_output_format,
+        "",
     )?;
 
     let mut result = TargetSet::new();
@@ -13,6 +13,13 @@ use buck2_query::query::environment::QueryTarget;
 pub(crate) trait QueryCommandTarget: QueryTarget {
     fn call_stack(&self) -> Option<String>;
 
+    /// The keys chosen by the `select()`s of each attribute, empty if the target is not
+    /// configured.
+    fn resolved_selects(&self) -> Vec<(&str, Vec<String>)>;
+
+    /// The label without the configuration, and the configuration if the target is configured.
+    fn label_and_configuration(&self) -> (String, Option<String>);
+
     fn attr_to_string_alternate(&self, attr: &Self::Attr<'_>) -> String;
 
     fn attr_serialize<S: serde::Serializer>(
@@ -38,6 +38,15 @@ impl QueryCommandTarget for TargetNode {
         TargetNodeData::call_stack(self)
     }
 
+    fn resolved_selects(&self) -> Vec<(&str, Vec<String>)> {
+        // Selects are only resolved in the configured graph.
+        Vec::new()
+    }
+
+    fn label_and_configuration(&self) -> (String, Option<String>) {
+        (self.label().to_string(), None)
+    }
+
     fn attr_to_string_alternate(&self, attr: &Self::Attr<'_>) -> String {
         format!(
             "{:#}",
@@ -111,6 +120,7 @@ async fn uquery(
         &cell_resolver,
         &request.output_attributes,
         request.unstable_output_format,
+        &request.output_template,
     )?;
 
     let UqueryRequest {
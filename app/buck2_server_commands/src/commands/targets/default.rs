@@ -41,10 +41,10 @@ use crate::target_hash::TargetHashes;
 use crate::target_hash::TargetHashesFileMode;
 
 pub(crate) struct TargetHashOptions {
-    file_mode: TargetHashesFileMode,
-    fast_hash: bool,
-    graph_type: TargetHashGraphType,
-    recursive: bool,
+    pub(crate) file_mode: TargetHashesFileMode,
+    pub(crate) fast_hash: bool,
+    pub(crate) graph_type: TargetHashGraphType,
+    pub(crate) recursive: bool,
 }
 
 impl TargetHashOptions {
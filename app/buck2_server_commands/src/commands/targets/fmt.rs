@@ -16,6 +16,9 @@ use buck2_cli_proto::targets_request::OutputFormat;
 use buck2_cli_proto::targets_request::TargetHashGraphType;
 use buck2_cli_proto::HasClientContext;
 use buck2_cli_proto::TargetsRequest;
+use buck2_common::output_template::OutputTemplate;
+use buck2_common::output_template::TemplateValue;
+use buck2_common::output_template::TemplateVariableKind;
 use buck2_core::bzl::ImportPath;
 use buck2_core::cells::cell_path::CellPath;
 use buck2_core::package::PackageLabel;
@@ -405,6 +408,42 @@ impl TargetFormatter for TargetNameFormat {
     }
 }
 
+const TARGETS_TEMPLATE_VARIABLES: &[(&str, TemplateVariableKind)] = &[
+    ("label", TemplateVariableKind::String),
+    ("package", TemplateVariableKind::String),
+    ("name", TemplateVariableKind::String),
+    ("type", TemplateVariableKind::String),
+    ("oncall", TemplateVariableKind::String),
+    ("target_hash", TemplateVariableKind::String),
+];
+
+struct TemplateFormat {
+    template: OutputTemplate,
+}
+
+impl TargetFormatter for TemplateFormat {
+    fn target(&self, target_info: TargetInfo<'_>, buffer: &mut String) {
+        let label = target_info.node.label();
+        let label_str = label.to_string();
+        let package = label.pkg().to_string();
+        let rule_type = target_info.node.rule_type().to_string();
+        let target_hash = target_info
+            .target_hash
+            .map(|h| h.to_string())
+            .unwrap_or_default();
+        self.template.render(buffer, |name| {
+            TemplateValue::String(match name {
+                "label" => &label_str,
+                "package" => &package,
+                "name" => label.name().as_str(),
+                "type" => &rule_type,
+                "oncall" => target_info.node.oncall().unwrap_or_default(),
+                _ => &target_hash,
+            })
+        });
+    }
+}
+
 pub(crate) fn print_target_call_stack_after_target(out: &mut String, call_stack: Option<&str>) {
     if let Some(call_stack) = call_stack {
         write!(out, "{}", indent("  ", call_stack)).unwrap();
@@ -459,5 +498,8 @@ pub(crate) fn create_formatter(
                 json_lines: output_format == OutputFormat::JsonLines,
             },
         })),
+        OutputFormat::Template => Ok(Arc::new(TemplateFormat {
+            template: OutputTemplate::parse(&other.output_template, TARGETS_TEMPLATE_VARIABLES)?,
+        })),
     }
 }
@@ -28,6 +28,7 @@ use buck2_server_ctx::ctx::ServerCommandContextTrait;
 use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
 use buck2_server_ctx::pattern::global_cfg_options_from_client_context;
 use buck2_server_ctx::pattern::parse_patterns_from_cli_args;
+use buck2_server_ctx::pattern::PatternParser;
 use buck2_server_ctx::template::run_server_command;
 use buck2_server_ctx::template::ServerCommandTemplate;
 use dice::DiceTransaction;
@@ -161,27 +162,46 @@ async fn targets(
 ) -> anyhow::Result<TargetsResponse> {
     let cwd = server_ctx.working_dir();
     let cell_resolver = dice.get_cell_resolver().await?;
-    let parsed_target_patterns = parse_patterns_from_cli_args::<TargetPatternExtra>(
-        &mut dice,
-        &request.target_patterns,
-        cwd,
-    )
-    .await?;
 
     let mut outputter = Outputter::new(request)?;
 
     let response = match &request.targets {
-        Some(targets_request::Targets::ResolveAlias(_)) => {
-            targets_resolve_aliases(dice, request, parsed_target_patterns).await?
+        Some(targets_request::Targets::ResolveAlias(resolve_alias)) => {
+            // Parse the aliases one by one, so that with `--keep-going` an invalid alias doesn't
+            // prevent resolving the others.
+            let parser = PatternParser::new(&mut dice, cwd).await?;
+            let parsed_target_patterns = request
+                .target_patterns
+                .iter()
+                .map(|pattern| parser.parse_pattern::<TargetPatternExtra>(&pattern.value))
+                .collect();
+            targets_resolve_aliases(
+                server_ctx,
+                dice,
+                request,
+                parsed_target_patterns,
+                resolve_alias.keep_going,
+            )
+            .await?
         }
         Some(targets_request::Targets::Other(other)) => {
+            let parsed_target_patterns = parse_patterns_from_cli_args::<TargetPatternExtra>(
+                &mut dice,
+                &request.target_patterns,
+                cwd,
+            )
+            .await?;
             if other.streaming {
                 let formatter = create_formatter(request, other)?;
                 let hashing = match TargetHashGraphType::from_i32(other.target_hash_graph_type)
                     .expect("buck cli should send valid target hash graph type")
                 {
                     TargetHashGraphType::None => None,
-                    _ => Some(other.target_hash_use_fast_hash),
+                    _ => Some(TargetHashOptions::new(
+                        other,
+                        &cell_resolver,
+                        server_ctx.project_root(),
+                    )?),
                 };
 
                 let res = targets_streaming(
@@ -15,11 +15,14 @@ enum ResolveAliasError {
     OutputFormatNotSet,
     #[error("`--stat` format is not supported by `--resolve-alias`")]
     StatFormatNotSupported,
+    #[error("`--output-template` is not supported by `--resolve-alias`")]
+    TemplateFormatNotSupported,
 }
 
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt::Write;
+use std::io::Write as _;
 
 use buck2_cli_proto::targets_request::OutputFormat;
 use buck2_cli_proto::TargetsRequest;
@@ -30,6 +33,7 @@ use buck2_core::target::label::TargetLabel;
 use buck2_error::Context;
 use buck2_node::nodes::attributes::PACKAGE;
 use buck2_node::nodes::frontend::TargetGraphCalculation;
+use buck2_server_ctx::ctx::ServerCommandContextTrait;
 use dice::DiceTransaction;
 use dupe::Dupe;
 use futures::FutureExt;
@@ -49,6 +53,9 @@ trait ResolveAliasFormatter {
 
     /// Emit an alias
     fn emit(&self, alias: &str, label: &TargetLabel, buffer: &mut String);
+
+    /// Emit an alias which could not be resolved, with `--keep-going`.
+    fn emit_error(&self, alias: &str, error: &anyhow::Error, buffer: &mut String);
 }
 
 impl ResolveAliasFormatter for JsonWriter {
@@ -83,6 +90,19 @@ impl ResolveAliasFormatter for JsonWriter {
         );
         self.entry_end(buffer, first);
     }
+
+    fn emit_error(&self, alias: &str, error: &anyhow::Error, buffer: &mut String) {
+        let mut first = true;
+        self.entry_start(buffer);
+        self.entry_item(buffer, &mut first, "alias", QuotedJson::quote_str(alias));
+        self.entry_item(
+            buffer,
+            &mut first,
+            "buck.error",
+            QuotedJson::quote_str(&format!("{:?}", error)),
+        );
+        self.entry_end(buffer, first);
+    }
 }
 
 struct LinesWriter;
@@ -99,29 +119,48 @@ impl ResolveAliasFormatter for LinesWriter {
     fn emit(&self, _alias: &str, label: &TargetLabel, buffer: &mut String) {
         write!(buffer, "{}", label).unwrap();
     }
+
+    fn emit_error(&self, _alias: &str, _error: &anyhow::Error, _buffer: &mut String) {
+        // Leave the line empty, so that lines still match the aliases. The error is on stderr.
+    }
 }
 
 pub(crate) async fn targets_resolve_aliases(
+    server_ctx: &dyn ServerCommandContextTrait,
     mut dice: DiceTransaction,
     request: &TargetsRequest,
-    parsed_target_patterns: Vec<ParsedPattern<TargetPatternExtra>>,
+    parsed_target_patterns: Vec<anyhow::Result<ParsedPattern<TargetPatternExtra>>>,
+    keep_going: bool,
 ) -> anyhow::Result<TargetsResponse> {
     // If we are only asked to resolve aliases, then don't expand any of the patterns, and just
     // print them out. This expects the aliases to resolve to individual targets.
-    let parsed_target_patterns = std::iter::zip(&request.target_patterns, parsed_target_patterns)
-        .map(|(alias, pattern)| match pattern {
-            ParsedPattern::Target(package, target_name, TargetPatternExtra) => {
-                Ok((package, target_name))
-            }
-            _ => Err(anyhow::anyhow!(
-                "Invalid alias (does not expand to a single target): `{}`",
-                alias.value
-            )),
-        })
-        .collect::<Result<Vec<_>, _>>()?;
+    let parsed_target_patterns: Vec<anyhow::Result<_>> =
+        std::iter::zip(&request.target_patterns, parsed_target_patterns)
+            .map(|(alias, pattern)| match pattern? {
+                ParsedPattern::Target(package, target_name, TargetPatternExtra) => {
+                    Ok((package, target_name))
+                }
+                _ => Err(anyhow::anyhow!(
+                    "Invalid alias (does not expand to a single target): `{}`",
+                    alias.value
+                )),
+            })
+            .collect();
+    let parsed_target_patterns = if keep_going {
+        parsed_target_patterns
+    } else {
+        // Fail before loading any package.
+        parsed_target_patterns
+            .into_iter()
+            .collect::<anyhow::Result<Vec<_>>>()?
+            .into_iter()
+            .map(Ok)
+            .collect()
+    };
 
     let packages = parsed_target_patterns
         .iter()
+        .filter_map(|pattern| pattern.as_ref().ok())
         .map(|(package, _name)| package.dupe())
         .collect::<HashSet<_>>();
 
@@ -160,46 +199,97 @@ pub(crate) async fn targets_resolve_aliases(
             &json_writer as &dyn ResolveAliasFormatter
         }
         OutputFormat::Stats => return Err(ResolveAliasError::StatFormatNotSupported.into()),
+        OutputFormat::Template => {
+            return Err(ResolveAliasError::TemplateFormatNotSupported.into());
+        }
     };
 
     let mut needs_separator = false;
+    let mut error_count = 0;
 
     formatter.begin(&mut buffer);
 
-    for (alias, (package, target_name)) in
-        std::iter::zip(&request.target_patterns, &parsed_target_patterns)
-    {
+    for (alias, pattern) in std::iter::zip(&request.target_patterns, parsed_target_patterns) {
         // NOTE: We don't technically need the node to get the label, but we need the node to
         // validate it exists.
-        let node = packages
-            .get(package)
-            .with_context(|| format!("Package does not exist: `{}`", package))
-            .and_then(|package_data| {
-                package_data
-                    .as_ref()
-                    .map_err(|e| e.dupe())
-                    .with_context(|| format!("Package cannot be evaluated: `{}`", package))?
-                    .resolve_target(target_name)
-                    .with_context(|| {
-                        format!(
-                            "Target does not exist in package `{}`: `{}`",
-                            package, target_name,
-                        )
+        let node = pattern
+            .and_then(|(package, target_name)| {
+                packages
+                    .get(&package)
+                    .with_context(|| format!("Package does not exist: `{}`", package))
+                    .and_then(|package_data| {
+                        package_data
+                            .as_ref()
+                            .map_err(|e| e.dupe())
+                            .with_context(|| format!("Package cannot be evaluated: `{}`", package))?
+                            .resolve_target(&target_name)
+                            .with_context(|| {
+                                format!(
+                                    "Target does not exist in package `{}`: `{}`",
+                                    package, target_name,
+                                )
+                            })
                     })
+                    .map(|node| node.label().dupe())
             })
-            .with_context(|| format!("Invalid alias: `{}`", alias.value))?;
+            .with_context(|| format!("Invalid alias: `{}`", alias.value));
 
         if needs_separator {
             formatter.separator(&mut buffer);
         }
         needs_separator = true;
-        formatter.emit(&alias.value, node.label(), &mut buffer);
+        match node {
+            Ok(label) => formatter.emit(&alias.value, &label, &mut buffer),
+            Err(e) if keep_going => {
+                error_count += 1;
+                writeln!(server_ctx.stderr()?, "{:?}", e)?;
+                formatter.emit_error(&alias.value, &e, &mut buffer);
+            }
+            Err(e) => return Err(e),
+        }
     }
 
     formatter.end(&mut buffer);
 
     Ok(TargetsResponse {
-        error_count: 0,
+        error_count,
         serialized_targets_output: buffer,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_aliases(formatter: &dyn ResolveAliasFormatter) -> String {
+        let mut buffer = String::new();
+        formatter.begin(&mut buffer);
+        formatter.emit(
+            "foo",
+            &TargetLabel::testing_parse("root//foo:bar"),
+            &mut buffer,
+        );
+        formatter.separator(&mut buffer);
+        formatter.emit_error("missing", &anyhow::anyhow!("Invalid alias"), &mut buffer);
+        formatter.end(&mut buffer);
+        buffer
+    }
+
+    #[test]
+    fn test_emit_error_json() {
+        let output = write_aliases(&JsonWriter { json_lines: false });
+        assert_eq!(
+            serde_json::json!([
+                {"alias": "foo", "buck.package": "root//foo", "name": "bar"},
+                {"alias": "missing", "buck.error": "Invalid alias"},
+            ]),
+            serde_json::from_str::<serde_json::Value>(&output).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_emit_error_lines() {
+        // The line of the alias which can't be resolved is empty.
+        assert_eq!("root//foo:bar\n", write_aliases(&LinesWriter));
+    }
+}
@@ -45,6 +45,7 @@ use itertools::Itertools;
 use starlark_map::small_set::SmallSet;
 use tokio::sync::Semaphore;
 
+use crate::commands::targets::default::TargetHashOptions;
 use crate::commands::targets::fmt::Stats;
 use crate::commands::targets::fmt::TargetFormatter;
 use crate::commands::targets::fmt::TargetInfo;
@@ -61,7 +62,7 @@ pub(crate) async fn targets_streaming(
     keep_going: bool,
     cached: bool,
     imports: bool,
-    fast_hash: Option<bool>, // None = no hashing
+    hashing: Option<TargetHashOptions>, // None = no hashing
     threads: Option<usize>,
 ) -> anyhow::Result<TargetsResponse> {
     struct Res {
@@ -71,6 +72,16 @@ pub(crate) async fn targets_streaming(
         stdout: String,         // Print to stdout
     }
 
+    // Streaming only has the targets of one package at a time, so the hashes are never recursive,
+    // and always of unconfigured targets.
+    let (fast_hash, file_hasher) = match hashing {
+        Some(hashing) => (
+            Some(hashing.fast_hash),
+            TargetHashes::new_file_hasher(dice.dupe(), hashing.file_mode),
+        ),
+        None => (None, None),
+    };
+
     let imported = Arc::new(Mutex::new(SmallSet::new()));
     let threads = Arc::new(Semaphore::new(threads.unwrap_or(Semaphore::MAX_PERMITS)));
 
@@ -80,6 +91,7 @@ pub(crate) async fn targets_streaming(
             let formatter = formatter.dupe();
             let imported = imported.dupe();
             let threads = threads.dupe();
+            let file_hasher = file_hasher.dupe();
             let mut ctx = cloned_dice.dupe();
 
             spawn_cancellable(
@@ -135,12 +147,21 @@ pub(crate) async fn targets_streaming(
                                         if imports || i != 0 {
                                             formatter.separator(&mut res.stdout);
                                         }
+                                        let target_hash = match fast_hash {
+                                            Some(fast) => Some(
+                                                TargetHashes::hash_immediate(
+                                                    node,
+                                                    file_hasher.dupe(),
+                                                    fast,
+                                                )
+                                                .await?,
+                                            ),
+                                            None => None,
+                                        };
                                         formatter.target(
                                             TargetInfo {
                                                 node: node.as_ref(),
-                                                target_hash: fast_hash.map(|fast| {
-                                                    TargetHashes::compute_immediate_one(node, fast)
-                                                }),
+                                                target_hash,
                                                 super_package: eval_result.super_package(),
                                             },
                                             &mut res.stdout,
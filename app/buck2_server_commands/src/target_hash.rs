@@ -98,7 +98,7 @@ pub enum TargetHashesFileMode {
 }
 
 #[async_trait]
-trait FileHasher: Send + Sync {
+pub(crate) trait FileHasher: Send + Sync {
     /// Obtain information about a path in some manner.
     async fn hash_path(&self, path: &CellPath) -> anyhow::Result<Vec<u8>>;
 }
@@ -368,27 +368,8 @@ impl TargetHashes {
             .map(|target| {
                 let file_hasher = file_hasher.dupe();
                 async move {
-                    let hash_result: anyhow::Result<BuckTargetHash> = try {
-                        let mut hasher = TargetHashes::new_hasher(use_fast_hash);
-                        TargetHashes::hash_node(&target, &mut *hasher);
-
-                        if let Some(file_hasher) = file_hasher {
-                            let mut input_futs = Vec::new();
-                            target.inputs_for_each(|cell_path| {
-                                let file_hasher = file_hasher.dupe();
-                                input_futs.push(async move {
-                                    let file_hash = file_hasher.hash_path(&cell_path).await;
-                                    (cell_path, file_hash)
-                                });
-                                anyhow::Ok(())
-                            })?;
-
-                            let input_hashes = join_all(input_futs).await;
-                            TargetHashes::hash_files(input_hashes, &mut *hasher)?;
-                        }
-
-                        hasher.finish_u128()
-                    };
+                    let hash_result =
+                        TargetHashes::hash_immediate(&target, file_hasher, use_fast_hash).await;
                     (
                         target.node_key().unconfigured_label().dupe(),
                         hash_result.map_err(buck2_error::Error::from),
@@ -404,10 +385,31 @@ impl TargetHashes {
         Ok(Self { target_mapping })
     }
 
-    pub fn compute_immediate_one(node: &TargetNode, use_fast_hash: bool) -> BuckTargetHash {
+    /// Hash of a single target and its sources, not including its dependencies.
+    pub(crate) async fn hash_immediate<T: TargetHashingTargetNode>(
+        target: &T,
+        file_hasher: Option<Arc<dyn FileHasher>>,
+        use_fast_hash: bool,
+    ) -> anyhow::Result<BuckTargetHash> {
         let mut hasher = TargetHashes::new_hasher(use_fast_hash);
-        TargetHashes::hash_node(node, &mut *hasher);
-        hasher.finish_u128()
+        TargetHashes::hash_node(target, &mut *hasher);
+
+        if let Some(file_hasher) = file_hasher {
+            let mut input_futs = Vec::new();
+            target.inputs_for_each(|cell_path| {
+                let file_hasher = file_hasher.dupe();
+                input_futs.push(async move {
+                    let file_hash = file_hasher.hash_path(&cell_path).await;
+                    (cell_path, file_hash)
+                });
+                anyhow::Ok(())
+            })?;
+
+            let input_hashes = join_all(input_futs).await;
+            TargetHashes::hash_files(input_hashes, &mut *hasher)?;
+        }
+
+        Ok(hasher.finish_u128())
     }
 
     pub async fn compute<T: TargetHashingTargetNode, L: AsyncNodeLookup<T>>(
@@ -432,7 +434,7 @@ impl TargetHashes {
         }
     }
 
-    fn new_file_hasher(
+    pub(crate) fn new_file_hasher(
         dice: DiceTransaction,
         file_hash_mode: TargetHashesFileMode,
     ) -> Option<Arc<dyn FileHasher>> {
@@ -487,7 +489,30 @@ impl TargetHashes {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    use buck2_core::bzl::ImportPath;
+    use buck2_core::cells::cell_path::CellPath;
+    use buck2_core::package::package_relative_path::PackageRelativePath;
+    use buck2_core::package::PackageLabel;
+    use buck2_core::target::label::TargetLabel;
+    use buck2_core::target::name::TargetName;
+    use buck2_node::attrs::attr::Attribute;
+    use buck2_node::attrs::attr_type::AttrType;
+    use buck2_node::attrs::coerced_attr::CoercedAttr;
+    use buck2_node::attrs::coerced_path::CoercedPath;
+    use buck2_node::nodes::unconfigured::testing::TargetNodeExt;
+    use buck2_node::nodes::unconfigured::TargetNode;
+    use buck2_node::rule_type::RuleType;
+    use buck2_node::rule_type::StarlarkRuleType;
+    use buck2_query::query::syntax::simple::eval::set::TargetSet;
+    use dupe::Dupe;
+
     use crate::target_hash::BuckTargetHash;
+    use crate::target_hash::FileHasher;
+    use crate::target_hash::PathsOnlyFileHasher;
+    use crate::target_hash::TargetHashes;
 
     #[test]
     fn test_hash_display() {
@@ -500,4 +525,60 @@ mod tests {
             BuckTargetHash(u128::MAX).to_string()
         );
     }
+
+    fn node_with_source(name: &str, src: &str) -> anyhow::Result<TargetNode> {
+        let rule_type = RuleType::Starlark(Arc::new(StarlarkRuleType {
+            import_path: ImportPath::testing_new("cell//pkg:rules.bzl"),
+            name: "some_rule".to_owned(),
+        }));
+        Ok(TargetNode::testing_new(
+            TargetLabel::new(
+                PackageLabel::testing_parse("cell//pkg"),
+                TargetName::unchecked_new(name).as_ref(),
+            ),
+            rule_type,
+            vec![(
+                "src",
+                Attribute::new(None, "", AttrType::source(false)),
+                CoercedAttr::SourceFile(CoercedPath::File(PackageRelativePath::new(src)?.to_arc())),
+            )],
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_immediate_hashes_match_batch() -> anyhow::Result<()> {
+        let nodes = [node_with_source("a", "a.c")?, node_with_source("b", "b.c")?];
+        let mut targets = TargetSet::new();
+        for node in &nodes {
+            targets.insert(node.dupe());
+        }
+
+        for use_fast_hash in [true, false] {
+            let file_hasher = || {
+                Some(Arc::new(PathsOnlyFileHasher {
+                    pseudo_changed_paths: HashSet::from([CellPath::testing_new("cell//pkg/a.c")]),
+                }) as Arc<dyn FileHasher>)
+            };
+            let batch = TargetHashes::compute_immediate_target_hashes(
+                targets.clone(),
+                file_hasher(),
+                use_fast_hash,
+            )
+            .await?;
+            for node in &nodes {
+                let immediate =
+                    TargetHashes::hash_immediate(node, file_hasher(), use_fast_hash).await?;
+                let batch = batch.get(node.label()).unwrap().as_ref().unwrap();
+                assert_eq!(batch.0, immediate.0);
+            }
+
+            // The sources are part of the hash.
+            let without_files =
+                TargetHashes::hash_immediate(&nodes[0], None, use_fast_hash).await?;
+            let with_files =
+                TargetHashes::hash_immediate(&nodes[0], file_hasher(), use_fast_hash).await?;
+            assert_ne!(without_files.0, with_files.0);
+        }
+        Ok(())
+    }
 }
@@ -8,8 +8,13 @@
  */
 
 use async_trait::async_trait;
+use buck2_cli_proto::new_generic::ChangedTargetsRequest;
+use buck2_cli_proto::new_generic::ChangedTargetsResponse;
+use buck2_cli_proto::new_generic::DebugDeterminismRequest;
+use buck2_cli_proto::new_generic::DebugDeterminismResponse;
 use buck2_cli_proto::new_generic::DebugEvalRequest;
 use buck2_cli_proto::new_generic::DebugEvalResponse;
+use buck2_cli_proto::ClientContext;
 use buck2_util::late_binding::LateBinding;
 
 use crate::ctx::ServerCommandContextTrait;
@@ -71,6 +76,17 @@ pub trait OtherServerCommands: Send + Sync + 'static {
         ctx: &dyn ServerCommandContextTrait,
         req: DebugEvalRequest,
     ) -> anyhow::Result<DebugEvalResponse>;
+    async fn changed_targets(
+        &self,
+        ctx: &dyn ServerCommandContextTrait,
+        req: ChangedTargetsRequest,
+    ) -> anyhow::Result<ChangedTargetsResponse>;
+    async fn debug_determinism(
+        &self,
+        ctx: &dyn ServerCommandContextTrait,
+        client_ctx: &ClientContext,
+        req: DebugDeterminismRequest,
+    ) -> anyhow::Result<DebugDeterminismResponse>;
 }
 
 pub static OTHER_SERVER_COMMANDS: LateBinding<&'static dyn OtherServerCommands> =
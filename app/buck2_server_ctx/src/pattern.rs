@@ -22,6 +22,37 @@ use gazebo::prelude::*;
 
 use crate::ctx::ServerCommandContextTrait;
 
+#[derive(Debug, buck2_error::Error)]
+enum PatternModifiersError {
+    #[error("Empty modifier in `{0}`, expecting `<pattern>?<modifier>+<modifier>...`")]
+    #[buck2(user)]
+    EmptyModifier(String),
+    #[error("Modifiers (`?<modifier>`) are not supported by this command, in `{0}`")]
+    #[buck2(user)]
+    Unsupported(String),
+}
+
+/// A target pattern from the command line, with the modifiers of its `?<modifier>` suffix.
+pub struct PatternWithModifiers<T: PatternType> {
+    pub pattern: ParsedPattern<T>,
+    /// Applied after the `--modifier` ones, in order.
+    pub modifiers: Vec<String>,
+}
+
+/// Split `//foo:bar?asan+debug` into `//foo:bar` and its modifiers, `asan` and `debug`.
+fn split_pattern_modifiers(pattern: &str) -> anyhow::Result<(&str, Vec<String>)> {
+    match pattern.split_once('?') {
+        None => Ok((pattern, Vec::new())),
+        Some((target, modifiers)) => {
+            let modifiers: Vec<String> = modifiers.split('+').map(|m| m.to_owned()).collect();
+            if modifiers.iter().any(|m| m.is_empty()) {
+                return Err(PatternModifiersError::EmptyModifier(pattern.to_owned()).into());
+            }
+            Ok((target, modifiers))
+        }
+    }
+}
+
 pub struct PatternParser {
     cell_resolver: CellResolver,
     cwd: CellPath,
@@ -69,7 +100,30 @@ pub async fn parse_patterns_from_cli_args<T: PatternType>(
 ) -> anyhow::Result<Vec<ParsedPattern<T>>> {
     let parser = PatternParser::new(ctx, cwd).await?;
 
-    target_patterns.try_map(|value| parser.parse_pattern(&value.value))
+    target_patterns.try_map(|value| {
+        if value.value.contains('?') {
+            return Err(PatternModifiersError::Unsupported(value.value.clone()).into());
+        }
+        parser.parse_pattern(&value.value)
+    })
+}
+
+/// Like `parse_patterns_from_cli_args`, for commands which support per-pattern modifiers,
+/// e.g. `//foo:bar?asan`.
+pub async fn parse_patterns_with_modifiers_from_cli_args<T: PatternType>(
+    ctx: &mut DiceComputations<'_>,
+    target_patterns: &[buck2_data::TargetPattern],
+    cwd: &ProjectRelativePath,
+) -> anyhow::Result<Vec<PatternWithModifiers<T>>> {
+    let parser = PatternParser::new(ctx, cwd).await?;
+
+    target_patterns.try_map(|value| {
+        let (pattern, modifiers) = split_pattern_modifiers(&value.value)?;
+        Ok(PatternWithModifiers {
+            pattern: parser.parse_pattern(pattern)?,
+            modifiers,
+        })
+    })
 }
 
 /// Extract target configuration components from [`ClientContext`].
@@ -96,3 +150,27 @@ pub async fn global_cfg_options_from_client_context(
         cli_modifiers: client_context.cli_modifiers.clone().into(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_pattern_modifiers() -> anyhow::Result<()> {
+        assert_eq!(("//foo:bar", vec![]), split_pattern_modifiers("//foo:bar")?);
+        assert_eq!(
+            (
+                "//foo:bar[sub]",
+                vec!["asan".to_owned(), "//c:debug".to_owned()]
+            ),
+            split_pattern_modifiers("//foo:bar[sub]?asan+//c:debug")?
+        );
+        assert_eq!(
+            ("//foo/...", vec!["linux".to_owned()]),
+            split_pattern_modifiers("//foo/...?linux")?
+        );
+        assert!(split_pattern_modifiers("//foo:bar?").is_err());
+        assert!(split_pattern_modifiers("//foo:bar?asan++debug").is_err());
+        Ok(())
+    }
+}
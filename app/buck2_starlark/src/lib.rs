@@ -58,7 +58,7 @@ pub enum StarlarkOpaqueCommand {
 #[derive(Debug, clap::Parser, serde::Serialize, serde::Deserialize, Default)]
 pub struct StarlarkCommandCommonOptions {
     #[clap(flatten)]
-    config_opts: CommonBuildConfigurationOptions,
+    pub(crate) config_opts: CommonBuildConfigurationOptions,
 
     #[clap(flatten)]
     console_opts: CommonConsoleOptions,
@@ -144,6 +144,9 @@ impl StarlarkCommand {
     pub fn exec(self, matches: &clap::ArgMatches, ctx: ClientCommandContext<'_>) -> ExitResult {
         let matches = matches.subcommand().expect("subcommand not found").1;
         match self {
+            StarlarkCommand::Opaque(StarlarkOpaqueCommand::Lint(cmd)) if cmd.no_daemon => {
+                cmd.exec_no_daemon(ctx)
+            }
             StarlarkCommand::Opaque(cmd) => cmd.exec(matches, ctx),
             StarlarkCommand::DebugAttach(cmd) => cmd.exec(matches, ctx),
         }
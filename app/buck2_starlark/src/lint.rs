@@ -15,6 +15,8 @@ use std::sync::Arc;
 use anyhow::Context;
 use async_trait::async_trait;
 use buck2_cli_proto::ClientContext;
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::exit_result::ExitResult;
 use buck2_client_ctx::path_arg::PathArg;
 use buck2_common::dice::cells::HasCellResolver;
 use buck2_common::dice::data::HasIoProvider;
@@ -22,6 +24,10 @@ use buck2_common::dice::file_ops::DiceFileOps;
 use buck2_common::io::IoProvider;
 use buck2_core::cells::name::CellName;
 use buck2_core::cells::CellResolver;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::project::ProjectRoot;
+use buck2_interpreter::deprecation::DEPRECATED_GLOBALS;
+use buck2_interpreter::dice::starlark_types::GetStarlarkTypes;
 use buck2_interpreter::file_type::StarlarkFileType;
 use buck2_interpreter::paths::path::StarlarkPath;
 use buck2_server_ctx::ctx::ServerCommandContextTrait;
@@ -30,13 +36,17 @@ use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
 use dice::DiceTransaction;
 use dupe::Dupe;
 use dupe::OptionDupedExt;
+use starlark::analysis::remove_unused_loads;
+use starlark::analysis::replace_deprecated_globals;
 use starlark::analysis::AstModuleLint;
 use starlark::codemap::FileSpan;
 use starlark::errors::EvalSeverity;
 use starlark::errors::Lint;
 use starlark::syntax::AstModule;
+use starlark::syntax::Dialect;
 
 use crate::util::environment::Environment;
+use crate::util::paths::local_starlark_files;
 use crate::util::paths::starlark_files;
 use crate::StarlarkCommandCommonOptions;
 use crate::StarlarkOpaqueSubcommand;
@@ -49,6 +59,174 @@ pub struct StarlarkLintCommand {
 
     #[clap(value_name = "PATH", required = true)]
     paths: Vec<PathArg>,
+
+    /// Don't report lints with this name (e.g. `unused-load`). May be given multiple times.
+    #[clap(long, value_name = "LINT")]
+    disable: Vec<String>,
+
+    /// Report uses of the global `NAME` as deprecated, suggesting `REPLACEMENT` if given,
+    /// in addition to the deprecated globals registered by Buck2.
+    /// May be given multiple times.
+    #[clap(long, value_name = "NAME[=REPLACEMENT]")]
+    deprecated: Vec<String>,
+
+    /// Rewrite the files to fix the lints which have a mechanical fix:
+    /// unused loads are removed, and deprecated globals with a replacement are renamed.
+    #[clap(long)]
+    fix: bool,
+
+    /// Lint the files in the client, without connecting to the daemon. The globals of the cell
+    /// are not known, so undefined and shadowed names are not reported, and every deprecated
+    /// global registered by Buck2 is reported, whether the file can see it or not.
+    #[clap(long)]
+    pub(crate) no_daemon: bool,
+}
+
+impl StarlarkLintCommand {
+    /// The deprecated globals, with their replacement. Registered deprecated globals are only
+    /// included if they are in `globals`, when those are known.
+    fn deprecated(&self, globals: Option<&HashSet<String>>) -> HashMap<String, Option<String>> {
+        let registered = DEPRECATED_GLOBALS
+            .iter()
+            .filter(|x| globals.map_or(true, |globals| globals.contains(x.name)))
+            .map(|x| (x.name.to_owned(), x.replacement.map(str::to_owned)));
+        let flags = self.deprecated.iter().map(|x| match x.split_once('=') {
+            Some((name, replacement)) => (name.to_owned(), Some(replacement.to_owned())),
+            None => (x.clone(), None),
+        });
+        registered.chain(flags).collect()
+    }
+
+    fn is_enabled(&self, short_name: &str) -> bool {
+        !self.disable.iter().any(|x| x == short_name)
+    }
+
+    /// Apply the mechanical fixes, returning the new contents if anything changed.
+    fn fix(
+        &self,
+        path_str: &str,
+        content: &str,
+        dialect: &Dialect,
+        deprecated: &HashMap<String, Option<String>>,
+    ) -> anyhow::Result<Option<String>> {
+        let mut fixed = None;
+        if self.is_enabled("unused-load") {
+            if let Some(x) =
+                remove_unused_loads(path_str, content, dialect).map_err(|e| e.into_anyhow())?
+            {
+                fixed = Some(x);
+            }
+        }
+        if self.is_enabled("deprecated-global") {
+            let current = fixed.as_deref().unwrap_or(content);
+            if let Some(x) = replace_deprecated_globals(path_str, current, dialect, deprecated)
+                .map_err(|e| e.into_anyhow())?
+            {
+                fixed = Some(x);
+            }
+        }
+        Ok(fixed)
+    }
+
+    /// Lint the contents of a file, after fixing it if requested. Returns the lints to report and
+    /// the new contents, if they should be written back.
+    fn lint_contents(
+        &self,
+        path_str: String,
+        content: String,
+        file_type: StarlarkFileType,
+        disable_starlark_types: bool,
+        globals: Option<&HashSet<String>>,
+    ) -> anyhow::Result<(Vec<Lint>, Option<String>)> {
+        let dialect = file_type.dialect(disable_starlark_types);
+        let deprecated = self.deprecated(globals);
+        match AstModule::parse(&path_str, content.clone(), &dialect) {
+            Ok(ast) => {
+                let fixed = if self.fix {
+                    self.fix(&path_str, &content, &dialect, &deprecated)?
+                } else {
+                    None
+                };
+                let ast = match &fixed {
+                    Some(fixed) => AstModule::parse(&path_str, fixed.clone(), &dialect)
+                        .map_err(|e| e.into_anyhow())?,
+                    None => ast,
+                };
+                let mut lints = ast.lint(globals);
+                lints.extend(ast.lint_globals(globals, &deprecated));
+                lints.retain(|x| self.is_enabled(&x.short_name));
+                Ok((lints, fixed))
+            }
+            Err(err) => {
+                // There was a parse error, so we don't want to fail, we want to give a nice error message
+                // Do the best we can - it is probably a `Diagnostic`, which gives us more precise info.
+                let lint = Lint {
+                    location: err
+                        .span()
+                        .duped()
+                        .unwrap_or_else(|| FileSpan::new(path_str, content)),
+                    short_name: "parse_error".to_owned(),
+                    severity: EvalSeverity::Error,
+                    problem: format!("{:#}", err.without_diagnostic()),
+                    original: "".to_owned(),
+                };
+                Ok((vec![lint], None))
+            }
+        }
+    }
+
+    /// Lint the files without a daemon, for `--no-daemon`.
+    pub(crate) fn exec_no_daemon(self, ctx: ClientCommandContext<'_>) -> ExitResult {
+        ctx.instant_command("starlark-lint", async move |ctx| {
+            let paths: Vec<_> = self
+                .paths
+                .iter()
+                .map(|x| x.resolve(&ctx.working_dir))
+                .collect();
+            let files = local_starlark_files(&paths)?;
+            let disable_starlark_types = self.common_opts.config_opts.disable_starlark_types;
+
+            let mut lint_count = 0;
+            for (path, file_type) in &files {
+                let path_str = match path.strip_prefix(ctx.working_dir.path()) {
+                    Ok(x) => x.display().to_string(),
+                    Err(_) => path.to_string(),
+                };
+                let content = fs_util::read_to_string(path)?;
+                let (lints, fixed) = self.lint_contents(
+                    path_str,
+                    content,
+                    *file_type,
+                    disable_starlark_types,
+                    None,
+                )?;
+                if let Some(fixed) = fixed {
+                    fs_util::write(path, fixed)?;
+                }
+                lint_count += lints.len();
+                for lint in lints {
+                    buck2_client_ctx::println!("{}", lint)?;
+                }
+            }
+            report(lint_count, files.len(), |msg| {
+                buck2_client_ctx::eprintln!("{}", msg)?;
+                Ok(())
+            })
+        })
+    }
+}
+
+/// Fail if there were any lints, otherwise report how many files were checked.
+fn report(
+    lint_count: usize,
+    file_count: usize,
+    mut no_lints: impl FnMut(String) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    if lint_count > 0 {
+        Err(anyhow::anyhow!("Found {} lints", lint_count))
+    } else {
+        no_lints(format!("Found no lints in {} files", file_count))
+    }
 }
 
 /// The cache of names for a path, keyed by its CellName and its path type.
@@ -82,35 +260,32 @@ impl<'a> Cache<'a> {
 }
 
 async fn lint_file(
+    cmd: &StarlarkLintCommand,
     path: &StarlarkPath<'_>,
     cell_resolver: &CellResolver,
     io: &dyn IoProvider,
+    project_root: &ProjectRoot,
+    disable_starlark_types: bool,
     cache: &mut Cache<'_>,
 ) -> anyhow::Result<Vec<Lint>> {
-    let dialect = path.file_type().dialect(false);
     let proj_path = cell_resolver.resolve_path(path.path().as_ref().as_ref())?;
     let path_str = proj_path.to_string();
     let content = io
-        .read_file_if_exists(proj_path)
+        .read_file_if_exists(proj_path.clone())
         .await?
         .with_context(|| format!("File not found: `{}`", path_str))?;
-    match AstModule::parse(&path_str, content.clone(), &dialect) {
-        Ok(ast) => Ok(ast.lint(Some(&*cache.get_names(path).await?))),
-        Err(err) => {
-            // There was a parse error, so we don't want to fail, we want to give a nice error message
-            // Do the best we can - it is probably a `Diagnostic`, which gives us more precise info.
-            Ok(vec![Lint {
-                location: err
-                    .span()
-                    .duped()
-                    .unwrap_or_else(|| FileSpan::new(path_str, content)),
-                short_name: "parse_error".to_owned(),
-                severity: EvalSeverity::Error,
-                problem: format!("{:#}", err.without_diagnostic()),
-                original: "".to_owned(),
-            }])
-        }
+    let globals = cache.get_names(path).await?;
+    let (lints, fixed) = cmd.lint_contents(
+        path_str,
+        content,
+        path.file_type(),
+        disable_starlark_types,
+        Some(&*globals),
+    )?;
+    if let Some(fixed) = fixed {
+        fs_util::write(project_root.resolve(&proj_path), fixed)?;
     }
+    Ok(lints)
 }
 
 #[async_trait]
@@ -125,6 +300,7 @@ impl StarlarkOpaqueSubcommand for StarlarkLintCommand {
             .with_dice_ctx(async move |server_ctx, mut ctx| {
                 let cell_resolver = ctx.get_cell_resolver().await?;
                 let io = ctx.global_data().get_io_provider();
+                let disable_starlark_types = ctx.get_disable_starlark_types().await?;
                 let mut cache = Cache::new(&ctx);
 
                 let mut stdout = stdout.as_writer();
@@ -138,22 +314,25 @@ impl StarlarkOpaqueSubcommand for StarlarkLintCommand {
                 )
                 .await?;
                 for file in &files {
-                    let lints = lint_file(&file.borrow(), &cell_resolver, &*io, &mut cache).await?;
+                    let lints = lint_file(
+                        self,
+                        &file.borrow(),
+                        &cell_resolver,
+                        &*io,
+                        server_ctx.project_root(),
+                        disable_starlark_types,
+                        &mut cache,
+                    )
+                    .await?;
                     lint_count += lints.len();
                     for lint in lints {
                         writeln!(stdout, "{}", lint)?;
                     }
                 }
-                if lint_count > 0 {
-                    Err(anyhow::anyhow!("Found {} lints", lint_count))
-                } else {
-                    writeln!(
-                        server_ctx.stderr()?,
-                        "Found no lints in {} files",
-                        files.len()
-                    )?;
+                report(lint_count, files.len(), |msg| {
+                    writeln!(server_ctx.stderr()?, "{}", msg)?;
                     Ok(())
-                }
+                })
             })
             .await
     }
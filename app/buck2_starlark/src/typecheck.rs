@@ -40,6 +40,10 @@ use crate::util::paths::starlark_files;
 use crate::StarlarkCommandCommonOptions;
 use crate::StarlarkOpaqueSubcommand;
 
+/// Typecheck the files (and the files they load), reporting all the errors found.
+///
+/// This runs the typechecker regardless of whether typechecking is enabled when the files are
+/// loaded, so it can be used to check files before enforcement is turned on.
 #[derive(Debug, clap::Parser, serde::Serialize, serde::Deserialize)]
 #[clap(name = "starlark-typecheck", about = "Run the Starlark typechecker.")]
 pub struct StarlarkTypecheckCommand {
@@ -48,6 +52,91 @@ pub struct StarlarkTypecheckCommand {
 
     #[clap(value_name = "PATH", required = true)]
     paths: Vec<PathArg>,
+
+    /// Print the errors to stdout as JSON, one object per line, with the `path`, `span`
+    /// (1-based lines and columns), `code` and `message` of each error.
+    /// The bindings and approximations are not printed.
+    #[clap(long)]
+    json: bool,
+}
+
+/// A type error, as printed with `--json`.
+#[derive(serde::Serialize)]
+struct TypecheckDiagnostic {
+    path: String,
+    span: Option<DiagnosticSpan>,
+    code: &'static str,
+    message: String,
+}
+
+#[derive(serde::Serialize)]
+struct DiagnosticSpan {
+    begin_line: usize,
+    begin_column: usize,
+    end_line: usize,
+    end_column: usize,
+}
+
+impl TypecheckDiagnostic {
+    fn new(path: &str, error: &starlark::Error) -> Self {
+        let (path, span) = match error.span() {
+            Some(span) => {
+                let resolved = span.resolve_span();
+                (
+                    span.filename().to_owned(),
+                    Some(DiagnosticSpan {
+                        begin_line: resolved.begin.line + 1,
+                        begin_column: resolved.begin.column + 1,
+                        end_line: resolved.end.line + 1,
+                        end_column: resolved.end.column + 1,
+                    }),
+                )
+            }
+            None => (path.to_owned(), None),
+        };
+        TypecheckDiagnostic {
+            path,
+            span,
+            code: error_code(error.kind()),
+            message: format!("{:#}", error.without_diagnostic()),
+        }
+    }
+}
+
+/// Print all the `errors` of the file at `path`, as JSON or as text.
+fn write_errors(
+    stdout: &mut (dyn Write + Send + Sync),
+    path: &str,
+    errors: &[starlark::Error],
+    json: bool,
+) -> anyhow::Result<()> {
+    if json {
+        for x in errors {
+            let diagnostic = TypecheckDiagnostic::new(path, x);
+            writeln!(stdout, "{}", serde_json::to_string(&diagnostic)?)?;
+        }
+    } else if !errors.is_empty() {
+        writeln!(stdout, "\n\nERRORS:")?;
+        for x in errors {
+            writeln!(stdout, "{x}")?;
+        }
+    }
+    Ok(())
+}
+
+/// A stable name for the kind of error, so tools can filter on it.
+fn error_code(kind: &starlark::ErrorKind) -> &'static str {
+    match kind {
+        starlark::ErrorKind::Fail(_) => "fail",
+        starlark::ErrorKind::StackOverflow(_) => "stack-overflow",
+        starlark::ErrorKind::Value(_) => "value",
+        starlark::ErrorKind::Function(_) => "function",
+        starlark::ErrorKind::Scope(_) => "scope",
+        starlark::ErrorKind::Lexer(_) => "lexer",
+        starlark::ErrorKind::Internal(_) => "internal",
+        // The typechecker reports incompatible types as untyped errors.
+        _ => "type",
+    }
 }
 
 struct Cache<'a> {
@@ -58,9 +147,12 @@ struct Cache<'a> {
     // Things we have access to write information
     stdout: &'a mut (dyn Write + Send + Sync),
     stderr: &'a mut (dyn Write + Send + Sync),
+    json: bool,
     // Our accumulated state
     oracle: HashMap<(CellName, StarlarkFileType), Globals>,
     cache: HashMap<OwnedStarlarkModulePath, Interface>,
+    error_count: usize,
+    files_with_errors: usize,
 }
 
 impl<'a> Cache<'a> {
@@ -128,25 +220,25 @@ impl<'a> Cache<'a> {
             .await?;
         let (errors, bindings, interface, approxiomations) = ast.typecheck(&globals, &loads);
 
-        if !approxiomations.is_empty() {
-            writeln!(self.stderr, "\n\nAPPROXIMATIONS:")?;
-            for x in approxiomations {
-                writeln!(self.stderr, "{x}")?;
+        if !self.json {
+            if !approxiomations.is_empty() {
+                writeln!(self.stderr, "\n\nAPPROXIMATIONS:")?;
+                for x in approxiomations {
+                    writeln!(self.stderr, "{x}")?;
+                }
             }
-        }
 
-        writeln!(self.stderr, "\n\nBINDINGS:\n{bindings}")?;
+            writeln!(self.stderr, "\n\nBINDINGS:\n{bindings}")?;
+        }
+        write_errors(self.stdout, &path_str, &errors, self.json)?;
 
-        let errors_count = errors.len();
-        if errors_count == 0 {
-            Ok(interface)
-        } else {
-            writeln!(self.stdout, "\n\nERRORS:")?;
-            for x in errors {
-                writeln!(self.stdout, "{x}")?;
-            }
-            Err(anyhow::anyhow!("Detected {errors_count} errors"))
+        // Keep going, so all the errors are reported in one run. The interface is still
+        // usable by the files that load this one.
+        if !errors.is_empty() {
+            self.error_count += errors.len();
+            self.files_with_errors += 1;
         }
+        Ok(interface)
     }
 }
 
@@ -179,13 +271,26 @@ impl StarlarkOpaqueSubcommand for StarlarkTypecheckCommand {
                     cell_resolver: &cell_resolver,
                     stdout: &mut stdout,
                     stderr: &mut stderr,
+                    json: self.json,
                     oracle: HashMap::new(),
                     cache: HashMap::new(),
+                    error_count: 0,
+                    files_with_errors: 0,
                 };
                 for file in files {
                     cache.typecheck(file).await?;
                 }
                 let file_count = cache.cache.len();
+                let Cache {
+                    error_count,
+                    files_with_errors,
+                    ..
+                } = cache;
+                if error_count > 0 {
+                    return Err(anyhow::anyhow!(
+                        "Detected {error_count} errors in {files_with_errors} files"
+                    ));
+                }
                 writeln!(stderr, "Found no type errors in {file_count} files")?;
                 Ok(())
             })
@@ -196,3 +301,78 @@ impl StarlarkOpaqueSubcommand for StarlarkTypecheckCommand {
         &self.common_opts
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use starlark::syntax::AstModule;
+    use starlark::syntax::Dialect;
+
+    use super::*;
+
+    /// Two functions with a type error each, so both must be reported.
+    const TWO_ERRORS: &str = r#"
+def f():
+    hash(1)
+
+def g():
+    hash(2)
+"#;
+
+    fn typecheck_errors(program: &str) -> Vec<starlark::Error> {
+        let ast =
+            AstModule::parse("root//:test.bzl", program.to_owned(), &Dialect::Extended).unwrap();
+        let (errors, ..) = ast.typecheck(&Globals::standard(), &HashMap::new());
+        errors
+    }
+
+    fn write(errors: &[starlark::Error], json: bool) -> String {
+        let mut out = Vec::new();
+        write_errors(&mut out, "root//:test.bzl", errors, json).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_reports_all_errors() {
+        let errors = typecheck_errors(TWO_ERRORS);
+        assert_eq!(errors.len(), 2);
+
+        let text = write(&errors, false);
+        assert!(text.starts_with("\n\nERRORS:\n"), "{text}");
+        for x in &errors {
+            assert!(text.contains(&x.to_string()), "{text}");
+        }
+    }
+
+    #[test]
+    fn test_no_errors() {
+        let errors = typecheck_errors("def f():\n    hash(\"x\")\n");
+        assert!(errors.is_empty());
+        assert_eq!(write(&errors, false), "");
+        assert_eq!(write(&errors, true), "");
+    }
+
+    #[test]
+    fn test_json_diagnostics() {
+        let errors = typecheck_errors(TWO_ERRORS);
+        let json = write(&errors, true);
+        let diagnostics: Vec<serde_json::Value> = json
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(diagnostics.len(), 2);
+
+        let mut lines = Vec::new();
+        for x in &diagnostics {
+            assert_eq!(x["path"], "root//:test.bzl");
+            assert_eq!(x["code"], "type");
+            assert!(!x["message"].as_str().unwrap().is_empty());
+            let span = &x["span"];
+            assert!(span["begin_column"].as_u64().unwrap() >= 1);
+            assert!(span["end_line"].as_u64() >= span["begin_line"].as_u64());
+            lines.push(span["begin_line"].as_u64().unwrap());
+        }
+        // The spans are 1-based, and the calls are on lines 3 and 6.
+        lines.sort();
+        assert_eq!(lines, vec![3, 6]);
+    }
+}
@@ -7,8 +7,10 @@
  * of this source tree.
  */
 
+use std::fs;
 use std::ops::Deref;
 
+use anyhow::Context;
 use async_recursion::async_recursion;
 use buck2_client_ctx::path_arg::PathArg;
 use buck2_common::file_ops::FileOps;
@@ -18,9 +20,12 @@ use buck2_common::io::IoProvider;
 use buck2_core::build_file_path::BuildFilePath;
 use buck2_core::bzl::ImportPath;
 use buck2_core::cells::CellResolver;
+use buck2_core::fs::paths::abs_path::AbsPath;
+use buck2_core::fs::paths::abs_path::AbsPathBuf;
 use buck2_core::fs::paths::file_name::FileName;
 use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
 use buck2_core::package::PackageLabel;
+use buck2_interpreter::file_type::StarlarkFileType;
 use buck2_interpreter::paths::bxl::BxlFilePath;
 use buck2_interpreter::paths::package::PackageFilePath;
 use buck2_interpreter::paths::path::OwnedStarlarkPath;
@@ -33,6 +38,8 @@ enum StarlarkFilesError {
     FileNotFound(ProjectRelativePathBuf),
     #[error("Symlinks and other esoteric files are not supported, `{0}`")]
     UnsupportedFileType(ProjectRelativePathBuf),
+    #[error("Symlinks and other esoteric files are not supported, `{0}`")]
+    UnsupportedLocalFileType(AbsPathBuf),
 }
 
 #[async_recursion]
@@ -139,3 +146,69 @@ pub(crate) async fn starlark_files(
     }
     Ok(files)
 }
+
+/// Find the paths to apply Starlark to on the local file system, for when there is no daemon.
+///
+/// Without the cell configuration we can't know what the build files are called, so recognise the
+/// usual names, and don't skip anything the cell might ignore other than hidden directories and
+/// `buck-out`.
+pub(crate) fn local_starlark_files(
+    paths: &[AbsPathBuf],
+) -> anyhow::Result<Vec<(AbsPathBuf, StarlarkFileType)>> {
+    fn file_type(path: &AbsPath, explicit: bool) -> Option<StarlarkFileType> {
+        let name = path.file_name()?.to_str()?;
+        if ["BUCK", "BUCK.v2", "TARGETS", "TARGETS.v2"].contains(&name) {
+            Some(StarlarkFileType::Buck)
+        } else if name == PackageFilePath::PACKAGE_FILE_NAME.as_str() {
+            Some(StarlarkFileType::Package)
+        } else if name.ends_with(".bxl") {
+            Some(StarlarkFileType::Bxl)
+        } else if explicit || name.ends_with(".bzl") {
+            Some(StarlarkFileType::Bzl)
+        } else {
+            None
+        }
+    }
+
+    fn visit(
+        path: AbsPathBuf,
+        explicit: bool,
+        files: &mut Vec<(AbsPathBuf, StarlarkFileType)>,
+    ) -> anyhow::Result<()> {
+        let metadata = if explicit {
+            fs::metadata(&path)
+        } else {
+            fs::symlink_metadata(&path)
+        }
+        .with_context(|| format!("Reading metadata of `{}`", path))?;
+        if metadata.is_dir() {
+            let hidden = path
+                .file_name()
+                .and_then(|x| x.to_str())
+                .map_or(false, |x| x.starts_with('.') || x == "buck-out");
+            if explicit || !hidden {
+                let mut children = fs::read_dir(&path)
+                    .with_context(|| format!("Listing `{}`", path))?
+                    .map(|x| Ok(path.join(x?.file_name())))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                children.sort();
+                for child in children {
+                    visit(child, false, files)?;
+                }
+            }
+        } else if metadata.is_file() {
+            if let Some(typ) = file_type(&path, explicit) {
+                files.push((path, typ));
+            }
+        } else if explicit {
+            return Err(StarlarkFilesError::UnsupportedLocalFileType(path).into());
+        }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    for path in paths {
+        visit(path.clone(), true, &mut files)?;
+    }
+    Ok(files)
+}
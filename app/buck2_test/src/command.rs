@@ -55,6 +55,8 @@ use buck2_core::target::name::TargetName;
 use buck2_events::dispatch::console_message;
 use buck2_events::dispatch::with_dispatcher_async;
 use buck2_events::errors::create_error_report;
+use buck2_execute::execute::resource_pools::HasResourcePools;
+use buck2_execute::execute::resource_pools::ResourcePools;
 use buck2_futures::cancellation::CancellationContext;
 use buck2_node::load_patterns::MissingTargetBehavior;
 use buck2_node::nodes::configured_frontend::ConfiguredTargetNodeCalculation;
@@ -298,6 +300,14 @@ async fn test(
         }
     };
 
+    let resource_pools = ctx
+        .get_legacy_config_property(cell_resolver.root_cell(), "test", "resource_pools")
+        .await?;
+    let resource_pools = ctx
+        .per_transaction_data()
+        .get_resource_pools()
+        .get(resource_pools.as_deref())?;
+
     let parsed_patterns =
         parse_patterns_from_cli_args(&mut ctx, &request.target_patterns, cwd).await?;
     server_ctx.log_target_pattern(&parsed_patterns);
@@ -320,6 +330,7 @@ async fn test(
         allow_re: options.allow_re,
         force_use_project_relative_paths: options.force_use_project_relative_paths,
         force_run_from_project_root: options.force_run_from_project_root,
+        listing_only: options.listing_only,
     });
 
     let build_opts = request
@@ -352,6 +363,7 @@ async fn test(
         build_opts.skip_incompatible_targets,
         MissingTargetBehavior::from_skip(build_opts.skip_missing_targets),
         timeout,
+        resource_pools,
     )
     .await?;
 
@@ -426,6 +438,7 @@ async fn test_targets(
     skip_incompatible_targets: bool,
     missing_target_behavior: MissingTargetBehavior,
     timeout: Option<Duration>,
+    resource_pools: Arc<ResourcePools>,
 ) -> anyhow::Result<TestOutcome> {
     let session = Arc::new(session);
 
@@ -489,6 +502,7 @@ async fn test_targets(
                     test_status_sender,
                     CancellationContext::never_cancelled(), // sending the orchestrator directly to be spawned by make_server, which never calls it.
                     local_resource_registry.dupe(),
+                    resource_pools.dupe(),
                 )
                 .await
                 .context("Failed to create a BuckTestOrchestrator")?;
@@ -12,6 +12,7 @@
 use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::sync::Arc;
+use std::sync::OnceLock;
 use std::time::Duration;
 
 use async_trait::async_trait;
@@ -36,6 +37,8 @@ use buck2_common::events::HasEvents;
 use buck2_common::liveliness_observer::LivelinessObserver;
 use buck2_common::local_resource_state::LocalResourceState;
 use buck2_core::cells::cell_root_path::CellRootPathBuf;
+use buck2_core::execution_types::executor_config::ActionExecutorPreferences;
+use buck2_core::execution_types::executor_config::ActionTimeouts;
 use buck2_core::execution_types::executor_config::CommandExecutorConfig;
 use buck2_core::execution_types::executor_config::CommandGenerationOptions;
 use buck2_core::execution_types::executor_config::Executor;
@@ -65,6 +68,7 @@ use buck2_execute::artifact::fs::ExecutorFs;
 use buck2_execute::artifact_value::ArtifactValue;
 use buck2_execute::digest_config::DigestConfig;
 use buck2_execute::digest_config::HasDigestConfig;
+use buck2_execute::execute::action_digest::ActionDigest;
 use buck2_execute::execute::blocking::HasBlockingExecutor;
 use buck2_execute::execute::cache_uploader::NoOpCacheUploader;
 use buck2_execute::execute::claim::MutexClaimManager;
@@ -80,6 +84,7 @@ use buck2_execute::execute::request::CommandExecutionPaths;
 use buck2_execute::execute::request::CommandExecutionRequest;
 use buck2_execute::execute::request::ExecutorPreference;
 use buck2_execute::execute::request::OutputCreationBehavior;
+use buck2_execute::execute::resource_pools::ResourcePools;
 use buck2_execute::execute::result::CommandExecutionMetadata;
 use buck2_execute::execute::result::CommandExecutionReport;
 use buck2_execute::execute::result::CommandExecutionResult;
@@ -110,6 +115,7 @@ use buck2_test_api::data::PrepareForLocalExecutionResult;
 use buck2_test_api::data::RequiredLocalResources;
 use buck2_test_api::data::TestResult;
 use buck2_test_api::protocol::TestOrchestrator;
+use dashmap::DashMap;
 use derive_more::From;
 use dice::DiceTransaction;
 use dupe::Dupe;
@@ -148,6 +154,7 @@ pub struct BuckTestOrchestrator<'a> {
     digest_config: DigestConfig,
     cancellations: &'a CancellationContext<'a>,
     local_resource_state_registry: Arc<LocalResourceRegistry<'a>>,
+    resource_pools: Arc<ResourcePools>,
 }
 
 impl<'a> BuckTestOrchestrator<'a> {
@@ -158,6 +165,7 @@ impl<'a> BuckTestOrchestrator<'a> {
         results_channel: UnboundedSender<anyhow::Result<ExecutorMessage>>,
         cancellations: &'a CancellationContext<'a>,
         local_resource_state_registry: Arc<LocalResourceRegistry<'a>>,
+        resource_pools: Arc<ResourcePools>,
     ) -> anyhow::Result<BuckTestOrchestrator<'a>> {
         let events = dice.per_transaction_data().get_dispatcher().dupe();
         let digest_config = dice.global_data().get_digest_config();
@@ -170,6 +178,7 @@ impl<'a> BuckTestOrchestrator<'a> {
             digest_config,
             cancellations,
             local_resource_state_registry,
+            resource_pools,
         ))
     }
 
@@ -182,6 +191,7 @@ impl<'a> BuckTestOrchestrator<'a> {
         digest_config: DigestConfig,
         cancellations: &'a CancellationContext,
         local_resource_state_registry: Arc<LocalResourceRegistry<'a>>,
+        resource_pools: Arc<ResourcePools>,
     ) -> BuckTestOrchestrator<'a> {
         Self {
             dice,
@@ -192,6 +202,7 @@ impl<'a> BuckTestOrchestrator<'a> {
             digest_config,
             cancellations,
             local_resource_state_registry,
+            resource_pools,
         }
     }
 
@@ -217,6 +228,12 @@ impl<'a> BuckTestOrchestrator<'a> {
     ) -> Result<ExecutionResult2, ExecuteError> {
         self.require_alive().await?;
 
+        if self.session.options().listing_only {
+            if let DisplayMetadata::Testing { .. } = metadata {
+                return Err(ExecuteError::Cancelled(Cancelled));
+            }
+        }
+
         let test_target = self.session.get(test_target)?;
 
         let fs = self.dice.bad_dice().get_artifact_fs().await?;
@@ -245,9 +262,24 @@ impl<'a> BuckTestOrchestrator<'a> {
             declared_outputs,
         } = test_executable_expanded;
 
-        let executor_preference = self.executor_preference(supports_re)?;
+        let executor_preference = self.executor_preference(supports_re, test_info.remote_only())?;
+
+        let local_execution_possible =
+            test_executor.is_local_execution_possible(executor_preference);
+
+        // Held until the test finished executing.
+        let _resource_pools_permits = if local_execution_possible {
+            let permits = self
+                .resource_pools
+                .acquire(&test_info.resource_pools())
+                .await?;
+            self.require_alive().await?;
+            Some(permits)
+        } else {
+            None
+        };
 
-        let required_resources = if test_executor.is_local_execution_possible(executor_preference) {
+        let required_resources = if local_execution_possible {
             let setup_local_resources_executor = self.get_local_executor(&fs).await?;
 
             let setup_contexts = {
@@ -543,6 +575,14 @@ impl<'a> TestOrchestrator for BuckTestOrchestrator<'a> {
     }
 }
 
+#[derive(Debug, buck2_error::Error)]
+#[buck2(user)]
+#[error(
+    "Test is `remote_only`, but cannot run on RE: it must set `use_project_relative_paths` and `run_from_project_root`"
+)]
+struct TestRemoteOnlyError;
+
+#[derive(Clone)]
 struct ExecuteData {
     pub stdout: ExecutionStream,
     pub stderr: ExecutionStream,
@@ -552,10 +592,34 @@ struct ExecuteData {
     pub outputs: Vec<(BuckOutTestPath, ArtifactValue)>,
 }
 
+/// Successful listings of test binaries, by the digest of the listing action, which covers the
+/// test binary, the command and the environment. Listing an unchanged test binary again reuses
+/// its listing, for as long as the daemon lives.
+fn listing_cache() -> &'static DashMap<ActionDigest, ExecuteData> {
+    static LISTING_CACHE: OnceLock<DashMap<ActionDigest, ExecuteData>> = OnceLock::new();
+    LISTING_CACHE.get_or_init(DashMap::new)
+}
+
+/// Bound on the number of cached listings; the cache is cleared when it is reached.
+const MAX_CACHED_LISTINGS: usize = 10000;
+
 impl<'b> BuckTestOrchestrator<'b> {
-    fn executor_preference(&self, test_supports_re: bool) -> anyhow::Result<ExecutorPreference> {
+    fn executor_preference(
+        &self,
+        test_supports_re: bool,
+        test_remote_only: bool,
+    ) -> anyhow::Result<ExecutorPreference> {
         let mut executor_preference = ExecutorPreference::Default;
 
+        if test_remote_only {
+            // Running locally would materialize the inputs of the test, which is what remote-only
+            // tests avoid, so they run on RE even if the session doesn't allow RE.
+            if !test_supports_re {
+                return Err(TestRemoteOnlyError.into());
+            }
+            return Ok(ExecutorPreference::RemoteRequired);
+        }
+
         if !self.session.options().allow_re {
             // We don't ban RE (we only prefer not to use it) if the session doesn't allow it, so
             // that executor overrides or default executor can still route executions to RE.
@@ -599,9 +663,20 @@ impl<'b> BuckTestOrchestrator<'b> {
             action_key_suffix,
         };
 
-        // For test execution, we currently do not do any cache queries
+        // For test execution, we currently do not do any cache queries, but listings are cached
+        // in memory.
 
         let prepared_action = executor.prepare_action(&request, self.digest_config)?;
+        let listing_cache_key = match &metadata {
+            DisplayMetadata::Listing(_) => {
+                let key = prepared_action.digest();
+                if let Some(listing) = listing_cache().get(&key) {
+                    return Ok(listing.clone());
+                }
+                Some(key)
+            }
+            DisplayMetadata::Testing { .. } => None,
+        };
         let prepared_command = PreparedCommand {
             target: &test_target as _,
             request: &request,
@@ -628,6 +703,7 @@ impl<'b> BuckTestOrchestrator<'b> {
             dep_file_key: _,
             eligible_for_full_hybrid: _,
             dep_file_metadata: _,
+            undeclared_inputs: _,
         } = match metadata {
             DisplayMetadata::Listing(listing) => {
                 let start = TestDiscoveryStart {
@@ -688,7 +764,7 @@ impl<'b> BuckTestOrchestrator<'b> {
         let stdout = ExecutionStream::Inline(std_streams.stdout);
         let stderr = ExecutionStream::Inline(std_streams.stderr);
 
-        Ok(match status {
+        let data = match status {
             CommandExecutionStatus::Success { execution_kind } => ExecuteData {
                 stdout,
                 stderr,
@@ -737,7 +813,22 @@ impl<'b> BuckTestOrchestrator<'b> {
             CommandExecutionStatus::Cancelled => {
                 return Err(ExecuteError::Cancelled(Cancelled));
             }
-        })
+        };
+
+        if let Some(key) = listing_cache_key {
+            // Listings writing outputs aren't cached, since their outputs may not be there later.
+            if matches!(data.status, ExecutionStatus::Finished { exitcode: 0 })
+                && data.outputs.is_empty()
+            {
+                let cache = listing_cache();
+                if cache.len() >= MAX_CACHED_LISTINGS {
+                    cache.clear();
+                }
+                cache.insert(key, data.clone());
+            }
+        }
+
+        Ok(data)
     }
 
     async fn get_command_executor(
@@ -783,6 +874,8 @@ impl<'b> BuckTestOrchestrator<'b> {
                 path_separator: PathSeparatorKind::system_default(),
                 output_paths_behavior: Default::default(),
             },
+            default_timeouts: ActionTimeouts::default(),
+            executor_preferences: ActionExecutorPreferences::default(),
         };
         let CommandExecutorResponse {
             executor,
@@ -1114,6 +1207,7 @@ impl<'b> BuckTestOrchestrator<'b> {
             dep_file_key: _,
             eligible_for_full_hybrid: _,
             dep_file_metadata: _,
+            undeclared_inputs: _,
         } = execution_result;
 
         let std_streams = std_streams
@@ -1479,10 +1573,20 @@ mod tests {
     use futures::stream::TryStreamExt;
 
     use super::*;
+    use crate::session::TestSessionOptions;
 
     async fn make() -> anyhow::Result<(
         BuckTestOrchestrator<'static>,
         UnboundedReceiver<anyhow::Result<ExecutorMessage>>,
+    )> {
+        make_with_options(Default::default()).await
+    }
+
+    async fn make_with_options(
+        options: TestSessionOptions,
+    ) -> anyhow::Result<(
+        BuckTestOrchestrator<'static>,
+        UnboundedReceiver<anyhow::Result<ExecutorMessage>>,
     )> {
         let fs = ProjectRootTemp::new().unwrap();
 
@@ -1504,13 +1608,14 @@ mod tests {
         Ok((
             BuckTestOrchestrator::from_parts(
                 dice,
-                Arc::new(TestSession::new(Default::default())),
+                Arc::new(TestSession::new(options)),
                 NoopLivelinessObserver::create(),
                 sender,
                 EventDispatcher::null(),
                 DigestConfig::testing_default(),
                 CancellationContext::testing(),
                 Arc::new(LocalResourceRegistry::new()),
+                Arc::new(ResourcePools::from_config(None)?),
             ),
             receiver,
         ))
@@ -1584,6 +1689,76 @@ mod tests {
         Ok(())
     }
 
+    async fn execute(
+        orchestrator: &BuckTestOrchestrator<'_>,
+        metadata: DisplayMetadata,
+    ) -> anyhow::Result<ExecuteResponse> {
+        TestOrchestrator::execute2(
+            orchestrator,
+            metadata,
+            ConfiguredTargetHandle::from(0),
+            Vec::new(),
+            SortedVectorMap::new(),
+            Duration::from_secs(1),
+            HostSharingRequirements::default(),
+            Vec::new(),
+            None,
+            RequiredLocalResources {
+                resources: Vec::new(),
+            },
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn orchestrator_listing_only() -> anyhow::Result<()> {
+        let (orchestrator, _channel) = make_with_options(TestSessionOptions {
+            listing_only: true,
+            ..Default::default()
+        })
+        .await?;
+
+        // Tests are not run.
+        let res = execute(
+            &orchestrator,
+            DisplayMetadata::Testing {
+                suite: "suite".to_owned(),
+                testcases: Vec::new(),
+            },
+        )
+        .await?;
+        assert!(matches!(res, ExecuteResponse::Cancelled));
+
+        // Listings are, but this handle was not registered.
+        let res = execute(&orchestrator, DisplayMetadata::Listing("suite".to_owned())).await;
+        assert!(format!("{:#}", res.unwrap_err()).contains("Invalid id provided to TestSession"),);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn orchestrator_executor_preference_remote_only() -> anyhow::Result<()> {
+        let (orchestrator, _channel) = make().await?;
+
+        assert!(matches!(
+            orchestrator.executor_preference(true, true)?,
+            ExecutorPreference::RemoteRequired
+        ));
+        assert!(matches!(
+            orchestrator.executor_preference(true, false)?,
+            ExecutorPreference::LocalPreferred
+        ));
+
+        let err = orchestrator.executor_preference(false, true).unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("Test is `remote_only`, but cannot run on RE"),
+            "{:#}",
+            err
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn orchestrator_attach_info_messages() -> anyhow::Result<()> {
         let (orchestrator, channel) = make().await?;
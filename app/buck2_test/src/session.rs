@@ -25,6 +25,8 @@ pub struct TestSessionOptions {
     pub allow_re: bool,
     pub force_use_project_relative_paths: bool,
     pub force_run_from_project_root: bool,
+    /// Only execute the listing of test binaries, not the tests.
+    pub listing_only: bool,
 }
 
 /// The state of a buck2 test command.
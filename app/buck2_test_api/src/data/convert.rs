@@ -301,6 +301,7 @@ impl TryFrom<buck2_test_proto::ExternalRunnerSpec> for ExternalRunnerSpec {
             contacts,
             oncall,
             working_dir_cell,
+            shards,
         } = s;
 
         Ok(Self {
@@ -320,6 +321,7 @@ impl TryFrom<buck2_test_proto::ExternalRunnerSpec> for ExternalRunnerSpec {
             contacts,
             oncall,
             working_dir_cell: CellName::unchecked_new(&working_dir_cell)?,
+            shards: shards.max(1),
         })
     }
 }
@@ -337,6 +339,7 @@ impl TryInto<buck2_test_proto::ExternalRunnerSpec> for ExternalRunnerSpec {
             contacts,
             oncall,
             working_dir_cell,
+            shards,
         } = self;
         Ok(buck2_test_proto::ExternalRunnerSpec {
             target: Some(target.try_into().context("Invalid `target`")?),
@@ -352,6 +355,7 @@ impl TryInto<buck2_test_proto::ExternalRunnerSpec> for ExternalRunnerSpec {
             contacts,
             oncall,
             working_dir_cell: working_dir_cell.as_str().to_owned(),
+            shards,
         })
     }
 }
@@ -931,6 +935,7 @@ mod tests {
             contacts: vec!["contact1".to_owned(), "contact2".to_owned()],
             oncall: Some("contact1".to_owned()),
             working_dir_cell: CellName::testing_new("qux"),
+            shards: 4,
         };
         assert_roundtrips::<buck2_test_proto::ExternalRunnerSpec, ExternalRunnerSpec>(&test_spec);
     }
@@ -137,6 +137,9 @@ pub struct ExternalRunnerSpec {
     pub oncall: Option<String>,
     /// Cell of current working directory for test command.
     pub working_dir_cell: CellName,
+    /// Number of shards to split the test into, each executed with `TEST_SHARD_INDEX` and
+    /// `TEST_TOTAL_SHARDS` in its environment. 1 if the test is not sharded.
+    pub shards: u32,
 }
 
 /// Command line argument or environment variable value
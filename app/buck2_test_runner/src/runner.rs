@@ -76,17 +76,26 @@ impl Buck2TestRunner {
                 );
                 let target_handle = spec.target.handle.to_owned();
 
-                let execution_response = self
-                    .execute_test_from_spec(spec)
-                    .await
-                    .expect("Test execution request failed");
+                // Shards are independent executions of the test, run concurrently.
+                let shards = spec.shards;
+                let execution_responses = futures::future::join_all((0..shards).map(|index| {
+                    let shard = (shards > 1).then_some(Shard {
+                        index,
+                        count: shards,
+                    });
+                    self.execute_test_from_spec(&spec, shard)
+                }))
+                .await;
 
-                let execution_result = match execution_response {
-                    ExecuteResponse::Result(r) => r,
-                    ExecuteResponse::Cancelled => return TestStatus::OMITTED,
-                };
+                let mut execution_results = Vec::with_capacity(execution_responses.len());
+                for execution_response in execution_responses {
+                    match execution_response.expect("Test execution request failed") {
+                        ExecuteResponse::Result(r) => execution_results.push(r),
+                        ExecuteResponse::Cancelled => return TestStatus::OMITTED,
+                    }
+                }
 
-                let test_result = get_test_result(name, target_handle, execution_result);
+                let test_result = get_test_result(name, target_handle, execution_results);
                 let test_status = test_result.status.clone();
 
                 self.report_test_result(test_result)
@@ -117,10 +126,11 @@ impl Buck2TestRunner {
 
     async fn execute_test_from_spec(
         &self,
-        spec: ExternalRunnerSpec,
+        spec: &ExternalRunnerSpec,
+        shard: Option<Shard>,
     ) -> anyhow::Result<ExecuteResponse> {
         let display_metadata = DisplayMetadata::Testing {
-            suite: spec.target.target,
+            suite: spec.target.target.clone(),
             testcases: Vec::new(),
         };
 
@@ -133,7 +143,8 @@ impl Buck2TestRunner {
 
         let command = spec
             .command
-            .into_iter()
+            .iter()
+            .cloned()
             .map(|spec_value| ArgValue {
                 content: ArgValueContent::ExternalRunnerSpecValue(spec_value),
                 format: None,
@@ -153,19 +164,40 @@ impl Buck2TestRunner {
             )
         });
 
+        let shard_env = shard
+            .into_iter()
+            .flat_map(|Shard { index, count }| {
+                [
+                    ("TEST_SHARD_INDEX", index.to_string()),
+                    ("TEST_TOTAL_SHARDS", count.to_string()),
+                ]
+            })
+            .map(|(name, value)| {
+                (
+                    name.to_owned(),
+                    ArgValue {
+                        content: ArgValueContent::ExternalRunnerSpecValue(
+                            ExternalRunnerSpecValue::Verbatim(value),
+                        ),
+                        format: None,
+                    },
+                )
+            });
+
         let env = spec
             .env
-            .into_iter()
+            .iter()
             .map(|(key, value)| {
                 (
-                    key,
+                    key.to_owned(),
                     ArgValue {
-                        content: ArgValueContent::ExternalRunnerSpecValue(value),
+                        content: ArgValueContent::ExternalRunnerSpecValue(value.clone()),
                         format: None,
                     },
                 )
             })
             .chain(config_env)
+            .chain(shard_env)
             .collect();
 
         let target_handle = spec.target.handle;
@@ -195,28 +227,51 @@ impl Buck2TestRunner {
     }
 }
 
+/// One of the executions of a sharded test.
+#[derive(Clone, Copy, Debug)]
+struct Shard {
+    index: u32,
+    count: u32,
+}
+
+/// Merge the executions of the shards of a test, or its only execution, into its result.
 fn get_test_result(
     name: String,
     target: ConfiguredTargetHandle,
-    execution_result: ExecutionResult2,
+    execution_results: Vec<ExecutionResult2>,
 ) -> TestResult {
-    let status = match execution_result.status {
-        ExecutionStatus::Finished { exitcode } => match exitcode {
-            0 => TestStatus::PASS,
-            _ => TestStatus::FAIL,
-        },
-        ExecutionStatus::TimedOut { .. } => TestStatus::TIMEOUT,
-    };
+    let count = execution_results.len();
+    let mut status = TestStatus::PASS;
+    let mut duration = None;
+    let mut details = String::new();
+    for (index, execution_result) in execution_results.into_iter().enumerate() {
+        let shard_status = match execution_result.status {
+            ExecutionStatus::Finished { exitcode } => match exitcode {
+                0 => TestStatus::PASS,
+                _ => TestStatus::FAIL,
+            },
+            ExecutionStatus::TimedOut { .. } => TestStatus::TIMEOUT,
+        };
+        if status == TestStatus::PASS {
+            status = shard_status;
+        }
+        // Shards run concurrently, so the test takes as long as its longest shard.
+        duration = duration.max(Some(execution_result.execution_time));
+        if count > 1 {
+            details.push_str(&format!("==== SHARD {}/{} ====\n", index, count));
+        }
+        details.push_str(&format!(
+            "---- STDOUT ----\n{:?}\n---- STDERR ----\n{:?}\n",
+            execution_result.stdout, execution_result.stderr
+        ));
+    }
     TestResult {
         target,
         name,
         status,
         msg: None,
-        duration: Some(execution_result.execution_time),
-        details: format!(
-            "---- STDOUT ----\n{:?}\n---- STDERR ----\n{:?}\n",
-            execution_result.stdout, execution_result.stderr
-        ),
+        duration,
+        details,
     }
 }
 
@@ -234,3 +289,75 @@ impl RunVerdict {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use std::time::SystemTime;
+
+    use buck2_test_api::data::ExecutionDetails;
+    use buck2_test_api::data::ExecutionStream;
+
+    use super::*;
+
+    fn execution_result(status: ExecutionStatus, execution_time: Duration) -> ExecutionResult2 {
+        ExecutionResult2 {
+            status,
+            stdout: ExecutionStream::Inline(b"out".to_vec()),
+            stderr: ExecutionStream::Inline(Vec::new()),
+            outputs: Default::default(),
+            start_time: SystemTime::UNIX_EPOCH,
+            execution_time,
+            execution_details: ExecutionDetails::default(),
+        }
+    }
+
+    #[test]
+    fn test_get_test_result_not_sharded() {
+        let result = get_test_result(
+            "test".to_owned(),
+            ConfiguredTargetHandle::from(1),
+            vec![execution_result(
+                ExecutionStatus::Finished { exitcode: 0 },
+                Duration::from_secs(1),
+            )],
+        );
+        assert_eq!(TestStatus::PASS, result.status);
+        assert_eq!(Some(Duration::from_secs(1)), result.duration);
+        assert!(!result.details.contains("SHARD"), "{}", result.details);
+    }
+
+    #[test]
+    fn test_get_test_result_sharded() {
+        let result = get_test_result(
+            "test".to_owned(),
+            ConfiguredTargetHandle::from(1),
+            vec![
+                execution_result(
+                    ExecutionStatus::Finished { exitcode: 0 },
+                    Duration::from_secs(1),
+                ),
+                execution_result(
+                    ExecutionStatus::Finished { exitcode: 1 },
+                    Duration::from_secs(3),
+                ),
+                execution_result(
+                    ExecutionStatus::TimedOut {
+                        duration: Duration::from_secs(2),
+                    },
+                    Duration::from_secs(2),
+                ),
+            ],
+        );
+        // The first shard which didn't pass decides the status.
+        assert_eq!(TestStatus::FAIL, result.status);
+        // Shards run concurrently.
+        assert_eq!(Some(Duration::from_secs(3)), result.duration);
+        assert!(
+            result.details.contains("==== SHARD 0/3 ====")
+                && result.details.contains("==== SHARD 2/3 ===="),
+            "{}",
+            result.details
+        );
+    }
+}
@@ -50,6 +50,7 @@ use starlark_map::sorted_map::SortedMap;
 use crate::coerced_attr::CoercedAttrResolveExt;
 use crate::transition::calculation_fetch_transition::FetchTransition;
 use crate::transition::starlark::FrozenTransition;
+use crate::transition::starlark::FrozenTransitionImplementation;
 
 #[derive(buck2_error::Error, Debug)]
 enum ApplyTransitionError {
@@ -77,6 +78,24 @@ fn call_transition_function<'v>(
     attrs: Option<Value<'v>>,
     eval: &mut Evaluator<'v, '_>,
 ) -> anyhow::Result<TransitionApplied> {
+    let implementation = match &transition.implementation {
+        FrozenTransitionImplementation::Function(implementation) => *implementation,
+        FrozenTransitionImplementation::Composed(parts) => {
+            // `refs` and `attrs` of a composed transition have those of all its parts.
+            let mut conf = conf.dupe();
+            for part in parts {
+                let part = part.as_ref();
+                let part_attrs = if part.attrs.is_some() { attrs } else { None };
+                match call_transition_function(part, &conf, refs, part_attrs, eval)? {
+                    TransitionApplied::Single(new) => conf = new,
+                    TransitionApplied::Split(_) => {
+                        unreachable!("split transitions are not composable")
+                    }
+                }
+            }
+            return Ok(TransitionApplied::Single(conf));
+        }
+    };
     let mut args = vec![
         (
             "platform",
@@ -89,7 +108,7 @@ fn call_transition_function<'v>(
         args.push(("attrs", attrs));
     }
     let new_platforms = eval
-        .eval_function(transition.implementation.to_value(), &[], &args)
+        .eval_function(implementation.to_value(), &[], &args)
         .map_err(BuckStarlarkError::new)?;
     if transition.split {
         match DictOf::<&str, &PlatformInfo>::unpack_value(new_platforms) {
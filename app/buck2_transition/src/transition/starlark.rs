@@ -21,6 +21,7 @@ use buck2_interpreter::functions::transition::REGISTER_TRANSITION;
 use buck2_interpreter::types::transition::TransitionValue;
 use derive_more::Display;
 use dupe::Dupe;
+use either::Either;
 use gazebo::prelude::*;
 use itertools::Itertools;
 use starlark::any::ProvidesStaticType;
@@ -38,6 +39,7 @@ use starlark::values::Freeze;
 use starlark::values::Freezer;
 use starlark::values::FrozenStringValue;
 use starlark::values::FrozenValue;
+use starlark::values::FrozenValueTyped;
 use starlark::values::NoSerialize;
 use starlark::values::StarlarkValue;
 use starlark::values::StringValue;
@@ -62,12 +64,34 @@ enum TransitionError {
     MustBeDefWrongSig(String, &'static [&'static str]),
     #[error("Non-unique list of attrs")]
     NonUniqueAttrs,
+    #[error("`composed_transition` expects a list of transitions, got `{0}`")]
+    ComposedNotTransition(String),
+    #[error("`composed_transition` expects at least one transition")]
+    ComposedEmpty,
+    #[error("Split transitions cannot be composed")]
+    SplitNotComposable,
+    #[error("Composed transitions use different targets for the ref `{0}`: `{1}` and `{2}`")]
+    ComposedConflictingRefs(String, TargetLabel, TargetLabel),
 }
 
 /// Wrapper for `TargetLabel` which is `Trace`.
 #[derive(Trace, Debug, Allocative)]
 struct TargetLabelTrace(TargetLabel);
 
+#[derive(Debug, Trace, Allocative)]
+enum TransitionImplementation<'v> {
+    /// The `impl` function of `transition()`.
+    Function(Value<'v>),
+    /// The transitions of `composed_transition()`, applied in order.
+    Composed(Vec<Value<'v>>),
+}
+
+#[derive(Debug, Allocative)]
+pub(crate) enum FrozenTransitionImplementation {
+    Function(FrozenValue),
+    Composed(Vec<FrozenValueTyped<'static, FrozenTransition>>),
+}
+
 #[derive(Debug, Display, Trace, ProvidesStaticType, NoSerialize, Allocative)]
 #[display(fmt = "transition")]
 pub(crate) struct Transition<'v> {
@@ -76,7 +100,7 @@ pub(crate) struct Transition<'v> {
     id: RefCell<Option<Arc<TransitionId>>>,
     /// The path where this `Transition` is created and assigned.
     path: ImportPath,
-    implementation: Value<'v>,
+    implementation: TransitionImplementation<'v>,
     /// Providers needed for the transition function. A map by target label.
     refs: SmallMap<StringValue<'v>, TargetLabelTrace>,
     /// Transition function accesses theses attributes.
@@ -89,7 +113,7 @@ pub(crate) struct Transition<'v> {
 #[display(fmt = "transition")]
 pub(crate) struct FrozenTransition {
     id: Arc<TransitionId>,
-    pub(crate) implementation: FrozenValue,
+    pub(crate) implementation: FrozenTransitionImplementation,
     pub(crate) refs: SmallMap<FrozenStringValue, TargetLabel>,
     pub(crate) attrs: Option<Vec<FrozenStringValue>>,
     pub(crate) split: bool,
@@ -131,7 +155,14 @@ impl<'v> Freeze for Transition<'v> {
     type Frozen = FrozenTransition;
 
     fn freeze(self, freezer: &Freezer) -> anyhow::Result<FrozenTransition> {
-        let implementation = freezer.freeze(self.implementation)?;
+        let implementation = match self.implementation {
+            TransitionImplementation::Function(f) => {
+                FrozenTransitionImplementation::Function(freezer.freeze(f)?)
+            }
+            TransitionImplementation::Composed(parts) => FrozenTransitionImplementation::Composed(
+                parts.into_try_map(|p| FrozenValueTyped::new_err(freezer.freeze(p)?))?,
+            ),
+        };
         let id = self
             .id
             .into_inner()
@@ -220,12 +251,92 @@ fn register_transition_function(builder: &mut GlobalsBuilder) {
         Ok(Transition {
             id: RefCell::new(None),
             path,
-            implementation,
+            implementation: TransitionImplementation::Function(implementation),
             refs,
             attrs: attrs.map(|a| a.items),
             split,
         })
     }
+
+    /// Compose transitions into one transition, which applies them in order: each transition
+    /// gets the configuration produced by the previous one.
+    ///
+    /// The composed transition has the `refs` and `attrs` of all its transitions. Split
+    /// transitions cannot be composed.
+    fn composed_transition<'v>(
+        #[starlark(require = pos)] transitions: UnpackListOrTuple<Value<'v>>,
+        eval: &mut Evaluator<'v, '_>,
+    ) -> anyhow::Result<Transition<'v>> {
+        let path: ImportPath = (*starlark_path_from_build_context(eval)?
+            .unpack_load_file()
+            .ok_or(TransitionError::OnlyBzl)?)
+        .clone();
+
+        if transitions.items.is_empty() {
+            return Err(TransitionError::ComposedEmpty.into());
+        }
+
+        let mut refs: SmallMap<StringValue<'v>, TargetLabelTrace> = SmallMap::new();
+        let mut attrs: Option<Vec<StringValue<'v>>> = None;
+        for part in &transitions.items {
+            let (split, part_refs, part_attrs): (
+                bool,
+                Vec<(StringValue<'v>, TargetLabel)>,
+                Option<Vec<StringValue<'v>>>,
+            ) = match Transition::from_value(*part) {
+                Some(Either::Left(t)) => (
+                    t.split,
+                    t.refs.iter().map(|(k, v)| (*k, v.0.dupe())).collect(),
+                    t.attrs.clone(),
+                ),
+                Some(Either::Right(t)) => (
+                    t.split,
+                    t.refs
+                        .iter()
+                        .map(|(k, v)| (k.to_string_value(), v.dupe()))
+                        .collect(),
+                    t.attrs.as_ref().map(|a| a.map(|a| a.to_string_value())),
+                ),
+                None => {
+                    return Err(TransitionError::ComposedNotTransition(part.to_repr()).into());
+                }
+            };
+            if split {
+                return Err(TransitionError::SplitNotComposable.into());
+            }
+            for (name, label) in part_refs {
+                if let Some(prev) = refs.get(&name) {
+                    if prev.0 != label {
+                        return Err(TransitionError::ComposedConflictingRefs(
+                            name.as_str().to_owned(),
+                            prev.0.dupe(),
+                            label,
+                        )
+                        .into());
+                    }
+                } else {
+                    refs.insert(name, TargetLabelTrace(label));
+                }
+            }
+            if let Some(part_attrs) = part_attrs {
+                let attrs = attrs.get_or_insert_with(Vec::new);
+                for attr in part_attrs {
+                    if !attrs.contains(&attr) {
+                        attrs.push(attr);
+                    }
+                }
+            }
+        }
+
+        Ok(Transition {
+            id: RefCell::new(None),
+            path,
+            implementation: TransitionImplementation::Composed(transitions.items),
+            refs,
+            attrs,
+            split: false,
+        })
+    }
 }
 
 pub(crate) fn init_register_transition() {
@@ -0,0 +1,100 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+/// Immediate dominators of a graph where every node is reachable from node 0, using the
+/// algorithm from "A Simple, Fast Dominance Algorithm" by Cooper, Harvey and Kennedy.
+///
+/// Returns the nodes in postorder, and the immediate dominator of every node (0 for the root).
+pub fn dominators(succs: &[Vec<usize>]) -> (Vec<usize>, Vec<usize>) {
+    let n = succs.len();
+
+    let mut postorder = Vec::with_capacity(n);
+    let mut visited = vec![false; n];
+    let mut stack = vec![(0, 0)];
+    visited[0] = true;
+    while let Some((node, next)) = stack.last_mut() {
+        match succs[*node].get(*next) {
+            Some(&s) => {
+                *next += 1;
+                if !visited[s] {
+                    visited[s] = true;
+                    stack.push((s, 0));
+                }
+            }
+            None => {
+                postorder.push(*node);
+                stack.pop();
+            }
+        }
+    }
+
+    let mut po_number = vec![0; n];
+    for (i, &node) in postorder.iter().enumerate() {
+        po_number[node] = i;
+    }
+    let mut preds = vec![Vec::new(); n];
+    for (node, ss) in succs.iter().enumerate() {
+        for &s in ss {
+            preds[s].push(node);
+        }
+    }
+
+    let mut idom: Vec<Option<usize>> = vec![None; n];
+    idom[0] = Some(0);
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for &node in postorder.iter().rev().skip(1) {
+            let mut new_idom = None;
+            for &p in &preds[node] {
+                if idom[p].is_none() {
+                    continue;
+                }
+                new_idom = Some(match new_idom {
+                    None => p,
+                    Some(d) => {
+                        let (mut a, mut b) = (p, d);
+                        while a != b {
+                            while po_number[a] < po_number[b] {
+                                a = idom[a].unwrap();
+                            }
+                            while po_number[b] < po_number[a] {
+                                b = idom[b].unwrap();
+                            }
+                        }
+                        a
+                    }
+                });
+            }
+            if new_idom != idom[node] {
+                idom[node] = new_idom;
+                changed = true;
+            }
+        }
+    }
+
+    (
+        postorder,
+        idom.into_iter().map(|d| d.unwrap_or(0)).collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dominators() {
+        // 0 -> 1 -> 3, 0 -> 2 -> 3, 3 -> 4
+        let succs = vec![vec![1, 2], vec![3], vec![3], vec![4], vec![]];
+        let (postorder, idom) = dominators(&succs);
+        assert_eq!(postorder.len(), 5);
+        assert_eq!(idom, vec![0, 0, 0, 0, 3]);
+    }
+}
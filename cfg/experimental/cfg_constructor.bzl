@@ -10,6 +10,7 @@ load(
     "get_modifier_info",
     "json_to_tagged_modifiers",
     "modifier_to_refs",
+    "resolve_cli_modifier_aliases",
     "resolve_modifier",
 )
 load(":name.bzl", "cfg_name")
@@ -36,6 +37,7 @@ def cfg_constructor_pre_constraint_analysis(
         # typing.Any is JSON form of modifier
         target_modifiers: list[Modifier] | None,
         cli_modifiers: list[str],
+        aliases: struct | None = None,
         **_kwargs) -> (list[str], PostConstraintAnalysisParams):
     """
     First stage of cfg constructor for modifiers.
@@ -49,6 +51,9 @@ def cfg_constructor_pre_constraint_analysis(
             A list of modifiers specified from buildfile via `metadata` attribute.
         cli_modifiers:
             modifiers specified from `--modifier` flag, `?modifier`, or BXL
+        aliases:
+            short names for modifiers passed to `set_cfg_constructor`, which can be used
+            in place of `cli_modifiers`
 
     Returns `(refs, PostConstraintAnalysisParams)`, where `refs` is a list of fully qualified configuration
     targets we need providers for.
@@ -64,7 +69,8 @@ def cfg_constructor_pre_constraint_analysis(
     if target_modifiers:
         merged_modifiers.append(TaggedModifiers(modifiers = target_modifiers, location = ModifierTargetLocation()))
 
-    # Convert CLI modifiers to `TaggedModifier`
+    # Convert CLI modifiers to `TaggedModifier`, in order so later modifiers win
+    cli_modifiers = resolve_cli_modifier_aliases(cli_modifiers, aliases)
     if cli_modifiers:
         merged_modifiers.append(TaggedModifiers(modifiers = cli_modifiers, location = ModifierCliLocation()))
 
@@ -153,6 +153,26 @@ def modifier_to_refs(modifier: Modifier, location: ModifierLocation) -> list[str
         fail("Internal error: Found unexpected modifier `{}` type `{}`".format(modifier, type(modifier)))
     return refs
 
+def resolve_cli_modifier_aliases(cli_modifiers: list[str], aliases: struct | None) -> list[str]:
+    # Expand aliases in modifiers from the command line, keeping their order. An alias is
+    # either a single modifier or a list of modifiers. Anything which is not a target label
+    # must be an alias.
+    resolved = []
+    for modifier in cli_modifiers:
+        alias = getattr(aliases, modifier, None)
+        if alias == None:
+            if ":" not in modifier:
+                fail(
+                    "Modifier `{}` from {} is neither a target label nor a modifier alias. ".format(modifier, _CLI_LOCATION_STR) +
+                    "Available aliases: {}".format(", ".join(dir(aliases)) or "none"),
+                )
+            resolved.append(modifier)
+        elif isinstance(alias, str):
+            resolved.append(alias)
+        else:
+            resolved.extend(alias)
+    return resolved
+
 def tagged_modifiers_to_json(tagged_modifiers: TaggedModifiers) -> dict[str, typing.Any]:
     return {
         "location": _location_to_json(tagged_modifiers.location),
@@ -9,7 +9,15 @@ load(":cfg_constructor.bzl?v2_only", "cfg_constructor_post_constraint_analysis",
 
 MODIFIER_METADATA_KEY = "buck.cfg_modifiers"
 
-def set_cfg_constructor():
+def set_cfg_constructor(aliases = struct()):
+    """
+    Register the modifiers cfg constructor.
+
+    `aliases` is a struct of short names for modifiers which can be used on the command line,
+    e.g. `struct(asan = "//sanitizers:asan")` allows `--modifier asan` and `//foo:bar?asan`.
+    An alias can also be a list of modifiers, applied in order.
+    """
+
     # This is to be buck1-proof.
     set_cfg_constructor_func = getattr(native, "set_cfg_constructor", None)
     if set_cfg_constructor_func:
@@ -17,4 +25,5 @@ def set_cfg_constructor():
             stage0 = cfg_constructor_pre_constraint_analysis,
             stage1 = cfg_constructor_post_constraint_analysis,
             key = MODIFIER_METADATA_KEY,
+            aliases = aliases,
         )
@@ -173,6 +173,7 @@ use serde::Serializer;
 use crate::api::cycles::DetectCycles;
 use crate::api::transaction::DiceTransactionUpdater;
 use crate::api::user_data::UserComputationData;
+use crate::introspection::stats::GraphStats;
 use crate::metrics::Metrics;
 use crate::DiceDataBuilderImpl;
 use crate::DiceImplementation;
@@ -226,6 +227,16 @@ impl Dice {
         self.implementation.serialize_serde(serializer)
     }
 
+    /// Statistics per key type, and the `top_keys` keys invalidated the most times.
+    pub fn graph_stats(&self, top_keys: usize) -> GraphStats {
+        self.implementation.graph_stats(top_keys)
+    }
+
+    /// The keys being computed, one per line, for crash reports.
+    pub fn keys_currently_running(&self) -> Vec<String> {
+        self.implementation.keys_currently_running()
+    }
+
     pub fn detect_cycles(&self) -> &DetectCycles {
         self.implementation.detect_cycles()
     }
@@ -54,6 +54,15 @@ pub fn serialize_graph(
     Ok(())
 }
 
+/// One line per key being computed, with its version, state and key type, as in the TSV dump.
+pub fn keys_currently_running(graph: &GraphIntrospectable) -> Vec<String> {
+    graph
+        .introspectables()
+        .flat_map(|engine| engine.keys_currently_running())
+        .map(|(k, v, s)| format!("{v}\t{s:?}\t{}\t{k}", k.short_type_name()))
+        .collect()
+}
+
 pub fn serialize_dense_graph<S>(graph: &GraphIntrospectable, writer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
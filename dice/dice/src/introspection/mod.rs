@@ -18,7 +18,9 @@ use crate::DiceImplementation;
 
 pub mod graph;
 pub(crate) mod introspect;
+pub mod stats;
 
+pub use crate::introspection::introspect::keys_currently_running;
 pub use crate::introspection::introspect::serialize_dense_graph;
 pub use crate::introspection::introspect::serialize_graph;
 use crate::legacy::DiceLegacy;
@@ -55,7 +57,9 @@ mod tests {
     use crate::api::cycles::DetectCycles;
     use crate::api::key::Key;
     use crate::introspection::graph::SerializedGraphNodesForKey;
+    use crate::introspection::keys_currently_running;
     use crate::introspection::serialize_graph;
+    use crate::introspection::stats::graph_stats;
     use crate::DiceLegacy;
     use crate::HashMap;
 
@@ -155,6 +159,7 @@ mod tests {
         assert_eq!(expected_edge_list, edge_list);
 
         assert!(nodes_currently_running.is_empty());
+        assert!(keys_currently_running(&dice.to_introspectable()).is_empty());
 
         Ok(())
     }
@@ -170,4 +175,24 @@ mod tests {
         let _out: Vec<SerializedGraphNodesForKey> = bincode::deserialize(&node)?;
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_graph_stats() -> anyhow::Result<()> {
+        let dice = DiceLegacy::builder().build(DetectCycles::Disabled);
+        let mut ctx = dice.updater().commit().await;
+        ctx.compute(&KeyA(3)).await?;
+
+        let stats = graph_stats(&dice.to_introspectable(), 10);
+        let key_types: HashMap<_, _> = stats
+            .key_types
+            .iter()
+            .map(|s| (s.type_name.as_str(), (s.keys, s.edges)))
+            .collect();
+        assert_eq!(Some(&(4, 4)), key_types.get("KeyA"));
+        assert_eq!(Some(&(1, 0)), key_types.get("KeyB"));
+        assert!(stats.key_types.iter().all(|s| s.memory_bytes.is_some()));
+        // Nothing was invalidated yet.
+        assert!(stats.most_invalidated_keys.is_empty());
+        Ok(())
+    }
 }
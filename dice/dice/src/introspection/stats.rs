@@ -0,0 +1,153 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Summary statistics of the DICE graph, to spot key types which use a lot of memory or which are
+//! invalidated on most transactions.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use allocative::Allocative;
+use allocative::FlameGraphBuilder;
+use allocative::Visitor;
+use serde::Serialize;
+
+use crate::introspection::graph::short_type_name;
+use crate::introspection::graph::GraphIntrospectable;
+use crate::introspection::graph::HistoryState;
+use crate::introspection::graph::SerializedGraphNodesForKey;
+use crate::legacy::incremental::ErasedEngine;
+use crate::HashMap;
+
+#[derive(Serialize)]
+pub struct GraphStats {
+    /// Per key type, sorted by decreasing number of keys.
+    pub key_types: Vec<KeyTypeStats>,
+    /// The keys invalidated the most times, in decreasing order.
+    pub most_invalidated_keys: Vec<KeyStats>,
+}
+
+#[derive(Serialize, Default)]
+pub struct KeyTypeStats {
+    pub type_name: String,
+    pub keys: u64,
+    /// Number of values stored, across all the versions kept in the graph.
+    pub nodes: u64,
+    /// Number of dependencies of the latest value of every key.
+    pub edges: u64,
+    /// Number of versions at which any key of this type was invalidated.
+    pub invalidations: u64,
+    /// Memory used by all the keys and values of this type, if known. Data shared with other key
+    /// types is counted in each of them.
+    pub memory_bytes: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct KeyStats {
+    pub type_name: String,
+    pub key: String,
+    pub invalidations: u64,
+}
+
+/// Forwards to the `Allocative` implementation of an engine.
+struct EngineAllocative<'a>(&'a (dyn ErasedEngine + Send + Sync + 'static));
+
+impl Allocative for EngineAllocative<'_> {
+    fn visit<'a, 'b: 'a>(&self, visitor: &'a mut Visitor<'b>) {
+        self.0.visit(visitor)
+    }
+}
+
+/// Versions at which the key was invalidated, across all its nodes.
+fn invalidations(node: &SerializedGraphNodesForKey) -> u64 {
+    let mut versions = BTreeSet::new();
+    for n in node.nodes.values().flatten() {
+        for (v, state) in &n.history.history {
+            match state {
+                HistoryState::Dirty | HistoryState::ForceDirty => {
+                    versions.insert(*v);
+                }
+                HistoryState::Verified => {}
+            }
+        }
+    }
+    versions.len() as u64
+}
+
+pub fn graph_stats(graph: &GraphIntrospectable, top_keys: usize) -> GraphStats {
+    let mut key_types: BTreeMap<String, KeyTypeStats> = BTreeMap::new();
+    let mut keys = Vec::new();
+    let mut reg = HashMap::default();
+
+    for engine in graph.introspectables() {
+        for node in engine.nodes(&mut reg) {
+            let type_name = short_type_name(&node.type_name).to_owned();
+            let invalidations = invalidations(&node);
+            let stats = key_types
+                .entry(type_name.clone())
+                .or_insert_with(|| KeyTypeStats {
+                    type_name: type_name.clone(),
+                    ..KeyTypeStats::default()
+                });
+            stats.keys += 1;
+            stats.nodes += node.nodes.values().flatten().count() as u64;
+            stats.edges += node
+                .nodes
+                .values()
+                .flatten()
+                .next_back()
+                .and_then(|n| n.deps.as_ref())
+                .map_or(0, |deps| deps.len() as u64);
+            stats.invalidations += invalidations;
+            if invalidations > 0 {
+                keys.push(KeyStats {
+                    type_name,
+                    key: node.key,
+                    invalidations,
+                });
+            }
+        }
+    }
+
+    // Legacy DICE has one engine per key type, so memory can be attributed to key types. Modern
+    // DICE stores all keys together, so memory per key type is unknown.
+    if let GraphIntrospectable::Legacy { introspectables } = graph {
+        for engine in &introspectables.0 {
+            let type_name = match engine.introspect().nodes(&mut reg).next() {
+                Some(node) => short_type_name(&node.type_name).to_owned(),
+                None => continue,
+            };
+            let mut builder = FlameGraphBuilder::default();
+            builder.visit_root(&EngineAllocative(&**engine));
+            let size = builder.finish().flamegraph().total_size() as u64;
+            if let Some(stats) = key_types.get_mut(&type_name) {
+                *stats.memory_bytes.get_or_insert(0) += size;
+            }
+        }
+    }
+
+    let mut key_types: Vec<_> = key_types.into_values().collect();
+    key_types.sort_by(|a, b| {
+        b.keys
+            .cmp(&a.keys)
+            .then_with(|| a.type_name.cmp(&b.type_name))
+    });
+
+    keys.sort_by(|a, b| {
+        b.invalidations
+            .cmp(&a.invalidations)
+            .then_with(|| a.key.cmp(&b.key))
+    });
+    keys.truncate(top_keys);
+
+    GraphStats {
+        key_types,
+        most_invalidated_keys: keys,
+    }
+}
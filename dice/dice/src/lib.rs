@@ -240,8 +240,11 @@ pub use crate::api::which::WhichDice;
 use crate::impls::dice::DiceModern;
 use crate::impls::dice::DiceModernDataBuilder;
 use crate::introspection::graph::GraphIntrospectable;
+use crate::introspection::keys_currently_running;
 use crate::introspection::serialize_dense_graph;
 use crate::introspection::serialize_graph;
+use crate::introspection::stats::graph_stats;
+use crate::introspection::stats::GraphStats;
 use crate::legacy::DiceLegacy;
 use crate::legacy::DiceLegacyDataBuilder;
 use crate::transaction_update::DiceTransactionUpdaterImpl;
@@ -293,6 +296,14 @@ impl DiceImplementation {
         Ok(())
     }
 
+    pub fn graph_stats(&self, top_keys: usize) -> GraphStats {
+        graph_stats(&self.to_introspectable(), top_keys)
+    }
+
+    pub fn keys_currently_running(&self) -> Vec<String> {
+        keys_currently_running(&self.to_introspectable())
+    }
+
     fn to_introspectable(&self) -> GraphIntrospectable {
         match self {
             DiceImplementation::Legacy(dice) => dice.to_introspectable(),
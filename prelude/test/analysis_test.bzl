@@ -0,0 +1,161 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is licensed under both the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree and the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree.
+
+"""Analysis tests: tests of the providers and command lines of a rule, run by `buck2 test`.
+
+An analysis test analyses a target and makes assertions on its providers, without building
+it. Define the test rule in a `.bzl` file:
+
+    load("@prelude//test/analysis_test.bzl", "analysis_test")
+
+    def _my_library_test(env, target):
+        env.expect.equals(1, len(target[DefaultInfo].default_outputs))
+        env.expect.command_line_contains(target[RunInfo], "--opt")
+
+    my_library_test = analysis_test(impl = _my_library_test)
+
+and use it in a `BUCK` file:
+
+    my_library_test(
+        name = "my_library_test",
+        target = ":my_library",
+    )
+
+Unlike `asserts`, failed assertions don't fail the analysis: they are all reported when the
+test runs, and the test fails.
+
+`env.expect.command_line_matches_golden(args, "name")` compares a command line, one argument
+per line, with the checked-in file `goldens["name"]` of the test, and fails with a diff if they
+differ. `buck2 run :my_library_test -- --update` rewrites the golden files instead.
+"""
+
+def _new_env(ctx: AnalysisContext):
+    failures = []
+    command_lines = []
+    goldens = []
+
+    def _fail(msg, expectation):
+        failures.append("{} ({})".format(msg, expectation) if msg else expectation)
+
+    def equals(expected, actual, msg = None):
+        if expected != actual:
+            _fail(msg, "Expected `{}`, but got `{}`".format(expected, actual))
+
+    def true(condition, msg = None):
+        if not condition:
+            _fail(msg, "Expected condition to be true, but was false")
+
+    def false(condition, msg = None):
+        if condition:
+            _fail(msg, "Expected condition to be false, but was true")
+
+    def contains(container, item, msg = None):
+        if item not in container:
+            _fail(msg, "Expected `{}` to contain `{}`".format(container, item))
+
+    def has_provider(target: Dependency, provider, msg = None) -> bool:
+        if target.get(provider) == None:
+            _fail(msg, "Expected `{}` to have provider `{}`".format(target.label, provider))
+            return False
+        return True
+
+    def _command_line(args, substring, expected, msg, expectation):
+        if isinstance(args, RunInfo):
+            args = args.args
+        command_line = ctx.actions.write(
+            "analysis_test/command_line_{}.txt".format(len(command_lines)),
+            cmd_args(args, delimiter = " "),
+        )
+        command_lines.append({
+            "command_line": command_line,
+            "expected": expected,
+            "message": "{} ({})".format(msg, expectation) if msg else expectation,
+            "substring": substring,
+        })
+
+    def command_line_contains(args, substring, msg = None):
+        _command_line(args, substring, True, msg, "Expected the command line to contain `{}`".format(substring))
+
+    def command_line_not_contains(args, substring, msg = None):
+        _command_line(args, substring, False, msg, "Expected the command line not to contain `{}`".format(substring))
+
+    def command_line_matches_golden(args, golden: str, msg = None):
+        if isinstance(args, RunInfo):
+            args = args.args
+        golden_file = ctx.attrs.goldens.get(golden)
+        if golden_file == None:
+            _fail(msg, "No golden file `{}`, add it to `goldens`".format(golden))
+            return
+        command_line = ctx.actions.write(
+            "analysis_test/golden_{}.txt".format(len(goldens)),
+            cmd_args(args),
+        )
+        goldens.append({
+            "command_line": command_line,
+            "golden": golden_file,
+            "message": "{} (The command line doesn't match the golden file `{}`)".format(msg, golden) if msg else "The command line doesn't match the golden file `{}`".format(golden),
+        })
+
+    expect = struct(
+        equals = equals,
+        true = true,
+        false = false,
+        contains = contains,
+        has_provider = has_provider,
+        # Command lines are only known once written, so these are checked when the test runs.
+        command_line_contains = command_line_contains,
+        command_line_not_contains = command_line_not_contains,
+        command_line_matches_golden = command_line_matches_golden,
+    )
+    return struct(ctx = ctx, expect = expect), failures, command_lines, goldens
+
+def analysis_test(impl, extra_attrs: dict[str, Attr] = {}, doc: str = ""):
+    """Create a rule testing the target in its `target` attribute.
+
+    `impl(env, target)` is called during the analysis of the test with the `Dependency` of the
+    target. `env.expect` has the assertions, and `env.ctx` is the `AnalysisContext` of the test,
+    with any `extra_attrs`.
+    """
+
+    def _analysis_test_impl(ctx: AnalysisContext) -> list[Provider]:
+        env, failures, command_lines, goldens = _new_env(ctx)
+        impl(env, ctx.attrs.target)
+
+        # Absolute, so that `buck2 run` can update the golden files from any directory.
+        results = ctx.actions.write_json(
+            "analysis_test/results.json",
+            {"command_lines": command_lines, "failures": failures, "goldens": goldens},
+            absolute = True,
+        )
+        command = cmd_args(ctx.attrs._check_analysis_test[RunInfo], results)
+        command.hidden([c["command_line"] for c in command_lines + goldens])
+        command.hidden([g["golden"] for g in goldens])
+
+        return [
+            DefaultInfo(default_output = results),
+            RunInfo(args = command),
+            ExternalRunnerTestInfo(
+                type = "custom",
+                command = [command],
+                labels = ctx.attrs.labels,
+                contacts = ctx.attrs.contacts,
+                run_from_project_root = True,
+                use_project_relative_paths = True,
+            ),
+        ]
+
+    return rule(
+        impl = _analysis_test_impl,
+        doc = doc,
+        attrs = {
+            "contacts": attrs.list(attrs.string(), default = []),
+            "goldens": attrs.dict(attrs.string(), attrs.source(), default = {}, doc = "The golden files of `command_line_matches_golden`, by name."),
+            "labels": attrs.list(attrs.string(), default = []),
+            "target": attrs.dep(doc = "The target under test, which is analysed but not built."),
+            "_check_analysis_test": attrs.default_only(attrs.exec_dep(default = "prelude//test/tools:check_analysis_test")),
+        } | extra_attrs,
+    )
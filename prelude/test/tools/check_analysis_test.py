@@ -0,0 +1,92 @@
+#!/usr/bin/env python3
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is licensed under both the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree and the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree.
+
+"""
+Report the result of an `analysis_test`: the failures of the assertions evaluated
+during analysis, and of the assertions on command lines, which can only be
+checked once the command lines are written.
+
+With `--update`, the golden files of the command lines are rewritten instead of
+being compared.
+"""
+
+import argparse
+import difflib
+import json
+import re
+import sys
+
+# The configuration hash in the paths of build artifacts, which changes with the
+# configuration and would otherwise make golden files churn.
+_CONFIGURATION_HASH = re.compile(r"(buck-out/[^/\s]+/(?:gen|tmp)/[^/\s]+/)[0-9a-f]{16}/")
+
+
+def normalize(command_line):
+    return _CONFIGURATION_HASH.sub(r"\1<hash>/", command_line)
+
+
+def check_golden(check, update):
+    with open(check["command_line"]) as command_line_file:
+        command_line = normalize(command_line_file.read())
+    with open(check["golden"]) as golden_file:
+        golden = golden_file.read()
+    if command_line == golden:
+        return None
+    if update:
+        with open(check["golden"], "w") as golden_file:
+            golden_file.write(command_line)
+        print("Updated `{}`".format(check["golden"]))
+        return None
+    diff = difflib.unified_diff(
+        golden.splitlines(keepends=True),
+        command_line.splitlines(keepends=True),
+        fromfile=check["golden"],
+        tofile="actual",
+    )
+    return "{}, run the test with `buck2 run <test> -- --update` to update it\n{}".format(
+        check["message"], "".join(diff)
+    )
+
+
+def main():
+    parser = argparse.ArgumentParser()
+    parser.add_argument(
+        "results", help="A JSON file containing the results of the analysis"
+    )
+    parser.add_argument(
+        "--update",
+        action="store_true",
+        help="Rewrite the golden files instead of comparing them",
+    )
+
+    args = parser.parse_args()
+
+    with open(args.results) as results_file:
+        results = json.load(results_file)
+
+    failures = list(results["failures"])
+    for check in results["command_lines"]:
+        with open(check["command_line"]) as command_line_file:
+            command_line = command_line_file.read().strip()
+        if (check["substring"] in command_line) != check["expected"]:
+            failures.append("{}\n  command line: {}".format(check["message"], command_line))
+    for check in results["goldens"]:
+        failure = check_golden(check, args.update)
+        if failure:
+            failures.append(failure)
+
+    for failure in failures:
+        print("FAIL: {}".format(failure), file=sys.stderr)
+    if failures:
+        print("{} assertion(s) failed".format(len(failures)), file=sys.stderr)
+        sys.exit(1)
+    print("All assertions passed")
+
+
+if __name__ == "__main__":
+    main()
@@ -30,6 +30,7 @@ use re_grpc_proto::build::bazel::remote::execution::v2::batch_update_blobs_reque
 use re_grpc_proto::build::bazel::remote::execution::v2::capabilities_client::CapabilitiesClient;
 use re_grpc_proto::build::bazel::remote::execution::v2::compressor;
 use re_grpc_proto::build::bazel::remote::execution::v2::content_addressable_storage_client::ContentAddressableStorageClient;
+use re_grpc_proto::build::bazel::remote::execution::v2::digest_function;
 use re_grpc_proto::build::bazel::remote::execution::v2::execution_client::ExecutionClient;
 use re_grpc_proto::build::bazel::remote::execution::v2::execution_stage;
 use re_grpc_proto::build::bazel::remote::execution::v2::ActionResult;
@@ -205,6 +206,9 @@ pub struct RECapabilities {
     max_msg_size: usize,
     /// Does the remote server support execution.
     exec_enabled: bool,
+    /// Names of the digest functions supported by the remote cache. Empty if the server didn't
+    /// say, or capabilities were not queried.
+    digest_functions: Vec<&'static str>,
 }
 
 struct InstanceName(Option<String>);
@@ -297,6 +301,7 @@ impl REClientBuilder {
             RECapabilities {
                 exec_enabled: true,
                 max_msg_size: DEFAULT_MAX_MSG_SIZE,
+                digest_functions: Vec::new(),
             }
         };
 
@@ -325,6 +330,7 @@ impl REClientBuilder {
         // with enough room for headers.
         let mut max_msg_size = DEFAULT_MAX_MSG_SIZE;
         let mut exec_enabled = true;
+        let mut digest_functions = Vec::new();
 
         if let Some(cache_cap) = resp.cache_capabilities {
             let size = cache_cap.max_batch_total_size_bytes as usize;
@@ -332,6 +338,11 @@ impl REClientBuilder {
             if size != 0 {
                 max_msg_size = size;
             }
+            digest_functions = cache_cap
+                .digest_functions
+                .iter()
+                .filter_map(|f| digest_function_name(*f))
+                .collect();
         }
 
         if let Some(exec_cap) = resp.execution_capabilities {
@@ -341,10 +352,30 @@ impl REClientBuilder {
         Ok(RECapabilities {
             max_msg_size,
             exec_enabled,
+            digest_functions,
         })
     }
 }
 
+/// The name of a digest function of the RE API, as used in `[buck2] digest_algorithms`.
+fn digest_function_name(value: i32) -> Option<&'static str> {
+    // Added to the RE API after the version of the protos we use.
+    const BLAKE3: i32 = 9;
+
+    match digest_function::Value::from_i32(value) {
+        Some(digest_function::Value::Sha1) => Some("SHA1"),
+        Some(digest_function::Value::Sha256) => Some("SHA256"),
+        Some(digest_function::Value::Md5) => Some("MD5"),
+        Some(digest_function::Value::Vso) => Some("VSO"),
+        Some(digest_function::Value::Sha384) => Some("SHA384"),
+        Some(digest_function::Value::Sha512) => Some("SHA512"),
+        Some(digest_function::Value::Murmur3) => Some("MURMUR3"),
+        Some(digest_function::Value::Unknown) => None,
+        None if value == BLAKE3 => Some("BLAKE3"),
+        None => None,
+    }
+}
+
 #[derive(Clone, Dupe)]
 struct InjectHeadersInterceptor {
     headers: Arc<Vec<(MetadataKey<metadata::Ascii>, MetadataValue<metadata::Ascii>)>>,
@@ -473,6 +504,12 @@ impl REClient {
         }
     }
 
+    /// Names of the digest functions supported by the remote cache, e.g. `SHA256`. Empty if the
+    /// server didn't say, in which case any may work.
+    pub fn supported_digest_functions(&self) -> &[&'static str] {
+        &self.capabilities.digest_functions
+    }
+
     pub async fn get_action_result(
         &self,
         metadata: RemoteExecutionMetadata,
@@ -1303,6 +1340,24 @@ mod tests {
     use crate::NamedDigest;
     use crate::NamedDigestWithPermissions;
 
+    #[test]
+    fn test_digest_function_name() {
+        assert_eq!(
+            Some("SHA256"),
+            digest_function_name(digest_function::Value::Sha256 as i32)
+        );
+        assert_eq!(
+            Some("SHA1"),
+            digest_function_name(digest_function::Value::Sha1 as i32)
+        );
+        assert_eq!(Some("BLAKE3"), digest_function_name(9));
+        assert_eq!(
+            None,
+            digest_function_name(digest_function::Value::Unknown as i32)
+        );
+        assert_eq!(None, digest_function_name(1000));
+    }
+
     #[tokio::test]
     async fn test_download_named() -> anyhow::Result<()> {
         let work = tempfile::tempdir()?;
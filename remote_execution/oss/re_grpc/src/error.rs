@@ -42,18 +42,29 @@ pub struct TCode(pub i32);
 impl TCode {
     pub const OK: Self = TCode(0i32);
     pub const INVALID_ARGUMENT: Self = TCode(3i32);
+    pub const DEADLINE_EXCEEDED: Self = TCode(4i32);
     pub const NOT_FOUND: Self = TCode(5i32);
     pub const PERMISSION_DENIED: Self = TCode(7i32);
+    pub const RESOURCE_EXHAUSTED: Self = TCode(8i32);
+    pub const ABORTED: Self = TCode(10i32);
+    pub const INTERNAL: Self = TCode(13i32);
+    pub const UNAVAILABLE: Self = TCode(14i32);
 }
 
 impl Display for TCode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self == &TCode::OK {
-            write!(f, "OK")
-        } else if self == &TCode::INVALID_ARGUMENT {
-            write!(f, "INVALID_ARGUMENT")
-        } else {
-            write!(f, "UNKNOWN")
-        }
+        let name = match *self {
+            TCode::OK => "OK",
+            TCode::INVALID_ARGUMENT => "INVALID_ARGUMENT",
+            TCode::DEADLINE_EXCEEDED => "DEADLINE_EXCEEDED",
+            TCode::NOT_FOUND => "NOT_FOUND",
+            TCode::PERMISSION_DENIED => "PERMISSION_DENIED",
+            TCode::RESOURCE_EXHAUSTED => "RESOURCE_EXHAUSTED",
+            TCode::ABORTED => "ABORTED",
+            TCode::INTERNAL => "INTERNAL",
+            TCode::UNAVAILABLE => "UNAVAILABLE",
+            _ => "UNKNOWN",
+        };
+        write!(f, "{}", name)
     }
 }
@@ -0,0 +1,520 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use starlark_syntax::syntax::ast::AstAssignIdent;
+use starlark_syntax::syntax::ast::AstExpr;
+use starlark_syntax::syntax::ast::AstParameter;
+use starlark_syntax::syntax::ast::AstStmt;
+use starlark_syntax::syntax::ast::Clause;
+use starlark_syntax::syntax::ast::Expr;
+use starlark_syntax::syntax::ast::Stmt;
+use starlark_syntax::syntax::module::AstModuleFields;
+use starlark_syntax::syntax::uniplate::Visit;
+use thiserror::Error;
+
+use crate::analysis::types::LintT;
+use crate::analysis::types::LintWarning;
+use crate::analysis::EvalSeverity;
+use crate::codemap::FileSpan;
+use crate::codemap::Span;
+use crate::syntax::AstModule;
+use crate::syntax::Dialect;
+
+#[derive(Error, Debug)]
+pub(crate) enum GlobalsWarning {
+    #[error("Use of deprecated global `{0}`")]
+    Deprecated(String),
+    #[error("Use of deprecated global `{0}`, use `{1}` instead")]
+    DeprecatedWithReplacement(String, String),
+    #[error("Top-level definition of `{0}` shadows a global")]
+    Shadowed(String),
+}
+
+impl LintWarning for GlobalsWarning {
+    fn severity(&self) -> EvalSeverity {
+        EvalSeverity::Warning
+    }
+
+    fn short_name(&self) -> &'static str {
+        match self {
+            Self::Deprecated(..) | Self::DeprecatedWithReplacement(..) => "deprecated-global",
+            Self::Shadowed(..) => "shadowed-global",
+        }
+    }
+}
+
+/// All the names bound anywhere in the module, in any scope.
+fn bound_names(module: &AstModule) -> HashSet<&str> {
+    fn params<'a>(xs: &'a [AstParameter], res: &mut HashSet<&'a str>) {
+        for x in xs {
+            if let (Some(name), _, _) = x.split() {
+                res.insert(&name.ident);
+            }
+        }
+    }
+
+    fn expr<'a>(x: &'a AstExpr, res: &mut HashSet<&'a str>) {
+        match &**x {
+            Expr::Lambda(lambda) => params(&lambda.params, res),
+            Expr::ListComprehension(_, for_, clauses)
+            | Expr::DictComprehension(_, for_, clauses) => {
+                for_.var.visit_lvalue(|x| {
+                    res.insert(&x.ident);
+                });
+                for clause in clauses {
+                    if let Clause::For(for_) = clause {
+                        for_.var.visit_lvalue(|x| {
+                            res.insert(&x.ident);
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+        x.visit_expr(|x| expr(x, res));
+    }
+
+    fn stmt<'a>(x: &'a AstStmt, res: &mut HashSet<&'a str>) {
+        visit_bindings(x, |x| {
+            res.insert(&x.ident);
+        });
+        if let Stmt::Def(def) = &**x {
+            params(&def.params, res);
+        }
+        x.visit_children(|x| match x {
+            Visit::Stmt(x) => stmt(x, res),
+            Visit::Expr(x) => expr(x, res),
+        });
+    }
+
+    let mut res = HashSet::new();
+    stmt(module.statement(), &mut res);
+    res
+}
+
+/// Visit the names bound by this statement in the scope it is in (not in nested `def`s).
+fn visit_bindings<'a>(x: &'a AstStmt, mut f: impl FnMut(&'a AstAssignIdent)) {
+    match &**x {
+        Stmt::Assign(assign) => assign.lhs.visit_lvalue(f),
+        Stmt::AssignModify(lhs, _, _) => lhs.visit_lvalue(f),
+        Stmt::For(for_) => for_.var.visit_lvalue(f),
+        Stmt::Def(def) => f(&def.name),
+        Stmt::Load(load) => load.args.iter().for_each(|x| f(&x.local)),
+        _ => {}
+    }
+}
+
+/// Identifiers which must refer to one of the `deprecated` globals.
+///
+/// Anything bound anywhere in the module is skipped, even if the occurrence is in an unrelated
+/// scope, so we never rename something which isn't the global.
+fn deprecated_uses<'a>(
+    module: &AstModule,
+    deprecated: &'a HashMap<String, Option<String>>,
+) -> Vec<(Span, &'a str, Option<&'a str>)> {
+    let bound = bound_names(module);
+    let mut res = Vec::new();
+    module
+        .statement()
+        .visit_ident(|x| {
+            if !bound.contains(x.node.ident.as_str()) {
+                if let Some((name, replacement)) = deprecated.get_key_value(&x.node.ident) {
+                    res.push((x.span, name.as_str(), replacement.as_deref()));
+                }
+            }
+            Ok::<(), ()>(())
+        })
+        .unwrap();
+    res
+}
+
+/// The names bound directly in the scope of `x` (not in nested `def`s).
+fn scope_bindings<'a>(x: &'a AstStmt, res: &mut HashSet<&'a str>) {
+    visit_bindings(x, |x| {
+        res.insert(&x.ident);
+    });
+    if !matches!(&**x, Stmt::Def(_)) {
+        x.visit_stmt(|x| scope_bindings(x, res));
+    }
+}
+
+/// The names of the parameters of a `def` or `lambda`.
+fn param_names(xs: &[AstParameter]) -> HashSet<&str> {
+    xs.iter()
+        .filter_map(|x| x.split().0.map(|name| name.ident.as_str()))
+        .collect()
+}
+
+/// The uses in `module` of the globals in `names`, which are either names of globals (e.g.
+/// `glob`) or attributes of globals (e.g. `native.glob`).
+///
+/// Scopes are resolved like the compiler does: an identifier is only a global if no enclosing
+/// scope (the module, a `def`, a `lambda` or a comprehension) binds it, anywhere in that scope.
+pub fn global_uses(module: &AstModule, names: &HashSet<String>) -> Vec<(FileSpan, String)> {
+    /// The names bound by each scope enclosing an expression, innermost last.
+    struct Scopes<'a>(Vec<HashSet<&'a str>>);
+
+    impl Scopes<'_> {
+        fn is_global(&self, name: &str) -> bool {
+            !self.0.iter().any(|scope| scope.contains(name))
+        }
+    }
+
+    fn global<'a>(x: &AstExpr, scopes: &Scopes, names: &'a HashSet<String>) -> Option<&'a str> {
+        let name = match &**x {
+            Expr::Identifier(ident) if scopes.is_global(&ident.node.ident) => {
+                names.get(&ident.node.ident)
+            }
+            Expr::Dot(object, attribute) => match &***object {
+                Expr::Identifier(ident) if scopes.is_global(&ident.node.ident) => {
+                    names.get(&format!("{}.{}", ident.node.ident, attribute.node))
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+        name.map(|name| name.as_str())
+    }
+
+    fn expr<'a, 'b>(
+        x: &'b AstExpr,
+        scopes: &mut Scopes<'b>,
+        names: &'a HashSet<String>,
+        res: &mut Vec<(Span, &'a str)>,
+    ) {
+        if let Some(name) = global(x, scopes, names) {
+            res.push((x.span, name));
+        }
+        match &**x {
+            Expr::Lambda(lambda) => {
+                // Default values and types are evaluated in the enclosing scope.
+                for param in &lambda.params {
+                    param.visit_expr(|x| expr(x, scopes, names, res));
+                }
+                scopes.0.push(param_names(&lambda.params));
+                expr(&lambda.body, scopes, names, res);
+                scopes.0.pop();
+            }
+            Expr::ListComprehension(_, for_, clauses)
+            | Expr::DictComprehension(_, for_, clauses) => {
+                // The first iterable is evaluated in the enclosing scope, the rest of the
+                // comprehension in its own scope, which binds all its loop variables.
+                expr(&for_.over, scopes, names, res);
+                let mut scope = HashSet::new();
+                for var in
+                    std::iter::once(&for_.var).chain(clauses.iter().filter_map(|c| match c {
+                        Clause::For(for_) => Some(&for_.var),
+                        Clause::If(_) => None,
+                    }))
+                {
+                    var.visit_lvalue(|x| {
+                        scope.insert(x.ident.as_str());
+                    });
+                }
+                scopes.0.push(scope);
+                for_.var.visit_expr(|x| expr(x, scopes, names, res));
+                for clause in clauses {
+                    clause.visit_expr(|x| expr(x, scopes, names, res));
+                }
+                match &**x {
+                    Expr::ListComprehension(item, _, _) => expr(item, scopes, names, res),
+                    Expr::DictComprehension(k_v, _, _) => {
+                        expr(&k_v.0, scopes, names, res);
+                        expr(&k_v.1, scopes, names, res);
+                    }
+                    _ => unreachable!(),
+                }
+                scopes.0.pop();
+            }
+            _ => x.visit_expr(|x| expr(x, scopes, names, res)),
+        }
+    }
+
+    fn stmt<'a, 'b>(
+        x: &'b AstStmt,
+        scopes: &mut Scopes<'b>,
+        names: &'a HashSet<String>,
+        res: &mut Vec<(Span, &'a str)>,
+    ) {
+        match &**x {
+            Stmt::Def(def) => {
+                // Default values and types are evaluated in the enclosing scope.
+                for param in &def.params {
+                    param.visit_expr(|x| expr(x, scopes, names, res));
+                }
+                if let Some(return_type) = &def.return_type {
+                    return_type.visit_expr(|x| expr(x, scopes, names, res));
+                }
+                let mut scope = param_names(&def.params);
+                scope_bindings(&def.body, &mut scope);
+                scopes.0.push(scope);
+                stmt(&def.body, scopes, names, res);
+                scopes.0.pop();
+            }
+            _ => x.visit_children(|x| match x {
+                Visit::Stmt(x) => stmt(x, scopes, names, res),
+                Visit::Expr(x) => expr(x, scopes, names, res),
+            }),
+        }
+    }
+
+    let mut module_scope = HashSet::new();
+    scope_bindings(module.statement(), &mut module_scope);
+    let mut scopes = Scopes(vec![module_scope]);
+    let mut res = Vec::new();
+    stmt(module.statement(), &mut scopes, names, &mut res);
+    res.into_iter()
+        .map(|(span, name)| (module.file_span(span), name.to_owned()))
+        .collect()
+}
+
+fn deprecated(
+    module: &AstModule,
+    deprecated: &HashMap<String, Option<String>>,
+    res: &mut Vec<LintT<GlobalsWarning>>,
+) {
+    for (span, name, replacement) in deprecated_uses(module, deprecated) {
+        let warning = match replacement {
+            None => GlobalsWarning::Deprecated(name.to_owned()),
+            Some(replacement) => {
+                GlobalsWarning::DeprecatedWithReplacement(name.to_owned(), replacement.to_owned())
+            }
+        };
+        res.push(LintT::new(module.codemap(), span, warning));
+    }
+}
+
+fn shadowed(module: &AstModule, globals: &HashSet<String>, res: &mut Vec<LintT<GlobalsWarning>>) {
+    fn stmt<'a>(x: &'a AstStmt, names: &mut Vec<&'a AstAssignIdent>) {
+        visit_bindings(x, |x| names.push(x));
+        // Don't descend into `def`, their locals don't shadow anything at the top level.
+        if !matches!(&**x, Stmt::Def(_)) {
+            x.visit_stmt(|x| stmt(x, names));
+        }
+    }
+
+    let mut names = Vec::new();
+    stmt(module.statement(), &mut names);
+    for name in names {
+        if globals.contains(&name.ident) {
+            res.push(LintT::new(
+                module.codemap(),
+                name.span,
+                GlobalsWarning::Shadowed(name.ident.clone()),
+            ));
+        }
+    }
+}
+
+pub(crate) fn lint(
+    module: &AstModule,
+    globals: Option<&HashSet<String>>,
+    deprecated_globals: &HashMap<String, Option<String>>,
+) -> Vec<LintT<GlobalsWarning>> {
+    let mut res = Vec::new();
+    deprecated(module, deprecated_globals, &mut res);
+    if let Some(globals) = globals {
+        shadowed(module, globals, &mut res);
+    }
+    res
+}
+
+/// Parse the program with `dialect`, and rename the uses of `deprecated` globals which have a
+/// replacement.
+///
+/// Return `None` if there is nothing to rename.
+pub fn replace_deprecated_globals(
+    name: &str,
+    program: &str,
+    dialect: &Dialect,
+    deprecated: &HashMap<String, Option<String>>,
+) -> crate::Result<Option<String>> {
+    let module = AstModule::parse(name, program.to_owned(), dialect)?;
+    let mut uses: Vec<_> = deprecated_uses(&module, deprecated)
+        .into_iter()
+        .filter_map(|(span, _, replacement)| Some((span, replacement?)))
+        .collect();
+    if uses.is_empty() {
+        return Ok(None);
+    }
+    uses.sort_by_key(|(span, _)| span.begin());
+
+    let codemap = module.codemap();
+    let mut out = String::new();
+    let mut pos = codemap.full_span().begin();
+    for (span, replacement) in uses {
+        out.push_str(codemap.source_span(Span::new(pos, span.begin())));
+        out.push_str(replacement);
+        pos = span.end();
+    }
+    out.push_str(codemap.source_span(Span::new(pos, codemap.full_span().end())));
+    Ok(Some(out))
+}
+
+#[cfg(test)]
+mod tests {
+    use starlark_syntax::slice_vec_ext::SliceExt;
+
+    use super::*;
+
+    fn module(x: &str) -> AstModule {
+        AstModule::parse("X", x.to_owned(), &Dialect::Extended).unwrap()
+    }
+
+    fn deprecated_list() -> HashMap<String, Option<String>> {
+        HashMap::from([
+            ("old".to_owned(), Some("new".to_owned())),
+            ("gone".to_owned(), None),
+        ])
+    }
+
+    #[test]
+    fn test_lint_deprecated() {
+        let m = module(
+            r#"
+x = old(gone)
+def f(y):
+    return [z for z in y]
+"#,
+        );
+        let res = lint(&m, None, &deprecated_list());
+        assert_eq!(
+            res.map(|x| x.problem.to_string()),
+            &[
+                "Use of deprecated global `old`, use `new` instead",
+                "Use of deprecated global `gone`",
+            ]
+        );
+
+        // Anything defined in the module is not the global.
+        let m = module(
+            r#"
+def f(old):
+    return old
+y = [gone for gone in []]
+"#,
+        );
+        assert!(lint(&m, None, &deprecated_list()).is_empty());
+    }
+
+    #[test]
+    fn test_lint_shadowed() {
+        let m = module(
+            r#"
+load("foo", "glob")
+def len(x):
+    native = 1
+    return native
+other = 1
+"#,
+        );
+        let globals = HashSet::from(["glob".to_owned(), "len".to_owned(), "native".to_owned()]);
+        let res = lint(&m, Some(&globals), &HashMap::new());
+        assert_eq!(
+            res.map(|x| x.problem.to_string()),
+            &[
+                "Top-level definition of `glob` shadows a global",
+                "Top-level definition of `len` shadows a global",
+            ]
+        );
+        assert!(lint(&m, None, &HashMap::new()).is_empty());
+    }
+
+    #[test]
+    fn test_global_uses() {
+        let m = module(
+            r#"
+x = native.glob(["*"]) + glob([])
+def f(glob):
+    return glob + native.other + len(native)
+y = native
+"#,
+        );
+        let names = HashSet::from(["glob".to_owned(), "native.glob".to_owned()]);
+        let res = global_uses(&m, &names);
+        // `glob` is only bound as a parameter of `f`, so it is the global outside of `f`.
+        assert_eq!(
+            res.map(|(span, name)| (span.resolve_span().begin.line, name.as_str())),
+            &[(1, "native.glob"), (1, "glob")]
+        );
+
+        let names = HashSet::from(["native".to_owned(), "len".to_owned()]);
+        assert_eq!(
+            global_uses(&m, &names).map(|(_, name)| name.as_str()),
+            &["native", "native", "len", "native", "native"]
+        );
+    }
+
+    #[test]
+    fn test_global_uses_scopes() {
+        let m = module(
+            r#"
+def f():
+    x = glob
+    glob = 1
+    return x
+g = lambda glob, y = glob: glob
+z = [glob for glob in glob]
+w = {k: glob for k in []}
+def h(glob = glob):
+    return [native for native in []]
+"#,
+        );
+        let names = HashSet::from(["glob".to_owned(), "native".to_owned()]);
+        assert_eq!(
+            global_uses(&m, &names)
+                .map(|(span, name)| (span.resolve_span().begin.line, name.as_str())),
+            // The default values and the first iterable are in the enclosing scope.
+            &[(5, "glob"), (6, "glob"), (7, "glob"), (8, "glob")]
+        );
+    }
+
+    #[test]
+    fn test_replace_deprecated_globals() {
+        let program = "x = old(1)\ny = old + gone\n";
+        assert_eq!(
+            Some("x = new(1)\ny = new + gone\n"),
+            replace_deprecated_globals("X", program, &Dialect::Extended, &deprecated_list())
+                .unwrap()
+                .as_deref()
+        );
+        assert_eq!(
+            None,
+            replace_deprecated_globals("X", "x = gone\n", &Dialect::Extended, &deprecated_list())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_replace_deprecated_globals_uses_dialect() {
+        let program = "def f(x: int):\n    return old\n";
+        assert_eq!(
+            Some("def f(x: int):\n    return new\n"),
+            replace_deprecated_globals("X", program, &Dialect::Extended, &deprecated_list())
+                .unwrap()
+                .as_deref()
+        );
+        // Type annotations are not part of the standard dialect.
+        assert!(
+            replace_deprecated_globals("X", program, &Dialect::Standard, &deprecated_list())
+                .is_err()
+        );
+    }
+}
@@ -17,8 +17,11 @@
 
 //! Linter.
 
+use std::collections::HashMap;
 use std::collections::HashSet;
 
+pub use globals::global_uses;
+pub use globals::replace_deprecated_globals;
 pub use lint_message::LintMessage;
 pub use types::EvalMessage;
 pub use types::EvalSeverity;
@@ -31,6 +34,7 @@ use crate::syntax::AstModule;
 mod dubious;
 pub mod find_call_name;
 mod flow;
+mod globals;
 mod incompatible;
 mod lint_message;
 mod names;
@@ -45,6 +49,15 @@ pub trait AstModuleLint {
     /// they can be passed as the `globals` argument, resulting in name-resolution lint errors.
     /// The precise checks run by the linter are not considered stable between versions.
     fn lint(&self, globals: Option<&HashSet<String>>) -> Vec<Lint>;
+
+    /// Run the lints which depend on what the globals mean: uses of `deprecated` globals
+    /// (mapped to their replacement, if there is one) and, if the complete set of `globals`
+    /// is known, top-level definitions which shadow one of them.
+    fn lint_globals(
+        &self,
+        globals: Option<&HashSet<String>>,
+        deprecated: &HashMap<String, Option<String>>,
+    ) -> Vec<Lint>;
 }
 
 impl AstModuleLint for AstModule {
@@ -58,4 +71,15 @@ impl AstModuleLint for AstModule {
         res.extend(performance::lint(self).into_iter().map(LintT::erase));
         res
     }
+
+    fn lint_globals(
+        &self,
+        globals: Option<&HashSet<String>>,
+        deprecated: &HashMap<String, Option<String>>,
+    ) -> Vec<Lint> {
+        globals::lint(self, globals, deprecated)
+            .into_iter()
+            .map(LintT::erase)
+            .collect()
+    }
 }
@@ -68,12 +68,13 @@ fn has_unused_marker_in_range(span: FileSpanRef) -> bool {
     false
 }
 
-/// Parse the module and find unused loads.
+/// Parse the module with `dialect` and find unused loads.
 pub(crate) fn find_unused_loads(
     name: &str,
     program: &str,
+    dialect: &Dialect,
 ) -> crate::Result<(CodeMap, Vec<UnusedLoad>)> {
-    let module = AstModule::parse(name, program.to_owned(), &Dialect::Extended)?;
+    let module = AstModule::parse(name, program.to_owned(), dialect)?;
     let names = MutableNames::new();
     let heap = FrozenHeap::new();
     let (codemap, statement, dialect, ..) = module.into_parts();
@@ -128,7 +129,6 @@ pub(crate) fn find_unused_loads(
 
     for top in top_level_stmts(&module_scopes.cst) {
         top.visit_ident(|ident| {
-            println!("visit ident: {:?}", ident);
             let ResolvedIdent::Slot(Slot::Module(_), binding_id) = ident
                 .payload
                 .context("ident is not resolved (internal error)")?
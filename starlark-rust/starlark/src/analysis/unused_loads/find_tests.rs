@@ -23,6 +23,7 @@ use starlark_syntax::golden_test_template::golden_test_template;
 use starlark_syntax::span_display::span_display;
 
 use crate::analysis::unused_loads::find::find_unused_loads;
+use crate::syntax::Dialect;
 
 fn test_unused_loads(name: &str, program: &str) {
     let program = program.trim();
@@ -31,7 +32,7 @@ fn test_unused_loads(name: &str, program: &str) {
     writeln!(out, "{}", program).unwrap();
     writeln!(out).unwrap();
 
-    let (codemap, unused_loads) = find_unused_loads(name, program).unwrap();
+    let (codemap, unused_loads) = find_unused_loads(name, program, &Dialect::Extended).unwrap();
     if unused_loads.is_empty() {
         writeln!(out, "No unused loads").unwrap();
     } else {
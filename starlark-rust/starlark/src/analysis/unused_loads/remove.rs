@@ -20,6 +20,7 @@ use starlark_syntax::codemap::Pos;
 use starlark_syntax::codemap::Span;
 
 use crate::analysis::unused_loads::find::find_unused_loads;
+use crate::syntax::Dialect;
 
 struct Out<'a> {
     codemap: &'a CodeMap,
@@ -49,9 +50,15 @@ impl<'a> Out<'a> {
     }
 }
 
+/// Parse the program with `dialect`, and remove the unused loads.
+///
 /// Return `None` if there is no unused loads.
-pub fn remove_unused_loads(name: &str, program: &str) -> crate::Result<Option<String>> {
-    let (codemap, unused_loads) = find_unused_loads(name, program)?;
+pub fn remove_unused_loads(
+    name: &str,
+    program: &str,
+    dialect: &Dialect,
+) -> crate::Result<Option<String>> {
+    let (codemap, unused_loads) = find_unused_loads(name, program, dialect)?;
     if unused_loads.is_empty() {
         return Ok(None);
     }
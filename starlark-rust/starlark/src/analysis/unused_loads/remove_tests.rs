@@ -22,6 +22,7 @@ use std::fmt::Write;
 use starlark_syntax::golden_test_template::golden_test_template;
 
 use crate::analysis::unused_loads::remove::remove_unused_loads;
+use crate::syntax::Dialect;
 
 fn test_remove(name: &str, program: &str) {
     let program = program.trim();
@@ -32,7 +33,7 @@ fn test_remove(name: &str, program: &str) {
     writeln!(out, "{}", program).unwrap();
     writeln!(out).unwrap();
 
-    let removed = remove_unused_loads(name, program).unwrap();
+    let removed = remove_unused_loads(name, program, &Dialect::Extended).unwrap();
     match removed {
         None => writeln!(out, "No unused loads").unwrap(),
         Some(removed) => {
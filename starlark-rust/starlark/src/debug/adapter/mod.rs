@@ -25,6 +25,7 @@ use std::fmt::Display;
 use debugserver_types::*;
 use dupe::Dupe;
 
+use crate::any::ProvidesStaticType;
 use crate::codemap::FileSpan;
 use crate::eval::Evaluator;
 use crate::syntax::AstModule;
@@ -41,6 +42,23 @@ pub trait DapAdapterClient: Debug + Send + Sync + 'static {
     fn event_stopped(&self);
 }
 
+/// Values can provide this trait (from [`StarlarkValue::provide`](crate::values::StarlarkValue::provide))
+/// to choose what the debugger shows when they are expanded, instead of their attributes.
+///
+/// This is useful for values whose contents are only reachable by indexing, or through methods.
+pub trait DebugChildren<'v> {
+    /// Named children of the value, in display order.
+    fn debug_children(&self) -> Vec<(String, Value<'v>)>;
+}
+
+unsafe impl<'v> ProvidesStaticType<'v> for &'v dyn DebugChildren<'v> {
+    type StaticType = &'static dyn DebugChildren<'static>;
+}
+
+fn debug_children<'v>(v: Value<'v>) -> Option<Vec<(String, Value<'v>)>> {
+    Some(v.request_value::<&dyn DebugChildren>()?.debug_children())
+}
+
 /// Information about the variables scopes
 pub struct ScopesInfo {
     /// Number of local variables.
@@ -142,7 +160,14 @@ impl PathSegment {
     fn get<'v>(&self, v: &Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>> {
         match self {
             PathSegment::Index(i) => v.at(heap.alloc(*i), heap).map_err(Into::into),
-            PathSegment::Attr(key) => v.get_attr_error(key.as_str(), heap),
+            PathSegment::Attr(key) => match debug_children(*v) {
+                Some(children) => children
+                    .into_iter()
+                    .find(|(name, _)| name == key)
+                    .map(|(_, child)| child)
+                    .ok_or_else(|| anyhow::anyhow!("No child `{}` in value", key).into()),
+                None => v.get_attr_error(key.as_str(), heap),
+            },
             PathSegment::Key(i) => v.at(heap.alloc(i.to_owned()), heap).map_err(Into::into),
         }
     }
@@ -185,8 +210,11 @@ impl Variable {
     }
 
     fn struct_like_value_as_str<'v>(v: Value<'v>) -> String {
-        let attrs = v.dir_attr();
-        format!("<type:{}, size={}>", v.get_type(), attrs.len())
+        let size = match debug_children(v) {
+            Some(children) => children.len(),
+            None => v.dir_attr().len(),
+        };
+        format!("<type:{}, size={}>", v.get_type(), size)
     }
 
     pub(crate) fn truncate_string(mut str_value: String, mut max_len: usize) -> String {
@@ -325,6 +353,14 @@ impl InspectVariableInfo {
 
     /// Trying to create InspectVariableInfo from a given starlark value
     pub fn try_from_value<'v>(v: Value<'v>, heap: &'v Heap) -> crate::Result<Self> {
+        if let Some(children) = debug_children(v) {
+            return Ok(Self {
+                sub_values: children
+                    .into_iter()
+                    .map(|(name, child)| Variable::from_value(PathSegment::Attr(name), child))
+                    .collect(),
+            });
+        }
         match v.get_type() {
             "dict" => Self::try_from_dict(
                 DictRef::from_value(v).ok_or(anyhow::Error::msg("not a dictionary"))?,
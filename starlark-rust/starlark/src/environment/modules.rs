@@ -50,6 +50,7 @@ use crate::eval::ProfileData;
 use crate::values::layout::heap::heap_type::HeapKind;
 use crate::values::layout::heap::profile::aggregated::AggregateHeapProfileInfo;
 use crate::values::layout::heap::profile::aggregated::RetainedHeapProfile;
+use crate::values::string::SharedFrozenStrings;
 use crate::values::Freeze;
 use crate::values::Freezer;
 use crate::values::FrozenHeap;
@@ -213,6 +214,20 @@ impl FrozenModule {
         self.module.names()
     }
 
+    /// Iterate through all the names defined in this module with their values, including the
+    /// private ones (the symbols loaded from other modules).
+    /// Returned values are owned by this module.
+    pub fn all_names_and_values(
+        &self,
+    ) -> impl Iterator<Item = (FrozenStringValue, FrozenValue)> + '_ {
+        self.module.all_items()
+    }
+
+    /// The docstring of the module, if it has one.
+    pub fn docstring(&self) -> Option<&str> {
+        self.module.docstring.as_deref()
+    }
+
     /// Obtain the [`FrozenHeapRef`] which owns the storage of all values defined in this module.
     pub fn frozen_heap(&self) -> &FrozenHeapRef {
         &self.heap
@@ -408,6 +423,22 @@ impl Module {
 
     /// Freeze the environment, all its value will become immutable afterwards.
     pub fn freeze(self) -> anyhow::Result<FrozenModule> {
+        self.freeze_impl(None)
+    }
+
+    /// Freeze the environment like [`freeze`](Module::freeze), except that strings are frozen to
+    /// `shared_strings` when possible, so that modules freezing equal strings store them once.
+    pub fn freeze_sharing_strings(
+        self,
+        shared_strings: &'static SharedFrozenStrings,
+    ) -> anyhow::Result<FrozenModule> {
+        self.freeze_impl(Some(shared_strings))
+    }
+
+    fn freeze_impl(
+        self,
+        shared_strings: Option<&'static SharedFrozenStrings>,
+    ) -> anyhow::Result<FrozenModule> {
         let Module {
             names,
             slots,
@@ -423,7 +454,8 @@ impl Module {
         // Note that we even freeze anonymous slots, since they are accessed by
         // slot-index in the code, and we don't walk into them, so don't know if
         // they are used.
-        let freezer = Freezer::new(frozen_heap);
+        let mut freezer = Freezer::new(frozen_heap);
+        freezer.shared_strings = shared_strings;
         let slots = slots.freeze(&freezer)?;
         let extra_value = extra_value.into_inner().freeze(&freezer)?;
         let stacks = if let Some(mode) = heap_profile_on_freeze.get() {
@@ -487,7 +519,7 @@ impl Module {
 
     /// Set the value of a variable in the environment. Set its visibliity to
     /// "private" to ensure that it is not re-exported
-    pub(crate) fn set_private<'v>(&'v self, name: FrozenStringValue, value: Value<'v>) {
+    pub fn set_private<'v>(&'v self, name: FrozenStringValue, value: Value<'v>) {
         let slot = self.names.add_name_visibility(name, Visibility::Private);
         let slots = self.slots();
         slots.ensure_slot(slot);
@@ -522,7 +554,9 @@ impl Module {
         }
     }
 
-    pub(crate) fn set_docstring(&self, docstring: String) {
+    /// Set the docstring of the module, which is otherwise set by the evaluation of a string
+    /// literal at the start of the module.
+    pub fn set_docstring(&self, docstring: String) {
         self.docstring.replace(Some(docstring));
     }
 
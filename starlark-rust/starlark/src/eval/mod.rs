@@ -29,6 +29,7 @@ use std::time::Instant;
 use dupe::Dupe;
 pub use runtime::arguments::Arguments;
 pub use runtime::before_stmt::BeforeStmtFuncDyn;
+pub use runtime::evaluator::CallStackProbe;
 pub use runtime::evaluator::Evaluator;
 pub use runtime::file_loader::FileLoader;
 pub use runtime::file_loader::ReturnFileLoader;
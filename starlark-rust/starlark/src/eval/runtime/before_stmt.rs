@@ -29,6 +29,25 @@ pub(crate) struct BeforeStmt<'a> {
     /// even if no `before_stmt` functions are registered.
     /// This is needed when compiling dependencies of a file to be profiled.
     pub(crate) instrument: bool,
+    /// Called every few statements, and fails the evaluation if it returns an error.
+    pub(crate) check: Option<StmtCheck<'a>>,
+}
+
+pub(crate) struct StmtCheck<'a> {
+    pub(crate) every: u64,
+    pub(crate) stmts: u64,
+    pub(crate) check: &'a dyn Fn() -> anyhow::Result<()>,
+}
+
+impl<'a> StmtCheck<'a> {
+    pub(crate) fn before_stmt(&mut self) -> crate::Result<()> {
+        self.stmts += 1;
+        if self.stmts % self.every == 0 {
+            (self.check)().map_err(crate::Error::from)
+        } else {
+            Ok(())
+        }
+    }
 }
 
 /// This is used by DAP, and it is not public API.
@@ -60,7 +79,7 @@ pub trait BeforeStmtFuncDyn<'a> {
 
 impl<'a> BeforeStmt<'a> {
     pub(crate) fn enabled(&self) -> bool {
-        self.instrument || !self.before_stmt.is_empty()
+        self.instrument || !self.before_stmt.is_empty() || self.check.is_some()
     }
 }
 
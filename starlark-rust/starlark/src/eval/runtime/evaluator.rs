@@ -47,6 +47,7 @@ use crate::eval::compiler::def::DefInfo;
 use crate::eval::compiler::def::FrozenDef;
 use crate::eval::runtime::before_stmt::BeforeStmt;
 use crate::eval::runtime::before_stmt::BeforeStmtFunc;
+use crate::eval::runtime::before_stmt::StmtCheck;
 use crate::eval::runtime::cheap_call_stack::CheapCallStack;
 use crate::eval::runtime::frame_span::FrameSpan;
 use crate::eval::runtime::inlined_frame::InlinedFrames;
@@ -65,6 +66,7 @@ use crate::eval::runtime::slots::LocalCapturedSlotId;
 use crate::eval::runtime::slots::LocalSlotId;
 use crate::eval::CallStack;
 use crate::eval::FileLoader;
+use crate::hint::unlikely;
 use crate::stdlib::breakpoint::BreakpointConsole;
 use crate::stdlib::breakpoint::RealBreakpointConsole;
 use crate::stdlib::extra::PrintHandler;
@@ -105,6 +107,14 @@ enum EvaluatorError {
     CallstackSizeAlreadySet,
     #[error("Max callstack size cannot be zero")]
     ZeroCallstackSize,
+    #[error("Call check is already set")]
+    CallCheckAlreadySet,
+    #[error("Call check interval cannot be zero")]
+    ZeroCallCheckInterval,
+    #[error("Statement check is already set")]
+    StmtCheckAlreadySet,
+    #[error("Statement check interval cannot be zero")]
+    ZeroStmtCheckInterval,
 }
 
 /// Number of bytes to allocate between GC's.
@@ -164,11 +174,32 @@ pub struct Evaluator<'v, 'a> {
     pub(crate) print_handler: &'a (dyn PrintHandler + 'a),
     /// Max size of starlark stack
     pub(crate) max_callstack_size: Option<usize>,
+    /// Called every few function calls, see [`set_call_check`](Evaluator::set_call_check).
+    call_check: Option<CallCheck<'a>>,
+    /// See [`set_call_stack_probe`](Evaluator::set_call_stack_probe).
+    call_stack_probe: Option<Box<dyn CallStackProbe + 'a>>,
     // The Starlark-level call-stack of functions.
     // Must go last because it's quite a big structure
     pub(crate) call_stack: CheapCallStack<'v>,
 }
 
+struct CallCheck<'a> {
+    every: u64,
+    calls: u64,
+    check: &'a dyn Fn(u64, &Heap) -> anyhow::Result<()>,
+}
+
+/// Lets another thread capture the call stack of a running evaluation, see
+/// [`set_call_stack_probe`](Evaluator::set_call_stack_probe).
+pub trait CallStackProbe {
+    /// Whether the call stack should be recorded. Called on every function call, so it must be
+    /// cheap, e.g. an atomic load.
+    fn requested(&self) -> bool;
+
+    /// Called with the call stack when a function is called while `requested` returns true.
+    fn record(&self, call_stack: CallStack);
+}
+
 /// Just holds things that require using EvaluationCallbacksEnabled so that we can cache whether that needs to be enabled or not.
 struct EvaluationInstrumentation<'a> {
     // Bytecode profile.
@@ -236,6 +267,8 @@ impl<'v, 'a> Evaluator<'v, 'a> {
             verbose_gc: false,
             static_typechecking: false,
             max_callstack_size: None,
+            call_check: None,
+            call_stack_probe: None,
         }
     }
 
@@ -454,11 +487,32 @@ impl<'v, 'a> Evaluator<'v, 'a> {
 
         self.call_stack.push(function, span)?;
         // Must always call .pop regardless
-        let res = within(self).map_err(|e| add_diagnostics(e, self));
+        let res = match self.check_calls() {
+            Ok(()) => within(self),
+            Err(e) => Err(e),
+        }
+        .map_err(|e| add_diagnostics(e, self));
         self.call_stack.pop();
         res
     }
 
+    #[inline(always)]
+    fn check_calls(&mut self) -> crate::Result<()> {
+        if let Some(probe) = &self.call_stack_probe {
+            if unlikely(probe.requested()) {
+                probe.record(self.call_stack());
+            }
+        }
+        if let Some(call_check) = &mut self.call_check {
+            call_check.calls += 1;
+            if unlikely(call_check.calls % call_check.every == 0) {
+                let (calls, check) = (call_check.calls, call_check.check);
+                return check(calls, self.heap()).map_err(crate::Error::from);
+            }
+        }
+        Ok(())
+    }
+
     /// Called to change the local variables, from the callee.
     /// Only called for user written functions.
     #[inline(always)] // There is only one caller
@@ -814,6 +868,66 @@ impl<'v, 'a> Evaluator<'v, 'a> {
         }
     }
 
+    /// Call `check` every `every` function calls (including calls of native functions), with
+    /// the number of calls so far and the heap. If it returns an error, the evaluation fails
+    /// with it, e.g. to limit the resources an evaluation may use.
+    ///
+    /// Unlike `before_stmt` instrumentation, this also applies to the functions of loaded
+    /// modules, and is cheap enough to be always enabled.
+    pub fn set_call_check(
+        &mut self,
+        every: u64,
+        check: &'a dyn Fn(u64, &Heap) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        if every == 0 {
+            return Err(EvaluatorError::ZeroCallCheckInterval.into());
+        }
+        if self.call_check.is_some() {
+            return Err(EvaluatorError::CallCheckAlreadySet.into());
+        }
+        self.call_check = Some(CallCheck {
+            every,
+            calls: 0,
+            check,
+        });
+        Ok(())
+    }
+
+    /// Record the call stack with `probe` at the next function call (including calls of native
+    /// functions) after it is requested, e.g. to show what an evaluation which appears hung is
+    /// doing. Evaluations which make no calls (e.g. in a loop, or in a native function) don't
+    /// record a call stack until they make one.
+    pub fn set_call_stack_probe(&mut self, probe: Box<dyn CallStackProbe + 'a>) {
+        self.call_stack_probe = Some(probe);
+    }
+
+    /// Call `check` every `every` statements executed. If it returns an error, the evaluation
+    /// fails with it, e.g. to stop an evaluation which has exceeded its deadline, including in
+    /// loops which call no function.
+    ///
+    /// Like `before_stmt` instrumentation, this makes evaluation slower, even if `check` is
+    /// cheap.
+    pub fn set_stmt_check(
+        &mut self,
+        every: u64,
+        check: &'a dyn Fn() -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        if every == 0 {
+            return Err(EvaluatorError::ZeroStmtCheckInterval.into());
+        }
+        if self.eval_instrumentation.before_stmt.check.is_some() {
+            return Err(EvaluatorError::StmtCheckAlreadySet.into());
+        }
+        self.eval_instrumentation.change(|v| {
+            v.before_stmt.check = Some(StmtCheck {
+                every,
+                stmts: 0,
+                check,
+            })
+        });
+        Ok(())
+    }
+
     /// Sets max call stack size.
     /// Stack allocation will happen on entry point of evaluation if not allocated yet.
     pub fn set_max_callstack_size(&mut self, stack_size: usize) -> anyhow::Result<()> {
@@ -829,14 +943,26 @@ impl<'v, 'a> Evaluator<'v, 'a> {
 }
 
 pub(crate) trait EvaluationCallbacks {
-    fn before_instr(&mut self, _eval: &mut Evaluator, _ip: BcPtrAddr, _opcode: BcOpcode);
+    fn before_instr(
+        &mut self,
+        _eval: &mut Evaluator,
+        _ip: BcPtrAddr,
+        _opcode: BcOpcode,
+    ) -> crate::Result<()>;
 }
 
 pub(crate) struct EvalCallbacksDisabled;
 
 impl EvaluationCallbacks for EvalCallbacksDisabled {
     #[inline(always)]
-    fn before_instr(&mut self, _eval: &mut Evaluator, _ip: BcPtrAddr, _opcode: BcOpcode) {}
+    fn before_instr(
+        &mut self,
+        _eval: &mut Evaluator,
+        _ip: BcPtrAddr,
+        _opcode: BcOpcode,
+    ) -> crate::Result<()> {
+        Ok(())
+    }
 }
 
 pub(crate) struct EvalCallbacksEnabled<'a> {
@@ -847,23 +973,30 @@ pub(crate) struct EvalCallbacksEnabled<'a> {
 }
 
 impl<'a> EvalCallbacksEnabled<'a> {
-    fn before_stmt(&mut self, eval: &mut Evaluator, ip: BcPtrAddr) {
+    fn before_stmt(&mut self, eval: &mut Evaluator, ip: BcPtrAddr) -> crate::Result<()> {
         let offset = ip.offset_from(self.bc_start_ptr);
         if let Some(loc) = self.stmt_locs.stmt_at(offset) {
-            before_stmt(loc.span, eval);
+            before_stmt(loc.span, eval)?;
         }
+        Ok(())
     }
 }
 
 impl<'a> EvaluationCallbacks for EvalCallbacksEnabled<'a> {
     #[inline(always)]
-    fn before_instr(&mut self, eval: &mut Evaluator, ip: BcPtrAddr, opcode: BcOpcode) {
+    fn before_instr(
+        &mut self,
+        eval: &mut Evaluator,
+        ip: BcPtrAddr,
+        opcode: BcOpcode,
+    ) -> crate::Result<()> {
         if self.bc_profile {
             eval.eval_instrumentation.bc_profile.before_instr(opcode)
         }
         if self.before_stmt {
-            self.before_stmt(eval, ip);
+            self.before_stmt(eval, ip)?;
         }
+        Ok(())
     }
 }
 
@@ -871,7 +1004,7 @@ impl<'a> EvaluationCallbacks for EvalCallbacksEnabled<'a> {
 // The purposes are GC, profiling and debugging.
 //
 // This function is called only if `before_stmt` is set before compilation start.
-pub(crate) fn before_stmt(span: FrameSpan, eval: &mut Evaluator) {
+pub(crate) fn before_stmt(span: FrameSpan, eval: &mut Evaluator) -> crate::Result<()> {
     assert!(
         eval.eval_instrumentation.before_stmt.enabled(),
         "this code should only be called if `before_stmt` is set"
@@ -885,4 +1018,8 @@ pub(crate) fn before_stmt(span: FrameSpan, eval: &mut Evaluator) {
         added.is_empty(),
         "`before_stmt` cannot be modified during evaluation"
     );
+    match &mut eval.eval_instrumentation.before_stmt.check {
+        Some(check) => check.before_stmt(),
+        None => Ok(()),
+    }
 }
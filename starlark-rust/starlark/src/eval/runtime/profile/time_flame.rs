@@ -25,6 +25,8 @@ use starlark_map::StarlarkHasherBuilder;
 use starlark_syntax::slice_vec_ext::SliceExt;
 
 use crate as starlark;
+use crate::eval::compiler::def::Def;
+use crate::eval::compiler::def::FrozenDef;
 use crate::eval::runtime::profile::data::ProfileData;
 use crate::eval::runtime::profile::data::ProfileDataImpl;
 use crate::eval::runtime::profile::flamegraph::FlameGraphData;
@@ -252,8 +254,8 @@ impl<'v> TimeFlameProfile<'v> {
         // Need to write out lines which look like:
         // root;calls1;calls2 1
         // All the numbers at the end must be whole numbers (we use milliseconds)
-        let mutable_names = x.index.mutable_values.map(|x| x.to_repr());
-        let frozen_names = x.index.frozen_values.map(|x| x.to_value().to_repr());
+        let mutable_names = x.index.mutable_values.map(|x| frame_name(*x));
+        let frozen_names = x.index.frozen_values.map(|x| frame_name(x.to_value()));
         ProfileData {
             profile_mode: ProfileMode::TimeFlame,
             profile: ProfileDataImpl::TimeFlameProfile(
@@ -263,6 +265,21 @@ impl<'v> TimeFlameProfile<'v> {
     }
 }
 
+/// Name of a function in the flame graph.
+///
+/// Functions defined in Starlark are named `file:function`, so that profiles merged
+/// across many modules can be attributed to the file the hot function lives in.
+fn frame_name(function: Value) -> String {
+    let def_info = match function.downcast_ref::<Def>() {
+        Some(def) => Some(def.def_info),
+        None => function.downcast_ref::<FrozenDef>().map(|def| def.def_info),
+    };
+    match def_info {
+        Some(info) => format!("{}:{}", info.codemap.filename(), info.name.as_str()),
+        None => function.to_repr(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
@@ -348,5 +365,10 @@ bar()
             "Profile must contain a line `bar.*foo`: {:?}",
             profile
         );
+        assert!(
+            the_line.contains("x.star:bar"),
+            "Functions must be attributed to the file they are defined in: {:?}",
+            profile
+        );
     }
 }
@@ -0,0 +1,159 @@
+/*
+ * Copyright 2018 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::cell::Cell;
+use std::cell::RefCell;
+
+use crate::environment::Globals;
+use crate::environment::Module;
+use crate::eval::CallStack;
+use crate::eval::CallStackProbe;
+use crate::eval::Evaluator;
+use crate::syntax::AstModule;
+use crate::syntax::Dialect;
+use crate::values::Heap;
+
+fn eval_with_call_check(
+    program: &str,
+    every: u64,
+    check: &dyn Fn(u64, &Heap) -> anyhow::Result<()>,
+) -> crate::Result<()> {
+    let module = Module::new();
+    let globals = Globals::standard();
+    let mut evaluator = Evaluator::new(&module);
+    evaluator.set_call_check(every, check).unwrap();
+    let ast = AstModule::parse("a.star", program.to_owned(), &Dialect::Extended).unwrap();
+    evaluator.eval_module(ast, &globals)?;
+    Ok(())
+}
+
+#[test]
+fn call_check_counts_calls() {
+    let last = Cell::new(0);
+    let check = |calls: u64, _heap: &Heap| {
+        assert_eq!(last.get() + 1, calls);
+        last.set(calls);
+        Ok(())
+    };
+    let program = "\
+def f(x):
+  return len([x])
+for i in range(10):
+  f(i)
+";
+    eval_with_call_check(program, 1, &check).unwrap();
+    // A call of `f` and of `len` per iteration, and maybe one of `range`.
+    assert!(last.get() >= 20, "{}", last.get());
+}
+
+#[test]
+fn call_check_fails_evaluation() {
+    let check = |calls: u64, _heap: &Heap| {
+        if calls >= 100 {
+            Err(anyhow::anyhow!("Too many calls: {}", calls))
+        } else {
+            Ok(())
+        }
+    };
+    let program = "\
+def f(x):
+  return x
+for i in range(1000):
+  f(i)
+";
+    let err = eval_with_call_check(program, 10, &check).unwrap_err();
+    assert!(err.to_string().contains("Too many calls: 100"), "{}", err);
+}
+
+#[test]
+fn stmt_check_fails_loop_without_calls() {
+    let stmts = Cell::new(0);
+    let check = || {
+        stmts.set(stmts.get() + 1);
+        if stmts.get() >= 50 {
+            Err(anyhow::anyhow!("Out of time"))
+        } else {
+            Ok(())
+        }
+    };
+    let module = Module::new();
+    let globals = Globals::standard();
+    let mut evaluator = Evaluator::new(&module);
+    evaluator.set_stmt_check(10, &check).unwrap();
+    let program = "\
+x = 0
+for i in range(100000):
+  x += i
+";
+    let ast = AstModule::parse("a.star", program.to_owned(), &Dialect::Extended).unwrap();
+    let err = evaluator.eval_module(ast, &globals).unwrap_err();
+    assert!(err.to_string().contains("Out of time"), "{}", err);
+    assert_eq!(50, stmts.get());
+}
+
+#[test]
+fn stmt_check_interval() {
+    let check = || anyhow::Ok(());
+    let module = Module::new();
+    let mut evaluator = Evaluator::new(&module);
+    assert!(evaluator.set_stmt_check(0, &check).is_err());
+    evaluator.set_stmt_check(1, &check).unwrap();
+    assert!(evaluator.set_stmt_check(1, &check).is_err());
+}
+
+#[test]
+fn call_stack_probe_records_requested_stack() {
+    struct Probe<'a> {
+        requested: Cell<bool>,
+        stacks: &'a RefCell<Vec<String>>,
+    }
+
+    impl CallStackProbe for Probe<'_> {
+        fn requested(&self) -> bool {
+            self.requested.get()
+        }
+
+        fn record(&self, call_stack: CallStack) {
+            self.requested.set(false);
+            self.stacks.borrow_mut().push(call_stack.to_string());
+        }
+    }
+
+    let stacks = RefCell::new(Vec::new());
+    let module = Module::new();
+    let globals = Globals::standard();
+    let mut evaluator = Evaluator::new(&module);
+    evaluator.set_call_stack_probe(Box::new(Probe {
+        requested: Cell::new(true),
+        stacks: &stacks,
+    }));
+    let program = "\
+def inner():
+  return 1
+def outer():
+  return inner()
+outer()
+outer()
+";
+    let ast = AstModule::parse("a.star", program.to_owned(), &Dialect::Extended).unwrap();
+    evaluator.eval_module(ast, &globals).unwrap();
+    drop(evaluator);
+    // Only the first call after the request is recorded.
+    let stacks = stacks.into_inner();
+    assert_eq!(1, stacks.len(), "{:?}", stacks);
+    assert!(stacks[0].contains("outer"), "{}", stacks[0]);
+}
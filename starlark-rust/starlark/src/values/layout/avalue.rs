@@ -284,7 +284,7 @@ impl<'v> AValue<'v> for AValueImpl<Direct, StarlarkStr> {
         );
 
         let s = (*me).payload.1.as_str();
-        let fv = freezer.alloc(s);
+        let fv = freezer.alloc_str(s);
         debug_assert!(fv.is_str());
         AValueHeader::overwrite_with_forward::<Self>(me, ForwardPtr::new(fv.0.raw().ptr_value()));
         Ok(fv)
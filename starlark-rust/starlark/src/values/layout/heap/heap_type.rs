@@ -78,6 +78,7 @@ use crate::values::layout::value::FrozenValue;
 use crate::values::layout::value::Value;
 use crate::values::list::value::VALUE_EMPTY_FROZEN_LIST;
 use crate::values::string::intern::interner::FrozenStringInterner;
+use crate::values::string::SharedFrozenStrings;
 use crate::values::string::StarlarkStr;
 use crate::values::AllocFrozenValue;
 use crate::values::AllocValue;
@@ -216,6 +217,11 @@ impl FrozenHeapRef {
             .as_ref()
             .map_or_else(HeapSummary::default, |a| a.arena.allocated_summary())
     }
+
+    /// Heaps this heap keeps alive by reference.
+    pub fn refs(&self) -> impl ExactSizeIterator<Item = &FrozenHeapRef> {
+        self.0.as_ref().map_or(&[][..], |a| &*a.refs).iter()
+    }
 }
 
 impl FrozenHeap {
@@ -501,6 +507,8 @@ pub struct Freezer {
     pub(crate) heap: FrozenHeap,
     /// Defs frozen by this freezer.
     pub(crate) frozen_defs: RefCell<Vec<FrozenRef<'static, FrozenDef>>>,
+    /// Strings are frozen to these shared strings rather than to the heap, if set.
+    pub(crate) shared_strings: Option<&'static SharedFrozenStrings>,
 }
 
 impl Freezer {
@@ -508,6 +516,7 @@ impl Freezer {
         Freezer {
             heap,
             frozen_defs: RefCell::new(Vec::new()),
+            shared_strings: None,
         }
     }
 
@@ -541,6 +550,14 @@ impl Freezer {
         (fv, r, extra)
     }
 
+    /// Freeze a string, to a shared string if possible.
+    pub(crate) fn alloc_str(&self, s: &str) -> FrozenValue {
+        match self.shared_strings.and_then(|shared| shared.intern(s)) {
+            Some(shared) => shared.to_frozen_value(),
+            None => self.alloc(s),
+        }
+    }
+
     /// Freeze a nested value while freezing yourself.
     pub fn freeze(&self, value: Value) -> anyhow::Result<FrozenValue> {
         // Case 1: We have our value encoded in our pointer
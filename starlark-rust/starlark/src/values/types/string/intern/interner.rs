@@ -29,6 +29,12 @@ pub(crate) struct FrozenStringInterner {
 }
 
 impl FrozenStringInterner {
+    pub(crate) fn get(&self, s: Hashed<&str>) -> Option<FrozenStringValue> {
+        self.map
+            .get(s.hash().promote(), |x| s == x.get_hashed_str())
+            .copied()
+    }
+
     pub(crate) fn intern(
         &mut self,
         s: Hashed<&str>,
@@ -0,0 +1,187 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Strings shared by the frozen heaps of many modules.
+
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+
+use crate::collections::Hashed;
+use crate::values::layout::avalue::AValue;
+use crate::values::layout::avalue::StarlarkStrAValue;
+use crate::values::string::intern::interner::FrozenStringInterner;
+use crate::values::string::StarlarkStr;
+use crate::values::FrozenHeap;
+use crate::values::FrozenStringValue;
+
+const SHARDS: usize = 64;
+
+/// Strings shared by the modules frozen with
+/// [`Module::freeze_sharing_strings`](crate::environment::Module::freeze_sharing_strings):
+/// a string equal to one frozen by an earlier module is not copied to the frozen heap of the
+/// module, which points to the shared one instead.
+///
+/// Shared strings are never freed, which is why modules can only share strings of a `'static`
+/// table. To bound that memory, only strings up to `max_len` bytes are shared, and no strings are
+/// added once the table holds `max_bytes`: after that, only strings already in it are shared.
+pub struct SharedFrozenStrings {
+    shards: Box<[Mutex<Shard>]>,
+    max_len: usize,
+    max_bytes: usize,
+    allocated_bytes: AtomicUsize,
+    hits: AtomicU64,
+    saved_bytes: AtomicU64,
+}
+
+struct Shard {
+    heap: FrozenHeap,
+    interner: FrozenStringInterner,
+}
+
+// SAFETY: the heap and the interner are only used with the shard locked, and the strings they
+// hand out are immutable (their hash is atomic) and are never freed.
+unsafe impl Send for Shard {}
+
+/// Statistics of a [`SharedFrozenStrings`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SharedFrozenStringsStats {
+    /// Bytes allocated for the shared strings.
+    pub allocated_bytes: u64,
+    /// Number of strings which were frozen as a shared string already in the table.
+    pub hits: u64,
+    /// Bytes the frozen heaps would have allocated for those strings.
+    pub saved_bytes: u64,
+}
+
+impl SharedFrozenStrings {
+    /// A table sharing strings of up to `max_len` bytes, and holding up to `max_bytes`.
+    pub fn new(max_len: usize, max_bytes: usize) -> SharedFrozenStrings {
+        SharedFrozenStrings {
+            shards: (0..SHARDS)
+                .map(|_| {
+                    Mutex::new(Shard {
+                        heap: FrozenHeap::new(),
+                        interner: FrozenStringInterner::default(),
+                    })
+                })
+                .collect(),
+            max_len,
+            max_bytes,
+            allocated_bytes: AtomicUsize::new(0),
+            hits: AtomicU64::new(0),
+            saved_bytes: AtomicU64::new(0),
+        }
+    }
+
+    pub fn stats(&self) -> SharedFrozenStringsStats {
+        SharedFrozenStringsStats {
+            allocated_bytes: self.allocated_bytes.load(Ordering::Relaxed) as u64,
+            hits: self.hits.load(Ordering::Relaxed),
+            saved_bytes: self.saved_bytes.load(Ordering::Relaxed),
+        }
+    }
+
+    /// The shared string equal to `s`, if it is shared, adding it if it isn't in the table yet.
+    /// `s` must not be a constant string, i.e. must be at least two bytes long.
+    pub(crate) fn intern(&'static self, s: &str) -> Option<FrozenStringValue> {
+        if s.len() > self.max_len {
+            return None;
+        }
+        let size = <StarlarkStrAValue as AValue<'static>>::alloc_size_for_extra_len(
+            StarlarkStr::payload_len_for_len(s.len()),
+        )
+        .bytes() as usize;
+
+        let s = Hashed::new(s);
+        let mut shard = self.shards[s.hash().get() as usize % SHARDS]
+            .lock()
+            .unwrap();
+        if let Some(shared) = shard.interner.get(s) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            self.saved_bytes.fetch_add(size as u64, Ordering::Relaxed);
+            return Some(shared);
+        }
+        if self.allocated_bytes.load(Ordering::Relaxed) + size > self.max_bytes {
+            return None;
+        }
+        self.allocated_bytes.fetch_add(size, Ordering::Relaxed);
+        let Shard { heap, interner } = &mut *shard;
+        Some(interner.intern(s, || heap.alloc_str_hashed(s)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use once_cell::sync::Lazy;
+
+    use crate::environment::Module;
+    use crate::values::string::intern::shared::SharedFrozenStrings;
+    use crate::values::string::intern::shared::SharedFrozenStringsStats;
+
+    #[test]
+    fn test_shared_strings() {
+        static STRINGS: Lazy<SharedFrozenStrings> = Lazy::new(|| SharedFrozenStrings::new(8, 64));
+
+        let foo1 = STRINGS.intern("foo").unwrap();
+        let foo2 = STRINGS.intern("foo").unwrap();
+        assert!(foo1.to_value().ptr_eq(foo2.to_value()));
+        assert_eq!("foo", foo1.as_str());
+        // Too long.
+        assert!(STRINGS.intern("foobarbaz").is_none());
+
+        let stats = STRINGS.stats();
+        assert_eq!(1, stats.hits);
+        assert_eq!(stats.allocated_bytes, stats.saved_bytes);
+
+        // Only strings already shared are shared once the table is full.
+        let mut i = 0;
+        while STRINGS.intern(&format!("s{}", i)).is_some() {
+            i += 1;
+        }
+        assert!(i > 0);
+        assert!(STRINGS.stats().allocated_bytes <= 64);
+        assert!(STRINGS.intern("foo").is_some());
+        assert_ne!(SharedFrozenStringsStats::default(), STRINGS.stats());
+    }
+
+    #[test]
+    fn test_freeze_sharing_strings() {
+        static STRINGS: Lazy<SharedFrozenStrings> =
+            Lazy::new(|| SharedFrozenStrings::new(100, 1000));
+
+        let freeze = |s: &str| {
+            let module = Module::new();
+            module.set("x", module.heap().alloc_str(s).to_value());
+            module.freeze_sharing_strings(&STRINGS).unwrap()
+        };
+        let module1 = freeze("some string");
+        let module2 = freeze("some string");
+        let x1 = module1.get("x").unwrap();
+        let x2 = module2.get("x").unwrap();
+        assert_eq!(Some("some string"), x1.value().unpack_str());
+        assert!(x1.value().ptr_eq(x2.value()));
+        assert_eq!(1, STRINGS.stats().hits);
+
+        // Not shared when freezing without the table.
+        let module3 = Module::new();
+        module3.set("x", module3.heap().alloc_str("some string").to_value());
+        let module3 = module3.freeze().unwrap();
+        assert!(!x1.value().ptr_eq(module3.get("x").unwrap().value()));
+    }
+}
@@ -65,6 +65,9 @@ pub(crate) mod iter;
 pub(crate) mod repr;
 pub(crate) mod simd;
 
+pub use crate::values::types::string::intern::shared::SharedFrozenStrings;
+pub use crate::values::types::string::intern::shared::SharedFrozenStringsStats;
+
 /// The result of calling `type()` on strings.
 pub const STRING_TYPE: &str = "string";
 
@@ -0,0 +1,484 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Signature help, i.e. showing the parameters of the function being called at the cursor.
+
+use std::fmt::Write;
+use std::path::Path;
+
+use lsp_types::Documentation;
+use lsp_types::InitializeParams;
+use lsp_types::MarkupContent;
+use lsp_types::MarkupKind;
+use lsp_types::ParameterInformation;
+use lsp_types::ParameterLabel;
+use lsp_types::SignatureHelp;
+use lsp_types::SignatureHelpParams;
+use lsp_types::SignatureInformation;
+use starlark::codemap::CodeMap;
+use starlark::codemap::Pos;
+use starlark::codemap::ResolvedSpan;
+use starlark::docs::markdown::render_doc_param;
+use starlark::docs::DocFunction;
+use starlark::docs::DocItem;
+use starlark::docs::DocMember;
+use starlark::docs::DocParam;
+use starlark::syntax::AstModule;
+use starlark_syntax::codemap::ResolvedPos;
+use starlark_syntax::syntax::ast::ArgumentP;
+use starlark_syntax::syntax::ast::AstArgumentP;
+use starlark_syntax::syntax::ast::AstExprP;
+use starlark_syntax::syntax::ast::AstNoPayload;
+use starlark_syntax::syntax::ast::ExprP;
+use starlark_syntax::syntax::module::AstModuleFields;
+use starlark_syntax::syntax::uniplate::Visit;
+
+use crate::definition::Definition;
+use crate::definition::IdentifierDefinition;
+use crate::definition::LspModule;
+use crate::server::Backend;
+use crate::server::LspContext;
+use crate::server::LspUrl;
+use crate::symbols::find_symbols_at_location;
+
+/// The innermost function call around a position.
+#[derive(Debug, PartialEq)]
+pub(crate) struct EnclosingCall {
+    /// The span of the expression being called.
+    pub(crate) function_name_span: ResolvedSpan,
+    /// The argument that the position is in.
+    pub(crate) active_argument: ActiveArgument,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum ActiveArgument {
+    /// The n-th positional argument.
+    Positional(usize),
+    /// A named argument.
+    Named(String),
+    /// We can't tell which parameter the argument binds to, e.g. because it is after
+    /// `*args` or named arguments.
+    Unknown,
+}
+
+/// Find the innermost function call containing the given position, excluding the name of the
+/// function being called.
+pub(crate) fn find_enclosing_call(ast: &AstModule, line: u32, col: u32) -> Option<EnclosingCall> {
+    // The document got edited to add new lines, just bail out
+    let line_span = ast.codemap().line_span_opt(line as usize)?;
+    let position = std::cmp::min(line_span.begin() + col, line_span.end());
+
+    fn walk(
+        codemap: &CodeMap,
+        position: Pos,
+        node: Visit<AstNoPayload>,
+        found: &mut Option<EnclosingCall>,
+    ) {
+        let span = match &node {
+            Visit::Stmt(stmt) => stmt.span,
+            Visit::Expr(expr) => expr.span,
+        };
+        if !span.contains(position) {
+            return;
+        }
+        if let Visit::Expr(AstExprP {
+            node: ExprP::Call(name, args),
+            span,
+        }) = &node
+        {
+            // Right after the closing bracket, e.g. `foo()|`, is not in the call.
+            if !name.span.contains(position) && span.end() != position {
+                *found = Some(EnclosingCall {
+                    function_name_span: codemap.resolve_span(name.span),
+                    active_argument: active_argument(args, position),
+                });
+            }
+        }
+        // Keep going, so that the innermost call wins.
+        node.visit_children(|child| walk(codemap, position, child, found));
+    }
+
+    let mut found = None;
+    walk(
+        ast.codemap(),
+        position,
+        Visit::Stmt(ast.statement()),
+        &mut found,
+    );
+    found
+}
+
+fn active_argument(args: &[AstArgumentP<AstNoPayload>], position: Pos) -> ActiveArgument {
+    let mut positional = 0;
+    let mut only_positional = true;
+    for arg in args {
+        if arg.span.end() >= position {
+            return match &arg.node {
+                ArgumentP::Positional(_) if only_positional => {
+                    ActiveArgument::Positional(positional)
+                }
+                ArgumentP::Named(name, _) => ActiveArgument::Named(name.node.clone()),
+                _ => ActiveArgument::Unknown,
+            };
+        }
+        match &arg.node {
+            ArgumentP::Positional(_) => positional += 1,
+            _ => only_positional = false,
+        }
+    }
+    // After the last argument, e.g. `foo(x, |)`.
+    if only_positional {
+        ActiveArgument::Positional(positional)
+    } else {
+        ActiveArgument::Unknown
+    }
+}
+
+/// Length of a string in UTF-16 code units, which is what offsets in the LSP are measured in.
+fn utf16_len(s: &str) -> u32 {
+    s.encode_utf16().count() as u32
+}
+
+fn render_param(param: &DocParam) -> String {
+    match param {
+        DocParam::Arg {
+            name,
+            typ,
+            default_value,
+            ..
+        } => {
+            let mut res = name.clone();
+            if !typ.is_any() {
+                write!(res, ": {}", typ).unwrap();
+            }
+            if let Some(default_value) = default_value {
+                write!(res, " = {}", default_value).unwrap();
+            }
+            res
+        }
+        DocParam::NoArgs => "*".to_owned(),
+        DocParam::OnlyPosBefore => "/".to_owned(),
+        DocParam::Args { name, .. } => format!("*{}", name),
+        DocParam::Kwargs { name, .. } => format!("**{}", name),
+    }
+}
+
+/// Index of the parameter an argument binds to, among the parameters other than `*` and `/`.
+fn active_parameter(params: &[DocParam], active_argument: &ActiveArgument) -> Option<u32> {
+    let bound: Vec<_> = params
+        .iter()
+        .filter(|p| !matches!(p, DocParam::NoArgs | DocParam::OnlyPosBefore))
+        .collect();
+    let index = match active_argument {
+        ActiveArgument::Positional(n) => {
+            // The parameters that can be passed positionally come first.
+            let positional = params
+                .iter()
+                .take_while(|p| matches!(p, DocParam::Arg { .. } | DocParam::OnlyPosBefore))
+                .filter(|p| matches!(p, DocParam::Arg { .. }))
+                .count();
+            if *n < positional {
+                Some(*n)
+            } else {
+                bound
+                    .iter()
+                    .position(|p| matches!(p, DocParam::Args { .. }))
+            }
+        }
+        ActiveArgument::Named(arg) => bound
+            .iter()
+            .position(|p| matches!(p, DocParam::Arg { name, .. } if name == arg))
+            .or_else(|| {
+                bound
+                    .iter()
+                    .position(|p| matches!(p, DocParam::Kwargs { .. }))
+            }),
+        ActiveArgument::Unknown => None,
+    };
+    index.map(|i| i as u32)
+}
+
+pub(crate) fn signature_information(
+    name: &str,
+    function: &DocFunction,
+    active_argument: &ActiveArgument,
+) -> SignatureInformation {
+    let mut label = format!("{}(", name);
+    let mut parameters = Vec::new();
+    for (i, param) in function.params.iter().enumerate() {
+        if i != 0 {
+            label.push_str(", ");
+        }
+        let start = utf16_len(&label);
+        label.push_str(&render_param(param));
+        if matches!(param, DocParam::NoArgs | DocParam::OnlyPosBefore) {
+            continue;
+        }
+        let documentation = match param {
+            DocParam::Arg { docs: Some(_), .. }
+            | DocParam::Args { docs: Some(_), .. }
+            | DocParam::Kwargs { docs: Some(_), .. } => {
+                Some(Documentation::MarkupContent(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: render_doc_param(param),
+                }))
+            }
+            _ => None,
+        };
+        parameters.push(ParameterInformation {
+            label: ParameterLabel::LabelOffsets([start, utf16_len(&label)]),
+            documentation,
+        });
+    }
+    label.push(')');
+
+    let documentation = function.docs.as_ref().map(|docs| {
+        let mut value = docs.summary.clone();
+        if let Some(details) = &docs.details {
+            write!(value, "\n\n{}", details).unwrap();
+        }
+        Documentation::MarkupContent(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        })
+    });
+
+    SignatureInformation {
+        label,
+        documentation,
+        parameters: Some(parameters),
+        active_parameter: active_parameter(&function.params, active_argument),
+    }
+}
+
+impl<T: LspContext> Backend<T> {
+    /// Get the signature of the function being called at a given position in a document.
+    pub(crate) fn signature_help_info(
+        &self,
+        params: SignatureHelpParams,
+        initialize_params: &InitializeParams,
+    ) -> anyhow::Result<Option<SignatureHelp>> {
+        let uri = params
+            .text_document_position_params
+            .text_document
+            .uri
+            .try_into()?;
+        let line = params.text_document_position_params.position.line;
+        let character = params.text_document_position_params.position.character;
+        let workspace_root =
+            Self::get_workspace_root(initialize_params.workspace_folders.as_ref(), &uri);
+
+        let Some(document) = self.get_ast(&uri) else {
+            return Ok(None);
+        };
+        let Some(call) = find_enclosing_call(&document.ast, line, character) else {
+            return Ok(None);
+        };
+        let definition = match document.find_definition_at_location(
+            call.function_name_span.begin.line as u32,
+            call.function_name_span.begin.column as u32,
+        ) {
+            Definition::Identifier(definition) => definition,
+            // Methods of values don't come with docs we can find.
+            Definition::Dotted(_) => return Ok(None),
+        };
+
+        Ok(self
+            .function_docs(definition, &document, &uri, workspace_root.as_deref())?
+            .map(|(name, function)| SignatureHelp {
+                signatures: vec![signature_information(
+                    &name,
+                    &function,
+                    &call.active_argument,
+                )],
+                active_signature: Some(0),
+                active_parameter: None,
+            }))
+    }
+
+    /// Find the docs of a function, wherever it was defined.
+    fn function_docs(
+        &self,
+        definition: IdentifierDefinition,
+        document: &LspModule,
+        document_uri: &LspUrl,
+        workspace_root: Option<&Path>,
+    ) -> anyhow::Result<Option<(String, DocFunction)>> {
+        let function = |docs: Option<DocItem>| match docs {
+            Some(DocItem::Function(function)) => Some(function),
+            _ => None,
+        };
+        let exported_function = |uri: &LspUrl, name: &str| -> anyhow::Result<_> {
+            Ok(self
+                .get_ast_or_load_from_disk(uri)?
+                .and_then(|ast| ast.find_exported_symbol(name))
+                .and_then(|symbol| function(symbol.docs)))
+        };
+
+        Ok(match definition {
+            IdentifierDefinition::Location {
+                destination, name, ..
+            } => find_symbols_at_location(
+                document.ast.codemap(),
+                document.ast.statement(),
+                ResolvedPos {
+                    line: destination.begin.line,
+                    column: destination.begin.column,
+                },
+            )
+            .remove(&name)
+            .and_then(|symbol| function(symbol.doc))
+            .map(|f| (name, f)),
+            IdentifierDefinition::LoadedLocation { path, name, .. } => {
+                let load_uri = self.resolve_load_path(&path, document_uri, workspace_root)?;
+                exported_function(&load_uri, &name)?.map(|f| (name, f))
+            }
+            IdentifierDefinition::Unresolved { name, .. } => {
+                // Native globals (e.g. rules) are documented in the environment, globals
+                // implemented in Starlark can be found where they are defined.
+                let native = self
+                    .context
+                    .get_environment(document_uri)
+                    .members
+                    .into_iter()
+                    .find(|(symbol, _)| symbol == &name)
+                    .and_then(|(_, member)| match member {
+                        DocMember::Function(f) => Some(f),
+                        DocMember::Property(_) => None,
+                    });
+                let found = match native {
+                    Some(f) => Some(f),
+                    None => match self
+                        .context
+                        .get_url_for_global_symbol(document_uri, &name)?
+                    {
+                        Some(uri) => exported_function(&uri, &name)?,
+                        None => None,
+                    },
+                };
+                found.map(|f| (name, f))
+            }
+            IdentifierDefinition::LoadPath { .. }
+            | IdentifierDefinition::StringLiteral { .. }
+            | IdentifierDefinition::NotFound => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use starlark::docs::DocString;
+    use starlark::docs::DocStringKind;
+    use starlark::syntax::Dialect;
+    use starlark::typing::Ty;
+    use textwrap::dedent;
+
+    use super::*;
+
+    fn call_at(contents: &str, line: u32, col: u32) -> Option<EnclosingCall> {
+        let ast = AstModule::parse(
+            "foo.star",
+            dedent(contents).trim().to_owned(),
+            &Dialect::Extended,
+        )
+        .unwrap();
+        find_enclosing_call(&ast, line, col)
+    }
+
+    #[test]
+    fn finds_innermost_call() {
+        let contents = r#"
+            foo(1, bar(x = 2), baz = 3)
+        "#;
+        let outer = call_at(contents, 0, 5).unwrap();
+        assert_eq!(ActiveArgument::Positional(0), outer.active_argument);
+        assert_eq!(0, outer.function_name_span.begin.column);
+
+        let inner = call_at(contents, 0, 15).unwrap();
+        assert_eq!(ActiveArgument::Named("x".to_owned()), inner.active_argument);
+        assert_eq!(7, inner.function_name_span.begin.column);
+
+        let named = call_at(contents, 0, 24).unwrap();
+        assert_eq!(
+            ActiveArgument::Named("baz".to_owned()),
+            named.active_argument
+        );
+
+        // On the function name, and right after the call.
+        assert_eq!(None, call_at(contents, 0, 1));
+        assert_eq!(None, call_at(contents, 0, 27));
+    }
+
+    #[test]
+    fn active_argument_after_last_argument() {
+        assert_eq!(
+            ActiveArgument::Positional(2),
+            call_at("foo(1, 2, )", 0, 10).unwrap().active_argument
+        );
+        assert_eq!(
+            ActiveArgument::Unknown,
+            call_at("foo(x = 1, )", 0, 11).unwrap().active_argument
+        );
+    }
+
+    #[test]
+    fn renders_signature() {
+        let arg = |name: &str, default_value: Option<&str>| DocParam::Arg {
+            name: name.to_owned(),
+            docs: None,
+            typ: Ty::any(),
+            default_value: default_value.map(|s| s.to_owned()),
+        };
+        let function = DocFunction {
+            docs: DocString::from_docstring(DocStringKind::Starlark, "Does things."),
+            params: vec![
+                arg("name", None),
+                DocParam::NoArgs,
+                arg("srcs", Some("[]")),
+                DocParam::Kwargs {
+                    name: "kwargs".to_owned(),
+                    docs: None,
+                    typ: Ty::any(),
+                },
+            ],
+            ..DocFunction::default()
+        };
+
+        let info = signature_information(
+            "my_rule",
+            &function,
+            &ActiveArgument::Named("srcs".to_owned()),
+        );
+        assert_eq!("my_rule(name, *, srcs = [], **kwargs)", info.label);
+        assert_eq!(Some(1), info.active_parameter);
+        let parameters = info.parameters.unwrap();
+        assert_eq!(3, parameters.len());
+        assert_eq!(ParameterLabel::LabelOffsets([8, 12]), parameters[0].label);
+        assert_eq!(ParameterLabel::LabelOffsets([17, 26]), parameters[1].label);
+
+        let info = signature_information(
+            "my_rule",
+            &function,
+            &ActiveArgument::Named("deps".to_owned()),
+        );
+        assert_eq!(Some(2), info.active_parameter);
+
+        // `srcs` is keyword-only.
+        let info = signature_information("my_rule", &function, &ActiveArgument::Positional(1));
+        assert_eq!(None, info.active_parameter);
+    }
+}
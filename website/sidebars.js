@@ -91,6 +91,7 @@ const manualSidebar = [
         items: [
           isInternal() ? 'users/faq/getting_help' : [],
           'users/faq/common_issues',
+          'users/error_codes',
           isInternal() ? 'users/faq/meta_issues' : [],
           isInternal() ? 'users/faq/meta_installation' : [],
           isInternal() ? 'users/faq/remote_execution' : [],
@@ -126,7 +127,12 @@ const manualSidebar = [
         items: [
           'users/advanced/deferred_materialization',
           'users/advanced/restarter',
+          'users/advanced/source_digest_cache',
+          'users/advanced/bzl_cache',
           'users/advanced/in_memory_cache',
+          'users/advanced/graph_limits',
+          'users/advanced/speculative_builds',
+          'users/advanced/external_cells',
           isInternal() ? 'users/advanced/offline_build_archives' : [],
           isInternal() ? 'users/advanced/vpnless' : [],
         ],
@@ -144,6 +150,7 @@ const manualSidebar = [
       'rule_authors/dynamic_dependencies',
       'rule_authors/anon_targets',
       'rule_authors/test_execution',
+      'rule_authors/analysis_tests',
       'rule_authors/optimization',
       isInternal() ? 'rule_authors/rule_writing_tips' : [],
       'rule_authors/incremental_actions',